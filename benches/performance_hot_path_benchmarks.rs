@@ -0,0 +1,253 @@
+//! Hot-Path Performance Benchmarks
+//!
+//! Criterion benchmarks for the four paths every message touches:
+//!
+//! * **Event persist** — content hashing and signing of a PDU, plus a full
+//!   `PUT /send` through the router.
+//! * **Sync computation** — sync token round-trips, plus initial and
+//!   incremental `/sync` for a member of a room with recent history.
+//! * **Token validation** — access-token hashing/verification, plus an
+//!   authenticated `GET /account/whoami`.
+//! * **Push evaluation** — evaluating a user's push rules against an event.
+//!
+//! The pure benchmarks always run. The router-backed ones use
+//! `synapse_rust::test_server::TestServer`, so they need the same
+//! PostgreSQL as the integration tests (`TEST_DATABASE_URL`); when it is
+//! unreachable they are **skipped** with a log line.
+//!
+//! Record a baseline and compare later runs against it with criterion's
+//! own baseline support:
+//!
+//! ```bash
+//! cargo bench --features test-utils --bench performance_hot_path_benchmarks -- --save-baseline main
+//! cargo bench --features test-utils --bench performance_hot_path_benchmarks -- --baseline main
+//! ```
+
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
+use serde_json::{json, Value};
+use std::time::Duration;
+use tokio::runtime::Runtime;
+
+use synapse_common::crypto::{hash_token, verify_token_hash};
+use synapse_federation::signing::{compute_event_content_hash, sign_json};
+use synapse_rust::test_server::{TestServer, TestUser};
+use synapse_services::push::PushNotificationService;
+use synapse_services::sync_service::SyncToken;
+use synapse_storage::push_notification::PushRule;
+
+/// Fixed Ed25519 seed (32 bytes of 0x07, unpadded base64) so signing cost is
+/// independent of key generation.
+const BENCH_SIGNING_SEED: &str = "BwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwcHBwc";
+
+/// Messages seeded into the sync benchmark room.
+const SYNC_HISTORY_MESSAGES: usize = 50;
+
+fn sample_pdu(body_len: usize) -> Value {
+    json!({
+        "room_id": "!bench:localhost",
+        "sender": "@alice:localhost",
+        "origin": "localhost",
+        "origin_server_ts": 1_700_000_000_000_i64,
+        "type": "m.room.message",
+        "depth": 42,
+        "prev_events": ["$prev1:localhost", "$prev2:localhost"],
+        "auth_events": ["$create:localhost", "$power:localhost", "$member:localhost"],
+        "content": { "msgtype": "m.text", "body": "x".repeat(body_len) },
+    })
+}
+
+/// Starts a router-backed server with one room holding some history, or
+/// `None` when the test database is unavailable.
+fn start_server(rt: &Runtime) -> Option<(TestServer, TestUser, String)> {
+    rt.block_on(async {
+        let server = match TestServer::start().await {
+            Ok(server) => server,
+            Err(e) => {
+                eprintln!("[perf] test database unavailable ({e}); skipping router-backed benches");
+                return None;
+            }
+        };
+        let suffix = rand::random::<u32>();
+        let alice = server.register_user(&format!("bench_alice_{suffix}")).await.ok()?;
+        let bob = server.register_user(&format!("bench_bob_{suffix}")).await.ok()?;
+        let room_id = server.seed_room(&alice, &[&bob]).await.ok()?;
+        for i in 0..SYNC_HISTORY_MESSAGES {
+            server.client(&bob).send_text(&room_id, &format!("history {i}")).await.ok()?;
+        }
+        Some((server, alice, room_id))
+    })
+}
+
+// ---------------------------------------------------------------------------
+//  Event persist
+// ---------------------------------------------------------------------------
+
+fn benchmark_event_hash_and_sign(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_event_persist");
+    for body_len in [64, 4096] {
+        let pdu = sample_pdu(body_len);
+        group.bench_with_input(BenchmarkId::new("content_hash", body_len), &pdu, |b, pdu| {
+            b.iter(|| compute_event_content_hash(black_box(pdu)));
+        });
+        group.bench_with_input(BenchmarkId::new("hash_and_sign", body_len), &pdu, |b, pdu| {
+            b.iter(|| {
+                let mut event = pdu.clone();
+                let hash = compute_event_content_hash(&event).expect("sample PDU hashes");
+                event["hashes"] = json!({ "sha256": hash });
+                sign_json("localhost", "ed25519:bench", BENCH_SIGNING_SEED, &mut event).expect("sample PDU signs");
+                black_box(event)
+            });
+        });
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+//  Sync computation
+// ---------------------------------------------------------------------------
+
+fn benchmark_sync_token(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_sync");
+    group.bench_function("token_parse_encode", |b| {
+        b.iter(|| SyncToken::parse(black_box("s1777000000000_4321_9876")).map(|token| token.encode()));
+    });
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+//  Token validation
+// ---------------------------------------------------------------------------
+
+fn benchmark_token_hashing(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_token_validation");
+    let token = "syt_YmVuY2g_abcdefghijklmnopqrst_0a1b2c";
+    let stored = hash_token(token);
+    group.bench_function("hash_token", |b| b.iter(|| hash_token(black_box(token))));
+    group.bench_function("verify_token_hash", |b| b.iter(|| verify_token_hash(black_box(token), black_box(&stored))));
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+//  Push evaluation
+// ---------------------------------------------------------------------------
+
+fn push_rule(rule_id: &str, priority: i32, conditions: Value, actions: Value) -> PushRule {
+    PushRule {
+        id: i64::from(priority),
+        user_id: "@alice:localhost".to_string(),
+        rule_id: rule_id.to_string(),
+        scope: "global".to_string(),
+        kind: "override".to_string(),
+        priority,
+        priority_class: 5,
+        conditions,
+        actions,
+        is_enabled: true,
+        is_default: true,
+        created_ts: 0,
+        updated_ts: None,
+        pattern: None,
+    }
+}
+
+/// A rule set shaped like the spec defaults: suppressing overrides first,
+/// keyword rules, then the catch-all message rule.
+fn push_rules(keyword_rules: usize) -> Vec<PushRule> {
+    let mut rules = vec![
+        push_rule(
+            ".m.rule.suppress_notices",
+            0,
+            json!([{"kind": "event_match", "key": "content.msgtype", "pattern": "m.notice"}]),
+            json!(["dont_notify"]),
+        ),
+        push_rule(
+            ".m.rule.tombstone",
+            1,
+            json!([{"kind": "event_match", "key": "type", "pattern": "m.room.tombstone"}]),
+            json!(["notify", {"set_tweak": "highlight", "value": true}]),
+        ),
+    ];
+    for i in 0..keyword_rules {
+        rules.push(push_rule(
+            &format!("keyword_{i}"),
+            10 + i as i32,
+            json!([{"kind": "event_match", "key": "content.body", "pattern": format!("keyword{i}")}]),
+            json!(["notify", {"set_tweak": "sound", "value": "default"}]),
+        ));
+    }
+    rules.push(push_rule(
+        ".m.rule.message",
+        1_000,
+        json!([{"kind": "event_match", "key": "type", "pattern": "m.room.message"}]),
+        json!(["notify"]),
+    ));
+    rules
+}
+
+fn benchmark_push_evaluation(c: &mut Criterion) {
+    let mut group = c.benchmark_group("hot_path_push_evaluation");
+    let event = json!({
+        "type": "m.room.message",
+        "sender": "@bob:localhost",
+        "room_id": "!bench:localhost",
+        "content": { "msgtype": "m.text", "body": "an ordinary message that matches no keyword" },
+    });
+    for keyword_rules in [0, 20, 100] {
+        let rules = push_rules(keyword_rules);
+        group.bench_with_input(BenchmarkId::new("evaluate_rules", keyword_rules), &rules, |b, rules| {
+            b.iter(|| {
+                PushNotificationService::evaluate_rules(rules.clone(), black_box(&event)).expect("rules evaluate")
+            });
+        });
+    }
+    group.finish();
+}
+
+// ---------------------------------------------------------------------------
+//  Router-backed paths (test database required)
+// ---------------------------------------------------------------------------
+
+fn benchmark_router_hot_paths(c: &mut Criterion) {
+    let rt = Runtime::new().expect("bench runtime must be constructible");
+    let Some((server, alice, room_id)) = start_server(&rt) else {
+        return;
+    };
+    let client = server.client(&alice);
+
+    let mut group = c.benchmark_group("hot_path_router");
+    group.sample_size(30);
+    group.measurement_time(Duration::from_secs(10));
+
+    group.bench_function("send_message", |b| {
+        b.iter(|| rt.block_on(client.send_text(&room_id, "benchmark message")).expect("send succeeds"));
+    });
+
+    group.bench_function("whoami", |b| {
+        b.iter(|| rt.block_on(client.get("/_matrix/client/v3/account/whoami")).expect("whoami succeeds"));
+    });
+
+    group.bench_function("initial_sync", |b| {
+        b.iter(|| rt.block_on(client.sync(None)).expect("initial sync succeeds"));
+    });
+
+    let since = rt
+        .block_on(client.sync(None))
+        .ok()
+        .and_then(|body| body["next_batch"].as_str().map(str::to_string))
+        .unwrap_or_default();
+    group.bench_function("incremental_sync", |b| {
+        b.iter(|| rt.block_on(client.sync(Some(&since))).expect("incremental sync succeeds"));
+    });
+
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    benchmark_event_hash_and_sign,
+    benchmark_sync_token,
+    benchmark_token_hashing,
+    benchmark_push_evaluation,
+    benchmark_router_hot_paths
+);
+criterion_main!(benches);
@@ -61,7 +61,7 @@ def calculate_mac(
     message.extend(b"\x00")
 
     # admin or notadmin
-    message.extend(b"admin\x00\x00\x00" if admin else b"notadmin")
+    message.extend(b"admin" if admin else b"notadmin")
 
     # 计算 HMAC
     key = secret.encode("utf-8")
@@ -0,0 +1,15 @@
+-- Add the `order` column to space_children so `m.space.child` ordering keys
+-- survive persistence and hierarchy output can sort children per spec.
+-- The runtime DDL path already creates this column (defaulting to ''), so the
+-- guard keeps both paths converging on the same shape. Empty strings are
+-- treated as "no order" by the storage layer.
+
+DO $$
+BEGIN
+    IF NOT EXISTS (
+        SELECT 1 FROM information_schema.columns
+        WHERE table_name = 'space_children' AND column_name = 'order'
+    ) THEN
+        ALTER TABLE space_children ADD COLUMN "order" TEXT;
+    END IF;
+END $$;
@@ -0,0 +1,4 @@
+-- Rollback for 20261016120000_space_children_order.sql
+-- Drops the `order` column from space_children.
+
+ALTER TABLE space_children DROP COLUMN IF EXISTS "order";
@@ -0,0 +1,17 @@
+-- Track the newest event each federation destination is owed per room.
+-- Together with destination_retry_timings.last_successful_stream_ordering this
+-- lets the sender catch a destination up after downtime by replaying only the
+-- latest event per room instead of every queued transaction.
+
+CREATE TABLE IF NOT EXISTS destination_rooms (
+    destination TEXT NOT NULL,
+    room_id TEXT NOT NULL,
+    event_id TEXT NOT NULL,
+    stream_ordering BIGINT NOT NULL,
+    pdu JSONB NOT NULL,
+    updated_ts BIGINT NOT NULL,
+    CONSTRAINT pk_destination_rooms PRIMARY KEY (destination, room_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_destination_rooms_stream
+    ON destination_rooms(destination, stream_ordering);
@@ -0,0 +1,5 @@
+-- Rollback for 20261016130000_federation_destination_rooms.sql
+-- Drops the per-destination catch-up table.
+
+DROP INDEX IF EXISTS idx_destination_rooms_stream;
+DROP TABLE IF EXISTS destination_rooms;
@@ -0,0 +1,15 @@
+-- Queue the batched backfill of events.redacts for redactions persisted before
+-- 20260619120000_add_redacts_column. The rows are updated in small batches by
+-- the online migration runner (synapse-storage online_migration.rs) from the
+-- maintenance loop, so large events tables are never locked by this migration.
+
+INSERT INTO background_updates (
+    update_name, job_name, job_type, description, table_name, column_name,
+    status, progress, batch_size, sleep_ms, created_ts
+)
+VALUES (
+    'events_populate_redacts', 'events_populate_redacts', 'online_migration',
+    'Backfill events.redacts from content.redacts', 'events', 'redacts',
+    'pending', '{}', 1000, 100, (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT
+)
+ON CONFLICT (update_name) DO NOTHING;
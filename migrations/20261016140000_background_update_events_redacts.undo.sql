@@ -0,0 +1,4 @@
+-- Rollback for 20261016140000_background_update_events_redacts.sql
+-- Removes the queued backfill job; already backfilled values are kept.
+
+DELETE FROM background_updates WHERE update_name = 'events_populate_redacts';
@@ -0,0 +1,27 @@
+-- Soft deletes for purged events and deleted media.
+-- Purges and media deletes now only stamp `events.purged_at` /
+-- `media_metadata.deleted_at`; the maintenance loop deletes tombstoned rows in
+-- small batches, so the ON DELETE CASCADE fan-out (event_edges,
+-- state_groups, thumbnails, ...) is spread over many short transactions
+-- instead of one long DELETE on a large room.
+-- Adding a nullable column without a default does not rewrite the table.
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS purged_at BIGINT;
+ALTER TABLE media_metadata ADD COLUMN IF NOT EXISTS deleted_at BIGINT;
+
+-- media_metadata is small enough to index inline; the events index is built
+-- CONCURRENTLY by the online migration runner (synapse-storage
+-- online_migration.rs) so writes to events are never blocked.
+CREATE INDEX IF NOT EXISTS idx_media_metadata_deleted_at
+    ON media_metadata(deleted_at) WHERE deleted_at IS NOT NULL;
+
+INSERT INTO background_updates (
+    update_name, job_name, job_type, description, table_name, column_name,
+    status, progress, batch_size, sleep_ms, created_ts
+)
+VALUES (
+    'events_purged_at_index', 'events_purged_at_index', 'online_migration',
+    'Index tombstoned events for the purge vacuum', 'events', 'purged_at',
+    'pending', '{}', 1, 0, (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT
+)
+ON CONFLICT (update_name) DO NOTHING;
@@ -0,0 +1,13 @@
+-- Rollback for 20261016150000_event_media_tombstones.sql
+-- Tombstoned rows are deleted first so they do not reappear once the columns
+-- that hide them are gone.
+
+DELETE FROM background_updates WHERE update_name = 'events_purged_at_index';
+DROP INDEX IF EXISTS idx_events_purged_at;
+DROP INDEX IF EXISTS idx_media_metadata_deleted_at;
+
+DELETE FROM events WHERE purged_at IS NOT NULL;
+DELETE FROM media_metadata WHERE deleted_at IS NOT NULL;
+
+ALTER TABLE events DROP COLUMN IF EXISTS purged_at;
+ALTER TABLE media_metadata DROP COLUMN IF EXISTS deleted_at;
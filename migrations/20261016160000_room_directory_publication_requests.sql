@@ -0,0 +1,23 @@
+-- Moderation queue for public room directory publications.
+-- With room_directory.require_publication_approval set, a request to publish a
+-- room lands here as 'pending' instead of in room_directory; an admin approval
+-- moves the room into room_directory and a rejection keeps it out. One row per
+-- room: a new request after a review resets the row to 'pending'.
+
+CREATE TABLE IF NOT EXISTS room_directory_publication_requests (
+    room_id TEXT NOT NULL,
+    requested_by TEXT NOT NULL,
+    requested_ts BIGINT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    reviewed_by TEXT,
+    reviewed_ts BIGINT,
+    reason TEXT,
+    CONSTRAINT pk_room_directory_publication_requests PRIMARY KEY (room_id),
+    CONSTRAINT fk_room_directory_publication_requests_room
+        FOREIGN KEY (room_id) REFERENCES rooms(room_id) ON DELETE CASCADE,
+    CONSTRAINT chk_room_directory_publication_requests_status
+        CHECK (status IN ('pending', 'approved', 'rejected'))
+);
+
+CREATE INDEX IF NOT EXISTS idx_room_directory_publication_requests_status
+    ON room_directory_publication_requests(status, requested_ts);
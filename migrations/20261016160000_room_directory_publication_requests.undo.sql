@@ -0,0 +1,5 @@
+-- Rollback for 20261016160000_room_directory_publication_requests.sql
+-- Pending requests are dropped; rooms already approved stay in room_directory.
+
+DROP INDEX IF EXISTS idx_room_directory_publication_requests_status;
+DROP TABLE IF EXISTS room_directory_publication_requests;
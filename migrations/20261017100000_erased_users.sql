@@ -0,0 +1,11 @@
+-- Users deactivated with `erase: true`. Clients other than the sender see
+-- the content of their events pruned as if redacted. The row outlives
+-- deactivation; it is dropped only with the user.
+
+CREATE TABLE IF NOT EXISTS erased_users (
+    user_id TEXT NOT NULL,
+    erased_ts BIGINT NOT NULL,
+    CONSTRAINT pk_erased_users PRIMARY KEY (user_id),
+    CONSTRAINT fk_erased_users_user
+        FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Rollback for 20261017100000_erased_users.sql
+-- Erased users' events become visible unpruned again.
+
+DROP TABLE IF EXISTS erased_users;
@@ -0,0 +1,13 @@
+-- Per-user daily send quota counters. Redis holds the live counters; rows
+-- here are written only while Redis is unavailable and are read back when
+-- an admin inspects a user's usage.
+
+CREATE TABLE IF NOT EXISTS user_daily_quotas (
+    user_id TEXT NOT NULL,
+    day DATE NOT NULL,
+    kind TEXT NOT NULL,
+    count BIGINT NOT NULL DEFAULT 0,
+    CONSTRAINT pk_user_daily_quotas PRIMARY KEY (user_id, day, kind),
+    CONSTRAINT fk_user_daily_quotas_user
+        FOREIGN KEY (user_id) REFERENCES users(user_id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Rollback for 20261017110000_user_daily_quotas.sql
+-- Counters recorded while Redis was unavailable are lost.
+
+DROP TABLE IF EXISTS user_daily_quotas;
@@ -0,0 +1,34 @@
+-- Access tokens are looked up only by hash. Rows written before tokens were
+-- hashed may still carry the raw token; replace a raw value left in
+-- token_hash with its unkeyed SHA-256 (the legacy hash lookups still accept),
+-- clear the plaintext column, and forbid writing it again.
+
+UPDATE access_tokens
+SET token_hash = translate(rtrim(encode(sha256(convert_to(token, 'UTF8')), 'base64'), '='), '+/', '-_')
+WHERE token IS NOT NULL
+  AND (token_hash = token OR token_hash = '')
+  AND NOT EXISTS (
+      SELECT 1 FROM access_tokens existing
+      WHERE existing.token_hash =
+          translate(rtrim(encode(sha256(convert_to(access_tokens.token, 'UTF8')), 'base64'), '='), '+/', '-_')
+  );
+
+UPDATE access_tokens SET token = NULL WHERE token IS NOT NULL;
+
+UPDATE token_blacklist
+SET token_hash = translate(rtrim(encode(sha256(convert_to(token, 'UTF8')), 'base64'), '='), '+/', '-_')
+WHERE token IS NOT NULL
+  AND (token_hash = token OR token_hash = '')
+  AND NOT EXISTS (
+      SELECT 1 FROM token_blacklist existing
+      WHERE existing.token_hash =
+          translate(rtrim(encode(sha256(convert_to(token_blacklist.token, 'UTF8')), 'base64'), '='), '+/', '-_')
+  );
+
+UPDATE token_blacklist SET token = NULL WHERE token IS NOT NULL;
+
+ALTER TABLE access_tokens DROP CONSTRAINT IF EXISTS chk_access_tokens_no_plaintext;
+ALTER TABLE access_tokens ADD CONSTRAINT chk_access_tokens_no_plaintext CHECK (token IS NULL);
+
+ALTER TABLE token_blacklist DROP CONSTRAINT IF EXISTS chk_token_blacklist_no_plaintext;
+ALTER TABLE token_blacklist ADD CONSTRAINT chk_token_blacklist_no_plaintext CHECK (token IS NULL);
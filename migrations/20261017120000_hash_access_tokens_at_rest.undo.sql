@@ -0,0 +1,5 @@
+-- Rollback for 20261017120000_hash_access_tokens_at_rest.sql
+-- Hashed tokens cannot be restored to plaintext; only the constraints are removed.
+
+ALTER TABLE token_blacklist DROP CONSTRAINT IF EXISTS chk_token_blacklist_no_plaintext;
+ALTER TABLE access_tokens DROP CONSTRAINT IF EXISTS chk_access_tokens_no_plaintext;
@@ -0,0 +1,14 @@
+-- State groups stored as deltas. Each state event gets a group holding only
+-- the entry it changed plus an edge to the room's previous group; every
+-- STATE_GROUP_SNAPSHOT_INTERVAL groups (and for a room's first group) the
+-- full state is copied instead, so resolving a group never walks further
+-- back than the last snapshot. Groups are ordered by the stream position of
+-- the event that created them, which is how state at an arbitrary event is
+-- found.
+
+ALTER TABLE state_groups ADD COLUMN IF NOT EXISTS is_snapshot BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE state_groups ADD COLUMN IF NOT EXISTS delta_depth INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE state_groups ADD COLUMN IF NOT EXISTS stream_ordering BIGINT;
+
+CREATE INDEX IF NOT EXISTS idx_state_groups_room_stream_ordering
+    ON state_groups(room_id, stream_ordering DESC) WHERE stream_ordering IS NOT NULL;
@@ -0,0 +1,6 @@
+-- Rollback for 20261017130000_state_group_snapshots.sql
+
+DROP INDEX IF EXISTS idx_state_groups_room_stream_ordering;
+ALTER TABLE state_groups DROP COLUMN IF EXISTS stream_ordering;
+ALTER TABLE state_groups DROP COLUMN IF EXISTS delta_depth;
+ALTER TABLE state_groups DROP COLUMN IF EXISTS is_snapshot;
@@ -0,0 +1,13 @@
+-- Per-room progress of the archived state group compaction job: groups up to
+-- last_state_group_id have been compacted, and rows_saved counts the
+-- state_group_state rows removed so far.
+
+CREATE TABLE IF NOT EXISTS state_compaction_progress (
+    room_id TEXT NOT NULL,
+    last_state_group_id BIGINT NOT NULL,
+    rows_saved BIGINT NOT NULL DEFAULT 0,
+    compacted_ts BIGINT NOT NULL,
+    CONSTRAINT pk_state_compaction_progress PRIMARY KEY (room_id),
+    CONSTRAINT fk_state_compaction_progress_room
+        FOREIGN KEY (room_id) REFERENCES rooms(room_id) ON DELETE CASCADE
+);
@@ -0,0 +1,4 @@
+-- Rollback for 20261017140000_state_compaction_progress.sql
+-- The next compaction pass starts over from each room's first state group.
+
+DROP TABLE IF EXISTS state_compaction_progress;
@@ -0,0 +1,12 @@
+-- The server_name this database was created for. Written on first startup;
+-- every later startup refuses to run if the configured name differs, since
+-- every user, room alias and event ID in the database embeds it.
+
+CREATE TABLE IF NOT EXISTS server_identity (
+    id BOOLEAN NOT NULL DEFAULT TRUE,
+    server_name TEXT NOT NULL,
+    created_ts BIGINT NOT NULL,
+    updated_ts BIGINT,
+    CONSTRAINT pk_server_identity PRIMARY KEY (id),
+    CONSTRAINT chk_server_identity_single_row CHECK (id)
+);
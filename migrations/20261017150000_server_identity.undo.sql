@@ -0,0 +1,4 @@
+-- Rollback for 20261017150000_server_identity.sql
+-- The next startup records the configured server_name again.
+
+DROP TABLE IF EXISTS server_identity;
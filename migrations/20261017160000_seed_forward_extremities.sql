@@ -0,0 +1,11 @@
+-- Seed event_forward_extremities from the stored DAG. Events with a depth
+-- that no stored event lists as a prev event are the current extremities;
+-- from here on the table is maintained as events are persisted.
+
+INSERT INTO event_forward_extremities (room_id, event_id)
+SELECT e.room_id, e.event_id
+FROM events e
+WHERE e.depth > 0
+  AND e.purged_at IS NULL
+  AND NOT EXISTS (SELECT 1 FROM event_edges ee WHERE ee.prev_event_id = e.event_id)
+ON CONFLICT DO NOTHING;
@@ -0,0 +1,3 @@
+-- Rollback for 20261017160000_seed_forward_extremities.sql
+
+DELETE FROM event_forward_extremities;
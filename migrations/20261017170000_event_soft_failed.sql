@@ -0,0 +1,21 @@
+-- Soft-failed events: inbound events that are valid in the DAG but fail auth
+-- against the room's current state, or that a spam checker quarantined. They
+-- stay in `events` and `event_edges` for state resolution and are hidden
+-- from clients. `soft_fail_kind` is 'auth' or 'spam'.
+-- A constant default does not rewrite the table.
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS soft_failed BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS soft_fail_kind TEXT;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS soft_fail_reason TEXT;
+
+-- The per-room index is built CONCURRENTLY by the online migration runner.
+INSERT INTO background_updates (
+    update_name, job_name, job_type, description, table_name, column_name,
+    status, progress, batch_size, sleep_ms, created_ts
+)
+VALUES (
+    'events_soft_failed_index', 'events_soft_failed_index', 'online_migration',
+    'Index soft-failed events per room', 'events', 'soft_failed',
+    'pending', '{}', 1, 0, (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT
+)
+ON CONFLICT (update_name) DO NOTHING;
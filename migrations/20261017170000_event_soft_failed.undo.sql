@@ -0,0 +1,12 @@
+-- Rollback for 20261017170000_event_soft_failed.sql
+-- Soft-failed events are deleted first so they do not become visible to
+-- clients once the column that hides them is gone.
+
+DELETE FROM background_updates WHERE update_name = 'events_soft_failed_index';
+DROP INDEX IF EXISTS idx_events_room_soft_failed;
+
+DELETE FROM events WHERE soft_failed;
+
+ALTER TABLE events DROP COLUMN IF EXISTS soft_fail_reason;
+ALTER TABLE events DROP COLUMN IF EXISTS soft_fail_kind;
+ALTER TABLE events DROP COLUMN IF EXISTS soft_failed;
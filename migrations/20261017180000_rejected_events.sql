@@ -0,0 +1,19 @@
+-- Inbound PDUs rejected before persistence, with the reason, for the admin
+-- API. Capped by federation.rejected_events_max: every insert deletes the
+-- rows older than the most recent N.
+
+CREATE TABLE IF NOT EXISTS rejected_events (
+    id BIGSERIAL,
+    event_id TEXT NOT NULL,
+    origin TEXT NOT NULL,
+    room_id TEXT,
+    sender TEXT,
+    event_type TEXT,
+    reason TEXT NOT NULL,
+    detail TEXT NOT NULL,
+    rejected_ts BIGINT NOT NULL,
+    CONSTRAINT pk_rejected_events PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_rejected_events_room ON rejected_events (room_id, id);
+CREATE INDEX IF NOT EXISTS idx_rejected_events_origin ON rejected_events (origin, id);
@@ -0,0 +1,3 @@
+-- Rollback for 20261017180000_rejected_events.sql
+
+DROP TABLE IF EXISTS rejected_events;
@@ -0,0 +1,19 @@
+-- Outgoing email that failed with a transient SMTP error, waiting for the
+-- worker to retry it with backoff (smtp.retry). Rows are deleted once sent
+-- and kept with status 'failed' after the last attempt.
+
+CREATE TABLE IF NOT EXISTS email_retry_queue (
+    id BIGSERIAL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    attempts INTEGER NOT NULL DEFAULT 1,
+    next_attempt_ts BIGINT NOT NULL,
+    last_error TEXT,
+    status TEXT NOT NULL DEFAULT 'pending',
+    created_ts BIGINT NOT NULL,
+    CONSTRAINT pk_email_retry_queue PRIMARY KEY (id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_email_retry_queue_due ON email_retry_queue (next_attempt_ts)
+    WHERE status = 'pending';
@@ -0,0 +1,3 @@
+-- Rollback for 20261017190000_email_retry_queue.sql
+
+DROP TABLE IF EXISTS email_retry_queue;
@@ -0,0 +1,14 @@
+-- Relations (`m.relates_to`) are read from `events` by their parent event ID
+-- for `/relations` and the bundled aggregations of client events.
+
+-- The index is built CONCURRENTLY by the online migration runner.
+INSERT INTO background_updates (
+    update_name, job_name, job_type, description, table_name, column_name,
+    status, progress, batch_size, sleep_ms, created_ts
+)
+VALUES (
+    'events_relations_index', 'events_relations_index', 'online_migration',
+    'Index events by the event they relate to', 'events', 'content',
+    'pending', '{}', 1, 0, (EXTRACT(EPOCH FROM clock_timestamp()) * 1000)::BIGINT
+)
+ON CONFLICT (update_name) DO NOTHING;
@@ -0,0 +1,4 @@
+-- Rollback for 20261017200000_event_relations_index.sql
+
+DELETE FROM background_updates WHERE update_name = 'events_relations_index';
+DROP INDEX IF EXISTS idx_events_relations;
@@ -0,0 +1,9 @@
+-- MSC4140 delayed events. The client addresses a scheduled event by its
+-- delay ID; the event ID is only known once the dispatcher has sent it.
+
+ALTER TABLE delayed_events ADD COLUMN IF NOT EXISTS delay_id TEXT;
+ALTER TABLE delayed_events ALTER COLUMN event_id DROP NOT NULL;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_delayed_events_delay_id ON delayed_events (delay_id);
+CREATE INDEX IF NOT EXISTS idx_delayed_events_due ON delayed_events (scheduled_ts)
+    WHERE status IN ('pending', 'sending');
@@ -0,0 +1,7 @@
+-- Rollback for 20261018090000_delayed_events_msc4140.sql
+
+DROP INDEX IF EXISTS idx_delayed_events_due;
+DROP INDEX IF EXISTS idx_delayed_events_delay_id;
+DELETE FROM delayed_events WHERE event_id IS NULL;
+ALTER TABLE delayed_events ALTER COLUMN event_id SET NOT NULL;
+ALTER TABLE delayed_events DROP COLUMN IF EXISTS delay_id;
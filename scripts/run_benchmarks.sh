@@ -40,6 +40,11 @@ run_benchmarks() {
     echo ""
     echo "--- Federation Criterion 基准 ---"
     cargo bench --bench performance_federation_benchmarks || echo "联邦基准测试失败"
+
+    echo ""
+    echo "--- 热路径 Criterion 基准（与 main 基线对比）---"
+    cargo bench --features test-utils --bench performance_hot_path_benchmarks -- --baseline main \
+        || echo "热路径基准测试失败（首次运行请先执行 -- --save-baseline main）"
 }
 
 # 运行单元测试
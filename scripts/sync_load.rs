@@ -0,0 +1,432 @@
+//! synapse-rust Sync Fan-out Load Generator
+//!
+//! Simulates N clients long-polling `/sync` in one room while a sender posts
+//! messages, and measures how long each message takes to reach every client.
+//! Results are written as JSON and compared against a stored baseline so
+//! fan-out regressions fail the run.
+//!
+//! Compile:  cargo build --release --bin sync_load
+//! Run:      LOAD_CLIENTS=100 cargo run --release --bin sync_load
+//!
+//! Users are created with the shared-secret admin registration endpoint when
+//! `LOAD_SHARED_SECRET` is set (a server started with
+//! `SYNAPSE_TEST_HARNESS=complement` accepts `complement`), otherwise through
+//! open registration with a dummy auth stage.
+//!
+//! Environment:
+//!   LOAD_BASE_URL                Server URL (default: http://localhost:8008)
+//!   LOAD_CLIENTS                 Concurrent syncing clients (default: 50)
+//!   LOAD_MESSAGES                Messages sent by the sender (default: 20)
+//!   LOAD_SEND_INTERVAL_MS        Delay between messages (default: 250)
+//!   LOAD_SYNC_TIMEOUT_MS         Long-poll timeout per /sync (default: 30000)
+//!   LOAD_SHARED_SECRET           Admin registration shared secret (optional)
+//!   LOAD_OUTPUT                  Output JSON path (default: .gstack/sync_load_results.json)
+//!   LOAD_BASELINE                Baseline JSON path (default: benches/baselines/sync_load.json)
+//!   LOAD_REGRESSION_TOLERANCE    Allowed p95 slowdown vs baseline (default: 0.25 = 25%)
+//!   LOAD_WRITE_BASELINE          Set to 1 to overwrite the baseline with this run
+
+#![allow(clippy::unwrap_used, clippy::cast_precision_loss)]
+
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use tokio::sync::Mutex;
+
+const PASSWORD: &str = "LoadTestPassword123!";
+
+// ---------------------------------------------------------------------------
+// Configuration
+// ---------------------------------------------------------------------------
+
+struct Config {
+    base_url: String,
+    clients: usize,
+    messages: usize,
+    send_interval: Duration,
+    sync_timeout_ms: u64,
+    shared_secret: Option<String>,
+    output_path: String,
+    baseline_path: String,
+    tolerance: f64,
+    write_baseline: bool,
+}
+
+fn env_or<T: std::str::FromStr>(key: &str, default: T) -> T {
+    env::var(key).ok().and_then(|v| v.parse().ok()).unwrap_or(default)
+}
+
+impl Config {
+    fn from_env() -> Self {
+        Self {
+            base_url: env::var("LOAD_BASE_URL").unwrap_or_else(|_| "http://localhost:8008".into()),
+            clients: env_or("LOAD_CLIENTS", 50),
+            messages: env_or("LOAD_MESSAGES", 20),
+            send_interval: Duration::from_millis(env_or("LOAD_SEND_INTERVAL_MS", 250)),
+            sync_timeout_ms: env_or("LOAD_SYNC_TIMEOUT_MS", 30_000),
+            shared_secret: env::var("LOAD_SHARED_SECRET").ok().filter(|s| !s.is_empty()),
+            output_path: env::var("LOAD_OUTPUT").unwrap_or_else(|_| ".gstack/sync_load_results.json".into()),
+            baseline_path: env::var("LOAD_BASELINE").unwrap_or_else(|_| "benches/baselines/sync_load.json".into()),
+            tolerance: env_or("LOAD_REGRESSION_TOLERANCE", 0.25),
+            write_baseline: env::var("LOAD_WRITE_BASELINE").is_ok_and(|v| v == "1" || v.eq_ignore_ascii_case("true")),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Statistics
+// ---------------------------------------------------------------------------
+
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+struct Percentiles {
+    count: usize,
+    p50_ms: f64,
+    p95_ms: f64,
+    p99_ms: f64,
+    max_ms: f64,
+}
+
+fn percentiles(mut samples: Vec<f64>) -> Percentiles {
+    if samples.is_empty() {
+        return Percentiles::default();
+    }
+    samples.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+    let at = |q: f64| samples[((samples.len() as f64 - 1.0) * q).round() as usize];
+    Percentiles { count: samples.len(), p50_ms: at(0.50), p95_ms: at(0.95), p99_ms: at(0.99), max_ms: at(1.0) }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct LoadReport {
+    clients: usize,
+    messages: usize,
+    initial_sync: Percentiles,
+    fanout: Percentiles,
+    sync_requests: usize,
+    sync_errors: usize,
+    delivery_ratio: f64,
+}
+
+// ---------------------------------------------------------------------------
+// Matrix client helpers
+// ---------------------------------------------------------------------------
+
+struct Session {
+    user_id: String,
+    access_token: String,
+}
+
+async fn register(client: &reqwest::Client, config: &Config, username: &str) -> Result<Session, String> {
+    let body = match &config.shared_secret {
+        Some(secret) => {
+            let url = format!("{}/_synapse/admin/v1/register", config.base_url);
+            let nonce: Value =
+                client.get(&url).send().await.map_err(|e| e.to_string())?.json().await.map_err(|e| e.to_string())?;
+            let nonce = nonce["nonce"].as_str().ok_or("nonce missing from response")?.to_string();
+            let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).map_err(|e| e.to_string())?;
+            let parts: [&[u8]; 7] =
+                [nonce.as_bytes(), b"\0", username.as_bytes(), b"\0", PASSWORD.as_bytes(), b"\0", b"notadmin"];
+            for part in parts {
+                mac.update(part);
+            }
+            let mac = hex::encode(mac.finalize().into_bytes());
+            client
+                .post(&url)
+                .json(
+                    &json!({ "nonce": nonce, "username": username, "password": PASSWORD, "admin": false, "mac": mac }),
+                )
+                .send()
+                .await
+        }
+        None => {
+            client
+                .post(format!("{}/_matrix/client/v3/register", config.base_url))
+                .json(&json!({ "username": username, "password": PASSWORD, "auth": { "type": "m.login.dummy" } }))
+                .send()
+                .await
+        }
+    }
+    .map_err(|e| e.to_string())?;
+
+    let status = body.status();
+    let body: Value = body.json().await.map_err(|e| e.to_string())?;
+    if !status.is_success() {
+        return Err(format!("registering {username} failed with {status}: {body}"));
+    }
+    Ok(Session {
+        user_id: body["user_id"].as_str().ok_or("user_id missing")?.to_string(),
+        access_token: body["access_token"].as_str().ok_or("access_token missing")?.to_string(),
+    })
+}
+
+async fn post_json(client: &reqwest::Client, url: String, token: &str, body: Value) -> Result<Value, String> {
+    let resp = client.post(url).bearer_auth(token).json(&body).send().await.map_err(|e| e.to_string())?;
+    let status = resp.status();
+    let body: Value = resp.json().await.unwrap_or(Value::Null);
+    if status.is_success() {
+        Ok(body)
+    } else {
+        Err(format!("request failed with {status}: {body}"))
+    }
+}
+
+fn timeline_event_ids(sync: &Value, room_id: &str) -> Vec<String> {
+    sync["rooms"]["join"][room_id]["timeline"]["events"]
+        .as_array()
+        .map(|events| events.iter().filter_map(|e| e["event_id"].as_str().map(str::to_string)).collect())
+        .unwrap_or_default()
+}
+
+// ---------------------------------------------------------------------------
+// Load run
+// ---------------------------------------------------------------------------
+
+struct ClientOutcome {
+    initial_sync_ms: f64,
+    received: Vec<(String, Instant)>,
+    requests: usize,
+    errors: usize,
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn run_client(
+    client: reqwest::Client,
+    base_url: String,
+    session: Session,
+    room_id: String,
+    expected: usize,
+    sync_timeout_ms: u64,
+    deadline: Instant,
+    start: Arc<tokio::sync::Barrier>,
+) -> ClientOutcome {
+    let sync_url = format!("{base_url}/_matrix/client/v3/sync");
+    let filter = json!({ "room": { "timeline": { "limit": 50 }, "state": { "lazy_load_members": true } } }).to_string();
+    let mut outcome = ClientOutcome { initial_sync_ms: 0.0, received: Vec::new(), requests: 0, errors: 0 };
+
+    let started = Instant::now();
+    let initial = client
+        .get(&sync_url)
+        .bearer_auth(&session.access_token)
+        .query(&[("filter", filter.as_str()), ("timeout", "0")])
+        .send()
+        .await;
+    outcome.requests += 1;
+    let mut since = match initial {
+        Ok(resp) => resp.json::<Value>().await.ok().and_then(|b| b["next_batch"].as_str().map(str::to_string)),
+        Err(_) => None,
+    };
+    outcome.initial_sync_ms = started.elapsed().as_secs_f64() * 1000.0;
+    if since.is_none() {
+        outcome.errors += 1;
+    }
+    start.wait().await;
+
+    while outcome.received.len() < expected && Instant::now() < deadline {
+        let mut query = vec![("filter", filter.clone()), ("timeout", sync_timeout_ms.to_string())];
+        if let Some(token) = &since {
+            query.push(("since", token.clone()));
+        }
+        outcome.requests += 1;
+        let resp = client.get(&sync_url).bearer_auth(&session.access_token).query(&query).send().await;
+        let body = match resp {
+            Ok(resp) if resp.status().is_success() => resp.json::<Value>().await.ok(),
+            _ => None,
+        };
+        let Some(body) = body else {
+            outcome.errors += 1;
+            tokio::time::sleep(Duration::from_millis(200)).await;
+            continue;
+        };
+        let now = Instant::now();
+        outcome.received.extend(timeline_event_ids(&body, &room_id).into_iter().map(|id| (id, now)));
+        since = body["next_batch"].as_str().map(str::to_string).or(since);
+    }
+    outcome
+}
+
+async fn run(config: &Config) -> Result<LoadReport, String> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_millis(config.sync_timeout_ms + 30_000))
+        .pool_max_idle_per_host(config.clients + 8)
+        .build()
+        .map_err(|e| e.to_string())?;
+
+    let run_id = rand::random::<u32>();
+    let sender = register(&client, config, &format!("load_sender_{run_id}")).await?;
+    let room = post_json(
+        &client,
+        format!("{}/_matrix/client/v3/createRoom", config.base_url),
+        &sender.access_token,
+        json!({ "preset": "public_chat", "name": format!("sync load {run_id}") }),
+    )
+    .await?;
+    let room_id = room["room_id"].as_str().ok_or("room_id missing")?.to_string();
+
+    println!("Registering {} clients and joining {room_id} ...", config.clients);
+    let mut sessions = Vec::with_capacity(config.clients);
+    for i in 0..config.clients {
+        let session = register(&client, config, &format!("load_client_{run_id}_{i}")).await?;
+        post_json(
+            &client,
+            format!("{}/_matrix/client/v3/rooms/{room_id}/join", config.base_url),
+            &session.access_token,
+            json!({}),
+        )
+        .await
+        .map_err(|e| format!("{} failed to join: {e}", session.user_id))?;
+        sessions.push(session);
+    }
+
+    let deadline = Instant::now()
+        + config.send_interval * config.messages as u32
+        + Duration::from_millis(config.sync_timeout_ms)
+        + Duration::from_secs(60);
+    let barrier = Arc::new(tokio::sync::Barrier::new(config.clients + 1));
+    let handles: Vec<_> = sessions
+        .into_iter()
+        .map(|session| {
+            tokio::spawn(run_client(
+                client.clone(),
+                config.base_url.clone(),
+                session,
+                room_id.clone(),
+                config.messages,
+                config.sync_timeout_ms,
+                deadline,
+                barrier.clone(),
+            ))
+        })
+        .collect();
+    barrier.wait().await;
+
+    println!("Sending {} messages ...", config.messages);
+    let sent: Arc<Mutex<HashMap<String, Instant>>> = Arc::new(Mutex::new(HashMap::new()));
+    for i in 0..config.messages {
+        let url =
+            format!("{}/_matrix/client/v3/rooms/{room_id}/send/m.room.message/load-{run_id}-{i}", config.base_url);
+        let sent_at = Instant::now();
+        let resp = client
+            .put(url)
+            .bearer_auth(&sender.access_token)
+            .json(&json!({ "msgtype": "m.text", "body": format!("load message {i}") }))
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        let body: Value = resp.json().await.map_err(|e| e.to_string())?;
+        if let Some(event_id) = body["event_id"].as_str() {
+            sent.lock().await.insert(event_id.to_string(), sent_at);
+        }
+        tokio::time::sleep(config.send_interval).await;
+    }
+
+    let sent = sent.lock().await.clone();
+    let mut initial_sync = Vec::new();
+    let mut fanout = Vec::new();
+    let (mut requests, mut errors, mut delivered) = (0, 0, 0usize);
+    for handle in handles {
+        let outcome = handle.await.map_err(|e| e.to_string())?;
+        initial_sync.push(outcome.initial_sync_ms);
+        requests += outcome.requests;
+        errors += outcome.errors;
+        for (event_id, received_at) in outcome.received {
+            if let Some(sent_at) = sent.get(&event_id) {
+                delivered += 1;
+                fanout.push(received_at.saturating_duration_since(*sent_at).as_secs_f64() * 1000.0);
+            }
+        }
+    }
+
+    let expected = (config.clients * sent.len()).max(1);
+    Ok(LoadReport {
+        clients: config.clients,
+        messages: config.messages,
+        initial_sync: percentiles(initial_sync),
+        fanout: percentiles(fanout),
+        sync_requests: requests,
+        sync_errors: errors,
+        delivery_ratio: delivered as f64 / expected as f64,
+    })
+}
+
+/// Regressions of `report` against `baseline`, as human-readable lines.
+fn regressions(report: &LoadReport, baseline: &LoadReport, tolerance: f64) -> Vec<String> {
+    let mut out = Vec::new();
+    for (label, current, base) in [
+        ("initial_sync p95", report.initial_sync.p95_ms, baseline.initial_sync.p95_ms),
+        ("fanout p95", report.fanout.p95_ms, baseline.fanout.p95_ms),
+    ] {
+        if base > 0.0 && current > base * (1.0 + tolerance) {
+            out.push(format!(
+                "{label}: {current:.1}ms vs baseline {base:.1}ms (+{:.0}%)",
+                (current / base - 1.0) * 100.0
+            ));
+        }
+    }
+    if report.delivery_ratio + 1e-9 < baseline.delivery_ratio {
+        out.push(format!("delivery ratio {:.3} below baseline {:.3}", report.delivery_ratio, baseline.delivery_ratio));
+    }
+    out
+}
+
+fn write_json(path: &str, report: &LoadReport) {
+    if let Some(parent) = Path::new(path).parent() {
+        let _ = fs::create_dir_all(parent);
+    }
+    fs::write(path, serde_json::to_string_pretty(report).unwrap()).unwrap();
+}
+
+// ---------------------------------------------------------------------------
+// Main
+// ---------------------------------------------------------------------------
+
+#[tokio::main]
+async fn main() {
+    let config = Config::from_env();
+    let versions = reqwest::get(format!("{}/_matrix/client/versions", config.base_url)).await;
+    if !versions.is_ok_and(|r| r.status().is_success()) {
+        eprintln!("Server at {} is not reachable", config.base_url);
+        std::process::exit(2);
+    }
+
+    let report = match run(&config).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Load run failed: {e}");
+            std::process::exit(2);
+        }
+    };
+
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    write_json(&config.output_path, &report);
+    println!("Results written to {}", config.output_path);
+
+    if config.write_baseline {
+        write_json(&config.baseline_path, &report);
+        println!("Baseline updated at {}", config.baseline_path);
+        return;
+    }
+
+    let baseline =
+        fs::read_to_string(&config.baseline_path).ok().and_then(|s| serde_json::from_str::<LoadReport>(&s).ok());
+    match baseline {
+        Some(baseline) if baseline.clients == report.clients && baseline.messages == report.messages => {
+            let found = regressions(&report, &baseline, config.tolerance);
+            if !found.is_empty() {
+                eprintln!("Regressions against {}:", config.baseline_path);
+                for line in &found {
+                    eprintln!("  {line}");
+                }
+                std::process::exit(1);
+            }
+            println!("No regressions against {}", config.baseline_path);
+        }
+        Some(_) => println!("Baseline {} was recorded with a different shape; not comparing", config.baseline_path),
+        None => println!("No baseline at {}; run with LOAD_WRITE_BASELINE=1 to record one", config.baseline_path),
+    }
+}
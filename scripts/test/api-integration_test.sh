@@ -1062,7 +1062,7 @@ n='$NONCE'
 u='$ADMIN_LOGIN_USER'
 p='$ADMIN_PASS'
 t='$ADMIN_USER_TYPE'
-msg = n.encode() + b'\x00' + u.encode() + b'\x00' + p.encode() + b'\x00' + b'admin\x00\x00\x00'
+msg = n.encode() + b'\x00' + u.encode() + b'\x00' + p.encode() + b'\x00' + b'admin'
 if t:
     msg += b'\x00' + t.encode()
 print(hmac.new(b'$ADMIN_SHARED_SECRET', msg, hashlib.sha256).hexdigest())
@@ -3702,7 +3702,7 @@ n='$REGISTER_NONCE'
 u='$REGISTER_USERNAME'
 p='$REGISTER_PASSWORD'
 t='$ADMIN_USER_TYPE'
-msg = n.encode() + b'\x00' + u.encode() + b'\x00' + p.encode() + b'\x00' + b'admin\x00\x00\x00'
+msg = n.encode() + b'\x00' + u.encode() + b'\x00' + p.encode() + b'\x00' + b'admin'
 if t:
     msg += b'\x00' + t.encode()
 print(hmac.new(b'$ADMIN_SHARED_SECRET', msg, hashlib.sha256).hexdigest())
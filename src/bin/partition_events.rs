@@ -0,0 +1,112 @@
+//! `partition_events` - 将 events 表转换为 Postgres 声明式分区表
+//!
+//! 默认只打印转换 SQL（dry-run），加 `--apply` 后在单个事务中执行。
+//! 原表保留为 `events_unpartitioned`，核对无误后由运维手动删除。
+//! 转换期间 events 表被锁定，应在停机窗口内运行。
+//!
+//! ## 用法
+//! ```bash
+//! # 按 origin_server_ts 范围分区，每 30 天一个分区
+//! DATABASE_URL=postgres://... cargo run --bin partition_events -- --strategy range --interval-days 30
+//! # 按 room_id 哈希分为 16 个分区，并执行转换
+//! DATABASE_URL=postgres://... cargo run --bin partition_events -- --strategy hash --partitions 16 --apply
+//! ```
+//!
+//! 范围分区转换后需在配置中开启 `database.event_partitioning.enabled`，
+//! 由后台任务预建未来分区。
+//!
+//! ## 退出码
+//! - 0: 成功
+//! - 1: 参数错误或转换失败
+//! - 2: 连接错误
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use sqlx::postgres::PgPoolOptions;
+
+use synapse_common::current_timestamp_millis;
+use synapse_rust::storage::event::partitioning::EventPartitionStrategy;
+use synapse_rust::storage::event::EventStorage;
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+fn parse_strategy(args: &[String]) -> Result<EventPartitionStrategy, String> {
+    let number = |flag: &str, default: u32| -> Result<u32, String> {
+        flag_value(args, flag).map_or(Ok(default), |v| {
+            v.parse().ok().filter(|n| *n > 0).ok_or_else(|| format!("{flag} must be a positive integer"))
+        })
+    };
+    match flag_value(args, "--strategy") {
+        Some("range") => Ok(EventPartitionStrategy::Range { interval_days: number("--interval-days", 30)? }),
+        Some("hash") => Ok(EventPartitionStrategy::Hash { partitions: number("--partitions", 16)? }),
+        _ => Err("--strategy must be `range` or `hash`".to_string()),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let apply = args.iter().any(|a| a == "--apply");
+
+    let strategy = match parse_strategy(&args) {
+        Ok(strategy) => strategy,
+        Err(e) => {
+            eprintln!("ERROR: {e}");
+            return ExitCode::from(1);
+        }
+    };
+
+    let database_url = match std::env::var("DATABASE_URL") {
+        Ok(url) => url,
+        Err(_) => {
+            eprintln!("ERROR: DATABASE_URL environment variable is not set");
+            return ExitCode::from(2);
+        }
+    };
+
+    let pool = match PgPoolOptions::new()
+        .max_connections(2)
+        .acquire_timeout(std::time::Duration::from_secs(10))
+        .connect(&database_url)
+        .await
+    {
+        Ok(pool) => Arc::new(pool),
+        Err(e) => {
+            eprintln!("ERROR: Failed to connect to database: {e}");
+            return ExitCode::from(2);
+        }
+    };
+    let storage = EventStorage::new(&pool, String::new());
+
+    let plan = match storage.partition_conversion_plan(strategy, current_timestamp_millis()).await {
+        Ok(plan) => plan,
+        Err(e) => {
+            eprintln!("ERROR: Failed to build conversion plan: {e}");
+            return ExitCode::from(1);
+        }
+    };
+
+    for statement in &plan {
+        println!("{statement};");
+    }
+
+    if !apply {
+        eprintln!("[partition_events] Dry run; re-run with --apply to execute {} statements", plan.len());
+        return ExitCode::SUCCESS;
+    }
+
+    eprintln!("[partition_events] Applying {} statements in one transaction...", plan.len());
+    match storage.apply_partition_conversion(&plan).await {
+        Ok(()) => {
+            eprintln!("[partition_events] Done; drop events_unpartitioned once the new table has been verified");
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("ERROR: Conversion failed and was rolled back: {e}");
+            ExitCode::from(1)
+        }
+    }
+}
@@ -0,0 +1,120 @@
+//! `rotate_signing_key` - 轮换联邦签名密钥
+//!
+//! 生成新的 ed25519 签名密钥并设为当前密钥；旧密钥以轮换时间作为
+//! `expired_ts` 保留在 `/_matrix/key/v2/server` 的 `old_verify_keys` 中，
+//! 旧密钥签名的本地事件在轮换后仍可验证。已吊销的密钥不会再被发布。
+//!
+//! 配置读取方式与服务端相同（`SYNAPSE_CONFIG_PATH`，默认 `./homeserver.yaml`），
+//! 需要与服务端一致的 `federation.signing_key_master_key`。
+//!
+//! ## 用法
+//! ```bash
+//! # 查看当前密钥与 old_verify_keys，不做修改
+//! SYNAPSE_CONFIG_PATH=homeserver.yaml cargo run --bin rotate_signing_key -- --list
+//! # 轮换，新密钥 ID 自动生成或由 --key-id 指定
+//! SYNAPSE_CONFIG_PATH=homeserver.yaml cargo run --bin rotate_signing_key -- --key-id ed25519:b
+//! ```
+//!
+//! 运行中的服务端在重启前仍使用内存中的旧密钥签名；不停机轮换请使用
+//! `POST /_matrix/client/v1/keys/rotation/rotate`。
+//!
+//! ## 退出码
+//! - 0: 成功
+//! - 1: 参数错误或轮换失败
+//! - 2: 配置或连接错误
+
+use std::process::ExitCode;
+use std::sync::Arc;
+
+use sqlx::postgres::PgPoolOptions;
+
+use synapse_rust::common::config::Config;
+use synapse_rust::federation::key_rotation::KeyRotationManager;
+
+fn flag_value<'a>(args: &'a [String], flag: &str) -> Option<&'a str> {
+    args.iter().position(|a| a == flag).and_then(|i| args.get(i + 1)).map(String::as_str)
+}
+
+fn print_server_keys(server_keys: &serde_json::Value) {
+    match serde_json::to_string_pretty(server_keys) {
+        Ok(pretty) => println!("{pretty}"),
+        Err(_) => println!("{server_keys}"),
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().collect();
+    let list_only = args.iter().any(|a| a == "--list");
+    let requested_key_id = flag_value(&args, "--key-id").map(str::to_owned);
+
+    if let Some(key_id) = &requested_key_id {
+        if !key_id.starts_with("ed25519:") || key_id.len() == "ed25519:".len() {
+            eprintln!("ERROR: --key-id must look like ed25519:<version>");
+            return ExitCode::from(1);
+        }
+    }
+
+    let config = match Config::load() {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("ERROR: Failed to load configuration: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let pool = match PgPoolOptions::new()
+        .max_connections(2)
+        .acquire_timeout(std::time::Duration::from_secs(10))
+        .connect(&config.database_url())
+        .await
+    {
+        Ok(pool) => Arc::new(pool),
+        Err(e) => {
+            eprintln!("ERROR: Failed to connect to database: {e}");
+            return ExitCode::from(2);
+        }
+    };
+
+    let server_name = if config.federation.server_name.is_empty() {
+        config.server.name.clone()
+    } else {
+        config.federation.server_name.clone()
+    };
+    let manager = KeyRotationManager::with_key_path_and_master_key(
+        &pool,
+        &server_name,
+        config.server.signing_key_path.clone(),
+        config.federation.signing_key_master_key.as_ref().map(|k| k.as_bytes().to_vec()),
+    );
+
+    if let Err(e) = manager.load_or_create_key().await {
+        eprintln!("ERROR: Failed to load the current signing key: {e}");
+        return ExitCode::from(1);
+    }
+    let old_key_id = manager.get_current_key().await.ok().flatten().map(|key| key.key_id);
+
+    if !list_only {
+        if let Err(e) = manager.rotate_keys(requested_key_id).await {
+            eprintln!("ERROR: Key rotation failed: {e}");
+            return ExitCode::from(1);
+        }
+        let new_key_id = manager.get_current_key().await.ok().flatten().map(|key| key.key_id);
+        eprintln!(
+            "[rotate_signing_key] {server_name}: {} -> {}; restart the server to start signing with the new key",
+            old_key_id.as_deref().unwrap_or("<none>"),
+            new_key_id.as_deref().unwrap_or("<none>"),
+        );
+    }
+
+    match manager.get_server_keys_response().await {
+        Ok(server_keys) => {
+            print_server_keys(&server_keys);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("ERROR: Failed to build the server keys response: {e}");
+            ExitCode::from(1)
+        }
+    }
+}
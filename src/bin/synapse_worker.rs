@@ -1,13 +1,16 @@
 #![cfg_attr(test, allow(clippy::panic))]
 
 use axum::{extract::State, response::IntoResponse, routing::get, Router};
+use lettre::message::dkim::{DkimConfig, DkimSigningAlgorithm, DkimSigningKey};
 use lettre::message::Mailbox;
 use lettre::{AsyncTransport, Message, Tokio1Executor};
 use std::net::SocketAddr;
 use std::sync::Arc;
-use synapse_rust::common::config::{Config, SmtpConfig};
+use std::time::Duration;
+use synapse_rust::common::config::{Config, DkimAlgorithm, SmtpConfig, SmtpDkimConfig, SmtpRetryConfig, SmtpSecurity};
 use synapse_rust::common::BackgroundJob;
 use synapse_rust::common::RedisTaskQueue;
+use synapse_rust::storage::email_retry::EmailRetryStorage;
 use synapse_rust::storage::event::EventStorage;
 use tokio::signal;
 
@@ -19,6 +22,11 @@ struct MetricsState {
 
 type SmtpMailer = lettre::AsyncSmtpTransport<Tokio1Executor>;
 
+const EMAIL_RETRY_POLL_INTERVAL: Duration = Duration::from_secs(15);
+/// How long a claimed retry stays hidden from other workers while it is sent.
+const EMAIL_RETRY_LEASE_MS: i64 = 10 * 60 * 1000;
+const EMAIL_RETRY_BATCH: i64 = 50;
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing_subscriber::fmt::init();
@@ -33,6 +41,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Connecting to Database...");
     let pool = sqlx::PgPool::connect(&db_url).await?;
     let server_name = config.server.name.clone();
+    let email_retry_storage = Arc::new(EmailRetryStorage::new(pool.clone()));
     let event_storage = Arc::new(EventStorage::new(&Arc::new(pool), server_name));
 
     // Build SMTP transport if SMTP is enabled
@@ -42,11 +51,12 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             match build_smtp_mailer(&smtp_config) {
                 Ok(mailer) => {
                     tracing::info!(
-                        "SMTP enabled: {}:{} (from={}, tls={})",
+                        "SMTP enabled: {}:{} (from={}, security={:?}, pool_max_size={})",
                         smtp_config.host,
                         smtp_config.port,
                         smtp_config.from,
-                        smtp_config.tls
+                        smtp_config.effective_security(),
+                        smtp_config.pool.max_size
                     );
                     Some(Arc::new(mailer))
                 }
@@ -59,6 +69,28 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tracing::info!("SMTP not configured; email sending disabled");
             None
         };
+    let dkim: Option<Arc<DkimConfig>> = match smtp_config.dkim.as_ref().filter(|_| smtp_mailer.is_some()) {
+        Some(dkim_config) => match load_dkim_config(dkim_config) {
+            Ok(dkim) => {
+                tracing::info!("DKIM signing enabled for {} (selector {})", dkim_config.domain, dkim_config.selector);
+                Some(Arc::new(dkim))
+            }
+            Err(e) => {
+                tracing::error!("Failed to load DKIM key {}: {}", dkim_config.private_key_path.display(), e);
+                return Err(e);
+            }
+        },
+        None => None,
+    };
+    let retry_handle = smtp_mailer.clone().map(|mailer| {
+        tokio::spawn(run_email_retry_loop(
+            email_retry_storage.clone(),
+            mailer,
+            dkim.clone(),
+            smtp_config.from.clone(),
+            smtp_config.retry.clone(),
+        ))
+    });
 
     let worker_id = uuid::Uuid::new_v4().to_string();
     let consumer_name = format!("worker-{worker_id}");
@@ -69,14 +101,39 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let event_storage_clone = event_storage.clone();
     let smtp_mailer_clone = smtp_mailer.clone();
     let smtp_from = smtp_config.from.clone();
+    let smtp_retry = smtp_config.retry.clone();
     let job_handler = move |job: BackgroundJob| {
         let event_storage = event_storage_clone.clone();
         let smtp_mailer = smtp_mailer_clone.clone();
         let smtp_from = smtp_from.clone();
+        let dkim = dkim.clone();
+        let email_retry_storage = email_retry_storage.clone();
+        let smtp_retry = smtp_retry.clone();
         async move {
             match job {
                 BackgroundJob::SendEmail { to, subject, body } => {
-                    process_send_email_job(smtp_mailer.clone(), &smtp_from, &to, &subject, &body).await
+                    match process_send_email_job(smtp_mailer.clone(), dkim.as_deref(), &smtp_from, &to, &subject, &body)
+                        .await
+                    {
+                        Ok(()) => Ok(()),
+                        Err(EmailSendError::Transient(e)) => {
+                            let now = chrono::Utc::now().timestamp_millis();
+                            let Some(next) = next_email_attempt_ts(&smtp_retry, 1, now) else {
+                                return Err(e);
+                            };
+                            match email_retry_storage.enqueue(&to, &subject, &body, next, &e, now).await {
+                                Ok(id) => {
+                                    tracing::info!("[EMAIL] Queued email {} for retry", id);
+                                    Ok(())
+                                }
+                                Err(db_err) => {
+                                    tracing::error!("[EMAIL] Failed to queue email for retry: {}", db_err);
+                                    Err(e)
+                                }
+                            }
+                        }
+                        Err(EmailSendError::Permanent(e)) => Err(e),
+                    }
                 }
                 BackgroundJob::ProcessMedia { file_id } => {
                     tracing::info!("[MEDIA] Processing media file: {}", file_id);
@@ -232,6 +289,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     }
 
     handle.abort();
+    if let Some(h) = retry_handle {
+        h.abort();
+    }
     if let Some(h) = metrics_handle {
         h.abort();
     }
@@ -242,16 +302,21 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
 }
 
 fn build_smtp_mailer(config: &SmtpConfig) -> Result<SmtpMailer, Box<dyn std::error::Error>> {
-    let tls = if config.tls {
-        lettre::transport::smtp::client::Tls::Required(lettre::transport::smtp::client::TlsParameters::new(
-            config.host.clone(),
-        )?)
-    } else {
-        lettre::transport::smtp::client::Tls::None
+    use lettre::transport::smtp::client::{Tls, TlsParameters};
+
+    let tls = match config.effective_security() {
+        SmtpSecurity::None => Tls::None,
+        SmtpSecurity::StartTls => Tls::Required(TlsParameters::new(config.host.clone())?),
+        SmtpSecurity::ImplicitTls => Tls::Wrapper(TlsParameters::new(config.host.clone())?),
     };
+    let pool = lettre::transport::smtp::PoolConfig::new()
+        .max_size(config.pool.max_size.max(1))
+        .idle_timeout(Duration::from_secs(config.pool.idle_timeout_secs));
 
-    let mut builder =
-        lettre::AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host).port(config.port).tls(tls);
+    let mut builder = lettre::AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.host)
+        .port(config.port)
+        .tls(tls)
+        .pool_config(pool);
 
     if !config.username.is_empty() {
         let credentials =
@@ -262,13 +327,39 @@ fn build_smtp_mailer(config: &SmtpConfig) -> Result<SmtpMailer, Box<dyn std::err
     Ok(builder.build())
 }
 
+fn load_dkim_config(config: &SmtpDkimConfig) -> Result<DkimConfig, Box<dyn std::error::Error>> {
+    let pem = std::fs::read_to_string(&config.private_key_path)?;
+    let algorithm = match config.algorithm {
+        DkimAlgorithm::Rsa => DkimSigningAlgorithm::Rsa,
+        DkimAlgorithm::Ed25519 => DkimSigningAlgorithm::Ed25519,
+    };
+    let key = DkimSigningKey::new(pem.trim(), algorithm)?;
+    Ok(DkimConfig::default_config(config.selector.clone(), config.domain.clone(), key))
+}
+
+/// Why an email was not sent. Only `Transient` failures are worth retrying.
+#[derive(Debug)]
+enum EmailSendError {
+    Transient(String),
+    Permanent(String),
+}
+
+impl std::fmt::Display for EmailSendError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Transient(msg) | Self::Permanent(msg) => f.write_str(msg),
+        }
+    }
+}
+
 async fn process_send_email_job(
     smtp_mailer: Option<Arc<SmtpMailer>>,
+    dkim: Option<&DkimConfig>,
     smtp_from: &str,
     to: &str,
     subject: &str,
     body: &str,
-) -> Result<(), String> {
+) -> Result<(), EmailSendError> {
     tracing::info!("[EMAIL] Sending email (recipient masked)");
     tracing::debug!("[EMAIL] Recipient: {}", to);
     tracing::debug!("[EMAIL] Subject: {}", subject);
@@ -277,7 +368,7 @@ async fn process_send_email_job(
         Some(m) => m,
         None => {
             tracing::error!("[EMAIL] SMTP not configured, cannot send email (recipient masked)");
-            return Err("SMTP not configured".to_string());
+            return Err(EmailSendError::Permanent("SMTP not configured".to_string()));
         }
     };
 
@@ -285,7 +376,7 @@ async fn process_send_email_job(
         Ok(f) => f,
         Err(e) => {
             tracing::error!("[EMAIL] Invalid from address '{}': {}", smtp_from, e);
-            return Err(format!("Invalid from address: {}", e));
+            return Err(EmailSendError::Permanent(format!("Invalid from address: {}", e)));
         }
     };
 
@@ -293,17 +384,21 @@ async fn process_send_email_job(
         Ok(t) => t,
         Err(e) => {
             tracing::error!("[EMAIL] Invalid to address (recipient masked): {}", e);
-            return Err(format!("Invalid to address: {}", e));
+            return Err(EmailSendError::Permanent(format!("Invalid to address: {}", e)));
         }
     };
 
-    let email = match Message::builder().from(from).to(to_mailbox).subject(subject.to_string()).body(body.to_string()) {
-        Ok(e) => e,
-        Err(e) => {
-            tracing::error!("[EMAIL] Failed to build email message: {}", e);
-            return Err(format!("Failed to build email: {}", e));
-        }
-    };
+    let mut email =
+        match Message::builder().from(from).to(to_mailbox).subject(subject.to_string()).body(body.to_string()) {
+            Ok(e) => e,
+            Err(e) => {
+                tracing::error!("[EMAIL] Failed to build email message: {}", e);
+                return Err(EmailSendError::Permanent(format!("Failed to build email: {}", e)));
+            }
+        };
+    if let Some(dkim) = dkim {
+        email.sign(dkim);
+    }
 
     match mailer.send(email).await {
         Ok(_) => {
@@ -312,7 +407,78 @@ async fn process_send_email_job(
         }
         Err(e) => {
             tracing::error!("[EMAIL] Failed to send email (recipient masked): {}", e);
-            Err(format!("SMTP send error: {}", e))
+            let message = format!("SMTP send error: {}", e);
+            // 5xx replies and malformed requests fail the same way every
+            // time; connection, TLS and 4xx errors may not.
+            if e.is_permanent() || e.is_client() {
+                Err(EmailSendError::Permanent(message))
+            } else {
+                Err(EmailSendError::Transient(message))
+            }
+        }
+    }
+}
+
+/// When to retry an email after `failed_attempts` failed sends, or `None`
+/// once `smtp.retry.max_attempts` is used up.
+fn next_email_attempt_ts(retry: &SmtpRetryConfig, failed_attempts: u32, now_ts: i64) -> Option<i64> {
+    if failed_attempts >= retry.max_attempts {
+        return None;
+    }
+    let backoff_ms = i64::try_from(retry.backoff_secs(failed_attempts).saturating_mul(1000)).unwrap_or(i64::MAX);
+    Some(now_ts.saturating_add(backoff_ms))
+}
+
+/// Retries the emails in `email_retry_queue` as they come due.
+async fn run_email_retry_loop(
+    storage: Arc<EmailRetryStorage>,
+    mailer: Arc<SmtpMailer>,
+    dkim: Option<Arc<DkimConfig>>,
+    smtp_from: String,
+    retry: SmtpRetryConfig,
+) {
+    let mut interval = tokio::time::interval(EMAIL_RETRY_POLL_INTERVAL);
+    loop {
+        interval.tick().await;
+        let now = chrono::Utc::now().timestamp_millis();
+        let due = match storage.claim_due(now, now + EMAIL_RETRY_LEASE_MS, EMAIL_RETRY_BATCH).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::error!("[EMAIL] Failed to load queued email retries: {}", e);
+                continue;
+            }
+        };
+
+        for entry in due {
+            let result = process_send_email_job(
+                Some(mailer.clone()),
+                dkim.as_deref(),
+                &smtp_from,
+                &entry.recipient,
+                &entry.subject,
+                &entry.body,
+            )
+            .await;
+            let failed_attempts = u32::try_from(entry.attempts).unwrap_or(0).saturating_add(1);
+            let now = chrono::Utc::now().timestamp_millis();
+            let update = match result {
+                Ok(()) => storage.mark_sent(entry.id).await,
+                Err(EmailSendError::Transient(e)) => match next_email_attempt_ts(&retry, failed_attempts, now) {
+                    Some(next) => storage.reschedule(entry.id, next, &e).await,
+                    None => {
+                        tracing::warn!(
+                            "[EMAIL] Giving up on queued email {} after {} attempts",
+                            entry.id,
+                            failed_attempts
+                        );
+                        storage.mark_failed(entry.id, &e).await
+                    }
+                },
+                Err(EmailSendError::Permanent(e)) => storage.mark_failed(entry.id, &e).await,
+            };
+            if let Err(e) = update {
+                tracing::error!("[EMAIL] Failed to update queued email {}: {}", entry.id, e);
+            }
         }
     }
 }
@@ -362,8 +528,9 @@ mod smtp_smoke_tests {
     use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
     use tokio::net::TcpListener;
 
-    #[test]
-    fn test_build_smtp_mailer_with_tls() {
+    // The pooled transport spawns its shutdown on drop, so it needs a runtime.
+    #[tokio::test]
+    async fn test_build_smtp_mailer_with_tls() {
         let config = SmtpConfig {
             enabled: true,
             host: "smtp.example.com".to_string(),
@@ -389,8 +556,8 @@ mod smtp_smoke_tests {
         }
     }
 
-    #[test]
-    fn test_build_smtp_mailer_without_tls() {
+    #[tokio::test]
+    async fn test_build_smtp_mailer_without_tls() {
         let config = SmtpConfig {
             enabled: true,
             host: "localhost".to_string(),
@@ -404,8 +571,8 @@ mod smtp_smoke_tests {
         assert!(result.is_ok(), "mailer should build without TLS: {:?}", result.err());
     }
 
-    #[test]
-    fn test_build_smtp_mailer_no_credentials() {
+    #[tokio::test]
+    async fn test_build_smtp_mailer_no_credentials() {
         let config = SmtpConfig {
             host: "localhost".to_string(),
             port: 25,
@@ -417,6 +584,39 @@ mod smtp_smoke_tests {
         assert!(result.is_ok(), "mailer should build without auth credentials");
     }
 
+    #[tokio::test]
+    async fn test_build_smtp_mailer_with_implicit_tls() {
+        let config = SmtpConfig {
+            host: "localhost".to_string(),
+            port: 465,
+            security: Some(SmtpSecurity::ImplicitTls),
+            ..Default::default()
+        };
+        assert!(build_smtp_mailer(&config).is_ok(), "mailer should build with implicit TLS");
+    }
+
+    #[test]
+    fn test_next_email_attempt_stops_after_max_attempts() {
+        let retry = SmtpRetryConfig { max_attempts: 3, initial_backoff_secs: 10, max_backoff_secs: 15 };
+        assert_eq!(next_email_attempt_ts(&retry, 1, 1_000), Some(11_000));
+        assert_eq!(next_email_attempt_ts(&retry, 2, 1_000), Some(16_000));
+        assert_eq!(next_email_attempt_ts(&retry, 3, 1_000), None);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_smtp_server_is_a_transient_failure() {
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap_or_else(|e| panic!("bind: {e}"));
+        let port = listener.local_addr().unwrap_or_else(|e| panic!("read addr: {e}")).port();
+        drop(listener);
+
+        let config = SmtpConfig { host: "127.0.0.1".to_string(), port, tls: false, ..Default::default() };
+        let mailer = Arc::new(build_smtp_mailer(&config).unwrap_or_else(|e| panic!("build mailer: {e}")));
+        let result =
+            process_send_email_job(Some(mailer), None, "noreply@example.com", "user@example.com", "Subject", "Body")
+                .await;
+        assert!(matches!(result, Err(EmailSendError::Transient(_))), "unexpected result: {result:?}");
+    }
+
     #[test]
     fn test_email_job_message_building() {
         // Verify that a valid email message can be constructed
@@ -501,9 +701,16 @@ mod smtp_smoke_tests {
         };
         let mailer = Arc::new(build_smtp_mailer(&config).unwrap_or_else(|e| panic!("build fake smtp mailer: {e}")));
 
-        process_send_email_job(Some(mailer), &config.from, "user@example.com", "Smoke Test Subject", "Smoke Test Body")
-            .await
-            .unwrap_or_else(|e| panic!("smtp send should succeed: {e}"));
+        process_send_email_job(
+            Some(mailer),
+            None,
+            &config.from,
+            "user@example.com",
+            "Smoke Test Subject",
+            "Smoke Test Body",
+        )
+        .await
+        .unwrap_or_else(|e| panic!("smtp send should succeed: {e}"));
 
         let message = match server.await {
             Ok(message) => message,
@@ -45,7 +45,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -60,6 +62,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "localhost".to_string(),
@@ -138,6 +144,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -155,6 +162,7 @@ mod tests {
             oidc: OidcConfig::default(),
             builtin_oidc: BuiltinOidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: synapse_common::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: synapse_common::telemetry_config::PrometheusConfig::default(),
@@ -162,6 +170,15 @@ mod tests {
             experimental: ExperimentalConfig::default(),
             identity: IdentityConfig::default(),
             translate: TranslateConfig::default(),
+            content_filter: ContentFilterConfig::default(),
+            room_directory: RoomDirectoryConfig::default(),
+            room_creation: RoomCreationConfig::default(),
+            welcome: WelcomeConfig::default(),
+            policy_lists: PolicyListsConfig::default(),
+            column_encryption: ColumnEncryptionConfig::default(),
+            outbound_proxy: OutboundProxyConfig::default(),
+            egress: EgressConfig::default(),
+            health: HealthConfig::default(),
             sso_redirect_allowlist: vec![],
         };
 
@@ -206,7 +223,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -221,6 +240,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "redis.example.com".to_string(),
@@ -299,6 +322,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -315,6 +339,7 @@ mod tests {
             url_preview: UrlPreviewConfig::default(),
             oidc: OidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: synapse_common::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: synapse_common::telemetry_config::PrometheusConfig::default(),
@@ -364,7 +389,9 @@ mod tests {
             allow_public_rooms_over_federation: true,
             auto_join_rooms: vec![],
             autocreate_auto_join_rooms: true,
+            auto_join_mxid_localpart: None,
             encryption_enabled_by_default_for_room_type: None,
+            reject_unencrypted_messages_in_encrypted_rooms: false,
             app_service_config_files: vec![],
             presence_enabled: true,
             ..Default::default()
@@ -393,6 +420,10 @@ mod tests {
             max_size: 20,
             min_idle: None,
             connection_timeout: 60,
+            schema_drift_policy: Default::default(),
+            migrations_dir: None,
+            event_partitioning: Default::default(),
+            state_compaction: Default::default(),
         };
 
         assert_eq!(config.host, "db.example.com");
@@ -481,7 +512,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -496,6 +529,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "localhost".to_string(),
@@ -574,6 +611,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -591,6 +629,7 @@ mod tests {
             oidc: OidcConfig::default(),
             builtin_oidc: BuiltinOidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: synapse_common::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: synapse_common::telemetry_config::PrometheusConfig::default(),
@@ -598,6 +637,15 @@ mod tests {
             experimental: ExperimentalConfig::default(),
             identity: IdentityConfig::default(),
             translate: TranslateConfig::default(),
+            content_filter: ContentFilterConfig::default(),
+            room_directory: RoomDirectoryConfig::default(),
+            room_creation: RoomCreationConfig::default(),
+            welcome: WelcomeConfig::default(),
+            policy_lists: PolicyListsConfig::default(),
+            column_encryption: ColumnEncryptionConfig::default(),
+            outbound_proxy: OutboundProxyConfig::default(),
+            egress: EgressConfig::default(),
+            health: HealthConfig::default(),
             sso_redirect_allowlist: vec![],
         };
 
@@ -59,6 +59,36 @@ impl HealthCheck for CacheHealthCheck {
     }
 }
 
+/// 联邦签名密钥健康检查。
+///
+/// 联邦启用时，服务器必须已加载当前签名密钥才能签发事件和响应 key 查询。
+pub struct FederationKeyHealthCheck {
+    key_rotation: synapse_federation::KeyRotationManager,
+}
+
+impl FederationKeyHealthCheck {
+    pub fn new(key_rotation: synapse_federation::KeyRotationManager) -> Self {
+        Self { key_rotation }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for FederationKeyHealthCheck {
+    async fn check(&self) -> CheckResult {
+        let start = std::time::Instant::now();
+        let (status, message) = match self.key_rotation.get_current_key().await {
+            Ok(Some(key)) => ("healthy", format!("Signing key {} loaded", key.key_id)),
+            Ok(None) => ("unhealthy", "No federation signing key loaded".to_string()),
+            Err(e) => ("unhealthy", format!("Signing key lookup failed: {e}")),
+        };
+        CheckResult { status: status.to_string(), message, duration_ms: start.elapsed().as_millis() as u64 }
+    }
+
+    fn name(&self) -> &str {
+        "federation_key"
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -4,7 +4,7 @@
 //! `synapse_federation::edu`. This module provides the dispatcher and handlers
 //! that depend on `FederationContext` and the service container.
 
-pub use synapse_federation::edu::{user_matches_origin, EduProcessResult, EduType, UnknownEduType};
+pub use synapse_federation::edu::{parse_receipt_edu, user_matches_origin, EduProcessResult, EduType, UnknownEduType};
 
 use crate::web::routes::context::FederationContext;
 use serde_json::Value;
@@ -279,7 +279,10 @@ async fn handle_direct_to_device_edu(
                         )
                         .await
                     {
-                        Ok(()) => result.processed += 1,
+                        Ok(()) => {
+                            result.processed += 1;
+                            ctx.event_notifier.notify_user(recipient_user_id);
+                        }
                         Err(e) => {
                             ::tracing::warn!(
                                 "Failed to persist m.direct_to_device EDU for {}:{} from {}: {}",
@@ -309,6 +312,42 @@ async fn handle_direct_to_device_edu(
     result
 }
 
+async fn handle_receipt_edu(ctx: &FederationContext, origin: &str, edu: &Value, remaining: usize) -> EduProcessResult {
+    let Some(content) = edu.get("content") else {
+        ::tracing::debug!("Dropping m.receipt EDU from {} without content", origin);
+        return EduProcessResult { dropped: 1, ..Default::default() };
+    };
+
+    let (rooms, dropped) = parse_receipt_edu(origin, content, remaining, current_timestamp_millis());
+    let mut result = EduProcessResult { dropped, ..Default::default() };
+
+    for (room_id, receipts) in rooms {
+        let count = receipts.len();
+        match ctx.room_service.messaging().receive_remote_receipts(&room_id, receipts).await {
+            Ok(stored) => {
+                result.processed += stored;
+                result.dropped += count - stored;
+            }
+            Err(e) => {
+                ::tracing::warn!("Failed to persist m.receipt EDU for {} from {}: {}", room_id, origin, e);
+                result.errored += count;
+            }
+        }
+    }
+
+    if result.processed > 0 {
+        increment_counter_by(ctx, "federation_inbound_receipt_processed_total", result.processed as u64);
+    }
+    if result.dropped > 0 {
+        increment_counter_by(ctx, "federation_inbound_receipt_dropped_total", result.dropped as u64);
+    }
+    if result.errored > 0 {
+        increment_counter_by(ctx, "federation_inbound_receipt_error_total", result.errored as u64);
+    }
+
+    result
+}
+
 // ---------------------------------------------------------------------------
 // EduDispatcher — routes inbound EDUs to the correct handler
 // ---------------------------------------------------------------------------
@@ -330,6 +369,7 @@ impl EduDispatcher {
             EduType::Typing => handle_typing_edu(ctx, origin, edu, remaining).await,
             EduType::DeviceListUpdate => handle_device_list_update_edu(ctx, origin, edu, remaining).await,
             EduType::DirectToDevice => handle_direct_to_device_edu(ctx, origin, edu, remaining).await,
+            EduType::Receipt => handle_receipt_edu(ctx, origin, edu, remaining).await,
         };
 
         Some(result)
@@ -19,6 +19,9 @@ pub mod worker;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_config;
 
+#[cfg(feature = "test-utils")]
+pub mod test_server;
+
 // Explicit root re-exports (replacing the former per-module wildcard globs).
 // Only the items consumed through the crate root (`synapse_rust::Foo`) are
 // re-exported here; everything else is reached path-qualified
@@ -28,7 +31,7 @@ pub use e2ee::backup::KeyBackupService;
 pub use e2ee::device_keys::DeviceKeyService;
 pub use e2ee::megolm::{EncryptedEvent, MegolmSession};
 pub use e2ee::signature::{EventSignature, SignatureService};
-pub use server::SynapseServer;
+pub use server::{StartupOptions, SynapseServer};
 pub use storage::presence::PresenceStorage;
 pub use synapse_common::{
     impl_api_error, map_bad_request, map_forbidden, map_internal, map_not_found, map_unauthorized,
@@ -1,4 +1,5 @@
 use synapse_rust::common::config::Config;
+use synapse_rust::StartupOptions;
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
@@ -17,6 +18,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         eprintln!("Backtrace: {:?}", std::backtrace::Backtrace::capture());
     }));
 
+    let options = StartupOptions::from_args(std::env::args().skip(1));
+
     // 1. Load configuration
     let config = match Config::load() {
         Ok(c) => c,
@@ -34,7 +37,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     tracing::info!("Server name: {}", config.server.name);
     tracing::info!("Listening on: {}:{}", config.server.host, config.server.port);
 
-    let server = synapse_rust::SynapseServer::new(config).await?;
+    let server = synapse_rust::SynapseServer::new(config, options).await?;
 
     server.run().await?;
 
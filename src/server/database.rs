@@ -3,9 +3,11 @@ use sqlx::PgPool;
 use std::sync::Arc;
 use std::time::Duration;
 
-use crate::common::config::Config;
+use crate::common::config::{Config, SchemaDriftPolicy};
 use synapse_services::database_initializer::DatabaseInitService;
 use synapse_storage::schema_health_check::run_schema_health_check;
+use synapse_storage::schema_validator::SchemaValidator;
+use synapse_storage::server_identity::{verify_server_name, ServerNameCheck};
 
 const DEFAULT_MAX_LIFETIME: Duration = Duration::from_secs(1800);
 const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(600);
@@ -114,6 +116,8 @@ pub async fn build_database_pool(config: &Config) -> Result<PgPool, Box<dyn std:
                 .into());
             }
         }
+
+        check_schema_drift(&pool, config).await?;
     }
 
     // Drop the Arc wrapper and return the inner PgPool.
@@ -128,3 +132,83 @@ pub async fn build_database_pool(config: &Config) -> Result<PgPool, Box<dyn std:
         }
     }
 }
+
+/// 将实时 Schema 与 `database.migrations_dir` 中迁移声明的表、列和索引比对，
+/// 按 `database.schema_drift_policy` 决定告警或拒绝启动。
+async fn check_schema_drift(pool: &Arc<PgPool>, config: &Config) -> Result<(), Box<dyn std::error::Error>> {
+    let migrations_dir = config.database.migrations_dir();
+    let report = match SchemaValidator::new(pool.clone()).detect_drift(std::path::Path::new(migrations_dir)).await {
+        Ok(report) => report,
+        Err(e) => {
+            ::tracing::warn!("Schema drift detection skipped: cannot read migrations from {migrations_dir}: {e}");
+            return Ok(());
+        }
+    };
+
+    if !report.has_drift() {
+        ::tracing::info!(
+            "✅ Database schema matches {} migration(s) ({} tables, {} indexes)",
+            report.migration_files,
+            report.expected_tables,
+            report.expected_indexes
+        );
+        return Ok(());
+    }
+
+    ::tracing::warn!("Database schema drift detected against {migrations_dir}");
+    if !report.missing_tables.is_empty() {
+        ::tracing::warn!("  Missing tables: {:?}", report.missing_tables);
+    }
+    if !report.missing_columns.is_empty() {
+        ::tracing::warn!("  Missing columns: {:?}", report.missing_columns);
+    }
+    if !report.missing_indexes.is_empty() {
+        ::tracing::warn!("  Missing indexes: {:?}", report.missing_indexes);
+    }
+
+    if config.database.schema_drift_policy == SchemaDriftPolicy::Refuse {
+        return Err(format!(
+            "Database schema drift detected ({} missing tables, {} missing columns, {} missing indexes) and \
+             database.schema_drift_policy is \"refuse\". Run `docker/db_migrate.sh migrate` against the configured \
+             database, or set the policy to \"warn\".",
+            report.missing_tables.len(),
+            report.missing_columns.len(),
+            report.missing_indexes.len()
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Records `server.name` on first startup and refuses to start if it differs
+/// from the recorded one, unless the operator passed `--force-rename-danger`.
+pub(super) async fn check_server_name(
+    pool: &PgPool,
+    config: &Config,
+    force_rename: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let configured = config.server.name.as_str();
+    match verify_server_name(pool, configured, force_rename).await? {
+        ServerNameCheck::Recorded => {
+            ::tracing::info!(server_name = configured, "Recorded server_name for this database");
+        }
+        ServerNameCheck::Unchanged => {}
+        ServerNameCheck::Renamed { previous } => {
+            ::tracing::warn!(
+                previous = %previous,
+                server_name = configured,
+                "server_name changed with --force-rename-danger; existing user, room and event IDs still carry \
+                 the previous name"
+            );
+        }
+        ServerNameCheck::Mismatch { stored } => {
+            return Err(format!(
+                "server.name is \"{configured}\" but this database belongs to \"{stored}\". Changing the server \
+                 name corrupts every stored user, room and event ID. Restore the original server.name, or start \
+                 once with --force-rename-danger if you really mean to rename."
+            )
+            .into());
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,255 @@
+//! HTTP/1 listener loop with the connection limits of `server.http`.
+//!
+//! `axum::serve` accepts connections without bound and never times out a
+//! client trickling in its request headers or refusing to read its response,
+//! so each listener runs its own accept loop: hyper's header read timeout
+//! bounds both the request head and idle keep-alive connections, a write
+//! timeout on the socket drops clients that stop reading, and a semaphore
+//! caps the open connections.
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::Router;
+use hyper::body::Incoming;
+use hyper::server::conn::http1;
+use hyper::service::service_fn;
+use hyper::Request;
+use hyper_util::rt::{TokioIo, TokioTimer};
+use std::future::Future;
+use std::io;
+use std::pin::{pin, Pin};
+use std::sync::Arc;
+use std::task::{Context, Poll};
+use std::time::Duration;
+use synapse_common::config::HttpServerConfig;
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Semaphore};
+use tokio::time::Sleep;
+use tower::Service;
+
+/// Pause after an accept error other than a reset connection, e.g. running
+/// out of file descriptors.
+const ACCEPT_ERROR_BACKOFF: Duration = Duration::from_secs(1);
+
+fn timeout(secs: u64) -> Option<Duration> {
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Serves `router` on `listener` until `shutdown` resolves, then waits for
+/// the open connections to finish their in-flight requests.
+pub(crate) async fn serve(
+    listener: TcpListener,
+    router: Router,
+    config: &HttpServerConfig,
+    shutdown: impl Future<Output = ()>,
+) {
+    let max_connections = if config.max_connections == 0 {
+        Semaphore::MAX_PERMITS
+    } else {
+        config.max_connections.min(Semaphore::MAX_PERMITS)
+    };
+    let connections = Arc::new(Semaphore::new(max_connections));
+    let mut builder = http1::Builder::new();
+    builder
+        .timer(TokioTimer::new())
+        .keep_alive(config.keep_alive)
+        .header_read_timeout(timeout(config.header_read_timeout_secs));
+    let write_timeout = timeout(config.write_timeout_secs);
+
+    // Connections watch `signal_rx` for the shutdown and hold `close_rx` until
+    // they are done, so `close_tx.closed()` resolves once all have closed.
+    let (signal_tx, signal_rx) = watch::channel(());
+    let (close_tx, close_rx) = watch::channel(());
+    let mut shutdown = pin!(shutdown);
+
+    loop {
+        let permit = match connections.clone().try_acquire_owned() {
+            Ok(permit) => permit,
+            Err(_) => {
+                ::tracing::warn!(max_connections, "HTTP listener at its connection limit; pausing accepts");
+                tokio::select! {
+                    permit = connections.clone().acquire_owned() => match permit {
+                        Ok(permit) => permit,
+                        Err(_) => break,
+                    },
+                    () = &mut shutdown => break,
+                }
+            }
+        };
+
+        let (stream, remote_addr) = tokio::select! {
+            accepted = listener.accept() => match accepted {
+                Ok(accepted) => accepted,
+                Err(e) => {
+                    if !matches!(
+                        e.kind(),
+                        io::ErrorKind::ConnectionAborted | io::ErrorKind::ConnectionReset | io::ErrorKind::ConnectionRefused
+                    ) {
+                        ::tracing::error!(error = %e, "Failed to accept HTTP connection");
+                        tokio::time::sleep(ACCEPT_ERROR_BACKOFF).await;
+                    }
+                    continue;
+                }
+            },
+            () = &mut shutdown => break,
+        };
+
+        let router = router.clone();
+        let service = service_fn(move |mut request: Request<Incoming>| {
+            request.extensions_mut().insert(ConnectInfo(remote_addr));
+            router.clone().call(request.map(Body::new))
+        });
+        let io = TokioIo::new(WriteTimeout::new(stream, write_timeout));
+        let builder = builder.clone();
+        let mut signal_rx = signal_rx.clone();
+        let close_rx = close_rx.clone();
+
+        tokio::spawn(async move {
+            let mut conn = pin!(builder.serve_connection(io, service).with_upgrades());
+            loop {
+                tokio::select! {
+                    result = conn.as_mut() => {
+                        if let Err(e) = result {
+                            ::tracing::debug!(remote_addr = %remote_addr, error = %e, "HTTP connection closed with error");
+                        }
+                        break;
+                    }
+                    _ = signal_rx.changed() => conn.as_mut().graceful_shutdown(),
+                }
+            }
+            drop(permit);
+            drop(close_rx);
+        });
+    }
+
+    drop(listener);
+    drop(close_rx);
+    signal_tx.send_replace(());
+    close_tx.closed().await;
+}
+
+/// Socket whose writes fail with `TimedOut` once they make no progress for
+/// the configured duration.
+struct WriteTimeout {
+    stream: TcpStream,
+    timeout: Option<Duration>,
+    deadline: Option<Pin<Box<Sleep>>>,
+}
+
+impl WriteTimeout {
+    fn new(stream: TcpStream, timeout: Option<Duration>) -> Self {
+        Self { stream, timeout, deadline: None }
+    }
+
+    fn check<T>(&mut self, cx: &mut Context<'_>, poll: Poll<io::Result<T>>) -> Poll<io::Result<T>> {
+        if poll.is_ready() {
+            self.deadline = None;
+            return poll;
+        }
+        let Some(timeout) = self.timeout else {
+            return Poll::Pending;
+        };
+        let deadline = self.deadline.get_or_insert_with(|| Box::pin(tokio::time::sleep(timeout)));
+        match deadline.as_mut().poll(cx) {
+            Poll::Ready(()) => Poll::Ready(Err(io::Error::new(io::ErrorKind::TimedOut, "HTTP write timed out"))),
+            Poll::Pending => Poll::Pending,
+        }
+    }
+}
+
+impl AsyncRead for WriteTimeout {
+    fn poll_read(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut ReadBuf<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.stream).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for WriteTimeout {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.stream).poll_write(cx, buf);
+        self.check(cx, poll)
+    }
+
+    fn poll_write_vectored(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        bufs: &[io::IoSlice<'_>],
+    ) -> Poll<io::Result<usize>> {
+        let poll = Pin::new(&mut self.stream).poll_write_vectored(cx, bufs);
+        self.check(cx, poll)
+    }
+
+    fn is_write_vectored(&self) -> bool {
+        self.stream.is_write_vectored()
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.stream).poll_flush(cx);
+        self.check(cx, poll)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        let poll = Pin::new(&mut self.stream).poll_shutdown(cx);
+        self.check(cx, poll)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::routing::get;
+    use std::net::SocketAddr;
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+    async fn spawn_server(config: HttpServerConfig) -> (SocketAddr, watch::Sender<()>) {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap_or_else(|e| panic!("bind: {e}"));
+        let addr = listener.local_addr().unwrap_or_else(|e| panic!("local_addr: {e}"));
+        let router = Router::new().route("/", get(|| async { "ok" }));
+        let (shutdown_tx, mut shutdown_rx) = watch::channel(());
+        tokio::spawn(async move {
+            serve(listener, router, &config, async move {
+                shutdown_rx.changed().await.ok();
+            })
+            .await;
+        });
+        (addr, shutdown_tx)
+    }
+
+    #[tokio::test]
+    async fn slow_request_headers_are_dropped_after_the_read_timeout() {
+        let config = HttpServerConfig { header_read_timeout_secs: 1, ..HttpServerConfig::default() };
+        let (addr, _shutdown) = spawn_server(config).await;
+
+        let mut client = TcpStream::connect(addr).await.unwrap_or_else(|e| panic!("connect: {e}"));
+        client.write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\n").await.unwrap_or_else(|e| panic!("write: {e}"));
+        let mut response = Vec::new();
+        let read = tokio::time::timeout(Duration::from_secs(5), client.read_to_end(&mut response)).await;
+        assert!(read.is_ok(), "server should close a connection that never finishes its headers");
+        assert!(!String::from_utf8_lossy(&response).contains("200 OK"));
+    }
+
+    #[tokio::test]
+    async fn connections_over_the_limit_wait_for_a_free_slot() {
+        let config = HttpServerConfig { max_connections: 1, ..HttpServerConfig::default() };
+        let (addr, _shutdown) = spawn_server(config).await;
+
+        let first = TcpStream::connect(addr).await.unwrap_or_else(|e| panic!("connect: {e}"));
+        // Let the server accept the first connection before the second arrives.
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let mut second = TcpStream::connect(addr).await.unwrap_or_else(|e| panic!("connect: {e}"));
+        second
+            .write_all(b"GET / HTTP/1.1\r\nHost: localhost\r\nConnection: close\r\n\r\n")
+            .await
+            .unwrap_or_else(|e| panic!("write: {e}"));
+        let mut response = Vec::new();
+        let blocked = tokio::time::timeout(Duration::from_millis(300), second.read_to_end(&mut response)).await;
+        assert!(blocked.is_err(), "second connection should not be served while the first is open");
+
+        drop(first);
+        tokio::time::timeout(Duration::from_secs(5), second.read_to_end(&mut response))
+            .await
+            .unwrap_or_else(|e| panic!("second connection should be served once a slot frees: {e}"))
+            .unwrap_or_else(|e| panic!("read: {e}"));
+        assert!(String::from_utf8_lossy(&response).starts_with("HTTP/1.1 200 OK"));
+    }
+}
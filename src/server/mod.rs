@@ -21,6 +21,7 @@ use tokio::signal;
 use synapse_storage::*;
 
 mod database;
+mod listener;
 mod router;
 mod services;
 pub mod telemetry;
@@ -32,6 +33,26 @@ const MIN_DEHYDRATED_DEVICE_CLEANUP_INTERVAL_SECS: u64 = 300;
 /// Interval (seconds) between background maintenance task ticks.
 const BACKGROUND_TASK_INTERVAL_SECS: u64 = 60;
 
+/// Time each maintenance tick may spend on batched online migrations, so a
+/// large backfill runs across many ticks instead of holding one long lock.
+const ONLINE_MIGRATION_BUDGET: Duration = Duration::from_secs(20);
+
+/// Maintenance ticks between `events` partition maintenance passes (hourly).
+const EVENT_PARTITION_MAINTENANCE_TICKS: u64 = 60;
+
+/// Maintenance ticks between passes reclaiming purged events and deleted media.
+const TOMBSTONE_VACUUM_TICKS: u64 = 5;
+
+/// Rows deleted per vacuum transaction. Each deleted event cascades to its
+/// edges and state rows, so batches stay small to keep locks short.
+const TOMBSTONE_VACUUM_BATCH: i64 = 500;
+
+/// Time one vacuum pass may spend before yielding to the next tick.
+const TOMBSTONE_VACUUM_BUDGET: Duration = Duration::from_secs(10);
+
+/// Pause between vacuum batches, leaving room for foreground writes.
+const TOMBSTONE_VACUUM_PAUSE: Duration = Duration::from_millis(100);
+
 /// Minimum interval (seconds) between background task executions to prevent
 /// tight loops when a task completes quickly.
 const MIN_BACKGROUND_INTERVAL_SECS: u64 = 10;
@@ -52,6 +73,12 @@ const MEGOLM_CLEANUP_INTERVAL_SECS: u64 = 6 * 3600;
 /// Interval (seconds) between event pruning runs.
 const PRUNING_INTERVAL_SECS: u64 = 86400;
 
+/// Interval (seconds) between sweeps of abandoned device verification flows.
+const VERIFICATION_CLEANUP_INTERVAL_SECS: u64 = 60;
+
+/// Interval (milliseconds) between checks for due MSC4140 delayed events.
+const DELAYED_EVENT_POLL_INTERVAL_MS: u64 = 1000;
+
 /// Helper macro for pruning background tasks.
 /// Each pruning operation follows the same pattern: call an async function,
 /// log success with a count, or log a warning on failure.
@@ -89,6 +116,8 @@ fn global_maintenance_tasks_enabled() -> bool {
 struct PrometheusMetricsState {
     metrics: Arc<crate::common::metrics::MetricsCollector>,
     app_service_manager: Arc<synapse_services::application_service::ApplicationServiceManager>,
+    cache: Arc<synapse_cache::CacheManager>,
+    forward_extremities: Arc<synapse_services::forward_extremity_service::ForwardExtremityService>,
 }
 
 fn dehydrated_device_cleanup_interval(configured_interval_secs: u64) -> Duration {
@@ -100,6 +129,22 @@ fn create_rate_limit_manager(config_path: &std::path::Path) -> Arc<RateLimitConf
     Arc::new(RateLimitConfigManager::new(default_config, config_path.to_path_buf()))
 }
 
+/// Command-line switches that change how the server starts.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StartupOptions {
+    /// Accept a `server.name` that differs from the one recorded in the
+    /// database and record the new one instead of refusing to start.
+    pub force_rename_danger: bool,
+}
+
+impl StartupOptions {
+    pub const FORCE_RENAME_DANGER_FLAG: &'static str = "--force-rename-danger";
+
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Self {
+        Self { force_rename_danger: args.into_iter().any(|arg| arg == Self::FORCE_RENAME_DANGER_FLAG) }
+    }
+}
+
 pub struct SynapseServer {
     app_state: Arc<AppState>,
     router: Router,
@@ -113,7 +158,7 @@ pub struct SynapseServer {
 }
 
 impl SynapseServer {
-    pub async fn new(config: Config) -> Result<Self, Box<dyn std::error::Error>> {
+    pub async fn new(config: Config, options: StartupOptions) -> Result<Self, Box<dyn std::error::Error>> {
         // Make CORS origins from homeserver.yaml visible to the security check
         // BEFORE we run validation, so operators don't have to also set
         // ALLOWED_ORIGINS env var when they have already configured the file.
@@ -126,6 +171,11 @@ impl SynapseServer {
             .unwrap_or(false);
         set_trust_forwarded_headers(trust_forwarded);
 
+        synapse_common::error::set_expose_internal_errors(config.server.expose_internal_errors);
+        if config.server.expose_internal_errors {
+            ::tracing::warn!("server.expose_internal_errors is enabled; 500 responses include internal error details");
+        }
+
         let cors_report = check_cors_security();
         log_cors_security_report(&cors_report);
 
@@ -148,12 +198,29 @@ impl SynapseServer {
         };
         let pool = Arc::new(pool);
 
+        database::check_server_name(&pool, &config, options.force_rename_danger).await?;
+
         // Validate TOKEN_HASH_SECRET before accepting any requests.
         // In production, a missing or weak secret is a fatal startup error.
         if let Err(e) = synapse_common::crypto::validate_token_hash_secret() {
             return Err(format!("FATAL: {e}").into());
         }
 
+        // Install the column cipher before any storage reads or writes a
+        // sealed column. A bad key configuration is fatal.
+        if let Err(e) = synapse_common::column_encryption::initialize_global(&config.column_encryption) {
+            return Err(format!("FATAL: {e}").into());
+        }
+
+        // Services build their HTTP clients during construction, so the
+        // outbound proxy has to be in place first.
+        if let Err(e) = synapse_common::outbound_http::initialize_global(&config.outbound_proxy) {
+            return Err(format!("FATAL: {e}").into());
+        }
+        if let Err(e) = synapse_common::egress::initialize_global(&config.egress) {
+            return Err(format!("FATAL: {e}").into());
+        }
+
         let (services, cache, redis_pool_option) = services::build_service_container(&pool, &config).await?;
 
         // Startup topology validation — ensures worker configuration is consistent before proceeding
@@ -179,6 +246,23 @@ impl SynapseServer {
                 "Imported application service configs from app_service_config_files"
             );
         }
+
+        // A server without an admin gets one from `admin_bootstrap`, or a
+        // one-time token for `POST /_synapse/admin/v1/bootstrap`.
+        match services.admin.user.admin_bootstrap_service.run().await {
+            Ok(synapse_services::admin::BootstrapOutcome::Created { user_id }) => {
+                ::tracing::info!(%user_id, "Created bootstrap admin account");
+            }
+            Ok(synapse_services::admin::BootstrapOutcome::SetupToken(token)) => {
+                ::tracing::warn!(
+                    "No admin account exists. Create one with POST /_synapse/admin/v1/bootstrap \
+                     and this one-time setup token: {token}"
+                );
+            }
+            Ok(_) => {}
+            Err(e) => ::tracing::error!(error = %e, "Admin bootstrap failed"),
+        }
+
         let app_state = Arc::new(AppState::new(services, cache));
 
         // Create the graceful-shutdown broadcast channel early so it can be
@@ -404,6 +488,11 @@ impl SynapseServer {
         let mut shutdown_rx5 = shutdown_tx.subscribe();
         let mut shutdown_rx6 = shutdown_tx.subscribe();
         let mut shutdown_rx7 = shutdown_tx.subscribe();
+        let mut shutdown_rx8 = shutdown_tx.subscribe();
+        let mut shutdown_rx9 = shutdown_tx.subscribe();
+        let mut shutdown_rx10 = shutdown_tx.subscribe();
+        let mut shutdown_rx11 = shutdown_tx.subscribe();
+        let mut shutdown_rx12 = shutdown_tx.subscribe();
         let mut shutdown_rx_drain_gate = shutdown_tx.subscribe();
 
         if run_global_maintenance {
@@ -413,8 +502,18 @@ impl SynapseServer {
             let event_broadcaster = self.app_state.services.core.event_broadcaster.clone();
             let remote_media_lifetime = self.app_state.services.core.config.server.remote_media_lifetime;
             let local_media_lifetime = self.app_state.services.core.config.server.local_media_lifetime;
+            let event_partitioning = self.app_state.services.core.config.database.event_partitioning.clone();
+            let partition_event_storage = synapse_storage::event::EventStorage::new(
+                self.app_state.services.account.user_storage.pool(),
+                self.app_state.services.core.config.server.name.clone(),
+            );
+            let tombstone_media_storage = synapse_storage::admin_media::AdminMediaStorage::new(
+                self.app_state.services.account.user_storage.pool(),
+            );
             let mut media_cleanup_counter: u64 = 0;
             let mut federation_retry_counter: u64 = 0;
+            let mut partition_maintenance_counter: u64 = 0;
+            let mut tombstone_vacuum_counter: u64 = 0;
             tokio::spawn(async move {
                 let mut interval =
                     tokio::time::interval(tokio::time::Duration::from_secs(BACKGROUND_TASK_INTERVAL_SECS));
@@ -427,9 +526,38 @@ impl SynapseServer {
                             if let Err(e) = bg_service.cleanup_expired_locks().await {
                                 ::tracing::warn!("Background lock cleanup failed: {}", e);
                             }
+                            if let Err(e) = bg_service.run_online_migrations(ONLINE_MIGRATION_BUDGET).await {
+                                ::tracing::warn!("Online migration run failed: {}", e);
+                            }
                             if let Err(e) = retention_service.run_scheduled_cleanups().await {
                                 ::tracing::warn!("Retention cleanup failed: {}", e);
                             }
+                            if event_partitioning.enabled
+                                && partition_maintenance_counter.is_multiple_of(EVENT_PARTITION_MAINTENANCE_TICKS)
+                            {
+                                match partition_event_storage
+                                    .maintain_event_partitions(
+                                        current_timestamp_millis(),
+                                        event_partitioning.range_interval_days,
+                                        event_partitioning.premake_partitions,
+                                        event_partitioning.drop_empty_partitions,
+                                    )
+                                    .await
+                                {
+                                    Ok(outcome) if outcome.created.is_empty() && outcome.dropped.is_empty() => {}
+                                    Ok(outcome) => ::tracing::info!(
+                                        created = ?outcome.created,
+                                        dropped = ?outcome.dropped,
+                                        "Event partition maintenance"
+                                    ),
+                                    Err(e) => ::tracing::warn!("Event partition maintenance failed: {}", e),
+                                }
+                            }
+                            partition_maintenance_counter += 1;
+                            if tombstone_vacuum_counter.is_multiple_of(TOMBSTONE_VACUUM_TICKS) {
+                                vacuum_tombstones(&partition_event_storage, &tombstone_media_storage).await;
+                            }
+                            tombstone_vacuum_counter += 1;
                             media_cleanup_counter += 1;
                             if media_cleanup_counter >= 60 {
                                 media_cleanup_counter = 0;
@@ -456,6 +584,13 @@ impl SynapseServer {
                                         ::tracing::info!("Federation retry: {} transactions retried", retried);
                                     }
                                 }
+                                match event_broadcaster.run_catch_up().await {
+                                    Ok(0) => {}
+                                    Ok(caught_up) => {
+                                        ::tracing::info!("Federation catch-up: {} destinations caught up", caught_up);
+                                    }
+                                    Err(e) => ::tracing::warn!("Federation catch-up failed: {}", e),
+                                }
                             }
                         }
                         _ = shutdown_rx4.recv() => {
@@ -595,24 +730,200 @@ impl SynapseServer {
             });
         }
 
+        let state_compaction = self.app_state.services.core.config.database.state_compaction.clone();
+        if run_global_maintenance && state_compaction.enabled {
+            // Compact archived state groups and log the rows saved.
+            let compaction_pool = self.app_state.services.account.user_storage.pool().clone();
+            tokio::spawn(async move {
+                let mut interval_timer =
+                    tokio::time::interval(Duration::from_secs(state_compaction.interval_secs.max(60)));
+                interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                interval_timer.tick().await; // skip immediate tick after startup
+
+                loop {
+                    tokio::select! {
+                        _ = interval_timer.tick() => {
+                            let min_age_ms = i64::from(state_compaction.min_age_days) * 24 * 60 * 60 * 1000;
+                            let params = synapse_storage::state_compaction::StateCompactionParams {
+                                older_than_ts: current_timestamp_millis() - min_age_ms,
+                                archive_snapshot_interval: state_compaction.archive_snapshot_interval,
+                                max_rooms: state_compaction.rooms_per_run.max(1),
+                            };
+                            match synapse_storage::state_compaction::compact_state_groups(&compaction_pool, &params).await {
+                                Ok(stats) if stats.rooms == 0 => {}
+                                Ok(stats) => ::tracing::info!(
+                                    rooms = stats.rooms,
+                                    groups_merged = stats.groups_merged,
+                                    snapshots_created = stats.snapshots_created,
+                                    snapshots_thinned = stats.snapshots_thinned,
+                                    rows_before = stats.rows_before,
+                                    rows_after = stats.rows_after,
+                                    rows_saved = stats.rows_saved(),
+                                    "State group compaction pass finished"
+                                ),
+                                Err(e) => ::tracing::warn!(error = %e, "State group compaction failed"),
+                            }
+                        }
+                        _ = shutdown_rx10.recv() => {
+                            ::tracing::info!("State group compaction task shutting down");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        let forward_extremities_config = self.app_state.services.core.config.federation.forward_extremities.clone();
+        let forward_extremity_service =
+            Arc::new(synapse_services::forward_extremity_service::ForwardExtremityService::new(
+                self.app_state.services.rooms.room_service.messaging().clone(),
+                forward_extremities_config.clone(),
+                &self.app_state.services.core.metrics,
+            ));
+        if run_global_maintenance && forward_extremities_config.enabled {
+            // Merge forward extremities of rooms that accumulated too many
+            // by sending dummy events into them.
+            let forward_extremity_service = forward_extremity_service.clone();
+            tokio::spawn(async move {
+                let mut interval_timer =
+                    tokio::time::interval(Duration::from_secs(forward_extremities_config.interval_secs.max(30)));
+                interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                interval_timer.tick().await; // skip immediate tick after startup
+
+                loop {
+                    tokio::select! {
+                        _ = interval_timer.tick() => {
+                            match forward_extremity_service.run_cycle().await {
+                                Ok(summary) if summary.rooms_over_limit == 0 => {}
+                                Ok(summary) => ::tracing::info!(
+                                    rooms_over_limit = summary.rooms_over_limit,
+                                    dummy_events_sent = summary.dummy_events_sent,
+                                    rooms_without_local_member = summary.rooms_without_local_member,
+                                    failed = summary.failed,
+                                    "Forward extremity maintenance pass finished"
+                                ),
+                                Err(e) => ::tracing::warn!(error = %e, "Forward extremity maintenance failed"),
+                            }
+                        }
+                        _ = shutdown_rx11.recv() => {
+                            ::tracing::info!("Forward extremity maintenance task shutting down");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        let delayed_event_service = self.app_state.services.rooms.delayed_event_service.clone();
+        if run_global_maintenance && delayed_event_service.is_enabled() {
+            // Send MSC4140 delayed events once their delay has run out.
+            tokio::spawn(async move {
+                let mut interval_timer = tokio::time::interval(Duration::from_millis(DELAYED_EVENT_POLL_INTERVAL_MS));
+                interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+                loop {
+                    tokio::select! {
+                        _ = interval_timer.tick() => {
+                            match delayed_event_service.send_due_events().await {
+                                Ok(0) => {}
+                                Ok(sent) => ::tracing::debug!(sent, "Sent due delayed events"),
+                                Err(e) => ::tracing::warn!(error = %e, "Sending due delayed events failed"),
+                            }
+                        }
+                        _ = shutdown_rx12.recv() => {
+                            ::tracing::info!("Delayed event task shutting down");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        {
+            // Verification flows are tracked in memory by whichever process
+            // relayed them, so every process sweeps its own.
+            let verification_tracker = self.app_state.services.e2ee.to_device_service.verification_tracker().clone();
+            tokio::spawn(async move {
+                let mut interval_timer = tokio::time::interval(Duration::from_secs(VERIFICATION_CLEANUP_INTERVAL_SECS));
+                interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+                interval_timer.tick().await; // skip immediate tick after startup
+
+                loop {
+                    tokio::select! {
+                        _ = interval_timer.tick() => {
+                            let expired = verification_tracker.expire_stale();
+                            if expired > 0 {
+                                ::tracing::debug!(expired, "Expired stale device verification flows");
+                            }
+                        }
+                        _ = shutdown_rx9.recv() => {
+                            ::tracing::info!("Device verification cleanup task shutting down");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        let column_encryption = &self.app_state.services.core.config.column_encryption;
+        if run_global_maintenance && column_encryption.enabled {
+            // Re-seal sensitive columns that are still in plaintext or under
+            // a rotated-out key. Runs shortly after startup so a rotation
+            // takes effect without waiting a full interval.
+            let reencrypt_pool = self.app_state.services.account.user_storage.pool().clone();
+            let reencrypt_interval = Duration::from_secs(column_encryption.reencrypt_interval_secs.max(60));
+            let batch_size = i64::from(column_encryption.reencrypt_batch_size.max(1));
+            tokio::spawn(async move {
+                let mut interval_timer = tokio::time::interval(reencrypt_interval);
+                interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+
+                loop {
+                    tokio::select! {
+                        _ = interval_timer.tick() => {
+                            let cipher = synapse_common::column_encryption::global();
+                            for column in synapse_storage::column_encryption::ENCRYPTED_COLUMNS {
+                                match synapse_storage::column_encryption::reencrypt_column(&reencrypt_pool, column, cipher, batch_size).await {
+                                    Ok(stats) if stats.resealed == 0 && stats.failed == 0 => {}
+                                    Ok(stats) => {
+                                        ::tracing::info!(
+                                            column = column.name,
+                                            resealed = stats.resealed,
+                                            failed = stats.failed,
+                                            "Column re-encryption pass finished"
+                                        );
+                                    }
+                                    Err(e) => {
+                                        ::tracing::warn!(column = column.name, error = %e, "Column re-encryption failed");
+                                    }
+                                }
+                            }
+                        }
+                        _ = shutdown_rx8.recv() => {
+                            ::tracing::info!("Column re-encryption task shutting down");
+                            break;
+                        }
+                    }
+                }
+            });
+        }
+
+        let http_config = self.app_state.services.core.config.server.http.clone();
+        let client_http_config = http_config.clone();
         tokio::spawn(async move {
             let _ = shutdown_tx;
-            axum::serve(client_listener, router.into_make_service_with_connect_info::<SocketAddr>())
-                .with_graceful_shutdown(async move {
-                    shutdown_rx1.recv().await.ok();
-                })
-                .await
-                .ok();
+            listener::serve(client_listener, router, &client_http_config, async move {
+                shutdown_rx1.recv().await.ok();
+            })
+            .await;
             let _ = client_tx.send(());
         });
 
+        let fed_http_config = http_config.clone();
         tokio::spawn(async move {
-            axum::serve(federation_listener, fed_router.into_make_service_with_connect_info::<SocketAddr>())
-                .with_graceful_shutdown(async move {
-                    shutdown_rx2.recv().await.ok();
-                })
-                .await
-                .ok();
+            listener::serve(federation_listener, fed_router, &fed_http_config, async move {
+                shutdown_rx2.recv().await.ok();
+            })
+            .await;
             let _ = fed_tx.send(());
         });
 
@@ -620,18 +931,18 @@ impl SynapseServer {
             let metrics_state = PrometheusMetricsState {
                 metrics: self.app_state.services.core.metrics.clone(),
                 app_service_manager: self.app_state.services.admin.modules.app_service_manager.clone(),
+                cache: self.app_state.services.core.cache.clone(),
+                forward_extremities: forward_extremity_service.clone(),
             };
             let prometheus_path = prometheus_config.path.clone();
             let prometheus_router =
                 Router::new().route(&prometheus_path, get(render_prometheus_metrics)).with_state(metrics_state);
 
             tokio::spawn(async move {
-                axum::serve(prometheus_listener, prometheus_router.into_make_service())
-                    .with_graceful_shutdown(async move {
-                        shutdown_rx3.recv().await.ok();
-                    })
-                    .await
-                    .ok();
+                listener::serve(prometheus_listener, prometheus_router, &http_config, async move {
+                    shutdown_rx3.recv().await.ok();
+                })
+                .await;
                 let _ = prom_tx.send(());
             });
         } else {
@@ -749,10 +1060,61 @@ impl SynapseServer {
     }
 }
 
+/// Delete tombstoned events and media in [`TOMBSTONE_VACUUM_BATCH`]-row
+/// transactions until nothing is left or [`TOMBSTONE_VACUUM_BUDGET`] is spent.
+async fn vacuum_tombstones(
+    event_storage: &synapse_storage::event::EventStorage,
+    media_storage: &synapse_storage::admin_media::AdminMediaStorage,
+) {
+    let deadline = Instant::now() + TOMBSTONE_VACUUM_BUDGET;
+
+    let mut events_removed = 0u64;
+    loop {
+        match event_storage.vacuum_purged_events(TOMBSTONE_VACUUM_BATCH).await {
+            Ok(removed) => {
+                events_removed += removed;
+                if removed < TOMBSTONE_VACUUM_BATCH as u64 || Instant::now() >= deadline {
+                    break;
+                }
+            }
+            Err(e) => {
+                ::tracing::warn!("Purged event vacuum failed: {}", e);
+                break;
+            }
+        }
+        tokio::time::sleep(TOMBSTONE_VACUUM_PAUSE).await;
+    }
+
+    let mut media_removed = 0u64;
+    while Instant::now() < deadline {
+        match media_storage.vacuum_deleted_media(TOMBSTONE_VACUUM_BATCH).await {
+            Ok(removed) => {
+                media_removed += removed;
+                if removed < TOMBSTONE_VACUUM_BATCH as u64 {
+                    break;
+                }
+            }
+            Err(e) => {
+                ::tracing::warn!("Deleted media vacuum failed: {}", e);
+                break;
+            }
+        }
+        tokio::time::sleep(TOMBSTONE_VACUUM_PAUSE).await;
+    }
+
+    if events_removed > 0 || media_removed > 0 {
+        ::tracing::info!(events = events_removed, media = media_removed, "Reclaimed tombstoned rows");
+    }
+}
+
 async fn render_prometheus_metrics(
     axum::extract::State(state): axum::extract::State<PrometheusMetricsState>,
 ) -> impl IntoResponse {
     let mut rendered = state.metrics.to_prometheus_format();
+    rendered.push_str(&render_cache_prometheus_metrics(
+        &state.cache.degradation_metrics(),
+        state.cache.circuit_breaker_metrics().as_ref(),
+    ));
 
     match state.app_service_manager.get_statistics().await {
         Ok(appservice_statistics) => {
@@ -764,9 +1126,27 @@ async fn render_prometheus_metrics(
         }
     }
 
+    rendered
+        .push_str(&render_forward_extremity_prometheus_metrics(&state.forward_extremities.rooms_over_limit().await));
+
     ([(http::header::CONTENT_TYPE, "text/plain; version=0.0.4; charset=utf-8")], rendered)
 }
 
+/// Per-room extremity counts as of the last maintenance run. Only rooms over
+/// the configured limit are listed, so the series stays small.
+fn render_forward_extremity_prometheus_metrics(rooms: &[(String, i64)]) -> String {
+    let mut output = String::new();
+    output.push_str(
+        "# HELP synapse_room_forward_extremities Forward extremities of rooms over the limit at the last maintenance run\n",
+    );
+    output.push_str("# TYPE synapse_room_forward_extremities gauge\n");
+    for (room_id, count) in rooms {
+        let room_id = room_id.replace('\\', "\\\\").replace('"', "\\\"");
+        output.push_str(&format!("synapse_room_forward_extremities{{room_id=\"{room_id}\"}} {count}\n"));
+    }
+    output
+}
+
 fn render_appservice_scheduler_prometheus_metrics(summary: &AppserviceSchedulerTelemetrySummary) -> String {
     let mut output = String::new();
     append_prometheus_gauge(
@@ -844,6 +1224,107 @@ fn render_appservice_scheduler_prometheus_metrics(summary: &AppserviceSchedulerT
     output
 }
 
+fn render_cache_prometheus_metrics(
+    degradation: &synapse_cache::DegradationMetrics,
+    circuit_breaker: Option<&(synapse_cache::CircuitState, synapse_cache::CircuitBreakerMetrics)>,
+) -> String {
+    let mut output = String::new();
+    append_prometheus_counter(
+        &mut output,
+        "synapse_cache_local_hits_total",
+        "Lookups answered by the in-process cache",
+        degradation.local_cache_hits,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_cache_local_misses_total",
+        "Lookups not found in the in-process cache",
+        degradation.local_cache_misses,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_cache_redis_hits_total",
+        "Lookups answered by Redis",
+        degradation.redis_cache_hits,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_cache_redis_misses_total",
+        "Lookups not found in Redis",
+        degradation.redis_cache_misses,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_cache_redis_fallbacks_total",
+        "Redis lookups that failed and were treated as cache misses",
+        degradation.fallback_operations,
+    );
+    append_prometheus_gauge(
+        &mut output,
+        "synapse_cache_hit_ratio",
+        "Fraction of cache lookups answered by either cache tier",
+        degradation.hit_rate() / 100.0,
+    );
+
+    let Some((state, breaker)) = circuit_breaker else {
+        return output;
+    };
+    let state_value = match state {
+        synapse_cache::CircuitState::Closed => 0.0,
+        synapse_cache::CircuitState::HalfOpen => 1.0,
+        synapse_cache::CircuitState::Open => 2.0,
+    };
+    append_prometheus_gauge(
+        &mut output,
+        "synapse_redis_circuit_breaker_state",
+        "Redis circuit breaker state (0 closed, 1 half-open, 2 open)",
+        state_value,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_rejections_total",
+        "Redis calls rejected while the circuit breaker was open",
+        breaker.rejected_requests,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_failures_total",
+        "Redis calls that failed or timed out",
+        breaker.failed_requests,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_timeouts_total",
+        "Redis calls that timed out",
+        breaker.timeout_requests,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_opened_total",
+        "Times the Redis circuit breaker opened",
+        breaker.opened_transitions,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_half_opened_total",
+        "Times the Redis circuit breaker moved to half-open",
+        breaker.half_opened_transitions,
+    );
+    append_prometheus_counter(
+        &mut output,
+        "synapse_redis_circuit_breaker_closed_total",
+        "Times the Redis circuit breaker closed again",
+        breaker.closed_transitions,
+    );
+    output
+}
+
+fn append_prometheus_counter(output: &mut String, name: &str, help: &str, value: u64) {
+    output.push_str(&format!("# HELP {name} {help}\n"));
+    output.push_str(&format!("# TYPE {name} counter\n"));
+    output.push_str(&format!("{name} {value}\n"));
+}
+
 fn append_prometheus_gauge(output: &mut String, name: &str, help: &str, value: f64) {
     output.push_str(&format!("# HELP {name} {help}\n"));
     output.push_str(&format!("# TYPE {name} gauge\n"));
@@ -861,6 +1342,14 @@ mod tests {
     #[cfg(feature = "test-utils")]
     use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
 
+    #[test]
+    fn force_rename_danger_flag_is_opt_in() {
+        let args = |list: &[&str]| list.iter().map(|a| a.to_string()).collect::<Vec<_>>();
+        assert!(!StartupOptions::from_args(args(&[])).force_rename_danger);
+        assert!(!StartupOptions::from_args(args(&["--force-rename"])).force_rename_danger);
+        assert!(StartupOptions::from_args(args(&["--force-rename-danger"])).force_rename_danger);
+    }
+
     #[test]
     fn dehydrated_device_cleanup_uses_minimum_interval() {
         assert_eq!(
@@ -947,6 +1436,46 @@ mod tests {
         assert!(rendered.contains("synapse_appservice_scheduler_in_flight_count 5"));
     }
 
+    #[test]
+    fn render_forward_extremity_prometheus_metrics_labels_rooms() {
+        let rooms = vec![("!busy:example.org".to_string(), 42), ("!odd\"room:example.org".to_string(), 11)];
+
+        let rendered = render_forward_extremity_prometheus_metrics(&rooms);
+
+        assert!(rendered.contains("# TYPE synapse_room_forward_extremities gauge"));
+        assert!(rendered.contains("synapse_room_forward_extremities{room_id=\"!busy:example.org\"} 42"));
+        assert!(rendered.contains("synapse_room_forward_extremities{room_id=\"!odd\\\"room:example.org\"} 11"));
+    }
+
+    #[test]
+    fn render_cache_prometheus_metrics_includes_circuit_breaker_series() {
+        let degradation = synapse_cache::DegradationMetrics {
+            local_cache_hits: 3,
+            local_cache_misses: 1,
+            fallback_operations: 2,
+            ..Default::default()
+        };
+        let breaker = synapse_cache::CircuitBreakerMetrics {
+            rejected_requests: 4,
+            opened_transitions: 2,
+            closed_transitions: 1,
+            ..Default::default()
+        };
+
+        let local_only = render_cache_prometheus_metrics(&degradation, None);
+        assert!(local_only.contains("synapse_cache_local_hits_total 3"));
+        assert!(local_only.contains("synapse_cache_hit_ratio 0.75"));
+        assert!(!local_only.contains("synapse_redis_circuit_breaker_state"));
+
+        let rendered =
+            render_cache_prometheus_metrics(&degradation, Some(&(synapse_cache::CircuitState::Open, breaker)));
+        assert!(rendered.contains("synapse_cache_redis_fallbacks_total 2"));
+        assert!(rendered.contains("synapse_redis_circuit_breaker_state 2"));
+        assert!(rendered.contains("# TYPE synapse_redis_circuit_breaker_rejections_total counter"));
+        assert!(rendered.contains("synapse_redis_circuit_breaker_rejections_total 4"));
+        assert!(rendered.contains("synapse_redis_circuit_breaker_opened_total 2"));
+    }
+
     #[cfg(feature = "test-utils")]
     #[tokio::test]
     async fn render_appservice_scheduler_prometheus_metrics_reflects_recovery_summary() {
@@ -12,7 +12,9 @@ pub use synapse_storage::audit;
 pub use synapse_storage::background_update;
 #[cfg(feature = "beacons")]
 pub use synapse_storage::beacon;
+pub use synapse_storage::delayed_event;
 pub use synapse_storage::device;
+pub use synapse_storage::email_retry;
 pub use synapse_storage::event;
 pub use synapse_storage::event_report;
 pub use synapse_storage::feature_flags;
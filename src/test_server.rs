@@ -0,0 +1,278 @@
+//! In-process test server for integration tests.
+//!
+//! [`TestServer`] builds the full router on top of a schema leased from the
+//! shared test-schema pool (see [`crate::test_utils::acquire_pooled_schema`]),
+//! so a test needs nothing beyond a reachable PostgreSQL (`TEST_DATABASE_URL`
+//! or `DATABASE_URL`). Requests are dispatched straight into the router with
+//! `tower::ServiceExt::oneshot`; no socket is bound.
+//!
+//! ```ignore
+//! let Ok(server) = TestServer::start().await else { return };
+//! let alice = server.register_user("alice").await?;
+//! let bob = server.register_user("bob").await?;
+//! let room_id = server.seed_room(&alice, &[&bob]).await?;
+//! server.client(&bob).send_text(&room_id, "hello").await?;
+//! ```
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+use axum::body::Body;
+use axum::extract::ConnectInfo;
+use axum::http::{Method, Request, StatusCode};
+use axum::Router;
+use serde_json::{json, Value};
+use sqlx::PgPool;
+use synapse_services::ServiceContainer;
+use tower::ServiceExt;
+
+use crate::cache::{CacheConfig, CacheManager};
+use crate::test_utils::{acquire_pooled_schema, prepare_isolated_test_pool, LeasedSchema};
+use crate::web::routes::state::AppState;
+
+/// Password given to every user created through [`TestServer::register_user`].
+pub const TEST_USER_PASSWORD: &str = "TestPassword123!";
+
+/// Upper bound on response bodies read by the helpers.
+const MAX_RESPONSE_BYTES: usize = 16 * 1024 * 1024;
+
+/// A registered user and the session the helpers act as.
+#[derive(Debug, Clone)]
+pub struct TestUser {
+    pub user_id: String,
+    pub access_token: String,
+    pub device_id: String,
+}
+
+/// Status and decoded JSON body of a response. Non-JSON bodies are exposed as
+/// a JSON string.
+#[derive(Debug, Clone)]
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub body: Value,
+}
+
+impl TestResponse {
+    pub fn is_success(&self) -> bool {
+        self.status.is_success()
+    }
+
+    /// The body, or a description of the failure for non-2xx responses.
+    pub fn into_result(self) -> Result<Value, String> {
+        if self.is_success() {
+            Ok(self.body)
+        } else {
+            Err(format!("request failed with {}: {}", self.status, self.body))
+        }
+    }
+}
+
+/// The full router backed by an isolated database schema.
+pub struct TestServer {
+    pub app: Router,
+    pub state: AppState,
+    pub pool: Arc<PgPool>,
+    // Returns the schema to the pool on drop; `None` for `start_isolated`.
+    _lease: Option<LeasedSchema>,
+}
+
+impl TestServer {
+    /// Start a server on a schema leased from the test-schema pool.
+    pub async fn start() -> Result<Self, String> {
+        Self::start_with(|_| {}).await
+    }
+
+    /// Start a server, letting `configure` adjust the service container (for
+    /// example its config) before the router is built.
+    pub async fn start_with<F>(configure: F) -> Result<Self, String>
+    where
+        F: FnOnce(&mut ServiceContainer),
+    {
+        let lease = acquire_pooled_schema().await?;
+        let pool = lease.pool.clone();
+        Ok(Self::build(pool, Some(lease), configure).await)
+    }
+
+    /// Start a server on a freshly migrated schema that is never reused. Use
+    /// for tests that alter the schema itself.
+    pub async fn start_isolated() -> Result<Self, String> {
+        let pool = prepare_isolated_test_pool().await?;
+        Ok(Self::build(pool, None, |_| {}).await)
+    }
+
+    async fn build<F>(pool: Arc<PgPool>, lease: Option<LeasedSchema>, configure: F) -> Self
+    where
+        F: FnOnce(&mut ServiceContainer),
+    {
+        let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+        let mut container = ServiceContainer::new_test_with_pool_and_cache(pool.clone(), cache.clone()).await;
+        configure(&mut container);
+        let state = AppState::new(container, cache);
+        let app = crate::web::create_router(state.clone());
+        Self { app, state, pool, _lease: lease }
+    }
+
+    /// Server name the router was built with.
+    pub fn server_name(&self) -> &str {
+        &self.state.services.core.config.server.name
+    }
+
+    /// Send a request to the router. Requests appear to come from loopback so
+    /// localhost-only endpoints are reachable.
+    pub async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        access_token: Option<&str>,
+        body: Option<Value>,
+    ) -> Result<TestResponse, String> {
+        let mut builder = Request::builder().method(method).uri(path);
+        if let Some(token) = access_token {
+            builder = builder.header("Authorization", format!("Bearer {token}"));
+        }
+        let body = match body {
+            Some(value) => {
+                builder = builder.header("Content-Type", "application/json");
+                Body::from(value.to_string())
+            }
+            None => Body::empty(),
+        };
+        let mut request = builder.body(body).map_err(|e| format!("invalid request: {e}"))?;
+        let loopback: SocketAddr = ([127, 0, 0, 1], 65530).into();
+        request.extensions_mut().insert(ConnectInfo(loopback));
+
+        let response = self.app.clone().oneshot(request).await.map_err(|e| format!("router error: {e}"))?;
+        let status = response.status();
+        let bytes = axum::body::to_bytes(response.into_body(), MAX_RESPONSE_BYTES)
+            .await
+            .map_err(|e| format!("failed to read response body: {e}"))?;
+        let body = if bytes.is_empty() {
+            Value::Null
+        } else {
+            serde_json::from_slice(&bytes).unwrap_or_else(|_| Value::String(String::from_utf8_lossy(&bytes).into()))
+        };
+        Ok(TestResponse { status, body })
+    }
+
+    /// Register `localpart` through the client API with a dummy auth stage.
+    pub async fn register_user(&self, localpart: &str) -> Result<TestUser, String> {
+        let body = self
+            .request(
+                Method::POST,
+                "/_matrix/client/v3/register",
+                None,
+                Some(json!({
+                    "username": localpart,
+                    "password": TEST_USER_PASSWORD,
+                    "auth": { "type": "m.login.dummy" }
+                })),
+            )
+            .await?
+            .into_result()?;
+        Ok(TestUser {
+            user_id: string_field(&body, "user_id")?,
+            access_token: string_field(&body, "access_token")?,
+            device_id: string_field(&body, "device_id")?,
+        })
+    }
+
+    /// Register `localpart` and grant it server admin rights.
+    pub async fn register_admin(&self, localpart: &str) -> Result<TestUser, String> {
+        let user = self.register_user(localpart).await?;
+        sqlx::query("UPDATE users SET is_admin = TRUE WHERE user_id = $1")
+            .bind(&user.user_id)
+            .execute(&*self.pool)
+            .await
+            .map_err(|e| format!("failed to promote {}: {e}", user.user_id))?;
+        self.state.cache.delete(&format!("user:admin:{}", user.user_id)).await;
+        Ok(user)
+    }
+
+    /// Create a private room owned by `creator` and join every user in `members`.
+    pub async fn seed_room(&self, creator: &TestUser, members: &[&TestUser]) -> Result<String, String> {
+        let creator_client = self.client(creator);
+        let room_id = creator_client.create_room(json!({ "preset": "private_chat" })).await?;
+        for member in members {
+            creator_client.invite(&room_id, &member.user_id).await?;
+            self.client(member).join_room(&room_id).await?;
+        }
+        Ok(room_id)
+    }
+
+    /// Helpers acting as `user`.
+    pub fn client<'a>(&'a self, user: &'a TestUser) -> TestClient<'a> {
+        TestClient { server: self, user }
+    }
+}
+
+/// Typed client-API helpers bound to one user.
+pub struct TestClient<'a> {
+    server: &'a TestServer,
+    user: &'a TestUser,
+}
+
+impl TestClient<'_> {
+    pub async fn get(&self, path: &str) -> Result<TestResponse, String> {
+        self.server.request(Method::GET, path, Some(&self.user.access_token), None).await
+    }
+
+    pub async fn post(&self, path: &str, body: Value) -> Result<TestResponse, String> {
+        self.server.request(Method::POST, path, Some(&self.user.access_token), Some(body)).await
+    }
+
+    pub async fn put(&self, path: &str, body: Value) -> Result<TestResponse, String> {
+        self.server.request(Method::PUT, path, Some(&self.user.access_token), Some(body)).await
+    }
+
+    pub async fn delete(&self, path: &str) -> Result<TestResponse, String> {
+        self.server.request(Method::DELETE, path, Some(&self.user.access_token), None).await
+    }
+
+    /// `POST /createRoom`, returning the new room ID.
+    pub async fn create_room(&self, body: Value) -> Result<String, String> {
+        let body = self.post("/_matrix/client/v3/createRoom", body).await?.into_result()?;
+        string_field(&body, "room_id")
+    }
+
+    pub async fn invite(&self, room_id: &str, user_id: &str) -> Result<(), String> {
+        self.post(&format!("/_matrix/client/v3/rooms/{room_id}/invite"), json!({ "user_id": user_id }))
+            .await?
+            .into_result()
+            .map(|_| ())
+    }
+
+    pub async fn join_room(&self, room_id: &str) -> Result<(), String> {
+        self.post(&format!("/_matrix/client/v3/rooms/{room_id}/join"), json!({})).await?.into_result().map(|_| ())
+    }
+
+    /// Send an event, returning its event ID.
+    pub async fn send_event(&self, room_id: &str, event_type: &str, content: Value) -> Result<String, String> {
+        let txn_id = format!("test-{}", uuid::Uuid::new_v4().simple());
+        let body = self
+            .put(&format!("/_matrix/client/v3/rooms/{room_id}/send/{event_type}/{txn_id}"), content)
+            .await?
+            .into_result()?;
+        string_field(&body, "event_id")
+    }
+
+    /// Send an `m.text` message, returning its event ID.
+    pub async fn send_text(&self, room_id: &str, text: &str) -> Result<String, String> {
+        self.send_event(room_id, "m.room.message", json!({ "msgtype": "m.text", "body": text })).await
+    }
+
+    /// `GET /sync`, optionally incremental from `since`.
+    pub async fn sync(&self, since: Option<&str>) -> Result<Value, String> {
+        let path = match since {
+            Some(token) => format!("/_matrix/client/v3/sync?timeout=0&since={token}"),
+            None => "/_matrix/client/v3/sync?timeout=0".to_string(),
+        };
+        self.get(&path).await?.into_result()
+    }
+}
+
+fn string_field(body: &Value, field: &str) -> Result<String, String> {
+    body.get(field)
+        .and_then(Value::as_str)
+        .map(str::to_string)
+        .ok_or_else(|| format!("response has no {field}: {body}"))
+}
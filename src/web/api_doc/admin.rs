@@ -1,5 +1,139 @@
 #![cfg(feature = "openapi-docs")]
 
+/// Bearer-token security scheme referenced by `security(("BearerAuth" = []))`.
+pub struct BearerAuthAddon;
+
+impl utoipa::Modify for BearerAuthAddon {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        use utoipa::openapi::security::{HttpAuthScheme, HttpBuilder, SecurityScheme};
+
+        let components = openapi.components.get_or_insert_with(Default::default);
+        components.add_security_scheme(
+            "BearerAuth",
+            SecurityScheme::Http(
+                HttpBuilder::new().scheme(HttpAuthScheme::Bearer).description(Some("Admin access token")).build(),
+            ),
+        );
+    }
+}
+
+/// OpenAPI document for the admin API, served on its own at
+/// `/_synapse/admin/openapi.json` and merged into the full API document.
+#[derive(utoipa::OpenApi)]
+#[openapi(
+    info(
+        title = "Synapse-Rust Admin API",
+        version = env!("CARGO_PKG_VERSION"),
+        description = "Server administration endpoints under `/_synapse/admin`. \
+            Every endpoint requires the access token of a server admin."
+    ),
+    servers(
+        (url = "/", description = "Local Synapse-Rust instance"),
+    ),
+    tags(
+        (name = "Admin", description = "Server administration endpoints"),
+    ),
+    modifiers(&BearerAuthAddon),
+    paths(
+        list_users_admin_doc,
+        list_rooms_admin_doc,
+        admin_delete_user_doc,
+        admin_evict_user_doc,
+        admin_set_user_admin_doc,
+        admin_deactivate_user_doc,
+        admin_reset_user_password_doc,
+        admin_user_v2_doc,
+        admin_upsert_user_v2_doc,
+        admin_user_rooms_doc,
+        admin_user_devices_doc,
+        admin_delete_user_device_doc,
+        admin_login_as_user_doc,
+        admin_logout_user_devices_doc,
+        admin_user_stats_doc,
+        admin_single_user_stats_doc,
+        admin_batch_create_users_doc,
+        admin_provision_users_doc,
+        admin_batch_deactivate_users_doc,
+        admin_user_sessions_doc,
+        admin_invalidate_user_sessions_doc,
+        admin_account_details_doc,
+        admin_update_account_doc,
+        admin_room_doc,
+        admin_room_members_doc,
+        admin_force_join_room_doc,
+        admin_force_part_room_doc,
+        admin_shutdown_room_doc,
+        admin_room_state_doc,
+        admin_spaces_doc,
+        admin_space_doc,
+        admin_delete_space_doc,
+        admin_space_users_doc,
+        admin_space_rooms_doc,
+        admin_space_stats_doc,
+        admin_room_stats_doc,
+        admin_single_room_stats_doc,
+        admin_room_listings_doc,
+        admin_set_room_public_doc,
+        admin_set_room_private_doc,
+        admin_room_block_status_doc,
+        admin_block_room_doc,
+        admin_unblock_room_doc,
+        admin_make_room_admin_doc,
+        admin_purge_history_doc,
+        admin_purge_room_doc,
+        admin_join_room_member_doc,
+        admin_remove_room_member_doc,
+        admin_cleanup_abnormal_rooms_doc,
+        admin_server_version_doc,
+        admin_info_doc,
+        admin_whoami_doc,
+        admin_statistics_doc,
+        admin_status_doc,
+        admin_whois_doc,
+        admin_whois_device_doc,
+        admin_purge_media_cache_doc,
+        admin_config_doc,
+        admin_schema_drift_doc,
+        admin_jitsi_config_doc,
+        admin_invite_blocklist_doc,
+        admin_invite_allowlist_doc,
+        admin_federation_destinations_doc,
+        admin_federation_destination_doc,
+        admin_federation_destination_rooms_doc,
+        admin_federation_endpoints_doc,
+        admin_reports_doc,
+        admin_report_doc,
+        admin_retention_policy_doc,
+        admin_set_retention_policy_doc,
+        admin_room_retention_policy_doc,
+        admin_retention_status_doc,
+        admin_registration_tokens_doc,
+        admin_create_registration_token_doc,
+        admin_registration_token_doc,
+        admin_delete_registration_token_doc,
+        admin_update_registration_token_doc,
+        admin_user_tokens_doc,
+        admin_delete_user_token_doc,
+        admin_user_refresh_tokens_doc,
+        admin_delete_refresh_token_doc,
+        admin_media_list_doc,
+        admin_media_info_doc,
+        admin_delete_media_doc,
+        admin_media_quota_doc,
+        admin_user_media_doc,
+        admin_delete_user_media_doc,
+        admin_shadow_ban_user_doc,
+        admin_unshadow_ban_user_doc,
+        admin_user_rate_limit_doc,
+        admin_set_user_rate_limit_doc,
+        admin_delete_user_rate_limit_doc,
+        admin_override_rate_limit_doc,
+        admin_set_override_rate_limit_doc,
+        admin_delete_override_rate_limit_doc,
+    ),
+)]
+pub struct AdminApiDoc;
+
 /// `GET /_synapse/admin/v1/users` — List registered users (Admin only).
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -493,6 +627,38 @@ pub fn admin_batch_create_users_doc() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `POST /_synapse/admin/v1/users/provision` — Idempotently create users from a JSON list or CSV.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    post,
+    path = "/_synapse/admin/v1/users/provision",
+    tag = "Admin",
+    request_body(content = String, content_type = "text/csv"),
+    responses(
+        (status = 200, description = "Per-row provisioning report",
+            body = serde_json::Value,
+            example = json!({
+                "total": 2,
+                "created": 1,
+                "exists": 1,
+                "failed": 0,
+                "results": [
+                    {"row": 1, "username": "alice", "user_id": "@alice:example.com", "status": "created", "error": null},
+                    {"row": 2, "username": "bob", "user_id": "@bob:example.com", "status": "exists", "error": null}
+                ]
+            })
+        ),
+        (status = 400, description = "Malformed CSV/JSON or more than 1000 rows"),
+        (status = 403, description = "Only super_admin can provision users")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_provision_users_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `POST /_synapse/admin/v1/users/batch_deactivate` — Deactivate multiple users in one request.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -711,6 +877,88 @@ pub fn admin_room_members_doc() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `POST /_synapse/admin/v1/join/{room_id_or_alias}` — Join a local user to a room, bypassing join rules.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    post,
+    path = "/_synapse/admin/v1/join/{room_id_or_alias}",
+    tag = "Admin",
+    params(
+        ("room_id_or_alias" = String, Path, description = "Matrix room ID or room alias")
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "User joined the room",
+            body = serde_json::Value,
+            example = json!({ "room_id": "!abc123:example.com" })
+        ),
+        (status = 400, description = "User is not local to this server"),
+        (status = 403, description = "User is banned from the room"),
+        (status = 404, description = "Room, alias or user not found")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_force_join_room_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
+/// `POST /_synapse/admin/v1/part/{room_id_or_alias}` — Remove a local user from a room.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    post,
+    path = "/_synapse/admin/v1/part/{room_id_or_alias}",
+    tag = "Admin",
+    params(
+        ("room_id_or_alias" = String, Path, description = "Matrix room ID or room alias")
+    ),
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "User left the room",
+            body = serde_json::Value,
+            example = json!({ "room_id": "!abc123:example.com" })
+        ),
+        (status = 400, description = "User is not local to this server"),
+        (status = 404, description = "Room, alias or user not found")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_force_part_room_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
+/// `POST /_synapse/admin/v1/shutdown_room` — Evict local members, block the room and delist it.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    post,
+    path = "/_synapse/admin/v1/shutdown_room",
+    tag = "Admin",
+    request_body = serde_json::Value,
+    responses(
+        (status = 200, description = "Room shut down",
+            body = serde_json::Value,
+            example = json!({
+                "kicked_users": ["@alice:example.com"],
+                "failed_to_kick_users": [],
+                "local_aliases": ["#lobby:example.com"],
+                "new_room_id": "!notice456:example.com",
+                "closed_room": true
+            })
+        ),
+        (status = 400, description = "new_room_user_id is not a local user"),
+        (status = 404, description = "Room or new_room_user_id not found")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_shutdown_room_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `GET /_synapse/admin/v1/rooms/{room_id}/state` — List state events for a room.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -1502,6 +1750,39 @@ pub fn admin_config_doc() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `GET /_synapse/admin/v1/database/schema_drift` — Compare the live schema with the migrations.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    get,
+    path = "/_synapse/admin/v1/database/schema_drift",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Tables, columns and indexes declared by the migrations but missing from the database",
+            body = serde_json::Value,
+            example = json!({
+                "has_drift": true,
+                "policy": "warn",
+                "report": {
+                    "migrations_dir": "/app/migrations",
+                    "migration_files": 9,
+                    "expected_tables": 265,
+                    "expected_indexes": 812,
+                    "missing_tables": [],
+                    "missing_columns": ["space_children.order"],
+                    "missing_indexes": ["idx_destination_rooms_stream"]
+                }
+            })
+        ),
+        (status = 500, description = "Migrations directory unreadable or database error")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_schema_drift_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `GET /_synapse/admin/v1/jitsi/config` — Read Jitsi integration settings.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -1658,6 +1939,37 @@ pub fn admin_federation_destination_rooms_doc() -> axum::Json<serde_json::Value>
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `GET /_synapse/admin/v1/federation/endpoints` — List the federation endpoints this server serves.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    get,
+    path = "/_synapse/admin/v1/federation/endpoints",
+    tag = "Admin",
+    responses(
+        (status = 200, description = "Federation endpoint registry",
+            body = serde_json::Value,
+            example = json!({
+                "server": { "name": "synapse-rust", "version": "0.1.0" },
+                "total": 1,
+                "spec_total": 1,
+                "endpoints": [{
+                    "method": "GET",
+                    "path": "/_matrix/federation/v1/version",
+                    "auth": "public",
+                    "spec": true
+                }]
+            })
+        ),
+        (status = 403, description = "Admin only")
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn admin_federation_endpoints_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `GET /_synapse/admin/v1/reports` — List moderation reports.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -2411,3 +2723,18 @@ pub fn admin_set_override_rate_limit_doc() -> axum::Json<serde_json::Value> {
 pub fn admin_delete_override_rate_limit_doc() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
+
+#[cfg(test)]
+mod tests {
+    use super::AdminApiDoc;
+    use utoipa::OpenApi;
+
+    #[test]
+    fn admin_document_only_covers_admin_paths_and_declares_bearer_auth() {
+        let doc = AdminApiDoc::openapi();
+        assert!(!doc.paths.paths.is_empty());
+        assert!(doc.paths.paths.keys().all(|path| path.starts_with("/_synapse/admin/")));
+        let components = doc.components.expect("admin document has components");
+        assert!(components.security_schemes.contains_key("BearerAuth"));
+    }
+}
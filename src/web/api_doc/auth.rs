@@ -580,7 +580,8 @@ pub fn check_username_availability_doc() -> axum::Json<serde_json::Value> {
             example = json!({
                 "flows": [
                     {"type": "m.login.password"},
-                    {"type": "m.login.token"}
+                    {"type": "m.login.token"},
+                    {"type": "m.login.application_service"}
                 ]
             })
         )
@@ -1471,6 +1471,33 @@ pub fn get_relations_doc() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `GET /_matrix/client/v3/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_type}` — List relations of one type and event type.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    get,
+    path = "/_matrix/client/v3/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_type}",
+    tag = "Client-Server",
+    params(
+        ("room_id" = String, Path, description = "Room containing the event"),
+        ("event_id" = String, Path, description = "Target event ID"),
+        ("rel_type" = String, Path, description = "Relation type"),
+        ("event_type" = String, Path, description = "Type of the relating events"),
+        ("limit" = Option<i64>, Query, description = "Maximum relations to return"),
+        ("from" = Option<String>, Query, description = "Pagination token"),
+        ("to" = Option<String>, Query, description = "Pagination token to stop at"),
+        ("dir" = Option<String>, Query, description = "Direction")
+    ),
+    responses(
+        (status = 200, description = "Relations response", body = serde_json::Value)
+    ),
+    security(
+        ("BearerAuth" = [])
+    )
+)]
+pub fn get_relations_by_event_type_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `PUT /_matrix/client/v3/rooms/{room_id}/relations/{event_id}/{rel_type}/{txn_id}` — Send a related event.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
@@ -3518,6 +3545,10 @@ pub fn sliding_sync_msc3575_doc() -> axum::Json<serde_json::Value> {
     post,
     path = "/_matrix/client/unstable/org.matrix.simplified_msc3575/sync",
     tag = "Unstable MSC",
+    params(
+        ("pos" = Option<String>, Query, description = "Position token from the previous response"),
+        ("timeout" = Option<u32>, Query, description = "Long-poll timeout in milliseconds")
+    ),
     request_body = serde_json::Value,
     responses(
         (status = 200, description = "Sync response", body = serde_json::Value),
@@ -40,6 +40,51 @@ pub fn health_check() -> axum::Json<serde_json::Value> {
     unreachable!("This function exists only for OpenAPI documentation purposes")
 }
 
+/// `GET /health/live` — Liveness probe (process is serving requests).
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    get,
+    path = "/health/live",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Process is alive",
+            body = serde_json::Value,
+            example = json!({"status": "healthy", "version": "0.1.0", "timestamp": 1760600000, "checks": {}})
+        ),
+    ),
+)]
+pub fn liveness_check_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
+/// `GET /health/ready` — Readiness probe with per-dependency detail.
+#[cfg(feature = "openapi-docs")]
+#[utoipa::path(
+    get,
+    path = "/health/ready",
+    tag = "Health",
+    responses(
+        (status = 200, description = "Ready to receive traffic (possibly degraded)",
+            body = serde_json::Value,
+            example = json!({
+                "status": "healthy",
+                "version": "0.1.0",
+                "timestamp": 1760600000,
+                "checks": {
+                    "database": {"status": "healthy", "message": "Database connection successful", "duration_ms": 1},
+                    "migrations": {"status": "healthy", "message": "212 migrations applied", "duration_ms": 2},
+                    "cache": {"status": "healthy", "message": "Cache connection successful", "duration_ms": 1},
+                    "federation_key": {"status": "healthy", "message": "Signing key ed25519:a_abcd loaded", "duration_ms": 0}
+                }
+            })
+        ),
+        (status = 503, description = "A critical dependency is unhealthy, or degraded while `health.ready_when_degraded` is false"),
+    ),
+)]
+pub fn readiness_check_doc() -> axum::Json<serde_json::Value> {
+    unreachable!("This function exists only for OpenAPI documentation purposes")
+}
+
 /// `GET /.well-known/matrix/server` — Server discovery.
 #[cfg(feature = "openapi-docs")]
 #[utoipa::path(
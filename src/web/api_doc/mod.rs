@@ -2,6 +2,8 @@
 //!
 //! Enabled via the `openapi-docs` feature flag. When enabled, the Swagger UI
 //! is served at `/_swagger` and the OpenAPI JSON schema at `/_api-doc/openapi.json`.
+//! The admin API additionally has its own document at
+//! `/_synapse/admin/openapi.json`, browsable at `/_synapse/admin/swagger`.
 //!
 //! Route annotation is progressive — health, versions, capabilities, and
 //! well-known endpoints are annotated as canonical examples. Additional routes
@@ -20,6 +22,7 @@ use crate::web::routes::AppState;
 ///
 /// The UI is mounted at `/_swagger` with a redirect from `/_swagger/` for
 /// convenience. The raw OpenAPI JSON is served at `/_api-doc/openapi.json`.
+/// The admin-only document and its UI live under `/_synapse/admin`.
 #[cfg(feature = "openapi-docs")]
 pub fn swagger_ui_router(_state: AppState) -> axum::Router<AppState> {
     use utoipa::OpenApi;
@@ -43,9 +46,12 @@ pub fn swagger_ui_router(_state: AppState) -> axum::Router<AppState> {
             (name = "Admin", description = "Server administration endpoints"),
             (name = "Federation", description = "Server-to-server federation API"),
         ),
+        modifiers(&admin::BearerAuthAddon),
         paths(
             health::get_client_versions,
             health::health_check,
+            health::liveness_check_doc,
+            health::readiness_check_doc,
             health::get_well_known_server,
             health::get_capabilities,
             health::get_well_known_client,
@@ -187,6 +193,7 @@ pub fn swagger_ui_router(_state: AppState) -> axum::Router<AppState> {
             client_server::update_report_score_doc,
             client_server::get_relations_by_event_doc,
             client_server::get_relations_doc,
+            client_server::get_relations_by_event_type_doc,
             client_server::send_relation_doc,
             client_server::get_aggregations_doc,
             client_server::add_reaction_doc,
@@ -344,95 +351,6 @@ pub fn swagger_ui_router(_state: AppState) -> axum::Router<AppState> {
             client_server::remove_friend_from_group_doc,
             client_server::get_friend_groups_for_user_doc,
             client_server::get_friend_dm_doc,
-            admin::list_users_admin_doc,
-            admin::list_rooms_admin_doc,
-            admin::admin_delete_user_doc,
-            admin::admin_evict_user_doc,
-            admin::admin_set_user_admin_doc,
-            admin::admin_deactivate_user_doc,
-            admin::admin_reset_user_password_doc,
-            admin::admin_user_v2_doc,
-            admin::admin_upsert_user_v2_doc,
-            admin::admin_user_rooms_doc,
-            admin::admin_user_devices_doc,
-            admin::admin_delete_user_device_doc,
-            admin::admin_login_as_user_doc,
-            admin::admin_logout_user_devices_doc,
-            admin::admin_user_stats_doc,
-            admin::admin_single_user_stats_doc,
-            admin::admin_batch_create_users_doc,
-            admin::admin_batch_deactivate_users_doc,
-            admin::admin_user_sessions_doc,
-            admin::admin_invalidate_user_sessions_doc,
-            admin::admin_account_details_doc,
-            admin::admin_update_account_doc,
-            admin::admin_room_doc,
-            admin::admin_room_members_doc,
-            admin::admin_room_state_doc,
-            admin::admin_spaces_doc,
-            admin::admin_space_doc,
-            admin::admin_delete_space_doc,
-            admin::admin_space_users_doc,
-            admin::admin_space_rooms_doc,
-            admin::admin_space_stats_doc,
-            admin::admin_room_stats_doc,
-            admin::admin_single_room_stats_doc,
-            admin::admin_room_listings_doc,
-            admin::admin_set_room_public_doc,
-            admin::admin_set_room_private_doc,
-            admin::admin_room_block_status_doc,
-            admin::admin_block_room_doc,
-            admin::admin_unblock_room_doc,
-            admin::admin_make_room_admin_doc,
-            admin::admin_purge_history_doc,
-            admin::admin_purge_room_doc,
-            admin::admin_join_room_member_doc,
-            admin::admin_remove_room_member_doc,
-            admin::admin_cleanup_abnormal_rooms_doc,
-            admin::admin_server_version_doc,
-            admin::admin_info_doc,
-            admin::admin_whoami_doc,
-            admin::admin_statistics_doc,
-            admin::admin_status_doc,
-            admin::admin_whois_doc,
-            admin::admin_whois_device_doc,
-            admin::admin_purge_media_cache_doc,
-            admin::admin_config_doc,
-            admin::admin_jitsi_config_doc,
-            admin::admin_invite_blocklist_doc,
-            admin::admin_invite_allowlist_doc,
-            admin::admin_federation_destinations_doc,
-            admin::admin_federation_destination_doc,
-            admin::admin_federation_destination_rooms_doc,
-            admin::admin_reports_doc,
-            admin::admin_report_doc,
-            admin::admin_retention_policy_doc,
-            admin::admin_set_retention_policy_doc,
-            admin::admin_room_retention_policy_doc,
-            admin::admin_retention_status_doc,
-            admin::admin_registration_tokens_doc,
-            admin::admin_create_registration_token_doc,
-            admin::admin_registration_token_doc,
-            admin::admin_delete_registration_token_doc,
-            admin::admin_update_registration_token_doc,
-            admin::admin_user_tokens_doc,
-            admin::admin_delete_user_token_doc,
-            admin::admin_user_refresh_tokens_doc,
-            admin::admin_delete_refresh_token_doc,
-            admin::admin_media_list_doc,
-            admin::admin_media_info_doc,
-            admin::admin_delete_media_doc,
-            admin::admin_media_quota_doc,
-            admin::admin_user_media_doc,
-            admin::admin_delete_user_media_doc,
-            admin::admin_shadow_ban_user_doc,
-            admin::admin_unshadow_ban_user_doc,
-            admin::admin_user_rate_limit_doc,
-            admin::admin_set_user_rate_limit_doc,
-            admin::admin_delete_user_rate_limit_doc,
-            admin::admin_override_rate_limit_doc,
-            admin::admin_set_override_rate_limit_doc,
-            admin::admin_delete_override_rate_limit_doc,
             federation::get_federation_version_doc,
             federation::get_federation_discovery_doc,
             federation::get_public_rooms_federation_doc,
@@ -482,9 +400,14 @@ pub fn swagger_ui_router(_state: AppState) -> axum::Router<AppState> {
     )]
     struct ApiDoc;
 
-    let openapi = ApiDoc::openapi();
+    let admin_openapi = admin::AdminApiDoc::openapi();
+    let mut openapi = ApiDoc::openapi();
+    openapi.merge(admin_openapi.clone());
 
-    axum::Router::new().merge(SwaggerUi::new("/_swagger").url("/_api-doc/openapi.json", openapi)).with_state(_state)
+    axum::Router::new()
+        .merge(SwaggerUi::new("/_swagger").url("/_api-doc/openapi.json", openapi))
+        .merge(SwaggerUi::new("/_synapse/admin/swagger").url("/_synapse/admin/openapi.json", admin_openapi))
+        .with_state(_state)
 }
 
 /// Stub for when `openapi-docs` is not enabled.
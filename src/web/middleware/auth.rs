@@ -2,13 +2,15 @@ use crate::common::ApiError;
 use crate::web::routes::admin::audit::resolve_request_id;
 use crate::web::routes::context::{AdminContext, CoreContext};
 use crate::web::utils::admin_auth::authorize_admin_from_services;
-use crate::web::utils::auth::bearer_token;
+use crate::web::utils::auth::{bearer_token, masquerade_user_id};
 use crate::web::utils::ip::extract_client_ip;
 use axum::extract::State;
 use axum::http::{HeaderMap, Method, Request};
 use axum::response::IntoResponse;
 use axum::{body::Body, response::Response, Json};
-use serde_json::json;
+use serde_json::{json, Value};
+use std::time::Duration;
+use synapse_common::crypto::{generate_event_id, generate_room_id};
 use synapse_storage::audit::CreateAuditEventRequest;
 
 pub fn extract_token(headers: &HeaderMap, uri: &str) -> Option<String> {
@@ -26,7 +28,7 @@ pub async fn auth_middleware(
         None => return ApiError::missing_token().into_response(),
     };
 
-    if let Err(err) = ctx.token_auth.validate_token(&token).await {
+    if let Err(err) = ctx.token_auth.validate_token_as(&token, masquerade_user_id(&uri).as_deref()).await {
         return err.into_response();
     }
 
@@ -56,29 +58,13 @@ pub async fn shadow_ban_middleware(
         None => return next.run(request).await,
     };
 
-    match ctx.token_auth.validate_token(&token).await {
+    match ctx.token_auth.validate_token_as(&token, masquerade_user_id(&uri).as_deref()).await {
         Ok((_, _, _, is_shadow_banned, is_guest)) => {
             if is_shadow_banned {
-                ::tracing::warn!(
-                    target: "security_audit",
-                    event = "shadow_banned_write_blocked",
-                    path = path.as_str(),
-                    method = method.to_string(),
-                    "Shadow-banned user attempted write operation - silently dropping"
-                );
-
-                if path.contains("/send/")
-                    || path.contains("/invite")
-                    || path.contains("/join")
-                    || path.contains("/leave")
-                    || path.contains("/kick")
-                    || path.contains("/ban")
-                    || path.contains("/redact")
-                {
-                    return Json(json!({"event_id": format!("${}", uuid::Uuid::new_v4())})).into_response();
-                }
-
-                return Json(json!({})).into_response();
+                return match shadow_ban_reply(&method, &path) {
+                    Some(reply) => drop_shadow_banned_request(&ctx, reply, &method, &path, request, next).await,
+                    None => next.run(request).await,
+                };
             }
 
             if is_guest {
@@ -118,6 +104,95 @@ pub async fn shadow_ban_middleware(
     }
 }
 
+/// Largest `createRoom` body buffered to look for invites.
+const MAX_CREATE_ROOM_BODY_BYTES: usize = 1024 * 1024;
+
+/// What a shadow-banned user gets back instead of their request being run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ShadowBanReply {
+    /// A made-up event ID, as if the event had been sent.
+    FakeEvent,
+    /// An empty object, as if the invite or typing notification went out.
+    Empty,
+    /// A made-up room ID, as if the room had been created.
+    FakeRoom,
+    /// `createRoom` is only dropped when it invites someone.
+    CreateRoom,
+    /// A made-up replacement room ID, as if the room had been upgraded.
+    FakeUpgrade,
+}
+
+/// The client API requests that shadow-banned users only pretend to make:
+/// anything that sends a room event, invites someone or signals typing.
+/// Everything else (joins, leaves, profile, keys, account data) runs as usual.
+fn shadow_ban_reply(method: &Method, path: &str) -> Option<ShadowBanReply> {
+    let rest = path.strip_prefix("/_matrix/client/")?;
+    let segments: Vec<&str> = rest.split('/').skip(1).collect();
+    let post = *method == Method::POST;
+    let put_or_post = post || *method == Method::PUT;
+
+    match segments.as_slice() {
+        ["createRoom"] if post => Some(ShadowBanReply::CreateRoom),
+        ["create_dm"] if post => Some(ShadowBanReply::FakeRoom),
+        ["invite", _] | ["rooms", _, "invite"] | ["spaces", _, "invite"] if post => Some(ShadowBanReply::Empty),
+        ["rooms", "typing"] if post => Some(ShadowBanReply::Empty),
+        ["rooms", _, "typing", ..] if put_or_post => Some(ShadowBanReply::Empty),
+        ["rooms", _, "send" | "state" | "redact", ..] if put_or_post => Some(ShadowBanReply::FakeEvent),
+        ["rooms", _, "relations", _, _, _] if *method == Method::PUT => Some(ShadowBanReply::FakeEvent),
+        ["rooms", _, "upgrade"] if post => Some(ShadowBanReply::FakeUpgrade),
+        _ => None,
+    }
+}
+
+/// Whether a `createRoom` body invites anyone.
+fn create_room_invites_anyone(body: &Value) -> bool {
+    ["invite", "invite_3pid"]
+        .iter()
+        .any(|key| body.get(key).and_then(Value::as_array).is_some_and(|invites| !invites.is_empty()))
+}
+
+/// Answers as if `request` had succeeded, after the 1-10s a real event send
+/// might take, so the sender cannot tell from the timing that it was dropped.
+async fn drop_shadow_banned_request(
+    ctx: &CoreContext,
+    reply: ShadowBanReply,
+    method: &Method,
+    path: &str,
+    request: Request<Body>,
+    next: axum::middleware::Next,
+) -> Response {
+    if reply == ShadowBanReply::CreateRoom {
+        let (parts, body) = request.into_parts();
+        let Ok(bytes) = axum::body::to_bytes(body, MAX_CREATE_ROOM_BODY_BYTES).await else {
+            return ApiError::bad_request("Request body too large".to_string()).into_response();
+        };
+        let invites = serde_json::from_slice::<Value>(&bytes).is_ok_and(|body| create_room_invites_anyone(&body));
+        if !invites {
+            return next.run(Request::from_parts(parts, Body::from(bytes))).await;
+        }
+    }
+
+    ::tracing::warn!(
+        target: "security_audit",
+        event = "shadow_banned_action_dropped",
+        path = path,
+        method = method.to_string(),
+        "Shadow-banned user request silently dropped"
+    );
+
+    let delay_secs = rand::Rng::random_range(&mut rand::rng(), 1..=10);
+    tokio::time::sleep(Duration::from_secs(delay_secs)).await;
+
+    let server_name = &ctx.config.server.name;
+    let body = match reply {
+        ShadowBanReply::FakeEvent => json!({ "event_id": generate_event_id(server_name) }),
+        ShadowBanReply::Empty => json!({}),
+        ShadowBanReply::FakeRoom | ShadowBanReply::CreateRoom => json!({ "room_id": generate_room_id(server_name) }),
+        ShadowBanReply::FakeUpgrade => json!({ "replacement_room": generate_room_id(server_name) }),
+    };
+    Json(body).into_response()
+}
+
 fn is_shadow_ban_exempt_path(path: &str) -> bool {
     path.starts_with("/_synapse/admin/")
 }
@@ -240,4 +315,33 @@ mod tests {
         assert!(!is_shadow_ban_exempt_path("/_matrix/client/v3/createRoom"));
         assert!(!is_shadow_ban_exempt_path("/_matrix/client/v1/rendezvous"));
     }
+
+    #[test]
+    fn test_shadow_ban_drops_event_sends_and_invites() {
+        let send = "/_matrix/client/v3/rooms/!r:localhost/send/m.room.message/txn1";
+        assert_eq!(shadow_ban_reply(&Method::PUT, send), Some(ShadowBanReply::FakeEvent));
+        let state = "/_matrix/client/v3/rooms/!r:localhost/state/m.room.topic/";
+        assert_eq!(shadow_ban_reply(&Method::PUT, state), Some(ShadowBanReply::FakeEvent));
+        let invite = "/_matrix/client/r0/rooms/!r:localhost/invite";
+        assert_eq!(shadow_ban_reply(&Method::POST, invite), Some(ShadowBanReply::Empty));
+        let typing = "/_matrix/client/v3/rooms/!r:localhost/typing/@u:localhost";
+        assert_eq!(shadow_ban_reply(&Method::PUT, typing), Some(ShadowBanReply::Empty));
+        assert_eq!(shadow_ban_reply(&Method::POST, "/_matrix/client/v3/createRoom"), Some(ShadowBanReply::CreateRoom));
+    }
+
+    #[test]
+    fn test_shadow_ban_lets_other_writes_through() {
+        assert_eq!(shadow_ban_reply(&Method::POST, "/_matrix/client/v3/rooms/!r:localhost/join"), None);
+        assert_eq!(shadow_ban_reply(&Method::POST, "/_matrix/client/v3/rooms/!r:localhost/leave"), None);
+        assert_eq!(shadow_ban_reply(&Method::PUT, "/_matrix/client/v3/sendToDevice/m.room_key/txn1"), None);
+        assert_eq!(shadow_ban_reply(&Method::POST, "/_matrix/client/v3/keys/upload"), None);
+        assert_eq!(shadow_ban_reply(&Method::GET, "/_matrix/client/v3/rooms/!r:localhost/state/m.room.topic/"), None);
+    }
+
+    #[test]
+    fn test_create_room_is_only_dropped_with_invites() {
+        assert!(create_room_invites_anyone(&json!({ "invite": ["@bob:localhost"] })));
+        assert!(create_room_invites_anyone(&json!({ "invite_3pid": [{ "medium": "email" }] })));
+        assert!(!create_room_invites_anyone(&json!({ "invite": [], "name": "room" })));
+    }
 }
@@ -0,0 +1,124 @@
+use crate::common::error::ApiError;
+use crate::web::middleware::auth::extract_token;
+use crate::web::routes::context::CoreContext;
+use crate::web::utils::auth::masquerade_user_id;
+use axum::extract::State;
+use axum::http::{HeaderValue, Request};
+use axum::response::{IntoResponse, Response};
+use axum::{body::Body, middleware::Next};
+use std::sync::Arc;
+use tokio::sync::Semaphore;
+
+/// Suggested wait before retrying a request rejected for concurrency; a slot
+/// frees as soon as one of the user's in-flight requests completes.
+const CONCURRENCY_RETRY_AFTER_MS: u64 = 1_000;
+
+/// Idle semaphores are pruned once the map holds this many entries.
+const SEMAPHORE_PRUNE_THRESHOLD: usize = 4_096;
+
+/// Caps the concurrent in-flight requests of each user on the endpoints
+/// matched by `rate_limit.concurrency.rules`, answering 429 beyond the cap.
+/// Requests without a valid token pass through for the handler to reject.
+pub async fn concurrency_limit_middleware(
+    State(ctx): State<CoreContext>,
+    request: Request<Body>,
+    next: Next,
+) -> Response {
+    let concurrency = &ctx.config.rate_limit.concurrency;
+    if !concurrency.enabled {
+        return next.run(request).await;
+    }
+    let Some(rule) = concurrency.rule_for(request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let uri = request.uri().to_string();
+    let Some(token) = extract_token(request.headers(), &uri) else {
+        return next.run(request).await;
+    };
+    let (user_id, is_admin) = match ctx.token_auth.validate_token_as(&token, masquerade_user_id(&uri).as_deref()).await
+    {
+        Ok((user_id, _, is_admin, _, _)) => (user_id, is_admin),
+        Err(_) => return next.run(request).await,
+    };
+    if is_admin && concurrency.exempt_admins {
+        return next.run(request).await;
+    }
+
+    let key = format!("{}:{}", rule.name, user_id);
+    let semaphore = {
+        let mut guard = ctx.user_request_semaphores.lock().await;
+        if guard.len() >= SEMAPHORE_PRUNE_THRESHOLD && !guard.contains_key(&key) {
+            guard.retain(|_, semaphore| Arc::strong_count(semaphore) > 1);
+        }
+        guard.entry(key).or_insert_with(|| Arc::new(Semaphore::new(rule.max_concurrent.max(1) as usize))).clone()
+    };
+
+    let Ok(_permit) = semaphore.try_acquire_owned() else {
+        tracing::info!(
+            user_id = %user_id,
+            rule = %rule.name,
+            max_concurrent = rule.max_concurrent,
+            "Rejecting request over the per-user concurrency limit"
+        );
+        let mut response = ApiError::rate_limited_with_retry(CONCURRENCY_RETRY_AFTER_MS).into_response();
+        response.headers_mut().insert("retry-after", HeaderValue::from(CONCURRENCY_RETRY_AFTER_MS.div_ceil(1000)));
+        return response;
+    };
+
+    next.run(request).await
+}
+
+#[cfg(test)]
+mod tests {
+    #[cfg(feature = "test-utils")]
+    use super::*;
+    #[cfg(feature = "test-utils")]
+    use crate::cache::{CacheConfig, CacheManager};
+    #[cfg(feature = "test-utils")]
+    use crate::common::config::{ConcurrencyLimitConfig, ConcurrencyLimitRule};
+    #[cfg(feature = "test-utils")]
+    use crate::web::routes::AppState;
+    #[cfg(feature = "test-utils")]
+    use axum::http::StatusCode;
+    #[cfg(feature = "test-utils")]
+    use axum::{middleware, routing::get, Router};
+    #[cfg(feature = "test-utils")]
+    use synapse_services::ServiceContainer;
+    #[cfg(feature = "test-utils")]
+    use tower::ServiceExt;
+
+    #[cfg(feature = "test-utils")]
+    #[tokio::test]
+    async fn test_concurrency_limit_passes_unauthenticated_and_unmatched_requests() {
+        async fn ok_handler() -> StatusCode {
+            StatusCode::OK
+        }
+
+        let mut services = ServiceContainer::new_test().await;
+        services.core.config.rate_limit.concurrency = ConcurrencyLimitConfig {
+            enabled: true,
+            rules: vec![ConcurrencyLimitRule {
+                name: "search".to_string(),
+                paths: vec!["/_matrix/client/*/search".to_string()],
+                max_concurrent: 0,
+            }],
+            exempt_admins: true,
+        };
+
+        let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+        let state = AppState::new(services, cache);
+        let app = Router::new()
+            .route("/_matrix/client/v3/search", get(ok_handler))
+            .route("/_matrix/client/v3/sync", get(ok_handler))
+            .layer(middleware::from_fn_with_state(state.clone(), concurrency_limit_middleware))
+            .with_state(state.clone());
+
+        for uri in ["/_matrix/client/v3/search", "/_matrix/client/v3/sync"] {
+            let request = Request::builder().uri(uri).body(Body::empty()).expect("request should build");
+            let response = app.clone().oneshot(request).await.expect("request should return a response");
+            assert_eq!(response.status(), StatusCode::OK);
+        }
+        assert!(state.user_request_semaphores.lock().await.is_empty());
+    }
+}
@@ -213,6 +213,8 @@ pub async fn cors_middleware(request: Request<Body>, next: axum::middleware::Nex
             "x-matrix",
             "unstable-prefix",
             "accept-language",
+            "if-match",
+            "if-none-match",
         ];
 
         let allow_headers_value = if let Some(ref req_headers) = request_headers {
@@ -237,7 +239,7 @@ pub async fn cors_middleware(request: Request<Body>, next: axum::middleware::Nex
         response.headers_mut().insert(
             "Access-Control-Expose-Headers",
             HeaderValue::from_static(
-                "X-Request-ID, X-CSRF-Token, X-Matrix-Error, X-Ratelimit-Limit, X-Ratelimit-Remaining, X-Ratelimit-Retry-After",
+                "X-Request-ID, X-CSRF-Token, X-Matrix-Error, X-Ratelimit-Limit, X-Ratelimit-Remaining, X-Ratelimit-Retry-After, ETag, Expires, Last-Modified",
             ),
         );
         if allow_credentials {
@@ -271,7 +273,7 @@ pub async fn cors_middleware(request: Request<Body>, next: axum::middleware::Nex
     response.headers_mut().insert(
         "Access-Control-Expose-Headers",
         HeaderValue::from_static(
-            "X-Request-ID, X-CSRF-Token, X-Matrix-Error, X-Ratelimit-Limit, X-Ratelimit-Remaining, X-Ratelimit-Retry-After",
+            "X-Request-ID, X-CSRF-Token, X-Matrix-Error, X-Ratelimit-Limit, X-Ratelimit-Remaining, X-Ratelimit-Retry-After, ETag, Expires, Last-Modified",
         ),
     );
 
@@ -320,20 +320,30 @@ async fn verify_federation_signature(
     }
 }
 
+/// Longest head start a remote verify key is refreshed with before its cache
+/// entry expires.
+const VERIFY_KEY_REFRESH_AHEAD_MAX_SECS: u64 = 300;
+/// How long a claimed (or failed) background refresh suppresses another one.
+const VERIFY_KEY_REFRESH_RETRY_SECS: u64 = 30;
+
 async fn get_federation_verify_key(
     ctx: &FederationContext,
     origin: &str,
     key_id: &str,
     key_fetch_priority: bool,
 ) -> Result<[u8; 32], ApiError> {
+    let is_local = origin == ctx.server_name || origin == ctx.config.federation.server_name;
     let cache_key = format!("federation:verify_key:{origin}:{key_id}");
     if let Ok(Some(cached)) = ctx.cache.get::<String>(&cache_key).await {
         if let Ok(key) = decode_ed25519_public_key(&cached) {
+            if !is_local {
+                refresh_verify_key_if_expiring(ctx, origin, key_id).await;
+            }
             return Ok(key);
         }
     }
 
-    if origin == ctx.server_name || origin == ctx.config.federation.server_name {
+    if is_local {
         if let Some(key) = get_local_verify_key(ctx, key_id).await {
             let key_str = base64::engine::general_purpose::STANDARD_NO_PAD.encode(key);
             let ttl = 3600u64;
@@ -345,11 +355,71 @@ async fn get_federation_verify_key(
     }
 
     let fetched = fetch_federation_verify_key(ctx, origin, key_id, key_fetch_priority).await?;
-    let ttl = 3600u64;
-    if let Err(e) = ctx.cache.set(&cache_key, &fetched, ttl).await {
+    cache_remote_verify_key(ctx, origin, key_id, &fetched).await;
+    decode_ed25519_public_key(&fetched.key).map_err(|_| ApiError::unauthorized("Invalid public key".to_string()))
+}
+
+/// Cache a fetched remote key for `key_cache_ttl`, cut short by the key's
+/// `valid_until_ts`, together with a freshness marker that lapses shortly
+/// before the key entry does.
+async fn cache_remote_verify_key(ctx: &FederationContext, origin: &str, key_id: &str, fetched: &FetchedVerifyKey) {
+    let (key_ttl, fresh_ttl) = verify_key_cache_ttls(
+        ctx.config.federation.key_cache_ttl,
+        fetched.valid_until_ts,
+        synapse_common::current_timestamp_millis(),
+    );
+    let cache_key = format!("federation:verify_key:{origin}:{key_id}");
+    if let Err(e) = ctx.cache.set(&cache_key, &fetched.key, key_ttl).await {
         tracing::warn!(origin = %origin, key_id = %key_id, "Failed to cache fetched federation verify key: {e}");
     }
-    decode_ed25519_public_key(&fetched).map_err(|_| ApiError::unauthorized("Invalid public key".to_string()))
+    let fresh_key = format!("federation:verify_key_fresh:{origin}:{key_id}");
+    if let Err(e) = ctx.cache.set(&fresh_key, true, fresh_ttl).await {
+        tracing::debug!(origin = %origin, key_id = %key_id, "Failed to mark federation verify key fresh: {e}");
+    }
+}
+
+/// `(key_ttl, fresh_ttl)` in seconds for a remote verify key.
+fn verify_key_cache_ttls(configured_ttl: u64, valid_until_ts: Option<i64>, now_ms: i64) -> (u64, u64) {
+    let configured_ttl = configured_ttl.max(60);
+    let key_ttl = match valid_until_ts {
+        Some(valid_until_ts) => configured_ttl.min(((valid_until_ts - now_ms) / 1000).max(1) as u64),
+        None => configured_ttl,
+    };
+    let refresh_ahead = (key_ttl / 5).min(VERIFY_KEY_REFRESH_AHEAD_MAX_SECS);
+    (key_ttl, key_ttl.saturating_sub(refresh_ahead).max(1))
+}
+
+/// Refetch a cached remote key in the background once its freshness marker
+/// has lapsed, so PDUs arriving around expiry are still verified from cache
+/// instead of all stalling on a key fetch.
+async fn refresh_verify_key_if_expiring(ctx: &FederationContext, origin: &str, key_id: &str) {
+    let fresh_key = format!("federation:verify_key_fresh:{origin}:{key_id}");
+    if let Ok(Some(true)) = ctx.cache.get::<bool>(&fresh_key).await {
+        return;
+    }
+    // Claim the refresh so concurrent requests do not all refetch.
+    if let Err(e) = ctx.cache.set(&fresh_key, true, VERIFY_KEY_REFRESH_RETRY_SECS).await {
+        tracing::debug!(origin = %origin, key_id = %key_id, "Failed to claim federation verify key refresh: {e}");
+        return;
+    }
+
+    let ctx = ctx.clone();
+    let origin = origin.to_string();
+    let key_id = key_id.to_string();
+    tokio::spawn(async move {
+        match fetch_federation_verify_key(&ctx, &origin, &key_id, false).await {
+            Ok(fetched) => {
+                cache_remote_verify_key(&ctx, &origin, &key_id, &fetched).await;
+                tracing::debug!(origin = %origin, key_id = %key_id, "Refreshed federation verify key ahead of expiry");
+            }
+            Err(e) => tracing::debug!(
+                origin = %origin,
+                key_id = %key_id,
+                error = %e,
+                "Background federation verify key refresh failed; keeping the cached key until it expires"
+            ),
+        }
+    });
 }
 
 async fn get_local_verify_key(ctx: &FederationContext, key_id: &str) -> Option<[u8; 32]> {
@@ -360,57 +430,24 @@ async fn get_local_verify_key(ctx: &FederationContext, key_id: &str) -> Option<[
     }
 
     let config_key_id = config.key_id.as_deref().unwrap_or("ed25519:1");
-    if key_id != config_key_id {
-        if ctx.key_rotation_manager.load_or_create_key().await.is_err() {
-            return None;
+    if key_id == config_key_id {
+        if let Some(signing_key) = config.signing_key.as_deref() {
+            let signing_key_bytes = decode_base64_32(signing_key)?;
+            let signing_key = ed25519_dalek::SigningKey::from_bytes(&signing_key_bytes);
+            let verifying_key = signing_key.verifying_key();
+            return Some(*verifying_key.as_bytes());
         }
-
-        let current_key = match ctx.key_rotation_manager.get_current_key().await {
-            Ok(Some(key)) => key,
-            Ok(None) => {
-                ::tracing::error!("No current federation signing key available");
-                return None;
-            }
-            Err(e) => {
-                ::tracing::error!(error = %e, "Failed to fetch current federation signing key from DB");
-                return None;
-            }
-        };
-
-        if current_key.key_id != key_id {
-            return None;
-        }
-
-        return match decode_ed25519_public_key(&current_key.public_key) {
-            Ok(key) => Some(key),
-            Err(_) => {
-                ::tracing::warn!(
-                    key_id = %key_id,
-                    "Failed to decode stored federation signing key — federation signature verification will fail for this key"
-                );
-                None
-            }
-        };
-    }
-
-    if let Some(signing_key) = config.signing_key.as_deref() {
-        let signing_key_bytes = decode_base64_32(signing_key)?;
-        let signing_key = ed25519_dalek::SigningKey::from_bytes(&signing_key_bytes);
-        let verifying_key = signing_key.verifying_key();
-        return Some(*verifying_key.as_bytes());
     }
 
-    if ctx.key_rotation_manager.load_or_create_key().await.is_err() {
-        return None;
-    }
-
-    let current_key = ctx.key_rotation_manager.get_current_key().await.ok().flatten()?;
-
-    if current_key.key_id != key_id {
+    if let Err(e) = ctx.key_rotation_manager.load_or_create_key().await {
+        ::tracing::error!(error = %e, "Failed to load federation signing keys from DB");
         return None;
     }
 
-    match decode_ed25519_public_key(&current_key.public_key) {
+    // Rotated-out keys are still accepted: events we signed before a
+    // rotation carry the old key ID.
+    let public_key = ctx.key_rotation_manager.get_verify_key(key_id).await?;
+    match decode_ed25519_public_key(&public_key) {
         Ok(key) => Some(key),
         Err(_) => {
             ::tracing::warn!(
@@ -422,12 +459,17 @@ async fn get_local_verify_key(ctx: &FederationContext, key_id: &str) -> Option<[
     }
 }
 
+struct FetchedVerifyKey {
+    key: String,
+    valid_until_ts: Option<i64>,
+}
+
 async fn fetch_federation_verify_key(
     ctx: &FederationContext,
     origin: &str,
     key_id: &str,
     key_fetch_priority: bool,
-) -> Result<String, ApiError> {
+) -> Result<FetchedVerifyKey, ApiError> {
     let backoff_key = format!("federation:key_fetch_backoff:{origin}:{key_id}");
     if let Ok(Some(true)) = ctx.cache.get::<bool>(&backoff_key).await {
         return Err(ApiError::unauthorized("Public key not found".to_string()));
@@ -445,11 +487,11 @@ async fn fetch_federation_verify_key(
         .map_err(|e| ApiError::internal_with_log("Rate limit semaphore closed", &e))?;
 
     let timeout_ms = ctx.config.federation.key_fetch_timeout_ms.max(1);
-    let client = reqwest::Client::builder()
+    let client = synapse_common::outbound_http::client_builder()
         .timeout(std::time::Duration::from_millis(timeout_ms))
         .redirect(reqwest::redirect::Policy::none())
         .build()
-        .map_err(|e| ApiError::internal(e.to_string()))?;
+        .map_err(|e| ApiError::internal_with_log("Failed to build federation HTTP client", &e))?;
 
     // SSRF protection: reuse the URL preview IP blacklist to block private/loopback addresses.
     // When `allow_http_key_fetch` is set (test/dev only), HTTP is used and SSRF checks are skipped.
@@ -482,9 +524,17 @@ async fn fetch_federation_verify_key(
         };
         if let Some(key) = extract_verify_key_from_server_keys(&json, origin, key_id) {
             if verify_server_keys_signature(&json, origin, key_id, &key) {
-                return Ok(key);
+                let valid_until_ts = extract_valid_until_ts_from_server_keys(&json, origin);
+                return Ok(FetchedVerifyKey { key, valid_until_ts });
             }
             tracing::warn!("Server keys signature verification failed for {} key_id={}", origin, key_id);
+        } else if let Some(key) = extract_old_verify_key_from_server_keys(&json, key_id) {
+            // A rotated-out key is vouched for by the server's current keys,
+            // not by itself. It no longer expires, so no validity is passed on.
+            if verify_server_keys_self_signature(&json, origin) {
+                return Ok(FetchedVerifyKey { key, valid_until_ts: None });
+            }
+            tracing::warn!("Server keys signature verification failed for {} old key_id={}", origin, key_id);
         }
     }
 
@@ -513,6 +563,18 @@ fn extract_verify_key_from_server_keys(body: &Value, origin: &str, key_id: &str)
     None
 }
 
+fn extract_valid_until_ts_from_server_keys(body: &Value, origin: &str) -> Option<i64> {
+    if let Some(ts) = body.get("valid_until_ts").and_then(|v| v.as_i64()) {
+        return Some(ts);
+    }
+
+    body.get("server_keys")?
+        .as_array()?
+        .iter()
+        .filter(|entry| entry.get("server_name").and_then(|v| v.as_str()).is_none_or(|v| v == origin))
+        .find_map(|entry| entry.get("valid_until_ts").and_then(|v| v.as_i64()))
+}
+
 fn extract_verify_key_from_server_keys_object(body: &Value, key_id: &str) -> Option<String> {
     let verify_keys = body.get("verify_keys")?.as_object()?;
     if let Some(entry) = verify_keys.get(key_id) {
@@ -523,6 +585,21 @@ fn extract_verify_key_from_server_keys_object(body: &Value, key_id: &str) -> Opt
     None
 }
 
+fn extract_old_verify_key_from_server_keys(body: &Value, key_id: &str) -> Option<String> {
+    body.get("old_verify_keys")?.get(key_id)?.get("key")?.as_str().map(str::to_string)
+}
+
+fn verify_server_keys_self_signature(body: &Value, origin: &str) -> bool {
+    body.get("verify_keys").and_then(|v| v.as_object()).is_some_and(|verify_keys| {
+        verify_keys.iter().any(|(key_id, entry)| {
+            entry
+                .get("key")
+                .and_then(|v| v.as_str())
+                .is_some_and(|key| verify_server_keys_signature(body, origin, key_id, key))
+        })
+    })
+}
+
 fn verify_server_keys_signature(body: &Value, origin: &str, key_id: &str, verify_key: &str) -> bool {
     let signature =
         match body.get("signatures").and_then(|s| s.get(origin)).and_then(|s| s.get(key_id)).and_then(|s| s.as_str()) {
@@ -645,6 +722,65 @@ mod tests {
         assert_eq!(key, Some("SGVsbG9Xb3JsZA".to_string()));
     }
 
+    #[test]
+    fn test_extract_valid_until_ts_from_server_key_responses() {
+        let direct = serde_json::json!({ "server_name": "example.org", "valid_until_ts": 1_000 });
+        assert_eq!(extract_valid_until_ts_from_server_keys(&direct, "example.org"), Some(1_000));
+
+        let query = serde_json::json!({
+            "server_keys": [
+                { "server_name": "other.org", "valid_until_ts": 5 },
+                { "server_name": "example.org", "valid_until_ts": 2_000 }
+            ]
+        });
+        assert_eq!(extract_valid_until_ts_from_server_keys(&query, "example.org"), Some(2_000));
+        assert_eq!(extract_valid_until_ts_from_server_keys(&serde_json::json!({}), "example.org"), None);
+    }
+
+    #[test]
+    fn test_verify_key_cache_ttls_bounded_by_validity_and_refreshed_ahead() {
+        let now_ms = 1_700_000_000_000;
+
+        // No validity: configured TTL, refreshed at most five minutes early.
+        assert_eq!(verify_key_cache_ttls(3600, None, now_ms), (3600, 3300));
+        // Key expiring in ten minutes: the cache entry must not outlive it.
+        assert_eq!(verify_key_cache_ttls(3600, Some(now_ms + 600_000), now_ms), (600, 480));
+        // Already expired keys are cached for a second and refreshed at once.
+        assert_eq!(verify_key_cache_ttls(3600, Some(now_ms - 1), now_ms), (1, 1));
+        // Tiny configured TTLs are raised to a minute.
+        assert_eq!(verify_key_cache_ttls(5, None, now_ms), (60, 48));
+    }
+
+    #[test]
+    fn test_old_verify_key_is_vouched_for_by_current_key() {
+        let current = ed25519_dalek::SigningKey::from_bytes(&[0x21; 32]);
+        let old = ed25519_dalek::SigningKey::from_bytes(&[0x22; 32]);
+        let b64 = |bytes: &[u8]| base64::engine::general_purpose::STANDARD_NO_PAD.encode(bytes);
+
+        let mut body = serde_json::json!({
+            "server_name": "example.org",
+            "verify_keys": { "ed25519:new": { "key": b64(current.verifying_key().as_bytes()) } },
+            "old_verify_keys": {
+                "ed25519:old": { "key": b64(old.verifying_key().as_bytes()), "expired_ts": 1_700_000_000_000_i64 }
+            },
+            "valid_until_ts": 1_800_000_000_000_i64
+        });
+        crate::federation::signing::sign_json("example.org", "ed25519:new", &b64(current.as_bytes()), &mut body)
+            .unwrap();
+
+        assert_eq!(extract_verify_key_from_server_keys(&body, "example.org", "ed25519:old"), None);
+        assert_eq!(
+            extract_old_verify_key_from_server_keys(&body, "ed25519:old"),
+            Some(b64(old.verifying_key().as_bytes()))
+        );
+        assert!(verify_server_keys_self_signature(&body, "example.org"));
+
+        // Someone else's signature does not vouch for example.org's old keys.
+        assert!(!verify_server_keys_self_signature(&body, "evil.example"));
+        body["old_verify_keys"]["ed25519:old"]["key"] = serde_json::json!(b64(current.verifying_key().as_bytes()));
+        assert!(!verify_server_keys_self_signature(&body, "example.org"));
+    }
+
     #[test]
     fn test_parse_x_matrix_authorization_header() {
         let params =
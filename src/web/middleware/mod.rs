@@ -1,4 +1,5 @@
 pub mod auth;
+pub mod concurrency_limit;
 pub mod cors;
 pub mod csrf;
 pub mod federation_auth;
@@ -7,6 +8,7 @@ pub mod rate_limit;
 pub mod security;
 
 pub use auth::*;
+pub use concurrency_limit::*;
 pub use cors::*;
 pub use csrf::*;
 pub use federation_auth::*;
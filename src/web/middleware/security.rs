@@ -1,11 +1,14 @@
 use crate::common::error::ApiError;
 use crate::web::utils::auth::resolve_request_id;
-use axum::body::Body;
+use axum::body::{Body, HttpBody};
+use axum::extract::MatchedPath;
 use axum::http::{HeaderValue, Request};
 use axum::middleware::Next;
 use axum::response::IntoResponse;
 use axum::response::Response;
+use std::sync::{Arc, OnceLock};
 use std::time::Instant;
+use tracing::Instrument;
 
 pub async fn logging_middleware(request: Request<Body>, next: axum::middleware::Next) -> Response {
     let start = Instant::now();
@@ -165,22 +168,95 @@ fn is_long_polling_endpoint(path: &str) -> bool {
         || path.contains("/_matrix/client/unstable/org.matrix.simplified_msc3575/sync")
 }
 
+tokio::task_local! {
+    static REQUEST_USER: Arc<OnceLock<String>>;
+}
+
+/// Record the authenticated user of the request being served, for its span
+/// and access log line. A no-op outside [`request_id_middleware`].
+pub(crate) fn record_request_user(user_id: &str) {
+    tracing::Span::current().record("user_id", user_id);
+    let _ = REQUEST_USER.try_with(|slot| slot.set(user_id.to_string()));
+}
+
+/// Assigns every request an ID (the inbound `X-Request-Id` when acceptable),
+/// runs it inside a `request` span carrying that ID, echoes the ID on the
+/// response and in JSON error bodies, and emits one `access_log` line.
 pub async fn request_id_middleware(mut request: Request<Body>, next: Next) -> Response {
+    let start = Instant::now();
     let request_id = resolve_request_id(request.headers());
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map_or_else(|| UNMATCHED_ROUTE.to_string(), |path| path.as_str().to_string());
 
     if let Ok(v) = HeaderValue::from_str(&request_id) {
         request.headers_mut().insert("x-request-id", v);
     }
 
-    let mut response = next.run(request).await;
+    let span = tracing::info_span!(
+        "request",
+        request_id = %request_id,
+        method = %method,
+        route = %route,
+        user_id = tracing::field::Empty,
+    );
+    let user = Arc::new(OnceLock::new());
+    let response = REQUEST_USER.scope(user.clone(), next.run(request)).instrument(span.clone()).await;
+    let mut response = attach_request_id_to_error(response, &request_id).await;
 
     if let Ok(v) = HeaderValue::from_str(&request_id) {
         response.headers_mut().insert("x-request-id", v);
     }
 
+    span.in_scope(|| {
+        tracing::info!(
+            target: "access_log",
+            method = %method,
+            route = %route,
+            status = response.status().as_u16(),
+            duration_ms = start.elapsed().as_millis() as u64,
+            user_id = user.get().map(String::as_str),
+            "request completed"
+        );
+    });
+
     response
 }
 
+/// Route label used in access logs for requests that matched no route.
+const UNMATCHED_ROUTE: &str = "<unmatched>";
+
+/// Adds `request_id` to Matrix JSON error bodies so clients can quote it
+/// when reporting a failure. Other responses are returned untouched.
+async fn attach_request_id_to_error(response: Response, request_id: &str) -> Response {
+    let status = response.status();
+    let is_json = response
+        .headers()
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    let small = response.body().size_hint().upper().is_some_and(|len| len <= MAX_ERROR_BODY_BYTES as u64);
+    if !(status.is_client_error() || status.is_server_error()) || !is_json || !small {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let Ok(bytes) = axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES).await else {
+        return Response::from_parts(parts, Body::empty());
+    };
+    let body = match serde_json::from_slice::<serde_json::Value>(&bytes) {
+        Ok(serde_json::Value::Object(mut map)) if map.contains_key("errcode") => {
+            map.entry("request_id").or_insert_with(|| request_id.into());
+            parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+            Body::from(serde_json::Value::Object(map).to_string())
+        }
+        _ => Body::from(bytes),
+    };
+    Response::from_parts(parts, body)
+}
+
 /// Converts axum's default empty-body 405 responses into Matrix-compliant
 /// M_UNRECOGNIZED JSON. The Router::fallback handles path-not-found (404);
 /// this middleware catches method-not-allowed (405) which axum auto-generates
@@ -214,6 +290,69 @@ pub async fn method_not_allowed_middleware(request: Request<Body>, next: Next) -
         .into_response()
 }
 
+/// Largest error body inspected when re-shaping a non-JSON error response.
+const MAX_ERROR_BODY_BYTES: usize = 16 * 1024;
+
+/// Rewrites error responses that are not already JSON into the spec's
+/// `{"errcode", "error"}` shape. Axum's extractor rejections (bad path
+/// segments, query strings or JSON bodies) answer with plain text, which
+/// remote homeservers cannot parse. Layered on the federation router.
+pub async fn matrix_error_shape_middleware(request: Request<Body>, next: Next) -> Response {
+    let response = next.run(request).await;
+
+    let status = response.status();
+    if !(status.is_client_error() || status.is_server_error()) {
+        return response;
+    }
+    let is_json = response
+        .headers()
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|v| v.starts_with("application/json"));
+    if is_json {
+        return response;
+    }
+
+    let (mut parts, body) = response.into_parts();
+    let text = axum::body::to_bytes(body, MAX_ERROR_BODY_BYTES)
+        .await
+        .map(|bytes| String::from_utf8_lossy(&bytes).trim().to_string())
+        .unwrap_or_default();
+    let (status, errcode) = matrix_error_for_status(status, &text);
+    let message =
+        if text.is_empty() { status.canonical_reason().unwrap_or("Request failed").to_string() } else { text };
+
+    parts.headers.remove(axum::http::header::CONTENT_LENGTH);
+    parts.headers.remove(axum::http::header::CONTENT_TYPE);
+    let mut shaped = (status, axum::Json(serde_json::json!({ "errcode": errcode, "error": message }))).into_response();
+    for (name, value) in parts.headers.iter() {
+        shaped.headers_mut().entry(name.clone()).or_insert_with(|| value.clone());
+    }
+    shaped
+}
+
+/// Spec status and `errcode` for a bare error response. Extractor rejections
+/// that axum reports as 415/422 become `400` as the spec requires.
+pub(crate) fn matrix_error_for_status(
+    status: axum::http::StatusCode,
+    body: &str,
+) -> (axum::http::StatusCode, &'static str) {
+    use axum::http::StatusCode;
+    match status {
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => (StatusCode::BAD_REQUEST, "M_NOT_JSON"),
+        StatusCode::UNPROCESSABLE_ENTITY => (StatusCode::BAD_REQUEST, "M_BAD_JSON"),
+        StatusCode::BAD_REQUEST if body.contains("JSON") => (status, "M_NOT_JSON"),
+        StatusCode::BAD_REQUEST => (status, "M_INVALID_PARAM"),
+        StatusCode::UNAUTHORIZED => (status, "M_UNAUTHORIZED"),
+        StatusCode::FORBIDDEN => (status, "M_FORBIDDEN"),
+        StatusCode::NOT_FOUND => (status, "M_NOT_FOUND"),
+        StatusCode::METHOD_NOT_ALLOWED => (status, "M_UNRECOGNIZED"),
+        StatusCode::PAYLOAD_TOO_LARGE => (status, "M_TOO_LARGE"),
+        StatusCode::TOO_MANY_REQUESTS => (status, "M_LIMIT_EXCEEDED"),
+        _ => (status, "M_UNKNOWN"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -334,6 +473,34 @@ mod tests {
         assert_eq!(header_value, body_text);
     }
 
+    #[tokio::test]
+    async fn test_request_id_middleware_honors_inbound_id_in_error_body() {
+        async fn failing_handler() -> Response {
+            record_request_user("@alice:localhost");
+            ApiError::not_found("Room not found".to_string()).into_response()
+        }
+
+        let app = Router::new().route("/fails", get(failing_handler)).layer(middleware::from_fn(request_id_middleware));
+        let request = Request::builder()
+            .uri("/fails")
+            .header("x-request-id", "client-abc")
+            .body(Body::empty())
+            .expect("request should build");
+
+        let response = app.oneshot(request).await.expect("request should succeed");
+        assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        assert_eq!(response.headers().get("x-request-id").and_then(|v| v.to_str().ok()), Some("client-abc"));
+        let body = axum::body::to_bytes(response.into_body(), 1024).await.expect("body should be readable");
+        let json: serde_json::Value = serde_json::from_slice(&body).expect("error body should be JSON");
+        assert_eq!(json["errcode"], "M_NOT_FOUND");
+        assert_eq!(json["request_id"], "client-abc");
+    }
+
+    #[test]
+    fn test_record_request_user_outside_request_is_noop() {
+        record_request_user("@alice:localhost");
+    }
+
     #[tokio::test]
     async fn test_method_not_allowed_passthrough_with_body() {
         use axum::{
@@ -368,4 +535,61 @@ mod tests {
         let body_str = String::from_utf8(body.to_vec()).unwrap();
         assert_eq!(body_str, r#"{"error":"custom"}"#);
     }
+
+    #[test]
+    fn test_matrix_error_for_status_maps_extractor_rejections() {
+        assert_eq!(
+            matrix_error_for_status(StatusCode::UNPROCESSABLE_ENTITY, "Failed to deserialize the JSON body"),
+            (StatusCode::BAD_REQUEST, "M_BAD_JSON")
+        );
+        assert_eq!(
+            matrix_error_for_status(StatusCode::UNSUPPORTED_MEDIA_TYPE, ""),
+            (StatusCode::BAD_REQUEST, "M_NOT_JSON")
+        );
+        assert_eq!(
+            matrix_error_for_status(StatusCode::BAD_REQUEST, "Invalid URL: bad segment"),
+            (StatusCode::BAD_REQUEST, "M_INVALID_PARAM")
+        );
+        assert_eq!(matrix_error_for_status(StatusCode::BAD_GATEWAY, ""), (StatusCode::BAD_GATEWAY, "M_UNKNOWN"));
+    }
+
+    #[tokio::test]
+    async fn test_matrix_error_shape_middleware_wraps_plain_text_rejections() {
+        async fn handler(axum::Json(_body): axum::Json<serde_json::Value>) -> StatusCode {
+            StatusCode::OK
+        }
+        async fn already_json() -> ApiError {
+            ApiError::forbidden("nope".to_string())
+        }
+
+        let app = Router::new()
+            .route("/json", axum::routing::put(handler))
+            .route("/forbidden", get(already_json))
+            .layer(middleware::from_fn(matrix_error_shape_middleware));
+
+        let response = app
+            .clone()
+            .oneshot(
+                Request::builder()
+                    .method("PUT")
+                    .uri("/json")
+                    .header("content-type", "application/json")
+                    .body(Body::from("{not json"))
+                    .unwrap(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["errcode"], "M_NOT_JSON");
+        assert!(json["error"].as_str().is_some_and(|e| !e.is_empty()));
+
+        let response = app.oneshot(Request::builder().uri("/forbidden").body(Body::empty()).unwrap()).await.unwrap();
+        assert_eq!(response.status(), StatusCode::FORBIDDEN);
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+        assert_eq!(json["errcode"], "M_FORBIDDEN");
+        assert_eq!(json["error"], "nope");
+    }
 }
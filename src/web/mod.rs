@@ -4,6 +4,7 @@ pub mod middleware;
 pub mod routes;
 pub mod streaming;
 pub(crate) mod utils;
+pub(crate) mod websocket;
 
 pub use api_doc::swagger_ui_router;
 pub use filter::*;
@@ -1,4 +1,4 @@
-use super::auth_compat::{request_email_verification_with_submit_path, session_client_secret};
+use super::auth_compat::{accept_language, request_email_verification_with_submit_path, session_client_secret};
 use crate::common::ApiError;
 use crate::web::extractors::{AuthenticatedUser, MatrixJson, OptionalAuthenticatedUser};
 use crate::web::routes::context::AuthContext;
@@ -394,6 +394,7 @@ pub(crate) async fn request_password_email_verification(
         resolved_user_id.as_deref(),
         "password_reset",
         &request_id,
+        accept_language(&headers),
     )
     .await
 }
@@ -419,8 +420,9 @@ pub(crate) async fn deactivate_account(
     }
 
     let user_id = auth_user.user_id.clone();
+    let erase = body.get("erase").and_then(Value::as_bool).unwrap_or(false);
 
-    ctx.registration_service.deactivate_account(&user_id).await?;
+    ctx.registration_service.deactivate_account(&user_id, erase).await?;
 
     ctx.cache.delete(&format!("user:active:{user_id}")).await;
 
@@ -608,6 +610,7 @@ pub(crate) async fn request_3pid_add_email_verification(
         Some(auth_user.user_id.as_str()),
         "3pid_add",
         &request_id,
+        accept_language(&headers),
     )
     .await
 }
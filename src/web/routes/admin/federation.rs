@@ -13,6 +13,7 @@ use synapse_services::admin_federation_service::{
     decode_destination_cursor, decode_pending_federation_cursor, encode_destination_cursor,
     encode_pending_federation_cursor,
 };
+use synapse_storage::event::RejectedEventFilter;
 use synapse_storage::federation_blacklist::decode_federation_blacklist_cursor;
 use tracing::info;
 
@@ -34,6 +35,8 @@ pub fn create_federation_router() -> Router<crate::web::routes::AppState> {
         .route("/_synapse/admin/v1/federation/cache", get(get_federation_cache))
         .route("/_synapse/admin/v1/federation/cache/{key}", delete(delete_federation_cache_entry))
         .route("/_synapse/admin/v1/federation/cache/clear", post(clear_federation_cache))
+        .route("/_synapse/admin/v1/federation/endpoints", get(get_federation_endpoints))
+        .route("/_synapse/admin/v1/federation/rejected_events", get(get_rejected_events))
 }
 
 pub fn admin_federation_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry> {
@@ -56,6 +59,8 @@ pub fn admin_federation_route_manifest() -> Vec<crate::web::routes::route_ledger
         (Method::GET, "/_synapse/admin/v1/federation/cache"),
         (Method::DELETE, "/_synapse/admin/v1/federation/cache/{key}"),
         (Method::POST, "/_synapse/admin/v1/federation/cache/clear"),
+        (Method::GET, "/_synapse/admin/v1/federation/endpoints"),
+        (Method::GET, "/_synapse/admin/v1/federation/rejected_events"),
     ]
     .into_iter()
     .map(|(m, p)| RouteEntry::new(m, p, "admin::federation"))
@@ -85,6 +90,16 @@ pub struct ListPendingQuery {
     pub from: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct RejectedEventsQuery {
+    pub room_id: Option<String>,
+    pub origin: Option<String>,
+    /// `too_large`, `content_hash`, `signature`, `malformed` or `auth`.
+    pub reason: Option<String>,
+    pub from: Option<String>,
+    pub limit: Option<i64>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct BlacklistQuery {
     pub limit: Option<i32>,
@@ -370,6 +385,75 @@ pub async fn clear_federation_cache(
     Ok(Json(json!({ "deleted": deleted })))
 }
 
+/// Federation endpoints served by this build, from the compile-time registry.
+#[allow(clippy::unused_async)]
+pub async fn get_federation_endpoints(_admin: AdminUser) -> Result<Json<Value>, ApiError> {
+    use crate::web::routes::federation::registry::FEDERATION_ENDPOINTS;
+
+    let endpoints: Vec<Value> = FEDERATION_ENDPOINTS
+        .iter()
+        .map(|endpoint| {
+            json!({
+                "method": endpoint.method,
+                "path": endpoint.path,
+                "auth": endpoint.auth,
+                "spec": endpoint.is_spec(),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "server": {
+            "name": "synapse-rust",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "total": endpoints.len(),
+        "spec_total": FEDERATION_ENDPOINTS.iter().filter(|endpoint| endpoint.is_spec()).count(),
+        "endpoints": endpoints,
+    })))
+}
+
+/// Inbound PDUs rejected before persistence, most recent first. Only the
+/// most recent `federation.rejected_events_max` rejections are kept.
+#[axum::debug_handler]
+pub async fn get_rejected_events(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Query(query): Query<RejectedEventsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let reason = query.reason.as_deref().map(|reason| reason.parse().map_err(ApiError::bad_request)).transpose()?;
+    let before = query
+        .from
+        .as_deref()
+        .map(|from| from.parse::<i64>().map_err(|_| ApiError::bad_request("Invalid from token".to_string())))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(100).clamp(MIN_PAGINATION_LIMIT, MAX_PAGINATION_LIMIT);
+    let filter = RejectedEventFilter { room_id: query.room_id, origin: query.origin, reason, before };
+
+    let events = ctx.room_service.messaging().get_rejected_events(&filter, limit).await?;
+    let next_batch = if events.len() as i64 == limit { events.last().map(|event| event.id.to_string()) } else { None };
+    let events: Vec<Value> = events
+        .iter()
+        .map(|event| {
+            json!({
+                "event_id": event.event_id,
+                "origin": event.origin,
+                "room_id": event.room_id,
+                "sender": event.sender,
+                "type": event.event_type,
+                "reason": event.reason,
+                "detail": event.detail,
+                "rejected_ts": event.rejected_ts
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "events": events,
+        "next_batch": next_batch
+    })))
+}
+
 #[cfg(test)]
 mod destinations_query_tests {
     use super::{validate_destinations_query, DestinationsQuery};
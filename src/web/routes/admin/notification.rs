@@ -19,8 +19,6 @@ use serde::Deserialize;
 #[cfg(feature = "server-notifications")]
 use serde_json::{json, Value};
 #[cfg(feature = "server-notifications")]
-use synapse_common::current_timestamp_millis;
-#[cfg(feature = "server-notifications")]
 use synapse_storage::server_notification::{decode_server_notification_cursor, CreateNotificationRequest};
 
 #[cfg(feature = "server-notifications")]
@@ -331,38 +329,24 @@ pub async fn send_server_notice(
     State(ctx): State<AdminContext>,
     Json(body): Json<ServerNoticeRequest>,
 ) -> Result<Json<Value>, ApiError> {
-    let target_user = ctx.user_service.get_user_by_identifier(&body.user_id).await?;
-    let Some(target_user) = target_user else {
-        return Err(ApiError::not_found("User not found".to_string()));
-    };
-
-    let room_id = format!("!server_notice_{}:{}", uuid::Uuid::new_v4(), ctx.server_name);
-    let now = current_timestamp_millis();
-    let server_user = format!("@server:{}", ctx.server_name);
-    let message_event_id = format!("${}:{}", uuid::Uuid::new_v4(), ctx.server_name);
-    let create_event_id = format!("${}:{}", uuid::Uuid::new_v4(), ctx.server_name);
-    let membership_event_id = format!("${}:{}", uuid::Uuid::new_v4(), ctx.server_name);
-
-    let notice_id = ctx
-        .server_notification_service
-        .send_server_notice(
-            &room_id,
-            &server_user,
-            &target_user.user_id,
-            &target_user.displayname,
-            &target_user.avatar_url,
-            &message_event_id,
-            &create_event_id,
-            &membership_event_id,
-            &body.content.msgtype,
-            &body.content.body,
-            now,
-        )
-        .await?;
+    let (room_id, message_event_id, notice_id) =
+        deliver_server_notice(&ctx, &body.user_id, &body.content.msgtype, &body.content.body).await?;
 
     Ok(Json(json!({ "event_id": message_event_id, "room_id": room_id, "notice_id": notice_id })))
 }
 
+/// Sends a server notice to `user_id` in a fresh notice room and returns the
+/// room ID, the message event ID and the stored notice ID.
+#[cfg(feature = "server-notifications")]
+pub(crate) async fn deliver_server_notice(
+    ctx: &AdminContext,
+    user_id: &str,
+    msgtype: &str,
+    body: &str,
+) -> Result<(String, String, i64), ApiError> {
+    ctx.server_notification_service.notify_user(&ctx.server_name, user_id, msgtype, body).await
+}
+
 #[cfg(feature = "server-notifications")]
 #[axum::debug_handler]
 pub async fn get_server_notices(
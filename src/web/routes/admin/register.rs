@@ -1,6 +1,6 @@
 // 管理后台 - 管理员注册
 // 实现 Synapse 兼容的管理员注册 API
-// API: /_synapse/admin/v1/register/nonce, /_synapse/admin/v1/register
+// API: /_synapse/admin/v1/register/nonce, /_synapse/admin/v1/register, /_synapse/admin/v1/bootstrap
 //
 // 安全说明：此 API 默认仅允许从 localhost (127.0.0.1) 调用
 // 如需从外部调用，请修改 allow_external_access 配置
@@ -24,6 +24,7 @@ use ipnetwork::IpNetwork;
 use serde::{Deserialize, Serialize};
 use std::net::IpAddr;
 use std::net::SocketAddr;
+use synapse_services::admin_bootstrap_service::BootstrapAdminResponse;
 use synapse_services::admin_registration_service::AdminRegisterRequest;
 use synapse_services::captcha_service::VerifyCaptchaRequest;
 use validator::Validate;
@@ -32,16 +33,21 @@ pub fn create_register_router(state: AppState) -> Router<crate::web::routes::App
     Router::new()
         .route("/_synapse/admin/v1/register/nonce", get(get_nonce))
         .route("/_synapse/admin/v1/register", post(register))
+        .route("/_synapse/admin/v1/bootstrap", post(bootstrap))
         .with_state(state)
 }
 
 pub fn admin_register_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry> {
     use crate::web::routes::route_ledger::RouteEntry;
     use axum::http::Method;
-    [(Method::GET, "/_synapse/admin/v1/register/nonce"), (Method::POST, "/_synapse/admin/v1/register")]
-        .into_iter()
-        .map(|(m, p)| RouteEntry::new(m, p, "admin::register"))
-        .collect()
+    [
+        (Method::GET, "/_synapse/admin/v1/register/nonce"),
+        (Method::POST, "/_synapse/admin/v1/register"),
+        (Method::POST, "/_synapse/admin/v1/bootstrap"),
+    ]
+    .into_iter()
+    .map(|(m, p)| RouteEntry::new(m, p, "admin::register"))
+    .collect()
 }
 
 #[derive(Serialize)]
@@ -57,6 +63,7 @@ struct RegisterRequest {
     username: String,
     #[validate(length(min = 8, max = 512))]
     password: String,
+    #[serde(default)]
     admin: bool,
     #[validate(length(max = 255))]
     #[serde(default)]
@@ -77,6 +84,16 @@ struct RegisterRequest {
     approval_token: Option<String>,
 }
 
+#[derive(Deserialize, Validate)]
+struct BootstrapRequest {
+    #[validate(length(min = 1, max = 255))]
+    setup_token: String,
+    #[validate(length(min = 1, max = 255))]
+    username: String,
+    #[validate(length(min = 8, max = 512))]
+    password: String,
+}
+
 #[derive(Serialize)]
 struct RegisterResponse {
     access_token: String,
@@ -93,7 +110,7 @@ fn map_admin_register_service_error(error: ApiError) -> Response<Body> {
 
     match message.as_str() {
         "Unrecognised nonce" => register_error_response(400, "M_UNKNOWN", &message),
-        "HMAC incorrect" => register_error_response(400, "M_UNKNOWN", &message),
+        "HMAC incorrect" => register_error_response(403, "M_FORBIDDEN", &message),
         "Admin registration is not enabled" => register_error_response(400, "M_UNKNOWN", &message),
         _ if error.is_conflict() || error.code_is(MatrixErrorCode::UserInUse) => {
             register_error_response(400, "M_USER_IN_USE", "User already exists")
@@ -286,6 +303,21 @@ async fn register(
     }))
 }
 
+/// 使用启动时输出的一次性 setup token 创建首个管理员
+///
+/// 仅在服务器没有任何管理员、且启动时未通过配置创建管理员时可用；
+/// 不受 localhost 限制，凭 token 授权。
+async fn bootstrap(
+    State(ctx): State<AdminContext>,
+    Json(payload): Json<BootstrapRequest>,
+) -> Result<Json<BootstrapAdminResponse>, ApiError> {
+    payload.validate().map_err(|e| ApiError::bad_request(format!("Validation error: {e}")))?;
+
+    let response =
+        ctx.admin_bootstrap_service.redeem(&payload.setup_token, &payload.username, &payload.password).await?;
+    Ok(Json(response))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,188 @@
+//! Bulk membership changes for large room administration: invite, kick or
+//! ban a list of users in one call, with per-user failure reporting.
+
+use super::management::{ban_user_internal, kick_user_internal};
+use crate::common::ApiError;
+use crate::web::routes::admin::audit::{record_audit_event, resolve_request_id};
+use crate::web::routes::context::AdminContext;
+use crate::web::routes::AdminUser;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+
+/// Upper bound on `user_ids` per call; larger migrations page through.
+const MAX_BULK_MEMBERSHIP_USERS: usize = 1000;
+
+/// Users are processed in batches of this size, with one audit record per
+/// batch rather than per user.
+const BULK_MEMBERSHIP_BATCH_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+pub struct BulkMembershipRequest {
+    pub user_ids: Vec<String>,
+    pub reason: Option<String>,
+    /// Member that sends the invites. Defaults to the calling admin, who then
+    /// needs invite power in the room.
+    pub inviter: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BulkMembershipAction {
+    Invite,
+    Kick,
+    Ban,
+}
+
+impl BulkMembershipAction {
+    fn as_str(self) -> &'static str {
+        match self {
+            Self::Invite => "invite",
+            Self::Kick => "kick",
+            Self::Ban => "ban",
+        }
+    }
+}
+
+/// Invite every user in `user_ids` to the room.
+#[axum::debug_handler]
+pub async fn bulk_invite(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<BulkMembershipRequest>,
+) -> Result<Json<Value>, ApiError> {
+    bulk_membership(&ctx, &admin, &room_id, &headers, BulkMembershipAction::Invite, body).await
+}
+
+/// Kick every user in `user_ids` from the room.
+#[axum::debug_handler]
+pub async fn bulk_kick(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<BulkMembershipRequest>,
+) -> Result<Json<Value>, ApiError> {
+    bulk_membership(&ctx, &admin, &room_id, &headers, BulkMembershipAction::Kick, body).await
+}
+
+/// Ban every user in `user_ids` from the room.
+#[axum::debug_handler]
+pub async fn bulk_ban(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<BulkMembershipRequest>,
+) -> Result<Json<Value>, ApiError> {
+    bulk_membership(&ctx, &admin, &room_id, &headers, BulkMembershipAction::Ban, body).await
+}
+
+async fn bulk_membership(
+    ctx: &AdminContext,
+    admin: &AdminUser,
+    room_id: &str,
+    headers: &HeaderMap,
+    action: BulkMembershipAction,
+    body: BulkMembershipRequest,
+) -> Result<Json<Value>, ApiError> {
+    let user_ids = dedup_user_ids(body.user_ids);
+    if user_ids.is_empty() {
+        return Err(ApiError::bad_request("user_ids must not be empty".to_string()));
+    }
+    if user_ids.len() > MAX_BULK_MEMBERSHIP_USERS {
+        return Err(ApiError::bad_request(format!("Too many users in bulk request (max {MAX_BULK_MEMBERSHIP_USERS})")));
+    }
+    if !ctx.room_service.state().room_exists(room_id).await? {
+        return Err(ApiError::not_found("Room not found".to_string()));
+    }
+
+    let request_id = resolve_request_id(headers);
+    let reason = body.reason.as_deref();
+    let inviter = body.inviter.as_deref().unwrap_or(&admin.user_id);
+
+    let mut succeeded = Vec::with_capacity(user_ids.len());
+    let mut failed = Vec::new();
+
+    for batch in user_ids.chunks(BULK_MEMBERSHIP_BATCH_SIZE) {
+        let mut batch_succeeded = Vec::with_capacity(batch.len());
+        for user_id in batch {
+            let result = match action {
+                BulkMembershipAction::Invite => {
+                    ctx.room_service.membership().invite_user(room_id, inviter, user_id).await
+                }
+                BulkMembershipAction::Kick => {
+                    kick_user_internal(ctx, room_id, user_id, &admin.user_id, reason, &request_id).await.map(drop)
+                }
+                BulkMembershipAction::Ban => {
+                    ban_user_internal(ctx, room_id, user_id, &admin.user_id, reason, &request_id).await.map(drop)
+                }
+            };
+            match result {
+                Ok(()) => batch_succeeded.push(user_id.clone()),
+                Err(e) => failed.push(json!({
+                    "user_id": user_id,
+                    "errcode": e.code_str(),
+                    "error": e.message(),
+                })),
+            }
+        }
+
+        if !batch_succeeded.is_empty() {
+            record_audit_event(
+                ctx,
+                &admin.user_id,
+                &format!("admin.room.bulk_{}", action.as_str()),
+                "room",
+                room_id,
+                request_id.clone(),
+                json!({
+                    "user_ids": batch_succeeded,
+                    "reason": reason,
+                }),
+            )
+            .await?;
+        }
+        succeeded.extend(batch_succeeded);
+    }
+
+    ::tracing::info!(
+        request_id = %request_id,
+        room_id = %room_id,
+        action = action.as_str(),
+        succeeded = succeeded.len(),
+        failed = failed.len(),
+        "Bulk room membership change finished"
+    );
+
+    Ok(Json(json!({
+        "room_id": room_id,
+        "action": action.as_str(),
+        "total": succeeded.len() + failed.len(),
+        "succeeded": succeeded,
+        "failed": failed,
+    })))
+}
+
+/// Drop repeated user IDs while keeping the caller's order, so a user listed
+/// twice is neither processed twice nor reported as a spurious failure.
+fn dedup_user_ids(user_ids: Vec<String>) -> Vec<String> {
+    let mut seen = std::collections::HashSet::with_capacity(user_ids.len());
+    user_ids.into_iter().filter(|user_id| seen.insert(user_id.clone())).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dedup_user_ids_keeps_first_occurrence_order() {
+        let ids = vec!["@b:hs".to_string(), "@a:hs".to_string(), "@b:hs".to_string()];
+        assert_eq!(dedup_user_ids(ids), vec!["@b:hs".to_string(), "@a:hs".to_string()]);
+    }
+}
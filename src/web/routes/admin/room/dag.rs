@@ -0,0 +1,77 @@
+use crate::common::ApiError;
+use crate::web::routes::context::AdminContext;
+use crate::web::routes::AdminUser;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synapse_storage::event::{DagMissingReference, DAG_DIAGNOSTICS_LIMIT};
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RoomDagQuery {
+    /// Only inspect this many depth levels below the deepest event.
+    pub depth: Option<i64>,
+}
+
+/// Report forward extremities, references to events the server does not
+/// have, and depth statistics of a room's stored DAG. A large number of
+/// extremities or any missing prev/auth events usually explains a room that
+/// stopped making progress over federation.
+#[axum::debug_handler]
+pub async fn get_room_dag(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    Query(query): Query<RoomDagQuery>,
+) -> Result<Json<Value>, ApiError> {
+    if query.depth.is_some_and(|depth| depth < 1) {
+        return Err(ApiError::bad_request("depth must be a positive integer".to_string()));
+    }
+    if !ctx.room_service.state().room_exists(&room_id).await? {
+        return Err(ApiError::not_found("Room not found".to_string()));
+    }
+
+    let dag = ctx.room_service.messaging().get_room_dag_diagnostics(&room_id, query.depth).await?;
+
+    let limited = [dag.forward_extremities.len(), dag.dangling_prev_events.len(), dag.missing_auth_events.len()]
+        .into_iter()
+        .any(|len| len as i64 >= DAG_DIAGNOSTICS_LIMIT);
+    let forward_extremities: Vec<Value> = dag
+        .forward_extremities
+        .iter()
+        .map(|event| {
+            json!({
+                "event_id": event.event_id,
+                "type": event.event_type,
+                "sender": event.sender,
+                "depth": event.depth,
+                "origin_server_ts": event.origin_server_ts
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "room_id": room_id,
+        "min_depth": dag.min_depth,
+        "depth": {
+            "events": dag.depth.event_count,
+            "events_without_depth": dag.depth.events_without_depth,
+            "min": dag.depth.min_depth,
+            "max": dag.depth.max_depth,
+            "violations": dag.depth_violations
+        },
+        "forward_extremities": forward_extremities,
+        "dangling_prev_events": missing_references(&dag.dangling_prev_events),
+        "missing_auth_events": missing_references(&dag.missing_auth_events),
+        "limited": limited
+    })))
+}
+
+fn missing_references(references: &[DagMissingReference]) -> Vec<Value> {
+    references
+        .iter()
+        .map(|reference| json!({ "event_id": reference.event_id, "missing_event_id": reference.missing_event_id }))
+        .collect()
+}
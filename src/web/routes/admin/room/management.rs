@@ -264,25 +264,64 @@ pub async fn purge_room(
 /// Join a user to a room (force join)
 #[axum::debug_handler]
 pub async fn join_room_member(
-    _admin: AdminUser,
+    admin: AdminUser,
     State(ctx): State<AdminContext>,
     headers: HeaderMap,
     Path((room_id, user_id)): Path<(String, String)>,
 ) -> Result<Json<Value>, ApiError> {
     let request_id = resolve_request_id(&headers);
-    Ok(Json(join_room_member_internal(&ctx, &room_id, &user_id, &request_id).await?))
+    Ok(Json(join_room_member_internal(&ctx, &room_id, &user_id, &admin.user_id, None, &request_id).await?))
 }
 
 /// Remove a user from a room
 #[axum::debug_handler]
 pub async fn remove_room_member(
-    _admin: AdminUser,
+    admin: AdminUser,
     State(ctx): State<AdminContext>,
     headers: HeaderMap,
     Path((room_id, user_id)): Path<(String, String)>,
 ) -> Result<Json<Value>, ApiError> {
     let request_id = resolve_request_id(&headers);
-    Ok(Json(remove_room_member_internal(&ctx, &room_id, &user_id, &request_id).await?))
+    Ok(Json(remove_room_member_internal(&ctx, &room_id, &user_id, &admin.user_id, None, &request_id).await?))
+}
+
+/// Make a local user join a room, given by ID or alias, regardless of its
+/// join rules.
+#[axum::debug_handler]
+pub async fn force_join_room(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id_or_alias): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<RoomUserActionRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let request_id = resolve_request_id(&headers);
+    let room_id = resolve_room_id_or_alias(&ctx, &room_id_or_alias).await?;
+    ensure_local_user(&ctx, &body.user_id)?;
+
+    join_room_member_internal(&ctx, &room_id, &body.user_id, &admin.user_id, body.reason.as_deref(), &request_id)
+        .await?;
+
+    Ok(Json(json!({ "room_id": room_id })))
+}
+
+/// Make a local user leave a room, given by ID or alias.
+#[axum::debug_handler]
+pub async fn force_part_room(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id_or_alias): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<RoomUserActionRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let request_id = resolve_request_id(&headers);
+    let room_id = resolve_room_id_or_alias(&ctx, &room_id_or_alias).await?;
+    ensure_local_user(&ctx, &body.user_id)?;
+
+    remove_room_member_internal(&ctx, &room_id, &body.user_id, &admin.user_id, body.reason.as_deref(), &request_id)
+        .await?;
+
+    Ok(Json(json!({ "room_id": room_id })))
 }
 
 #[axum::debug_handler]
@@ -356,7 +395,9 @@ async fn join_room_member_internal(
     ctx: &AdminContext,
     room_id: &str,
     user_id: &str,
-    _request_id: &str,
+    actor_user_id: &str,
+    reason: Option<&str>,
+    request_id: &str,
 ) -> Result<Value, ApiError> {
     if !ctx.room_service.state().room_exists(room_id).await? {
         return Err(ApiError::not_found("Room not found".to_string()));
@@ -369,7 +410,22 @@ async fn join_room_member_internal(
     let existing_membership = ctx.room_service.membership().get_room_membership(room_id, user_id).await?;
 
     if existing_membership.as_deref() != Some("join") {
-        ctx.room_service.membership().join_room(room_id, user_id).await?;
+        ctx.room_service.membership().force_join_room(room_id, user_id).await?;
+
+        record_audit_event(
+            ctx,
+            actor_user_id,
+            "admin.room.force_join",
+            "room",
+            room_id,
+            request_id.to_string(),
+            json!({
+                "user_id": user_id,
+                "previous_membership": existing_membership,
+                "reason": reason
+            }),
+        )
+        .await?;
     }
 
     Ok(json!({
@@ -383,7 +439,9 @@ async fn remove_room_member_internal(
     ctx: &AdminContext,
     room_id: &str,
     user_id: &str,
-    _request_id: &str,
+    actor_user_id: &str,
+    reason: Option<&str>,
+    request_id: &str,
 ) -> Result<Value, ApiError> {
     if !ctx.room_service.state().room_exists(room_id).await? {
         return Err(ApiError::not_found("Room not found".to_string()));
@@ -395,8 +453,23 @@ async fn remove_room_member_internal(
 
     let existing_membership = ctx.room_service.membership().get_room_membership(room_id, user_id).await?;
 
-    if existing_membership.as_deref() == Some("join") {
+    if matches!(existing_membership.as_deref(), Some("join" | "invite" | "knock")) {
         ctx.room_service.membership().leave_room(room_id, user_id).await?;
+
+        record_audit_event(
+            ctx,
+            actor_user_id,
+            "admin.room.force_part",
+            "room",
+            room_id,
+            request_id.to_string(),
+            json!({
+                "user_id": user_id,
+                "previous_membership": existing_membership,
+                "reason": reason
+            }),
+        )
+        .await?;
     }
 
     Ok(json!({
@@ -406,7 +479,28 @@ async fn remove_room_member_internal(
     }))
 }
 
-async fn ban_user_internal(
+/// Accept either a room ID or a `#room:alias` in the path, as Synapse's
+/// admin API does.
+async fn resolve_room_id_or_alias(ctx: &AdminContext, room_id_or_alias: &str) -> Result<String, ApiError> {
+    if !room_id_or_alias.starts_with('#') {
+        return Ok(room_id_or_alias.to_string());
+    }
+
+    ctx.room_service
+        .state()
+        .get_room_by_alias(room_id_or_alias)
+        .await?
+        .ok_or_else(|| ApiError::not_found(format!("Room alias {room_id_or_alias} not found")))
+}
+
+fn ensure_local_user(ctx: &AdminContext, user_id: &str) -> Result<(), ApiError> {
+    match user_id.split_once(':') {
+        Some((localpart, server_name)) if localpart.starts_with('@') && server_name == ctx.server_name => Ok(()),
+        _ => Err(ApiError::bad_request("This endpoint can only be used with local users".to_string())),
+    }
+}
+
+pub(super) async fn ban_user_internal(
     ctx: &AdminContext,
     room_id: &str,
     user_id: &str,
@@ -487,7 +581,7 @@ async fn unban_user_internal(
     }))
 }
 
-async fn kick_user_internal(
+pub(super) async fn kick_user_internal(
     ctx: &AdminContext,
     room_id: &str,
     user_id: &str,
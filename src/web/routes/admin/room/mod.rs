@@ -1,20 +1,29 @@
+pub mod bulk;
+pub mod dag;
+pub mod export;
 pub mod management;
+pub mod power_levels;
+pub mod publication;
+pub mod soft_failed;
 pub mod spaces;
 pub mod types;
 
 use crate::common::ApiError;
 use crate::common::{MAX_PAGINATION_LIMIT, MIN_PAGINATION_LIMIT};
+use crate::web::routes::admin::audit::{record_audit_event, resolve_request_id};
 use crate::web::routes::admin::room::types::{
-    RoomTokenSyncQueryParams, SearchAllRoomsRequest, SearchRoomMessagesRequest,
+    RoomTokenSyncQueryParams, SearchAllRoomsRequest, SearchRoomMessagesRequest, ShutdownRoomRequest,
 };
 use crate::web::routes::context::AdminContext;
 use crate::web::routes::{AdminUser, AppState};
 use axum::{
     extract::{Path, State},
+    http::HeaderMap,
     routing::{delete, get, post, put},
     Json, Router,
 };
 use serde_json::{json, Value};
+use synapse_services::room::ShutdownRoomParams;
 use synapse_storage::room::{decode_room_search_cursor, RoomSearchCursor, RoomSearchOrder};
 use synapse_storage::sliding_sync::{
     decode_room_token_sync_cursor, encode_room_token_sync_cursor, RoomTokenSyncCursor,
@@ -129,6 +138,15 @@ pub fn create_room_router(_state: AppState) -> Router<AppState> {
             "/_synapse/admin/v1/rooms/{room_id}/version",
             get(get_room_version),
         )
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/export",
+            get(export::export_room_events),
+        )
+        .route("/_synapse/admin/v1/rooms/{room_id}/dag", get(dag::get_room_dag))
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/soft_failed",
+            get(soft_failed::get_room_soft_failed_events),
+        )
         .route("/_synapse/admin/v1/rooms/{room_id}/block", post(management::block_room))
         .route(
             "/_synapse/admin/v1/rooms/{room_id}/block",
@@ -142,6 +160,14 @@ pub fn create_room_router(_state: AppState) -> Router<AppState> {
             "/_synapse/admin/v1/rooms/{room_id}/make_admin",
             post(management::make_room_admin).put(management::make_room_admin),
         )
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/power_levels/template",
+            post(power_levels::apply_power_level_template),
+        )
+        .route(
+            "/_synapse/admin/v1/power_level_templates",
+            get(power_levels::list_power_level_templates),
+        )
         .route(
             "/_synapse/admin/v1/rooms/{room_id}/purge_history",
             post(management::purge_history_by_room),
@@ -183,6 +209,14 @@ pub fn create_room_router(_state: AppState) -> Router<AppState> {
             "/_synapse/admin/v1/rooms/{room_id}/members/{user_id}",
             delete(management::remove_room_member),
         )
+        .route(
+            "/_synapse/admin/v1/join/{room_id_or_alias}",
+            post(management::force_join_room),
+        )
+        .route(
+            "/_synapse/admin/v1/part/{room_id_or_alias}",
+            post(management::force_part_room),
+        )
         .route(
             "/_synapse/admin/v1/rooms/{room_id}/ban/{user_id}",
             post(management::ban_user),
@@ -203,6 +237,18 @@ pub fn create_room_router(_state: AppState) -> Router<AppState> {
             "/_synapse/admin/v1/rooms/{room_id}/kick",
             post(management::kick_user_by_body),
         )
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/bulk/invite",
+            post(bulk::bulk_invite),
+        )
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/bulk/kick",
+            post(bulk::bulk_kick),
+        )
+        .route(
+            "/_synapse/admin/v1/rooms/{room_id}/bulk/ban",
+            post(bulk::bulk_ban),
+        )
         // Room listing
         .route(
             "/_synapse/admin/v1/rooms/{room_id}/listings",
@@ -216,6 +262,18 @@ pub fn create_room_router(_state: AppState) -> Router<AppState> {
             "/_synapse/admin/v1/rooms/{room_id}/listings/public",
             delete(spaces::set_room_private),
         )
+        .route(
+            "/_synapse/admin/v1/room_directory/publication_requests",
+            get(publication::list_publication_requests),
+        )
+        .route(
+            "/_synapse/admin/v1/room_directory/publication_requests/{room_id}/approve",
+            post(publication::approve_publication_request),
+        )
+        .route(
+            "/_synapse/admin/v1/room_directory/publication_requests/{room_id}/reject",
+            post(publication::reject_publication_request),
+        )
         // Additional room APIs for 100% coverage
         .route(
             "/_synapse/admin/v1/rooms/{room_id}/event_context/{event_id}",
@@ -256,11 +314,16 @@ pub fn admin_room_route_manifest() -> Vec<crate::web::routes::route_ledger::Rout
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/messages"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/aliases"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/version"),
+        (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/export"),
+        (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/dag"),
+        (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/soft_failed"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/block"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/block"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/unblock"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/make_admin"),
         (Method::PUT, "/_synapse/admin/v1/rooms/{room_id}/make_admin"),
+        (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/power_levels/template"),
+        (Method::GET, "/_synapse/admin/v1/power_level_templates"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/purge_history"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/backfill"),
         (Method::POST, "/_synapse/admin/v1/purge_history"),
@@ -276,14 +339,22 @@ pub fn admin_room_route_manifest() -> Vec<crate::web::routes::route_ledger::Rout
         (Method::GET, "/_synapse/admin/v1/room_stats/{room_id}"),
         (Method::PUT, "/_synapse/admin/v1/rooms/{room_id}/members/{user_id}"),
         (Method::DELETE, "/_synapse/admin/v1/rooms/{room_id}/members/{user_id}"),
+        (Method::POST, "/_synapse/admin/v1/join/{room_id_or_alias}"),
+        (Method::POST, "/_synapse/admin/v1/part/{room_id_or_alias}"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/ban/{user_id}"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/ban"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/unban/{user_id}"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/kick/{user_id}"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/kick"),
+        (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/bulk/invite"),
+        (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/bulk/kick"),
+        (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/bulk/ban"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/listings"),
         (Method::PUT, "/_synapse/admin/v1/rooms/{room_id}/listings/public"),
         (Method::DELETE, "/_synapse/admin/v1/rooms/{room_id}/listings/public"),
+        (Method::GET, "/_synapse/admin/v1/room_directory/publication_requests"),
+        (Method::POST, "/_synapse/admin/v1/room_directory/publication_requests/{room_id}/approve"),
+        (Method::POST, "/_synapse/admin/v1/room_directory/publication_requests/{room_id}/reject"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/event_context/{event_id}"),
         (Method::GET, "/_synapse/admin/v1/rooms/{room_id}/token_sync"),
         (Method::POST, "/_synapse/admin/v1/rooms/{room_id}/search"),
@@ -539,26 +610,51 @@ pub async fn get_room_messages_admin(
     })))
 }
 
+/// Shut a room down: remove its local members, block it, take it out of the
+/// directory and optionally move everyone into a notice room.
 #[axum::debug_handler]
 pub async fn shutdown_room(
-    _admin: AdminUser,
+    admin: AdminUser,
     State(ctx): State<AdminContext>,
-    Json(body): Json<Value>,
+    headers: HeaderMap,
+    Json(body): Json<ShutdownRoomRequest>,
 ) -> Result<Json<Value>, ApiError> {
-    let room_id = body
-        .get("room_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("Missing 'room_id' field".to_string()))?;
-
-    if !ctx.room_service.state().room_exists(room_id).await? {
-        return Err(ApiError::not_found("Room not found".to_string()));
-    }
+    let outcome = ctx
+        .room_service
+        .shutdown_room(
+            &body.room_id,
+            ShutdownRoomParams {
+                requester_id: admin.user_id.clone(),
+                new_room_user_id: body.new_room_user_id.clone(),
+                new_room_name: body.room_name.clone(),
+                message: body.message.clone(),
+                block: body.block,
+            },
+        )
+        .await?;
 
-    ctx.room_service.state().shutdown_room_and_remove_members(room_id).await?;
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.shutdown",
+        "room",
+        &body.room_id,
+        resolve_request_id(&headers),
+        json!({
+            "block": body.block,
+            "new_room_user_id": body.new_room_user_id,
+            "new_room_id": outcome.new_room_id,
+            "kicked_users": outcome.kicked_users.len(),
+            "failed_to_kick_users": outcome.failed_to_kick_users.len()
+        }),
+    )
+    .await?;
 
     Ok(Json(json!({
-        "kicked_users": [],
-        "failed_to_kick_users": [],
+        "kicked_users": outcome.kicked_users,
+        "failed_to_kick_users": outcome.failed_to_kick_users,
+        "local_aliases": outcome.local_aliases,
+        "new_room_id": outcome.new_room_id,
         "closed_room": true
     })))
 }
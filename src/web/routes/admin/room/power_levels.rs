@@ -0,0 +1,142 @@
+use crate::common::ApiError;
+use crate::web::routes::admin::audit::{record_audit_event, resolve_request_id};
+use crate::web::routes::context::AdminContext;
+use crate::web::routes::AdminUser;
+use axum::{
+    extract::{Path, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synapse_common::current_timestamp_millis;
+use synapse_services::room::power_level_templates::validate_power_levels_content;
+use synapse_services::room::PowerLevelTemplate;
+use synapse_storage::CreateEventParams;
+
+#[derive(Debug, Deserialize)]
+pub struct ApplyPowerLevelTemplateRequest {
+    pub template: String,
+}
+
+/// List the named power-level templates and the levels each one sets.
+#[allow(clippy::unused_async)]
+pub async fn list_power_level_templates(
+    _admin: AdminUser,
+    State(_ctx): State<AdminContext>,
+) -> Result<Json<Value>, ApiError> {
+    let templates: Vec<Value> = PowerLevelTemplate::ALL
+        .into_iter()
+        .map(|template| {
+            json!({
+                "name": template.name(),
+                "description": template.description(),
+                "levels": template.levels(),
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({ "templates": templates })))
+}
+
+/// Rewrite the room's `m.room.power_levels` with a template in one state
+/// event. Personal levels in `users` are kept; the event is sent by the
+/// highest-powered local member, who must stay able to change power levels.
+#[axum::debug_handler]
+pub async fn apply_power_level_template(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<ApplyPowerLevelTemplateRequest>,
+) -> Result<Json<Value>, ApiError> {
+    let template = PowerLevelTemplate::from_name(&body.template)
+        .ok_or_else(|| ApiError::invalid_param(format!("Unknown power level template: {}", body.template)))?;
+
+    if !ctx.room_service.state().room_exists(&room_id).await? {
+        return Err(ApiError::not_found("Room not found".to_string()));
+    }
+
+    let current = ctx
+        .room_service
+        .messaging()
+        .get_state_events_by_type(&room_id, "m.room.power_levels")
+        .await?
+        .into_iter()
+        .find(|event| event.get("state_key").and_then(Value::as_str) == Some(""))
+        .and_then(|event| event.get("content").cloned())
+        .ok_or_else(|| ApiError::not_found("Power levels not found".to_string()))?;
+
+    let sender = highest_powered_local_member(&ctx, &room_id, &current).await?;
+    let content = template.apply_to(&current);
+    validate_power_levels_content(&content, &sender)?;
+    ctx.room_auth.verify_power_levels_change(&room_id, &sender, &content).await?;
+
+    let event_id = synapse_common::generate_event_id(&ctx.server_name);
+    ctx.room_service
+        .messaging()
+        .create_event(
+            CreateEventParams {
+                event_id: event_id.clone(),
+                room_id: room_id.clone(),
+                user_id: sender.clone(),
+                event_type: "m.room.power_levels".to_string(),
+                content: content.clone(),
+                state_key: Some(String::new()),
+                origin_server_ts: current_timestamp_millis(),
+                redacts: None,
+            },
+            None,
+        )
+        .await?;
+
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.power_level_template",
+        "room",
+        &room_id,
+        resolve_request_id(&headers),
+        json!({
+            "template": template.name(),
+            "sender": sender,
+            "event_id": event_id,
+        }),
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "event_id": event_id,
+        "template": template.name(),
+        "sender": sender,
+        "content": content,
+    })))
+}
+
+/// The joined local user with the highest level in `power_levels.users`.
+async fn highest_powered_local_member(
+    ctx: &AdminContext,
+    room_id: &str,
+    power_levels: &Value,
+) -> Result<String, ApiError> {
+    let server_suffix = format!(":{}", ctx.server_name);
+    let mut candidates: Vec<(i64, &str)> = power_levels
+        .get("users")
+        .and_then(Value::as_object)
+        .into_iter()
+        .flatten()
+        .filter(|(user_id, _)| user_id.ends_with(&server_suffix))
+        .filter_map(|(user_id, level)| Some((level.as_i64()?, user_id.as_str())))
+        .collect();
+    candidates.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(b.1)));
+
+    for (_, user_id) in candidates {
+        if ctx.room_service.membership().get_room_membership(room_id, user_id).await?.as_deref() == Some("join") {
+            return Ok(user_id.to_string());
+        }
+    }
+
+    Err(ApiError::bad_request(
+        "No joined local member holds a power level in this room; use make_room_admin first".to_string(),
+    ))
+}
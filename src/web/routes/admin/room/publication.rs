@@ -0,0 +1,139 @@
+use crate::common::ApiError;
+use crate::common::{MAX_PAGINATION_LIMIT, MIN_PAGINATION_LIMIT};
+use crate::web::routes::admin::audit::{record_audit_event, resolve_request_id};
+use crate::web::routes::context::AdminContext;
+use crate::web::routes::AdminUser;
+use axum::{
+    extract::{Path, Query, State},
+    http::HeaderMap,
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synapse_storage::room::RoomPublicationRequest;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct PublicationRequestsQuery {
+    pub status: Option<String>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RejectPublicationBody {
+    pub reason: Option<String>,
+}
+
+/// List room directory publication requests, pending ones by default.
+#[axum::debug_handler]
+pub async fn list_publication_requests(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Query(query): Query<PublicationRequestsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let status = query.status.as_deref().unwrap_or("pending");
+    let limit = query.limit.unwrap_or(100).clamp(MIN_PAGINATION_LIMIT, MAX_PAGINATION_LIMIT);
+
+    let requests = ctx.room_service.state().list_room_publication_requests(status, limit).await?;
+
+    Ok(Json(json!({
+        "total": requests.len(),
+        "requests": requests,
+    })))
+}
+
+/// Approve a pending publication request, listing the room in the directory.
+#[axum::debug_handler]
+pub async fn approve_publication_request(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    let request =
+        ctx.room_service.state().review_room_publication_request(&room_id, true, &admin.user_id, None).await?;
+
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.publication_approve",
+        "room",
+        &room_id,
+        resolve_request_id(&headers),
+        json!({ "requested_by": request.requested_by }),
+    )
+    .await?;
+
+    notify_requester(
+        &ctx,
+        &request,
+        format!("Your request to publish {} to the room directory was approved.", room_id),
+    )
+    .await;
+
+    Ok(Json(json!(request)))
+}
+
+/// Reject a pending publication request; the room stays out of the directory.
+#[axum::debug_handler]
+pub async fn reject_publication_request(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    headers: HeaderMap,
+    body: Option<Json<RejectPublicationBody>>,
+) -> Result<Json<Value>, ApiError> {
+    let reason = body.and_then(|Json(body)| body.reason);
+    let request = ctx
+        .room_service
+        .state()
+        .review_room_publication_request(&room_id, false, &admin.user_id, reason.as_deref())
+        .await?;
+
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.publication_reject",
+        "room",
+        &room_id,
+        resolve_request_id(&headers),
+        json!({ "requested_by": request.requested_by, "reason": reason }),
+    )
+    .await?;
+
+    let mut message = format!("Your request to publish {} to the room directory was rejected.", room_id);
+    if let Some(reason) = reason.as_deref() {
+        message.push_str(&format!(" Reason: {}", reason));
+    }
+    notify_requester(&ctx, &request, message).await;
+
+    Ok(Json(json!(request)))
+}
+
+/// Tell the requesting user about the decision. The review itself has already
+/// been committed, so a failed notice is only logged.
+#[cfg(feature = "server-notifications")]
+async fn notify_requester(ctx: &AdminContext, request: &RoomPublicationRequest, message: String) {
+    if let Err(e) =
+        crate::web::routes::admin::notification::deliver_server_notice(ctx, &request.requested_by, "m.text", &message)
+            .await
+    {
+        ::tracing::warn!(
+            room_id = %request.room_id,
+            user_id = %request.requested_by,
+            error = %e,
+            "Failed to notify user about room publication review"
+        );
+    }
+}
+
+#[cfg(not(feature = "server-notifications"))]
+#[allow(clippy::unused_async)]
+async fn notify_requester(_ctx: &AdminContext, request: &RoomPublicationRequest, message: String) {
+    ::tracing::info!(
+        room_id = %request.room_id,
+        user_id = %request.requested_by,
+        status = %request.status,
+        "Server notices are disabled; not notifying user about room publication review: {}",
+        message
+    );
+}
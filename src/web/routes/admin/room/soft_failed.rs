@@ -0,0 +1,78 @@
+use crate::common::ApiError;
+use crate::common::{MAX_PAGINATION_LIMIT, MIN_PAGINATION_LIMIT};
+use crate::web::routes::context::AdminContext;
+use crate::web::routes::AdminUser;
+use axum::{
+    extract::{Path, Query, State},
+    Json,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synapse_storage::event::SoftFailKind;
+
+#[derive(Debug, Deserialize, Default)]
+pub struct RoomSoftFailedQuery {
+    /// `auth` or `spam`; both when absent.
+    pub kind: Option<String>,
+    /// `next_batch` of a previous page.
+    pub from: Option<String>,
+    pub limit: Option<i64>,
+}
+
+/// List a room's soft-failed events, newest first: inbound events that were
+/// stored for the DAG but hidden from clients because they failed auth
+/// against the room's current state (`auth`) or were quarantined by a spam
+/// checker (`spam`).
+#[axum::debug_handler]
+pub async fn get_room_soft_failed_events(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(room_id): Path<String>,
+    Query(query): Query<RoomSoftFailedQuery>,
+) -> Result<Json<Value>, ApiError> {
+    let kind =
+        query.kind.as_deref().map(|kind| kind.parse::<SoftFailKind>().map_err(ApiError::bad_request)).transpose()?;
+    let before = query
+        .from
+        .as_deref()
+        .map(|from| from.parse::<i64>().map_err(|_| ApiError::bad_request("Invalid from token".to_string())))
+        .transpose()?;
+    let limit = query.limit.unwrap_or(100).clamp(MIN_PAGINATION_LIMIT, MAX_PAGINATION_LIMIT);
+    if !ctx.room_service.state().room_exists(&room_id).await? {
+        return Err(ApiError::not_found("Room not found".to_string()));
+    }
+
+    let messaging = ctx.room_service.messaging();
+    let events = messaging.get_soft_failed_events(&room_id, kind, before, limit).await?;
+    let counts: serde_json::Map<String, Value> = messaging
+        .count_soft_failed_events(&room_id)
+        .await?
+        .into_iter()
+        .map(|(kind, count)| (kind, json!(count)))
+        .collect();
+
+    let next_batch =
+        if events.len() as i64 == limit { events.last().map(|event| event.stream_ordering.to_string()) } else { None };
+    let events: Vec<Value> = events
+        .iter()
+        .map(|event| {
+            json!({
+                "event_id": event.event_id,
+                "type": event.event_type,
+                "sender": event.sender,
+                "state_key": event.state_key,
+                "depth": event.depth,
+                "origin_server_ts": event.origin_server_ts,
+                "kind": event.soft_fail_kind,
+                "reason": event.soft_fail_reason
+            })
+        })
+        .collect();
+
+    Ok(Json(json!({
+        "room_id": room_id,
+        "counts": counts,
+        "events": events,
+        "next_batch": next_batch
+    })))
+}
@@ -1,8 +1,10 @@
 use crate::common::ApiError;
+use crate::web::routes::admin::audit::{record_audit_event, resolve_request_id};
 use crate::web::routes::context::AdminContext;
 use crate::web::routes::AdminUser;
 use axum::{
     extract::{Path, State},
+    http::HeaderMap,
     Json,
 };
 use serde_json::{json, Value};
@@ -158,12 +160,14 @@ pub async fn get_room_listings(
     })))
 }
 
-/// Set room as public
+/// Set room as public. Admins are not bound by `room_directory.publication`,
+/// so this also overrides the policy for rooms users may not publish.
 #[axum::debug_handler]
 pub async fn set_room_public(
-    _admin: AdminUser,
+    admin: AdminUser,
     State(ctx): State<AdminContext>,
     Path(room_id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, ApiError> {
     let found = ctx.room_service.state().set_room_public_with_directory(&room_id).await?;
 
@@ -171,6 +175,17 @@ pub async fn set_room_public(
         return Err(ApiError::not_found("Room not found".to_string()));
     }
 
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.publish",
+        "room",
+        &room_id,
+        resolve_request_id(&headers),
+        json!({ "public": true }),
+    )
+    .await?;
+
     Ok(Json(json!({
         "room_id": room_id,
         "public": true
@@ -180,9 +195,10 @@ pub async fn set_room_public(
 /// Set room as private
 #[axum::debug_handler]
 pub async fn set_room_private(
-    _admin: AdminUser,
+    admin: AdminUser,
     State(ctx): State<AdminContext>,
     Path(room_id): Path<String>,
+    headers: HeaderMap,
 ) -> Result<Json<Value>, ApiError> {
     let found = ctx.room_service.state().set_room_private_with_directory(&room_id).await?;
 
@@ -190,6 +206,17 @@ pub async fn set_room_private(
         return Err(ApiError::not_found("Room not found".to_string()));
     }
 
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.room.unpublish",
+        "room",
+        &room_id,
+        resolve_request_id(&headers),
+        json!({ "public": false }),
+    )
+    .await?;
+
     Ok(Json(json!({
         "room_id": room_id,
         "public": false
@@ -6,6 +6,20 @@ pub struct BlockRoomRequest {
     pub reason: Option<String>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct ShutdownRoomRequest {
+    pub room_id: String,
+    pub new_room_user_id: Option<String>,
+    pub room_name: Option<String>,
+    pub message: Option<String>,
+    #[serde(default = "default_shutdown_block")]
+    pub block: bool,
+}
+
+fn default_shutdown_block() -> bool {
+    true
+}
+
 #[derive(Debug, Deserialize)]
 pub struct MakeRoomAdminRequest {
     pub user_id: String,
@@ -21,6 +21,8 @@ pub fn create_security_router() -> Router<crate::web::routes::AppState> {
         .route("/_synapse/admin/v1/users/{user_id}/override_ratelimit", get(get_user_override_rate_limit))
         .route("/_synapse/admin/v1/users/{user_id}/override_ratelimit", post(set_user_override_rate_limit))
         .route("/_synapse/admin/v1/users/{user_id}/override_ratelimit", delete(delete_user_override_rate_limit))
+        .route("/_synapse/admin/v1/users/{user_id}/quota", get(get_user_quota))
+        .route("/_synapse/admin/v1/users/{user_id}/quota", delete(reset_user_quota))
 }
 
 pub fn admin_security_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry> {
@@ -35,6 +37,8 @@ pub fn admin_security_route_manifest() -> Vec<crate::web::routes::route_ledger::
         (Method::GET, "/_synapse/admin/v1/users/{user_id}/override_ratelimit"),
         (Method::POST, "/_synapse/admin/v1/users/{user_id}/override_ratelimit"),
         (Method::DELETE, "/_synapse/admin/v1/users/{user_id}/override_ratelimit"),
+        (Method::GET, "/_synapse/admin/v1/users/{user_id}/quota"),
+        (Method::DELETE, "/_synapse/admin/v1/users/{user_id}/quota"),
     ]
     .into_iter()
     .map(|(m, p)| RouteEntry::new(m, p, "admin::security"))
@@ -208,3 +212,42 @@ pub async fn delete_user_override_rate_limit(
 ) -> Result<Json<Value>, ApiError> {
     delete_user_rate_limit(admin, State(ctx), Path(user_id), headers).await
 }
+
+/// Today's daily message and invite counters for a user, with their limits.
+#[axum::debug_handler]
+pub async fn get_user_quota(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_user_exists(&ctx, &user_id).await?;
+
+    let usage = ctx.send_quota_service.usage(&user_id).await?;
+
+    Ok(Json(json!(usage)))
+}
+
+#[axum::debug_handler]
+pub async fn reset_user_quota(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+) -> Result<Json<Value>, ApiError> {
+    ensure_user_exists(&ctx, &user_id).await?;
+
+    ctx.send_quota_service.reset(&user_id).await?;
+
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.user.quota.reset",
+        "user",
+        &user_id,
+        resolve_request_id(&headers),
+        json!({}),
+    )
+    .await?;
+
+    Ok(Json(json!({})))
+}
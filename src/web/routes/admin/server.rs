@@ -21,6 +21,7 @@ pub fn create_server_router(_state: AppState) -> Router<crate::web::routes::AppS
         .route("/_synapse/admin/v1/whois/{user_id}", get(whois))
         .route("/_synapse/admin/v1/whois/{user_id}/{device_id}", get(whois_device))
         .route("/_synapse/admin/v1/health", get(get_health))
+        .route("/_synapse/admin/v1/database/schema_drift", get(get_schema_drift))
         .route("/_synapse/admin/v1/config", get(get_config))
         .route("/_synapse/admin/v1/experimental_features", get(get_experimental_features))
         .route("/_synapse/admin/v1/backups", get(get_backups))
@@ -41,6 +42,7 @@ pub fn admin_server_route_manifest() -> Vec<crate::web::routes::route_ledger::Ro
         (Method::GET, "/_synapse/admin/v1/whois/{user_id}"),
         (Method::GET, "/_synapse/admin/v1/whois/{user_id}/{device_id}"),
         (Method::GET, "/_synapse/admin/v1/health"),
+        (Method::GET, "/_synapse/admin/v1/database/schema_drift"),
         (Method::GET, "/_synapse/admin/v1/config"),
         (Method::GET, "/_synapse/admin/v1/experimental_features"),
         (Method::GET, "/_synapse/admin/v1/backups"),
@@ -296,6 +298,18 @@ pub async fn get_health(_admin: AdminUser, State(ctx): State<AdminContext>) -> R
     })))
 }
 
+/// Live schema compared with what `database.migrations_dir` declares.
+#[axum::debug_handler]
+pub async fn get_schema_drift(_admin: AdminUser, State(ctx): State<AdminContext>) -> Result<Json<Value>, ApiError> {
+    let report = ctx.admin_server_service.schema_drift_report(ctx.config.database.migrations_dir()).await?;
+
+    Ok(Json(json!({
+        "has_drift": report.has_drift(),
+        "policy": ctx.config.database.schema_drift_policy,
+        "report": report,
+    })))
+}
+
 #[allow(clippy::unused_async)]
 pub async fn get_config(_admin: AdminUser, State(ctx): State<AdminContext>) -> Result<Json<Value>, ApiError> {
     Ok(Json(json!({
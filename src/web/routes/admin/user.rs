@@ -12,7 +12,9 @@ use axum::{
 use serde::Deserialize;
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
-use synapse_services::admin_user_service::{decode_user_cursor, encode_user_cursor, AdminUserCursor};
+use synapse_services::admin_user_service::{
+    decode_user_cursor, encode_user_cursor, AdminUserCursor, ProvisionStatus, ProvisionUserOutcome, ProvisionUserRow,
+};
 use synapse_storage::user::User as AdminUserRecord;
 use validator::Validate;
 
@@ -22,6 +24,10 @@ pub fn create_user_router() -> Router<crate::web::routes::AppState> {
         .route("/_synapse/admin/v1/users/{user_id}", get(get_user))
         .route("/_synapse/admin/v1/users/{user_id}", delete(delete_user))
         .route("/_synapse/admin/v1/users/{user_id}/admin", put(set_admin))
+        .route(
+            "/_synapse/admin/v1/users/{user_id}/user_directory",
+            get(get_user_directory_visibility).put(set_user_directory_visibility),
+        )
         .route("/_synapse/admin/v1/users/{user_id}/evict", post(evict_user))
         .route(
             "/_synapse/admin/v1/users/{user_id}/deactivate",
@@ -77,6 +83,7 @@ pub fn create_user_router() -> Router<crate::web::routes::AppState> {
             "/_synapse/admin/v1/users/batch_deactivate",
             post(batch_deactivate_users),
         )
+        .route("/_synapse/admin/v1/users/provision", post(provision_users))
         // User sessions
         .route(
             "/_synapse/admin/v1/user_sessions/{user_id}",
@@ -102,6 +109,8 @@ pub fn admin_user_route_manifest() -> Vec<crate::web::routes::route_ledger::Rout
         (Method::GET, "/_synapse/admin/v1/users/{user_id}"),
         (Method::DELETE, "/_synapse/admin/v1/users/{user_id}"),
         (Method::PUT, "/_synapse/admin/v1/users/{user_id}/admin"),
+        (Method::GET, "/_synapse/admin/v1/users/{user_id}/user_directory"),
+        (Method::PUT, "/_synapse/admin/v1/users/{user_id}/user_directory"),
         (Method::POST, "/_synapse/admin/v1/users/{user_id}/evict"),
         (Method::POST, "/_synapse/admin/v1/users/{user_id}/deactivate"),
         (Method::POST, "/_synapse/admin/v1/users/{user_id}/password"),
@@ -120,6 +129,7 @@ pub fn admin_user_route_manifest() -> Vec<crate::web::routes::route_ledger::Rout
         (Method::GET, "/_synapse/admin/v1/users/{user_id}/stats"),
         (Method::POST, "/_synapse/admin/v1/users/batch"),
         (Method::POST, "/_synapse/admin/v1/users/batch_deactivate"),
+        (Method::POST, "/_synapse/admin/v1/users/provision"),
         (Method::GET, "/_synapse/admin/v1/user_sessions/{user_id}"),
         (Method::POST, "/_synapse/admin/v1/user_sessions/{user_id}/invalidate"),
         (Method::GET, "/_synapse/admin/v1/account/{user_id}"),
@@ -835,7 +845,7 @@ pub async fn batch_deactivate_users(
         return Err(ApiError::bad_request("Too many users in batch request (max 100)".to_string()));
     }
 
-    let result = ctx.admin_user_service.batch_deactivate_users(&body.users).await?;
+    let result = ctx.admin_user_service.batch_deactivate_users(&body.users, body.erase.unwrap_or(false)).await?;
 
     Ok(Json(json!({
         "deactivated": result.succeeded,
@@ -844,6 +854,172 @@ pub async fn batch_deactivate_users(
     })))
 }
 
+/// Upper bound on rows in one provisioning request.
+const MAX_PROVISION_ROWS: usize = 1000;
+
+/// Bulk account provisioning for onboarding. Takes `{"users": [...]}` or a
+/// `text/csv` body whose header names the `username`, `password`,
+/// `displayname` and `admin` columns. Existing accounts are reported as
+/// `exists` and left alone, so an import can be re-run after a partial
+/// failure.
+#[axum::debug_handler]
+pub async fn provision_users(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    headers: HeaderMap,
+    body: String,
+) -> Result<Json<Value>, ApiError> {
+    let is_csv = headers
+        .get(axum::http::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .is_some_and(|content_type| content_type.trim_start().starts_with("text/csv"));
+    let rows = if is_csv {
+        parse_provisioning_csv(&body).map_err(ApiError::bad_request)?
+    } else {
+        let request: BatchCreateUsersRequest = serde_json::from_str(&body)
+            .map_err(|e| ApiError::bad_request(format!("Invalid provisioning request: {e}")))?;
+        request
+            .users
+            .into_iter()
+            .map(|user| ProvisionUserRow {
+                username: user.username,
+                password: user.password,
+                displayname: user.displayname,
+                admin: user.admin.unwrap_or(false),
+            })
+            .collect()
+    };
+
+    if rows.is_empty() {
+        return Err(ApiError::bad_request("No users to provision".to_string()));
+    }
+    if rows.len() > MAX_PROVISION_ROWS {
+        return Err(ApiError::bad_request(format!(
+            "Too many users in provisioning request (max {MAX_PROVISION_ROWS})"
+        )));
+    }
+    if rows.iter().any(|row| row.admin) {
+        ensure_super_admin_for_privilege_change(&admin)?;
+    }
+
+    let mut results = Vec::with_capacity(rows.len());
+    let (mut created, mut exists, mut failed) = (0usize, 0usize, 0usize);
+    for (index, row) in rows.iter().enumerate() {
+        let validation = ctx
+            .validator
+            .validate_username(&row.username)
+            .and_then(|()| row.password.as_deref().map_or(Ok(()), |p| ctx.validator.validate_password(p)));
+        let outcome = match validation {
+            Ok(()) => ctx.admin_user_service.provision_user(row).await,
+            Err(e) => ProvisionUserOutcome {
+                user_id: format!("@{}:{}", row.username, ctx.server_name),
+                status: ProvisionStatus::Failed,
+                error: Some(e.to_string()),
+            },
+        };
+        match outcome.status {
+            ProvisionStatus::Created => created += 1,
+            ProvisionStatus::Exists => exists += 1,
+            ProvisionStatus::Failed => failed += 1,
+        }
+        results.push(json!({
+            "row": index + 1,
+            "username": row.username,
+            "user_id": outcome.user_id,
+            "status": outcome.status.as_str(),
+            "error": outcome.error,
+        }));
+    }
+
+    let request_id = resolve_request_id(&headers);
+    if let Err(e) = record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "provision_users",
+        "user",
+        "*",
+        request_id,
+        json!({
+            "admin_role": admin.role,
+            "total": rows.len(),
+            "created": created,
+            "exists": exists,
+            "failed": failed,
+        }),
+    )
+    .await
+    {
+        tracing::warn!("Failed to record audit event: {}", e);
+    }
+
+    Ok(Json(json!({
+        "total": rows.len(),
+        "created": created,
+        "exists": exists,
+        "failed": failed,
+        "results": results
+    })))
+}
+
+/// Parses a provisioning CSV. Fields may be double-quoted, with `""` for a
+/// literal quote; quoted fields cannot span lines.
+fn parse_provisioning_csv(body: &str) -> Result<Vec<ProvisionUserRow>, String> {
+    let mut lines = body.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+    let (_, header) = lines.next().ok_or_else(|| "CSV body is empty".to_string())?;
+    let header = split_csv_line(header).map_err(|e| format!("line 1: {e}"))?;
+    let column = |name: &str| header.iter().position(|h| h.trim().eq_ignore_ascii_case(name));
+    let username_col = column("username").ok_or_else(|| "CSV header has no username column".to_string())?;
+    let (password_col, displayname_col, admin_col) = (column("password"), column("displayname"), column("admin"));
+
+    lines
+        .map(|(index, line)| {
+            let line_no = index + 1;
+            let fields = split_csv_line(line).map_err(|e| format!("line {line_no}: {e}"))?;
+            let field = |col: Option<usize>| {
+                col.and_then(|c| fields.get(c)).map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+            };
+            let admin = match field(admin_col).map(|v| v.to_ascii_lowercase()).as_deref() {
+                None | Some("false" | "0" | "no") => false,
+                Some("true" | "1" | "yes") => true,
+                Some(other) => return Err(format!("line {line_no}: invalid admin value '{other}'")),
+            };
+            Ok(ProvisionUserRow {
+                username: field(Some(username_col)).ok_or_else(|| format!("line {line_no}: missing username"))?,
+                password: field(password_col),
+                displayname: field(displayname_col),
+                admin,
+            })
+        })
+        .collect()
+}
+
+fn split_csv_line(line: &str) -> Result<Vec<String>, String> {
+    let mut fields = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' if in_quotes => in_quotes = false,
+            '"' if field.trim().is_empty() => {
+                field.clear();
+                in_quotes = true;
+            }
+            ',' if !in_quotes => fields.push(std::mem::take(&mut field)),
+            _ => field.push(c),
+        }
+    }
+    if in_quotes {
+        return Err("unterminated quoted field".to_string());
+    }
+    fields.push(field);
+    Ok(fields)
+}
+
 /// Get user sessions (devices and connections)
 #[axum::debug_handler]
 pub async fn get_user_sessions(
@@ -971,3 +1147,81 @@ pub async fn update_account(
         "updated": true
     })))
 }
+
+#[axum::debug_handler]
+pub async fn get_user_directory_visibility(
+    _admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(user_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    let user = resolve_user(&ctx, &user_id).await?;
+    let hidden = ctx.account_data_service.is_hidden_from_directory(&user.user_id).await?;
+
+    Ok(Json(json!({
+        "user_id": user.user_id,
+        "hidden": hidden
+    })))
+}
+
+#[axum::debug_handler]
+pub async fn set_user_directory_visibility(
+    admin: AdminUser,
+    State(ctx): State<AdminContext>,
+    Path(user_id): Path<String>,
+    headers: HeaderMap,
+    Json(body): Json<Value>,
+) -> Result<Json<Value>, ApiError> {
+    let hidden = body
+        .get("hidden")
+        .and_then(|v| v.as_bool())
+        .ok_or_else(|| ApiError::bad_request("Missing 'hidden' field".to_string()))?;
+
+    let user = resolve_user(&ctx, &user_id).await?;
+    ctx.account_data_service.set_hidden_from_directory(&user.user_id, hidden).await?;
+
+    let request_id = resolve_request_id(&headers);
+    record_audit_event(
+        &ctx,
+        &admin.user_id,
+        "admin.user.user_directory_visibility",
+        "user",
+        &user.user_id,
+        request_id,
+        json!({ "hidden": hidden }),
+    )
+    .await?;
+
+    Ok(Json(json!({
+        "user_id": user.user_id,
+        "hidden": hidden
+    })))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn provisioning_csv_maps_columns_by_header() {
+        let csv = "Username,admin,displayname\n\nalice,yes,\"Liddell, Alice\"\nbob,,\"Bob \"\"B\"\" Smith\"\n";
+        let rows = parse_provisioning_csv(csv).unwrap();
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].username, "alice");
+        assert!(rows[0].admin);
+        assert_eq!(rows[0].displayname.as_deref(), Some("Liddell, Alice"));
+        assert_eq!(rows[0].password, None);
+        assert_eq!(rows[1].displayname.as_deref(), Some("Bob \"B\" Smith"));
+        assert!(!rows[1].admin);
+    }
+
+    #[test]
+    fn provisioning_csv_reports_bad_lines() {
+        assert!(parse_provisioning_csv("").is_err());
+        assert!(parse_provisioning_csv("displayname\nAlice\n").is_err());
+        assert_eq!(
+            parse_provisioning_csv("username,admin\nalice,maybe\n").unwrap_err(),
+            "line 2: invalid admin value 'maybe'"
+        );
+        assert_eq!(parse_provisioning_csv("username\n\"alice\n").unwrap_err(), "line 2: unterminated quoted field");
+    }
+}
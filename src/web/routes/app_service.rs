@@ -11,6 +11,7 @@ use serde::{Deserialize, Serialize};
 use crate::common::ApiError;
 use crate::web::routes::response_helpers::{created_json_from, empty_json, json_from, json_vec_from, require_found};
 use crate::web::routes::{AdminUser, AppState, AuthenticatedUser};
+use synapse_services::application_service::AUTO_PROVISION_MEMBERSHIP_KEY;
 use synapse_storage::application_service::{
     ApplicationService, ApplicationServiceState, ApplicationServiceUser, RegisterApplicationServiceRequest,
     UpdateApplicationServiceRequest,
@@ -29,6 +30,7 @@ pub struct RegisterAppServiceBody {
     pub is_rate_limited: Option<bool>,
     pub protocols: Option<Vec<String>>,
     pub namespaces: Option<serde_json::Value>,
+    pub auto_provision_membership: Option<bool>,
 }
 
 impl RegisterAppServiceBody {
@@ -49,7 +51,9 @@ impl RegisterAppServiceBody {
             protocols: self.protocols,
             namespaces: self.namespaces,
             api_key: None,
-            config: None,
+            config: self
+                .auto_provision_membership
+                .map(|enabled| serde_json::json!({ AUTO_PROVISION_MEMBERSHIP_KEY: enabled })),
         })
     }
 }
@@ -594,6 +598,7 @@ mod tests {
             is_rate_limited: Some(true),
             protocols: Some(vec!["irc".to_string()]),
             namespaces: Some(serde_json::json!({"users": [], "aliases": [], "rooms": []})),
+            auto_provision_membership: Some(true),
         };
 
         let request = body.into_request().expect("sender fallback should succeed");
@@ -601,6 +606,7 @@ mod tests {
         assert_eq!(request.as_id, "as-1");
         assert_eq!(request.sender, "@bot:example.com");
         assert_eq!(request.protocols, Some(vec!["irc".to_string()]));
+        assert_eq!(request.config, Some(serde_json::json!({"auto_provision_membership": true})));
     }
 
     #[test]
@@ -616,6 +622,7 @@ mod tests {
             is_rate_limited: None,
             protocols: None,
             namespaces: None,
+            auto_provision_membership: None,
         };
 
         let error = body.into_request().expect_err("missing sender should fail");
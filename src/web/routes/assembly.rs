@@ -1,14 +1,14 @@
 use super::route_ledger::{RouteEntry, RouteLedger};
 use super::route_module::{route_modules, ProfileFlags};
 use super::{
-    account_data, background_update, captcha, device, dm, e2ee, ephemeral, event_report, feature_flags, guest,
-    handlers, key_backup, key_rotation, media, moderation, presence, push, push_notification, reactions, relations,
-    rendezvous, room_summary, sliding_sync, space, sync, tags, telemetry, thirdparty, typing, verification_routes,
-    worker, *,
+    account_data, background_update, captcha, delayed_events, device, dm, e2ee, ephemeral, event_report, feature_flags,
+    guest, handlers, key_backup, key_rotation, media, moderation, presence, push, push_notification, reactions,
+    relations, rendezvous, room_summary, sliding_sync, space, sync, tags, telemetry, thirdparty, typing,
+    verification_routes, worker, *,
 };
 use crate::web::middleware::{
-    cors_middleware, csrf_middleware, method_not_allowed_middleware, rate_limit_middleware, request_id_middleware,
-    security_headers_middleware, shadow_ban_middleware,
+    concurrency_limit_middleware, cors_middleware, csrf_middleware, method_not_allowed_middleware,
+    rate_limit_middleware, request_id_middleware, security_headers_middleware, shadow_ban_middleware,
 };
 use axum::{
     http::Method,
@@ -64,6 +64,7 @@ fn base_route_manifest() -> RouteLedger {
     ledger.extend(guest::guest_route_manifest());
     ledger.extend(captcha::captcha_route_manifest());
     ledger.extend(rendezvous::rendezvous_route_manifest());
+    ledger.extend(delayed_events::delayed_events_route_manifest());
     ledger.extend(telemetry::telemetry_route_manifest());
     ledger.extend(thirdparty::thirdparty_route_manifest());
     ledger.extend(background_update::background_update_route_manifest());
@@ -105,6 +106,8 @@ pub fn top_level_inline_manifest() -> Vec<RouteEntry> {
     [
         (Method::GET, "/"),
         (Method::GET, "/health"),
+        (Method::GET, "/health/live"),
+        (Method::GET, "/health/ready"),
         (Method::GET, "/_health"),
         (Method::GET, "/_matrix/client/versions"),
         (Method::GET, "/_matrix/client/v3/versions"),
@@ -362,6 +365,8 @@ pub fn create_router(state: AppState) -> Router {
             }),
         )
         .route("/health", get(handlers::health_check))
+        .route("/health/live", get(handlers::liveness_check))
+        .route("/health/ready", get(handlers::readiness_check))
         .route("/_health", get(handlers::detailed_health_check))
         .route("/_matrix/client/versions", get(handlers::get_client_versions))
         .route("/_matrix/client/v3/versions", get(handlers::get_client_versions))
@@ -462,6 +467,7 @@ pub fn create_router(state: AppState) -> Router {
         .merge(ephemeral::create_ephemeral_router(state.clone()))
         .merge(crate::web::routes::handlers::thread::create_thread_routes(state.clone()))
         .merge(create_rendezvous_router(state.clone()))
+        .merge(create_delayed_events_router(state.clone()))
         .merge(create_presence_router());
 
     // Fallback handler: unmatched routes return M_UNRECOGNIZED per Matrix spec.
@@ -484,6 +490,7 @@ pub fn create_router(state: AppState) -> Router {
         .layer(axum::middleware::from_fn(method_not_allowed_middleware))
         .layer(CompressionLayer::new().compress_when(SizeAbove::new(1024)))
         .layer(axum::middleware::from_fn_with_state(core_ctx.clone(), csrf_middleware))
+        .layer(axum::middleware::from_fn_with_state(core_ctx.clone(), concurrency_limit_middleware))
         .layer(axum::middleware::from_fn_with_state(core_ctx.clone(), rate_limit_middleware))
         .layer(axum::middleware::from_fn_with_state(core_ctx, shadow_ban_middleware))
         .layer(axum::middleware::from_fn(request_id_middleware))
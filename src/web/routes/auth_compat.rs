@@ -3,7 +3,7 @@ use crate::web::extractors::{AuthenticatedUser, MatrixJson};
 use crate::web::routes::context::AuthContext;
 use crate::web::routes::formatting::format_token_response;
 use crate::web::utils::admin_auth::enforce_admin_login_mfa_svc;
-use crate::web::utils::auth::resolve_request_id;
+use crate::web::utils::auth::{bearer_token, resolve_request_id};
 use axum::{
     extract::{Query, State},
     http::{HeaderMap, StatusCode},
@@ -14,6 +14,7 @@ use serde_json::{json, Value};
 pub(crate) async fn register(
     State(ctx): State<AuthContext>,
     Query(query): Query<Value>,
+    headers: HeaderMap,
     MatrixJson(body): MatrixJson<Value>,
 ) -> Result<Response, ApiError> {
     let is_guest = query.get("kind").and_then(|v| v.as_str()) == Some("guest")
@@ -78,12 +79,34 @@ pub(crate) async fn register(
     let displayname = body.get("displayname").and_then(|v| v.as_str());
     let initial_device_display_name = body.get("initial_device_display_name").and_then(|v| v.as_str());
 
-    Ok(Json(
-        ctx.registration_service.register_user(username, password, displayname, initial_device_display_name).await?,
-    )
-    .into_response())
+    let response =
+        ctx.registration_service.register_user(username, password, displayname, initial_device_display_name).await?;
+
+    if let Some(user_id) = response.get("user_id").and_then(Value::as_str) {
+        ctx.auto_join_service.spawn_for_new_user(user_id.to_string());
+        send_welcome_message(&ctx, user_id, displayname, &headers);
+    }
+
+    Ok(Json(response).into_response())
+}
+
+/// Queue the configured welcome notice, localized from `Accept-Language`.
+#[cfg(feature = "server-notifications")]
+fn send_welcome_message(ctx: &AuthContext, user_id: &str, displayname: Option<&str>, headers: &HeaderMap) {
+    ctx.welcome_service.spawn_for_new_user(
+        user_id.to_string(),
+        displayname.map(str::to_string),
+        accept_language(headers).map(str::to_string),
+    );
+}
+
+pub(crate) fn accept_language(headers: &HeaderMap) -> Option<&str> {
+    headers.get(axum::http::header::ACCEPT_LANGUAGE).and_then(|v| v.to_str().ok())
 }
 
+#[cfg(not(feature = "server-notifications"))]
+fn send_welcome_message(_ctx: &AuthContext, _user_id: &str, _displayname: Option<&str>, _headers: &HeaderMap) {}
+
 pub(crate) async fn check_username_availability(
     State(ctx): State<AuthContext>,
     Query(params): Query<Value>,
@@ -119,6 +142,7 @@ pub(crate) async fn request_email_verification(
         None,
         "register",
         &request_id,
+        accept_language(&headers),
     )
     .await
 }
@@ -130,6 +154,7 @@ pub(crate) async fn request_email_verification_with_submit_path(
     user_id: Option<&str>,
     purpose: &str,
     request_id: &str,
+    accept_language: Option<&str>,
 ) -> Result<Json<Value>, ApiError> {
     let email = body
         .get("email")
@@ -180,6 +205,18 @@ pub(crate) async fn request_email_verification_with_submit_path(
 
     let sid = format!("{token_id}");
 
+    // A password reset for an unknown address gets a placeholder session
+    // and no email, so the response alone does not reveal whether the
+    // address is registered.
+    if ctx.config.smtp.enabled && (user_id.is_some() || purpose != "password_reset") {
+        let kind = if purpose == "password_reset" {
+            synapse_services::email_templates::EmailTemplateKind::PasswordReset
+        } else {
+            synapse_services::email_templates::EmailTemplateKind::Verification
+        };
+        ctx.registration_service.send_verification_email(kind, email, &token, &sid, 3600, accept_language).await?;
+    }
+
     let submit_url = format!("{}{}", ctx.config.server.get_public_baseurl(), submit_path);
 
     ::tracing::info!(
@@ -235,7 +272,11 @@ pub(crate) async fn submit_email_token(
 }
 
 pub(crate) async fn get_login_flows(State(ctx): State<AuthContext>) -> Json<Value> {
-    let mut flows = vec![json!({"type": "m.login.password"}), json!({"type": "m.login.token"})];
+    let mut flows = vec![
+        json!({"type": "m.login.password"}),
+        json!({"type": "m.login.token"}),
+        json!({"type": "m.login.application_service"}),
+    ];
 
     let mut sso_providers = Vec::new();
 
@@ -298,8 +339,13 @@ pub(crate) async fn get_register_flows() -> Json<Value> {
 
 pub(crate) async fn login(
     State(ctx): State<AuthContext>,
+    headers: HeaderMap,
     MatrixJson(body): MatrixJson<Value>,
 ) -> Result<Json<Value>, ApiError> {
+    if body.get("type").and_then(|v| v.as_str()) == Some("m.login.application_service") {
+        return login_as_app_service(&ctx, &headers, &body).await;
+    }
+
     let username = body
         .get("identifier")
         .and_then(|id| id.get("user"))
@@ -343,6 +389,34 @@ pub(crate) async fn login(
     )))
 }
 
+/// `m.login.application_service`, used by bridges for double puppeting: the
+/// service authenticates with its `as_token` and gets a device of a user in
+/// its namespaces.
+async fn login_as_app_service(ctx: &AuthContext, headers: &HeaderMap, body: &Value) -> Result<Json<Value>, ApiError> {
+    let as_token = bearer_token(headers)?;
+    let user = body
+        .get("identifier")
+        .and_then(|id| id.get("user"))
+        .or_else(|| body.get("user"))
+        .and_then(|v| v.as_str())
+        .filter(|user| !user.is_empty() && user.len() <= 255)
+        .ok_or_else(|| ApiError::bad_request("User identifier required".to_string()))?;
+    let device_id = body.get("device_id").and_then(|v| v.as_str());
+    let initial_display_name = body.get("initial_display_name").and_then(|v| v.as_str());
+
+    let (user, access_token, refresh_token, device_id) =
+        ctx.credential_auth.login_as_app_service(&as_token, user, device_id, initial_display_name).await?;
+
+    Ok(Json(format_token_response(
+        &access_token,
+        &refresh_token,
+        ctx.token_auth.token_expiry(),
+        &device_id,
+        &user.user_id(),
+        &ctx.config.server.get_public_baseurl(),
+    )))
+}
+
 pub(crate) async fn logout(
     State(ctx): State<AuthContext>,
     auth_user: AuthenticatedUser,
@@ -9,7 +9,7 @@ use tokio::sync::{Mutex, RwLock, Semaphore};
 // ── CoreContext ───────────────────────────────────────────────────────────
 
 /// Minimal context for the global request-pipeline middlewares (auth, shadow-ban,
-/// csrf, rate-limit). Carries only the shared services those middlewares read.
+/// csrf, rate-limit, concurrency-limit). Carries only the shared services those middlewares read.
 #[derive(Clone)]
 pub struct CoreContext {
     pub validator: Arc<synapse_common::validation::Validator>,
@@ -19,6 +19,7 @@ pub struct CoreContext {
     pub config: synapse_common::config::Config,
     pub cache: Arc<CacheManager>,
     pub rate_limit_config_manager: Option<Arc<RateLimitConfigManager>>,
+    pub user_request_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
 }
 
 impl CoreContext {
@@ -38,6 +39,7 @@ impl FromRef<AppState> for CoreContext {
             config: state.services.core.config.clone(),
             cache: state.cache.clone(),
             rate_limit_config_manager: state.rate_limit_config_manager().cloned(),
+            user_request_semaphores: state.user_request_semaphores.clone(),
         }
     }
 }
@@ -59,6 +61,8 @@ pub struct RoomContext {
     pub room_summary_service: Arc<synapse_services::room_summary_service::RoomSummaryService>,
     pub account_data_service: Arc<synapse_services::account_data_service::AccountDataService>,
     pub search_service: Arc<synapse_services::search_service::SearchService>,
+    pub event_visibility_service: Arc<synapse_services::event_visibility_service::EventVisibilityService>,
+    pub send_quota_service: Arc<synapse_services::send_quota_service::SendQuotaService>,
     pub retention_service: Arc<synapse_services::retention_service::RetentionService>,
     pub translation_service: Arc<synapse_services::translation_service::TranslationService>,
     pub federation_client: Arc<dyn synapse_federation::client_api::FederationClientApi>,
@@ -80,6 +84,8 @@ pub struct RoomContext {
     pub typing_service: Arc<synapse_services::typing_service::TypingService>,
     pub directory_service: Arc<synapse_services::directory_service::DirectoryService>,
     pub relations_service: Arc<synapse_services::relations_service::RelationsService>,
+    pub delayed_event_service: Arc<synapse_services::delayed_event_service::DelayedEventService>,
+    pub app_service_manager: Arc<synapse_services::application_service::ApplicationServiceManager>,
     #[cfg(feature = "voice-extended")]
     pub voice_service: Arc<synapse_services::voice_service::VoiceService>,
     pub ssss_service: synapse_e2ee::ssss::SecretStorageService,
@@ -104,6 +110,8 @@ impl FromRef<AppState> for RoomContext {
             room_summary_service: state.services.rooms.room_summary_service.clone(),
             account_data_service: state.services.core.account_data_service.clone(),
             search_service: state.services.core.search_service.clone(),
+            event_visibility_service: state.services.rooms.event_visibility_service.clone(),
+            send_quota_service: state.services.admin.security.send_quota_service.clone(),
             retention_service: state.services.admin.modules.retention_service.clone(),
             translation_service: state.services.extensions.translation_service.clone(),
             federation_client: state.services.federation.federation_client.clone(),
@@ -125,6 +133,8 @@ impl FromRef<AppState> for RoomContext {
             typing_service: state.services.rooms.typing_service.clone(),
             directory_service: state.services.extensions.directory_service.clone(),
             relations_service: state.services.rooms.relations_service.clone(),
+            delayed_event_service: state.services.rooms.delayed_event_service.clone(),
+            app_service_manager: state.services.admin.modules.app_service_manager.clone(),
             #[cfg(feature = "voice-extended")]
             voice_service: Arc::new(state.services.extensions.voice_service.clone()),
             ssss_service: state.services.e2ee.ssss_service.clone(),
@@ -289,6 +299,9 @@ pub struct AuthContext {
     pub credential_auth: Arc<dyn synapse_services::auth::CredentialAuth>,
     pub room_auth: Arc<dyn synapse_services::auth::RoomAuth>,
     pub registration_service: Arc<synapse_services::registration_service::RegistrationService>,
+    pub auto_join_service: Arc<synapse_services::auto_join_service::AutoJoinService>,
+    #[cfg(feature = "server-notifications")]
+    pub welcome_service: Arc<synapse_services::welcome_service::WelcomeService>,
     pub user_service: Arc<synapse_services::UserService>,
     pub server_name: String,
     pub cache: Arc<CacheManager>,
@@ -309,6 +322,7 @@ pub struct AuthContext {
     pub threepid_storage: Arc<dyn synapse_storage::threepid::ThreepidStoreApi>,
     pub rendezvous_storage: Arc<dyn synapse_storage::rendezvous::RendezvousStoreApi>,
     pub rendezvous_message_storage: Arc<dyn synapse_storage::rendezvous::RendezvousMessageStoreApi>,
+    pub rendezvous_channels: Arc<synapse_services::rendezvous_channel_service::RendezvousChannelService>,
 }
 
 impl FromRef<AppState> for AuthContext {
@@ -319,6 +333,9 @@ impl FromRef<AppState> for AuthContext {
             credential_auth: state.services.core.credential_auth.clone(),
             room_auth: state.services.core.room_auth.clone(),
             registration_service: state.services.core.registration_service.clone(),
+            auto_join_service: state.services.rooms.auto_join_service.clone(),
+            #[cfg(feature = "server-notifications")]
+            welcome_service: state.services.extensions.welcome_service.clone(),
             user_service: state.services.account.user_service.clone(),
             server_name: state.services.core.server_name.clone(),
             cache: state.cache.clone(),
@@ -339,6 +356,7 @@ impl FromRef<AppState> for AuthContext {
             threepid_storage: state.services.account.threepid_storage.clone(),
             rendezvous_storage: state.services.admin.modules.rendezvous_storage.clone(),
             rendezvous_message_storage: state.services.admin.modules.rendezvous_message_storage.clone(),
+            rendezvous_channels: state.services.admin.modules.rendezvous_channels.clone(),
         }
     }
 }
@@ -362,6 +380,7 @@ pub struct AdminContext {
     pub room_service: Arc<dyn synapse_services::RoomServiceApi>,
     pub sliding_sync_service: Arc<synapse_services::sliding_sync_service::SlidingSyncService>,
     pub space_service: Arc<synapse_services::space_service::SpaceService>,
+    pub event_visibility_service: Arc<synapse_services::event_visibility_service::EventVisibilityService>,
     // Account
     pub user_service: Arc<synapse_services::UserService>,
     pub account_identity_service: Arc<synapse_services::account_identity_service::AccountIdentityService>,
@@ -370,6 +389,7 @@ pub struct AdminContext {
     // Admin — user
     pub admin_user_service: Arc<synapse_services::admin_user_service::AdminUserService>,
     pub admin_registration_service: synapse_services::admin_registration_service::AdminRegistrationService,
+    pub admin_bootstrap_service: Arc<synapse_services::admin_bootstrap_service::AdminBootstrapService>,
     pub admin_token_service: Arc<synapse_services::admin_token_service::AdminTokenService>,
     pub refresh_token_service: Arc<synapse_services::refresh_token_service::RefreshTokenService>,
     pub registration_token_service: Arc<synapse_services::registration_token_service::RegistrationTokenService>,
@@ -389,6 +409,7 @@ pub struct AdminContext {
     // Admin — security
     pub admin_audit_service: Arc<synapse_services::AdminAuditService>,
     pub admin_security_service: Arc<synapse_services::admin_security_service::AdminSecurityService>,
+    pub send_quota_service: Arc<synapse_services::send_quota_service::SendQuotaService>,
     pub admin_server_service: Arc<synapse_services::admin_server_service::AdminServerService>,
     pub captcha_service: Arc<synapse_services::captcha_service::CaptchaService>,
     pub telemetry_alert_service: Arc<synapse_services::telemetry_service::TelemetryAlertService>,
@@ -442,12 +463,14 @@ impl FromRef<AppState> for AdminContext {
             room_service: state.services.rooms.room_service.clone(),
             sliding_sync_service: state.services.rooms.sliding_sync_service.clone(),
             space_service: state.services.rooms.space_service.clone(),
+            event_visibility_service: state.services.rooms.event_visibility_service.clone(),
             account_identity_service: state.services.account.account_identity_service.clone(),
             account_device_list_service: state.services.account.account_device_list_service.clone(),
             user_service: state.services.account.user_service.clone(),
             invite_blocklist_storage: state.services.account.invite_blocklist_storage.clone(),
             admin_user_service: state.services.admin.user.admin_user_service.clone(),
             admin_registration_service: state.services.admin.user.admin_registration_service.clone(),
+            admin_bootstrap_service: state.services.admin.user.admin_bootstrap_service.clone(),
             admin_token_service: state.services.admin.user.admin_token_service.clone(),
             refresh_token_service: state.services.admin.user.refresh_token_service.clone(),
             registration_token_service: state.services.admin.user.registration_token_service.clone(),
@@ -465,6 +488,7 @@ impl FromRef<AppState> for AdminContext {
             worker_manager: state.services.admin.modules.worker_manager.clone(),
             admin_audit_service: state.services.admin.security.admin_audit_service.clone(),
             admin_security_service: state.services.admin.security.admin_security_service.clone(),
+            send_quota_service: state.services.admin.security.send_quota_service.clone(),
             admin_server_service: state.services.admin.security.admin_server_service.clone(),
             captcha_service: state.services.admin.security.captcha_service.clone(),
             telemetry_alert_service: state.services.admin.security.telemetry_alert_service.clone(),
@@ -535,12 +559,14 @@ pub struct FederationContext {
     pub device_keys_service: synapse_e2ee::device_keys::DeviceKeyService,
     pub cross_signing_service: synapse_e2ee::cross_signing::CrossSigningService,
     pub to_device_service: synapse_e2ee::to_device::ToDeviceService,
+    pub event_notifier: synapse_services::event_notifier::EventNotifier,
     pub presence_storage: Arc<dyn synapse_storage::presence::PresenceStoreApi>,
     pub device_storage: Arc<dyn synapse_storage::device::DeviceListStoreApi>,
     pub federation_inbound_edu_semaphore: Arc<Semaphore>,
     pub federation_inbound_edu_origin_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
     pub federation_presence_backoff_until: Arc<RwLock<HashMap<String, i64>>>,
     pub federation_join_semaphore: Arc<Semaphore>,
+    pub module_service: Arc<synapse_services::module_service::ModuleService>,
 }
 
 impl FromRef<AppState> for FederationContext {
@@ -576,12 +602,14 @@ impl FromRef<AppState> for FederationContext {
             device_keys_service: state.services.e2ee.device_keys_service.clone(),
             cross_signing_service: state.services.e2ee.cross_signing_service.clone(),
             to_device_service: state.services.e2ee.to_device_service.clone(),
+            event_notifier: state.services.core.event_notifier.clone(),
             presence_storage: state.services.account.presence_storage.clone(),
             device_storage: state.services.account.device_storage.clone(),
             federation_inbound_edu_semaphore: state.federation_inbound_edu_semaphore.clone(),
             federation_inbound_edu_origin_semaphores: state.federation_inbound_edu_origin_semaphores.clone(),
             federation_presence_backoff_until: state.federation_presence_backoff_until.clone(),
             federation_join_semaphore: state.federation_join_semaphore.clone(),
+            module_service: state.services.admin.modules.module_service.clone(),
         }
     }
 }
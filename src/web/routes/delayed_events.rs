@@ -0,0 +1,99 @@
+use crate::common::ApiError;
+use crate::web::routes::context::RoomContext;
+use crate::web::routes::AppState;
+use crate::web::routes::AuthenticatedUser;
+use axum::{
+    extract::{Json, Path, State},
+    routing::{get, post},
+    Router,
+};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use synapse_services::delayed_event_service::{DelayedEventAction, DelayedEventRequest};
+
+const MSC4140_DELAYED_EVENTS_PATH: &str = "/_matrix/client/unstable/org.matrix.msc4140/delayed_events";
+
+pub fn create_delayed_events_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route(MSC4140_DELAYED_EVENTS_PATH, get(get_delayed_events))
+        .route("/_matrix/client/unstable/org.matrix.msc4140/delayed_events/{delay_id}", post(update_delayed_event))
+        .with_state(state)
+}
+
+pub fn delayed_events_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry> {
+    use crate::web::routes::route_ledger::RouteEntry;
+    use axum::http::Method;
+    [
+        (Method::GET, MSC4140_DELAYED_EVENTS_PATH),
+        (Method::POST, "/_matrix/client/unstable/org.matrix.msc4140/delayed_events/{delay_id}"),
+    ]
+    .into_iter()
+    .map(|(m, p)| RouteEntry::new(m, p, "delayed_events"))
+    .collect()
+}
+
+/// `org.matrix.msc4140.delay` on the send and state endpoints.
+#[derive(Debug, Default, Deserialize)]
+pub(crate) struct DelayQuery {
+    #[serde(rename = "org.matrix.msc4140.delay")]
+    pub delay: Option<u64>,
+}
+
+/// Schedules an event the sender may send now and returns its delay ID.
+pub(crate) async fn schedule_delayed_event(
+    ctx: &RoomContext,
+    auth_user: &AuthenticatedUser,
+    room_id: &str,
+    event_type: &str,
+    state_key: Option<&str>,
+    content: Value,
+    delay_ms: u64,
+) -> Result<Json<Value>, ApiError> {
+    let delay_id = ctx
+        .delayed_event_service
+        .schedule(DelayedEventRequest {
+            room_id: room_id.to_string(),
+            user_id: auth_user.user_id.clone(),
+            device_id: auth_user.device_id.clone().unwrap_or_default(),
+            event_type: event_type.to_string(),
+            state_key: state_key.map(str::to_string),
+            content,
+            delay_ms,
+        })
+        .await?;
+    Ok(Json(json!({ "delay_id": delay_id })))
+}
+
+fn ensure_msc4140_enabled(ctx: &RoomContext) -> Result<(), ApiError> {
+    if ctx.delayed_event_service.is_enabled() {
+        Ok(())
+    } else {
+        Err(ApiError::unrecognized("Delayed events are not enabled on this server"))
+    }
+}
+
+async fn get_delayed_events(
+    State(ctx): State<RoomContext>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    ensure_msc4140_enabled(&ctx)?;
+    let delayed_events = ctx.delayed_event_service.list(&auth_user.user_id).await?;
+    Ok(Json(json!({ "delayed_events": delayed_events })))
+}
+
+#[derive(Debug, Deserialize)]
+struct UpdateDelayedEventBody {
+    action: String,
+}
+
+async fn update_delayed_event(
+    State(ctx): State<RoomContext>,
+    auth_user: AuthenticatedUser,
+    Path(delay_id): Path<String>,
+    Json(body): Json<UpdateDelayedEventBody>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_msc4140_enabled(&ctx)?;
+    let action = DelayedEventAction::parse(&body.action)?;
+    ctx.delayed_event_service.update(&auth_user.user_id, &delay_id, action).await?;
+    Ok(Json(json!({})))
+}
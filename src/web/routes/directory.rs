@@ -1,5 +1,7 @@
 use crate::web::routes::context::RoomContext;
-use crate::web::routes::{ensure_room_member_ctx, validate_room_alias, ApiError, AuthenticatedUser};
+use crate::web::routes::{
+    ensure_alias_creation_allowed, ensure_room_member_ctx, validate_room_alias, ApiError, AuthenticatedUser,
+};
 use axum::{
     extract::{Path, Query, State},
     Json,
@@ -145,6 +147,13 @@ pub async fn set_room_alias_handler(
     let room_id = &body.room_id;
 
     ensure_room_alias_write_allowed(&ctx, &auth_user, room_id).await?;
+    ensure_alias_creation_allowed(
+        &ctx.config.room_directory,
+        &auth_user.user_id,
+        auth_user.is_admin,
+        &room_alias,
+        room_id,
+    )?;
 
     ctx.directory_service
         .set_room_alias(room_id, &room_alias)
@@ -3,7 +3,8 @@ use crate::web::extractors::{AuthenticatedUser, OptionalAuthenticatedUser};
 use crate::web::routes::context::AdminContext;
 use crate::web::routes::{
     account_compat::{can_view_profile_for_requester_batch, enforce_profile_visibility},
-    ensure_room_member_admin, validate_event_id, validate_room_alias, validate_room_id, validate_user_id,
+    ensure_alias_creation_allowed, ensure_room_member_admin, validate_event_id, validate_room_alias, validate_room_id,
+    validate_user_id,
 };
 use crate::web::utils::auth::resolve_request_id;
 use axum::{
@@ -144,10 +145,11 @@ pub(crate) async fn list_user_directory(
     let visibility =
         can_view_profile_for_requester_batch(&ctx.account_identity_service, Some(&auth_user.user_id), &target_user_ids)
             .await?;
+    let hidden = ctx.account_data_service.users_hidden_from_directory(&target_user_ids).await?;
 
     let mut users_json = Vec::new();
     for u in users {
-        if !visibility.get(&u.user_id).copied().unwrap_or(true) {
+        if !visibility.get(&u.user_id).copied().unwrap_or(true) || hidden.contains(&u.user_id) {
             continue;
         }
 
@@ -316,6 +318,13 @@ pub(crate) async fn set_room_alias(
     }
 
     ensure_room_alias_write_allowed(&ctx, &auth_user, &room_id).await?;
+    ensure_alias_creation_allowed(
+        &ctx.config.room_directory,
+        &auth_user.user_id,
+        auth_user.is_admin,
+        &room_alias,
+        &room_id,
+    )?;
 
     ctx.room_service.state().set_room_alias(&room_id, &room_alias, &auth_user.user_id).await?;
     ::tracing::info!(
@@ -382,6 +391,13 @@ pub(crate) async fn set_room_alias_direct(
     }
 
     ensure_room_alias_write_allowed(&ctx, &auth_user, room_id).await?;
+    ensure_alias_creation_allowed(
+        &ctx.config.room_directory,
+        &auth_user.user_id,
+        auth_user.is_admin,
+        &room_alias,
+        room_id,
+    )?;
 
     ctx.room_service.state().set_room_alias(room_id, &room_alias, &auth_user.user_id).await?;
     ::tracing::info!(
@@ -2,7 +2,8 @@
 // DM room creation and management
 
 use crate::web::routes::context::RoomContext;
-use crate::web::routes::{ApiError, AppState, AuthenticatedUser};
+use crate::web::routes::handlers::room::management::create::default_room_encryption;
+use crate::web::routes::{ensure_room_creation_allowed, ApiError, AppState, AuthenticatedUser};
 use axum::{
     extract::{Path, State},
     routing::{get, post, put},
@@ -359,6 +360,7 @@ async fn create_dm_room_via_service(
         visibility: body.visibility.clone().or_else(|| Some("private".to_string())),
         preset: Some("private_chat".to_string()),
         room_type: (invitee_user_ids.len() > 1).then(|| "m.direct".to_string()),
+        encryption: default_room_encryption(ctx, Some("private_chat"), body.visibility.as_deref()),
         ..Default::default()
     };
 
@@ -388,6 +390,7 @@ async fn create_dm_room_via_service(
                 is_direct: Some(true),
                 visibility: body.visibility.clone().or_else(|| Some("private".to_string())),
                 preset: Some("private_chat".to_string()),
+                encryption: default_room_encryption(ctx, Some("private_chat"), body.visibility.as_deref()),
                 ..Default::default()
             };
 
@@ -407,6 +410,7 @@ async fn create_dm_room_via_service(
             invite_list: Some(invitee_user_ids.to_vec()),
             is_direct: Some(body.is_direct.unwrap_or(true)),
             room_type: Some("m.direct".to_string()),
+            encryption: default_room_encryption(ctx, Some("private_chat"), body.visibility.as_deref()),
             ..Default::default()
         };
 
@@ -496,6 +500,15 @@ pub async fn create_dm_room(
         return Err(ApiError::bad_request("At least one user must be invited to create a DM"));
     };
 
+    let is_encrypted = default_room_encryption(&ctx, Some("private_chat"), body.visibility.as_deref()).is_some();
+    ensure_room_creation_allowed(
+        &ctx.config.room_creation,
+        &auth_user.user_id,
+        auth_user.is_admin,
+        false,
+        is_encrypted,
+    )?;
+
     let room_id = create_dm_room_via_service(&ctx, &auth_user.user_id, &users_to_invite, &body).await?;
 
     Ok(Json(json!({ "room_id": room_id })))
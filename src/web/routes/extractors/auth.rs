@@ -1,18 +1,32 @@
 use crate::common::ApiError;
+use crate::web::middleware::record_request_user;
 use crate::web::routes::context::{
     AdminContext, AuthContext, DeviceContext, E2eeRoomContext, FederationContext, MediaContext, RoomContext,
     SyncContext,
 };
 use crate::web::routes::AppState;
 use crate::web::utils::admin_auth::{authorize_admin_from_services, authorize_admin_request};
-use crate::web::utils::auth::resolve_request_id;
+use crate::web::utils::auth::{masquerade_user_id, resolve_request_id};
 use axum::{
     extract::FromRequestParts,
     http::{request::Parts, HeaderMap, Method},
 };
 use serde_json::json;
+use synapse_services::auth::TokenAuth;
 use synapse_storage::audit::CreateAuditEventRequest;
 
+/// Validate an access token and note its user on the request span. An
+/// application service token acts as the `user_id` query parameter, if any.
+async fn validate_access_token(
+    token_auth: &dyn TokenAuth,
+    token: &str,
+    uri: &str,
+) -> Result<(String, Option<String>, bool, bool, bool), ApiError> {
+    let validated = token_auth.validate_token_as(token, masquerade_user_id(uri).as_deref()).await?;
+    record_request_user(&validated.0);
+    Ok(validated)
+}
+
 #[derive(Clone)]
 pub struct AuthenticatedUser {
     pub user_id: String,
@@ -86,7 +100,7 @@ impl FromRequestParts<AppState> for AuthenticatedUser {
 
         async move {
             let token = token_result?;
-            let result = state.services.core.token_auth.validate_token(&token).await;
+            let result = validate_access_token(state.services.core.token_auth.as_ref(), &token, &uri).await;
             match result {
                 Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => {
                     audit_user_action(
@@ -144,7 +158,7 @@ impl FromRequestParts<AppState> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.services.core.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.services.core.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -199,7 +213,7 @@ impl FromRequestParts<RoomContext> for AuthenticatedUser {
 
         async move {
             let token = token_result?;
-            let result = state.token_auth.validate_token(&token).await;
+            let result = validate_access_token(state.token_auth.as_ref(), &token, &uri).await;
             match result {
                 Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => {
                     if let Some(ref audit_svc) = state.admin_audit_service {
@@ -230,7 +244,7 @@ impl FromRequestParts<E2eeRoomContext> for AuthenticatedUser {
 
         async move {
             let token = token_result?;
-            let result = state.token_auth.validate_token(&token).await;
+            let result = validate_access_token(state.token_auth.as_ref(), &token, &uri).await;
             match result {
                 Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => {
                     if let Some(ref audit_svc) = state.admin_audit_service {
@@ -262,7 +276,7 @@ impl FromRequestParts<SyncContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             if let Some(ref audit_svc) = state.admin_audit_service {
                 audit_user_action(audit_svc, &user_id, &method, &path, &headers, is_admin).await;
@@ -290,7 +304,7 @@ impl FromRequestParts<DeviceContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             if let Some(ref audit_svc) = state.admin_audit_service {
                 audit_user_action(audit_svc, &user_id, &method, &path, &headers, is_admin).await;
@@ -318,7 +332,7 @@ impl FromRequestParts<AuthContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             if let Some(ref audit_svc) = state.admin_audit_service {
                 audit_user_action(audit_svc, &user_id, &method, &path, &headers, is_admin).await;
@@ -343,7 +357,7 @@ impl FromRequestParts<RoomContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -387,7 +401,7 @@ impl FromRequestParts<SyncContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -431,7 +445,7 @@ impl FromRequestParts<DeviceContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -475,7 +489,7 @@ impl FromRequestParts<AuthContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -524,7 +538,7 @@ impl FromRequestParts<AdminContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             audit_user_action(&state.admin_audit_service, &user_id, &method, &path, &headers, is_admin).await;
 
@@ -550,7 +564,7 @@ impl FromRequestParts<FederationContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             if let Some(ref audit_svc) = state.admin_audit_service {
                 audit_user_action(audit_svc, &user_id, &method, &path, &headers, is_admin).await;
@@ -578,7 +592,7 @@ impl FromRequestParts<MediaContext> for AuthenticatedUser {
         async move {
             let token = token_result?;
             let (user_id, device_id, is_admin, is_shadow_banned, is_guest) =
-                state.token_auth.validate_token(&token).await?;
+                validate_access_token(state.token_auth.as_ref(), &token, &uri).await?;
 
             if let Some(ref audit_svc) = state.admin_audit_service {
                 audit_user_action(audit_svc, &user_id, &method, &path, &headers, is_admin).await;
@@ -603,7 +617,7 @@ impl FromRequestParts<AdminContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -647,7 +661,7 @@ impl FromRequestParts<FederationContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -691,7 +705,7 @@ impl FromRequestParts<MediaContext> for OptionalAuthenticatedUser {
 
         async move {
             match token_result {
-                Ok(token) => match state.token_auth.validate_token(&token).await {
+                Ok(token) => match validate_access_token(state.token_auth.as_ref(), &token, &uri).await {
                     Ok((user_id, device_id, is_admin, is_shadow_banned, is_guest)) => Ok(Self {
                         user_id: Some(user_id),
                         device_id,
@@ -1,6 +1,11 @@
 use crate::common::ApiError;
 use axum::extract::rejection::JsonRejection;
+use validator::Validate;
 
+/// JSON body extractor that reports failures as Matrix errors: a body that is
+/// not JSON (or not labelled as JSON) is `M_NOT_JSON`, JSON that does not fit
+/// the target type is `M_BAD_JSON` with serde's description of the offending
+/// field.
 pub struct MatrixJson<T>(pub T);
 
 impl<S, T> axum::extract::FromRequest<S> for MatrixJson<T>
@@ -13,24 +18,41 @@ where
     async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
         match axum::extract::Json::<T>::from_request(req, state).await {
             Ok(axum::extract::Json(value)) => Ok(Self(value)),
-            Err(rejection) => {
-                let message = match rejection {
-                    JsonRejection::JsonDataError(e) => format!("Invalid JSON data: {e}"),
-                    JsonRejection::JsonSyntaxError(e) => format!("JSON syntax error: {e}"),
-                    JsonRejection::MissingJsonContentType(e) => {
-                        format!("Missing Content-Type: application/json: {e}")
-                    }
-                    _ => format!("JSON error: {rejection}"),
-                };
-                Err(ApiError::bad_request(message))
-            }
+            Err(rejection) => Err(match rejection {
+                JsonRejection::JsonDataError(e) => {
+                    ApiError::bad_request(format!("Invalid JSON data: {}", e.body_text()))
+                }
+                JsonRejection::JsonSyntaxError(e) => ApiError::not_json(format!("JSON syntax error: {e}")),
+                JsonRejection::MissingJsonContentType(e) => {
+                    ApiError::not_json(format!("Missing Content-Type: application/json: {e}"))
+                }
+                _ => ApiError::bad_request(format!("JSON error: {rejection}")),
+            }),
         }
     }
 }
 
+/// [`MatrixJson`] followed by the type's `validator` rules; rule violations
+/// are reported as `M_INVALID_PARAM`.
+pub struct ValidatedJson<T>(pub T);
+
+impl<S, T> axum::extract::FromRequest<S> for ValidatedJson<T>
+where
+    S: Send + Sync,
+    T: serde::de::DeserializeOwned + Validate + Send,
+{
+    type Rejection = ApiError;
+
+    async fn from_request(req: axum::extract::Request, state: &S) -> Result<Self, Self::Rejection> {
+        let MatrixJson(value) = MatrixJson::<T>::from_request(req, state).await?;
+        value.validate().map_err(|e| ApiError::invalid_param(format!("Invalid request body: {e}")))?;
+        Ok(Self(value))
+    }
+}
+
 #[cfg(test)]
 mod tests {
-    use super::MatrixJson;
+    use super::{MatrixJson, ValidatedJson};
     use crate::common::ApiError;
     use axum::{
         body::Body,
@@ -39,9 +61,12 @@ mod tests {
     };
     use futures::stream;
     use serde::Deserialize;
+    use synapse_common::error::MatrixErrorCode;
+    use validator::Validate;
 
-    #[derive(Debug, Deserialize, PartialEq)]
+    #[derive(Debug, Deserialize, PartialEq, Validate)]
     struct SamplePayload {
+        #[validate(range(max = 10))]
         count: u32,
     }
 
@@ -76,6 +101,7 @@ mod tests {
         };
 
         assert_eq!(error.http_status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.code(), &MatrixErrorCode::NotJson);
         assert!(error.message().contains("JSON syntax error"));
     }
 
@@ -93,7 +119,9 @@ mod tests {
         };
 
         assert_eq!(error.http_status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.code(), &MatrixErrorCode::BadJson);
         assert!(error.message().contains("Invalid JSON data"));
+        assert!(error.message().contains("count"));
     }
 
     #[tokio::test]
@@ -128,4 +156,22 @@ mod tests {
         assert!(error.message().contains("JSON error"));
         assert!(error.message().contains("broken body stream"));
     }
+
+    #[tokio::test]
+    async fn validated_json_reports_rule_violations_as_invalid_param() {
+        let request = Request::builder()
+            .uri("/_matrix/test")
+            .header(CONTENT_TYPE, "application/json")
+            .body(Body::from(r#"{"count": 11}"#))
+            .unwrap();
+
+        let error = match ValidatedJson::<SamplePayload>::from_request(request, &()).await {
+            Ok(_) => panic!("expected validation error"),
+            Err(error) => error,
+        };
+
+        assert_eq!(error.http_status(), StatusCode::BAD_REQUEST);
+        assert_eq!(error.code(), &MatrixErrorCode::InvalidParam);
+        assert!(error.message().contains("count"));
+    }
 }
@@ -38,7 +38,7 @@ impl UserId {
 
 // extract_token_from_headers removed — use crate::web::utils::auth::bearer_token directly
 pub use auth::{AdminUser, AuthenticatedUser, OptionalAuthenticatedUser};
-pub use json::MatrixJson;
+pub use json::{MatrixJson, ValidatedJson};
 pub use pagination::Pagination;
 
 // ============== DeviceId ==============
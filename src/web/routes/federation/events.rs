@@ -91,8 +91,7 @@ pub(super) async fn get_event_auth(
     super::validate_federation_origin_can_observe_room(&ctx, &room_id, &auth.origin).await?;
 
     let event = get_room_event_in_room(&ctx, &room_id, &event_id).await?;
-    let auth_events =
-        ctx.room_service.messaging().get_state_events_at_or_before(&room_id, event.origin_server_ts).await?;
+    let auth_events = ctx.room_service.messaging().get_state_events_at_event(&event).await?;
 
     let auth_chain: Vec<Value> = auth_events
         .into_iter()
@@ -684,7 +683,7 @@ async fn load_federation_state_events(
     match event_id {
         Some(event_id) => {
             let event = get_room_event_in_room(ctx, room_id, event_id).await?;
-            ctx.room_service.messaging().get_state_events_at_or_before(room_id, event.origin_server_ts).await
+            ctx.room_service.messaging().get_state_events_at_event(&event).await
         }
         None => ctx.room_service.messaging().get_state_event_records(room_id).await,
     }
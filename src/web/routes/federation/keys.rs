@@ -294,7 +294,7 @@ async fn fetch_remote_server_keys_response(
         .map_err(|e| ApiError::internal_with_log("Federation key fetch semaphore closed", &e))?;
 
     let timeout_ms = ctx.config.federation.key_fetch_timeout_ms.max(1);
-    let client = reqwest::Client::builder()
+    let client = synapse_common::outbound_http::client_builder()
         .timeout(std::time::Duration::from_millis(timeout_ms))
         .redirect(reqwest::redirect::Policy::none())
         .build()
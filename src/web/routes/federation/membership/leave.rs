@@ -13,6 +13,34 @@ use super::{
     dispatch_federation_member_event_to_appservice, federatable_room_version, validate_federation_member_event,
 };
 
+/// Build a leave event template (pure, testable).
+///
+/// The requesting server completes and signs it before calling `send_leave`.
+pub(crate) fn build_leave_event_template(room_id: &str, user_id: &str, origin: &str) -> Value {
+    json!({
+        "type": "m.room.member",
+        "room_id": room_id,
+        "content": {
+            "membership": "leave"
+        },
+        "sender": user_id,
+        "state_key": user_id,
+        "origin": origin,
+        "origin_server_ts": current_timestamp_millis()
+    })
+}
+
+/// Only users who are invited, joined or knocking can leave: rejecting an
+/// invite is a leave, but leaving twice or leaving a room you were never in
+/// is not.
+async fn validate_federation_leave_access(ctx: &FederationContext, room_id: &str, user_id: &str) -> ApiResult<()> {
+    let member = ctx.room_service.membership().get_room_member_record(room_id, user_id).await?;
+    match member.as_ref().map(|m| m.membership.as_str()) {
+        Some("join" | "invite" | "knock") => Ok(()),
+        _ => Err(ApiError::forbidden("User is not invited to, knocking on or joined to this room".to_string())),
+    }
+}
+
 pub(crate) async fn make_leave(
     State(ctx): State<FederationContext>,
     Extension(auth): Extension<FederationRequestAuth>,
@@ -24,6 +52,7 @@ pub(crate) async fn make_leave(
     // Access denied and non-existent rooms both return 404.
     super::validate_federation_origin_can_observe_room(&ctx, &room_id, &auth.origin).await?;
     let room_version = federatable_room_version(&ctx, &room_id).await?;
+    validate_federation_leave_access(&ctx, &room_id, &user_id).await?;
 
     let auth_events = ctx.room_service.messaging().get_state_event_records(&room_id).await?;
 
@@ -41,14 +70,7 @@ pub(crate) async fn make_leave(
     Ok(Json(json!({
         "room_version": room_version,
         "auth_events": auth_events_json,
-        "event": {
-            "type": "m.room.member",
-            "content": {
-                "membership": "leave"
-            },
-            "sender": user_id,
-            "state_key": user_id
-        }
+        "event": build_leave_event_template(&room_id, &user_id, &auth.origin)
     })))
 }
 
@@ -68,6 +90,7 @@ pub(crate) async fn send_leave(
     // Access denied and non-existent rooms both return 404.
     super::validate_federation_origin_can_observe_room(&ctx, &room_id, &auth.origin).await?;
     let _room_version = federatable_room_version(&ctx, &room_id).await?;
+    validate_federation_leave_access(&ctx, &room_id, user_id).await?;
 
     let params = synapse_storage::event::CreateEventParams {
         event_id: event_id.clone(),
@@ -123,6 +146,7 @@ pub(crate) async fn send_leave_v2(
     }
     let sender = validate_federation_member_event(&auth.origin, &room_id, &event_id, &body, "leave")?;
     let _room_version = federatable_room_version(&ctx, &room_id).await?;
+    validate_federation_leave_access(&ctx, &room_id, sender).await?;
     let membership_content = serde_json::json!({
         "membership": "leave"
     });
@@ -19,6 +19,7 @@ pub mod events;
 pub mod keys;
 pub mod media;
 pub mod membership;
+pub mod registry;
 pub mod transaction;
 
 pub(super) fn validate_federation_origin(
@@ -35,14 +36,11 @@ pub(super) fn validate_federation_origin(
 }
 
 pub(super) fn sender_server_name(sender: &str) -> Option<&str> {
-    sender
-        .strip_prefix('@')
-        .and_then(|user| user.rsplit_once(':').map(|(_, server)| server))
-        .filter(|server| !server.is_empty())
+    synapse_common::validation::user_id_server_name(sender)
 }
 
 pub(super) fn user_matches_origin(user_id: &str, origin: &str) -> bool {
-    user_id.rsplit_once(':').is_some_and(|(_, server_name)| server_name == origin)
+    sender_server_name(user_id) == Some(origin)
 }
 
 pub(super) async fn validate_federation_origin_in_room(
@@ -300,7 +298,9 @@ pub fn create_federation_router(state: &AppState) -> Router<AppState> {
         ))
         .layer(middleware::from_fn_with_state(fed_ctx, crate::web::middleware::federation_auth_middleware));
 
-    public.merge(protected)
+    // Every federation response, including axum's own extractor rejections,
+    // leaves with a spec-shaped `{"errcode", "error"}` body.
+    public.merge(protected).layer(middleware::from_fn(crate::web::middleware::matrix_error_shape_middleware))
 }
 
 fn federation_public_relative_routes() -> Vec<(axum::http::Method, &'static str)> {
@@ -0,0 +1,124 @@
+//! Compile-time registry of the federation endpoints this server serves.
+//!
+//! The registry is the source for the admin federation diagnostics endpoint.
+//! A unit test keeps it in lockstep with [`super::federation_route_manifest`],
+//! so an endpoint cannot be routed without also being listed here.
+
+use serde::Serialize;
+
+/// How a federation endpoint authenticates its caller.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FederationEndpointAuth {
+    /// Reachable without an `X-Matrix` signature (keys, version, discovery).
+    Public,
+    /// Requires a signed `X-Matrix` authorization header.
+    Signed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct FederationEndpoint {
+    pub method: &'static str,
+    pub path: &'static str,
+    pub auth: FederationEndpointAuth,
+}
+
+impl FederationEndpoint {
+    /// `true` for endpoints defined by the server-server spec, `false` for
+    /// the non-standard extensions served under `/_synapse/federation/`.
+    pub fn is_spec(&self) -> bool {
+        self.path.starts_with("/_matrix/")
+    }
+}
+
+const fn public(method: &'static str, path: &'static str) -> FederationEndpoint {
+    FederationEndpoint { method, path, auth: FederationEndpointAuth::Public }
+}
+
+const fn signed(method: &'static str, path: &'static str) -> FederationEndpoint {
+    FederationEndpoint { method, path, auth: FederationEndpointAuth::Signed }
+}
+
+pub const FEDERATION_ENDPOINTS: &[FederationEndpoint] = &[
+    public("GET", "/_matrix/federation/v2/server"),
+    public("GET", "/_matrix/key/v2/server"),
+    public("GET", "/_matrix/federation/v2/query/{server_name}/{key_id}"),
+    public("GET", "/_matrix/key/v2/query/{server_name}/{key_id}"),
+    public("GET", "/_matrix/federation/v1/version"),
+    public("GET", "/_matrix/federation/v1"),
+    public("GET", "/_matrix/federation/v1/publicRooms"),
+    public("GET", "/_matrix/federation/v1/query/destination"),
+    public("GET", "/_matrix/federation/v1/openid/userinfo"),
+    signed("PUT", "/_matrix/federation/v1/send/{txn_id}"),
+    signed("POST", "/_matrix/federation/v1/get_missing_events/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/room/{room_id}/{event_id}"),
+    signed("GET", "/_matrix/federation/v1/timestamp_to_event/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/get_event_auth/{room_id}/{event_id}"),
+    signed("GET", "/_matrix/federation/v1/state/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/event/{event_id}"),
+    signed("GET", "/_matrix/federation/v1/state_ids/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/query/directory/room/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/query/profile"),
+    signed("GET", "/_matrix/federation/v1/query/profile/{user_id}"),
+    signed("GET", "/_matrix/federation/v1/hierarchy/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/backfill/{room_id}"),
+    signed("POST", "/_matrix/federation/v1/user/keys/upload"),
+    signed("POST", "/_matrix/federation/v1/user/keys/claim"),
+    signed("POST", "/_matrix/federation/v1/user/keys/query"),
+    signed("POST", "/_matrix/federation/v2/user/keys/query"),
+    signed("POST", "/_matrix/federation/v1/publicRooms"),
+    signed("GET", "/_matrix/federation/v1/query/directory"),
+    signed("GET", "/_matrix/federation/v1/media/download/{server_name}/{media_id}"),
+    signed("GET", "/_matrix/federation/v1/media/thumbnail/{server_name}/{media_id}"),
+    signed("POST", "/_synapse/federation/v2/key/clone"),
+    signed("POST", "/_synapse/federation/v1/keys/claim"),
+    signed("POST", "/_synapse/federation/v1/keys/query"),
+    signed("POST", "/_synapse/federation/v1/keys/upload"),
+    signed("GET", "/_synapse/federation/v1/room_auth/{room_id}"),
+    signed("GET", "/_synapse/federation/v1/query/auth"),
+    signed("GET", "/_synapse/federation/v1/event_auth"),
+    signed("GET", "/_matrix/federation/v1/members/{room_id}"),
+    signed("GET", "/_matrix/federation/v1/members/{room_id}/joined"),
+    signed("GET", "/_matrix/federation/v1/user/devices/{user_id}"),
+    signed("POST", "/_matrix/federation/v1/knock/{room_id}/{user_id}"),
+    signed("POST", "/_matrix/federation/v1/thirdparty/invite"),
+    signed("PUT", "/_matrix/federation/v2/invite/{room_id}/{event_id}"),
+    signed("GET", "/_matrix/federation/v1/make_join/{room_id}/{user_id}"),
+    signed("GET", "/_matrix/federation/v1/make_leave/{room_id}/{user_id}"),
+    signed("PUT", "/_matrix/federation/v1/send_join/{room_id}/{event_id}"),
+    signed("PUT", "/_matrix/federation/v1/send_leave/{room_id}/{event_id}"),
+    signed("PUT", "/_matrix/federation/v1/invite/{room_id}/{event_id}"),
+    signed("PUT", "/_matrix/federation/v2/send_join/{room_id}/{event_id}"),
+    signed("PUT", "/_matrix/federation/v2/send_leave/{room_id}/{event_id}"),
+    signed("PUT", "/_matrix/federation/v1/exchange_third_party_invite/{room_id}"),
+    signed("GET", "/_synapse/federation/v1/get_joining_rules/{room_id}"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    #[test]
+    fn registry_matches_route_manifest() {
+        let registry: BTreeSet<(String, String)> =
+            FEDERATION_ENDPOINTS.iter().map(|e| (e.method.to_string(), e.path.to_string())).collect();
+        let manifest: BTreeSet<(String, String)> = super::super::federation_route_manifest()
+            .iter()
+            .map(|e| (e.method.as_str().to_string(), e.path.to_string()))
+            .collect();
+        assert_eq!(registry.len(), FEDERATION_ENDPOINTS.len(), "registry contains duplicates");
+        assert_eq!(registry, manifest);
+    }
+
+    #[test]
+    fn version_endpoint_is_public_spec_endpoint() {
+        let version = FEDERATION_ENDPOINTS
+            .iter()
+            .find(|e| e.path == "/_matrix/federation/v1/version")
+            .expect("version endpoint registered");
+        assert_eq!(version.auth, FederationEndpointAuth::Public);
+        assert!(version.is_spec());
+        assert!(FEDERATION_ENDPOINTS.iter().any(|e| !e.is_spec()));
+    }
+}
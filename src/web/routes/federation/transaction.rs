@@ -10,9 +10,12 @@ use axum::{
 use serde_json::{json, Value};
 use std::sync::Arc;
 use synapse_common::current_timestamp_millis;
+use synapse_common::validation;
+use synapse_storage::event::{RejectedEventParams, RejectionReason, SoftFailKind};
 use tokio::sync::{OwnedSemaphorePermit, Semaphore};
 
 const TXN_DEDUP_TTL_SECS: u64 = 86400;
+const MAX_REJECTED_FIELD_LEN: usize = 255;
 
 pub(super) async fn send_transaction(
     State(ctx): State<FederationContext>,
@@ -207,6 +210,7 @@ pub(super) async fn send_transaction(
                 error = %e,
                 "Inbound PDU exceeded size limits"
             );
+            record_rejected_pdu(&ctx, origin, &event_id, pdu, RejectionReason::TooLarge, e.clone()).await;
             results.push(json!({
                 "event_id": event_id,
                 "error": e
@@ -224,6 +228,7 @@ pub(super) async fn send_transaction(
                 error = %e,
                 "Inbound PDU content hash verification failed"
             );
+            record_rejected_pdu(&ctx, origin, &event_id, pdu, RejectionReason::ContentHash, e.clone()).await;
             results.push(json!({
                 "event_id": event_id,
                 "error": e
@@ -241,6 +246,7 @@ pub(super) async fn send_transaction(
                 error = %e,
                 "Inbound PDU sender-server signature verification failed - rejecting potential impersonation"
             );
+            record_rejected_pdu(&ctx, origin, &event_id, pdu, RejectionReason::Signature, e.clone()).await;
             results.push(json!({
                 "event_id": event_id,
                 "error": format!("Invalid PDU signature: {}", e)
@@ -252,6 +258,7 @@ pub(super) async fn send_transaction(
             Ok(validated) => validated,
             Err(error) => {
                 super::increment_counter(&ctx, "federation_inbound_txn_pdu_error_total");
+                record_rejected_pdu(&ctx, origin, &event_id, pdu, RejectionReason::Malformed, error.to_string()).await;
                 results.push(json!({
                     "event_id": event_id,
                     "error": error.to_string()
@@ -279,6 +286,15 @@ pub(super) async fn send_transaction(
                             event_id = event_id,
                             "Rejected inbound PDU for non-federated room"
                         );
+                        record_rejected_pdu(
+                            &ctx,
+                            origin,
+                            &event_id,
+                            pdu,
+                            RejectionReason::Auth,
+                            "This room is not federated".to_string(),
+                        )
+                        .await;
                         results.push(json!({
                             "event_id": event_id,
                             "error": "This room is not federated"
@@ -301,6 +317,7 @@ pub(super) async fn send_transaction(
                     error = %e,
                     "Rejected inbound PDU from origin with no members in room"
                 );
+                record_rejected_pdu(&ctx, origin, &event_id, pdu, RejectionReason::Auth, e.to_string()).await;
                 results.push(json!({
                     "event_id": event_id,
                     "error": "Origin server has no joined members in this room"
@@ -309,22 +326,23 @@ pub(super) async fn send_transaction(
             }
         }
 
+        // Checks against the room's current state soft-fail the event rather
+        // than rejecting it: the event is stored in the DAG (other servers
+        // may have accepted it, and state resolution needs it) but hidden
+        // from clients and kept out of the current state.
+        let mut soft_fail: Option<(SoftFailKind, String)> = None;
+
         if state_key.is_some() && event_type != "m.room.member" {
             if let Err(error) = ctx.room_auth.verify_state_event_write(room_id, user_id, event_type).await {
-                super::increment_counter(&ctx, "federation_inbound_txn_pdu_error_total");
-                results.push(json!({
-                    "event_id": event_id,
-                    "error": error.to_string()
-                }));
-                continue;
+                soft_fail = Some((SoftFailKind::Auth, error.to_string()));
             }
         }
 
         // S5 gap 2: authorize inbound `m.room.member` transitions against the
         // membership state machine (banned re-join, invite-of-banned, self-ban,
         // knock into a non-knock room). Power is validated via the auth-event
-        // chain; this fails closed only on illegal state transitions.
-        if event_type == "m.room.member" {
+        // chain; illegal state transitions are soft-failed.
+        if event_type == "m.room.member" && soft_fail.is_none() {
             let to_membership = content
                 .get("membership")
                 .and_then(|v| v.as_str())
@@ -349,7 +367,6 @@ pub(super) async fn send_transaction(
                     .authorize_inbound_member_transition(room_id, user_id, target, to)
                     .await
                 {
-                    super::increment_counter(&ctx, "federation_inbound_txn_pdu_error_total");
                     ::tracing::warn!(
                         target: "security_audit",
                         event = "federation_illegal_member_transition",
@@ -359,13 +376,53 @@ pub(super) async fn send_transaction(
                         membership = ?to,
                         event_id = event_id,
                         error = %error,
-                        "Rejected inbound m.room.member with illegal state transition"
+                        "Soft-failing inbound m.room.member with illegal state transition"
                     );
-                    results.push(json!({
-                        "event_id": event_id,
-                        "error": error.to_string()
-                    }));
-                    continue;
+                    soft_fail = Some((SoftFailKind::Auth, error.to_string()));
+                }
+            }
+        }
+
+        // A state event concurrent with the current holder of its state key
+        // only replaces it if it wins state resolution, instead of the newer
+        // timestamp winning.
+        if state_key.is_some() && soft_fail.is_none() {
+            match ctx.room_service.state().inbound_state_wins_resolution(room_id, pdu).await {
+                Ok(true) => {}
+                Ok(false) => {
+                    soft_fail = Some((
+                        SoftFailKind::Auth,
+                        "Event lost state resolution against the current room state".to_string(),
+                    ));
+                }
+                Err(e) => {
+                    ::tracing::warn!(event_id = %event_id, room_id = room_id, error = %e, "State resolution failed for inbound PDU");
+                }
+            }
+        }
+
+        if soft_fail.is_none() {
+            let spam_context = synapse_services::module_service::SpamCheckContext {
+                event_id: event_id.clone(),
+                room_id: room_id.to_string(),
+                sender: user_id.to_string(),
+                event_type: event_type.to_string(),
+                content: content.clone(),
+            };
+            match ctx.module_service.check_spam(&spam_context).await {
+                Ok(output)
+                    if matches!(
+                        output.result,
+                        synapse_services::module_service::SpamCheckResultType::Block
+                            | synapse_services::module_service::SpamCheckResultType::ShadowBan
+                    ) =>
+                {
+                    let reason = output.reason.unwrap_or_else(|| "Flagged by spam checker".to_string());
+                    soft_fail = Some((SoftFailKind::Spam, reason));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    ::tracing::warn!(event_id = %event_id, room_id = room_id, error = %e, "Spam check failed for inbound PDU");
                 }
             }
         }
@@ -537,6 +594,49 @@ pub(super) async fn send_transaction(
             redacts: redacts_target.clone(),
         };
 
+        if let Some((kind, reason)) = soft_fail {
+            match ctx
+                .room_service
+                .messaging()
+                .create_soft_failed_event_with_graph(params, &prev_events, &auth_events, depth, kind, &reason)
+                .await
+            {
+                Ok(_) => {
+                    super::increment_counter(&ctx, "federation_inbound_txn_pdu_soft_failed_total");
+                    ::tracing::info!(
+                        request_id = %request_id,
+                        txn_id = %txn_id,
+                        origin = %origin,
+                        event_id = %event_id,
+                        room_id = room_id,
+                        kind = kind.as_str(),
+                        reason = %reason,
+                        "Soft-failed inbound PDU"
+                    );
+                    results.push(json!({
+                        "event_id": event_id,
+                        "success": true
+                    }));
+                }
+                Err(e) => {
+                    super::increment_counter(&ctx, "federation_inbound_txn_pdu_error_total");
+                    ::tracing::error!(
+                        request_id = %request_id,
+                        txn_id = %txn_id,
+                        origin = %origin,
+                        event_id = %event_id,
+                        error = %e,
+                        "Failed to persist soft-failed PDU"
+                    );
+                    results.push(json!({
+                        "event_id": event_id,
+                        "error": e.to_string()
+                    }));
+                }
+            }
+            continue;
+        }
+
         match ctx
             .room_service
             .messaging()
@@ -638,6 +738,10 @@ fn validate_inbound_transaction_pdu<'a>(authenticated_origin: &str, pdu: &'a Val
         .ok_or_else(|| ApiError::bad_request("Missing type in inbound PDU".to_string()))?;
     let state_key = pdu.get("state_key").and_then(|v| v.as_str());
 
+    validation::validate_room_id(room_id)
+        .and_then(|()| validation::validate_user_id(sender))
+        .map_err(|e| ApiError::bad_request(format!("Malformed inbound PDU: {}", e.message)))?;
+
     if super::sender_server_name(sender) != Some(authenticated_origin) {
         return Err(ApiError::forbidden("Federation PDU sender does not match authenticated origin".to_string()));
     }
@@ -649,6 +753,37 @@ fn validate_inbound_transaction_pdu<'a>(authenticated_origin: &str, pdu: &'a Val
     Ok((room_id, sender, event_type, state_key))
 }
 
+/// Records a PDU rejected before persistence for the admin API. Best effort:
+/// a failure is logged and does not change the PDU's result. Fields are
+/// copied from the PDU only when they are strings of a sane length, since
+/// the PDU may be rejected for being oversized or malformed.
+async fn record_rejected_pdu(
+    ctx: &FederationContext,
+    origin: &str,
+    event_id: &str,
+    pdu: &Value,
+    reason: RejectionReason,
+    detail: String,
+) {
+    let field = |key: &str| {
+        pdu.get(key).and_then(|v| v.as_str()).filter(|s| s.len() <= MAX_REJECTED_FIELD_LEN).map(String::from)
+    };
+    let params = RejectedEventParams {
+        event_id: event_id.chars().take(MAX_REJECTED_FIELD_LEN).collect(),
+        origin: origin.to_string(),
+        room_id: field("room_id"),
+        sender: field("sender"),
+        event_type: field("type"),
+        reason,
+        detail,
+    };
+    if let Err(e) =
+        ctx.room_service.messaging().record_rejected_event(params, ctx.config.federation.rejected_events_max).await
+    {
+        ::tracing::warn!(origin = origin, event_id = event_id, error = %e, "Failed to record rejected PDU");
+    }
+}
+
 async fn verify_pdu_sender_signature(ctx: &FederationContext, pdu: &Value) -> Result<(), String> {
     let sender = pdu.get("sender").and_then(|v| v.as_str()).ok_or_else(|| "Missing sender on PDU".to_string())?;
     let sender_server =
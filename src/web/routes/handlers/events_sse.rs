@@ -0,0 +1,118 @@
+//! Server-Sent Events variant of the legacy `/events` stream.
+//!
+//! Each room event is sent as an `m.room.event` message whose data is the
+//! client-format event JSON. The last message of every `/events` batch carries
+//! the batch's `end` token as its SSE id, so a reconnecting client resumes via
+//! `Last-Event-ID` (or `from` on the first connection) without gaps. The
+//! `filter` parameter is applied server-side exactly as on `/events`, which
+//! lets a bot follow a single room or event type without a sync loop. Errors
+//! end the stream with a final `error` message holding the Matrix errcode.
+
+use crate::common::ApiError;
+use crate::web::routes::context::SyncContext;
+use crate::web::routes::handlers::sync::{parse_u64_query_param, MAX_EVENTS_TIMEOUT_MS};
+use crate::web::routes::AuthenticatedUser;
+use axum::{
+    extract::{Query, State},
+    http::HeaderMap,
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures::stream::{self, StreamExt};
+use serde_json::Value;
+use std::convert::Infallible;
+
+struct EventsSseState {
+    ctx: SyncContext,
+    user_id: String,
+    access_token: String,
+    from: Option<String>,
+    filter: Option<String>,
+    timeout: u64,
+    finished: bool,
+}
+
+pub(crate) async fn events_sse(
+    State(ctx): State<SyncContext>,
+    headers: HeaderMap,
+    auth_user: AuthenticatedUser,
+    Query(params): Query<Value>,
+) -> Result<Response, ApiError> {
+    if !ctx.config.experimental.events_sse_enabled {
+        return Err(ApiError::unrecognized("Event streaming over SSE is not enabled on this server"));
+    }
+
+    let from = headers
+        .get("last-event-id")
+        .and_then(|v| v.to_str().ok())
+        .or_else(|| params.get("from").and_then(Value::as_str))
+        .map(str::to_owned);
+    let timeout = parse_u64_query_param(&params, "timeout").unwrap_or(30000).min(MAX_EVENTS_TIMEOUT_MS);
+
+    let state = EventsSseState {
+        ctx,
+        user_id: auth_user.user_id,
+        access_token: auth_user.access_token,
+        from,
+        filter: params.get("filter").and_then(Value::as_str).map(str::to_owned),
+        timeout,
+        finished: false,
+    };
+
+    let events = stream::unfold(state, |mut state| async move {
+        if state.finished {
+            return None;
+        }
+        let batch = next_batch(&mut state).await;
+        Some((stream::iter(batch), state))
+    })
+    .flatten()
+    .map(Ok::<_, Infallible>);
+
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()).into_response())
+}
+
+/// One `/events` round turned into SSE messages. The access token is checked
+/// every round so logging out also ends the stream.
+async fn next_batch(state: &mut EventsSseState) -> Vec<Event> {
+    if let Err(e) = state.ctx.token_auth.validate_token(&state.access_token).await {
+        state.finished = true;
+        return vec![Event::default().event("error").data(e.code_str())];
+    }
+
+    let response = match state
+        .ctx
+        .sync_service
+        .get_events(&state.user_id, state.from.as_deref(), state.filter.as_deref(), state.timeout)
+        .await
+    {
+        Ok(response) => response,
+        Err(e) => {
+            ::tracing::warn!(user_id = %state.user_id, error = %e, "SSE event stream round failed");
+            state.finished = true;
+            return vec![Event::default().event("error").data(e.code_str())];
+        }
+    };
+
+    let end = response.get("end").and_then(Value::as_str).map(str::to_owned);
+    let chunk = response.get("chunk").and_then(Value::as_array).cloned().unwrap_or_default();
+    let last = chunk.len().saturating_sub(1);
+    let events = chunk
+        .iter()
+        .enumerate()
+        .map(|(i, event)| {
+            let message = Event::default().event("m.room.event").data(event.to_string());
+            match &end {
+                Some(end) if i == last => message.id(end.as_str()),
+                _ => message,
+            }
+        })
+        .collect();
+
+    if end.is_some() {
+        state.from = end;
+    }
+    events
+}
@@ -2,6 +2,7 @@
 
 use crate::web::routes::context::AdminContext;
 use axum::extract::State;
+use axum::http::StatusCode;
 use axum::response::IntoResponse;
 use axum::Json;
 use serde_json::json;
@@ -25,6 +26,25 @@ pub async fn health_check(State(ctx): State<AdminContext>) -> impl IntoResponse
     )
 }
 
+/// Kubernetes liveness probe: answers `200` while the process is serving
+/// requests. Dependency checks are deliberately excluded so an outage of
+/// Postgres or Redis does not get the pod restarted.
+pub async fn liveness_check(State(ctx): State<AdminContext>) -> impl IntoResponse {
+    let status = ctx.health_checker.check_liveness().await;
+    let http_status = if status.status == "unhealthy" { StatusCode::SERVICE_UNAVAILABLE } else { StatusCode::OK };
+    (http_status, Json(status))
+}
+
+/// Kubernetes readiness probe: runs every dependency check (database,
+/// migrations, cache, federation signing key) and answers `503` when the
+/// configured degradation policy says the server should not receive traffic.
+pub async fn readiness_check(State(ctx): State<AdminContext>) -> impl IntoResponse {
+    let status = ctx.health_checker.check_readiness().await;
+    let http_status =
+        if ctx.health_checker.is_ready(&status) { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE };
+    (http_status, Json(status))
+}
+
 pub async fn detailed_health_check(State(ctx): State<AdminContext>) -> impl IntoResponse {
     let mut checks = serde_json::Map::new();
     let mut overall_status = "healthy";
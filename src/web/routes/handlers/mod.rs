@@ -8,6 +8,7 @@
 pub mod auth_discovery;
 pub mod client_config;
 pub mod dehydrated_device;
+pub mod events_sse;
 pub mod extended_profile;
 pub mod health;
 pub mod presence;
@@ -15,6 +16,7 @@ pub mod room;
 pub mod rtc_transports;
 pub mod search;
 pub mod sync;
+pub mod sync_ws;
 pub mod thread;
 pub mod versions;
 
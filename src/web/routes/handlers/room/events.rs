@@ -1,7 +1,9 @@
-use super::{ensure_room_view_access, get_room_event, parse_room_messages_from_token};
+use super::drop_redundant_lazy_members;
+use super::{ensure_room_view_access, get_room_event, provision_app_service_membership, room_messages_query};
 use crate::common::{ApiError, ContentSanitizer};
 use crate::map_internal;
 use crate::web::routes::context::RoomContext;
+use crate::web::routes::delayed_events::{schedule_delayed_event, DelayQuery};
 use crate::web::routes::{validate_event_id, validate_room_id, AuthenticatedUser};
 use crate::web::utils::auth::resolve_request_id;
 use axum::{
@@ -11,6 +13,7 @@ use axum::{
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use synapse_common::current_timestamp_millis;
+use synapse_services::send_quota_service::QuotaKind;
 use synapse_storage::event::CreateEventParams;
 
 pub(crate) async fn get_single_event(
@@ -23,7 +26,7 @@ pub(crate) async fn get_single_event(
 
     ensure_room_view_access(&ctx, &auth_user, &room_id).await?;
 
-    let event = ctx.room_service.messaging().get_event(&room_id, &event_id).await?;
+    let event = ctx.room_service.messaging().get_visible_event(&room_id, &auth_user.user_id, &event_id).await?;
 
     Ok(Json(event))
 }
@@ -182,16 +185,10 @@ pub(crate) async fn get_messages(
 
     ensure_room_view_access(&ctx, &auth_user, &room_id).await?;
 
-    let from = parse_room_messages_from_token(&params);
-    let limit = params
-        .get("limit")
-        .and_then(|v| v.as_u64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
-        .unwrap_or(10)
-        .min(1000) as i64;
-    let direction = params.get("dir").and_then(|v| v.as_str()).unwrap_or("b");
-
-    let response =
-        ctx.room_service.messaging().get_room_messages(&room_id, &auth_user.user_id, from, limit, direction).await?;
+    let messages_params = room_messages_query(&ctx, &auth_user.user_id, params).await?;
+    let mut response =
+        ctx.room_service.messaging().get_room_messages(&room_id, &auth_user.user_id, &messages_params).await?;
+    drop_redundant_lazy_members(&ctx, &auth_user, &room_id, &messages_params, &mut response).await;
 
     // Best-effort outbound backfill trigger: when paginating backwards
     // (`dir=b`) and the local DB returned fewer events than requested, the
@@ -206,9 +203,9 @@ pub(crate) async fn get_messages(
     //
     // A per-room cooldown (60 s) prevents excessive federation requests
     // when a client retries backward pagination rapidly.
-    if direction == "b" {
+    if messages_params.direction == "b" {
         let chunk_count = response.get("chunk").and_then(|c| c.as_array()).map_or(0, |a| a.len());
-        if (chunk_count as i64) < limit {
+        if (chunk_count as i64) < messages_params.limit {
             let room_id_clone = room_id.clone();
             let federation_client = ctx.federation_client.clone();
             let room_service = ctx.room_service.clone();
@@ -237,10 +234,45 @@ pub(crate) async fn get_messages(
     Ok(Json(response))
 }
 
+/// Per-(user, room) token bucket applied to message sends. Unlike the
+/// request middleware limiter it is keyed by room, so a sender cannot flood a
+/// single room while staying under their overall request budget. Transaction
+/// replays are answered from the dedup cache before this is consulted.
+async fn enforce_room_send_rate_limit(ctx: &RoomContext, user_id: &str, room_id: &str) -> Result<(), ApiError> {
+    let rate_limit = &ctx.config.rate_limit;
+    let room_send = &rate_limit.room_send;
+    if !room_send.enabled {
+        return Ok(());
+    }
+
+    let rate_limit_key = format!("ratelimit:room-send:{user_id}:{room_id}");
+    let decision =
+        match ctx.cache.rate_limit_token_bucket_take(&rate_limit_key, room_send.per_second, room_send.burst_size).await
+        {
+            Ok(decision) => decision,
+            Err(error) if rate_limit.fail_open_on_error => {
+                tracing::warn!(
+                    user_id = %user_id,
+                    room_id = %room_id,
+                    error = %error,
+                    "Room send rate limiter failed; allowing request"
+                );
+                return Ok(());
+            }
+            Err(error) => return Err(ApiError::internal_with_log("Room send rate limit failed", &error)),
+        };
+
+    if !decision.allowed {
+        return Err(ApiError::rate_limited_with_retry(decision.retry_after_seconds.saturating_mul(1000)));
+    }
+    Ok(())
+}
+
 pub(crate) async fn send_message(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_type, txn_id)): Path<(String, String, String)>,
+    Query(delay): Query<DelayQuery>,
     Json(body): Json<Value>,
 ) -> Result<Json<Value>, ApiError> {
     validate_room_id(&room_id)?;
@@ -259,7 +291,14 @@ pub(crate) async fn send_message(
         }
     }
 
-    ctx.room_auth.verify_message_event_write(&room_id, &auth_user.user_id, &event_type).await?;
+    enforce_room_send_rate_limit(&ctx, &auth_user.user_id, &room_id).await?;
+
+    if let Err(e) = ctx.room_auth.verify_message_event_write(&room_id, &auth_user.user_id, &event_type).await {
+        if !provision_app_service_membership(&ctx, &auth_user.user_id, &room_id).await? {
+            return Err(e);
+        }
+        ctx.room_auth.verify_message_event_write(&room_id, &auth_user.user_id, &event_type).await?;
+    }
 
     if event_type == "m.room.encrypted" {
         let is_encrypted = ctx.room_service.state().check_room_has_encryption(&room_id).await?;
@@ -271,6 +310,13 @@ pub(crate) async fn send_message(
         }
     }
 
+    if event_type == "m.room.message"
+        && ctx.config.server.reject_unencrypted_messages_in_encrypted_rooms
+        && ctx.room_service.state().check_room_has_encryption(&room_id).await?
+    {
+        return Err(ApiError::forbidden("This room is encrypted; send messages as m.room.encrypted".to_string()));
+    }
+
     if event_type == "m.room.power_levels" {
         ctx.room_auth.verify_power_levels_change(&room_id, &auth_user.user_id, &body).await?;
     }
@@ -287,7 +333,18 @@ pub(crate) async fn send_message(
         }
     }
 
-    let result = ctx.room_service.messaging().send_message(&room_id, &auth_user.user_id, &event_type, &body).await?;
+    ctx.send_quota_service.consume(&auth_user.user_id, QuotaKind::Messages, 1).await?;
+    let result = match delay.delay {
+        Some(delay_ms) => {
+            ctx.room_service
+                .messaging()
+                .event_auth()
+                .check(&room_id, &auth_user.user_id, &event_type, None, &body)
+                .await?;
+            schedule_delayed_event(&ctx, &auth_user, &room_id, &event_type, None, body, delay_ms).await?.0
+        }
+        None => ctx.room_service.messaging().send_message(&room_id, &auth_user.user_id, &event_type, &body).await?,
+    };
 
     if !txn_id.is_empty() {
         let cache_key = format!("txn:{}:{}:{}", auth_user.user_id, room_id, txn_id);
@@ -360,13 +417,11 @@ pub(crate) async fn get_room_timeline(
 
     ensure_room_view_access(&ctx, &auth_user, &room_id).await?;
 
-    let from = parse_room_messages_from_token(&params);
-    let limit = params.get("limit").and_then(|v| v.as_u64()).unwrap_or(10) as i64;
-    let direction = params.get("dir").and_then(|v| v.as_str()).unwrap_or("b");
-
-    Ok(Json(
-        ctx.room_service.messaging().get_room_messages(&room_id, &auth_user.user_id, from, limit, direction).await?,
-    ))
+    let messages_params = room_messages_query(&ctx, &auth_user.user_id, params).await?;
+    let mut response =
+        ctx.room_service.messaging().get_room_messages(&room_id, &auth_user.user_id, &messages_params).await?;
+    drop_redundant_lazy_members(&ctx, &auth_user, &room_id, &messages_params, &mut response).await;
+    Ok(Json(response))
 }
 
 pub(crate) async fn get_room_unread_count(
@@ -738,7 +793,7 @@ pub(crate) async fn translate_room_event(
                 error = %e,
                 "Translation failed"
             );
-            ApiError::bad_request(format!("Translation failed: {}", e))
+            ApiError::bad_request("Translation failed".to_string())
         })?;
 
     Ok(Json(json!({
@@ -788,7 +843,7 @@ pub(crate) async fn translate_text(
             error = %e,
             "Translation failed"
         );
-        ApiError::bad_request(format!("Translation failed: {}", e))
+        ApiError::bad_request("Translation failed".to_string())
     })?;
 
     Ok(Json(json!({
@@ -1,4 +1,5 @@
 use crate::common::ApiError;
+use crate::web::routes::{ensure_alias_creation_allowed, ensure_room_creation_allowed};
 use crate::web::utils::auth::{bearer_token, resolve_request_id};
 use axum::{
     extract::{Json, State},
@@ -6,9 +7,24 @@ use axum::{
 };
 use serde_json::Value;
 use synapse_services::room::service::CreateRoomConfig;
+use synapse_services::room::PowerLevelTemplate;
+use synapse_services::send_quota_service::QuotaKind;
 
 use crate::web::routes::context::RoomContext;
 
+/// Encryption algorithm for a new room when the server encrypts rooms of this
+/// kind by default (`encryption_enabled_by_default_for_room_type`).
+pub(crate) fn default_room_encryption(
+    ctx: &RoomContext,
+    preset: Option<&str>,
+    visibility: Option<&str>,
+) -> Option<String> {
+    ctx.config
+        .server
+        .encrypts_new_rooms_by_default(preset, visibility == Some("public"))
+        .then(|| "m.megolm.v1.aes-sha2".to_string())
+}
+
 pub(crate) async fn create_private_room(
     State(ctx): State<RoomContext>,
     headers: HeaderMap,
@@ -26,7 +42,7 @@ pub(crate) async fn create_room(
 ) -> Result<Json<Value>, ApiError> {
     let request_id = resolve_request_id(&headers);
     let token = bearer_token(&headers)?;
-    let (user_id, _, _, _, _) = ctx.token_auth.validate_token(&token).await?;
+    let (user_id, _, is_admin, _, _) = ctx.token_auth.validate_token(&token).await?;
 
     let visibility = body.get("visibility").and_then(|v| v.as_str());
     if let Some(v) = visibility {
@@ -43,6 +59,10 @@ pub(crate) async fn create_room(
         if !alias.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-' || c == '.') {
             return Err(ApiError::bad_request("Invalid characters in room alias name".to_string()));
         }
+        // The room ID is only allocated later and is random anyway, so only
+        // rules whose room_id pattern matches everything can apply here.
+        let full_alias = format!("#{alias}:{}", ctx.server_name);
+        ensure_alias_creation_allowed(&ctx.config.room_directory, &user_id, is_admin, &full_alias, "")?;
     }
 
     let name = body.get("name").and_then(|v| v.as_str());
@@ -76,12 +96,6 @@ pub(crate) async fn create_room(
         None => None,
     };
 
-    if let Some(ref inv) = invite {
-        if inv.len() > 100 {
-            return Err(ApiError::bad_request("Too many invites (max 100)".to_string()));
-        }
-    }
-
     let preset = body.get("preset").and_then(|v| v.as_str());
 
     let room_type = body
@@ -89,6 +103,29 @@ pub(crate) async fn create_room(
         .and_then(|v| v.as_str())
         .or_else(|| body.get("creation_content").and_then(|cc| cc.get("type")).and_then(|v| v.as_str()));
 
+    let initial_state = body.get("initial_state").and_then(|v| v.as_array()).cloned();
+    let encryption = default_room_encryption(&ctx, preset, visibility);
+    let is_encrypted = encryption.is_some()
+        || initial_state
+            .iter()
+            .flatten()
+            .any(|event| event.get("type").and_then(|t| t.as_str()) == Some("m.room.encryption"));
+    ensure_room_creation_allowed(
+        &ctx.config.room_creation,
+        &user_id,
+        is_admin,
+        room_type == Some("m.space"),
+        is_encrypted,
+    )?;
+
+    if let Some(ref inv) = invite {
+        if inv.len() > 100 {
+            return Err(ApiError::bad_request("Too many invites (max 100)".to_string()));
+        }
+        let invite_count = u32::try_from(inv.len()).unwrap_or(u32::MAX);
+        ctx.send_quota_service.consume(&user_id, QuotaKind::Invites, invite_count).await?;
+    }
+
     let is_direct = body.get("is_direct").and_then(|v| v.as_bool());
     let room_version = body.get("room_version").and_then(|v| v.as_str()).map(str::to_owned);
     let mut creation_content = body.get("creation_content").cloned();
@@ -97,8 +134,15 @@ pub(crate) async fn create_room(
         map.remove("room_version");
         map.remove("predecessor");
     }
-    let initial_state = body.get("initial_state").and_then(|v| v.as_array()).cloned();
     let power_level_content_override = body.get("power_level_content_override").cloned();
+    let power_level_template = body
+        .get("power_level_template")
+        .and_then(|v| v.as_str())
+        .map(|name| {
+            PowerLevelTemplate::from_name(name)
+                .ok_or_else(|| ApiError::invalid_param(format!("Unknown power_level_template: {name}")))
+        })
+        .transpose()?;
 
     let config = CreateRoomConfig {
         visibility: visibility.map(|s| s.to_string()),
@@ -112,7 +156,9 @@ pub(crate) async fn create_room(
         room_version,
         creation_content,
         initial_state,
+        power_level_template,
         power_level_content_override,
+        encryption,
         ..Default::default()
     };
 
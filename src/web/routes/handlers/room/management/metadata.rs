@@ -1,10 +1,11 @@
 use super::super::ensure_room_view_access;
 use crate::common::ApiError;
-use crate::web::routes::{validate_room_id, AuthenticatedUser};
+use crate::web::routes::{ensure_room_peek_access_ctx, validate_room_id, AuthenticatedUser, OptionalAuthenticatedUser};
 use axum::extract::{Json, Path, Query, State};
 use serde::Deserialize;
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
+use synapse_services::room::RoomMessagesParams;
 
 use crate::web::routes::context::RoomContext;
 
@@ -56,7 +57,7 @@ pub(crate) struct RoomSyncQueryDto {
 
 pub(crate) async fn room_initial_sync(
     State(ctx): State<RoomContext>,
-    auth_user: AuthenticatedUser,
+    auth_user: OptionalAuthenticatedUser,
     Path(room_id): Path<String>,
     Query(params): Query<std::collections::HashMap<String, String>>,
 ) -> Result<Json<Value>, ApiError> {
@@ -69,13 +70,25 @@ pub(crate) async fn room_initial_sync(
         .await?
         .ok_or_else(|| ApiError::not_found("Room not found".to_string()))?;
 
-    ensure_room_view_access(&ctx, &auth_user, &room_id).await?;
-
-    let from = params
-        .get("from")
-        .and_then(|value| crate::common::parse_stream_token(value).or_else(|| value.parse::<i64>().ok()))
-        .unwrap_or(0);
-    let limit = params.get("limit").and_then(|value| value.parse::<i64>().ok()).unwrap_or(10).clamp(1, 100);
+    // Non-members may peek into `world_readable` rooms; they get the same
+    // snapshot with `membership: leave`, mirroring Synapse's peeking path.
+    let is_member = ensure_room_peek_access_ctx(
+        &ctx,
+        auth_user.user_id.as_deref(),
+        &room_id,
+        "You must be a member of this room to view events",
+    )
+    .await?;
+
+    let messages_params = RoomMessagesParams {
+        from: params.get("from").filter(|from| !from.is_empty()).cloned(),
+        to: None,
+        limit: params.get("limit").and_then(|value| value.parse::<i64>().ok()).unwrap_or(10).clamp(1, 100),
+        direction: "b".to_string(),
+        filter: None,
+        lazy_load_members: false,
+        include_redundant_members: false,
+    };
 
     let state_events = ctx
         .room_service
@@ -97,16 +110,17 @@ pub(crate) async fn room_initial_sync(
         })
         .collect::<Vec<Value>>();
 
-    let members = ctx.room_service.membership().get_room_members(&room_id, &auth_user.user_id).await?;
-    let messages =
-        ctx.room_service.messaging().get_room_messages(&room_id, &auth_user.user_id, from, limit, "b").await?;
+    let viewer_id = auth_user.user_id.as_deref().unwrap_or_default();
+    let members = ctx.room_service.membership().get_joined_member_events(&room_id).await?;
+    let messages = ctx.room_service.messaging().get_room_messages(&room_id, viewer_id, &messages_params).await?;
 
     let member_events = members.get("chunk").and_then(Value::as_array).cloned().unwrap_or_default();
     let visibility = if room.is_public { "public" } else { "private" };
+    let membership = if is_member { "join" } else { "leave" };
 
     Ok(Json(json!({
         "room_id": room.room_id,
-        "membership": "join",
+        "membership": membership,
         "visibility": visibility,
         "messages": messages,
         "pagination_chunk": messages.get("chunk").cloned().unwrap_or_else(|| json!([])),
@@ -167,14 +181,20 @@ pub(crate) async fn get_room_capabilities(
 
     let encryption_status = ctx.room_service.state().get_room_encryption_status(&room_id).await?;
 
-    let join_rule = if room.is_public { "public" } else { "invite" };
+    let join_rule = if !room.join_rule.is_empty() {
+        room.join_rule.as_str()
+    } else if room.is_public {
+        "public"
+    } else {
+        "invite"
+    };
 
     Ok(Json(json!({
         "room_id": room_id,
         "room_version": room.room_version,
         "capabilities": {
-            "knock": false,
-            "restricted": false,
+            "knock": synapse_common::supports_knock(&room.room_version),
+            "restricted": synapse_common::supports_restricted_join(&room.room_version),
             "threading": true,
             "read_receipts": true,
             "typing_notifications": true
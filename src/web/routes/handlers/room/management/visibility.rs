@@ -1,7 +1,9 @@
 use crate::common::ApiError;
 use crate::map_internal;
 use crate::web::routes::{ensure_room_member_ctx, AuthenticatedUser, OptionalAuthenticatedUser};
+use crate::web::utils::auth::resolve_request_id;
 use axum::extract::{Json, Path, State};
+use axum::http::HeaderMap;
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
 
@@ -32,10 +34,12 @@ pub(crate) async fn get_room_visibility(
 #[axum::debug_handler]
 pub(crate) async fn set_room_visibility(
     State(ctx): State<RoomContext>,
+    headers: HeaderMap,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
     Json(body): Json<Value>,
 ) -> Result<Json<Value>, ApiError> {
+    let request_id = resolve_request_id(&headers);
     if !ctx.room_service.state().room_exists(&room_id).await? {
         return Err(ApiError::not_found("Room not found".to_string()));
     }
@@ -65,8 +69,52 @@ pub(crate) async fn set_room_visibility(
 
     let is_public = visibility == "public";
 
+    if is_public && !ctx.config.room_directory.may_publish(&auth_user.user_id, &room_id, auth_user.is_admin) {
+        ::tracing::warn!(
+            target: "security_audit",
+            request_id = %request_id,
+            event = "room_directory_publish_denied",
+            user_id = %auth_user.user_id,
+            room_id = %room_id,
+            "User is not allowed to publish rooms to the room directory"
+        );
+        return Err(ApiError::forbidden("You are not allowed to publish rooms to the room directory".to_string()));
+    }
+
+    let already_listed = ctx.room_service.state().get_room_visibility(&room_id).await? == "public";
+    if is_public && !already_listed && !auth_user.is_admin && ctx.config.room_directory.require_publication_approval {
+        ctx.room_service.state().request_room_publication(&room_id, &auth_user.user_id).await?;
+        ::tracing::info!(
+            target: "security_audit",
+            request_id = %request_id,
+            event = "room_directory_publication_requested",
+            user_id = %auth_user.user_id,
+            room_id = %room_id,
+            "Room directory publication queued for admin approval"
+        );
+        return Ok(Json(json!({
+            "room_id": room_id,
+            "visibility": "private",
+            "publication_status": "pending",
+            "updated_ts": current_timestamp_millis()
+        })));
+    }
+
+    if !is_public {
+        ctx.room_service.state().withdraw_room_publication_request(&room_id).await?;
+    }
     ctx.room_service.state().set_room_directory(&room_id, is_public).await?;
 
+    ::tracing::info!(
+        target: "security_audit",
+        request_id = %request_id,
+        event = "room_directory_visibility_changed",
+        user_id = %auth_user.user_id,
+        room_id = %room_id,
+        visibility = %visibility,
+        "Room directory visibility updated"
+    );
+
     Ok(Json(json!({
         "room_id": room_id,
         "visibility": visibility,
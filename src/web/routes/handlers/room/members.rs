@@ -2,15 +2,64 @@ use super::ensure_room_view_access;
 use crate::common::ApiError;
 use crate::web::routes::context::RoomContext;
 use crate::web::routes::{
-    is_member_ctx, is_member_or_creator_ctx, validate_membership, validate_room_id, validate_user_id, AuthenticatedUser,
+    is_member_ctx, is_member_or_creator_ctx, validate_membership, validate_room_id, validate_user_id,
+    AuthenticatedUser, ValidatedJson,
 };
 use crate::web::utils::auth::resolve_request_id;
 use axum::{
     extract::{Json, Path, State},
     http::HeaderMap,
 };
+use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
+use synapse_services::send_quota_service::QuotaKind;
+use validator::Validate;
+
+/// Longest accepted membership `reason`, in characters.
+const MAX_REASON_LEN: u64 = 512;
+
+/// Body of `/knock`.
+#[derive(Debug, Default, Deserialize, Validate)]
+pub(crate) struct KnockRequest {
+    #[validate(length(max = MAX_REASON_LEN))]
+    pub reason: Option<String>,
+}
+
+/// Body of `/invite`: either `user_id` or a 3PID `medium` and `address`.
+/// The identity server fields of a 3PID invite are accepted but unused.
+#[derive(Debug, Deserialize, Validate)]
+pub(crate) struct InviteRequest {
+    pub user_id: Option<String>,
+    pub medium: Option<String>,
+    pub address: Option<String>,
+    #[validate(length(max = MAX_REASON_LEN))]
+    pub reason: Option<String>,
+}
+
+/// Body of `/kick`, `/ban` and `/unban`.
+#[derive(Debug, Deserialize, Validate)]
+pub(crate) struct MembershipChangeRequest {
+    pub user_id: String,
+    #[validate(length(max = MAX_REASON_LEN))]
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct RoomIdResponse {
+    pub room_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub(crate) struct InviteResponse {
+    pub room_id: String,
+    pub invited_user_id: String,
+    pub invited_ts: i64,
+}
+
+/// The `{}` body returned by membership changes.
+#[derive(Debug, Default, Serialize)]
+pub(crate) struct EmptyResponse {}
 
 pub(crate) async fn join_room(
     State(ctx): State<RoomContext>,
@@ -132,8 +181,8 @@ pub(crate) async fn knock_room(
     headers: HeaderMap,
     auth_user: AuthenticatedUser,
     Path(room_id_or_alias): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<KnockRequest>,
+) -> Result<Json<RoomIdResponse>, ApiError> {
     let request_id = resolve_request_id(&headers);
 
     let room_id = if room_id_or_alias.starts_with('!') {
@@ -156,8 +205,6 @@ pub(crate) async fn knock_room(
             .ok_or_else(|| ApiError::not_found("Room ID not found for alias".to_string()))?
     };
 
-    let reason = body.get("reason").and_then(|v| v.as_str()).map(|s| s.to_string());
-
     ::tracing::info!(
         request_id = %request_id,
         user_id = %auth_user.user_id,
@@ -165,37 +212,25 @@ pub(crate) async fn knock_room(
         "User knocking on room"
     );
 
-    ctx.room_service.membership().knock_room(&room_id, &auth_user.user_id, reason.as_deref()).await?;
+    ctx.room_service.membership().knock_room(&room_id, &auth_user.user_id, body.reason.as_deref()).await?;
 
-    Ok(Json(json!({
-        "room_id": room_id
-    })))
+    Ok(Json(RoomIdResponse { room_id }))
 }
 
 pub(crate) async fn invite_user(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<InviteRequest>,
+) -> Result<Json<InviteResponse>, ApiError> {
     validate_room_id(&room_id)?;
-
-    let invitee = body
-        .get("user_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("User ID required".to_string()))?;
-
-    validate_user_id(invitee)?;
-
     ctx.room_auth.can_invite_user(&room_id, &auth_user.user_id).await?;
+    let invitee = resolve_invitee(&ctx, &body).await?;
+    ctx.send_quota_service.consume(&auth_user.user_id, QuotaKind::Invites, 1).await?;
 
-    ctx.room_service.membership().invite_user(&room_id, &auth_user.user_id, invitee).await?;
+    ctx.room_service.membership().invite_user(&room_id, &auth_user.user_id, &invitee).await?;
 
-    Ok(Json(json!({
-        "room_id": room_id,
-        "invited_user_id": invitee,
-        "invited_ts": current_timestamp_millis()
-    })))
+    Ok(Json(InviteResponse { room_id, invited_user_id: invitee, invited_ts: current_timestamp_millis() }))
 }
 
 pub(crate) async fn invite_user_by_room(
@@ -203,20 +238,14 @@ pub(crate) async fn invite_user_by_room(
     headers: HeaderMap,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<InviteRequest>,
+) -> Result<Json<InviteResponse>, ApiError> {
     let request_id = resolve_request_id(&headers);
 
     validate_room_id(&room_id)?;
-
-    let invitee = body
-        .get("user_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("User ID required".to_string()))?;
-
-    validate_user_id(invitee)?;
-
     ctx.room_auth.can_invite_user(&room_id, &auth_user.user_id).await?;
+    let invitee = resolve_invitee(&ctx, &body).await?;
+    ctx.send_quota_service.consume(&auth_user.user_id, QuotaKind::Invites, 1).await?;
 
     ::tracing::info!(
         request_id = %request_id,
@@ -226,13 +255,30 @@ pub(crate) async fn invite_user_by_room(
         "User inviting another user to room"
     );
 
-    ctx.room_service.membership().invite_user(&room_id, &auth_user.user_id, invitee).await?;
+    ctx.room_service.membership().invite_user(&room_id, &auth_user.user_id, &invitee).await?;
 
-    Ok(Json(json!({
-        "room_id": room_id,
-        "invited_user_id": invitee,
-        "invited_ts": current_timestamp_millis()
-    })))
+    Ok(Json(InviteResponse { room_id, invited_user_id: invitee, invited_ts: current_timestamp_millis() }))
+}
+
+/// The user an `/invite` body targets. A 3PID already bound to a local
+/// account becomes a plain `m.room.member` invite for that user, so the
+/// invitee sees it on their next sync without an identity server round
+/// trip; addresses with no local binding are not supported.
+async fn resolve_invitee(ctx: &RoomContext, body: &InviteRequest) -> Result<String, ApiError> {
+    if let Some(user_id) = &body.user_id {
+        validate_user_id(user_id)?;
+        return Ok(user_id.clone());
+    }
+
+    let (Some(medium), Some(address)) = (body.medium.as_deref(), body.address.as_deref()) else {
+        return Err(ApiError::missing_param("Missing 'user_id' or 'medium' and 'address'".to_string()));
+    };
+
+    ctx.account_identity_service.resolve_local_threepid_user(medium, address).await?.ok_or_else(|| {
+        ApiError::not_implemented(
+            "Invites to third-party identifiers not bound to a local user are not supported".to_string(),
+        )
+    })
 }
 
 pub(crate) async fn get_room_members(
@@ -489,24 +535,14 @@ pub(crate) async fn kick_user(
     headers: HeaderMap,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<MembershipChangeRequest>,
+) -> Result<Json<EmptyResponse>, ApiError> {
     let request_id = resolve_request_id(&headers);
     validate_room_id(&room_id)?;
 
-    let target = body
-        .get("user_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("User ID required".to_string()))?;
-
+    let target = body.user_id.as_str();
     validate_user_id(target)?;
-
-    let reason = body.get("reason").and_then(|v| v.as_str());
-    if let Some(r) = reason {
-        if r.len() > 512 {
-            return Err(ApiError::bad_request("Reason too long".to_string()));
-        }
-    }
+    let reason = body.reason.as_deref();
 
     ctx.room_service.membership().kick_user(&room_id, target, &auth_user.user_id, reason).await?;
 
@@ -526,7 +562,7 @@ pub(crate) async fn kick_user(
         );
     }
 
-    Ok(Json(json!({})))
+    Ok(Json(EmptyResponse {}))
 }
 
 pub(crate) async fn ban_user(
@@ -534,24 +570,14 @@ pub(crate) async fn ban_user(
     headers: HeaderMap,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<MembershipChangeRequest>,
+) -> Result<Json<EmptyResponse>, ApiError> {
     let request_id = resolve_request_id(&headers);
     validate_room_id(&room_id)?;
 
-    let target = body
-        .get("user_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("User ID required".to_string()))?;
-
+    let target = body.user_id.as_str();
     validate_user_id(target)?;
-
-    let reason = body.get("reason").and_then(|v| v.as_str());
-    if let Some(r) = reason {
-        if r.len() > 512 {
-            return Err(ApiError::bad_request("Reason too long".to_string()));
-        }
-    }
+    let reason = body.reason.as_deref();
 
     ctx.room_service.membership().ban_user(&room_id, target, &auth_user.user_id, reason).await?;
 
@@ -571,7 +597,7 @@ pub(crate) async fn ban_user(
         );
     }
 
-    Ok(Json(json!({})))
+    Ok(Json(EmptyResponse {}))
 }
 
 // ---------------------------------------------------------------------------
@@ -582,18 +608,12 @@ pub(crate) async fn unban_user(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path(room_id): Path<String>,
-    Json(body): Json<Value>,
-) -> Result<Json<Value>, ApiError> {
+    ValidatedJson(body): ValidatedJson<MembershipChangeRequest>,
+) -> Result<Json<EmptyResponse>, ApiError> {
     validate_room_id(&room_id)?;
+    validate_user_id(&body.user_id)?;
 
-    let target = body
-        .get("user_id")
-        .and_then(|v| v.as_str())
-        .ok_or_else(|| ApiError::bad_request("User ID required".to_string()))?;
+    ctx.room_service.membership().unban_user(&room_id, &body.user_id, &auth_user.user_id).await?;
 
-    validate_user_id(target)?;
-
-    ctx.room_service.membership().unban_user(&room_id, target, &auth_user.user_id).await?;
-
-    Ok(Json(json!({})))
+    Ok(Json(EmptyResponse {}))
 }
@@ -12,17 +12,118 @@ pub(crate) use members::*;
 pub(crate) use receipts::*;
 pub(crate) use state::*;
 
-use crate::common::{parse_stream_token, ApiError};
+use crate::common::ApiError;
 use crate::web::routes::context::RoomContext;
 use crate::web::routes::{ensure_room_member_ctx, ensure_room_member_strict_ctx, AuthenticatedUser};
 use serde::{Deserialize, Serialize};
+use synapse_services::room::RoomMessagesParams;
+use synapse_storage::EventQueryFilter;
 
-fn parse_room_messages_from_token(params: &serde_json::Value) -> i64 {
-    params
-        .get("from")
-        .and_then(|v| v.as_str())
-        .and_then(|token| parse_stream_token(token).or_else(|| token.parse().ok()))
-        .unwrap_or(0)
+/// Default and maximum `limit` of a `/messages` page.
+const ROOM_MESSAGES_DEFAULT_LIMIT: i64 = 10;
+const ROOM_MESSAGES_MAX_LIMIT: i64 = 1000;
+
+/// [`room_messages_params`] after resolving a stored filter: `filter` (or
+/// `filter_id`) may name one of the user's filters instead of carrying JSON,
+/// in which case its `room.timeline` section applies.
+pub(crate) async fn room_messages_query(
+    ctx: &RoomContext,
+    user_id: &str,
+    mut params: serde_json::Value,
+) -> Result<RoomMessagesParams, ApiError> {
+    let filter_ref = ["filter", "filter_id"]
+        .into_iter()
+        .find_map(|name| params.get(name).and_then(|v| v.as_str()).filter(|v| !v.is_empty()))
+        .map(str::to_string);
+    if let Some(filter_id) = filter_ref.filter(|f| !f.trim_start().starts_with('{')) {
+        let stored = ctx
+            .account_data_service
+            .get_filter(user_id, &filter_id)
+            .await?
+            .ok_or_else(|| ApiError::invalid_param(format!("Unknown filter '{filter_id}'")))?;
+        let timeline = stored.pointer("/room/timeline").cloned().unwrap_or_else(|| serde_json::json!({}));
+        params["filter"] = serde_json::Value::String(timeline.to_string());
+    }
+    room_messages_params(&params)
+}
+
+/// Drop from a lazy-loaded `/messages` `state` the members this device was
+/// already sent, per the filter's `include_redundant_members`.
+pub(crate) async fn drop_redundant_lazy_members(
+    ctx: &RoomContext,
+    auth_user: &AuthenticatedUser,
+    room_id: &str,
+    params: &RoomMessagesParams,
+    response: &mut serde_json::Value,
+) {
+    let Some(state) = response.get_mut("state").filter(|_| params.lazy_load_members) else {
+        return;
+    };
+    let serde_json::Value::Array(members) = std::mem::take(state) else {
+        return;
+    };
+    let members = ctx
+        .sync_service
+        .filter_lazy_loaded_member_state(
+            &auth_user.user_id,
+            auth_user.device_id.as_deref(),
+            room_id,
+            members,
+            params.include_redundant_members,
+        )
+        .await;
+    *state = serde_json::Value::Array(members);
+}
+
+/// Query of `/messages`: `from`/`to` tokens, `dir`, `limit` and a JSON
+/// `RoomEventFilter` in `filter`.
+fn room_messages_params(params: &serde_json::Value) -> Result<RoomMessagesParams, ApiError> {
+    let string_param = |name: &str| params.get(name).and_then(|v| v.as_str()).filter(|v| !v.is_empty());
+
+    let direction = match string_param("dir").unwrap_or("b") {
+        dir @ ("b" | "f") => dir.to_string(),
+        _ => return Err(ApiError::invalid_param("'dir' must be 'b' or 'f'")),
+    };
+    let filter_json = string_param("filter")
+        .map(serde_json::from_str::<serde_json::Value>)
+        .transpose()
+        .map_err(|e| ApiError::invalid_param(format!("Invalid 'filter': {e}")))?;
+
+    let limit = params
+        .get("limit")
+        .and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok())))
+        .unwrap_or(ROOM_MESSAGES_DEFAULT_LIMIT);
+    let bool_field =
+        |name: &str| filter_json.as_ref().and_then(|f| f.get(name)).and_then(|v| v.as_bool()).unwrap_or(false);
+    let filter_limit = filter_json.as_ref().and_then(|f| f.get("limit")).and_then(|v| v.as_i64());
+    let limit = filter_limit.map_or(limit, |filter_limit| limit.min(filter_limit)).clamp(1, ROOM_MESSAGES_MAX_LIMIT);
+
+    Ok(RoomMessagesParams {
+        from: string_param("from").map(str::to_string),
+        to: string_param("to").map(str::to_string),
+        limit,
+        direction,
+        filter: filter_json.as_ref().map(event_query_filter_from_json),
+        lazy_load_members: bool_field("lazy_load_members"),
+        include_redundant_members: bool_field("include_redundant_members"),
+    })
+}
+
+/// The event-selection fields of a `RoomEventFilter`.
+pub(crate) fn event_query_filter_from_json(filter: &serde_json::Value) -> EventQueryFilter {
+    let strings = |name: &str| {
+        filter
+            .get(name)
+            .and_then(|v| v.as_array())
+            .map(|values| values.iter().filter_map(|v| v.as_str().map(str::to_string)).collect())
+    };
+    EventQueryFilter {
+        types: strings("types"),
+        not_types: strings("not_types"),
+        senders: strings("senders"),
+        not_senders: strings("not_senders"),
+        contains_url: filter.get("contains_url").and_then(|v| v.as_bool()),
+    }
 }
 
 pub(crate) async fn ensure_room_view_access(
@@ -53,13 +154,36 @@ pub(crate) async fn ensure_room_state_write_access(
     room_id: &str,
     event_type: &str,
 ) -> Result<(), ApiError> {
-    ensure_room_member_ctx(ctx, auth_user, room_id, "You must be a member of this room to send state events").await?;
+    if let Err(e) =
+        ensure_room_member_ctx(ctx, auth_user, room_id, "You must be a member of this room to send state events").await
+    {
+        if !provision_app_service_membership(ctx, &auth_user.user_id, room_id).await? {
+            return Err(e);
+        }
+    }
 
     ctx.room_auth.verify_state_event_write(room_id, &auth_user.user_id, event_type).await?;
 
     Ok(())
 }
 
+/// Joins an application service user to `room_id` when it may not write
+/// there because it is not a member and its service set
+/// `auto_provision_membership`. The join follows the room's join rules.
+/// Returns whether the user was joined.
+pub(crate) async fn provision_app_service_membership(
+    ctx: &RoomContext,
+    user_id: &str,
+    room_id: &str,
+) -> Result<bool, ApiError> {
+    let membership = ctx.room_service.membership().get_room_membership(room_id, user_id).await?;
+    if membership.as_deref() == Some("join") || !ctx.app_service_manager.provisions_membership_for(user_id).await? {
+        return Ok(false);
+    }
+    ctx.room_service.membership().join_room(room_id, user_id).await?;
+    Ok(true)
+}
+
 pub(crate) async fn get_room_event(
     ctx: &RoomContext,
     room_id: &str,
@@ -80,9 +204,45 @@ pub(crate) struct UpgradeRoomResponse {
 
 #[cfg(test)]
 mod tests {
-    use super::state_event_content_response;
+    use super::{room_messages_params, state_event_content_response};
     use serde_json::json;
 
+    #[test]
+    fn test_room_messages_params_reads_tokens_direction_and_filter() {
+        let params = room_messages_params(&json!({
+            "from": "t3-40",
+            "to": "s12",
+            "dir": "f",
+            "limit": "50",
+            "filter": r#"{"types":["m.room.message"],"not_senders":["@spam:example.org"],"contains_url":true,"limit":20}"#
+        }))
+        .expect("valid query");
+
+        assert_eq!(params.from.as_deref(), Some("t3-40"));
+        assert_eq!(params.to.as_deref(), Some("s12"));
+        assert_eq!(params.direction, "f");
+        assert_eq!(params.limit, 20);
+        let filter = params.filter.expect("filter parsed");
+        assert_eq!(filter.types, Some(vec!["m.room.message".to_string()]));
+        assert_eq!(filter.not_senders, Some(vec!["@spam:example.org".to_string()]));
+        assert_eq!(filter.contains_url, Some(true));
+        assert!(!params.lazy_load_members);
+
+        let params = room_messages_params(&json!({ "filter": r#"{"lazy_load_members":true}"# })).expect("valid query");
+        assert!(params.lazy_load_members);
+    }
+
+    #[test]
+    fn test_room_messages_params_defaults_and_rejects_bad_input() {
+        let params = room_messages_params(&json!({})).expect("empty query is valid");
+        assert_eq!(params.direction, "b");
+        assert_eq!(params.limit, 10);
+        assert!(params.from.is_none() && params.filter.is_none());
+
+        assert!(room_messages_params(&json!({ "dir": "sideways" })).is_err());
+        assert!(room_messages_params(&json!({ "filter": "{not json" })).is_err());
+    }
+
     #[test]
     fn test_state_event_content_response_returns_raw_content_for_empty_state_key() {
         let content = json!({
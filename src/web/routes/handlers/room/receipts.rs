@@ -42,6 +42,9 @@ pub(crate) async fn send_receipt(
     let body: Value = if body.trim().is_empty() { json!({}) } else { serde_json::from_str(&body).unwrap_or(json!({})) };
 
     ctx.room_service.messaging().send_receipt(&room_id, &auth_user.user_id, &event_id, &receipt_type, &body).await?;
+    if matches!(receipt_type.as_str(), "m.read" | "m.read.private") {
+        spawn_badge_update(&ctx, &auth_user.user_id);
+    }
 
     Ok(Json(json!({
         "room_id": room_id,
@@ -64,7 +67,8 @@ pub(crate) async fn get_receipts(
     ensure_room_view_access(&ctx, &auth_user, &room_id).await?;
     get_room_event(&ctx, &room_id, &event_id).await?;
 
-    let receipts = ctx.room_service.messaging().get_receipts(&room_id, &receipt_type, &event_id).await?;
+    let receipts =
+        ctx.room_service.messaging().get_receipts(&room_id, &auth_user.user_id, &receipt_type, &event_id).await?;
 
     Ok(Json(json!({
         "receipts": receipts
@@ -90,9 +94,30 @@ pub(crate) async fn set_read_markers(
     }
 
     ctx.room_service.messaging().set_read_markers(&room_id, &auth_user.user_id, &body).await?;
+    if body.get("m.read").is_some() || body.get("m.read.private").is_some() {
+        spawn_badge_update(&ctx, &auth_user.user_id);
+    }
 
     Ok(Json(json!({
         "room_id": room_id,
         "updated_ts": current_timestamp_millis()
     })))
 }
+
+/// Best-effort badge refresh on the reader's push devices after their read
+/// position advanced, so badges clear without waiting for the next message.
+fn spawn_badge_update(ctx: &RoomContext, user_id: &str) {
+    let room_service = ctx.room_service.clone();
+    let push_notification_service = ctx.push_notification_service.clone();
+    let user_id = user_id.to_string();
+    tokio::spawn(async move {
+        let result = async {
+            let unread = room_service.messaging().get_total_unread_count(&user_id).await?;
+            push_notification_service.send_badge_update(&user_id, unread).await
+        }
+        .await;
+        if let Err(error) = result {
+            ::tracing::warn!(user_id = %user_id, error = %error, "Badge-only push update failed");
+        }
+    });
+}
@@ -4,8 +4,9 @@ use super::{
 use crate::common::ApiError;
 use crate::map_internal;
 use crate::web::routes::context::RoomContext;
+use crate::web::routes::delayed_events::{schedule_delayed_event, DelayQuery};
 use crate::web::routes::{validate_room_id, AuthenticatedUser};
-use axum::extract::{Json, Path, State};
+use axum::extract::{Json, Path, Query, State};
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
 #[cfg(feature = "beacons")]
@@ -185,6 +186,12 @@ pub(crate) async fn send_state_event(
         Some(auth_user.user_id.clone())
     };
 
+    ctx.room_service
+        .messaging()
+        .event_auth()
+        .check(&room_id, &auth_user.user_id, &final_event_type, state_key.as_deref(), &content)
+        .await?;
+
     let state_event = ctx
         .room_service
         .messaging()
@@ -220,6 +227,7 @@ pub(crate) async fn put_state_event(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_type, state_key)): Path<(String, String, String)>,
+    Query(delay): Query<DelayQuery>,
     Json(body): Json<Value>,
 ) -> Result<Json<Value>, ApiError> {
     validate_room_id(&room_id)?;
@@ -292,6 +300,17 @@ pub(crate) async fn put_state_event(
         None
     };
 
+    ctx.room_service
+        .messaging()
+        .event_auth()
+        .check(&room_id, &auth_user.user_id, &final_event_type, Some(&state_key), &body)
+        .await?;
+
+    if let Some(delay_ms) = delay.delay {
+        return schedule_delayed_event(&ctx, &auth_user, &room_id, &final_event_type, Some(&state_key), body, delay_ms)
+            .await;
+    }
+
     let event = ctx
         .room_service
         .messaging()
@@ -302,7 +321,7 @@ pub(crate) async fn put_state_event(
                 user_id: auth_user.user_id.clone(),
                 event_type: final_event_type.clone(),
                 content: body,
-                state_key: Some(state_key),
+                state_key: Some(state_key.clone()),
                 origin_server_ts: now,
                 redacts: None,
             },
@@ -310,6 +329,7 @@ pub(crate) async fn put_state_event(
         )
         .await
         .map_err(map_internal!("Failed to put state event"))?;
+    ctx.delayed_event_service.cancel_overwritten_state(&room_id, &final_event_type, &state_key).await;
 
     #[cfg(feature = "beacons")]
     if let Some(params) = beacon_info_params {
@@ -369,6 +389,7 @@ pub(crate) async fn put_state_event_empty_key(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_type)): Path<(String, String)>,
+    Query(delay): Query<DelayQuery>,
     Json(body): Json<Value>,
 ) -> Result<Json<Value>, ApiError> {
     validate_room_id(&room_id)?;
@@ -379,6 +400,16 @@ pub(crate) async fn put_state_event_empty_key(
     let final_event_type = normalize_room_event_type(&event_type);
     ensure_room_state_write_access(&ctx, &auth_user, &room_id, &final_event_type).await?;
 
+    ctx.room_service
+        .messaging()
+        .event_auth()
+        .check(&room_id, &auth_user.user_id, &final_event_type, Some(""), &body)
+        .await?;
+
+    if let Some(delay_ms) = delay.delay {
+        return schedule_delayed_event(&ctx, &auth_user, &room_id, &final_event_type, Some(""), body, delay_ms).await;
+    }
+
     let event = ctx
         .room_service
         .messaging()
@@ -397,6 +428,7 @@ pub(crate) async fn put_state_event_empty_key(
         )
         .await
         .map_err(map_internal!("Failed to put state event"))?;
+    ctx.delayed_event_service.cancel_overwritten_state(&room_id, &event.event_type, "").await;
 
     Ok(Json(json!({
         "event_id": new_event_id,
@@ -409,6 +441,7 @@ pub(crate) async fn put_state_event_no_key(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_type)): Path<(String, String)>,
+    Query(delay): Query<DelayQuery>,
     Json(body): Json<Value>,
 ) -> Result<Json<Value>, ApiError> {
     validate_room_id(&room_id)?;
@@ -419,6 +452,16 @@ pub(crate) async fn put_state_event_no_key(
     let final_event_type = normalize_room_event_type(&event_type);
     ensure_room_state_write_access(&ctx, &auth_user, &room_id, &final_event_type).await?;
 
+    ctx.room_service
+        .messaging()
+        .event_auth()
+        .check(&room_id, &auth_user.user_id, &final_event_type, Some(""), &body)
+        .await?;
+
+    if let Some(delay_ms) = delay.delay {
+        return schedule_delayed_event(&ctx, &auth_user, &room_id, &final_event_type, Some(""), body, delay_ms).await;
+    }
+
     let event = ctx
         .room_service
         .messaging()
@@ -437,6 +480,7 @@ pub(crate) async fn put_state_event_no_key(
         )
         .await
         .map_err(map_internal!("Failed to put state event"))?;
+    ctx.delayed_event_service.cancel_overwritten_state(&room_id, &event.event_type, "").await;
 
     Ok(Json(json!({
         "event_id": new_event_id,
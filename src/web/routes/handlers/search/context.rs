@@ -1,5 +1,6 @@
 use crate::common::ApiError;
 use crate::web::routes::context::RoomContext;
+use crate::web::routes::handlers::room::event_query_filter_from_json;
 use crate::web::routes::{ensure_room_member_strict_ctx, AuthenticatedUser};
 use axum::extract::{Json, Path, Query, State};
 use serde_json::{json, Value};
@@ -19,58 +20,24 @@ pub(crate) async fn get_event_context(
     crate::web::routes::validate_room_id(&room_id)?;
     crate::web::routes::validate_event_id(&event_id)?;
 
-    let limit = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(10).clamp(1, 100);
+    let limit: i64 = params.get("limit").and_then(|v| v.parse().ok()).unwrap_or(10).clamp(1, 100);
+
+    let filter = params
+        .get("filter")
+        .filter(|filter| !filter.is_empty())
+        .map(|filter| serde_json::from_str::<Value>(filter))
+        .transpose()
+        .map_err(|e| ApiError::invalid_param(format!("Invalid 'filter': {e}")))?
+        .map(|filter| event_query_filter_from_json(&filter));
 
     ensure_room_member_strict_ctx(&ctx, &auth_user, &room_id, "Not a member of this room").await?;
 
-    let target_event = ctx.room_service.messaging().get_event(&room_id, &event_id).await?;
-
-    let target_ts = target_event.get("origin_server_ts").and_then(|v| v.as_i64()).unwrap_or(0);
-
-    let context_window = ctx.search_service.get_event_context_window(&room_id, target_ts, limit as i64).await?;
-
-    let events_before_list: Vec<Value> = context_window
-        .events_before
-        .iter()
-        .map(|event| {
-            json!({
-                "event_id": event.event_id,
-                "sender": event.sender,
-                "type": event.event_type,
-                "content": event.content.clone(),
-                "origin_server_ts": event.origin_server_ts
-            })
-        })
-        .collect();
-
-    let events_after_list: Vec<Value> = context_window
-        .events_after
-        .iter()
-        .map(|event| {
-            json!({
-                "event_id": event.event_id,
-                "sender": event.sender,
-                "type": event.event_type,
-                "content": event.content.clone(),
-                "origin_server_ts": event.origin_server_ts
-            })
-        })
-        .collect();
-
-    Ok(Json(json!({
-        "event": {
-            "event_id": target_event.get("event_id").and_then(|v| v.as_str()).unwrap_or(""),
-            "sender": target_event.get("sender").and_then(|v| v.as_str()).unwrap_or(""),
-            "type": target_event.get("type").and_then(|v| v.as_str()).unwrap_or(""),
-            "content": target_event.get("content"),
-            "origin_server_ts": target_ts
-        },
-        "events_before": events_before_list,
-        "events_after": events_after_list,
-        "state": [],
-        "start": events_before_list.last().and_then(|e| e.get("event_id").and_then(|v| v.as_str())).unwrap_or(&event_id),
-        "end": events_after_list.last().and_then(|e| e.get("event_id").and_then(|v| v.as_str())).unwrap_or(&event_id)
-    })))
+    Ok(Json(
+        ctx.room_service
+            .messaging()
+            .get_event_context(&room_id, &auth_user.user_id, &event_id, limit, filter.as_ref())
+            .await?,
+    ))
 }
 
 pub(crate) async fn timestamp_to_event(
@@ -250,9 +250,9 @@ async fn search_room_events(
 
     let page =
         ctx.search_service.search_room_events(user_id, &search.search_term, filter.as_ref(), limit, next_batch).await?;
+    let visible = ctx.event_visibility_service.filter_events_for_client(user_id, page.results).await?;
 
-    let results: Vec<Value> = page
-        .results
+    let results: Vec<Value> = visible
         .into_iter()
         .map(|event| {
             json!({
@@ -60,7 +60,10 @@ pub(crate) async fn sync(
     let is_full_state = parse_bool_query_param(&params, "full_state").unwrap_or(false);
     let request_id = crate::web::utils::auth::resolve_request_id(&headers);
     let set_presence = params.get("set_presence").and_then(|v| v.as_str()).unwrap_or("online").to_string();
-    let filter = params.get("filter").and_then(|v| v.as_str()).map(|s| s.to_string());
+    let filter = ["filter", "filter_id"]
+        .into_iter()
+        .find_map(|name| params.get(name).and_then(|v| v.as_str()))
+        .map(|s| s.to_string());
     let since = params.get("since").and_then(|v| v.as_str()).map(|s| s.to_string());
 
     let (fail_open_on_error, sync_rate_limit_enabled, init_per_second, init_burst_size, inc_per_second, inc_burst_size) =
@@ -145,20 +148,25 @@ async fn execute_sync(params: SyncParams) -> Result<Json<Value>, ApiError> {
     }
 }
 
+/// Upper bound on the `/events` long-poll so a client cannot pin a
+/// connection open indefinitely.
+pub(crate) const MAX_EVENTS_TIMEOUT_MS: u64 = 120_000;
+
 pub(crate) async fn get_events(
     State(ctx): State<SyncContext>,
     auth_user: AuthenticatedUser,
     Query(params): Query<Value>,
 ) -> Result<Json<Value>, ApiError> {
-    let from = params.get("from").and_then(|v| v.as_str()).unwrap_or("0");
-    let timeout = parse_u64_query_param(&params, "timeout").unwrap_or(30000);
+    let from = params.get("from").and_then(|v| v.as_str());
+    let filter = params.get("filter").and_then(|v| v.as_str());
+    let timeout = parse_u64_query_param(&params, "timeout").unwrap_or(30000).min(MAX_EVENTS_TIMEOUT_MS);
 
-    let result = ctx.sync_service.get_events(&auth_user.user_id, from, timeout).await?;
+    let result = ctx.sync_service.get_events(&auth_user.user_id, from, filter, timeout).await?;
 
     Ok(Json(result))
 }
 
-fn parse_u64_query_param(params: &Value, key: &str) -> Option<u64> {
+pub(crate) fn parse_u64_query_param(params: &Value, key: &str) -> Option<u64> {
     let value = params.get(key)?;
     match value {
         Value::Number(number) => number.as_u64(),
@@ -0,0 +1,162 @@
+//! Experimental WebSocket transport for `/sync`.
+//!
+//! The client opens the socket with the same query parameters as `/sync`
+//! (`since`, `filter`, `full_state`, `set_presence`). The server then runs the
+//! long-poll loop itself and pushes every response that advances `next_batch`
+//! as a text frame containing the unchanged `/sync` body, so a client can drop
+//! back to plain `/sync` with the last `next_batch` it saw. Idle polls are
+//! answered with a ping instead of a duplicate body.
+
+use crate::common::ApiError;
+use crate::web::routes::context::SyncContext;
+use crate::web::routes::AuthenticatedUser;
+use crate::web::websocket::{
+    self, Message, MessageReader, WebSocketError, CLOSE_INTERNAL_ERROR, CLOSE_NORMAL, CLOSE_POLICY_VIOLATION,
+};
+use axum::{
+    body::Body,
+    extract::{Query, State},
+    http::Request,
+    response::Response,
+};
+use hyper_util::rt::TokioIo;
+use serde_json::Value;
+use synapse_services::sync_service::SyncServiceRequest;
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::sync::mpsc;
+
+/// Long-poll timeout of each sync round on the socket. Updates are pushed as
+/// soon as a round returns, so this only sets the keep-alive cadence.
+const SYNC_WS_POLL_TIMEOUT_MS: u64 = 30_000;
+
+pub(crate) async fn sync_ws(
+    State(ctx): State<SyncContext>,
+    auth_user: AuthenticatedUser,
+    Query(params): Query<Value>,
+    mut request: Request<Body>,
+) -> Result<Response, ApiError> {
+    if !ctx.config.experimental.sync_websocket_enabled {
+        return Err(ApiError::unrecognized("WebSocket sync is not enabled on this server"));
+    }
+
+    let response = websocket::accept_handshake(request.headers()).map_err(|e| ApiError::bad_request(e.to_string()))?;
+    let on_upgrade = hyper::upgrade::on(&mut request);
+
+    let session = SyncWsSession {
+        ctx,
+        user_id: auth_user.user_id,
+        device_id: auth_user.device_id,
+        access_token: auth_user.access_token,
+        set_presence: params.get("set_presence").and_then(Value::as_str).unwrap_or("online").to_string(),
+        filter: params.get("filter").and_then(Value::as_str).map(str::to_owned),
+    };
+    let since = params.get("since").and_then(Value::as_str).map(str::to_owned);
+    let full_state = matches!(params.get("full_state").and_then(Value::as_str), Some("true" | "1"));
+
+    tokio::spawn(async move {
+        match on_upgrade.await {
+            Ok(upgraded) => session.run(TokioIo::new(upgraded), since, full_state).await,
+            Err(e) => ::tracing::debug!(error = %e, "WebSocket sync upgrade failed"),
+        }
+    });
+
+    Ok(response)
+}
+
+struct SyncWsSession {
+    ctx: SyncContext,
+    user_id: String,
+    device_id: Option<String>,
+    access_token: String,
+    set_presence: String,
+    filter: Option<String>,
+}
+
+impl SyncWsSession {
+    async fn run<S>(self, io: S, mut since: Option<String>, full_state: bool)
+    where
+        S: AsyncRead + AsyncWrite + Send + Unpin + 'static,
+    {
+        let (read_half, mut write_half) = tokio::io::split(io);
+
+        // Client frames are read on their own task so a ping is answered
+        // while a sync round is still waiting for updates.
+        let (incoming_tx, mut incoming_rx) = mpsc::channel::<Result<Message, WebSocketError>>(8);
+        let reader = tokio::spawn(async move {
+            let mut reader = MessageReader::new(read_half);
+            loop {
+                let message = reader.next_message().await;
+                let done = matches!(message, Err(_) | Ok(Message::Close(_)));
+                if incoming_tx.send(message).await.is_err() || done {
+                    break;
+                }
+            }
+        });
+
+        let mut round = Box::pin(self.next_round(since.clone(), full_state));
+        let close = loop {
+            tokio::select! {
+                incoming = incoming_rx.recv() => match incoming {
+                    Some(Ok(Message::Ping(payload))) => {
+                        if websocket::write_message(&mut write_half, &Message::Pong(payload)).await.is_err() {
+                            break None;
+                        }
+                    }
+                    Some(Ok(Message::Close(_))) | None => break Some((CLOSE_NORMAL, String::new())),
+                    Some(Ok(_)) => {}
+                    Some(Err(e)) => break Some((e.close_code(), e.to_string())),
+                },
+                result = &mut round => match result {
+                    Ok(body) => {
+                        let next_batch = body.get("next_batch").and_then(Value::as_str).map(str::to_owned);
+                        let message = if since.is_none() || next_batch != since {
+                            Message::Text(body.to_string())
+                        } else {
+                            Message::Ping(Vec::new())
+                        };
+                        if websocket::write_message(&mut write_half, &message).await.is_err() {
+                            break None;
+                        }
+                        if next_batch.is_some() {
+                            since = next_batch;
+                        }
+                        round = Box::pin(self.next_round(since.clone(), false));
+                    }
+                    Err(SyncWsClose(code, reason)) => break Some((code, reason)),
+                },
+            }
+        };
+
+        if let Some(close) = close {
+            let _ = websocket::write_message(&mut write_half, &Message::Close(Some(close))).await;
+        }
+        reader.abort();
+    }
+
+    /// One sync round. The access token is re-validated first so that a
+    /// logout or device deletion also ends the stream.
+    async fn next_round(&self, since: Option<String>, full_state: bool) -> Result<Value, SyncWsClose> {
+        if let Err(e) = self.ctx.token_auth.validate_token(&self.access_token).await {
+            return Err(SyncWsClose(CLOSE_POLICY_VIOLATION, e.code_str().to_string()));
+        }
+
+        self.ctx
+            .sync_service
+            .sync_with_request(SyncServiceRequest {
+                user_id: &self.user_id,
+                device_id: self.device_id.as_deref(),
+                timeout: SYNC_WS_POLL_TIMEOUT_MS,
+                is_full_state: full_state,
+                set_presence: &self.set_presence,
+                filter_id: self.filter.as_deref(),
+                since: since.as_deref(),
+            })
+            .await
+            .map_err(|e| {
+                ::tracing::warn!(user_id = %self.user_id, error = %e, "WebSocket sync round failed");
+                SyncWsClose(CLOSE_INTERNAL_ERROR, e.code_str().to_string())
+            })
+    }
+}
+
+struct SyncWsClose(u16, String);
@@ -50,6 +50,8 @@ pub async fn rotate_keys(
             "message": "Keys rotated successfully",
             "has_new_key": has_new_key,
         }))),
+        // A reused key ID is the caller's mistake and is reported as such.
+        Err(e) if e.http_status().is_client_error() => Err(e),
         Err(e) => {
             tracing::error!("Key rotation failed: {e}");
             Err(ApiError::internal("Internal server error".to_string()))
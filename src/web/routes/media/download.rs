@@ -162,15 +162,16 @@ async fn fetch_remote_media_via_federation(
     response_filename: Option<&str>,
 ) -> Result<synapse_services::media::MediaResponsePayload, ApiError> {
     let federation_client = ctx.federation_client.clone();
-    let resp = federation_client
-        .media_download(server_name, server_name, media_id)
-        .await
-        .map_err(|e| ApiError::not_found(format!("Remote media not reachable: {e}")))?;
+    let resp = federation_client.media_download(server_name, server_name, media_id).await.map_err(|e| {
+        ::tracing::warn!(server_name, media_id, error = %e, "Remote media not reachable");
+        ApiError::not_found("Remote media not reachable".to_string())
+    })?;
 
     let status = resp.status();
     if !status.is_success() {
-        let body = resp.text().await.unwrap_or_else(|e| format!("Failed to read remote media response: {e}"));
-        return Err(ApiError::not_found(format!("Remote media fetch failed: {status} {body}")));
+        let body = resp.text().await.unwrap_or_default();
+        ::tracing::warn!(server_name, media_id, %status, %body, "Remote media fetch failed");
+        return Err(ApiError::not_found(format!("Remote media fetch failed: {status}")));
     }
 
     let content_type = resp
@@ -180,7 +181,7 @@ async fn fetch_remote_media_via_federation(
         .map_or_else(|| "application/octet-stream".to_string(), |s| s.to_string());
 
     let content =
-        resp.bytes().await.map_err(|e| ApiError::internal(format!("Failed to read remote media body: {e}")))?.to_vec();
+        resp.bytes().await.map_err(|e| ApiError::internal_with_log("Failed to read remote media body", &e))?.to_vec();
 
     let headers = build_proxy_media_headers(content_type, content.len(), response_filename);
     Ok(synapse_services::media::MediaResponsePayload { content, headers })
@@ -199,12 +200,16 @@ async fn fetch_remote_thumbnail_via_federation(
     let resp = federation_client
         .media_thumbnail(server_name, server_name, media_id, width, height, method)
         .await
-        .map_err(|e| ApiError::not_found(format!("Remote thumbnail not reachable: {e}")))?;
+        .map_err(|e| {
+            ::tracing::warn!(server_name, media_id, error = %e, "Remote thumbnail not reachable");
+            ApiError::not_found("Remote thumbnail not reachable".to_string())
+        })?;
 
     let status = resp.status();
     if !status.is_success() {
-        let body = resp.text().await.unwrap_or_else(|e| format!("Failed to read remote thumbnail response: {e}"));
-        return Err(ApiError::not_found(format!("Remote thumbnail fetch failed: {status} {body}")));
+        let body = resp.text().await.unwrap_or_default();
+        ::tracing::warn!(server_name, media_id, %status, %body, "Remote thumbnail fetch failed");
+        return Err(ApiError::not_found(format!("Remote thumbnail fetch failed: {status}")));
     }
 
     let content_type = resp
@@ -216,7 +221,7 @@ async fn fetch_remote_thumbnail_via_federation(
     let content = resp
         .bytes()
         .await
-        .map_err(|e| ApiError::internal(format!("Failed to read remote thumbnail body: {e}")))?
+        .map_err(|e| ApiError::internal_with_log("Failed to read remote thumbnail body", &e))?
         .to_vec();
 
     let headers = build_proxy_media_headers(content_type, content.len(), None);
@@ -44,6 +44,9 @@ pub(crate) async fn preview_url(
     if let Err(e) = crate::common::check_url_against_blacklist(url, blacklist) {
         return Err(ApiError::forbidden(format!("URL not allowed: {e}")));
     }
+    if let Err(e) = synapse_common::egress::check_url(url).await {
+        return Err(ApiError::forbidden(format!("URL not allowed: {e}")));
+    }
 
     let ts = params.get("ts").and_then(|v| v.as_i64()).unwrap_or_else(current_timestamp_millis);
 
@@ -10,6 +10,7 @@ mod auth_compat;
 pub mod background_update;
 pub mod captcha;
 pub mod context;
+pub mod delayed_events;
 pub mod device;
 pub mod directory;
 mod directory_reporting;
@@ -136,7 +137,8 @@ pub use event_report::create_event_report_router;
 #[cfg(feature = "external-services")]
 pub use external_service::create_external_service_router;
 // extract_token_from_headers removed — use crate::web::utils::auth::bearer_token directly
-pub use extractors::{AdminUser, AuthenticatedUser, MatrixJson, OptionalAuthenticatedUser};
+pub use delayed_events::create_delayed_events_router;
+pub use extractors::{AdminUser, AuthenticatedUser, MatrixJson, OptionalAuthenticatedUser, ValidatedJson};
 pub use feature_flags::create_feature_flags_router;
 pub use federation::create_federation_router;
 #[cfg(feature = "friends")]
@@ -179,8 +181,9 @@ pub use relations::create_relations_router;
 pub use rendezvous::create_rendezvous_router;
 pub use room::create_room_router;
 pub(crate) use room_access::{
-    ensure_room_member_admin, ensure_room_member_ctx, ensure_room_member_strict_admin, ensure_room_member_strict_ctx,
-    is_member_ctx, is_member_or_creator_ctx,
+    ensure_alias_creation_allowed, ensure_room_creation_allowed, ensure_room_member_admin, ensure_room_member_ctx,
+    ensure_room_member_strict_admin, ensure_room_member_strict_ctx, ensure_room_peek_access_ctx, is_member_ctx,
+    is_member_or_creator_ctx,
 };
 pub use room_summary::create_room_summary_router;
 pub use route_module::ProfileFlags;
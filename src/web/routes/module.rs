@@ -10,6 +10,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use synapse_services::module_service::*;
+use synapse_storage::module::PasswordAuthProvider;
 use synapse_storage::module::*;
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -3,7 +3,7 @@ use crate::web::routes::context::AdminContext;
 use crate::web::routes::{AppState, AuthenticatedUser};
 use axum::{
     extract::{Json, Path, State},
-    routing::{get, post, put},
+    routing::{get, post},
     Router,
 };
 use serde::{Deserialize, Serialize};
@@ -32,7 +32,10 @@ pub fn create_push_router(state: AppState) -> Router<AppState> {
     Router::new()
         .nest("/_matrix/client/v3", compat_router.clone())
         .nest("/_matrix/client/r0", compat_router)
-        .route("/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/actions", put(set_push_rule_actions))
+        .route(
+            "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/actions",
+            get(get_push_rule_actions).put(set_push_rule_actions),
+        )
         .route(
             "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/enabled",
             get(get_push_rule_enabled).put(set_push_rule_enabled),
@@ -64,6 +67,7 @@ fn push_v3_only_absolute_routes() -> Vec<crate::web::routes::route_ledger::Route
     use crate::web::routes::route_ledger::RouteEntry;
     use axum::http::Method;
     [
+        (Method::GET, "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/actions"),
         (Method::PUT, "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/actions"),
         (Method::GET, "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/enabled"),
         (Method::PUT, "/_matrix/client/v3/pushrules/{scope}/{kind}/{rule_id}/enabled"),
@@ -184,23 +188,47 @@ async fn set_pusher(
     }
 }
 
-async fn get_push_rules(
-    State(ctx): State<AdminContext>,
-    auth_user: AuthenticatedUser,
-) -> Result<Json<Value>, ApiError> {
+/// The user's complete rule set: server defaults, overrides stored in
+/// `m.push_rules` account data and the user's own rules from the push rules
+/// table.
+async fn user_push_rules(ctx: &AdminContext, user_id: &str) -> Result<Value, ApiError> {
     use crate::web::routes::push_rules::{default_push_rules_for_user, merge_default_push_rules};
 
-    let row: Option<serde_json::Value> = ctx.client_push_service.get_push_rules_content(&auth_user.user_id).await?;
+    let username: &str = user_id.trim_start_matches('@').split(':').next().unwrap_or("");
 
-    let username: &str = auth_user.user_id.trim_start_matches('@').split(':').next().unwrap_or("");
+    let mut content: Value = match ctx.client_push_service.get_push_rules_content(user_id).await? {
+        Some(mut content) => {
+            merge_default_push_rules(&mut content, user_id, username);
+            content
+        }
+        None => default_push_rules_for_user(user_id, username),
+    };
+    ctx.client_push_service.merge_custom_push_rules(user_id, &mut content).await?;
+    Ok(content)
+}
 
-    if let Some(content) = row {
-        let mut content: Value = content;
-        merge_default_push_rules(&mut content, &auth_user.user_id, username);
-        return Ok(Json(content));
-    }
+async fn find_push_rule(
+    ctx: &AdminContext,
+    user_id: &str,
+    scope: &str,
+    kind: &str,
+    rule_id: &str,
+) -> Result<Value, ApiError> {
+    let content: Value = user_push_rules(ctx, user_id).await?;
+    content
+        .get(scope)
+        .and_then(|s| s.get(kind))
+        .and_then(Value::as_array)
+        .and_then(|rules| rules.iter().find(|r| r.get("rule_id").and_then(Value::as_str) == Some(rule_id)))
+        .cloned()
+        .ok_or_else(|| ApiError::not_found("Push rule not found".to_string()))
+}
 
-    Ok(Json(default_push_rules_for_user(&auth_user.user_id, username)))
+async fn get_push_rules(
+    State(ctx): State<AdminContext>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    Ok(Json(user_push_rules(&ctx, &auth_user.user_id).await?))
 }
 
 async fn get_push_rules_scope(
@@ -208,34 +236,20 @@ async fn get_push_rules_scope(
     State(ctx): State<AdminContext>,
     auth_user: AuthenticatedUser,
 ) -> Result<Json<Value>, ApiError> {
-    if scope == "global" {
-        let username: &str = auth_user.user_id.strip_prefix('@').and_then(|s| s.split(':').next()).unwrap_or("");
-
-        let result: Option<serde_json::Value> =
-            ctx.client_push_service.get_push_rules_content(&auth_user.user_id).await?;
-
-        if let Some(content) = result {
-            if let Some(global) = content.get("global") {
-                return Ok(Json(global.clone()));
-            }
-        }
-
-        let defaults: serde_json::Value =
-            crate::web::routes::push_rules::default_push_rules_for_user(&auth_user.user_id, username);
-        if let Some(global) = defaults.get("global") {
-            Ok(Json(global.clone()))
-        } else {
-            Ok(Json(json!({
-                "content": [],
-                "override": [],
-                "room": [],
-                "sender": [],
-                "underride": []
-            })))
-        }
-    } else {
-        Err(ApiError::invalid_input(format!("Unsupported push rules scope: {scope}")))
+    if scope != "global" {
+        return Err(ApiError::invalid_input(format!("Unsupported push rules scope: {scope}")));
     }
+
+    let content: Value = user_push_rules(&ctx, &auth_user.user_id).await?;
+    Ok(Json(content.get("global").cloned().unwrap_or_else(|| {
+        json!({
+            "content": [],
+            "override": [],
+            "room": [],
+            "sender": [],
+            "underride": []
+        })
+    })))
 }
 
 async fn get_push_rules_kind(
@@ -243,8 +257,8 @@ async fn get_push_rules_kind(
     State(ctx): State<AdminContext>,
     auth_user: AuthenticatedUser,
 ) -> Result<Json<Value>, ApiError> {
-    let rules: Vec<serde_json::Value> =
-        ctx.client_push_service.get_user_push_rules(&auth_user.user_id, &scope, &kind).await?;
+    let content: Value = user_push_rules(&ctx, &auth_user.user_id).await?;
+    let rules: Value = content.get(&scope).and_then(|s| s.get(&kind)).cloned().unwrap_or_else(|| json!([]));
     Ok(Json(json!({
         kind: rules
     })))
@@ -255,16 +269,7 @@ async fn get_push_rule(
     State(ctx): State<AdminContext>,
     auth_user: AuthenticatedUser,
 ) -> Result<Json<Value>, ApiError> {
-    let rules: Vec<serde_json::Value> =
-        ctx.client_push_service.get_user_push_rules(&auth_user.user_id, &scope, &kind).await?;
-
-    let rule: Option<&serde_json::Value> =
-        rules.iter().find(|r| r.get("rule_id").and_then(|v| v.as_str()) == Some(&rule_id));
-
-    match rule {
-        Some(r) => Ok(Json(r.clone())),
-        None => Err(ApiError::not_found("Push rule not found".to_string())),
-    }
+    Ok(Json(find_push_rule(&ctx, &auth_user.user_id, &scope, &kind, &rule_id).await?))
 }
 
 async fn set_push_rule(
@@ -361,20 +366,26 @@ async fn set_push_rule_actions(
     })))
 }
 
+async fn get_push_rule_actions(
+    Path((scope, kind, rule_id)): Path<(String, String, String)>,
+    State(ctx): State<AdminContext>,
+    auth_user: AuthenticatedUser,
+) -> Result<Json<Value>, ApiError> {
+    let rule: Value = find_push_rule(&ctx, &auth_user.user_id, &scope, &kind, &rule_id).await?;
+    Ok(Json(json!({
+        "actions": rule.get("actions").cloned().unwrap_or_else(|| json!([]))
+    })))
+}
+
 async fn get_push_rule_enabled(
     Path((scope, kind, rule_id)): Path<(String, String, String)>,
     State(ctx): State<AdminContext>,
     auth_user: AuthenticatedUser,
 ) -> Result<Json<Value>, ApiError> {
-    let result: Option<bool> =
-        ctx.client_push_service.get_push_rule_enabled(&auth_user.user_id, &scope, &kind, &rule_id).await?;
-
-    match result {
-        Some(enabled) => Ok(Json(json!({
-            "enabled": enabled
-        }))),
-        None => Err(ApiError::not_found("Push rule not found".to_string())),
-    }
+    let rule: Value = find_push_rule(&ctx, &auth_user.user_id, &scope, &kind, &rule_id).await?;
+    Ok(Json(json!({
+        "enabled": rule.get("enabled").and_then(Value::as_bool).unwrap_or(true)
+    })))
 }
 
 async fn set_push_rule_enabled(
@@ -403,6 +414,17 @@ async fn get_notifications(
 
     let notifications_list: Vec<serde_json::Value> =
         ctx.client_push_service.get_notifications(&auth_user.user_id, limit).await?;
+    let event_ids: Vec<String> = notifications_list
+        .iter()
+        .filter_map(|notification| notification.get("event_id").and_then(Value::as_str).map(str::to_string))
+        .collect();
+    let visible = ctx.event_visibility_service.visible_event_ids(&auth_user.user_id, &event_ids).await?;
+    let notifications_list: Vec<serde_json::Value> = notifications_list
+        .into_iter()
+        .filter(|notification| {
+            notification.get("event_id").and_then(Value::as_str).is_none_or(|event_id| visible.contains(event_id))
+        })
+        .collect();
 
     Ok(Json(json!({
         "notifications": notifications_list,
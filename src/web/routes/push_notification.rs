@@ -258,6 +258,16 @@ pub async fn cleanup_logs(
     })))
 }
 
+pub async fn send_test_notification(
+    State(ctx): State<AdminContext>,
+    _admin: AdminUser,
+    Path((user_id, device_id)): Path<(String, String)>,
+) -> Result<impl IntoResponse, ApiError> {
+    let result = ctx.push_notification_service.send_test_notification(&user_id, &device_id).await?;
+
+    Ok(Json(result))
+}
+
 pub fn create_push_notification_router(state: AppState) -> axum::Router<AppState> {
     use axum::routing::*;
 
@@ -274,6 +284,7 @@ pub fn create_push_notification_router(state: AppState) -> axum::Router<AppState
         axum::Router::new()
             .route("/_synapse/admin/v1/push/process", post(process_queue))
             .route("/_synapse/admin/v1/push/cleanup", post(cleanup_logs))
+            .route("/_synapse/admin/v1/push/test/{user_id}/{device_id}", post(send_test_notification))
             .route_layer(
                 axum::middleware::from_fn_with_state(
                     <crate::web::routes::context::AdminContext as axum::extract::FromRef<
@@ -299,6 +310,7 @@ pub fn push_notification_route_manifest() -> Vec<crate::web::routes::route_ledge
         (Method::DELETE, "/_matrix/client/r0/push/rules/{scope}/{kind}/{rule_id}"),
         (Method::POST, "/_synapse/admin/v1/push/process"),
         (Method::POST, "/_synapse/admin/v1/push/cleanup"),
+        (Method::POST, "/_synapse/admin/v1/push/test/{user_id}/{device_id}"),
     ]
     .into_iter()
     .map(|(m, p)| RouteEntry::new(m, p, "push_notification"))
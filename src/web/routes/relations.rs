@@ -13,17 +13,21 @@ use crate::web::routes::validators::{
 use crate::web::routes::{AppState, AuthenticatedUser};
 use axum::{
     extract::{Path, Query, State},
-    routing::{get, put},
+    routing::get,
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use synapse_common::current_timestamp_millis;
+use synapse_services::room::EventRelationsParams;
 
 fn create_relations_core_router() -> Router<AppState> {
     Router::new()
         .route("/rooms/{room_id}/relations/{event_id}/{rel_type}", get(get_relations))
-        .route("/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_id}", put(send_relation))
+        .route(
+            "/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_id}",
+            get(get_relations_by_event_type).put(send_relation),
+        )
         .route("/rooms/{room_id}/aggregations/{event_id}/{rel_type}", get(get_aggregations))
 }
 
@@ -46,6 +50,7 @@ fn relations_core_relative_routes() -> Vec<(axum::http::Method, &'static str)> {
     use axum::http::Method;
     vec![
         (Method::GET, "/rooms/{room_id}/relations/{event_id}/{rel_type}"),
+        (Method::GET, "/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_id}"),
         (Method::PUT, "/rooms/{room_id}/relations/{event_id}/{rel_type}/{event_id}"),
         (Method::GET, "/rooms/{room_id}/aggregations/{event_id}/{rel_type}"),
     ]
@@ -73,24 +78,11 @@ pub fn relations_route_manifest() -> Vec<crate::web::routes::route_ledger::Route
 pub struct RelationsQuery {
     limit: Option<i64>,
     from: Option<String>,
-    #[serde(rename = "to")]
-    _to: Option<String>,
+    to: Option<String>,
     #[serde(rename = "dir")]
     direction: Option<String>,
 }
 
-#[derive(Debug, Serialize)]
-pub struct RelationsResponse {
-    pub chunk: Vec<Value>,
-    pub next_batch: Option<String>,
-    pub prev_batch: Option<String>,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub origin_server_ts: Option<i64>,
-    /// SDK `getRelationCount` 读取此字段；空时下游永远视为 0。
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub total: Option<i64>,
-}
-
 #[derive(Debug, Serialize)]
 pub struct RelationSendResponse {
     pub event_id: String,
@@ -115,77 +107,71 @@ fn validate_event_id(event_id: &str) -> Result<(), ApiError> {
     shared_validate_event_id(event_id)
 }
 
-/// Get relations for an event without rel_type filter
-/// This returns all relations for an event
+/// Events relating to `event_id`, optionally only those of `rel_type` and
+/// then of `event_type`, with bundled aggregations.
+async fn relations_response(
+    ctx: &RoomContext,
+    auth_user: &AuthenticatedUser,
+    room_id: &str,
+    event_id: &str,
+    rel_type: Option<String>,
+    event_type: Option<String>,
+    query: RelationsQuery,
+) -> Result<Json<Value>, ApiError> {
+    validate_room_id(room_id)?;
+    validate_event_id(event_id)?;
+
+    ensure_room_member_ctx(ctx, auth_user, room_id, "User is not a member of the room").await?;
+
+    let direction = match query.direction.as_deref().unwrap_or("b") {
+        dir @ ("b" | "f") => dir.to_string(),
+        _ => return Err(ApiError::invalid_param("'dir' must be 'b' or 'f'")),
+    };
+    let params = EventRelationsParams {
+        rel_type,
+        event_type,
+        from: query.from,
+        to: query.to,
+        limit: query.limit.unwrap_or(50).clamp(1, 100),
+        direction,
+    };
+
+    tracing::debug!("Getting relations for event {} in room {}: {:?}", event_id, room_id, params);
+
+    let response =
+        ctx.room_service.messaging().get_event_relations(room_id, &auth_user.user_id, event_id, &params).await?;
+    Ok(Json(response))
+}
+
+/// All events relating to an event.
 async fn get_relations_by_event(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_id)): Path<(String, String)>,
     Query(query): Query<RelationsQuery>,
-) -> Result<Json<RelationsResponse>, ApiError> {
-    validate_room_id(&room_id)?;
-    validate_event_id(&event_id)?;
-
-    ensure_room_member_ctx(&ctx, &auth_user, &room_id, "User is not a member of the room").await?;
-
-    let limit = query.limit.unwrap_or(50).min(100) as i32;
-    let direction = query.direction.clone();
-
-    tracing::debug!("Getting all relations for event {} in room {}", event_id, room_id,);
-
-    let response =
-        ctx.relations_service.get_relations(&room_id, &event_id, None, Some(limit), query.from, direction).await?;
-
-    Ok(Json(RelationsResponse {
-        chunk: response.chunk,
-        next_batch: response.next_batch,
-        prev_batch: response.prev_batch,
-        origin_server_ts: None,
-        total: response.total,
-    }))
+) -> Result<Json<Value>, ApiError> {
+    relations_response(&ctx, &auth_user, &room_id, &event_id, None, None, query).await
 }
 
-/// Get relations for an event
-/// This endpoint is used to fetch all events that relate to a given event
+/// Events relating to an event with one `rel_type`.
 async fn get_relations(
     State(ctx): State<RoomContext>,
     auth_user: AuthenticatedUser,
     Path((room_id, event_id, rel_type)): Path<(String, String, String)>,
     Query(query): Query<RelationsQuery>,
-) -> Result<Json<RelationsResponse>, ApiError> {
-    // Validate input
-    validate_room_id(&room_id)?;
-    validate_event_id(&event_id)?;
-
-    ensure_room_member_ctx(&ctx, &auth_user, &room_id, "User is not a member of the room").await?;
-
-    // Validate rel_type
-    let valid_rel_types = ["m.reference", "m.replace", "m.thread", "m.annotation"];
-    if !valid_rel_types.contains(&rel_type.as_str()) {
-        return Err(ApiError::bad_request(format!(
-            "Invalid rel_type: {}. Must be one of: {}",
-            rel_type,
-            valid_rel_types.join(", ")
-        )));
-    }
-
-    let limit = query.limit.unwrap_or(50).min(100) as i32;
-    let direction = query.direction.clone();
-
-    tracing::debug!("Getting relations for event {} in room {} with rel_type {}", event_id, room_id, rel_type);
-
-    let response = ctx
-        .relations_service
-        .get_relations(&room_id, &event_id, Some(&rel_type), Some(limit), query.from, direction)
-        .await?;
+) -> Result<Json<Value>, ApiError> {
+    relations_response(&ctx, &auth_user, &room_id, &event_id, Some(rel_type), None, query).await
+}
 
-    Ok(Json(RelationsResponse {
-        chunk: response.chunk,
-        next_batch: response.next_batch,
-        prev_batch: response.prev_batch,
-        origin_server_ts: None,
-        total: response.total,
-    }))
+/// Events of one type relating to an event with one `rel_type`. The last
+/// segment is the event type; it is shared with the send route's path.
+async fn get_relations_by_event_type(
+    State(ctx): State<RoomContext>,
+    auth_user: AuthenticatedUser,
+    Path((room_id, event_id, rel_type, event_type)): Path<(String, String, String, String)>,
+    Query(query): Query<RelationsQuery>,
+) -> Result<Json<Value>, ApiError> {
+    relations_response(&ctx, &auth_user, &room_id, &event_id, Some(rel_type), Some(event_type), query).await
 }
 
 /// Send a relation (annotation/reference/replace)
@@ -4,19 +4,25 @@ use crate::web::routes::AppState;
 use crate::web::routes::OptionalAuthenticatedUser;
 use crate::web::utils::auth::resolve_request_id;
 use axum::{
+    body::Bytes,
     extract::{Json, Path, State},
-    http::HeaderMap,
+    http::{header, HeaderMap, HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
     routing::{delete, get, post, put},
     Router,
 };
 use serde_json::{json, Value};
 use synapse_common::current_timestamp_millis;
+use synapse_services::rendezvous_channel_service::{
+    RendezvousChannel, RendezvousWriteError, RENDEZVOUS_MAX_CONTENT_LENGTH,
+};
 use synapse_storage::rendezvous::{
     CreateRendezvousSessionParams, RendezvousIntent, RendezvousMessage, RendezvousSession, RendezvousTransport,
     StoredRendezvousMessage,
 };
 
 const RENDEZVOUS_KEY_HEADER: &str = "x-matrix-rendezvous-key";
+const MSC4108_RENDEZVOUS_PATH: &str = "/_matrix/client/unstable/org.matrix.msc4108/rendezvous";
 
 pub fn create_rendezvous_router(state: AppState) -> Router<AppState> {
     Router::new()
@@ -26,6 +32,10 @@ pub fn create_rendezvous_router(state: AppState) -> Router<AppState> {
         .route("/_matrix/client/v1/rendezvous/{session_id}", delete(delete_session))
         .route("/_matrix/client/v1/rendezvous/{session_id}/messages", post(send_message))
         .route("/_matrix/client/v1/rendezvous/{session_id}/messages", get(get_messages))
+        .route("/_matrix/client/unstable/org.matrix.msc4108/rendezvous", post(msc4108_create_session))
+        .route("/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}", get(msc4108_get_session))
+        .route("/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}", put(msc4108_update_session))
+        .route("/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}", delete(msc4108_delete_session))
         .with_state(state)
 }
 
@@ -39,6 +49,10 @@ pub fn rendezvous_route_manifest() -> Vec<crate::web::routes::route_ledger::Rout
         (Method::DELETE, "/_matrix/client/v1/rendezvous/{session_id}"),
         (Method::POST, "/_matrix/client/v1/rendezvous/{session_id}/messages"),
         (Method::GET, "/_matrix/client/v1/rendezvous/{session_id}/messages"),
+        (Method::POST, "/_matrix/client/unstable/org.matrix.msc4108/rendezvous"),
+        (Method::GET, "/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}"),
+        (Method::PUT, "/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}"),
+        (Method::DELETE, "/_matrix/client/unstable/org.matrix.msc4108/rendezvous/{session_id}"),
     ]
     .into_iter()
     .map(|(m, p)| RouteEntry::new(m, p, "rendezvous"))
@@ -311,3 +325,185 @@ async fn get_messages(
         "messages": messages_json
     })))
 }
+
+// ── MSC4108: sign in with a QR code ─────────────────────────────────────
+//
+// Unauthenticated mailbox for the secure channel between the two devices
+// (see `RendezvousChannelService`). Payloads are opaque and returned with
+// the content type they were written with.
+
+fn ensure_msc4108_enabled(ctx: &AuthContext) -> Result<(), ApiError> {
+    if ctx.config.experimental.msc4108_enabled {
+        Ok(())
+    } else {
+        Err(ApiError::unrecognized("Sign in with QR code is not enabled on this server"))
+    }
+}
+
+fn msc4108_content_type(headers: &HeaderMap) -> Result<String, ApiError> {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| ApiError::missing_param("Missing Content-Type header"))
+}
+
+/// `M_TOO_LARGE` with a 413 status, which `ApiError` cannot express.
+fn msc4108_too_large() -> Response {
+    (
+        StatusCode::PAYLOAD_TOO_LARGE,
+        Json(json!({
+            "errcode": "M_TOO_LARGE",
+            "error": format!("Rendezvous payload is larger than {RENDEZVOUS_MAX_CONTENT_LENGTH} bytes")
+        })),
+    )
+        .into_response()
+}
+
+/// An ETag header value without its quotes or weak marker.
+fn bare_etag(value: &str) -> &str {
+    value.trim().trim_start_matches("W/").trim_matches('"')
+}
+
+fn http_date(timestamp_ms: i64) -> String {
+    chrono::DateTime::from_timestamp_millis(timestamp_ms)
+        .unwrap_or_default()
+        .format("%a, %d %b %Y %H:%M:%S GMT")
+        .to_string()
+}
+
+fn set_msc4108_headers(response: &mut Response, channel: &RendezvousChannel) {
+    let headers = response.headers_mut();
+    for (name, value) in [
+        (header::ETAG, format!("\"{}\"", channel.etag)),
+        (header::EXPIRES, http_date(channel.expires_at_ms)),
+        (header::LAST_MODIFIED, http_date(channel.last_modified_ms)),
+    ] {
+        if let Ok(value) = HeaderValue::from_str(&value) {
+            headers.insert(name, value);
+        }
+    }
+    headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    headers.insert(header::PRAGMA, HeaderValue::from_static("no-cache"));
+}
+
+async fn msc4108_create_session(
+    State(ctx): State<AuthContext>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    ensure_msc4108_enabled(&ctx)?;
+    if body.len() > RENDEZVOUS_MAX_CONTENT_LENGTH {
+        return Ok(msc4108_too_large());
+    }
+    let content_type = msc4108_content_type(&headers)?;
+
+    let (session_id, channel) = ctx.rendezvous_channels.create(body.to_vec(), content_type);
+    let url = format!(
+        "{}{MSC4108_RENDEZVOUS_PATH}/{session_id}",
+        ctx.config.server.get_public_baseurl().trim_end_matches('/')
+    );
+    ::tracing::debug!(request_id = %resolve_request_id(&headers), session_id = %session_id, "Created MSC4108 rendezvous session");
+
+    let mut response = (StatusCode::CREATED, Json(json!({ "url": url }))).into_response();
+    set_msc4108_headers(&mut response, &channel);
+    Ok(response)
+}
+
+async fn msc4108_get_session(
+    State(ctx): State<AuthContext>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+) -> Result<Response, ApiError> {
+    ensure_msc4108_enabled(&ctx)?;
+    let channel = ctx
+        .rendezvous_channels
+        .get(&session_id)
+        .ok_or_else(|| ApiError::not_found("Rendezvous session not found or expired"))?;
+
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| bare_etag(value) == channel.etag);
+    let mut response = if unchanged {
+        StatusCode::NOT_MODIFIED.into_response()
+    } else {
+        ([(header::CONTENT_TYPE, channel.content_type.clone())], channel.content.clone()).into_response()
+    };
+    set_msc4108_headers(&mut response, &channel);
+    Ok(response)
+}
+
+async fn msc4108_update_session(
+    State(ctx): State<AuthContext>,
+    headers: HeaderMap,
+    Path(session_id): Path<String>,
+    body: Bytes,
+) -> Result<Response, ApiError> {
+    ensure_msc4108_enabled(&ctx)?;
+    let if_match = headers
+        .get(header::IF_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(bare_etag)
+        .ok_or_else(|| ApiError::missing_param("Missing If-Match header"))?
+        .to_string();
+    if body.len() > RENDEZVOUS_MAX_CONTENT_LENGTH {
+        return Ok(msc4108_too_large());
+    }
+    let content_type = msc4108_content_type(&headers)?;
+
+    match ctx.rendezvous_channels.update(&session_id, &if_match, body.to_vec(), content_type) {
+        Ok(channel) => {
+            let mut response = (StatusCode::ACCEPTED, Json(json!({}))).into_response();
+            set_msc4108_headers(&mut response, &channel);
+            Ok(response)
+        }
+        Err(RendezvousWriteError::NotFound) => Err(ApiError::not_found("Rendezvous session not found or expired")),
+        Err(RendezvousWriteError::EtagMismatch) => Ok((
+            StatusCode::PRECONDITION_FAILED,
+            Json(json!({
+                "errcode": "M_CONCURRENT_WRITE",
+                "error": "The rendezvous session has been modified"
+            })),
+        )
+            .into_response()),
+    }
+}
+
+async fn msc4108_delete_session(
+    State(ctx): State<AuthContext>,
+    Path(session_id): Path<String>,
+) -> Result<Json<Value>, ApiError> {
+    ensure_msc4108_enabled(&ctx)?;
+    if !ctx.rendezvous_channels.delete(&session_id) {
+        return Err(ApiError::not_found("Rendezvous session not found or expired"));
+    }
+    Ok(Json(json!({})))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bare_etag_strips_quotes_and_weak_marker() {
+        assert_eq!(bare_etag("\"abc\""), "abc");
+        assert_eq!(bare_etag("W/\"abc\""), "abc");
+        assert_eq!(bare_etag("abc"), "abc");
+    }
+
+    #[test]
+    fn http_date_uses_imf_fixdate() {
+        assert_eq!(http_date(1_792_281_540_000), "Sat, 17 Oct 2026 23:59:00 GMT");
+    }
+
+    #[test]
+    fn content_type_is_required() {
+        let mut headers = HeaderMap::new();
+        assert!(msc4108_content_type(&headers).is_err());
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("text/plain"));
+        assert_eq!(msc4108_content_type(&headers).unwrap(), "text/plain");
+        assert_eq!(msc4108_too_large().status(), StatusCode::PAYLOAD_TOO_LARGE);
+    }
+}
@@ -88,6 +88,28 @@ pub(crate) async fn ensure_room_member_strict_ctx(
     Ok(())
 }
 
+/// Peeking gate: joined members always pass; anyone else, including
+/// unauthenticated callers, passes only when the room is `world_readable`.
+///
+/// Returns whether the caller is a joined member so handlers can decide
+/// between the full member view and the stripped peek view.
+pub(crate) async fn ensure_room_peek_access_ctx(
+    ctx: &RoomContext,
+    user_id: Option<&str>,
+    room_id: &str,
+    error_message: &str,
+) -> Result<bool, ApiError> {
+    if let Some(user_id) = user_id {
+        if is_member_via(&ctx.room_service, user_id, room_id).await? {
+            return Ok(true);
+        }
+    }
+    if ctx.room_service.state().is_room_world_readable(room_id).await? {
+        return Ok(false);
+    }
+    Err(ApiError::forbidden(error_message.to_string()))
+}
+
 // =============================================================================
 // AdminContext-based helpers — used by handlers migrated to State<AdminContext>.
 // =============================================================================
@@ -130,6 +152,55 @@ pub(crate) async fn ensure_room_member_strict_admin(
     Ok(())
 }
 
+// =============================================================================
+// Config-based policy helpers — shared by every route that creates aliases.
+// =============================================================================
+
+/// Enforce `room_directory.alias_creation_rules` before `alias` is created for
+/// `room_id`. Denials are logged to the security audit target.
+pub(crate) fn ensure_alias_creation_allowed(
+    config: &synapse_common::config::RoomDirectoryConfig,
+    user_id: &str,
+    is_admin: bool,
+    alias: &str,
+    room_id: &str,
+) -> Result<(), ApiError> {
+    if config.may_create_alias(user_id, alias, room_id, is_admin) {
+        return Ok(());
+    }
+    ::tracing::warn!(
+        target: "security_audit",
+        event = "room_alias_creation_denied",
+        user_id = %user_id,
+        room_alias = %alias,
+        room_id = %room_id,
+        "Alias creation rejected by alias_creation_rules"
+    );
+    Err(ApiError::forbidden("Not allowed to create alias".to_string()))
+}
+
+/// Enforce `room_creation.rules` before `user_id` creates a room. Denials
+/// carry the operator's message and are logged to the security audit target.
+pub(crate) fn ensure_room_creation_allowed(
+    config: &synapse_common::config::RoomCreationConfig,
+    user_id: &str,
+    is_admin: bool,
+    is_space: bool,
+    is_encrypted: bool,
+) -> Result<(), ApiError> {
+    config.check(user_id, is_admin, is_space, is_encrypted).map_err(|message| {
+        ::tracing::warn!(
+            target: "security_audit",
+            event = "room_creation_denied",
+            user_id = %user_id,
+            is_space,
+            is_encrypted,
+            "Room creation rejected by room_creation rules"
+        );
+        ApiError::forbidden(message)
+    })
+}
+
 // =============================================================================
 // RoomService-based helpers — for callers that have room_service directly.
 // =============================================================================
@@ -11,7 +11,9 @@ use serde::{Deserialize, Serialize};
 use crate::common::ApiError;
 use crate::web::routes::response_helpers::{created_json, created_json_from, json_from, json_vec_from, require_found};
 use crate::web::routes::AppState;
-use crate::web::routes::{ensure_room_member_strict_ctx, AdminUser, AuthenticatedUser};
+use crate::web::routes::{
+    ensure_room_member_strict_ctx, validate_room_id, AdminUser, AuthenticatedUser, OptionalAuthenticatedUser,
+};
 use synapse_services::room::summary::{
     CreateRoomSummaryRequest, CreateSummaryMemberRequest, RoomSummaryMember, RoomSummaryResponse, RoomSummaryState,
     RoomSummaryStats, UpdateRoomSummaryRequest, UpdateSummaryMemberRequest,
@@ -488,6 +490,12 @@ pub struct Msc3266RoomSummaryResponse {
     pub is_guest_can_join: bool,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub children_state: Option<Vec<serde_json::Value>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub membership: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub room_version: Option<String>,
+    #[serde(rename = "im.nheko.summary.encryption", skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<String>,
 }
 
 #[derive(Debug, Serialize)]
@@ -513,8 +521,66 @@ impl From<RoomSummaryResponse> for Msc3266RoomSummaryResponse {
             is_world_readable: s.history_visibility == "world_readable",
             is_guest_can_join: s.guest_access == "can_join",
             children_state: None,
+            membership: None,
+            room_version: None,
+            encryption: None,
+        }
+    }
+}
+
+impl Msc3266RoomSummaryResponse {
+    /// Fallback for rooms that have no materialized summary row yet.
+    fn from_room(room: synapse_storage::Room) -> Self {
+        Self {
+            is_world_readable: room.history_visibility == "world_readable",
+            room_id: room.room_id,
+            room_type: None,
+            name: room.name,
+            topic: room.topic,
+            avatar_url: room.avatar_url,
+            canonical_alias: room.canonical_alias,
+            join_rule: room.join_rule,
+            num_joined_members: room.member_count,
+            is_guest_can_join: false,
+            children_state: None,
+            membership: None,
+            room_version: Some(room.room_version),
+            encryption: room.encryption,
         }
     }
+
+    /// Build a summary from the `room` entry of a federation
+    /// `/hierarchy` response. The remote server has already applied its own
+    /// visibility rules, so no further filtering happens here.
+    fn from_hierarchy_room(room: &serde_json::Value) -> Option<Self> {
+        let str_field = |key: &str| room.get(key).and_then(serde_json::Value::as_str).map(str::to_string);
+        Some(Self {
+            room_id: str_field("room_id")?,
+            room_type: str_field("room_type"),
+            name: str_field("name"),
+            topic: str_field("topic"),
+            avatar_url: str_field("avatar_url"),
+            canonical_alias: str_field("canonical_alias"),
+            join_rule: str_field("join_rule").unwrap_or_else(|| "public".to_string()),
+            num_joined_members: room.get("num_joined_members").and_then(serde_json::Value::as_i64).unwrap_or(0),
+            is_world_readable: room.get("world_readable").and_then(serde_json::Value::as_bool).unwrap_or(false),
+            is_guest_can_join: room.get("guest_can_join").and_then(serde_json::Value::as_bool).unwrap_or(false),
+            children_state: None,
+            membership: None,
+            room_version: str_field("room_version"),
+            encryption: str_field("encryption"),
+        })
+    }
+}
+
+/// MSC3266 visibility rule: members and invitees always see the summary;
+/// everyone else only for rooms that are world-readable or joinable/knockable
+/// without an invite.
+fn is_room_summary_visible(join_rule: &str, is_world_readable: bool, membership: Option<&str>) -> bool {
+    if matches!(membership, Some("join" | "invite" | "knock")) {
+        return true;
+    }
+    is_world_readable || matches!(join_rule, "public" | "knock" | "knock_restricted")
 }
 
 fn create_room_summary_read_router() -> Router<AppState> {
@@ -574,6 +640,99 @@ pub async fn batch_get_room_summaries(
     Ok(Json(response))
 }
 
+async fn resolve_summary_room_id(
+    ctx: &RoomContext,
+    room_id_or_alias: &str,
+    via: &[String],
+) -> Result<(String, Vec<String>), ApiError> {
+    if !room_id_or_alias.starts_with('#') {
+        validate_room_id(room_id_or_alias)?;
+        return Ok((room_id_or_alias.to_string(), via.to_vec()));
+    }
+
+    if let Some(room_id) = ctx.room_service.state().get_room_by_alias(room_id_or_alias).await? {
+        return Ok((room_id, via.to_vec()));
+    }
+
+    let remote_server = room_id_or_alias
+        .rsplit_once(':')
+        .map(|(_, server)| server)
+        .filter(|server| *server != ctx.server_name.as_str())
+        .ok_or_else(|| ApiError::not_found("Room alias not found".to_string()))?;
+
+    let directory = ctx.federation_client.query_directory(remote_server, room_id_or_alias).await.map_err(|e| {
+        ::tracing::warn!(room_alias = %room_id_or_alias, server = %remote_server, error = %e, "Federation query_directory failed");
+        ApiError::not_found("Room alias not found".to_string())
+    })?;
+
+    let mut servers = via.to_vec();
+    servers.extend(directory.servers);
+    Ok((directory.room_id, servers))
+}
+
+async fn fetch_remote_room_summary(
+    ctx: &RoomContext,
+    room_id: &str,
+    servers: &[String],
+) -> Result<Msc3266RoomSummaryResponse, ApiError> {
+    let room_server = room_id.rsplit_once(':').map(|(_, server)| server.to_string());
+    let candidates = servers.iter().cloned().chain(room_server).filter(|server| *server != ctx.server_name);
+
+    for server in candidates {
+        match ctx.federation_client.get_hierarchy(&server, room_id, false).await {
+            Ok(hierarchy) => {
+                if let Some(summary) = hierarchy.get("room").and_then(Msc3266RoomSummaryResponse::from_hierarchy_room) {
+                    return Ok(summary);
+                }
+            }
+            Err(e) => {
+                ::tracing::debug!(room_id = %room_id, server = %server, error = %e, "Federation hierarchy lookup failed");
+            }
+        }
+    }
+
+    Err(ApiError::not_found("Room not found".to_string()))
+}
+
+/// `GET /_matrix/client/unstable/im.nheko.summary/rooms/{roomIdOrAlias}/summary`
+/// (MSC3266). Authentication is optional; rooms the caller cannot preview
+/// answer 404 so their existence is not leaked.
+pub async fn get_msc3266_room_summary(
+    State(ctx): State<RoomContext>,
+    Path(room_id_or_alias): Path<String>,
+    Query(query): Query<Vec<(String, String)>>,
+    auth_user: OptionalAuthenticatedUser,
+) -> Result<impl IntoResponse, ApiError> {
+    let via: Vec<String> = query.into_iter().filter(|(key, _)| key == "via").map(|(_, value)| value).collect();
+    let (room_id, servers) = resolve_summary_room_id(&ctx, &room_id_or_alias, &via).await?;
+
+    let Some(room) = ctx.room_service.state().get_room_record(&room_id).await? else {
+        return Ok(Json(fetch_remote_room_summary(&ctx, &room_id, &servers).await?));
+    };
+
+    let membership = match auth_user.user_id.as_deref() {
+        Some(user_id) => ctx.room_service.membership().get_room_membership(&room_id, user_id).await?,
+        None => None,
+    };
+
+    let room_version = room.room_version.clone();
+    let encryption = room.encryption.clone();
+    let mut summary = match ctx.room_service.room_summary_service().get_summary(&room_id).await? {
+        Some(summary) => Msc3266RoomSummaryResponse::from(summary),
+        None => Msc3266RoomSummaryResponse::from_room(room),
+    };
+
+    if !is_room_summary_visible(&summary.join_rule, summary.is_world_readable, membership.as_deref()) {
+        return Err(ApiError::not_found("Room not found".to_string()));
+    }
+
+    summary.room_version = Some(room_version);
+    summary.encryption = encryption;
+    summary.membership = Some(membership.unwrap_or_else(|| "leave".to_string()));
+
+    Ok(Json(summary))
+}
+
 fn create_room_summary_v1_router() -> Router<AppState> {
     Router::new().route("/rooms/{room_id}/summary", get(get_room_summary))
 }
@@ -583,6 +742,10 @@ pub fn create_room_summary_router(state: AppState) -> Router<AppState> {
         .nest("/_matrix/client/v3", create_room_summary_v3_router())
         .nest("/_matrix/client/r0", create_room_summary_read_router())
         .nest("/_matrix/client/v1", create_room_summary_v1_router())
+        .route(
+            "/_matrix/client/unstable/im.nheko.summary/rooms/{room_id_or_alias}/summary",
+            get(get_msc3266_room_summary),
+        )
         .route("/_synapse/room_summary/v1/summaries", get(get_user_summaries))
         .route("/_synapse/room_summary/v1/summaries", post(create_internal_room_summary))
         .route("/_synapse/room_summary/v1/summaries/batch", post(batch_get_room_summaries))
@@ -628,6 +791,7 @@ pub fn room_summary_route_manifest() -> Vec<crate::web::routes::route_ledger::Ro
     out.extend(expand_under_prefixes("room_summary", &["/_matrix/client/v3"], &v3_routes));
     out.extend(
         [
+            (Method::GET, "/_matrix/client/unstable/im.nheko.summary/rooms/{room_id_or_alias}/summary"),
             (Method::GET, "/_synapse/room_summary/v1/summaries"),
             (Method::POST, "/_synapse/room_summary/v1/summaries"),
             (Method::POST, "/_synapse/room_summary/v1/summaries/batch"),
@@ -809,6 +973,9 @@ mod tests {
                 is_world_readable: false,
                 is_guest_can_join: false,
                 children_state: None,
+                membership: None,
+                room_version: None,
+                encryption: None,
             }],
             events: vec![],
             total_room_count_estimate: 1,
@@ -826,6 +993,40 @@ mod tests {
         assert_eq!(rooms[0]["num_joined_members"].as_i64().unwrap(), 42);
     }
 
+    #[test]
+    fn test_room_summary_visibility_rules() {
+        assert!(is_room_summary_visible("invite", false, Some("join")));
+        assert!(is_room_summary_visible("invite", false, Some("invite")));
+        assert!(is_room_summary_visible("public", false, None));
+        assert!(is_room_summary_visible("knock", false, None));
+        assert!(is_room_summary_visible("invite", true, None));
+        assert!(!is_room_summary_visible("invite", false, None));
+        assert!(!is_room_summary_visible("restricted", false, Some("leave")));
+        assert!(!is_room_summary_visible("invite", false, Some("ban")));
+    }
+
+    #[test]
+    fn test_summary_from_hierarchy_room_maps_fields() {
+        let room = serde_json::json!({
+            "room_id": "!remote:other.example",
+            "name": "Remote",
+            "num_joined_members": 7,
+            "world_readable": true,
+            "guest_can_join": false,
+            "join_rule": "knock",
+            "room_type": "m.space",
+        });
+
+        let summary = Msc3266RoomSummaryResponse::from_hierarchy_room(&room).expect("room_id present");
+        assert_eq!(summary.room_id, "!remote:other.example");
+        assert_eq!(summary.join_rule, "knock");
+        assert_eq!(summary.num_joined_members, 7);
+        assert!(summary.is_world_readable);
+        assert_eq!(summary.room_type.as_deref(), Some("m.space"));
+
+        assert!(Msc3266RoomSummaryResponse::from_hierarchy_room(&serde_json::json!({ "name": "x" })).is_none());
+    }
+
     #[test]
     fn test_msc3266_batch_response_with_next_batch() {
         let response = Msc3266RoomSummaryBatchResponse {
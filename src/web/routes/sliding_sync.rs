@@ -1,7 +1,12 @@
 use crate::common::ApiError;
 use crate::web::routes::context::SyncContext;
 use crate::web::routes::{AppState, AuthenticatedUser};
-use axum::{extract::State, routing::post, Json, Router};
+use axum::{
+    extract::{Query, State},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
 use synapse_storage::sliding_sync::{SlidingSyncRequest, SlidingSyncResponse};
 
 /// Sliding Sync endpoint
@@ -31,12 +36,29 @@ pub fn sliding_sync_route_manifest() -> Vec<crate::web::routes::route_ledger::Ro
     .collect()
 }
 
+/// Simplified sliding sync (MSC4186) moves `pos` and `timeout` from the body
+/// into the query string, which is what Element X sends.
+#[derive(Debug, Default, Deserialize)]
+struct SlidingSyncQuery {
+    pos: Option<String>,
+    timeout: Option<u32>,
+}
+
+/// Body values win so MSC3575 clients that send both keep their behaviour.
+fn apply_sliding_sync_query(mut body: SlidingSyncRequest, query: SlidingSyncQuery) -> SlidingSyncRequest {
+    body.pos = body.pos.or(query.pos);
+    body.timeout = body.timeout.or(query.timeout);
+    body
+}
+
 #[axum::debug_handler]
 async fn sliding_sync(
     State(ctx): State<SyncContext>,
     auth_user: AuthenticatedUser,
+    Query(query): Query<SlidingSyncQuery>,
     Json(body): Json<SlidingSyncRequest>,
 ) -> Result<Json<SlidingSyncResponse>, ApiError> {
+    let body = apply_sliding_sync_query(body, query);
     tracing::debug!(
         "Sliding sync request from user: {}, pos: {:?}, lists: {:?}",
         auth_user.user_id,
@@ -141,6 +163,7 @@ fn resolve_sliding_sync_rate_limit(
 mod tests {
     #[cfg(feature = "test-utils")]
     use super::resolve_sliding_sync_rate_limit;
+    use super::{apply_sliding_sync_query, SlidingSyncQuery};
     #[cfg(feature = "test-utils")]
     use crate::cache::CacheConfig;
     #[cfg(feature = "test-utils")]
@@ -155,6 +178,7 @@ mod tests {
     use axum::extract::FromRef;
     #[cfg(feature = "test-utils")]
     use std::sync::Arc;
+    use synapse_storage::sliding_sync::SlidingSyncRequest;
 
     #[cfg(feature = "test-utils")]
     #[tokio::test]
@@ -205,4 +229,17 @@ mod tests {
         assert_eq!(resolve_sliding_sync_rate_limit(&ctx, Some(&sync_override), true), (5, 50));
         assert_eq!(resolve_sliding_sync_rate_limit(&ctx, None, false), (6, 60));
     }
+
+    #[test]
+    fn test_sliding_sync_query_params_fill_missing_body_fields() {
+        let body: SlidingSyncRequest = serde_json::from_value(serde_json::json!({ "conn_id": "room-list" })).unwrap();
+        let query = SlidingSyncQuery { pos: Some("42".to_string()), timeout: Some(30000) };
+        let merged = apply_sliding_sync_query(body, query);
+        assert_eq!(merged.pos.as_deref(), Some("42"));
+        assert_eq!(merged.timeout, Some(30000));
+
+        let body: SlidingSyncRequest = serde_json::from_value(serde_json::json!({ "pos": "7" })).unwrap();
+        let merged = apply_sliding_sync_query(body, SlidingSyncQuery { pos: Some("42".to_string()), timeout: None });
+        assert_eq!(merged.pos.as_deref(), Some("7"));
+    }
 }
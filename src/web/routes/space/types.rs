@@ -42,6 +42,8 @@ pub struct AddChildBody {
     #[validate(length(max = 100))]
     pub via_servers: Vec<String>,
     pub suggested: Option<bool>,
+    #[validate(length(min = 1, max = 50))]
+    pub order: Option<String>,
 }
 
 impl AddChildBody {
@@ -52,6 +54,7 @@ impl AddChildBody {
             sender,
             is_suggested: self.suggested.unwrap_or(false),
             via_servers: self.via_servers,
+            order: self.order,
         }
     }
 }
@@ -246,6 +249,7 @@ mod tests {
             room_id: "!child:example.com".to_string(),
             via_servers: vec!["example.com".to_string()],
             suggested: Some(true),
+            order: None,
         };
 
         assert_eq!(body.room_id, "!child:example.com");
@@ -304,6 +308,7 @@ mod tests {
             room_id: "!child:example.com".to_string(),
             via_servers: vec!["example.com".to_string()],
             suggested: None,
+            order: None,
         };
 
         let request = body.into_request("!space:example.com".to_string(), "@bob:example.com".to_string());
@@ -319,6 +324,7 @@ mod tests {
             room_id: "!child:example.com".to_string(),
             via_servers: vec!["example.com".to_string(), "backup.example.com".to_string()],
             suggested: Some(true),
+            order: Some("a1".to_string()),
         };
 
         let request = body.into_request("!space:example.com".to_string(), "@bob:example.com".to_string());
@@ -327,6 +333,7 @@ mod tests {
         assert_eq!(request.space_id, "!space:example.com");
         assert_eq!(request.via_servers, vec!["example.com".to_string(), "backup.example.com".to_string()]);
         assert!(request.is_suggested);
+        assert_eq!(request.order.as_deref(), Some("a1"));
     }
 
     #[test]
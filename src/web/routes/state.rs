@@ -1,5 +1,7 @@
-use crate::cache::{CacheManager, FederationSignatureCache, SignatureCacheConfig};
-use crate::common::health::{CacheHealthCheck, DatabaseHealthCheck, HealthChecker};
+use crate::cache::{CacheManager, FederationSignatureCache};
+use crate::common::health::{
+    CacheHealthCheck, DatabaseHealthCheck, FederationKeyHealthCheck, HealthChecker, MigrationsHealthCheck,
+};
 use crate::common::{RateLimitConfigFile, RateLimitConfigManager, SyncRateLimitConfigFile};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -18,6 +20,8 @@ pub struct AppState {
     pub federation_join_semaphore: Arc<Semaphore>,
     pub federation_inbound_edu_origin_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
     pub federation_presence_backoff_until: Arc<RwLock<HashMap<String, i64>>>,
+    /// Per-user concurrency permits of `concurrency_limit_middleware`, keyed by `"{rule}:{user_id}"`.
+    pub user_request_semaphores: Arc<Mutex<HashMap<String, Arc<Semaphore>>>>,
     rate_limit_config_manager: Option<Arc<RateLimitConfigManager>>,
     /// Optional graceful-shutdown signal. When set, the `POST /_synapse/admin/v1/restart`
     /// endpoint triggers it so the process manager (Docker / systemd) can restart
@@ -42,21 +46,19 @@ pub struct SyncRateLimitOverride {
 impl AppState {
     pub fn new(services: ServiceContainer, cache: Arc<CacheManager>) -> Self {
         let pool = services.database_pool();
-        let mut health_checker = HealthChecker::new("0.1.0".to_string());
+        let mut health_checker =
+            HealthChecker::new("0.1.0".to_string()).with_policy(services.core.config.health.clone());
 
         health_checker.add_check(Box::new(DatabaseHealthCheck::new((*pool).clone())));
+        health_checker.add_check(Box::new(MigrationsHealthCheck::new((*pool).clone())));
         health_checker.add_check(Box::new(CacheHealthCheck::new((*cache).clone())));
+        if services.core.config.federation.enabled {
+            health_checker
+                .add_check(Box::new(FederationKeyHealthCheck::new(services.federation.key_rotation_manager.clone())));
+        }
 
-        let federation_signature_cache =
-            Arc::new(FederationSignatureCache::new(SignatureCacheConfig::from_federation_config(
-                services.core.config.federation.signature_cache_ttl,
-                services.core.config.federation.key_cache_ttl,
-                services.core.config.federation.key_rotation_grace_period_ms,
-            )));
-
-        // Wire federation signature cache to key rotation manager so that
-        // cached signature verification results are invalidated on key rotation.
-        services.federation.key_rotation_manager.set_signature_cache(federation_signature_cache.clone());
+        // Built and wired to the key rotation manager in federation wiring.
+        let federation_signature_cache = services.federation.signature_cache.clone();
 
         #[cfg(feature = "openclaw-routes")]
         let canonical_cache = cache.clone();
@@ -87,6 +89,7 @@ impl AppState {
             federation_join_semaphore: Arc::new(Semaphore::new(join_max_concurrency)),
             federation_inbound_edu_origin_semaphores: Arc::new(Mutex::new(HashMap::new())),
             federation_presence_backoff_until: Arc::new(RwLock::new(HashMap::new())),
+            user_request_semaphores: Arc::new(Mutex::new(HashMap::new())),
             rate_limit_config_manager: None,
             shutdown_signal: None,
             #[cfg(feature = "openclaw-routes")]
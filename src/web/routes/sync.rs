@@ -1,7 +1,9 @@
 use crate::web::routes::context::SyncContext;
 use crate::web::routes::{
     get_joined_rooms, get_my_rooms,
+    handlers::events_sse::events_sse,
     handlers::sync::{get_events, sync},
+    handlers::sync_ws::sync_ws,
     AppState,
 };
 use axum::{
@@ -42,16 +44,37 @@ fn create_sync_v3_router(state: AppState) -> Router<AppState> {
     create_sync_compat_router(state).route("/joined_rooms", get(get_joined_rooms)).route("/my_rooms", get(get_my_rooms))
 }
 
+/// Experimental WebSocket sync transport. The route is always registered; the
+/// handler answers `M_UNRECOGNIZED` unless `experimental.sync_websocket_enabled`
+/// is set.
+fn create_sync_ws_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/sync", get(sync_ws))
+        .route_layer(middleware::from_fn_with_state(state, sync_route_owner_header_middleware))
+}
+
+/// Server-Sent Events variant of `/events`, gated by
+/// `experimental.events_sse_enabled` in the handler like the WebSocket route.
+fn create_events_sse_router(state: AppState) -> Router<AppState> {
+    Router::new()
+        .route("/events", get(events_sse))
+        .route_layer(middleware::from_fn_with_state(state, sync_route_owner_header_middleware))
+}
+
 pub fn create_sync_router(state: AppState) -> Router<AppState> {
     Router::new()
         .nest("/_matrix/client/r0", create_sync_r0_router(state.clone()))
-        .nest("/_matrix/client/v3", create_sync_v3_router(state))
+        .nest("/_matrix/client/v3", create_sync_v3_router(state.clone()))
+        .nest("/_matrix/client/unstable/org.synapse_rust.sync_ws", create_sync_ws_router(state.clone()))
+        .nest("/_matrix/client/unstable/org.synapse_rust.events_sse", create_events_sse_router(state))
 }
 
 /// Manifest of every `(method, absolute_path)` tuple `create_sync_router`
 /// registers. Each version has a distinct inner router (r0 has `/sync`,
-/// `/events`, `/joined_rooms`; v3 has all of the above plus `/my_rooms`) so
-/// the entries are enumerated per-prefix rather than expanded uniformly.
+/// `/events`, `/joined_rooms`; v3 has all of the above plus `/my_rooms`; the
+/// unstable WebSocket and SSE prefixes only have `/sync` and `/events`
+/// respectively) so the entries are enumerated per-prefix rather than
+/// expanded uniformly.
 pub fn sync_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry> {
     use crate::web::routes::route_ledger::expand_under_prefixes;
     use axum::http::Method;
@@ -69,6 +92,16 @@ pub fn sync_route_manifest() -> Vec<crate::web::routes::route_ledger::RouteEntry
         &["/_matrix/client/v3"],
         &[(Method::GET, "/sync"), (Method::GET, "/events"), (Method::GET, "/joined_rooms"), (Method::GET, "/my_rooms")],
     ));
+    out.extend(expand_under_prefixes(
+        MODULE,
+        &["/_matrix/client/unstable/org.synapse_rust.sync_ws"],
+        &[(Method::GET, "/sync")],
+    ));
+    out.extend(expand_under_prefixes(
+        MODULE,
+        &["/_matrix/client/unstable/org.synapse_rust.events_sse"],
+        &[(Method::GET, "/events")],
+    ));
     out
 }
 
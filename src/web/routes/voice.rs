@@ -154,7 +154,7 @@ async fn upload_voice_message(
         .await
     {
         Ok(result) => Ok(Json(result)),
-        Err(e) => Err(ApiError::internal(e.to_string())),
+        Err(e) => Err(ApiError::internal_with_log("Failed to upload voice message", &e)),
     }
 }
 
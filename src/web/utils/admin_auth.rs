@@ -1,5 +1,6 @@
 use crate::common::config::SecurityConfig;
 use crate::common::ApiError;
+use crate::web::middleware::record_request_user;
 use crate::web::routes::AppState;
 use crate::web::utils::auth::resolve_request_id;
 use axum::http::{HeaderMap, Method};
@@ -37,6 +38,7 @@ pub(crate) async fn authorize_admin_from_services(
     let access_token = super::auth::bearer_token(headers)?;
     let (user_id, device_id, is_admin, _, _): (String, Option<String>, bool, bool, bool) =
         auth_service.validate_token(&access_token).await?;
+    record_request_user(&user_id);
 
     if !is_admin {
         return Err(ApiError::forbidden("Admin access required".to_string()));
@@ -117,6 +119,7 @@ pub(crate) async fn authorize_admin_request(
     let access_token = super::auth::bearer_token(headers)?;
     let (user_id, device_id, is_admin, _, _): (String, Option<String>, bool, bool, bool) =
         state.services.core.token_auth.validate_token(&access_token).await?;
+    record_request_user(&user_id);
 
     if !is_admin {
         return Err(ApiError::forbidden("Admin access required".to_string()));
@@ -272,8 +275,8 @@ fn is_super_admin_only_endpoint(method: &Method, path: &str) -> bool {
         return true;
     }
 
-    // Batch user creation (not batch_deactivate which admin can do)
-    if path == "/_synapse/admin/v1/users/batch" {
+    // Batch user creation and bulk provisioning (not batch_deactivate which admin can do)
+    if path == "/_synapse/admin/v1/users/batch" || path == "/_synapse/admin/v1/users/provision" {
         return true;
     }
 
@@ -564,6 +567,7 @@ mod tests {
         assert!(!is_role_allowed("admin", &Method::POST, "/_synapse/admin/v1/registration_tokens"));
         assert!(!is_role_allowed("admin", &Method::GET, "/_synapse/admin/info"));
         assert!(!is_role_allowed("admin", &Method::POST, "/_synapse/admin/v1/users/batch"));
+        assert!(!is_role_allowed("admin", &Method::POST, "/_synapse/admin/v1/users/provision"));
         assert!(!is_role_allowed("admin", &Method::POST, "/_synapse/admin/v1/users/@u:localhost/delete_devices"));
         assert!(!is_role_allowed("admin", &Method::POST, "/_synapse/admin/v1/rooms/!room:localhost/purge"));
         assert!(!is_role_allowed("admin", &Method::PUT, "/_synapse/admin/v1/rooms/!room:localhost/retention"));
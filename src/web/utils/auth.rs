@@ -5,12 +5,17 @@ pub(crate) fn generate_request_id() -> String {
     format!("req-{}", uuid::Uuid::new_v4())
 }
 
+/// Longest inbound `X-Request-Id` that is reused as-is.
+const MAX_INBOUND_REQUEST_ID_LEN: usize = 128;
+
+/// The inbound `X-Request-Id`, or a fresh ID when it is missing, too long or
+/// contains anything but printable ASCII (it ends up in logs and headers).
 pub(crate) fn resolve_request_id(headers: &HeaderMap) -> String {
     headers
         .get("x-request-id")
         .and_then(|v| v.to_str().ok())
         .map(str::trim)
-        .filter(|v| !v.is_empty())
+        .filter(|v| !v.is_empty() && v.len() <= MAX_INBOUND_REQUEST_ID_LEN && v.bytes().all(|b| b.is_ascii_graphic()))
         .map_or_else(generate_request_id, |v| v.to_string())
 }
 
@@ -56,6 +61,13 @@ pub(crate) fn extract_token(headers: &HeaderMap, uri: &str) -> Result<String, Ap
     }
 }
 
+/// The `user_id` query parameter an application service uses to act as one
+/// of its users.
+pub(crate) fn masquerade_user_id(uri: &str) -> Option<String> {
+    let query = uri.split_once('?')?.1;
+    url::form_urlencoded::parse(query.as_bytes()).find(|(key, _)| key == "user_id").map(|(_, value)| value.into_owned())
+}
+
 /// Like `extract_token` but returns `None` instead of an error when
 /// no token is found.
 pub(crate) fn extract_token_opt(headers: &HeaderMap, uri: &str) -> Option<String> {
@@ -85,6 +97,14 @@ mod tests {
         assert_eq!(id.len(), "req-".len() + 36); // UUID v4 is 36 chars
     }
 
+    #[test]
+    fn test_masquerade_user_id_is_percent_decoded() {
+        let uri = "/_matrix/client/v3/rooms/!r:example.com/join?user_id=%40bridge_alice%3Aexample.com";
+        assert_eq!(masquerade_user_id(uri).as_deref(), Some("@bridge_alice:example.com"));
+        assert_eq!(masquerade_user_id("/_matrix/client/v3/sync?since=s1"), None);
+        assert_eq!(masquerade_user_id("/_matrix/client/v3/sync"), None);
+    }
+
     #[test]
     fn test_generate_request_id_unique() {
         let id1 = generate_request_id();
@@ -106,6 +126,16 @@ mod tests {
         assert!(id.starts_with("req-"));
     }
 
+    #[test]
+    fn test_resolve_request_id_replaces_unusable_header() {
+        let mut headers = HeaderMap::new();
+        headers.insert("x-request-id", "a".repeat(MAX_INBOUND_REQUEST_ID_LEN + 1).parse().unwrap());
+        assert!(resolve_request_id(&headers).starts_with("req-"));
+
+        headers.insert("x-request-id", "has space".parse().unwrap());
+        assert!(resolve_request_id(&headers).starts_with("req-"));
+    }
+
     #[test]
     fn test_resolve_request_id_empty_header() {
         let mut headers = HeaderMap::new();
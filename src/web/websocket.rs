@@ -0,0 +1,327 @@
+//! Minimal server side of the WebSocket protocol (RFC 6455).
+//!
+//! Only what the experimental sync transport needs: the opening handshake on
+//! top of a hyper upgrade, and a frame codec for text, ping, pong and close
+//! frames. Extensions and subprotocols are not negotiated.
+
+use axum::{
+    body::Body,
+    http::{header, HeaderMap, HeaderValue, Response, StatusCode},
+};
+use base64::Engine;
+use sha1::{Digest, Sha1};
+use thiserror::Error;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
+
+const HANDSHAKE_GUID: &str = "258EAFA5-E914-47DA-95CA-C5AB0DC85B11";
+
+/// Largest message accepted from a client. Clients only send control frames
+/// on the sync socket, so this is deliberately small.
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 64 * 1024;
+
+pub const CLOSE_NORMAL: u16 = 1000;
+pub const CLOSE_PROTOCOL_ERROR: u16 = 1002;
+pub const CLOSE_POLICY_VIOLATION: u16 = 1008;
+pub const CLOSE_TOO_BIG: u16 = 1009;
+pub const CLOSE_INTERNAL_ERROR: u16 = 1011;
+
+#[derive(Debug, Error)]
+pub enum WebSocketError {
+    #[error("Missing or invalid upgrade header: {0}")]
+    Handshake(&'static str),
+    #[error("Protocol violation: {0}")]
+    Protocol(&'static str),
+    #[error("Message exceeds {MAX_CLIENT_MESSAGE_BYTES} bytes")]
+    TooBig,
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+impl WebSocketError {
+    /// Close code to send before dropping the connection.
+    pub fn close_code(&self) -> u16 {
+        match self {
+            Self::TooBig => CLOSE_TOO_BIG,
+            Self::Io(_) => CLOSE_INTERNAL_ERROR,
+            Self::Handshake(_) | Self::Protocol(_) => CLOSE_PROTOCOL_ERROR,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Message {
+    Text(String),
+    Binary(Vec<u8>),
+    Ping(Vec<u8>),
+    Pong(Vec<u8>),
+    Close(Option<(u16, String)>),
+}
+
+/// Validate the client's opening handshake and build the `101 Switching
+/// Protocols` response. The caller still has to drive the hyper upgrade.
+pub fn accept_handshake(headers: &HeaderMap) -> Result<Response<Body>, WebSocketError> {
+    let header_has_token = |name: header::HeaderName, token: &str| {
+        headers
+            .get_all(name)
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .any(|part| part.trim().eq_ignore_ascii_case(token))
+    };
+
+    if !header_has_token(header::CONNECTION, "upgrade") {
+        return Err(WebSocketError::Handshake("connection"));
+    }
+    if !header_has_token(header::UPGRADE, "websocket") {
+        return Err(WebSocketError::Handshake("upgrade"));
+    }
+    if headers.get(header::SEC_WEBSOCKET_VERSION).and_then(|v| v.to_str().ok()) != Some("13") {
+        return Err(WebSocketError::Handshake("sec-websocket-version"));
+    }
+    let key = headers
+        .get(header::SEC_WEBSOCKET_KEY)
+        .and_then(|v| v.to_str().ok())
+        .ok_or(WebSocketError::Handshake("sec-websocket-key"))?;
+
+    let accept = HeaderValue::from_str(&accept_key(key)).map_err(|_| WebSocketError::Handshake("sec-websocket-key"))?;
+
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::SWITCHING_PROTOCOLS;
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CONNECTION, HeaderValue::from_static("upgrade"));
+    response_headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+    response_headers.insert(header::SEC_WEBSOCKET_ACCEPT, accept);
+    Ok(response)
+}
+
+fn accept_key(key: &str) -> String {
+    let mut hasher = Sha1::new();
+    hasher.update(key.trim().as_bytes());
+    hasher.update(HANDSHAKE_GUID.as_bytes());
+    base64::engine::general_purpose::STANDARD.encode(hasher.finalize())
+}
+
+/// Encode an unmasked server-to-client frame.
+pub fn encode(message: &Message) -> Vec<u8> {
+    let (opcode, payload): (u8, std::borrow::Cow<'_, [u8]>) = match message {
+        Message::Text(text) => (0x1, text.as_bytes().into()),
+        Message::Binary(data) => (0x2, data.as_slice().into()),
+        Message::Close(None) => (0x8, Vec::new().into()),
+        Message::Close(Some((code, reason))) => {
+            let mut payload = code.to_be_bytes().to_vec();
+            payload.extend_from_slice(truncate_utf8(reason, 123).as_bytes());
+            (0x8, payload.into())
+        }
+        Message::Ping(data) => (0x9, data.as_slice().into()),
+        Message::Pong(data) => (0xA, data.as_slice().into()),
+    };
+
+    let mut frame = Vec::with_capacity(payload.len() + 10);
+    frame.push(0x80 | opcode);
+    match payload.len() {
+        len @ 0..=125 => frame.push(len as u8),
+        len @ 126..=0xFFFF => {
+            frame.push(126);
+            frame.extend_from_slice(&(len as u16).to_be_bytes());
+        }
+        len => {
+            frame.push(127);
+            frame.extend_from_slice(&(len as u64).to_be_bytes());
+        }
+    }
+    frame.extend_from_slice(&payload);
+    frame
+}
+
+fn truncate_utf8(value: &str, max_bytes: usize) -> &str {
+    if value.len() <= max_bytes {
+        return value;
+    }
+    let mut end = max_bytes;
+    while !value.is_char_boundary(end) {
+        end -= 1;
+    }
+    &value[..end]
+}
+
+pub async fn write_message<W: AsyncWrite + Unpin>(writer: &mut W, message: &Message) -> Result<(), WebSocketError> {
+    writer.write_all(&encode(message)).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
+/// Reads client messages, reassembling fragmented data frames. Control frames
+/// may arrive between fragments and are returned as soon as they are read.
+pub struct MessageReader<R> {
+    reader: R,
+    fragments: Option<(u8, Vec<u8>)>,
+}
+
+impl<R: AsyncRead + Unpin> MessageReader<R> {
+    pub fn new(reader: R) -> Self {
+        Self { reader, fragments: None }
+    }
+
+    pub async fn next_message(&mut self) -> Result<Message, WebSocketError> {
+        loop {
+            let mut head = [0u8; 2];
+            self.reader.read_exact(&mut head).await?;
+            let fin = head[0] & 0x80 != 0;
+            if head[0] & 0x70 != 0 {
+                return Err(WebSocketError::Protocol("reserved bits set"));
+            }
+            let opcode = head[0] & 0x0F;
+            if head[1] & 0x80 == 0 {
+                return Err(WebSocketError::Protocol("client frames must be masked"));
+            }
+
+            let len = match head[1] & 0x7F {
+                126 => u64::from(self.reader.read_u16().await?),
+                127 => self.reader.read_u64().await?,
+                len => u64::from(len),
+            };
+            let is_control = opcode & 0x8 != 0;
+            if is_control && (!fin || len > 125) {
+                return Err(WebSocketError::Protocol("invalid control frame"));
+            }
+            let buffered = self.fragments.as_ref().map_or(0, |(_, data)| data.len());
+            if len > (MAX_CLIENT_MESSAGE_BYTES - buffered) as u64 {
+                return Err(WebSocketError::TooBig);
+            }
+
+            let mut mask = [0u8; 4];
+            self.reader.read_exact(&mut mask).await?;
+            let mut payload = vec![0u8; len as usize];
+            self.reader.read_exact(&mut payload).await?;
+            for (i, byte) in payload.iter_mut().enumerate() {
+                *byte ^= mask[i % 4];
+            }
+
+            match opcode {
+                0x8 => return Ok(Message::Close(parse_close(&payload)?)),
+                0x9 => return Ok(Message::Ping(payload)),
+                0xA => return Ok(Message::Pong(payload)),
+                0x0 => {
+                    let Some((_, data)) = self.fragments.as_mut() else {
+                        return Err(WebSocketError::Protocol("unexpected continuation frame"));
+                    };
+                    data.extend_from_slice(&payload);
+                }
+                0x1 | 0x2 => {
+                    if self.fragments.is_some() {
+                        return Err(WebSocketError::Protocol("new message before previous one finished"));
+                    }
+                    self.fragments = Some((opcode, payload));
+                }
+                _ => return Err(WebSocketError::Protocol("unknown opcode")),
+            }
+
+            if fin {
+                if let Some((opcode, data)) = self.fragments.take() {
+                    return if opcode == 0x1 {
+                        String::from_utf8(data)
+                            .map(Message::Text)
+                            .map_err(|_| WebSocketError::Protocol("text message is not UTF-8"))
+                    } else {
+                        Ok(Message::Binary(data))
+                    };
+                }
+            }
+        }
+    }
+}
+
+fn parse_close(payload: &[u8]) -> Result<Option<(u16, String)>, WebSocketError> {
+    match payload {
+        [] => Ok(None),
+        [_] => Err(WebSocketError::Protocol("truncated close code")),
+        [hi, lo, reason @ ..] => {
+            let reason =
+                std::str::from_utf8(reason).map_err(|_| WebSocketError::Protocol("close reason is not UTF-8"))?;
+            Ok(Some((u16::from_be_bytes([*hi, *lo]), reason.to_string())))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masked(first: u8, payload: &[u8]) -> Vec<u8> {
+        let mask = [0x12, 0x34, 0x56, 0x78];
+        let mut frame = vec![first];
+        if payload.len() < 126 {
+            frame.push(0x80 | payload.len() as u8);
+        } else {
+            frame.push(0x80 | 126);
+            frame.extend_from_slice(&(payload.len() as u16).to_be_bytes());
+        }
+        frame.extend_from_slice(&mask);
+        frame.extend(payload.iter().enumerate().map(|(i, b)| b ^ mask[i % 4]));
+        frame
+    }
+
+    #[test]
+    fn test_accept_key_matches_rfc_example() {
+        assert_eq!(accept_key("dGhlIHNhbXBsZSBub25jZQ=="), "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_handshake_requires_upgrade_headers() {
+        let mut headers = HeaderMap::new();
+        headers.insert(header::CONNECTION, HeaderValue::from_static("keep-alive, Upgrade"));
+        headers.insert(header::UPGRADE, HeaderValue::from_static("websocket"));
+        headers.insert(header::SEC_WEBSOCKET_VERSION, HeaderValue::from_static("13"));
+        assert!(matches!(accept_handshake(&headers), Err(WebSocketError::Handshake("sec-websocket-key"))));
+
+        headers.insert(header::SEC_WEBSOCKET_KEY, HeaderValue::from_static("dGhlIHNhbXBsZSBub25jZQ=="));
+        let response = accept_handshake(&headers).unwrap();
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        assert_eq!(response.headers()[header::SEC_WEBSOCKET_ACCEPT], "s3pPLMBiTxaQ9kYGzzhZRbK+xOo=");
+    }
+
+    #[test]
+    fn test_encode_uses_extended_lengths() {
+        assert_eq!(encode(&Message::Text("hi".into())), vec![0x81, 2, b'h', b'i']);
+
+        let medium = encode(&Message::Binary(vec![0; 300]));
+        assert_eq!(&medium[..4], &[0x82, 126, 0x01, 0x2C]);
+
+        let large = encode(&Message::Binary(vec![0; 70_000]));
+        assert_eq!(&large[..2], &[0x82, 127]);
+        assert_eq!(u64::from_be_bytes(large[2..10].try_into().unwrap()), 70_000);
+    }
+
+    #[test]
+    fn test_encode_close_frame_carries_code_and_reason() {
+        assert_eq!(
+            encode(&Message::Close(Some((CLOSE_NORMAL, "bye".into())))),
+            vec![0x88, 5, 0x03, 0xE8, b'b', b'y', b'e']
+        );
+    }
+
+    #[tokio::test]
+    async fn test_reader_reassembles_fragments_around_control_frames() {
+        let mut wire = masked(0x01, b"hel");
+        wire.extend(masked(0x89, b"p"));
+        wire.extend(masked(0x80, b"lo"));
+        wire.extend(masked(0x88, &[0x03, 0xE8]));
+        let mut reader = MessageReader::new(wire.as_slice());
+
+        assert_eq!(reader.next_message().await.unwrap(), Message::Ping(b"p".to_vec()));
+        assert_eq!(reader.next_message().await.unwrap(), Message::Text("hello".into()));
+        assert_eq!(reader.next_message().await.unwrap(), Message::Close(Some((CLOSE_NORMAL, String::new()))));
+    }
+
+    #[tokio::test]
+    async fn test_reader_rejects_unmasked_and_oversized_frames() {
+        let mut reader = MessageReader::new(&[0x81u8, 0x01, b'x'][..]);
+        assert!(matches!(reader.next_message().await, Err(WebSocketError::Protocol(_))));
+
+        let mut oversized = vec![0x82u8, 0x80 | 127];
+        oversized.extend_from_slice(&((MAX_CLIENT_MESSAGE_BYTES as u64) + 1).to_be_bytes());
+        let mut reader = MessageReader::new(oversized.as_slice());
+        let err = reader.next_message().await.unwrap_err();
+        assert_eq!(err.close_code(), CLOSE_TOO_BIG);
+    }
+}
@@ -18,6 +18,9 @@ pub struct CircuitBreakerMetrics {
     pub rejected_requests: u64,
     pub timeout_requests: u64,
     pub state_transitions: u64,
+    pub opened_transitions: u64,
+    pub half_opened_transitions: u64,
+    pub closed_transitions: u64,
     pub last_failure: Option<Instant>,
     pub last_state_change: Option<Instant>,
 }
@@ -200,6 +203,7 @@ impl CircuitBreaker {
 
             let mut metrics = self.metrics.write();
             metrics.state_transitions += 1;
+            metrics.opened_transitions += 1;
             metrics.last_state_change = Some(Instant::now());
 
             let should_log = {
@@ -238,6 +242,7 @@ impl CircuitBreaker {
 
             let mut metrics = self.metrics.write();
             metrics.state_transitions += 1;
+            metrics.half_opened_transitions += 1;
             metrics.last_state_change = Some(Instant::now());
 
             let should_log = {
@@ -276,6 +281,7 @@ impl CircuitBreaker {
 
             let mut metrics = self.metrics.write();
             metrics.state_transitions += 1;
+            metrics.closed_transitions += 1;
             metrics.last_state_change = Some(Instant::now());
 
             let should_log = {
@@ -435,6 +441,29 @@ mod tests {
         assert_eq!(cb.current_state(), CircuitState::Closed);
     }
 
+    #[test]
+    fn test_circuit_breaker_counts_transitions_by_target_state() {
+        let cb = CircuitBreaker::new(test_config());
+
+        for _ in 0..3 {
+            cb.is_call_allowed();
+            cb.record_failure();
+        }
+        assert!(!cb.is_call_allowed());
+        thread::sleep(Duration::from_millis(150));
+        cb.is_call_allowed();
+        for _ in 0..2 {
+            cb.record_success();
+        }
+
+        let metrics = cb.get_metrics();
+        assert_eq!(metrics.opened_transitions, 1);
+        assert_eq!(metrics.half_opened_transitions, 1);
+        assert_eq!(metrics.closed_transitions, 1);
+        assert_eq!(metrics.state_transitions, 3);
+        assert_eq!(metrics.rejected_requests, 1);
+    }
+
     #[test]
     fn test_circuit_breaker_reopens_on_failure_in_half_open() {
         let cb = CircuitBreaker::new(test_config());
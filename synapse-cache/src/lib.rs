@@ -298,6 +298,10 @@ impl LocalCache {
     pub fn remove(&self, token: &str) {
         self.cache.remove(token);
     }
+
+    pub fn take_raw(&self, key: &str) -> Option<String> {
+        self.cache.remove(key)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -444,7 +448,10 @@ impl RedisCache {
                 }
                 val
             }
-            Err(_) => None,
+            Err(_) => {
+                self.degradation_metrics.write().record_fallback();
+                None
+            }
         }
     }
 
@@ -480,7 +487,10 @@ impl RedisCache {
                 }
                 vals
             }
-            Err(_) => vec![None; keys.len()],
+            Err(_) => {
+                self.degradation_metrics.write().record_fallback();
+                vec![None; keys.len()]
+            }
         }
     }
 
@@ -501,6 +511,18 @@ impl RedisCache {
         .await
     }
 
+    /// Deletes `key` and returns its previous value in one `GETDEL`.
+    pub async fn take(&self, key: &str) -> Result<Option<String>, CacheError> {
+        self.with_circuit_breaker("GETDEL", |mut conn| async move {
+            redis::cmd("GETDEL")
+                .arg(key)
+                .query_async::<Option<String>>(&mut conn)
+                .await
+                .map_err(|e| CacheError::OperationFailed(e.to_string()))
+        })
+        .await
+    }
+
     pub async fn hincrby(&self, key: &str, field: &str, delta: i64) -> Result<i64, redis::RedisError> {
         use redis::AsyncCommands;
         self.with_circuit_breaker("HINCRBY", |mut conn| async move { conn.hincr(key, field, delta).await }).await
@@ -625,13 +647,15 @@ pub struct CacheManager {
     local: LocalCache,
     redis: Option<Arc<RedisCache>>,
     use_redis: bool,
-    rate_limit_local: Arc<parking_lot::Mutex<HashMap<String, LocalRateLimitState>>>,
+    rate_limit_local: Cache<String, LocalRateLimitState>,
     invalidation_manager: Option<Arc<CacheInvalidationManager>>,
     local_cache_ttl: Duration,
     /// Per-key single-flight guards used by `get_or_fetch` to prevent cache
     /// stampede when a hot key expires. Each entry is an `Arc<Mutex<()>>` that
     /// serializes concurrent fetches for the same key.
     in_flight: SingleFlightMap,
+    /// L1 hit/miss counters; Redis-side counters live on `RedisCache`.
+    local_metrics: Arc<parking_lot::RwLock<DegradationMetrics>>,
 }
 
 impl CacheManager {
@@ -640,10 +664,11 @@ impl CacheManager {
             local: LocalCache::new(config),
             redis: None,
             use_redis: false,
-            rate_limit_local: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            rate_limit_local: local_rate_limit_cache(),
             invalidation_manager: None,
             local_cache_ttl: Duration::from_secs(DEFAULT_LOCAL_CACHE_TTL_SECS),
             in_flight: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            local_metrics: Arc::new(parking_lot::RwLock::new(DegradationMetrics::new())),
         }
     }
 
@@ -669,10 +694,11 @@ impl CacheManager {
                     local: LocalCache::new(cache_config),
                     redis: Some(Arc::new(redis_cache)),
                     use_redis: true,
-                    rate_limit_local: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+                    rate_limit_local: local_rate_limit_cache(),
                     invalidation_manager: Some(invalidation_manager),
                     local_cache_ttl: Duration::from_secs(DEFAULT_LOCAL_CACHE_TTL_SECS),
                     in_flight: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+                    local_metrics: Arc::new(parking_lot::RwLock::new(DegradationMetrics::new())),
                 })
             }
             Err(e) => {
@@ -681,10 +707,11 @@ impl CacheManager {
                     local: LocalCache::new(cache_config),
                     redis: None,
                     use_redis: false,
-                    rate_limit_local: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+                    rate_limit_local: local_rate_limit_cache(),
                     invalidation_manager: None,
                     local_cache_ttl: Duration::from_secs(DEFAULT_LOCAL_CACHE_TTL_SECS),
                     in_flight: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+                    local_metrics: Arc::new(parking_lot::RwLock::new(DegradationMetrics::new())),
                 })
             }
         }
@@ -710,10 +737,11 @@ impl CacheManager {
             local: LocalCache::new(cache_config),
             redis: Some(Arc::new(redis_cache)),
             use_redis: true,
-            rate_limit_local: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            rate_limit_local: local_rate_limit_cache(),
             invalidation_manager: Some(invalidation_manager),
             local_cache_ttl: Duration::from_secs(DEFAULT_LOCAL_CACHE_TTL_SECS),
             in_flight: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            local_metrics: Arc::new(parking_lot::RwLock::new(DegradationMetrics::new())),
         }
     }
 
@@ -729,10 +757,11 @@ impl CacheManager {
             local: LocalCache::new(cache_config),
             redis: Some(Arc::new(redis_cache)),
             use_redis: true,
-            rate_limit_local: Arc::new(parking_lot::Mutex::new(HashMap::new())),
+            rate_limit_local: local_rate_limit_cache(),
             invalidation_manager: Some(invalidation_manager),
             local_cache_ttl: Duration::from_secs(invalidation_config.local_cache_ttl_secs),
             in_flight: Arc::new(tokio::sync::Mutex::new(HashMap::new())),
+            local_metrics: Arc::new(parking_lot::RwLock::new(DegradationMetrics::new())),
         }
     }
 
@@ -824,23 +853,26 @@ impl CacheManager {
     }
 
     pub async fn get_token(&self, token: &str) -> Option<Claims> {
-        if let Some(claims) = self.local.get(token) {
+        let key = CacheKeyBuilder::token(token);
+        let local = self.local.get(&key);
+        self.record_local_lookup(local.is_some());
+        if let Some(claims) = local {
             if claims.exp >= chrono::Utc::now().timestamp() {
                 return Some(claims);
             }
-            self.local.remove(token);
+            self.local.remove(&key);
             return None;
         }
 
         if self.use_redis {
             if let Some(redis) = &self.redis {
-                if let Some(val) = redis.get(token).await {
+                if let Some(val) = redis.get(&key).await {
                     if let Ok(claims) = serde_json::from_str::<Claims>(&val) {
                         if claims.exp >= chrono::Utc::now().timestamp() {
-                            self.local.set(token, &claims);
+                            self.local.set(&key, &claims);
                             return Some(claims);
                         }
-                        let _ = redis.delete(token).await;
+                        let _ = redis.delete(&key).await;
                         return None;
                     }
                 }
@@ -850,24 +882,26 @@ impl CacheManager {
     }
 
     pub async fn set_token(&self, token: &str, claims: &Claims, ttl: u64) {
+        let key = CacheKeyBuilder::token(token);
         // Update L1
-        self.local.set(token, claims);
+        self.local.set(&key, claims);
         // Update L2
         if self.use_redis {
             if let Some(redis) = &self.redis {
                 if let Ok(val) = serde_json::to_string(claims) {
-                    let _ = redis.set(token, &val, ttl).await;
+                    let _ = redis.set(&key, &val, ttl).await;
                 }
             }
         }
     }
 
     pub async fn delete_token(&self, token: &str) {
-        self.local.remove(token);
+        let key = CacheKeyBuilder::token(token);
+        self.local.remove(&key);
         if let Some(redis) = &self.redis {
-            let _ = redis.delete(token).await;
+            let _ = redis.delete(&key).await;
         }
-        if let Err(e) = self.broadcast_invalidation(token, InvalidationType::Key).await {
+        if let Err(e) = self.broadcast_invalidation(&key, InvalidationType::Key).await {
             tracing::warn!("Failed to broadcast token invalidation: {}", e);
         }
     }
@@ -907,6 +941,28 @@ impl CacheManager {
         }
     }
 
+    /// Removes `key` and returns the value it held. Of several concurrent
+    /// callers, at most one receives the value, which makes this suitable for
+    /// single-use tokens. With Redis enabled, Redis decides; the local copy is
+    /// only used while Redis is unreachable.
+    pub async fn take<T: for<'de> Deserialize<'de>>(&self, key: &str) -> Result<Option<T>, ApiError> {
+        let local = self.local.take_raw(key);
+        let raw = match (&self.redis, self.use_redis) {
+            (Some(redis), true) => match redis.take(key).await {
+                Ok(value) => value,
+                Err(e) => {
+                    ::tracing::warn!(target: "cache", cache_key = %key, error = %e, "Redis GETDEL failed, using local cache");
+                    local
+                }
+            },
+            _ => local,
+        };
+        if let Err(e) = self.broadcast_invalidation(key, InvalidationType::Key).await {
+            tracing::warn!("Failed to broadcast key invalidation: {}", e);
+        }
+        Ok(raw.and_then(|val| serde_json::from_str(&val).ok()))
+    }
+
     pub async fn delete_with_invalidation(&self, key: &str, invalidation_type: InvalidationType) {
         match invalidation_type {
             InvalidationType::Key => {
@@ -931,7 +987,9 @@ impl CacheManager {
         let key = key.to_string();
 
         // L1: Local Cache
-        if let Some(val) = self.local.get_raw(&key) {
+        let local = self.local.get_raw(&key);
+        self.record_local_lookup(local.is_some());
+        if let Some(val) = local {
             if let Ok(result) = serde_json::from_str(&val) {
                 return Ok(Some(result));
             }
@@ -969,7 +1027,9 @@ impl CacheManager {
 
         // L1: Local Cache - check all keys first
         for (i, key) in keys.iter().enumerate() {
-            if let Some(val) = self.local.get_raw(key) {
+            let local = self.local.get_raw(key);
+            self.record_local_lookup(local.is_some());
+            if let Some(val) = local {
                 if let Ok(result) = serde_json::from_str::<T>(&val) {
                     results.push(Some(result));
                     continue;
@@ -1118,6 +1178,38 @@ impl CacheManager {
         }
     }
 
+    fn record_local_lookup(&self, hit: bool) {
+        let mut metrics = self.local_metrics.write();
+        if hit {
+            metrics.record_local_hit();
+        } else {
+            metrics.record_local_miss();
+        }
+    }
+
+    /// L1 and Redis hit/miss counters together with Redis fallbacks and
+    /// circuit-breaker rejections.
+    pub fn degradation_metrics(&self) -> DegradationMetrics {
+        let mut metrics = self.local_metrics.read().clone();
+        if let Some(redis) = &self.redis {
+            let remote = redis.get_degradation_metrics();
+            metrics.redis_cache_hits = remote.redis_cache_hits;
+            metrics.redis_cache_misses = remote.redis_cache_misses;
+            metrics.circuit_breaker_rejections = remote.circuit_breaker_rejections;
+            metrics.fallback_operations = remote.fallback_operations;
+            metrics.total_degraded_requests = remote.total_degraded_requests;
+        }
+        metrics
+    }
+
+    /// State and counters of the Redis circuit breaker, if Redis is configured.
+    pub fn circuit_breaker_metrics(&self) -> Option<(CircuitState, CircuitBreakerMetrics)> {
+        self.redis.as_ref().map(|redis| {
+            let breaker = redis.get_circuit_breaker();
+            (breaker.current_state(), breaker.get_metrics())
+        })
+    }
+
     /// Returns `true` when the cache manager has a working Redis backend.
     ///
     /// This is used by the rate-limit layer to decide whether token-bucket
@@ -1162,25 +1254,35 @@ impl CacheManager {
             }
         }
 
-        let mut map = self.rate_limit_local.lock();
-        let state = map.get(key).copied().unwrap_or(LocalRateLimitState { tokens: burst_size as f64, last_ms: now_ms });
-
-        let delta_ms = now_ms.saturating_sub(state.last_ms);
-        let refill = (delta_ms as f64 / 1000.0) * (rate_per_second as f64);
-        let mut tokens = (state.tokens + refill).min(burst_size as f64);
-        let allowed = tokens >= 1.0;
-        let retry_after_seconds = if allowed || rate_per_second == 0 {
-            0
-        } else {
-            ((1.0 - tokens) / (rate_per_second as f64)).ceil().max(1.0) as u64
-        };
-        if allowed {
-            tokens -= 1.0;
-        }
+        // Buckets are updated under moka's per-key lock, so unrelated keys never
+        // contend, and a bucket idle for `ttl_seconds` is evicted (it would have
+        // refilled to `burst_size` by then anyway).
+        let mut decision = RateLimitDecision { allowed: false, retry_after_seconds: 0, remaining: 0 };
+        self.rate_limit_local.entry_by_ref(key).and_upsert_with(|entry| {
+            let state = entry.map(|entry| entry.into_value()).unwrap_or(LocalRateLimitState {
+                tokens: burst_size as f64,
+                last_ms: now_ms,
+                ttl_seconds,
+            });
+
+            let delta_ms = now_ms.saturating_sub(state.last_ms);
+            let refill = (delta_ms as f64 / 1000.0) * (rate_per_second as f64);
+            let mut tokens = (state.tokens + refill).min(burst_size as f64);
+            let allowed = tokens >= 1.0;
+            let retry_after_seconds = if allowed || rate_per_second == 0 {
+                0
+            } else {
+                ((1.0 - tokens) / (rate_per_second as f64)).ceil().max(1.0) as u64
+            };
+            if allowed {
+                tokens -= 1.0;
+            }
 
-        map.insert(key.to_string(), LocalRateLimitState { tokens, last_ms: now_ms });
+            decision = RateLimitDecision { allowed, retry_after_seconds, remaining: tokens.floor().max(0.0) as u32 };
+            LocalRateLimitState { tokens, last_ms: now_ms, ttl_seconds }
+        });
 
-        Ok(RateLimitDecision { allowed, retry_after_seconds, remaining: tokens.floor().max(0.0) as u32 })
+        Ok(decision)
     }
 }
 
@@ -1191,10 +1293,42 @@ pub struct RateLimitDecision {
     pub remaining: u32,
 }
 
+/// Upper bound on process-local token buckets kept while Redis is unavailable.
+const LOCAL_RATE_LIMIT_MAX_BUCKETS: u64 = 1_000_000;
+
 #[derive(Clone, Copy, Debug)]
 struct LocalRateLimitState {
     tokens: f64,
     last_ms: u64,
+    ttl_seconds: u64,
+}
+
+/// Expires each bucket `ttl_seconds` after its last update.
+struct LocalRateLimitExpiry;
+
+impl moka::Expiry<String, LocalRateLimitState> for LocalRateLimitExpiry {
+    fn expire_after_create(
+        &self,
+        _key: &String,
+        value: &LocalRateLimitState,
+        _created_at: std::time::Instant,
+    ) -> Option<Duration> {
+        Some(Duration::from_secs(value.ttl_seconds))
+    }
+
+    fn expire_after_update(
+        &self,
+        _key: &String,
+        value: &LocalRateLimitState,
+        _updated_at: std::time::Instant,
+        _duration_until_expiry: Option<Duration>,
+    ) -> Option<Duration> {
+        Some(Duration::from_secs(value.ttl_seconds))
+    }
+}
+
+fn local_rate_limit_cache() -> Cache<String, LocalRateLimitState> {
+    Cache::builder().max_capacity(LOCAL_RATE_LIMIT_MAX_BUCKETS).expire_after(LocalRateLimitExpiry).build()
 }
 
 #[cfg(test)]
@@ -1281,6 +1415,32 @@ mod tests {
         assert!(manager.get::<String>("test_key").await.unwrap().is_none());
     }
 
+    #[tokio::test]
+    async fn test_cache_manager_take_returns_value_once() {
+        let manager = CacheManager::new(&CacheConfig::default());
+        let _ = manager.set("single_use", 42_i64, 60).await;
+
+        assert_eq!(manager.take::<i64>("single_use").await.unwrap(), Some(42));
+        assert_eq!(manager.take::<i64>("single_use").await.unwrap(), None);
+        assert!(manager.get::<i64>("single_use").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_cache_manager_records_local_hits_and_misses() {
+        let manager = CacheManager::new(&CacheConfig::default());
+        let _ = manager.set("test_key", &"test_value".to_string(), 60).await;
+
+        let _ = manager.get::<String>("test_key").await;
+        let _ = manager.get::<String>("nonexistent").await;
+        let _ = manager.get_batch::<String>(&["test_key".to_string(), "other".to_string()]).await;
+
+        let metrics = manager.degradation_metrics();
+        assert_eq!(metrics.local_cache_hits, 2);
+        assert_eq!(metrics.local_cache_misses, 2);
+        assert!((metrics.hit_rate() - 50.0).abs() < f64::EPSILON);
+        assert!(manager.circuit_breaker_metrics().is_none());
+    }
+
     #[tokio::test]
     async fn test_cache_manager_get_nonexistent() {
         let config = CacheConfig::default();
@@ -1290,6 +1450,22 @@ mod tests {
         assert!(result.is_none());
     }
 
+    #[tokio::test]
+    async fn test_local_token_bucket_limits_each_key_independently() {
+        let manager = CacheManager::new(&CacheConfig::default());
+
+        for _ in 0..2 {
+            assert!(manager.rate_limit_token_bucket_take("rl:a", 1, 2).await.unwrap().allowed);
+        }
+        let denied = manager.rate_limit_token_bucket_take("rl:a", 1, 2).await.unwrap();
+        assert!(!denied.allowed);
+        assert_eq!(denied.retry_after_seconds, 1);
+
+        let other = manager.rate_limit_token_bucket_take("rl:b", 1, 2).await.unwrap();
+        assert!(other.allowed);
+        assert_eq!(other.remaining, 1);
+    }
+
     #[tokio::test]
     async fn test_cache_manager_token_operations() {
         let config = CacheConfig::default();
@@ -1312,6 +1488,7 @@ mod tests {
         let result = manager.get_token("test_token").await;
         assert!(result.is_some());
         assert_eq!(result.unwrap().user_id, "@test:example.com");
+        assert!(manager.get_raw("test_token").is_none());
 
         manager.delete_token("test_token").await;
         let result = manager.get_token("test_token").await;
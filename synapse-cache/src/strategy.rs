@@ -35,8 +35,9 @@ impl CacheKeyBuilder {
         format!("room:{room_id}:messages")
     }
 
+    /// Keyed by the token's hash so no cache tier holds a usable bearer token.
     pub fn token(token: &str) -> String {
-        format!("token:{token}")
+        format!("token:{}", synapse_common::crypto::hash_token(token))
     }
 
     pub fn public_rooms() -> String {
@@ -182,7 +183,8 @@ mod tests {
     #[test]
     fn test_cache_key_token() {
         let key = CacheKeyBuilder::token("abc123");
-        assert_eq!(key, "token:abc123");
+        assert_eq!(key, format!("token:{}", synapse_common::crypto::hash_token("abc123")));
+        assert!(!key.contains("abc123"));
     }
 
     #[test]
@@ -0,0 +1,324 @@
+//! Transparent encryption of sensitive database columns.
+//!
+//! Values are sealed with AES-256-GCM and stored as
+//! `encc:<key_id>:<base64(nonce || ciphertext || tag)>`. The column name is
+//! bound as associated data, so a value copied into another column does not
+//! open. Stored values without the prefix are returned unchanged: rows
+//! written before encryption was enabled keep working until the re-encryption
+//! job seals them, and rows under a retired key are moved to the active key
+//! by the same job.
+
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+use aes_gcm::aead::{Aead, KeyInit, OsRng, Payload};
+use aes_gcm::{AeadCore, Aes256Gcm, Nonce};
+use base64::Engine;
+use serde_json::Value;
+use thiserror::Error;
+
+use crate::config::ColumnEncryptionConfig;
+
+static GLOBAL_COLUMN_CIPHER: OnceLock<ColumnCipher> = OnceLock::new();
+
+const PREFIX: &str = "encc:";
+const NONCE_SIZE: usize = 12;
+const KEY_DERIVATION_INFO: &[u8] = b"synapse-rust-column-encryption-v1";
+
+/// Names of the encrypted columns, used as associated data.
+pub mod columns {
+    pub const MEGOLM_SESSION_KEY: &str = "megolm_sessions.session_key";
+    pub const MEGOLM_VODOZEMAC_PICKLE: &str = "megolm_sessions.vodozemac_pickle";
+    pub const KEY_BACKUP_AUTH_DATA: &str = "key_backups.auth_data";
+    pub const SAML_SESSION_ATTRIBUTES: &str = "saml_sessions.attributes";
+    pub const SAML_USER_MAPPING_ATTRIBUTES: &str = "saml_user_mapping.attributes";
+    pub const SAML_AUTH_EVENT_ATTRIBUTES: &str = "saml_auth_events.attributes";
+    pub const CAS_USER_ATTRIBUTE_VALUE: &str = "cas_user_attributes.attribute_value";
+}
+
+#[derive(Debug, Error)]
+pub enum ColumnEncryptionError {
+    #[error("Invalid column encryption config: {0}")]
+    InvalidConfig(String),
+
+    #[error("Column value is sealed with unknown key '{0}'")]
+    UnknownKey(String),
+
+    #[error("Malformed encrypted column value")]
+    Malformed,
+
+    #[error("Failed to encrypt column value")]
+    EncryptionFailed,
+
+    #[error("Failed to decrypt column value")]
+    DecryptionFailed,
+}
+
+pub struct ColumnCipher {
+    active_key_id: Option<String>,
+    keys: HashMap<String, [u8; 32]>,
+}
+
+impl ColumnCipher {
+    /// A cipher that stores new values in plaintext and opens nothing.
+    pub fn disabled() -> Self {
+        Self { active_key_id: None, keys: HashMap::new() }
+    }
+
+    /// Builds the cipher from config. Listed keys are loaded even while
+    /// `enabled` is false so values written earlier can still be read.
+    pub fn from_config(config: &ColumnEncryptionConfig) -> Result<Self, ColumnEncryptionError> {
+        let mut keys = HashMap::with_capacity(config.keys.len());
+        for (key_id, secret) in &config.keys {
+            if !is_valid_key_id(key_id) {
+                return Err(ColumnEncryptionError::InvalidConfig(format!("invalid key id '{key_id}'")));
+            }
+            if secret.len() < 32 {
+                return Err(ColumnEncryptionError::InvalidConfig(format!(
+                    "secret for key '{key_id}' must be at least 32 characters"
+                )));
+            }
+            keys.insert(key_id.clone(), derive_key(secret.as_bytes()));
+        }
+
+        let active_key_id = if config.enabled {
+            if !keys.contains_key(&config.active_key_id) {
+                return Err(ColumnEncryptionError::InvalidConfig(format!(
+                    "active_key_id '{}' is not listed in keys",
+                    config.active_key_id
+                )));
+            }
+            Some(config.active_key_id.clone())
+        } else {
+            None
+        };
+
+        Ok(Self { active_key_id, keys })
+    }
+
+    pub fn active_key_id(&self) -> Option<&str> {
+        self.active_key_id.as_deref()
+    }
+
+    /// `plaintext` sealed under the active key, or unchanged when disabled.
+    pub fn seal(&self, column: &str, plaintext: &str) -> Result<String, ColumnEncryptionError> {
+        let Some(key_id) = &self.active_key_id else {
+            return Ok(plaintext.to_string());
+        };
+        let cipher = self.cipher(key_id)?;
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = cipher
+            .encrypt(&nonce, Payload { msg: plaintext.as_bytes(), aad: column.as_bytes() })
+            .map_err(|_| ColumnEncryptionError::EncryptionFailed)?;
+
+        let mut combined = Vec::with_capacity(NONCE_SIZE + ciphertext.len());
+        combined.extend_from_slice(&nonce);
+        combined.extend_from_slice(&ciphertext);
+        Ok(format!("{PREFIX}{key_id}:{}", base64::engine::general_purpose::STANDARD.encode(combined)))
+    }
+
+    /// The plaintext of a stored value; values that were never sealed are
+    /// returned as they are.
+    pub fn open(&self, column: &str, stored: &str) -> Result<String, ColumnEncryptionError> {
+        let Some(sealed) = stored.strip_prefix(PREFIX) else {
+            return Ok(stored.to_string());
+        };
+        let (key_id, encoded) = sealed.split_once(':').ok_or(ColumnEncryptionError::Malformed)?;
+        let combined =
+            base64::engine::general_purpose::STANDARD.decode(encoded).map_err(|_| ColumnEncryptionError::Malformed)?;
+        if combined.len() < NONCE_SIZE {
+            return Err(ColumnEncryptionError::Malformed);
+        }
+
+        let (nonce, ciphertext) = combined.split_at(NONCE_SIZE);
+        let plaintext = self
+            .cipher(key_id)?
+            .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: column.as_bytes() })
+            .map_err(|_| ColumnEncryptionError::DecryptionFailed)?;
+        String::from_utf8(plaintext).map_err(|_| ColumnEncryptionError::DecryptionFailed)
+    }
+
+    /// A JSON column value sealed into a JSON string, or unchanged when
+    /// disabled.
+    pub fn seal_json(&self, column: &str, value: &Value) -> Result<Value, ColumnEncryptionError> {
+        if self.active_key_id.is_none() {
+            return Ok(value.clone());
+        }
+        let plaintext = serde_json::to_string(value).map_err(|_| ColumnEncryptionError::EncryptionFailed)?;
+        Ok(Value::String(self.seal(column, &plaintext)?))
+    }
+
+    /// The JSON value behind a column written by [`Self::seal_json`].
+    pub fn open_json(&self, column: &str, stored: Value) -> Result<Value, ColumnEncryptionError> {
+        match stored {
+            Value::String(sealed) if sealed.starts_with(PREFIX) => {
+                serde_json::from_str(&self.open(column, &sealed)?).map_err(|_| ColumnEncryptionError::Malformed)
+            }
+            other => Ok(other),
+        }
+    }
+
+    /// Whether `stored` is in plaintext or under a key other than the
+    /// active one. Always false while disabled.
+    pub fn needs_reencryption(&self, stored: &str) -> bool {
+        match &self.active_key_id {
+            Some(active) => stored_key_id(stored) != Some(active.as_str()),
+            None => false,
+        }
+    }
+
+    fn cipher(&self, key_id: &str) -> Result<Aes256Gcm, ColumnEncryptionError> {
+        let key = self.keys.get(key_id).ok_or_else(|| ColumnEncryptionError::UnknownKey(key_id.to_string()))?;
+        Aes256Gcm::new_from_slice(key).map_err(|_| ColumnEncryptionError::InvalidConfig("bad key length".into()))
+    }
+}
+
+/// Installs the process-wide cipher. Later calls are ignored.
+pub fn initialize_global(config: &ColumnEncryptionConfig) -> Result<(), ColumnEncryptionError> {
+    let cipher = ColumnCipher::from_config(config)?;
+    let _ = GLOBAL_COLUMN_CIPHER.set(cipher);
+    Ok(())
+}
+
+/// The process-wide cipher; disabled until [`initialize_global`] runs.
+pub fn global() -> &'static ColumnCipher {
+    GLOBAL_COLUMN_CIPHER.get_or_init(ColumnCipher::disabled)
+}
+
+pub fn seal(column: &str, plaintext: &str) -> Result<String, ColumnEncryptionError> {
+    global().seal(column, plaintext)
+}
+
+pub fn open(column: &str, stored: &str) -> Result<String, ColumnEncryptionError> {
+    global().open(column, stored)
+}
+
+pub fn seal_json(column: &str, value: &Value) -> Result<Value, ColumnEncryptionError> {
+    global().seal_json(column, value)
+}
+
+pub fn open_json(column: &str, stored: Value) -> Result<Value, ColumnEncryptionError> {
+    global().open_json(column, stored)
+}
+
+/// The key id a stored value is sealed under, if it is sealed.
+pub fn stored_key_id(stored: &str) -> Option<&str> {
+    stored.strip_prefix(PREFIX).and_then(|sealed| sealed.split_once(':')).map(|(key_id, _)| key_id)
+}
+
+/// A `LIKE` pattern matching values sealed under `key_id`.
+pub fn sealed_like_pattern(key_id: &str) -> String {
+    format!("{PREFIX}{}:%", key_id.replace('_', "\\_"))
+}
+
+fn is_valid_key_id(key_id: &str) -> bool {
+    !key_id.is_empty() && key_id.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+#[allow(clippy::expect_used)]
+fn derive_key(secret: &[u8]) -> [u8; 32] {
+    let hk = hkdf::Hkdf::<sha2::Sha256>::new(None, secret);
+    let mut okm = [0u8; 32];
+    // 32 bytes is well under the HKDF-SHA256 output limit.
+    hk.expand(KEY_DERIVATION_INFO, &mut okm).expect("32 bytes is valid HKDF output length");
+    okm
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const OLD_SECRET: &str = "old-column-secret-0123456789abcdef";
+    const NEW_SECRET: &str = "new-column-secret-0123456789abcdef";
+
+    fn cipher(active: &str, keys: &[(&str, &str)]) -> ColumnCipher {
+        ColumnCipher::from_config(&ColumnEncryptionConfig {
+            enabled: true,
+            active_key_id: active.to_string(),
+            keys: keys.iter().map(|(id, secret)| (id.to_string(), secret.to_string())).collect(),
+            ..ColumnEncryptionConfig::default()
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn sealed_values_open_and_are_tagged_with_the_key_id() {
+        let cipher = cipher("k1", &[("k1", OLD_SECRET)]);
+        let sealed = cipher.seal(columns::MEGOLM_SESSION_KEY, "pickle").unwrap();
+
+        assert_eq!(stored_key_id(&sealed), Some("k1"));
+        assert!(!cipher.needs_reencryption(&sealed));
+        assert_eq!(cipher.open(columns::MEGOLM_SESSION_KEY, &sealed).unwrap(), "pickle");
+    }
+
+    #[test]
+    fn values_do_not_open_under_another_column() {
+        let cipher = cipher("k1", &[("k1", OLD_SECRET)]);
+        let sealed = cipher.seal(columns::MEGOLM_SESSION_KEY, "pickle").unwrap();
+
+        assert!(matches!(
+            cipher.open(columns::MEGOLM_VODOZEMAC_PICKLE, &sealed),
+            Err(ColumnEncryptionError::DecryptionFailed)
+        ));
+    }
+
+    #[test]
+    fn rotation_keeps_old_values_readable_until_resealed() {
+        let old = cipher("k1", &[("k1", OLD_SECRET)]);
+        let sealed = old.seal_json(columns::KEY_BACKUP_AUTH_DATA, &json!({ "public_key": "abc" })).unwrap();
+
+        let rotated = cipher("k2", &[("k1", OLD_SECRET), ("k2", NEW_SECRET)]);
+        assert!(rotated.needs_reencryption(sealed.as_str().unwrap()));
+        let value = rotated.open_json(columns::KEY_BACKUP_AUTH_DATA, sealed).unwrap();
+        assert_eq!(value, json!({ "public_key": "abc" }));
+
+        let resealed = rotated.seal_json(columns::KEY_BACKUP_AUTH_DATA, &value).unwrap();
+        assert_eq!(stored_key_id(resealed.as_str().unwrap()), Some("k2"));
+
+        let retired = cipher("k2", &[("k2", NEW_SECRET)]);
+        assert!(matches!(
+            retired.open(columns::KEY_BACKUP_AUTH_DATA, &old.seal(columns::KEY_BACKUP_AUTH_DATA, "x").unwrap()),
+            Err(ColumnEncryptionError::UnknownKey(_))
+        ));
+    }
+
+    #[test]
+    fn plaintext_passes_through() {
+        let cipher = cipher("k1", &[("k1", OLD_SECRET)]);
+        assert_eq!(cipher.open(columns::CAS_USER_ATTRIBUTE_VALUE, "alice@example.com").unwrap(), "alice@example.com");
+        assert_eq!(
+            cipher.open_json(columns::SAML_SESSION_ATTRIBUTES, json!({ "mail": ["a"] })).unwrap(),
+            json!({ "mail": ["a"] })
+        );
+        assert!(cipher.needs_reencryption("alice@example.com"));
+
+        let disabled = ColumnCipher::disabled();
+        assert_eq!(disabled.seal(columns::CAS_USER_ATTRIBUTE_VALUE, "plain").unwrap(), "plain");
+        assert!(!disabled.needs_reencryption("plain"));
+    }
+
+    #[test]
+    fn config_must_name_a_listed_active_key() {
+        let config = ColumnEncryptionConfig {
+            enabled: true,
+            active_key_id: "missing".to_string(),
+            keys: HashMap::from([("k1".to_string(), OLD_SECRET.to_string())]),
+            ..ColumnEncryptionConfig::default()
+        };
+        assert!(matches!(ColumnCipher::from_config(&config), Err(ColumnEncryptionError::InvalidConfig(_))));
+
+        let config = ColumnEncryptionConfig {
+            enabled: true,
+            active_key_id: "k:1".to_string(),
+            keys: HashMap::from([("k:1".to_string(), OLD_SECRET.to_string())]),
+            ..ColumnEncryptionConfig::default()
+        };
+        assert!(matches!(ColumnCipher::from_config(&config), Err(ColumnEncryptionError::InvalidConfig(_))));
+    }
+
+    #[test]
+    fn like_pattern_escapes_underscores() {
+        assert_eq!(sealed_like_pattern("key_2026"), "encc:key\\_2026:%");
+    }
+}
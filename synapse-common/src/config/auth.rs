@@ -1,7 +1,8 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 // ============================================================================
-// SECTION: Authentication (OIDC, SAML)
+// SECTION: Authentication (OIDC, SAML, LDAP)
 // ============================================================================
 
 /// OpenID Connect configuration.
@@ -181,6 +182,11 @@ pub struct SamlConfig {
     pub timeout: u64,
 }
 
+/// How SAML assertion attributes become a Matrix user.
+///
+/// `user_id_template` on [`SamlConfig`] renders the localpart: `{uid}` is
+/// the value of the `uid` attribute, `{name_id}` the NameID, and any other
+/// `{name}` the assertion attribute of that name.
 #[derive(Debug, Clone, Deserialize, Default)]
 pub struct SamlAttributeMapping {
     /// Username attribute
@@ -189,6 +195,32 @@ pub struct SamlAttributeMapping {
     pub displayname: Option<String>,
     /// Email attribute
     pub email: Option<String>,
+    /// Display name built from several attributes, e.g. `"{givenName} {sn}"`.
+    /// Takes precedence over `displayname`.
+    #[serde(default)]
+    pub displayname_template: Option<String>,
+    /// Attributes a login must carry: `null` only requires the attribute,
+    /// a string requires one of its values to match.
+    #[serde(default)]
+    pub required_attributes: HashMap<String, Option<String>>,
+    /// On the first login of an identity, link it to the local account with
+    /// the same verified email address instead of creating a new one.
+    #[serde(default)]
+    pub grandfather_by_email: bool,
+    /// What to do when the mapped localpart belongs to an account that is not
+    /// linked to this identity.
+    #[serde(default)]
+    pub on_collision: SsoCollisionPolicy,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SsoCollisionPolicy {
+    /// Refuse the login.
+    #[default]
+    Reject,
+    /// Use the first free localpart of `alice1`, `alice2`, ...
+    AppendNumber,
 }
 
 fn default_saml_sp_entity_id() -> String {
@@ -277,6 +309,191 @@ impl SamlConfig {
     pub fn get_sp_sls_url(&self, server_name: &str) -> Option<String> {
         self.sp_sls_url.clone().or_else(|| Some(format!("https://{server_name}/_matrix/client/r0/logout/saml")))
     }
+
+    /// Startup check of the user mapping, so a broken template fails the
+    /// boot instead of every SAML login.
+    pub fn validate_user_mapping(&self) -> Result<(), String> {
+        let mapping = &self.attribute_mapping;
+        if !self.use_name_id_for_user_id && mapping.uid.is_some() {
+            let names = crate::sso_mapping::template_placeholders(&self.user_id_template)
+                .map_err(|e| format!("user_id_template: {e}"))?;
+            if names.is_empty() {
+                return Err("user_id_template must contain a placeholder such as {uid}; \
+                     otherwise every user maps to the same localpart"
+                    .to_string());
+            }
+        }
+        if let Some(template) = &mapping.displayname_template {
+            crate::sso_mapping::template_placeholders(template)
+                .map_err(|e| format!("attribute_mapping.displayname_template: {e}"))?;
+        }
+        if mapping.required_attributes.keys().any(|name| name.trim().is_empty()) {
+            return Err("attribute_mapping.required_attributes has an empty attribute name".to_string());
+        }
+        if mapping.grandfather_by_email && mapping.email.is_none() {
+            return Err("attribute_mapping.grandfather_by_email needs attribute_mapping.email".to_string());
+        }
+        if mapping.grandfather_by_email && !self.allow_existing_users {
+            return Err("attribute_mapping.grandfather_by_email needs allow_existing_users".to_string());
+        }
+        Ok(())
+    }
+}
+
+/// LDAP password provider.
+///
+/// Logins are verified by binding to the directory as the user. With
+/// `bind_dn` set, the user's entry is first located by searching `base` for
+/// `(<attributes.uid>=<localpart>)` ANDed with `filter`; without it the DN
+/// is assumed to be `<attributes.uid>=<localpart>,<base>`.
+///
+/// ```yaml
+/// ldap:
+///   enabled: true
+///   uris: ["ldaps://ldap1.example.com", "ldaps://ldap2.example.com"]
+///   base: "ou=people,dc=example,dc=com"
+///   bind_dn: "cn=matrix,ou=services,dc=example,dc=com"
+///   bind_password: "${LDAP_BIND_PASSWORD}"
+///   filter: "(objectClass=inetOrgPerson)"
+///   admin_groups: ["cn=matrix-admins,ou=groups,dc=example,dc=com"]
+///   fallback: ldap_first
+/// ```
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapConfig {
+    #[serde(default)]
+    pub enabled: bool,
+    /// Servers tried in order; the next one is used when a server cannot
+    /// be reached.
+    #[serde(default)]
+    pub uris: Vec<String>,
+    /// Upgrade `ldap://` connections with StartTLS.
+    #[serde(default)]
+    pub start_tls: bool,
+    #[serde(default)]
+    pub base: String,
+    /// Service account used to search for the user's DN.
+    #[serde(default)]
+    pub bind_dn: Option<String>,
+    #[serde(default)]
+    pub bind_password: Option<String>,
+    /// Extra search filter ANDed with the uid match, e.g. `(memberOf=...)`.
+    #[serde(default)]
+    pub filter: Option<String>,
+    #[serde(default)]
+    pub attributes: LdapAttributes,
+    /// Create a local account on the first successful LDAP login.
+    #[serde(default = "default_ldap_auto_provision")]
+    pub auto_provision: bool,
+    /// Group DNs whose members are server admins. When non-empty the admin
+    /// flag is synced from the directory on every LDAP login.
+    #[serde(default)]
+    pub admin_groups: Vec<String>,
+    #[serde(default)]
+    pub fallback: LdapFallback,
+    /// Connect and operation timeout (seconds)
+    #[serde(default = "default_ldap_timeout")]
+    pub timeout: u64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct LdapAttributes {
+    #[serde(default = "default_ldap_uid_attribute")]
+    pub uid: String,
+    #[serde(default = "default_ldap_name_attribute")]
+    pub name: String,
+    /// Multi-valued attribute listing the user's group DNs.
+    #[serde(default = "default_ldap_groups_attribute")]
+    pub groups: String,
+}
+
+/// Order in which LDAP and the local password database are consulted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LdapFallback {
+    /// Try LDAP, then the local password when LDAP rejects the login or is
+    /// unreachable.
+    #[default]
+    LdapFirst,
+    /// Try the local password, then LDAP.
+    LocalFirst,
+    /// Only LDAP; local password hashes are ignored.
+    LdapOnly,
+}
+
+fn default_ldap_auto_provision() -> bool {
+    true
+}
+
+fn default_ldap_timeout() -> u64 {
+    10
+}
+
+fn default_ldap_uid_attribute() -> String {
+    "uid".to_string()
+}
+
+fn default_ldap_name_attribute() -> String {
+    "cn".to_string()
+}
+
+fn default_ldap_groups_attribute() -> String {
+    "memberOf".to_string()
+}
+
+impl Default for LdapAttributes {
+    fn default() -> Self {
+        Self {
+            uid: default_ldap_uid_attribute(),
+            name: default_ldap_name_attribute(),
+            groups: default_ldap_groups_attribute(),
+        }
+    }
+}
+
+impl Default for LdapConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            uris: Vec::new(),
+            start_tls: false,
+            base: String::new(),
+            bind_dn: None,
+            bind_password: None,
+            filter: None,
+            attributes: LdapAttributes::default(),
+            auto_provision: default_ldap_auto_provision(),
+            admin_groups: Vec::new(),
+            fallback: LdapFallback::default(),
+            timeout: default_ldap_timeout(),
+        }
+    }
+}
+
+impl LdapConfig {
+    pub fn is_enabled(&self) -> bool {
+        self.enabled && !self.uris.is_empty()
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.uris.is_empty() {
+            return Err("uris must list at least one server".to_string());
+        }
+        if let Some(uri) = self.uris.iter().find(|u| !u.starts_with("ldap://") && !u.starts_with("ldaps://")) {
+            return Err(format!("'{uri}' is not an ldap:// or ldaps:// URI"));
+        }
+        if self.base.trim().is_empty() {
+            return Err("base must be set".to_string());
+        }
+        if self.bind_dn.is_some() != self.bind_password.is_some() {
+            return Err("bind_dn and bind_password must be set together".to_string());
+        }
+        if let Some(filter) = &self.filter {
+            if !(filter.starts_with('(') && filter.ends_with(')')) {
+                return Err("filter must be a parenthesised LDAP filter".to_string());
+            }
+        }
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -401,4 +618,49 @@ mod tests {
             Some("https://matrix.example.com/_matrix/client/r0/logout/saml".into())
         );
     }
+
+    // ── SamlConfig::validate_user_mapping ──────────────────────────────
+
+    #[test]
+    fn saml_user_mapping_validation_rejects_broken_rules() {
+        let mut config = SamlConfig::default();
+        config.attribute_mapping.uid = Some("uid".into());
+        assert!(config.validate_user_mapping().is_ok());
+
+        config.user_id_template = "{uid".into();
+        assert!(config.validate_user_mapping().is_err());
+        config.user_id_template = "everyone".into();
+        assert!(config.validate_user_mapping().is_err());
+        config.user_id_template = "{givenName}.{sn}".into();
+        assert!(config.validate_user_mapping().is_ok());
+
+        config.attribute_mapping.grandfather_by_email = true;
+        assert!(config.validate_user_mapping().is_err());
+        config.attribute_mapping.email = Some("mail".into());
+        assert!(config.validate_user_mapping().is_ok());
+    }
+
+    // ── LdapConfig ─────────────────────────────────────────────────────
+
+    #[test]
+    fn ldap_config_defaults_and_validation() {
+        let mut config: LdapConfig =
+            serde_yaml::from_str("enabled: true\nuris: [\"ldaps://ldap.example.com\"]\nbase: \"dc=example,dc=com\"")
+                .unwrap();
+        assert!(config.is_enabled());
+        assert_eq!(config.fallback, LdapFallback::LdapFirst);
+        assert_eq!(config.attributes.uid, "uid");
+        assert!(config.validate().is_ok());
+
+        config.bind_dn = Some("cn=matrix,dc=example,dc=com".into());
+        assert!(config.validate().is_err());
+        config.bind_password = Some("secret".into());
+        assert!(config.validate().is_ok());
+
+        config.filter = Some("objectClass=person".into());
+        assert!(config.validate().is_err());
+        config.filter = None;
+        config.uris.push("https://ldap.example.com".into());
+        assert!(config.validate().is_err());
+    }
 }
@@ -0,0 +1,75 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// ============================================================================
+// SECTION: Column Encryption Configuration
+// ============================================================================
+
+fn default_reencrypt_interval_secs() -> u64 {
+    3600
+}
+
+fn default_reencrypt_batch_size() -> u32 {
+    500
+}
+
+/// Application-level encryption of sensitive columns: key backup auth data,
+/// megolm session pickles, and SAML/CAS attributes.
+///
+/// Values are sealed with AES-256-GCM under `active_key_id` and tagged with
+/// the key id. To rotate, add a new key, point `active_key_id` at it and keep
+/// the old key listed until the re-encryption job has moved every row over.
+/// Secrets are stretched with HKDF, so any high-entropy string works; inject
+/// them from a secret store with `${ENV}` expansion rather than writing them
+/// into the file.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ColumnEncryptionConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Key used for new writes. Must be one of `keys`.
+    #[serde(default)]
+    pub active_key_id: String,
+
+    /// Key id to secret. Ids may use ASCII letters, digits, `-`, `_` and `.`.
+    #[serde(default)]
+    pub keys: HashMap<String, String>,
+
+    /// How often rows still in plaintext or under a retired key are
+    /// re-encrypted under the active key.
+    #[serde(default = "default_reencrypt_interval_secs")]
+    pub reencrypt_interval_secs: u64,
+
+    /// Rows fetched per query by the re-encryption job.
+    #[serde(default = "default_reencrypt_batch_size")]
+    pub reencrypt_batch_size: u32,
+}
+
+impl Default for ColumnEncryptionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            active_key_id: String::new(),
+            keys: HashMap::new(),
+            reencrypt_interval_secs: default_reencrypt_interval_secs(),
+            reencrypt_batch_size: default_reencrypt_batch_size(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_keys_and_defaults() {
+        let cfg: ColumnEncryptionConfig =
+            serde_yaml::from_str("enabled: true\nactive_key_id: k2\nkeys:\n  k1: old-secret\n  k2: new-secret\n")
+                .expect("column encryption YAML should deserialize");
+        assert!(cfg.enabled);
+        assert_eq!(cfg.active_key_id, "k2");
+        assert_eq!(cfg.keys.len(), 2);
+        assert_eq!(cfg.reencrypt_interval_secs, 3600);
+        assert_eq!(cfg.reencrypt_batch_size, 500);
+    }
+}
@@ -0,0 +1,131 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// ============================================================================
+// SECTION: Content Filter Configuration
+// ============================================================================
+
+fn default_content_filter_event_types() -> Vec<String> {
+    vec!["m.room.message".to_string()]
+}
+
+fn default_consult_spam_checkers() -> bool {
+    true
+}
+
+/// What happens to an event whose content matches a banned pattern.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ContentFilterAction {
+    /// Refuse the send with `M_FORBIDDEN`; nothing is persisted.
+    #[default]
+    Reject,
+    /// Persist the event with every match masked out of its string fields.
+    Redact,
+}
+
+/// Pre-send content filter configuration.
+///
+/// Patterns are case-insensitive regular expressions matched against the
+/// string values of an event's content before it is persisted. Server-wide
+/// `banned_patterns` apply to every room except those in `exempt_rooms`;
+/// `room_patterns` adds extra patterns for individual rooms.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ContentFilterConfig {
+    /// Whether pre-send content filtering is enabled.
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Action taken when a banned pattern matches.
+    #[serde(default)]
+    pub action: ContentFilterAction,
+
+    /// Server-wide banned patterns.
+    #[serde(default)]
+    pub banned_patterns: Vec<String>,
+
+    /// Additional banned patterns keyed by room ID.
+    #[serde(default)]
+    pub room_patterns: HashMap<String, Vec<String>>,
+
+    /// Rooms that opt out of the server-wide patterns. Their own
+    /// `room_patterns` entries still apply.
+    #[serde(default)]
+    pub exempt_rooms: Vec<String>,
+
+    /// Event types the filter inspects.
+    #[serde(default = "default_content_filter_event_types")]
+    pub event_types: Vec<String>,
+
+    /// Also run the spam checkers registered with the module system.
+    #[serde(default = "default_consult_spam_checkers")]
+    pub consult_spam_checkers: bool,
+}
+
+impl Default for ContentFilterConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            action: ContentFilterAction::default(),
+            banned_patterns: Vec::new(),
+            room_patterns: HashMap::new(),
+            exempt_rooms: Vec::new(),
+            event_types: default_content_filter_event_types(),
+            consult_spam_checkers: default_consult_spam_checkers(),
+        }
+    }
+}
+
+impl ContentFilterConfig {
+    /// Whether events of `event_type` should be inspected at all.
+    pub fn applies_to(&self, event_type: &str) -> bool {
+        self.enabled && self.event_types.iter().any(|t| t == event_type)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_empty_uses_defaults() {
+        let cfg: ContentFilterConfig = serde_yaml::from_str("{}\n").expect("empty YAML should deserialize");
+        assert!(!cfg.enabled);
+        assert_eq!(cfg.action, ContentFilterAction::Reject);
+        assert!(cfg.banned_patterns.is_empty());
+        assert!(cfg.room_patterns.is_empty());
+        assert!(cfg.exempt_rooms.is_empty());
+        assert_eq!(cfg.event_types, vec!["m.room.message".to_string()]);
+        assert!(cfg.consult_spam_checkers);
+    }
+
+    #[test]
+    fn deserialize_explicit_values() {
+        let yaml = "\
+enabled: true
+action: redact
+banned_patterns: ['bad\\s*word']
+room_patterns:
+  '!strict:example.com': ['heck']
+exempt_rooms: ['!lenient:example.com']
+event_types: ['m.room.message', 'm.sticker']
+consult_spam_checkers: false
+";
+        let cfg: ContentFilterConfig = serde_yaml::from_str(yaml).expect("explicit YAML should deserialize");
+        assert!(cfg.enabled);
+        assert_eq!(cfg.action, ContentFilterAction::Redact);
+        assert_eq!(cfg.banned_patterns, vec!["bad\\s*word".to_string()]);
+        assert_eq!(cfg.room_patterns["!strict:example.com"], vec!["heck".to_string()]);
+        assert_eq!(cfg.exempt_rooms, vec!["!lenient:example.com".to_string()]);
+        assert!(!cfg.consult_spam_checkers);
+    }
+
+    #[test]
+    fn applies_to_requires_enabled_and_listed_type() {
+        let mut cfg = ContentFilterConfig::default();
+        assert!(!cfg.applies_to("m.room.message"));
+        cfg.enabled = true;
+        assert!(cfg.applies_to("m.room.message"));
+        assert!(!cfg.applies_to("m.reaction"));
+    }
+}
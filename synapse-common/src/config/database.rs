@@ -1,4 +1,4 @@
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 // ============================================================================
 // SECTION: Database Configuration
@@ -25,6 +25,129 @@ pub struct DatabaseConfig {
     pub min_idle: Option<u32>,
     /// 连接超时时间（秒）
     pub connection_timeout: u64,
+    /// 启动时发现 Schema 漂移（缺少迁移声明的表、列或索引）时的处理策略
+    #[serde(default)]
+    pub schema_drift_policy: SchemaDriftPolicy,
+    /// 迁移脚本目录，用于推导期望的 Schema；未设置时使用工作目录下的 `migrations`
+    #[serde(default)]
+    pub migrations_dir: Option<String>,
+    /// events 表分区维护配置（分区转换由 `partition_events` 工具完成）
+    #[serde(default)]
+    pub event_partitioning: EventPartitioningConfig,
+    /// 历史状态组压缩任务配置
+    #[serde(default)]
+    pub state_compaction: StateCompactionConfig,
+}
+
+/// Schema 漂移处理策略。
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SchemaDriftPolicy {
+    /// 记录漂移报告后继续启动
+    #[default]
+    Warn,
+    /// 存在漂移时拒绝启动
+    Refuse,
+}
+
+/// events 表分区维护配置。
+///
+/// 仅在 events 已按 `origin_server_ts` 范围分区时生效；哈希分区无需维护。
+#[derive(Debug, Clone, Deserialize)]
+pub struct EventPartitioningConfig {
+    /// 是否在后台任务中维护分区
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每个范围分区覆盖的天数
+    #[serde(default = "default_partition_interval_days")]
+    pub range_interval_days: u32,
+    /// 预先创建的未来分区数量
+    #[serde(default = "default_premake_partitions")]
+    pub premake_partitions: u32,
+    /// 是否删除已被清理为空的历史分区
+    #[serde(default = "default_drop_empty_partitions")]
+    pub drop_empty_partitions: bool,
+}
+
+impl Default for EventPartitioningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            range_interval_days: default_partition_interval_days(),
+            premake_partitions: default_premake_partitions(),
+            drop_empty_partitions: default_drop_empty_partitions(),
+        }
+    }
+}
+
+fn default_partition_interval_days() -> u32 {
+    30
+}
+
+fn default_premake_partitions() -> u32 {
+    3
+}
+
+fn default_drop_empty_partitions() -> bool {
+    true
+}
+
+/// 历史状态组压缩任务配置。
+///
+/// 对早于 `min_age_days` 的状态组合并重复组、去除冗余条目，并将快照稀疏到每
+/// `archive_snapshot_interval` 个状态组一个，以减少 `state_group_state` 的行数。
+#[derive(Debug, Clone, Deserialize)]
+pub struct StateCompactionConfig {
+    /// 是否启用定时压缩
+    #[serde(default)]
+    pub enabled: bool,
+    /// 两次压缩之间的间隔（秒）
+    #[serde(default = "default_state_compaction_interval_secs")]
+    pub interval_secs: u64,
+    /// 仅压缩创建时间早于该天数的状态组
+    #[serde(default = "default_state_compaction_min_age_days")]
+    pub min_age_days: u32,
+    /// 压缩后相邻两个快照之间的状态组数量（100 到 800 之间）
+    #[serde(default = "default_archive_snapshot_interval")]
+    pub archive_snapshot_interval: i32,
+    /// 每次压缩处理的房间数量
+    #[serde(default = "default_state_compaction_rooms_per_run")]
+    pub rooms_per_run: i64,
+}
+
+impl Default for StateCompactionConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            interval_secs: default_state_compaction_interval_secs(),
+            min_age_days: default_state_compaction_min_age_days(),
+            archive_snapshot_interval: default_archive_snapshot_interval(),
+            rooms_per_run: default_state_compaction_rooms_per_run(),
+        }
+    }
+}
+
+fn default_state_compaction_interval_secs() -> u64 {
+    86400
+}
+
+fn default_state_compaction_min_age_days() -> u32 {
+    30
+}
+
+fn default_archive_snapshot_interval() -> i32 {
+    500
+}
+
+fn default_state_compaction_rooms_per_run() -> i64 {
+    100
+}
+
+impl DatabaseConfig {
+    /// 迁移脚本目录。
+    pub fn migrations_dir(&self) -> &str {
+        self.migrations_dir.as_deref().unwrap_or("migrations")
+    }
 }
 
 /// Redis 缓存配置。
@@ -0,0 +1,56 @@
+use serde::Deserialize;
+
+// ============================================================================
+// SECTION: Egress Policy Configuration
+// ============================================================================
+
+/// Address ranges the server refuses to contact on behalf of users or
+/// registered services: URL previews, push gateways, application services
+/// and identity servers.
+///
+/// A destination is blocked when any address its host resolves to falls in
+/// `ip_range_blacklist` and not in `ip_range_whitelist`. The default list
+/// covers loopback, RFC 1918, carrier-grade NAT, link-local (including cloud
+/// metadata endpoints such as 169.254.169.254) and IPv6 unique-local ranges.
+/// Application services on localhost or a private network need their
+/// address added to `ip_range_whitelist`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EgressConfig {
+    /// CIDR ranges or single IP addresses to refuse.
+    #[serde(default = "default_egress_ip_blacklist")]
+    pub ip_range_blacklist: Vec<String>,
+
+    /// CIDR ranges or single IP addresses allowed even when blacklisted.
+    #[serde(default)]
+    pub ip_range_whitelist: Vec<String>,
+}
+
+impl Default for EgressConfig {
+    fn default() -> Self {
+        Self { ip_range_blacklist: default_egress_ip_blacklist(), ip_range_whitelist: Vec::new() }
+    }
+}
+
+fn default_egress_ip_blacklist() -> Vec<String> {
+    [
+        "0.0.0.0/8",
+        "10.0.0.0/8",
+        "100.64.0.0/10",
+        "127.0.0.0/8",
+        "169.254.0.0/16",
+        "172.16.0.0/12",
+        "192.0.0.0/24",
+        "192.168.0.0/16",
+        "198.18.0.0/15",
+        "224.0.0.0/4",
+        "240.0.0.0/4",
+        "::/128",
+        "::1/128",
+        "fc00::/7",
+        "fe80::/10",
+        "ff00::/8",
+    ]
+    .into_iter()
+    .map(str::to_string)
+    .collect()
+}
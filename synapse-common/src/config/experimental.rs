@@ -27,6 +27,44 @@ pub struct ExperimentalConfig {
     /// the default client. Defaults to `true` for backward compatibility.
     #[serde(default = "default_true")]
     pub declare_private_extensions: bool,
+
+    /// Experimental WebSocket sync transport.
+    ///
+    /// When enabled, `GET /_matrix/client/unstable/org.synapse_rust.sync_ws/sync`
+    /// accepts a WebSocket upgrade and pushes each incremental `/sync` response
+    /// down the socket as soon as it is available, and
+    /// `org.synapse_rust.sync_ws` is declared in `/versions`. The wire format
+    /// may change without notice.
+    #[serde(default)]
+    pub sync_websocket_enabled: bool,
+
+    /// Server-Sent Events variant of the legacy `/events` stream.
+    ///
+    /// When enabled, `GET /_matrix/client/unstable/org.synapse_rust.events_sse/events`
+    /// streams each event from `/events` as an SSE message, resuming from
+    /// `Last-Event-ID` on reconnect, and `org.synapse_rust.events_sse` is
+    /// declared in `/versions`.
+    #[serde(default)]
+    pub events_sse_enabled: bool,
+
+    /// MSC4108: sign in with a QR code.
+    ///
+    /// When enabled, the rendezvous endpoints under
+    /// `/_matrix/client/unstable/org.matrix.msc4108/rendezvous` relay the
+    /// secure channel between the new and the existing device, and
+    /// `org.matrix.msc4108` is declared in `/versions`.
+    #[serde(default)]
+    pub msc4108_enabled: bool,
+
+    /// MSC4140: delayed events, in milliseconds.
+    ///
+    /// When set, sending a message or state event with the
+    /// `org.matrix.msc4140.delay` query parameter schedules it instead of
+    /// sending it, the longest accepted delay is this value, and
+    /// `org.matrix.msc4140` is declared in `/versions`. Unset disables the
+    /// feature.
+    #[serde(default)]
+    pub msc4140_max_delay_ms: Option<u64>,
 }
 
 fn default_true() -> bool {
@@ -41,6 +79,10 @@ impl Default for ExperimentalConfig {
             openclaw_routes_enabled: true,
             msc4452_enabled: false,
             declare_private_extensions: true,
+            sync_websocket_enabled: false,
+            events_sse_enabled: false,
+            msc4108_enabled: false,
+            msc4140_max_delay_ms: None,
         }
     }
 }
@@ -54,6 +96,10 @@ mod tests {
         let cfg = ExperimentalConfig::default();
         assert!(!cfg.msc4452_enabled, "msc4452 should default to false");
         assert!(cfg.declare_private_extensions, "declare_private_extensions should default to true");
+        assert!(!cfg.sync_websocket_enabled, "sync_websocket should default to false");
+        assert!(!cfg.events_sse_enabled, "events_sse should default to false");
+        assert!(!cfg.msc4108_enabled, "msc4108 should default to false");
+        assert!(cfg.msc4140_max_delay_ms.is_none(), "msc4140 should default to disabled");
         #[cfg(feature = "openclaw-routes")]
         assert!(cfg.openclaw_routes_enabled, "openclaw_routes_enabled should default to true");
     }
@@ -106,10 +152,18 @@ mod tests {
             openclaw_routes_enabled: false,
             msc4452_enabled: true,
             declare_private_extensions: false,
+            sync_websocket_enabled: true,
+            events_sse_enabled: true,
+            msc4108_enabled: true,
+            msc4140_max_delay_ms: Some(86_400_000),
         };
         let cloned = cfg.clone();
         assert_eq!(cfg.msc4452_enabled, cloned.msc4452_enabled);
         assert_eq!(cfg.declare_private_extensions, cloned.declare_private_extensions);
+        assert_eq!(cfg.sync_websocket_enabled, cloned.sync_websocket_enabled);
+        assert_eq!(cfg.events_sse_enabled, cloned.events_sse_enabled);
+        assert_eq!(cfg.msc4108_enabled, cloned.msc4108_enabled);
+        assert_eq!(cfg.msc4140_max_delay_ms, cloned.msc4140_max_delay_ms);
         #[cfg(feature = "openclaw-routes")]
         assert_eq!(cfg.openclaw_routes_enabled, cloned.openclaw_routes_enabled);
     }
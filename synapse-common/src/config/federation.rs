@@ -126,10 +126,131 @@ pub struct FederationConfig {
     #[serde(default = "default_event_broadcast_batch_size")]
     pub event_broadcast_batch_size: usize,
 
+    /// 每个目标服务器出站队列可积压的 PDU（及 EDU）上限，默认 1000。
+    ///
+    /// 目标服务器响应缓慢时，超出上限的 PDU 会被丢弃并让该目标进入
+    /// catch-up 模式，之后从数据库补发其错过的事件；超出上限的 EDU
+    /// 丢弃最旧的一条。用于避免事件风暴下内存无界增长。
+    #[serde(default = "default_event_broadcast_queue_size")]
+    pub event_broadcast_queue_size: usize,
+
+    /// Inbound PDUs rejected before persistence are kept, with the reason,
+    /// in `rejected_events` for the admin API. Only the most recent this
+    /// many rows are retained; 0 disables recording.
+    #[serde(default = "default_federation_rejected_events_max")]
+    pub rejected_events_max: i64,
+
     /// Per-origin federation rate limiting. When enabled, each remote server
     /// is rate-limited independently based on its authenticated `origin`.
     #[serde(default)]
     pub rate_limit: FederationRateLimitConfig,
+
+    /// Timeouts and concurrency limits for requests we send to other servers.
+    #[serde(default)]
+    pub outbound: FederationOutboundConfig,
+
+    /// Background merging of forward extremities with dummy events.
+    #[serde(default)]
+    pub forward_extremities: ForwardExtremitiesConfig,
+}
+
+/// Forward extremity maintenance.
+///
+/// Every event a room's DAG has not yet built on is a forward extremity, and
+/// state resolution runs over all of them whenever a new event is created or
+/// received. Rooms where events arrive concurrently from many servers can
+/// pile up hundreds, making every send slow. The maintenance task finds rooms
+/// above `max_extremities` and sends an empty `org.matrix.dummy_event` as a
+/// local member, which references (and so merges) up to ten of them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct ForwardExtremitiesConfig {
+    #[serde(default = "default_forward_extremities_enabled")]
+    pub enabled: bool,
+    /// Seconds between maintenance runs.
+    #[serde(default = "default_forward_extremities_interval_secs")]
+    pub interval_secs: u64,
+    /// Rooms with more forward extremities than this get a dummy event.
+    #[serde(default = "default_forward_extremities_max_extremities")]
+    pub max_extremities: i64,
+    /// Rooms handled per run, most extremities first.
+    #[serde(default = "default_forward_extremities_rooms_per_run")]
+    pub rooms_per_run: i64,
+}
+
+impl Default for ForwardExtremitiesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: default_forward_extremities_enabled(),
+            interval_secs: default_forward_extremities_interval_secs(),
+            max_extremities: default_forward_extremities_max_extremities(),
+            rooms_per_run: default_forward_extremities_rooms_per_run(),
+        }
+    }
+}
+
+fn default_forward_extremities_enabled() -> bool {
+    true
+}
+
+fn default_forward_extremities_interval_secs() -> u64 {
+    300
+}
+
+fn default_forward_extremities_max_extremities() -> i64 {
+    10
+}
+
+fn default_forward_extremities_rooms_per_run() -> i64 {
+    50
+}
+
+/// Outbound federation request limits.
+///
+/// A request first waits for a slot for its destination and only then for a
+/// global slot, so a slow remote queues its own requests without holding
+/// connections every other destination needs.
+#[derive(Debug, Clone, Deserialize)]
+pub struct FederationOutboundConfig {
+    /// Total time allowed for one request attempt, including reading the body.
+    #[serde(default = "default_federation_outbound_request_timeout_ms")]
+    pub request_timeout_ms: u64,
+    /// Time allowed to establish a connection.
+    #[serde(default = "default_federation_outbound_connect_timeout_ms")]
+    pub connect_timeout_ms: u64,
+    /// Requests in flight across all destinations.
+    #[serde(default = "default_federation_outbound_max_concurrent_requests")]
+    pub max_concurrent_requests: usize,
+    /// Requests in flight to any single destination.
+    #[serde(default = "default_federation_outbound_max_concurrent_requests_per_destination")]
+    pub max_concurrent_requests_per_destination: usize,
+}
+
+impl Default for FederationOutboundConfig {
+    fn default() -> Self {
+        Self {
+            request_timeout_ms: default_federation_outbound_request_timeout_ms(),
+            connect_timeout_ms: default_federation_outbound_connect_timeout_ms(),
+            max_concurrent_requests: default_federation_outbound_max_concurrent_requests(),
+            max_concurrent_requests_per_destination:
+                default_federation_outbound_max_concurrent_requests_per_destination(),
+        }
+    }
+}
+
+fn default_federation_outbound_request_timeout_ms() -> u64 {
+    30_000
+}
+
+fn default_federation_outbound_connect_timeout_ms() -> u64 {
+    10_000
+}
+
+fn default_federation_outbound_max_concurrent_requests() -> usize {
+    256
+}
+
+fn default_federation_outbound_max_concurrent_requests_per_destination() -> usize {
+    8
 }
 
 /// Per-origin federation rate limit configuration.
@@ -234,6 +355,14 @@ fn default_event_broadcast_batch_size() -> usize {
     100
 }
 
+fn default_event_broadcast_queue_size() -> usize {
+    1000
+}
+
+fn default_federation_rejected_events_max() -> i64 {
+    10_000
+}
+
 fn default_federation_join_max_concurrency() -> usize {
     16
 }
@@ -0,0 +1,57 @@
+use serde::Deserialize;
+
+// ============================================================================
+// SECTION: Health Probe Configuration
+// ============================================================================
+
+fn default_ready_when_degraded() -> bool {
+    true
+}
+
+fn default_non_critical_checks() -> Vec<String> {
+    vec!["cache".to_string()]
+}
+
+fn default_check_timeout_ms() -> u64 {
+    2_000
+}
+
+/// Liveness / readiness probe configuration.
+///
+/// `/health/live` only reports whether the process is serving requests.
+/// `/health/ready` runs every registered dependency check and answers `503`
+/// when a critical check is unhealthy. Checks listed in
+/// `non_critical_checks` only degrade readiness; whether a degraded server
+/// still counts as ready is controlled by `ready_when_degraded`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct HealthConfig {
+    /// Answer `200` on `/health/ready` while the overall status is `degraded`.
+    #[serde(default = "default_ready_when_degraded")]
+    pub ready_when_degraded: bool,
+
+    /// Checks whose failure degrades readiness instead of failing it.
+    #[serde(default = "default_non_critical_checks")]
+    pub non_critical_checks: Vec<String>,
+
+    /// Per-check timeout in milliseconds; a check that takes longer is
+    /// reported as unhealthy.
+    #[serde(default = "default_check_timeout_ms")]
+    pub check_timeout_ms: u64,
+}
+
+impl Default for HealthConfig {
+    fn default() -> Self {
+        Self {
+            ready_when_degraded: default_ready_when_degraded(),
+            non_critical_checks: default_non_critical_checks(),
+            check_timeout_ms: default_check_timeout_ms(),
+        }
+    }
+}
+
+impl HealthConfig {
+    /// Whether a failure of `check` only degrades readiness.
+    pub fn is_non_critical(&self, check: &str) -> bool {
+        self.non_critical_checks.iter().any(|name| name == check)
+    }
+}
@@ -35,6 +35,15 @@ impl Config {
 
         config_values.resolve_env_variables().map_err(|e| format!("Failed to resolve environment variables: {e}"))?;
 
+        if super::test_harness::test_harness_enabled() {
+            tracing::warn!(
+                "{} is set: running in test-harness mode with a well-known registration shared secret. \
+                 Never enable this outside of test suites.",
+                super::test_harness::TEST_HARNESS_ENV
+            );
+            config_values.apply_test_harness_mode();
+        }
+
         config_values.validate().map_err(|e| format!("Configuration validation failed: {e}"))?;
 
         tracing::info!("Environment variables resolved successfully");
@@ -109,6 +118,10 @@ impl Config {
         }
 
         self.security.secret = resolve_env_in_string(&self.security.secret)?;
+        self.admin_bootstrap.username =
+            self.admin_bootstrap.username.take().map(|v| resolve_env_in_string(&v)).transpose()?;
+        self.admin_bootstrap.password_file =
+            self.admin_bootstrap.password_file.take().map(|v| resolve_env_in_string(&v)).transpose()?;
         self.security.admin_mfa_shared_secret = resolve_env_in_string(&self.security.admin_mfa_shared_secret)?;
 
         self.search.elasticsearch_url = resolve_env_in_string(&self.search.elasticsearch_url)?;
@@ -131,6 +144,10 @@ impl Config {
             self.saml.sp_entity_id = resolve_env_in_string(&self.saml.sp_entity_id)?;
         }
 
+        if self.ldap.enabled {
+            self.ldap.bind_password = self.ldap.bind_password.take().map(|v| resolve_env_in_string(&v)).transpose()?;
+        }
+
         self.admin_registration.shared_secret = resolve_env_in_string(&self.admin_registration.shared_secret)?;
         self.admin_registration.ip_whitelist = self
             .admin_registration
@@ -14,51 +14,87 @@ pub use manager::ConfigManager;
 
 pub mod auth;
 pub mod builtin_oidc;
+pub mod column_encryption;
+pub mod content_filter;
 pub mod database;
+pub mod egress;
 pub mod error;
 pub mod experimental;
 pub mod federation;
+pub mod health;
 pub mod identity;
 pub mod logging;
+pub mod outbound_proxy;
 pub mod performance;
+pub mod policy_lists;
 pub mod policy_server;
 pub mod push;
 pub mod rate_limit;
 pub mod retention;
+pub mod room_creation;
+pub mod room_directory;
 pub mod search;
 pub mod security;
 pub mod server;
 pub mod sms;
 pub mod smtp;
+pub mod test_harness;
 pub mod translate;
 pub mod voip;
+pub mod welcome;
 pub mod worker;
 
 // ============================================================================
 // Re-exports for backward compatibility
 // ============================================================================
 
-pub use auth::{OidcAttributeMapping, OidcConfig, SamlAttributeMapping, SamlConfig};
+pub use auth::{
+    LdapAttributes, LdapConfig, LdapFallback, OidcAttributeMapping, OidcConfig, SamlAttributeMapping, SamlConfig,
+    SsoCollisionPolicy,
+};
 pub use builtin_oidc::{BuiltinOidcConfig, BuiltinOidcUser};
-pub use database::{CircuitBreakerConfig, DatabaseConfig, RedisConfig};
+pub use column_encryption::ColumnEncryptionConfig;
+pub use content_filter::{ContentFilterAction, ContentFilterConfig};
+pub use database::{
+    CircuitBreakerConfig, DatabaseConfig, EventPartitioningConfig, RedisConfig, SchemaDriftPolicy,
+    StateCompactionConfig,
+};
+pub use egress::EgressConfig;
 pub use error::ConfigError;
 pub use experimental::ExperimentalConfig;
-pub use federation::{FederationConfig, FederationRateLimitConfig, TrustedKeyServer};
+pub use federation::{
+    FederationConfig, FederationOutboundConfig, FederationRateLimitConfig, ForwardExtremitiesConfig, TrustedKeyServer,
+};
+pub use health::HealthConfig;
 pub use identity::IdentityConfig;
 pub use logging::LoggingConfig;
+pub use outbound_proxy::OutboundProxyConfig;
 pub use performance::PerformanceConfig;
+pub use policy_lists::PolicyListsConfig;
 pub use policy_server::PolicyServerConfig;
-pub use rate_limit::{RateLimitConfig, RateLimitEndpointRule, RateLimitMatchType, RateLimitRule, SyncRateLimitConfig};
+pub use rate_limit::{
+    ConcurrencyLimitConfig, ConcurrencyLimitRule, DailyQuotaConfig, RateLimitConfig, RateLimitEndpointRule,
+    RateLimitMatchType, RateLimitRule, RoomSendRateLimitConfig, SyncRateLimitConfig,
+};
 pub use retention::{RetentionConfig, RetentionPolicy, RetentionPurgeJob};
+pub use room_creation::{RoomCreationConfig, RoomCreationKind, RoomCreationRule};
+pub use room_directory::{
+    glob_matches, AliasCreationRule, RoomDirectoryConfig, RoomDirectoryRuleAction, RoomPublicationPolicy,
+    RoomPublicationRule,
+};
 pub use search::{PostgresFtsConfig, PostgresFtsWeights, SearchConfig};
-pub use security::{AdminRegistrationConfig, CorsConfig, SecurityConfig};
-pub use server::ServerConfig;
+pub use security::{AdminBootstrapConfig, AdminRegistrationConfig, CorsConfig, SecurityConfig};
+pub use server::{HttpServerConfig, ServerConfig};
 pub use sms::SmsConfig;
-pub use smtp::{SmtpConfig, SmtpRateLimitConfig};
+pub use smtp::{
+    DkimAlgorithm, EmailTemplatesConfig, SmtpConfig, SmtpDkimConfig, SmtpPoolConfig, SmtpRateLimitConfig,
+    SmtpRetryConfig, SmtpSecurity,
+};
 pub use translate::TranslateConfig;
 pub use voip::{
     ApnsConfig, FcmConfig, LivekitConfig, PushConfig, UrlBlacklistRule, UrlPreviewConfig, VoipConfig, WebPushConfig,
 };
+pub use welcome::{accept_language_tags, render_welcome_template, WelcomeConfig};
 pub use worker::{InstanceLocationConfig, ReplicationConfig, ReplicationHttpConfig, StreamWriters, WorkerConfig};
 
 // Re-export helper functions used in tests and serde defaults
@@ -92,6 +128,9 @@ pub struct Config {
     /// Admin registration configuration
     #[serde(default)]
     pub admin_registration: AdminRegistrationConfig,
+    /// First admin account creation on an empty server
+    #[serde(default)]
+    pub admin_bootstrap: AdminBootstrapConfig,
     /// Worker node configuration
     #[serde(default)]
     pub worker: WorkerConfig,
@@ -125,6 +164,9 @@ pub struct Config {
     /// SAML single sign-on configuration
     #[serde(default)]
     pub saml: SamlConfig,
+    /// LDAP password provider
+    #[serde(default)]
+    pub ldap: LdapConfig,
     /// Message retention policy configuration
     #[serde(default)]
     pub retention: RetentionConfig,
@@ -146,6 +188,33 @@ pub struct Config {
     /// Translation service configuration
     #[serde(default)]
     pub translate: TranslateConfig,
+    /// Pre-send content filter configuration
+    #[serde(default)]
+    pub content_filter: ContentFilterConfig,
+    /// Room directory publication configuration
+    #[serde(default)]
+    pub room_directory: RoomDirectoryConfig,
+    /// Who may create spaces, normal rooms and encrypted rooms
+    #[serde(default)]
+    pub room_creation: RoomCreationConfig,
+    /// Welcome message for newly registered users
+    #[serde(default)]
+    pub welcome: WelcomeConfig,
+    /// Moderation policy list enforcement
+    #[serde(default)]
+    pub policy_lists: PolicyListsConfig,
+    /// Encryption of sensitive e2ee and SSO columns
+    #[serde(default)]
+    pub column_encryption: ColumnEncryptionConfig,
+    /// Proxy for outbound HTTP requests
+    #[serde(default)]
+    pub outbound_proxy: OutboundProxyConfig,
+    /// Address ranges refused for user- and service-supplied URLs
+    #[serde(default)]
+    pub egress: EgressConfig,
+    /// Liveness / readiness probe configuration
+    #[serde(default)]
+    pub health: HealthConfig,
     /// Allowed redirect URL prefixes for SSO post-login redirects.
     /// If empty, only same-origin paths (starting with `/`) are permitted.
     /// Example: `["https://app.example.com/"]`
@@ -243,7 +312,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -258,6 +329,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "localhost".to_string(),
@@ -336,6 +411,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -353,6 +429,7 @@ mod tests {
             oidc: OidcConfig::default(),
             builtin_oidc: BuiltinOidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: crate::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: crate::telemetry_config::PrometheusConfig::default(),
@@ -360,6 +437,15 @@ mod tests {
             experimental: ExperimentalConfig::default(),
             identity: IdentityConfig::default(),
             translate: TranslateConfig::default(),
+            content_filter: ContentFilterConfig::default(),
+            room_directory: RoomDirectoryConfig::default(),
+            room_creation: RoomCreationConfig::default(),
+            welcome: WelcomeConfig::default(),
+            policy_lists: PolicyListsConfig::default(),
+            column_encryption: ColumnEncryptionConfig::default(),
+            outbound_proxy: OutboundProxyConfig::default(),
+            egress: EgressConfig::default(),
+            health: HealthConfig::default(),
             sso_redirect_allowlist: vec![],
         };
 
@@ -404,7 +490,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -419,6 +507,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "redis.example.com".to_string(),
@@ -497,6 +589,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -513,6 +606,7 @@ mod tests {
             url_preview: UrlPreviewConfig::default(),
             oidc: OidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: crate::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: crate::telemetry_config::PrometheusConfig::default(),
@@ -562,7 +656,9 @@ mod tests {
             allow_public_rooms_over_federation: true,
             auto_join_rooms: vec![],
             autocreate_auto_join_rooms: true,
+            auto_join_mxid_localpart: None,
             encryption_enabled_by_default_for_room_type: None,
+            reject_unencrypted_messages_in_encrypted_rooms: false,
             app_service_config_files: vec![],
             presence_enabled: true,
             ..Default::default()
@@ -591,6 +687,10 @@ mod tests {
             max_size: 20,
             min_idle: None,
             connection_timeout: 60,
+            schema_drift_policy: Default::default(),
+            migrations_dir: None,
+            event_partitioning: Default::default(),
+            state_compaction: Default::default(),
         };
 
         assert_eq!(config.host, "db.example.com");
@@ -679,7 +779,9 @@ mod tests {
                 allow_public_rooms_over_federation: true,
                 auto_join_rooms: vec![],
                 autocreate_auto_join_rooms: true,
+                auto_join_mxid_localpart: None,
                 encryption_enabled_by_default_for_room_type: None,
+                reject_unencrypted_messages_in_encrypted_rooms: false,
                 app_service_config_files: vec![],
                 presence_enabled: true,
                 ..Default::default()
@@ -694,6 +796,10 @@ mod tests {
                 max_size: 20,
                 min_idle: Some(5),
                 connection_timeout: 30,
+                schema_drift_policy: Default::default(),
+                migrations_dir: None,
+                event_partitioning: Default::default(),
+                state_compaction: Default::default(),
             },
             redis: RedisConfig {
                 host: "localhost".to_string(),
@@ -772,6 +878,7 @@ mod tests {
             },
             rate_limit: RateLimitConfig::default(),
             admin_registration: AdminRegistrationConfig::default(),
+            admin_bootstrap: AdminBootstrapConfig::default(),
             worker: WorkerConfig::default(),
             cors: CorsConfig {
                 allowed_origins: vec!["*".to_string()],
@@ -789,6 +896,7 @@ mod tests {
             oidc: OidcConfig::default(),
             builtin_oidc: BuiltinOidcConfig::default(),
             saml: SamlConfig::default(),
+            ldap: LdapConfig::default(),
             retention: RetentionConfig::default(),
             telemetry: crate::telemetry_config::OpenTelemetryConfig::default(),
             prometheus: crate::telemetry_config::PrometheusConfig::default(),
@@ -796,6 +904,15 @@ mod tests {
             experimental: ExperimentalConfig::default(),
             identity: IdentityConfig::default(),
             translate: TranslateConfig::default(),
+            content_filter: ContentFilterConfig::default(),
+            room_directory: RoomDirectoryConfig::default(),
+            room_creation: RoomCreationConfig::default(),
+            welcome: WelcomeConfig::default(),
+            policy_lists: PolicyListsConfig::default(),
+            column_encryption: ColumnEncryptionConfig::default(),
+            outbound_proxy: OutboundProxyConfig::default(),
+            egress: EgressConfig::default(),
+            health: HealthConfig::default(),
             sso_redirect_allowlist: vec![],
         };
 
@@ -0,0 +1,33 @@
+use serde::Deserialize;
+
+// ============================================================================
+// SECTION: Outbound Proxy Configuration
+// ============================================================================
+
+/// HTTP(S) proxy for every request the server makes: federation, push
+/// gateways, identity servers, application services, SSO providers and so on.
+///
+/// When neither proxy is set, the `HTTP_PROXY` / `HTTPS_PROXY` / `NO_PROXY`
+/// environment variables keep working as before. Setting either one here
+/// replaces the environment variables entirely.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct OutboundProxyConfig {
+    /// Proxy for plain `http://` requests, e.g. `http://proxy.internal:3128`.
+    #[serde(default)]
+    pub http_proxy: Option<String>,
+
+    /// Proxy for `https://` requests. Credentials may be embedded in the URL.
+    #[serde(default)]
+    pub https_proxy: Option<String>,
+
+    /// Hosts reached directly: domains (matching subdomains too), IP
+    /// addresses, CIDR ranges, or `*` for everything.
+    #[serde(default)]
+    pub no_proxy: Vec<String>,
+}
+
+impl OutboundProxyConfig {
+    pub fn is_configured(&self) -> bool {
+        self.http_proxy.is_some() || self.https_proxy.is_some()
+    }
+}
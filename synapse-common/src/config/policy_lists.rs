@@ -0,0 +1,81 @@
+use serde::Deserialize;
+
+// ============================================================================
+// SECTION: Policy List Configuration
+// ============================================================================
+
+fn default_true() -> bool {
+    true
+}
+
+fn default_policy_list_sync_interval_secs() -> u64 {
+    300
+}
+
+/// Server-side enforcement of moderation policy lists (`m.policy.rule.*`
+/// state in ban list rooms, as published by Mjolnir and Draupnir).
+///
+/// `m.ban` user rules ban matching members from `protected_rooms` as
+/// `moderator_localpart`, who needs ban power there. `m.ban` server rules add
+/// the server to the federation blacklist. Removing a rule does not lift
+/// bans or blocks that were already applied.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PolicyListsConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    /// Room IDs whose policy rules are followed. The server must be in them.
+    #[serde(default)]
+    pub policy_rooms: Vec<String>,
+
+    /// Room IDs where user rules are enforced.
+    #[serde(default)]
+    pub protected_rooms: Vec<String>,
+
+    /// Local user that issues the bans.
+    #[serde(default)]
+    pub moderator_localpart: Option<String>,
+
+    #[serde(default = "default_true")]
+    pub ban_users: bool,
+
+    #[serde(default = "default_true")]
+    pub block_servers: bool,
+
+    /// Full resync interval; rule changes are also picked up as they arrive.
+    #[serde(default = "default_policy_list_sync_interval_secs")]
+    pub sync_interval_secs: u64,
+}
+
+impl Default for PolicyListsConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            policy_rooms: Vec::new(),
+            protected_rooms: Vec::new(),
+            moderator_localpart: None,
+            ban_users: true,
+            block_servers: true,
+            sync_interval_secs: default_policy_list_sync_interval_secs(),
+        }
+    }
+}
+
+impl PolicyListsConfig {
+    pub fn is_active(&self) -> bool {
+        self.enabled && !self.policy_rooms.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_empty_uses_defaults() {
+        let cfg: PolicyListsConfig = serde_yaml::from_str("{}\n").expect("empty YAML should deserialize");
+        assert!(!cfg.is_active());
+        assert!(cfg.ban_users && cfg.block_servers);
+        assert_eq!(cfg.sync_interval_secs, 300);
+    }
+}
@@ -1,6 +1,8 @@
 use serde::Deserialize;
 use std::collections::HashMap;
 
+use super::room_directory::glob_matches;
+
 /// 限流配置。
 ///
 /// 配置 API 请求限流规则，包括全局限流和端点级限流。
@@ -41,6 +43,15 @@ pub struct RateLimitConfig {
     /// 同步接口的资源隔离限流（initial vs incremental）
     #[serde(default)]
     pub sync: SyncRateLimitConfig,
+    /// 按 (用户, 房间) 的消息发送洪泛控制，独立于全局中间件限流
+    #[serde(default)]
+    pub room_send: RoomSendRateLimitConfig,
+    /// 按用户的每日消息与邀请配额
+    #[serde(default)]
+    pub daily_quotas: DailyQuotaConfig,
+    /// 按用户的高开销端点并发上限
+    #[serde(default)]
+    pub concurrency: ConcurrencyLimitConfig,
     /// CIDR strings for trusted reverse proxies (e.g. "10.0.0.0/8", "127.0.0.1/32").
     #[serde(default)]
     pub trusted_proxies: Vec<String>,
@@ -67,6 +78,175 @@ pub struct SyncRateLimitConfig {
     pub incremental: RateLimitRule,
 }
 
+/// 房间消息发送洪泛控制。
+///
+/// 每个 (用户, 房间) 组合使用独立的令牌桶，防止单个发送者刷屏某个房间，
+/// 即使其整体请求量仍低于全局限流阈值。
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomSendRateLimitConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每秒补充的发送配额
+    #[serde(default = "default_room_send_per_second")]
+    pub per_second: u32,
+    /// 令牌桶容量（允许的突发消息数）
+    #[serde(default = "default_room_send_burst_size")]
+    pub burst_size: u32,
+}
+
+fn default_room_send_per_second() -> u32 {
+    1
+}
+
+fn default_room_send_burst_size() -> u32 {
+    10
+}
+
+impl Default for RoomSendRateLimitConfig {
+    fn default() -> Self {
+        Self { enabled: false, per_second: default_room_send_per_second(), burst_size: default_room_send_burst_size() }
+    }
+}
+
+/// 每日发送配额。
+///
+/// 按 UTC 自然日统计每个用户发送的消息数和邀请数，超出后返回
+/// `M_LIMIT_EXCEEDED`，直到次日零点。注册时间不足 `new_account_age_secs`
+/// 的新账号使用更严格的 `new_account_*` 配额。计数保存在 Redis 中，
+/// Redis 不可用时回退到数据库。
+#[derive(Debug, Clone, Deserialize)]
+pub struct DailyQuotaConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 每日消息数上限
+    #[serde(default = "default_daily_messages")]
+    pub messages_per_day: u32,
+    /// 每日邀请数上限
+    #[serde(default = "default_daily_invites")]
+    pub invites_per_day: u32,
+    /// 注册多久以内算作新账号（秒）
+    #[serde(default = "default_new_account_age_secs")]
+    pub new_account_age_secs: u64,
+    /// 新账号每日消息数上限
+    #[serde(default = "default_new_account_daily_messages")]
+    pub new_account_messages_per_day: u32,
+    /// 新账号每日邀请数上限
+    #[serde(default = "default_new_account_daily_invites")]
+    pub new_account_invites_per_day: u32,
+    /// 服务器管理员不受配额限制
+    #[serde(default = "default_exempt_admins")]
+    pub exempt_admins: bool,
+}
+
+fn default_daily_messages() -> u32 {
+    10_000
+}
+
+fn default_daily_invites() -> u32 {
+    500
+}
+
+fn default_new_account_age_secs() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+fn default_new_account_daily_messages() -> u32 {
+    1_000
+}
+
+fn default_new_account_daily_invites() -> u32 {
+    20
+}
+
+fn default_exempt_admins() -> bool {
+    true
+}
+
+impl Default for DailyQuotaConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            messages_per_day: default_daily_messages(),
+            invites_per_day: default_daily_invites(),
+            new_account_age_secs: default_new_account_age_secs(),
+            new_account_messages_per_day: default_new_account_daily_messages(),
+            new_account_invites_per_day: default_new_account_daily_invites(),
+            exempt_admins: default_exempt_admins(),
+        }
+    }
+}
+
+/// 按用户的并发请求上限。
+///
+/// 限制单个用户同时处理中的高开销请求数（`/sync`、`/search`、媒体下载等），
+/// 超出上限的请求立即返回 429 `M_LIMIT_EXCEEDED`，避免单个异常客户端占满
+/// 数据库连接池。第一条匹配请求路径的规则生效；未携带访问令牌的请求不受限制。
+/// 计数保存在进程内存中，多 worker 部署时各 worker 分别计数。
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConcurrencyLimitConfig {
+    /// 是否启用
+    #[serde(default)]
+    pub enabled: bool,
+    /// 并发规则
+    #[serde(default = "default_concurrency_rules")]
+    pub rules: Vec<ConcurrencyLimitRule>,
+    /// 服务器管理员不受限制
+    #[serde(default = "default_exempt_admins")]
+    pub exempt_admins: bool,
+}
+
+/// 单条并发规则：匹配 `paths` 中任一 glob 模式（`*` 可跨越 `/`）的请求，
+/// 每个用户最多同时处理 `max_concurrent` 个。
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConcurrencyLimitRule {
+    /// 规则名，用于计数分组与日志
+    pub name: String,
+    /// 路径 glob 模式
+    pub paths: Vec<String>,
+    /// 每个用户的并发上限
+    pub max_concurrent: u32,
+}
+
+fn concurrency_rule(name: &str, paths: &[&str], max_concurrent: u32) -> ConcurrencyLimitRule {
+    ConcurrencyLimitRule {
+        name: name.to_string(),
+        paths: paths.iter().map(|path| (*path).to_string()).collect(),
+        max_concurrent,
+    }
+}
+
+fn default_concurrency_rules() -> Vec<ConcurrencyLimitRule> {
+    vec![
+        concurrency_rule("sync", &["/_matrix/client/*/sync"], 2),
+        concurrency_rule("search", &["/_matrix/client/*/search"], 1),
+        concurrency_rule(
+            "media_download",
+            &[
+                "/_matrix/media/*/download/*",
+                "/_matrix/media/*/thumbnail/*",
+                "/_matrix/client/v1/media/download/*",
+                "/_matrix/client/v1/media/thumbnail/*",
+            ],
+            4,
+        ),
+    ]
+}
+
+impl Default for ConcurrencyLimitConfig {
+    fn default() -> Self {
+        Self { enabled: false, rules: default_concurrency_rules(), exempt_admins: default_exempt_admins() }
+    }
+}
+
+impl ConcurrencyLimitConfig {
+    /// 第一条匹配 `path` 的规则。
+    pub fn rule_for(&self, path: &str) -> Option<&ConcurrencyLimitRule> {
+        self.rules.iter().find(|rule| rule.paths.iter().any(|pattern| glob_matches(pattern, path)))
+    }
+}
+
 /// 单个限流规则。
 ///
 /// 定义令牌桶算法的参数：每秒补充令牌数和桶容量。
@@ -136,6 +316,9 @@ impl Default for RateLimitConfig {
             endpoint_aliases: HashMap::new(),
             fail_open_on_error: default_rate_limit_fail_open(),
             sync: SyncRateLimitConfig::default(),
+            room_send: RoomSendRateLimitConfig::default(),
+            daily_quotas: DailyQuotaConfig::default(),
+            concurrency: ConcurrencyLimitConfig::default(),
             trusted_proxies: Vec::new(),
             trust_forwarded: false,
         }
@@ -196,6 +379,61 @@ mod tests {
         assert_eq!(sync.incremental.burst_size, 20);
     }
 
+    #[test]
+    fn test_room_send_rate_limit_config_default() {
+        let room_send = RoomSendRateLimitConfig::default();
+        assert!(!room_send.enabled);
+        assert_eq!(room_send.per_second, 1);
+        assert_eq!(room_send.burst_size, 10);
+    }
+
+    #[test]
+    fn test_room_send_rate_limit_config_partial_yaml() {
+        let room_send: RoomSendRateLimitConfig =
+            serde_yaml::from_str("enabled: true\nburst_size: 3\n").expect("partial YAML should deserialize");
+        assert!(room_send.enabled);
+        assert_eq!(room_send.per_second, 1);
+        assert_eq!(room_send.burst_size, 3);
+    }
+
+    #[test]
+    fn test_daily_quota_config_partial_yaml() {
+        let quotas: DailyQuotaConfig =
+            serde_yaml::from_str("enabled: true\ninvites_per_day: 50\n").expect("partial YAML should deserialize");
+        assert!(quotas.enabled);
+        assert_eq!(quotas.invites_per_day, 50);
+        assert_eq!(quotas.messages_per_day, 10_000);
+        assert_eq!(quotas.new_account_invites_per_day, 20);
+        assert!(quotas.exempt_admins);
+    }
+
+    #[test]
+    fn test_concurrency_limit_default_rules_match_expensive_endpoints() {
+        let concurrency = ConcurrencyLimitConfig::default();
+        assert!(!concurrency.enabled);
+        let rule = |path: &str| concurrency.rule_for(path).map(|rule| (rule.name.as_str(), rule.max_concurrent));
+        assert_eq!(rule("/_matrix/client/v3/sync"), Some(("sync", 2)));
+        assert_eq!(rule("/_matrix/client/r0/search"), Some(("search", 1)));
+        assert_eq!(rule("/_matrix/client/v1/media/download/example.com/abc"), Some(("media_download", 4)));
+        assert_eq!(rule("/_matrix/media/v3/thumbnail/example.com/abc"), Some(("media_download", 4)));
+        assert_eq!(rule("/_matrix/client/v3/rooms/!a:example.com/messages"), None);
+    }
+
+    #[test]
+    fn test_concurrency_limit_custom_rules_replace_defaults() {
+        let concurrency: ConcurrencyLimitConfig = serde_yaml::from_str(
+            "enabled: true\nrules:\n  - name: messages\n    paths: ['/_matrix/client/*/rooms/*/messages']\n    max_concurrent: 3\n",
+        )
+        .expect("rules YAML should deserialize");
+        assert!(concurrency.enabled);
+        assert!(concurrency.exempt_admins);
+        assert_eq!(
+            concurrency.rule_for("/_matrix/client/v3/rooms/!a:example.com/messages").map(|r| r.max_concurrent),
+            Some(3)
+        );
+        assert!(concurrency.rule_for("/_matrix/client/v3/sync").is_none());
+    }
+
     #[test]
     fn test_rate_limit_match_type_default() {
         let match_type = RateLimitMatchType::default();
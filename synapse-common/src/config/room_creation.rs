@@ -0,0 +1,150 @@
+use serde::Deserialize;
+
+use super::room_directory::{glob_matches, RoomDirectoryRuleAction};
+
+// ============================================================================
+// SECTION: Room Creation Restrictions
+// ============================================================================
+
+fn default_match_all() -> String {
+    "*".to_string()
+}
+
+fn default_denied_message() -> String {
+    "You are not allowed to create this kind of room".to_string()
+}
+
+/// Kind of room a creation rule applies to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomCreationKind {
+    /// Every room, space or not.
+    #[default]
+    Any,
+    /// Rooms created with `room_type: m.space`.
+    Space,
+    /// Everything that is not a space.
+    Room,
+    /// Rooms or spaces encrypted from creation, whether requested in
+    /// `initial_state` or applied by `encryption_enabled_by_default_for_room_type`.
+    Encrypted,
+}
+
+/// One entry of `room_creation.rules`. `user_id` is a glob pattern in the
+/// syntax of the room directory rules.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomCreationRule {
+    #[serde(default = "default_match_all")]
+    pub user_id: String,
+
+    #[serde(default)]
+    pub kind: RoomCreationKind,
+
+    #[serde(default)]
+    pub action: RoomDirectoryRuleAction,
+
+    /// Message returned when this rule denies; `room_creation.error_message`
+    /// otherwise.
+    #[serde(default)]
+    pub error_message: Option<String>,
+}
+
+impl RoomCreationRule {
+    fn matches(&self, user_id: &str, is_space: bool, is_encrypted: bool) -> bool {
+        let kind_matches = match self.kind {
+            RoomCreationKind::Any => true,
+            RoomCreationKind::Space => is_space,
+            RoomCreationKind::Room => !is_space,
+            RoomCreationKind::Encrypted => is_encrypted,
+        };
+        kind_matches && glob_matches(&self.user_id, user_id)
+    }
+}
+
+/// Who may create spaces, normal rooms and encrypted rooms.
+///
+/// The first rule matching the creator and the requested room wins; a request
+/// no rule matches is allowed, so a trailing catch-all `deny` turns the list
+/// into an allow list. Server admins are never restricted.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomCreationConfig {
+    #[serde(default)]
+    pub rules: Vec<RoomCreationRule>,
+
+    #[serde(default = "default_denied_message")]
+    pub error_message: String,
+}
+
+impl Default for RoomCreationConfig {
+    fn default() -> Self {
+        Self { rules: Vec::new(), error_message: default_denied_message() }
+    }
+}
+
+impl RoomCreationConfig {
+    /// `Err` with the configured message when `user_id` may not create the
+    /// room described by `is_space` and `is_encrypted`.
+    pub fn check(&self, user_id: &str, is_admin: bool, is_space: bool, is_encrypted: bool) -> Result<(), String> {
+        if is_admin {
+            return Ok(());
+        }
+        match self.rules.iter().find(|rule| rule.matches(user_id, is_space, is_encrypted)) {
+            Some(rule) if rule.action == RoomDirectoryRuleAction::Deny => {
+                Err(rule.error_message.clone().unwrap_or_else(|| self.error_message.clone()))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_config_allows_everything() {
+        let cfg: RoomCreationConfig = serde_yaml::from_str("{}\n").expect("empty YAML should deserialize");
+        assert!(cfg.rules.is_empty());
+        assert!(cfg.check("@alice:example.com", false, true, true).is_ok());
+    }
+
+    #[test]
+    fn spaces_reserved_for_staff_with_custom_message() {
+        let yaml = "\
+error_message: Ask an administrator
+rules:
+  - user_id: '@staff_*:example.com'
+    kind: space
+  - kind: space
+    action: deny
+    error_message: Only staff may create spaces
+  - kind: encrypted
+    user_id: '@bot_*:example.com'
+    action: deny
+";
+        let cfg: RoomCreationConfig = serde_yaml::from_str(yaml).expect("rules YAML should deserialize");
+        assert!(cfg.check("@staff_ann:example.com", false, true, false).is_ok());
+        assert_eq!(
+            cfg.check("@alice:example.com", false, true, false),
+            Err("Only staff may create spaces".to_string())
+        );
+        assert!(cfg.check("@admin:example.com", true, true, false).is_ok());
+        assert!(cfg.check("@alice:example.com", false, false, true).is_ok());
+        assert_eq!(cfg.check("@bot_x:example.com", false, false, true), Err("Ask an administrator".to_string()));
+        assert!(cfg.check("@bot_x:example.com", false, false, false).is_ok());
+    }
+
+    #[test]
+    fn trailing_deny_turns_rules_into_allow_list() {
+        let yaml = "\
+rules:
+  - user_id: '@*:example.com'
+    kind: room
+  - action: deny
+";
+        let cfg: RoomCreationConfig = serde_yaml::from_str(yaml).expect("rules YAML should deserialize");
+        assert!(cfg.check("@alice:example.com", false, false, false).is_ok());
+        assert!(cfg.check("@alice:example.com", false, true, false).is_err());
+        assert!(cfg.check("@eve:other.org", false, false, false).is_err());
+    }
+}
@@ -0,0 +1,249 @@
+use serde::Deserialize;
+
+// ============================================================================
+// SECTION: Room Directory Configuration
+// ============================================================================
+
+fn default_match_all() -> String {
+    "*".to_string()
+}
+
+/// Who may publish rooms to the public room directory.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomPublicationPolicy {
+    /// Any room creator may publish their room.
+    #[default]
+    All,
+    /// Only server admins may publish rooms.
+    AdminsOnly,
+    /// `publication_rules` decide; the first matching rule wins and a request
+    /// no rule matches is denied.
+    Rules,
+}
+
+/// Outcome of a matching publication or alias creation rule.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RoomDirectoryRuleAction {
+    #[default]
+    Allow,
+    Deny,
+}
+
+/// One entry of `room_directory.publication_rules`. `user_id` and `room_id`
+/// are glob patterns where `*` matches any run of characters and `?` a single
+/// character.
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoomPublicationRule {
+    #[serde(default = "default_match_all")]
+    pub user_id: String,
+
+    #[serde(default = "default_match_all")]
+    pub room_id: String,
+
+    #[serde(default)]
+    pub action: RoomDirectoryRuleAction,
+}
+
+impl RoomPublicationRule {
+    fn matches(&self, user_id: &str, room_id: &str) -> bool {
+        glob_matches(&self.user_id, user_id) && glob_matches(&self.room_id, room_id)
+    }
+}
+
+/// One entry of `room_directory.alias_creation_rules`, in the same glob
+/// syntax as [`RoomPublicationRule`]. `alias` is matched against the full
+/// alias including the leading `#` and the server name.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AliasCreationRule {
+    #[serde(default = "default_match_all")]
+    pub user_id: String,
+
+    #[serde(default = "default_match_all")]
+    pub alias: String,
+
+    #[serde(default = "default_match_all")]
+    pub room_id: String,
+
+    #[serde(default)]
+    pub action: RoomDirectoryRuleAction,
+}
+
+impl AliasCreationRule {
+    fn matches(&self, user_id: &str, alias: &str, room_id: &str) -> bool {
+        glob_matches(&self.user_id, user_id) && glob_matches(&self.alias, alias) && glob_matches(&self.room_id, room_id)
+    }
+}
+
+/// Room directory publication and alias namespace configuration.
+///
+/// Only publishing is restricted; taking a room out of the directory is always
+/// allowed. Server admins are never restricted, and the admin listings API
+/// publishes regardless of the policy.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RoomDirectoryConfig {
+    #[serde(default)]
+    pub publication: RoomPublicationPolicy,
+
+    #[serde(default)]
+    pub publication_rules: Vec<RoomPublicationRule>,
+
+    /// Publications that pass the policy wait in a moderation queue until an
+    /// admin approves them instead of being listed right away.
+    #[serde(default)]
+    pub require_publication_approval: bool,
+
+    /// Synapse-style alias creation rules. Empty allows every alias; otherwise
+    /// the first matching rule wins and an alias no rule matches is denied.
+    #[serde(default)]
+    pub alias_creation_rules: Vec<AliasCreationRule>,
+}
+
+impl RoomDirectoryConfig {
+    /// Whether `user_id` may publish `room_id` to the public room directory.
+    pub fn may_publish(&self, user_id: &str, room_id: &str, is_admin: bool) -> bool {
+        if is_admin {
+            return true;
+        }
+        match self.publication {
+            RoomPublicationPolicy::All => true,
+            RoomPublicationPolicy::AdminsOnly => false,
+            RoomPublicationPolicy::Rules => self
+                .publication_rules
+                .iter()
+                .find(|rule| rule.matches(user_id, room_id))
+                .is_some_and(|rule| rule.action == RoomDirectoryRuleAction::Allow),
+        }
+    }
+
+    /// Whether `user_id` may create `alias` pointing at `room_id`.
+    pub fn may_create_alias(&self, user_id: &str, alias: &str, room_id: &str, is_admin: bool) -> bool {
+        if is_admin || self.alias_creation_rules.is_empty() {
+            return true;
+        }
+        self.alias_creation_rules
+            .iter()
+            .find(|rule| rule.matches(user_id, alias, room_id))
+            .is_some_and(|rule| rule.action == RoomDirectoryRuleAction::Allow)
+    }
+}
+
+/// Glob match where `*` matches any run of characters and `?` a single one.
+pub fn glob_matches(pattern: &str, value: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let value: Vec<char> = value.chars().collect();
+    let (mut p, mut v) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while v < value.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, v));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == value[v] => {
+                p += 1;
+                v += 1;
+            }
+            _ => match backtrack {
+                Some((star_p, star_v)) => {
+                    p = star_p + 1;
+                    v = star_v + 1;
+                    backtrack = Some((star_p, star_v + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deserialize_empty_uses_defaults() {
+        let cfg: RoomDirectoryConfig = serde_yaml::from_str("{}\n").expect("empty YAML should deserialize");
+        assert_eq!(cfg.publication, RoomPublicationPolicy::All);
+        assert!(cfg.publication_rules.is_empty());
+        assert!(!cfg.require_publication_approval);
+        assert!(cfg.may_publish("@alice:example.com", "!room:example.com", false));
+    }
+
+    #[test]
+    fn admins_only_still_lets_admins_publish() {
+        let cfg: RoomDirectoryConfig =
+            serde_yaml::from_str("publication: admins_only\n").expect("explicit YAML should deserialize");
+        assert!(!cfg.may_publish("@alice:example.com", "!room:example.com", false));
+        assert!(cfg.may_publish("@admin:example.com", "!room:example.com", true));
+    }
+
+    #[test]
+    fn rules_first_match_wins_and_unmatched_is_denied() {
+        let yaml = "\
+publication: rules
+publication_rules:
+  - user_id: '@spammer:example.com'
+    action: deny
+  - user_id: '@*:example.com'
+    room_id: '!public_*'
+  - room_id: '!open:example.com'
+";
+        let cfg: RoomDirectoryConfig = serde_yaml::from_str(yaml).expect("rules YAML should deserialize");
+        assert!(cfg.may_publish("@alice:example.com", "!public_lobby:example.com", false));
+        assert!(!cfg.may_publish("@spammer:example.com", "!public_lobby:example.com", false));
+        assert!(!cfg.may_publish("@alice:example.com", "!secret:example.com", false));
+        assert!(!cfg.may_publish("@bob:other.org", "!public_lobby:example.com", false));
+        assert!(cfg.may_publish("@bob:other.org", "!open:example.com", false));
+    }
+
+    #[test]
+    fn alias_rules_reserve_a_prefix_for_staff() {
+        let yaml = "\
+alias_creation_rules:
+  - user_id: '@staff_*:example.com'
+    alias: '#official-*'
+  - alias: '#official-*'
+    action: deny
+  - user_id: '@irc_*:example.com'
+    alias: '#irc_*'
+  - alias: '#irc_*'
+    action: deny
+  - {}
+";
+        let cfg: RoomDirectoryConfig = serde_yaml::from_str(yaml).expect("alias rules YAML should deserialize");
+        let room = "!room:example.com";
+        assert!(cfg.may_create_alias("@staff_ann:example.com", "#official-news:example.com", room, false));
+        assert!(!cfg.may_create_alias("@alice:example.com", "#official-news:example.com", room, false));
+        assert!(cfg.may_create_alias("@admin:example.com", "#official-news:example.com", room, true));
+        assert!(cfg.may_create_alias("@irc_bridge:example.com", "#irc_libera:example.com", room, false));
+        assert!(!cfg.may_create_alias("@alice:example.com", "#irc_libera:example.com", room, false));
+        assert!(cfg.may_create_alias("@alice:example.com", "#chat:example.com", room, false));
+    }
+
+    #[test]
+    fn alias_rules_deny_unmatched_once_configured() {
+        let mut cfg = RoomDirectoryConfig::default();
+        assert!(cfg.may_create_alias("@alice:example.com", "#chat:example.com", "!room:example.com", false));
+        cfg.alias_creation_rules = vec![AliasCreationRule {
+            user_id: "*".to_string(),
+            alias: "*".to_string(),
+            room_id: "!allowed:example.com".to_string(),
+            action: RoomDirectoryRuleAction::Allow,
+        }];
+        assert!(cfg.may_create_alias("@alice:example.com", "#chat:example.com", "!allowed:example.com", false));
+        assert!(!cfg.may_create_alias("@alice:example.com", "#chat:example.com", "!room:example.com", false));
+    }
+
+    #[test]
+    fn glob_supports_star_and_question_mark() {
+        assert!(glob_matches("*", ""));
+        assert!(glob_matches("@*:example.com", "@alice:example.com"));
+        assert!(!glob_matches("@*:example.com", "@alice:example.org"));
+        assert!(glob_matches("!ro?m*", "!room:example.com"));
+        assert!(glob_matches("*a*b", "xaxxab"));
+        assert!(!glob_matches("a", "ab"));
+    }
+}
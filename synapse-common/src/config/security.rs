@@ -235,3 +235,31 @@ impl Default for AdminRegistrationConfig {
         }
     }
 }
+
+/// Creation of the first admin account when the server starts without one.
+///
+/// With `username` and `password_file` set, that account is created as an
+/// admin. Otherwise a one-time setup token is logged at startup, which
+/// `POST /_synapse/admin/v1/bootstrap` exchanges for an admin account.
+/// Nothing happens once any active admin exists.
+#[derive(Debug, Clone, Deserialize)]
+pub struct AdminBootstrapConfig {
+    #[serde(default = "default_admin_bootstrap_enabled")]
+    pub enabled: bool,
+    /// Localpart of the admin to create.
+    #[serde(default)]
+    pub username: Option<String>,
+    /// File holding the admin's password. Trailing newlines are ignored.
+    #[serde(default)]
+    pub password_file: Option<String>,
+}
+
+fn default_admin_bootstrap_enabled() -> bool {
+    true
+}
+
+impl Default for AdminBootstrapConfig {
+    fn default() -> Self {
+        Self { enabled: default_admin_bootstrap_enabled(), username: None, password_file: None }
+    }
+}
@@ -189,10 +189,20 @@ pub struct ServerConfig {
     #[serde(default = "default_true")]
     pub autocreate_auto_join_rooms: bool,
 
-    /// 默认启用加密的房间类型（空表示不默认启用）
+    /// 自动加入房间的管理用户 localpart：由该用户创建不存在的房间，
+    /// 并在新用户无法直接加入时代为邀请；留空则由新用户自行创建
+    #[serde(default)]
+    pub auto_join_mxid_localpart: Option<String>,
+
+    /// 默认启用加密的房间类型：`all` 为所有新房间，`invite` 为私聊预设
+    /// （`private_chat`、`trusted_private_chat`）创建的房间，`off` 或留空表示不默认启用
     #[serde(default)]
     pub encryption_enabled_by_default_for_room_type: Option<String>,
 
+    /// 是否拒绝本地客户端向已启用加密的房间发送明文 `m.room.message`
+    #[serde(default)]
+    pub reject_unencrypted_messages_in_encrypted_rooms: bool,
+
     /// 应用服务配置文件路径列表
     #[serde(default)]
     pub app_service_config_files: Vec<String>,
@@ -229,6 +239,69 @@ pub struct ServerConfig {
     /// 与 `refresh_token_lifetime` 字段独立。
     #[serde(default = "default_refresh_token_ttl_secs")]
     pub refresh_token_ttl_secs: i64,
+
+    /// 是否在 500 响应中返回内部错误详情。
+    ///
+    /// 默认 `false`：客户端只收到通用的 `M_UNKNOWN` 消息和 `request_id`，
+    /// 原始数据库/IO 错误仅写入日志。仅用于本地开发调试，切勿在生产环境开启。
+    #[serde(default)]
+    pub expose_internal_errors: bool,
+
+    /// HTTP 监听器的连接超时与并发连接上限，用于抵御 slow-loris 类攻击。
+    #[serde(default)]
+    pub http: HttpServerConfig,
+}
+
+/// HTTP 监听器连接配置。
+///
+/// 作用于客户端、联邦与 Prometheus 三个监听器，每个监听器分别计算连接数。
+#[derive(Debug, Clone, Deserialize)]
+pub struct HttpServerConfig {
+    /// 读取请求头超时（秒）。
+    ///
+    /// 客户端须在此时间内发送完整的请求头，否则连接被关闭；keep-alive
+    /// 连接等待下一个请求的空闲时间同样受此限制。0 表示不限制。
+    #[serde(default = "default_header_read_timeout_secs")]
+    pub header_read_timeout_secs: u64,
+
+    /// 写超时（秒）。
+    ///
+    /// 响应写入因客户端不读取而停滞超过此时间时关闭连接。0 表示不限制。
+    #[serde(default = "default_write_timeout_secs")]
+    pub write_timeout_secs: u64,
+
+    /// 是否启用 HTTP/1.1 keep-alive
+    #[serde(default = "default_true")]
+    pub keep_alive: bool,
+
+    /// 每个监听器的最大并发连接数。
+    ///
+    /// 达到上限后暂停接受新连接，新连接在内核队列中等待已有连接关闭。0 表示不限制。
+    #[serde(default = "default_max_connections")]
+    pub max_connections: usize,
+}
+
+fn default_header_read_timeout_secs() -> u64 {
+    30
+}
+
+fn default_write_timeout_secs() -> u64 {
+    60
+}
+
+fn default_max_connections() -> usize {
+    10_000
+}
+
+impl Default for HttpServerConfig {
+    fn default() -> Self {
+        Self {
+            header_read_timeout_secs: default_header_read_timeout_secs(),
+            write_timeout_secs: default_write_timeout_secs(),
+            keep_alive: true,
+            max_connections: default_max_connections(),
+        }
+    }
 }
 
 fn default_suppress_key_server_warning() -> bool {
@@ -305,6 +378,19 @@ impl ServerConfig {
         format!("http://{}:{}", host, self.port)
     }
 
+    /// 按 `encryption_enabled_by_default_for_room_type` 判断以给定预设新建的
+    /// 房间是否默认启用加密。未指定预设时按规范视为 `private_chat`，公开房间除外。
+    pub fn encrypts_new_rooms_by_default(&self, preset: Option<&str>, is_public: bool) -> bool {
+        match self.encryption_enabled_by_default_for_room_type.as_deref() {
+            Some("all") => true,
+            Some("invite") => match preset {
+                Some(preset) => matches!(preset, "private_chat" | "trusted_private_chat"),
+                None => !is_public,
+            },
+            _ => false,
+        }
+    }
+
     /// 获取事件 ID 生成用的服务器名称。
     ///
     /// 这是 generate_event_id 函数使用的服务器名称。
@@ -346,6 +432,36 @@ mod tests {
         assert_eq!(config.get_event_server_name(), "events.example.com");
     }
 
+    #[test]
+    fn http_config_defaults_apply_to_partial_yaml() {
+        let http: HttpServerConfig =
+            serde_yaml::from_str("max_connections: 500\n").expect("http YAML should deserialize");
+        assert_eq!(http.max_connections, 500);
+        assert_eq!(http.header_read_timeout_secs, 30);
+        assert_eq!(http.write_timeout_secs, 60);
+        assert!(http.keep_alive);
+        assert_eq!(make_config().http.max_connections, 10_000);
+    }
+
+    #[test]
+    fn encrypts_new_rooms_by_default_follows_room_type_setting() {
+        let mut config = make_config();
+        assert!(!config.encrypts_new_rooms_by_default(Some("private_chat"), false));
+
+        config.encryption_enabled_by_default_for_room_type = Some("invite".into());
+        assert!(config.encrypts_new_rooms_by_default(Some("private_chat"), false));
+        assert!(config.encrypts_new_rooms_by_default(Some("trusted_private_chat"), false));
+        assert!(config.encrypts_new_rooms_by_default(None, false));
+        assert!(!config.encrypts_new_rooms_by_default(None, true));
+        assert!(!config.encrypts_new_rooms_by_default(Some("public_chat"), false));
+
+        config.encryption_enabled_by_default_for_room_type = Some("all".into());
+        assert!(config.encrypts_new_rooms_by_default(Some("public_chat"), true));
+
+        config.encryption_enabled_by_default_for_room_type = Some("off".into());
+        assert!(!config.encrypts_new_rooms_by_default(Some("private_chat"), false));
+    }
+
     #[test]
     fn get_public_baseurl_uses_configured_value() {
         let mut config = make_config();
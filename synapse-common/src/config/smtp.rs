@@ -1,4 +1,6 @@
 use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::PathBuf;
 
 /// SMTP邮件服务配置。
 ///
@@ -32,6 +34,179 @@ pub struct SmtpConfig {
     /// 速率限制配置
     #[serde(default)]
     pub rate_limit: SmtpRateLimitConfig,
+    /// 邮件模板与品牌配置
+    #[serde(default)]
+    pub templates: EmailTemplatesConfig,
+    /// Connection security. Unset keeps the behaviour of `tls`: `starttls`
+    /// when it is true, `none` otherwise.
+    #[serde(default)]
+    pub security: Option<SmtpSecurity>,
+    /// 连接池配置
+    #[serde(default)]
+    pub pool: SmtpPoolConfig,
+    /// DKIM签名配置，未设置时不签名
+    #[serde(default)]
+    pub dkim: Option<SmtpDkimConfig>,
+    /// 发送失败重试配置
+    #[serde(default)]
+    pub retry: SmtpRetryConfig,
+}
+
+impl SmtpConfig {
+    pub fn effective_security(&self) -> SmtpSecurity {
+        self.security.unwrap_or(if self.tls { SmtpSecurity::StartTls } else { SmtpSecurity::None })
+    }
+}
+
+/// How the SMTP connection is secured.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SmtpSecurity {
+    /// Plain text. Only for a relay on localhost or a trusted network.
+    None,
+    /// Connect in plain text and require an upgrade with STARTTLS
+    /// (usually port 587).
+    #[serde(rename = "starttls")]
+    StartTls,
+    /// TLS from the first byte (usually port 465).
+    #[serde(rename = "implicit_tls")]
+    ImplicitTls,
+}
+
+/// Pooled SMTP connections, reused across emails.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpPoolConfig {
+    #[serde(default = "default_smtp_pool_max_size")]
+    pub max_size: u32,
+    /// Seconds an unused connection is kept open.
+    #[serde(default = "default_smtp_pool_idle_timeout_secs")]
+    pub idle_timeout_secs: u64,
+}
+
+impl Default for SmtpPoolConfig {
+    fn default() -> Self {
+        Self { max_size: default_smtp_pool_max_size(), idle_timeout_secs: default_smtp_pool_idle_timeout_secs() }
+    }
+}
+
+/// DKIM signing of outgoing email. The public key must be published at
+/// `<selector>._domainkey.<domain>`.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpDkimConfig {
+    pub selector: String,
+    pub domain: String,
+    /// PEM private key: PKCS#1 for `rsa`, the base64 seed for `ed25519`.
+    pub private_key_path: PathBuf,
+    #[serde(default)]
+    pub algorithm: DkimAlgorithm,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DkimAlgorithm {
+    #[default]
+    Rsa,
+    Ed25519,
+}
+
+/// Retries of emails that failed with a transient error (connection
+/// refused, 4xx replies). They are kept in the database between attempts,
+/// so a restart does not drop them.
+#[derive(Debug, Clone, Deserialize)]
+pub struct SmtpRetryConfig {
+    /// Attempts, including the first, before the email is marked failed.
+    #[serde(default = "default_smtp_retry_max_attempts")]
+    pub max_attempts: u32,
+    #[serde(default = "default_smtp_retry_initial_backoff_secs")]
+    pub initial_backoff_secs: u64,
+    #[serde(default = "default_smtp_retry_max_backoff_secs")]
+    pub max_backoff_secs: u64,
+}
+
+impl Default for SmtpRetryConfig {
+    fn default() -> Self {
+        Self {
+            max_attempts: default_smtp_retry_max_attempts(),
+            initial_backoff_secs: default_smtp_retry_initial_backoff_secs(),
+            max_backoff_secs: default_smtp_retry_max_backoff_secs(),
+        }
+    }
+}
+
+impl SmtpRetryConfig {
+    /// Delay before the next attempt after `attempts` failed ones: doubles
+    /// from `initial_backoff_secs`, capped at `max_backoff_secs`.
+    pub fn backoff_secs(&self, attempts: u32) -> u64 {
+        let doublings = attempts.saturating_sub(1).min(32);
+        self.initial_backoff_secs.saturating_mul(1u64 << doublings).min(self.max_backoff_secs)
+    }
+}
+
+fn default_smtp_pool_max_size() -> u32 {
+    10
+}
+
+fn default_smtp_pool_idle_timeout_secs() -> u64 {
+    60
+}
+
+fn default_smtp_retry_max_attempts() -> u32 {
+    8
+}
+
+fn default_smtp_retry_initial_backoff_secs() -> u64 {
+    30
+}
+
+fn default_smtp_retry_max_backoff_secs() -> u64 {
+    3600
+}
+
+/// Templates for outgoing email.
+///
+/// Every email has a built-in English template. `template_dir` can override
+/// them per language: `<template_dir>/<locale>/<name>.txt` holds the body
+/// and `<name>.subject` the subject line, with files directly in
+/// `template_dir` applying to every language. The locale is picked from the
+/// request's `Accept-Language` where there is one, falling back to
+/// `default_locale`. Templates are `verification`, `password_reset`,
+/// `welcome`, `notification` and `new_device`.
+///
+/// `{{app_name}}`, `{{server_name}}`, `{{public_baseurl}}`,
+/// `{{support_email}}` and each key of `branding` can be used in any
+/// template, alongside the variables of the email itself.
+#[derive(Debug, Clone, Deserialize)]
+pub struct EmailTemplatesConfig {
+    #[serde(default)]
+    pub template_dir: Option<PathBuf>,
+    #[serde(default = "default_email_locale")]
+    pub default_locale: String,
+    #[serde(default = "default_email_app_name")]
+    pub app_name: String,
+    #[serde(default)]
+    pub support_email: Option<String>,
+    #[serde(default)]
+    pub branding: HashMap<String, String>,
+}
+
+impl Default for EmailTemplatesConfig {
+    fn default() -> Self {
+        Self {
+            template_dir: None,
+            default_locale: default_email_locale(),
+            app_name: default_email_app_name(),
+            support_email: None,
+            branding: HashMap::new(),
+        }
+    }
+}
+
+fn default_email_locale() -> String {
+    "en".to_string()
+}
+
+fn default_email_app_name() -> String {
+    "Matrix".to_string()
 }
 
 fn default_smtp_enabled() -> bool {
@@ -111,4 +286,24 @@ mod tests {
         assert_eq!(default_smtp_per_minute(), 3);
         assert_eq!(default_smtp_per_hour(), 10);
     }
+
+    #[test]
+    fn test_effective_security_falls_back_to_tls_flag() {
+        let mut config = SmtpConfig { tls: true, ..Default::default() };
+        assert_eq!(config.effective_security(), SmtpSecurity::StartTls);
+        config.tls = false;
+        assert_eq!(config.effective_security(), SmtpSecurity::None);
+        config.security = Some(SmtpSecurity::ImplicitTls);
+        assert_eq!(config.effective_security(), SmtpSecurity::ImplicitTls);
+    }
+
+    #[test]
+    fn test_retry_backoff_doubles_up_to_cap() {
+        let retry = SmtpRetryConfig::default();
+        assert_eq!(retry.backoff_secs(1), 30);
+        assert_eq!(retry.backoff_secs(2), 60);
+        assert_eq!(retry.backoff_secs(3), 120);
+        assert_eq!(retry.backoff_secs(8), 3600);
+        assert_eq!(retry.backoff_secs(u32::MAX), 3600);
+    }
 }
@@ -0,0 +1,96 @@
+//! Test-harness mode for running the Complement / Sytest suites locally.
+//!
+//! Enabled by setting `SYNAPSE_TEST_HARNESS` to `complement`, `1` or `true`.
+//! The mode exists only so the suites can provision users and exercise
+//! retention within their timeouts; it must never be set in production.
+//! When active it:
+//!
+//! - enables the shared-secret `/_synapse/admin/v1/register` endpoint with a
+//!   fixed, well-known secret and lifts its localhost/production restrictions
+//!   (Complement reaches the server over a container network),
+//! - shortens the retention, lifecycle and background task intervals,
+//! - disables request rate limiting, which the suites trip constantly,
+//! - lifts the egress blacklist, since the suites run their application
+//!   services and push gateways on the container network.
+
+use super::Config;
+
+/// Environment variable that turns the test-harness mode on.
+pub const TEST_HARNESS_ENV: &str = "SYNAPSE_TEST_HARNESS";
+
+/// Registration shared secret used in test-harness mode. Matches the value the
+/// Complement homeserver images are configured with.
+pub const TEST_HARNESS_SHARED_SECRET: &str = "complement";
+
+/// Interval (seconds) used for retention and background jobs in test-harness mode.
+pub const TEST_HARNESS_JOB_INTERVAL_SECS: u64 = 1;
+
+/// Whether `value` (the content of [`TEST_HARNESS_ENV`]) enables the mode.
+pub fn is_test_harness_value(value: &str) -> bool {
+    matches!(value.trim().to_ascii_lowercase().as_str(), "complement" | "1" | "true")
+}
+
+/// Whether test-harness mode is requested by the current environment.
+pub fn test_harness_enabled() -> bool {
+    std::env::var(TEST_HARNESS_ENV).is_ok_and(|value| is_test_harness_value(&value))
+}
+
+impl Config {
+    /// Apply the test-harness overrides described in the module docs.
+    pub fn apply_test_harness_mode(&mut self) {
+        self.admin_registration.enabled = true;
+        self.admin_registration.shared_secret = TEST_HARNESS_SHARED_SECRET.to_string();
+        self.admin_registration.allow_external_access = true;
+        self.admin_registration.production_only = false;
+        self.admin_registration.ip_whitelist.clear();
+        self.admin_registration.require_captcha = false;
+        self.admin_registration.require_manual_approval = false;
+        self.server.registration_shared_secret = Some(TEST_HARNESS_SHARED_SECRET.to_string());
+
+        self.retention.lifecycle_cleanup_interval_secs = TEST_HARNESS_JOB_INTERVAL_SECS;
+        for job in &mut self.retention.purge_jobs {
+            job.interval = TEST_HARNESS_JOB_INTERVAL_SECS;
+        }
+        self.server.background_tasks_interval = TEST_HARNESS_JOB_INTERVAL_SECS;
+        self.server.dehydrated_device_cleanup_interval_secs = TEST_HARNESS_JOB_INTERVAL_SECS;
+
+        self.rate_limit.enabled = false;
+        self.rate_limit.room_send.enabled = false;
+
+        self.egress.ip_range_blacklist.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recognises_harness_values() {
+        assert!(is_test_harness_value("complement"));
+        assert!(is_test_harness_value(" TRUE "));
+        assert!(is_test_harness_value("1"));
+        assert!(!is_test_harness_value("0"));
+        assert!(!is_test_harness_value(""));
+        assert!(!is_test_harness_value("production"));
+    }
+
+    #[test]
+    fn apply_enables_shared_secret_registration_and_fast_jobs() {
+        let mut config = Config::default();
+        config.security.secret = "a-very-secure-secret-that-is-long-enough".to_string();
+        config.admin_registration.ip_whitelist = vec!["10.0.0.0/8".to_string()];
+        config.apply_test_harness_mode();
+
+        assert!(config.admin_registration.enabled);
+        assert_eq!(config.admin_registration.shared_secret, TEST_HARNESS_SHARED_SECRET);
+        assert!(config.admin_registration.allow_external_access);
+        assert!(!config.admin_registration.production_only);
+        assert!(config.admin_registration.ip_whitelist.is_empty());
+        assert_eq!(config.server.registration_shared_secret.as_deref(), Some(TEST_HARNESS_SHARED_SECRET));
+        assert_eq!(config.retention.lifecycle_cleanup_interval_secs, TEST_HARNESS_JOB_INTERVAL_SECS);
+        assert!(config.retention.purge_jobs.iter().all(|job| job.interval == TEST_HARNESS_JOB_INTERVAL_SECS));
+        assert!(!config.rate_limit.enabled);
+        assert!(config.validate().is_ok());
+    }
+}
@@ -20,6 +20,23 @@ impl Config {
             );
         }
 
+        if let Some(room_type) = self.server.encryption_enabled_by_default_for_room_type.as_deref() {
+            if !matches!(room_type, "all" | "invite" | "off") {
+                return Err(format!(
+                    "server.encryption_enabled_by_default_for_room_type must be one of 'all', 'invite' or 'off', \
+                     got '{room_type}'."
+                ));
+            }
+        }
+
+        if self.saml.enabled {
+            self.saml.validate_user_mapping().map_err(|e| format!("saml: {e}"))?;
+        }
+
+        if self.ldap.enabled {
+            self.ldap.validate().map_err(|e| format!("ldap: {e}"))?;
+        }
+
         if self.security.secret.is_empty() {
             return Err("security.secret is not configured. \
                  Please set security.secret in your configuration file."
@@ -152,6 +169,17 @@ mod tests {
         assert!(config.validate().is_ok());
     }
 
+    #[test]
+    fn validate_rejects_unknown_default_encryption_room_type() {
+        let mut config = valid_config();
+        config.server.encryption_enabled_by_default_for_room_type = Some("private".to_string());
+        let err = config.validate().unwrap_err();
+        assert!(err.contains("encryption_enabled_by_default_for_room_type"));
+
+        config.server.encryption_enabled_by_default_for_room_type = Some("invite".to_string());
+        assert!(config.validate().is_ok());
+    }
+
     #[test]
     fn validate_short_secret_error_includes_actual_length() {
         let mut config = Config::default();
@@ -0,0 +1,138 @@
+use serde::Deserialize;
+use std::collections::HashMap;
+
+// ============================================================================
+// SECTION: Welcome Message Configuration
+// ============================================================================
+
+fn default_welcome_msgtype() -> String {
+    "m.text".to_string()
+}
+
+fn default_welcome_locale() -> String {
+    "en".to_string()
+}
+
+/// Onboarding message sent to newly registered users as a server notice.
+///
+/// `messages` maps a language tag (`en`, `zh-cn`, ...) to a template. The
+/// template is picked from the registration request's `Accept-Language`,
+/// falling back to `default_locale`. `{{user_id}}`, `{{localpart}}`,
+/// `{{displayname}}` and `{{server_name}}` are substituted.
+#[derive(Debug, Clone, Deserialize)]
+pub struct WelcomeConfig {
+    #[serde(default)]
+    pub enabled: bool,
+
+    #[serde(default = "default_welcome_msgtype")]
+    pub msgtype: String,
+
+    #[serde(default = "default_welcome_locale")]
+    pub default_locale: String,
+
+    #[serde(default)]
+    pub messages: HashMap<String, String>,
+}
+
+impl Default for WelcomeConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            msgtype: default_welcome_msgtype(),
+            default_locale: default_welcome_locale(),
+            messages: HashMap::new(),
+        }
+    }
+}
+
+impl WelcomeConfig {
+    /// Template for the best match in an `Accept-Language` value: an exact
+    /// tag first, then its primary subtag, in preference order.
+    pub fn template_for(&self, accept_language: Option<&str>) -> Option<&str> {
+        accept_language_tags(accept_language)
+            .into_iter()
+            .find_map(|tag| {
+                let primary = tag.split('-').next().unwrap_or_default().to_string();
+                self.message(&tag).or_else(|| self.message(&primary))
+            })
+            .or_else(|| self.message(&self.default_locale.to_ascii_lowercase()))
+    }
+
+    fn message(&self, locale: &str) -> Option<&str> {
+        self.messages.iter().find(|(key, _)| key.eq_ignore_ascii_case(locale)).map(|(_, template)| template.as_str())
+    }
+}
+
+/// Language tags of an `Accept-Language` value, lowercased, most preferred
+/// first. Wildcards and tags with `q=0` are dropped.
+pub fn accept_language_tags(accept_language: Option<&str>) -> Vec<String> {
+    let mut preferred: Vec<(&str, f32)> = accept_language
+        .unwrap_or_default()
+        .split(',')
+        .filter_map(|entry| {
+            let mut parts = entry.split(';');
+            let tag = parts.next()?.trim();
+            let quality = parts
+                .find_map(|param| param.trim().strip_prefix("q="))
+                .and_then(|q| q.parse::<f32>().ok())
+                .unwrap_or(1.0);
+            (!tag.is_empty() && tag != "*" && quality > 0.0).then_some((tag, quality))
+        })
+        .collect();
+    preferred.sort_by(|a, b| b.1.total_cmp(&a.1));
+    preferred.into_iter().map(|(tag, _)| tag.to_ascii_lowercase()).collect()
+}
+
+/// Replace `{{name}}` placeholders in `template`.
+pub fn render_welcome_template(template: &str, variables: &[(&str, &str)]) -> String {
+    variables
+        .iter()
+        .fold(template.to_string(), |rendered, (name, value)| rendered.replace(&format!("{{{{{name}}}}}"), value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> WelcomeConfig {
+        WelcomeConfig {
+            enabled: true,
+            messages: HashMap::from([
+                ("en".to_string(), "Welcome, {{displayname}}!".to_string()),
+                ("zh-CN".to_string(), "欢迎，{{displayname}}！".to_string()),
+                ("de".to_string(), "Willkommen, {{displayname}}!".to_string()),
+            ]),
+            ..WelcomeConfig::default()
+        }
+    }
+
+    #[test]
+    fn template_for_prefers_exact_then_primary_tag() {
+        let config = config();
+        assert_eq!(config.template_for(Some("zh-CN,zh;q=0.9")), Some("欢迎，{{displayname}}！"));
+        assert_eq!(config.template_for(Some("de-AT")), Some("Willkommen, {{displayname}}!"));
+        assert_eq!(config.template_for(Some("fr;q=0.9, de;q=0.8")), Some("Willkommen, {{displayname}}!"));
+    }
+
+    #[test]
+    fn template_for_falls_back_to_default_locale() {
+        let config = config();
+        assert_eq!(config.template_for(Some("fr")), Some("Welcome, {{displayname}}!"));
+        assert_eq!(config.template_for(None), Some("Welcome, {{displayname}}!"));
+    }
+
+    #[test]
+    fn accept_language_tags_orders_by_quality() {
+        assert_eq!(accept_language_tags(Some("fr;q=0.5, de-AT, *, en;q=0")), ["de-at", "fr"]);
+        assert!(accept_language_tags(None).is_empty());
+    }
+
+    #[test]
+    fn render_substitutes_placeholders() {
+        let rendered = render_welcome_template(
+            "Hi {{displayname}} ({{user_id}}) on {{server_name}}",
+            &[("displayname", "Alice"), ("user_id", "@alice:hs"), ("server_name", "hs")],
+        );
+        assert_eq!(rendered, "Hi Alice (@alice:hs) on hs");
+    }
+}
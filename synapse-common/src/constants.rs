@@ -85,6 +85,14 @@ pub const MAX_DEVICE_ID_LENGTH: usize = 255;
 /// Maximum room alias length
 pub const MAX_ROOM_ALIAS_LENGTH: usize = 255;
 
+// ============================================================================
+// Account Data Types
+// ============================================================================
+
+/// Global account data controlling the user's user-directory listing;
+/// `{"hidden": true}` keeps the user out of directory search results.
+pub const USER_DIRECTORY_ACCOUNT_DATA_TYPE: &str = "org.synapse_rust.user_directory";
+
 // ============================================================================
 // File Size Limits
 // ============================================================================
@@ -0,0 +1,151 @@
+//! Egress policy for URLs that users or registered services choose.
+//!
+//! URL previews, push gateways, application services and identity servers
+//! all make the server fetch an address someone else picked, which makes
+//! each of them a way to reach internal services and cloud metadata
+//! endpoints. Before such a request, the target host is resolved and every
+//! address checked against the configured ranges.
+//!
+//! The policy is installed once at startup. Until then, and in tests that
+//! never install it, nothing is blocked.
+
+use crate::config::EgressConfig;
+use ipnet::IpNet;
+use std::net::IpAddr;
+use std::sync::OnceLock;
+
+static GLOBAL_POLICY: OnceLock<EgressPolicy> = OnceLock::new();
+
+#[derive(Debug, thiserror::Error)]
+pub enum EgressError {
+    #[error("Invalid egress configuration: {0}")]
+    InvalidConfig(String),
+    #[error("Invalid URL: {0}")]
+    InvalidUrl(String),
+    #[error("{host} resolves to blocked address {addr}")]
+    Blocked { host: String, addr: IpAddr },
+}
+
+#[derive(Debug, Clone)]
+pub struct EgressPolicy {
+    blacklist: Vec<IpNet>,
+    whitelist: Vec<IpNet>,
+}
+
+impl EgressPolicy {
+    pub fn from_config(config: &EgressConfig) -> Result<Self, EgressError> {
+        Ok(Self {
+            blacklist: parse_ranges("ip_range_blacklist", &config.ip_range_blacklist)?,
+            whitelist: parse_ranges("ip_range_whitelist", &config.ip_range_whitelist)?,
+        })
+    }
+
+    pub fn is_allowed(&self, ip: IpAddr) -> bool {
+        // `::ffff:127.0.0.1` reaches the same host as `127.0.0.1`.
+        let ip = ip.to_canonical();
+        !self.blacklist.iter().any(|net| net.contains(&ip)) || self.whitelist.iter().any(|net| net.contains(&ip))
+    }
+
+    /// Resolves `host` and fails if any of its addresses is blocked. A host
+    /// that does not resolve is let through; the request itself fails then.
+    pub async fn check_host(&self, host: &str, port: u16) -> Result<(), EgressError> {
+        if self.blacklist.is_empty() {
+            return Ok(());
+        }
+        let host = host.trim_start_matches('[').trim_end_matches(']');
+        if let Ok(addr) = host.parse::<IpAddr>() {
+            return self.check_addr(host, addr);
+        }
+        let Ok(addrs) = tokio::net::lookup_host((host, port)).await else {
+            return Ok(());
+        };
+        for addr in addrs {
+            self.check_addr(host, addr.ip())?;
+        }
+        Ok(())
+    }
+
+    pub async fn check_url(&self, url: &str) -> Result<(), EgressError> {
+        let parsed = url::Url::parse(url).map_err(|e| EgressError::InvalidUrl(e.to_string()))?;
+        let host = parsed.host_str().ok_or_else(|| EgressError::InvalidUrl(format!("{url} has no host")))?;
+        self.check_host(host, parsed.port_or_known_default().unwrap_or(443)).await
+    }
+
+    fn check_addr(&self, host: &str, addr: IpAddr) -> Result<(), EgressError> {
+        if self.is_allowed(addr) {
+            Ok(())
+        } else {
+            Err(EgressError::Blocked { host: host.to_string(), addr })
+        }
+    }
+}
+
+fn parse_ranges(field: &str, ranges: &[String]) -> Result<Vec<IpNet>, EgressError> {
+    ranges
+        .iter()
+        .map(|range| {
+            range
+                .parse::<IpNet>()
+                .or_else(|_| range.parse::<IpAddr>().map(IpNet::from))
+                .map_err(|_| EgressError::InvalidConfig(format!("{field}: '{range}' is not an IP address or range")))
+        })
+        .collect()
+}
+
+/// Installs the process-wide policy. Later calls are ignored.
+pub fn initialize_global(config: &EgressConfig) -> Result<(), EgressError> {
+    let _ = GLOBAL_POLICY.set(EgressPolicy::from_config(config)?);
+    Ok(())
+}
+
+/// The installed policy, or one that allows everything.
+pub fn policy() -> &'static EgressPolicy {
+    static ALLOW_ALL: EgressPolicy = EgressPolicy { blacklist: Vec::new(), whitelist: Vec::new() };
+    GLOBAL_POLICY.get().unwrap_or(&ALLOW_ALL)
+}
+
+/// Shorthand for `policy().check_url(url)`.
+pub async fn check_url(url: &str) -> Result<(), EgressError> {
+    policy().check_url(url).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn default_policy() -> EgressPolicy {
+        EgressPolicy::from_config(&EgressConfig::default()).unwrap()
+    }
+
+    #[test]
+    fn private_and_metadata_addresses_are_blocked() {
+        let policy = default_policy();
+        for ip in
+            ["127.0.0.1", "10.1.2.3", "192.168.1.1", "169.254.169.254", "::1", "fd00:ec2::254", "::ffff:127.0.0.1"]
+        {
+            assert!(!policy.is_allowed(ip.parse().unwrap()), "{ip} should be blocked");
+        }
+        for ip in ["93.184.216.34", "2606:4700::1111"] {
+            assert!(policy.is_allowed(ip.parse().unwrap()), "{ip} should be allowed");
+        }
+    }
+
+    #[tokio::test]
+    async fn whitelist_overrides_blacklist() {
+        let config = EgressConfig { ip_range_whitelist: vec!["10.0.5.0/24".to_string()], ..Default::default() };
+        let policy = EgressPolicy::from_config(&config).unwrap();
+        assert!(policy.check_url("http://10.0.5.7:9000/_matrix/app/v1").await.is_ok());
+        assert!(matches!(
+            policy.check_url("http://10.0.6.7:9000/_matrix/app/v1").await,
+            Err(EgressError::Blocked { .. })
+        ));
+        assert!(matches!(policy.check_url("https://[::1]/").await, Err(EgressError::Blocked { .. })));
+    }
+
+    #[test]
+    fn malformed_range_is_rejected() {
+        let config = EgressConfig { ip_range_whitelist: vec!["10.0.0.0/33".to_string()], ..Default::default() };
+        let err = EgressPolicy::from_config(&config).unwrap_err();
+        assert!(err.to_string().contains("ip_range_whitelist"));
+    }
+}
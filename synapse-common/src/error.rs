@@ -1,3 +1,4 @@
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::{Arc, OnceLock};
 
 use axum::{
@@ -16,6 +17,17 @@ pub fn init_error_metrics(collector: Arc<MetricsCollector>) {
     let _ = ERROR_METRICS.set(collector);
 }
 
+static EXPOSE_INTERNAL_ERRORS: AtomicBool = AtomicBool::new(false);
+
+/// Message clients receive for internal errors unless details are exposed.
+pub const INTERNAL_ERROR_MESSAGE: &str = "An internal error occurred";
+
+/// Include internal error details in client responses. Set from
+/// `server.expose_internal_errors`; meant for local development only.
+pub fn set_expose_internal_errors(expose: bool) {
+    EXPOSE_INTERNAL_ERRORS.store(expose, Ordering::Relaxed);
+}
+
 // ---------------------------------------------------------------------------
 // MatrixErrorCode — Matrix spec error codes
 // ---------------------------------------------------------------------------
@@ -936,8 +948,14 @@ impl ApiError {
         self.code.as_str()
     }
 
-    /// Returns the user-facing error message.
+    /// Returns the user-facing error message. Internal errors are logged in
+    /// full (inside the request span, so the log line carries the request ID)
+    /// and replaced by [`INTERNAL_ERROR_MESSAGE`].
     pub fn message(&self) -> String {
+        self.client_message(EXPOSE_INTERNAL_ERRORS.load(Ordering::Relaxed))
+    }
+
+    fn client_message(&self, expose_internal: bool) -> String {
         match self.kind {
             ApiErrorKind::Internal => {
                 tracing::error!(
@@ -947,7 +965,11 @@ impl ApiError {
                     source = ?self.source,
                     "Internal error returned to client"
                 );
-                "An internal error occurred".to_string()
+                if expose_internal {
+                    format!("{INTERNAL_ERROR_MESSAGE}: {}", self.message)
+                } else {
+                    INTERNAL_ERROR_MESSAGE.to_string()
+                }
             }
             _ => self.message.clone(),
         }
@@ -1200,7 +1222,13 @@ impl From<std::num::ParseIntError> for ApiError {
 
 impl From<std::io::Error> for ApiError {
     fn from(err: std::io::Error) -> Self {
-        ApiError::internal(err.to_string())
+        ApiError::internal_with_log("I/O error", &err)
+    }
+}
+
+impl From<crate::column_encryption::ColumnEncryptionError> for ApiError {
+    fn from(err: crate::column_encryption::ColumnEncryptionError) -> Self {
+        ApiError::internal_with_log("Column encryption error", &err)
     }
 }
 
@@ -1616,6 +1644,14 @@ mod tests {
         assert_eq!(err.message(), "An internal error occurred");
     }
 
+    #[test]
+    fn test_internal_details_only_exposed_in_dev_mode() {
+        let err = ApiError::from(std::io::Error::other("disk /var/lib/synapse full"));
+        assert_eq!(err.client_message(false), INTERNAL_ERROR_MESSAGE);
+        assert!(err.client_message(true).contains("disk /var/lib/synapse full"));
+        assert!(err.internal_message().contains("I/O error"));
+    }
+
     #[test]
     fn test_api_error_message_for_non_internal() {
         let err = ApiError::bad_request("bad input");
@@ -1,5 +1,8 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::time::Duration;
+
+use crate::config::HealthConfig;
 
 /// 健康状态响应结构。
 ///
@@ -32,7 +35,7 @@ pub struct CheckResult {
 /// 健康检查级别。
 ///
 /// 用于区分存活检查和就绪检查。
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum HealthCheckLevel {
     /// 存活检查：服务是否正在运行
     Liveness,
@@ -49,6 +52,10 @@ pub trait HealthCheck: Send + Sync {
     async fn check(&self) -> CheckResult;
     /// 获取检查名称。
     fn name(&self) -> &str;
+    /// 检查所属级别。存活探针只运行 `Liveness` 级别的检查，就绪探针运行全部检查。
+    fn level(&self) -> HealthCheckLevel {
+        HealthCheckLevel::Readiness
+    }
 }
 
 /// 数据库健康检查。
@@ -94,6 +101,42 @@ impl HealthCheck for DatabaseHealthCheck {
     }
 }
 
+/// 数据库迁移健康检查。
+///
+/// 要求 `schema_migrations` 记录表存在、至少记录了一次迁移，且没有失败的迁移。
+pub struct MigrationsHealthCheck {
+    pool: sqlx::PgPool,
+}
+
+impl MigrationsHealthCheck {
+    pub fn new(pool: sqlx::PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait::async_trait]
+impl HealthCheck for MigrationsHealthCheck {
+    async fn check(&self) -> CheckResult {
+        let start = std::time::Instant::now();
+        let counts: Result<(i64, i64), sqlx::Error> =
+            sqlx::query_as("SELECT COUNT(*), COUNT(*) FILTER (WHERE NOT is_success) FROM schema_migrations")
+                .fetch_one(&self.pool)
+                .await;
+
+        let (status, message) = match counts {
+            Ok((0, _)) => ("unhealthy", "No migrations have been applied".to_string()),
+            Ok((applied, 0)) => ("healthy", format!("{applied} migrations applied")),
+            Ok((_, failed)) => ("unhealthy", format!("{failed} migrations failed")),
+            Err(e) => ("unhealthy", format!("Migration state unavailable: {e}")),
+        };
+        CheckResult { status: status.to_string(), message, duration_ms: start.elapsed().as_millis() as u64 }
+    }
+
+    fn name(&self) -> &str {
+        "migrations"
+    }
+}
+
 /// 健康检查器。
 ///
 /// 收集和管理多个健康检查组件，执行综合健康检查。
@@ -102,6 +145,8 @@ pub struct HealthChecker {
     checks: Vec<Box<dyn HealthCheck>>,
     /// 服务器版本
     version: String,
+    /// 降级策略与单项超时
+    policy: HealthConfig,
 }
 
 impl HealthChecker {
@@ -111,7 +156,13 @@ impl HealthChecker {
     ///
     /// * `version` - 服务器版本号
     pub fn new(version: String) -> Self {
-        Self { checks: Vec::new(), version }
+        Self { checks: Vec::new(), version, policy: HealthConfig::default() }
+    }
+
+    /// 使用给定的降级策略。
+    pub fn with_policy(mut self, policy: HealthConfig) -> Self {
+        self.policy = policy;
+        self
     }
 
     /// 添加健康检查组件。
@@ -141,27 +192,53 @@ impl HealthChecker {
         self.perform_checks(HealthCheckLevel::Readiness).await
     }
 
-    async fn perform_checks(&self, _level: HealthCheckLevel) -> HealthStatus {
-        let mut checks = HashMap::new();
-        let mut overall_status = "healthy";
-
-        for check in &self.checks {
-            let result = check.check().await;
-            if result.status != "healthy" {
-                overall_status = "unhealthy";
-            }
-            checks.insert(check.name().to_string(), result);
+    /// 按降级策略判断该状态是否应视为就绪（HTTP 200）。
+    pub fn is_ready(&self, status: &HealthStatus) -> bool {
+        match status.status.as_str() {
+            "healthy" => true,
+            "degraded" => self.policy.ready_when_degraded,
+            _ => false,
         }
+    }
+
+    async fn perform_checks(&self, level: HealthCheckLevel) -> HealthStatus {
+        let timeout = Duration::from_millis(self.policy.check_timeout_ms.max(1));
+        let selected =
+            self.checks.iter().filter(|check| level == HealthCheckLevel::Readiness || check.level() == level);
+        let results = futures::future::join_all(selected.map(|check| async move {
+            let result = tokio::time::timeout(timeout, check.check()).await.unwrap_or_else(|_| CheckResult {
+                status: "unhealthy".to_string(),
+                message: format!("Check timed out after {}ms", timeout.as_millis()),
+                duration_ms: timeout.as_millis() as u64,
+            });
+            (check.name().to_string(), result)
+        }))
+        .await;
 
+        let overall_status = aggregate_status(&self.policy, &results);
         HealthStatus {
             status: overall_status.to_string(),
             version: self.version.clone(),
             timestamp: chrono::Utc::now().timestamp(),
-            checks,
+            checks: results.into_iter().collect(),
         }
     }
 }
 
+/// 汇总各项结果：关键检查 unhealthy 则整体 unhealthy；其余非 healthy 结果
+/// （degraded，或非关键检查失败）使整体降级。
+fn aggregate_status(policy: &HealthConfig, results: &[(String, CheckResult)]) -> &'static str {
+    let mut overall = "healthy";
+    for (name, result) in results {
+        match result.status.as_str() {
+            "healthy" => {}
+            "unhealthy" if !policy.is_non_critical(name) => return "unhealthy",
+            _ => overall = "degraded",
+        }
+    }
+    overall
+}
+
 impl Default for HealthChecker {
     fn default() -> Self {
         Self::new("0.1.0".to_string())
@@ -213,6 +290,77 @@ mod tests {
         assert_eq!(status.version, "0.1.0");
     }
 
+    struct StaticCheck {
+        name: &'static str,
+        status: &'static str,
+        level: HealthCheckLevel,
+        delay: Duration,
+    }
+
+    impl StaticCheck {
+        fn boxed(name: &'static str, status: &'static str) -> Box<dyn HealthCheck> {
+            Box::new(Self { name, status, level: HealthCheckLevel::Readiness, delay: Duration::ZERO })
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl HealthCheck for StaticCheck {
+        async fn check(&self) -> CheckResult {
+            tokio::time::sleep(self.delay).await;
+            CheckResult { status: self.status.to_string(), message: String::new(), duration_ms: 0 }
+        }
+
+        fn name(&self) -> &str {
+            self.name
+        }
+
+        fn level(&self) -> HealthCheckLevel {
+            self.level
+        }
+    }
+
+    #[tokio::test]
+    async fn test_liveness_skips_dependency_checks() {
+        let mut checker = HealthChecker::default();
+        checker.add_check(StaticCheck::boxed("database", "unhealthy"));
+        let live = checker.check_liveness().await;
+        assert_eq!(live.status, "healthy");
+        assert!(live.checks.is_empty());
+
+        let ready = checker.check_readiness().await;
+        assert_eq!(ready.status, "unhealthy");
+        assert!(!checker.is_ready(&ready));
+    }
+
+    #[tokio::test]
+    async fn test_non_critical_failure_degrades_per_policy() {
+        let mut checker = HealthChecker::default();
+        checker.add_check(StaticCheck::boxed("database", "healthy"));
+        checker.add_check(StaticCheck::boxed("cache", "unhealthy"));
+        let ready = checker.check_readiness().await;
+        assert_eq!(ready.status, "degraded");
+        assert!(checker.is_ready(&ready));
+
+        let strict = HealthChecker::default()
+            .with_policy(HealthConfig { ready_when_degraded: false, ..HealthConfig::default() });
+        assert!(!strict.is_ready(&ready));
+    }
+
+    #[tokio::test]
+    async fn test_slow_check_times_out_as_unhealthy() {
+        let mut checker =
+            HealthChecker::default().with_policy(HealthConfig { check_timeout_ms: 10, ..HealthConfig::default() });
+        checker.add_check(Box::new(StaticCheck {
+            name: "federation_key",
+            status: "healthy",
+            level: HealthCheckLevel::Readiness,
+            delay: Duration::from_secs(5),
+        }));
+        let ready = checker.check_readiness().await;
+        assert_eq!(ready.status, "unhealthy");
+        assert!(ready.checks["federation_key"].message.contains("timed out"));
+    }
+
     #[tokio::test]
     async fn test_health_checker_check_readiness() {
         let checker = HealthChecker::default();
@@ -7,11 +7,13 @@ pub mod background_job;
 pub mod canonical_json;
 pub mod claims;
 pub mod collections;
+pub mod column_encryption;
 pub mod concurrency;
 pub mod config;
 pub mod constants;
 pub mod crypto;
 pub mod early_exit;
+pub mod egress;
 pub mod error;
 pub mod event_models;
 pub mod event_utils;
@@ -26,15 +28,18 @@ pub mod media_locator;
 pub mod membership_transition;
 pub mod metrics;
 pub mod nonce_cache;
+pub mod outbound_http;
 pub mod password_hash_pool;
 pub mod rate_limit;
 pub mod rate_limit_config;
 pub mod redaction;
 pub mod regex_cache;
+pub mod room_token;
 pub mod room_versions;
 pub mod sanitizer;
 pub mod security;
 pub mod server_metrics;
+pub mod sso_mapping;
 pub mod task_queue;
 pub mod telemetry_config;
 pub mod time;
@@ -68,13 +73,14 @@ pub use config::{
     default_admin_mfa_allowed_drift_steps, default_admin_rbac_enabled, default_allowed_headers,
     default_allowed_methods, default_cors_max_age, default_dehydrated_device_cleanup_interval_secs,
     default_ui_auth_session_timeout, AdminRegistrationConfig, ApnsConfig, BuiltinOidcConfig, BuiltinOidcUser,
-    CircuitBreakerConfig, Config, ConfigError, ConfigManager, CorsConfig, DatabaseConfig, ExperimentalConfig,
-    FcmConfig, FederationConfig, FederationRateLimitConfig, IdentityConfig, InstanceLocationConfig, LivekitConfig,
-    LoggingConfig, OidcAttributeMapping, OidcConfig, PerformanceConfig, PolicyServerConfig, PostgresFtsConfig,
-    PostgresFtsWeights, PushConfig, RedisConfig, ReplicationConfig, ReplicationHttpConfig, RetentionConfig,
-    RetentionPolicy, RetentionPurgeJob, SamlAttributeMapping, SamlConfig, SearchConfig, SecurityConfig, ServerConfig,
-    SmsConfig, SmtpConfig, SmtpRateLimitConfig, StreamWriters, SyncRateLimitConfig, TranslateConfig, TrustedKeyServer,
-    UrlBlacklistRule, UrlPreviewConfig, VoipConfig, WebPushConfig, WorkerConfig,
+    CircuitBreakerConfig, Config, ConfigError, ConfigManager, ContentFilterAction, ContentFilterConfig, CorsConfig,
+    DatabaseConfig, ExperimentalConfig, FcmConfig, FederationConfig, FederationOutboundConfig,
+    FederationRateLimitConfig, IdentityConfig, InstanceLocationConfig, LivekitConfig, LoggingConfig,
+    OidcAttributeMapping, OidcConfig, PerformanceConfig, PolicyServerConfig, PostgresFtsConfig, PostgresFtsWeights,
+    PushConfig, RedisConfig, ReplicationConfig, ReplicationHttpConfig, RetentionConfig, RetentionPolicy,
+    RetentionPurgeJob, RoomDirectoryConfig, RoomSendRateLimitConfig, SamlAttributeMapping, SamlConfig, SearchConfig,
+    SecurityConfig, ServerConfig, SmsConfig, SmtpConfig, SmtpRateLimitConfig, StreamWriters, SyncRateLimitConfig,
+    TranslateConfig, TrustedKeyServer, UrlBlacklistRule, UrlPreviewConfig, VoipConfig, WebPushConfig, WorkerConfig,
 };
 pub use constants::{
     millis, secs, ADMIN_REGISTER_NONCE_RATE_LIMIT, ADMIN_REGISTER_RATE_LIMIT, BURN_AFTER_READ_DELAY_SECS,
@@ -83,7 +89,8 @@ pub use constants::{
     DEFAULT_REFRESH_TOKEN_EXPIRY_SECS, MAX_DEVICE_ID_LENGTH, MAX_DISPLAY_NAME_LENGTH, MAX_MESSAGE_LENGTH,
     MAX_PAGINATION_LIMIT, MAX_PASSWORD_LENGTH, MAX_REASON_LENGTH, MAX_ROOM_ALIAS_LENGTH, MAX_USERNAME_LENGTH,
     MAX_VOICE_DATA_SIZE, MIN_PAGINATION_LIMIT, MIN_PASSWORD_LENGTH, MIN_USERNAME_LENGTH, SESSION_IDLE_TIMEOUT_SECS,
-    SESSION_MAX_LIFETIME_SECS, TIMESTAMP_WINDOW_SECONDS, TOKEN_BUCKET_CAPACITY, USER_PROFILE_CACHE_TTL,
+    SESSION_MAX_LIFETIME_SECS, TIMESTAMP_WINDOW_SECONDS, TOKEN_BUCKET_CAPACITY, USER_DIRECTORY_ACCOUNT_DATA_TYPE,
+    USER_PROFILE_CACHE_TTL,
 };
 #[cfg(test)]
 pub use crypto::generate_signing_key;
@@ -126,13 +133,16 @@ pub use rate_limit_config::{
     RateLimitRule, SyncRateLimitConfigFile,
 };
 pub use redaction::{
-    allowed_content_keys, extract_redacts, redact_content, redact_event_for_hash, CANONICAL_JSON_TOP_LEVEL_FIELDS,
+    allowed_content_keys, allowed_content_keys_for_room_version, extract_redacts, redact_content,
+    redact_content_for_room_version, redact_event_for_hash, CANONICAL_JSON_TOP_LEVEL_FIELDS,
 };
 pub use regex_cache::RegexCache;
+pub use room_token::RoomPaginationToken;
 pub use room_versions::{
     can_create_room_version, can_federate_room_version, can_join_room_version, can_parse_room_version,
     client_room_versions_capability, federation_room_versions_capability, is_supported_room_version,
-    resolve_room_version, RoomVersionCapability, RoomVersionDisposition, DEFAULT_ROOM_VERSION, SUPPORTED_ROOM_VERSIONS,
+    resolve_room_version, supports_knock, supports_knock_restricted, supports_restricted_join, RoomVersionCapability,
+    RoomVersionDisposition, DEFAULT_ROOM_VERSION, SUPPORTED_ROOM_VERSIONS,
 };
 pub use security::{
     check_url_against_blacklist, compute_signature_hash, is_ip_in_blacklist, ConstantTimeComparison,
@@ -71,8 +71,8 @@ pub fn allowed_content_keys(event_type: &str) -> &'static [&'static str] {
 /// given event type.  Returns a new JSON object.
 ///
 /// For event types with no special-cased retention table, the result is an
-/// empty object `{}`.  This is the runtime redaction path used by
-/// `EventStorage::redact_event_content` (P0-06).
+/// empty object `{}`.  Stored events are redacted with
+/// [`redact_content_for_room_version`] instead.
 pub fn redact_content(event_type: &str, content: &Value) -> Value {
     let allowed = allowed_content_keys(event_type);
     let Some(obj) = content.as_object() else {
@@ -93,6 +93,73 @@ pub fn redact_content(event_type: &str, content: &Value) -> Value {
     Value::Object(retained)
 }
 
+/// Numeric room version whose redaction rules apply to `room_version`.
+/// Unknown or non-numeric (unstable) identifiers use the newest rules.
+fn redaction_rules_version(room_version: &str) -> u32 {
+    room_version.parse().unwrap_or(u32::MAX)
+}
+
+/// Returns the content keys retained when redacting an `event_type` event in
+/// a room of `room_version`, per the spec's redaction algorithm:
+///
+/// - v1-v5 keep `aliases` on `m.room.aliases`; v6 dropped that exemption.
+/// - v8 keeps `allow` on `m.room.join_rules`.
+/// - v9 keeps `join_authorised_via_users_server` on `m.room.member`.
+/// - v11 keeps all of `m.room.create`, `invite` on `m.room.power_levels` and
+///   `redacts` on `m.room.redaction`.
+///
+/// `m.room.create` in v11+ keeps every key and is handled by
+/// [`redact_content_for_room_version`] rather than this table.
+pub fn allowed_content_keys_for_room_version(room_version: &str, event_type: &str) -> Vec<&'static str> {
+    let version = redaction_rules_version(room_version);
+    let mut keys = match event_type {
+        "m.room.member" => vec!["membership"],
+        "m.room.create" => vec!["creator"],
+        "m.room.join_rules" => vec!["join_rule"],
+        "m.room.power_levels" => {
+            vec!["users", "users_default", "events", "events_default", "state_default", "ban", "kick", "redact"]
+        }
+        "m.room.history_visibility" => vec!["history_visibility"],
+        "m.room.aliases" if version <= 5 => vec!["aliases"],
+        _ => Vec::new(),
+    };
+    match event_type {
+        "m.room.join_rules" if version >= 8 => keys.push("allow"),
+        "m.room.member" if version >= 9 => keys.push("join_authorised_via_users_server"),
+        "m.room.power_levels" if version >= 11 => keys.push("invite"),
+        "m.room.redaction" if version >= 11 => keys.push("redacts"),
+        _ => {}
+    }
+    keys
+}
+
+/// Strips `content` to what survives redaction of an `event_type` event in a
+/// room of `room_version`.  This is the runtime redaction path used by
+/// `EventStorage::redact_event_content`.
+pub fn redact_content_for_room_version(room_version: &str, event_type: &str, content: &Value) -> Value {
+    let version = redaction_rules_version(room_version);
+    let Some(obj) = content.as_object() else {
+        return Value::Object(Map::new());
+    };
+    if event_type == "m.room.create" && version >= 11 {
+        return content.clone();
+    }
+
+    let mut retained = Map::new();
+    for key in allowed_content_keys_for_room_version(room_version, event_type) {
+        if let Some(value) = obj.get(key) {
+            retained.insert(key.to_string(), value.clone());
+        }
+    }
+    // v11 keeps only the `signed` block of a member event's third-party invite.
+    if event_type == "m.room.member" && version >= 11 {
+        if let Some(signed) = obj.get("third_party_invite").and_then(|invite| invite.get("signed")) {
+            retained.insert("third_party_invite".to_string(), serde_json::json!({ "signed": signed }));
+        }
+    }
+    Value::Object(retained)
+}
+
 /// Produces a redacted copy of an event for content-hash computation.
 ///
 /// This strips both the top-level fields (keeping only
@@ -273,6 +340,77 @@ mod tests {
         assert!(redacted["content"].get("extra").is_none());
     }
 
+    #[test]
+    fn test_redact_for_room_version_member() {
+        let content = json!({
+            "membership": "join",
+            "displayname": "Alice",
+            "join_authorised_via_users_server": "@admin:example.org",
+            "third_party_invite": {"display_name": "alice", "signed": {"token": "abc"}},
+        });
+        assert_eq!(redact_content_for_room_version("8", "m.room.member", &content), json!({"membership": "join"}));
+        assert_eq!(
+            redact_content_for_room_version("9", "m.room.member", &content),
+            json!({"membership": "join", "join_authorised_via_users_server": "@admin:example.org"})
+        );
+        assert_eq!(
+            redact_content_for_room_version("11", "m.room.member", &content),
+            json!({
+                "membership": "join",
+                "join_authorised_via_users_server": "@admin:example.org",
+                "third_party_invite": {"signed": {"token": "abc"}},
+            })
+        );
+    }
+
+    #[test]
+    fn test_redact_for_room_version_create() {
+        let content = json!({"creator": "@alice:example.org", "room_version": "10", "m.federate": false});
+        assert_eq!(
+            redact_content_for_room_version("10", "m.room.create", &content),
+            json!({"creator": "@alice:example.org"})
+        );
+        assert_eq!(redact_content_for_room_version("11", "m.room.create", &content), content);
+    }
+
+    #[test]
+    fn test_redact_for_room_version_join_rules_power_levels_and_redaction() {
+        let join_rules = json!({"join_rule": "restricted", "allow": [{"type": "m.room_membership"}]});
+        assert_eq!(
+            redact_content_for_room_version("7", "m.room.join_rules", &join_rules),
+            json!({"join_rule": "restricted"})
+        );
+        assert_eq!(redact_content_for_room_version("8", "m.room.join_rules", &join_rules), join_rules);
+
+        let power_levels = json!({"ban": 50, "invite": 0, "notifications": {"room": 50}});
+        assert_eq!(redact_content_for_room_version("10", "m.room.power_levels", &power_levels), json!({"ban": 50}));
+        assert_eq!(
+            redact_content_for_room_version("11", "m.room.power_levels", &power_levels),
+            json!({"ban": 50, "invite": 0})
+        );
+
+        let redaction = json!({"redacts": "$target", "reason": "spam"});
+        assert_eq!(redact_content_for_room_version("10", "m.room.redaction", &redaction), json!({}));
+        assert_eq!(
+            redact_content_for_room_version("11", "m.room.redaction", &redaction),
+            json!({"redacts": "$target"})
+        );
+    }
+
+    #[test]
+    fn test_redact_for_room_version_aliases_and_messages() {
+        let aliases = json!({"aliases": ["#a:example.org"]});
+        assert_eq!(redact_content_for_room_version("5", "m.room.aliases", &aliases), aliases);
+        assert_eq!(redact_content_for_room_version("6", "m.room.aliases", &aliases), json!({}));
+
+        let message = json!({"msgtype": "m.text", "body": "secret"});
+        assert_eq!(redact_content_for_room_version("11", "m.room.message", &message), json!({}));
+        assert_eq!(
+            redact_content_for_room_version("org.example.custom", "m.room.create", &json!({"a": 1})),
+            json!({"a": 1})
+        );
+    }
+
     #[test]
     fn test_extract_redacts_top_level_v1_v10() {
         let event = json!({
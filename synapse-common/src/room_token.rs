@@ -0,0 +1,106 @@
+//! Room pagination tokens shared by `/messages`, `/context` and the
+//! `prev_batch` of `/sync`.
+//!
+//! A token names a position in a room's timeline: every event whose
+//! `stream_ordering` is at or below the position lies before it. Three forms
+//! are accepted:
+//!
+//! * `s{stream}` — a stream position, as handed out in `/sync` `prev_batch`.
+//! * `t{topological}-{stream}` — a topological token, as returned by
+//!   `/messages` and `/context`. The topological part is the boundary event's
+//!   depth; positions are compared on the stream part.
+//! * `t{origin_server_ts}` or a bare integer — tokens issued by earlier
+//!   releases, which paginated by timestamp. They are still accepted and
+//!   resolved to a stream position against the room.
+
+use std::fmt;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RoomPaginationToken {
+    Stream(i64),
+    Topological { topological: i64, stream: i64 },
+    Timestamp(i64),
+}
+
+impl RoomPaginationToken {
+    pub fn parse(token: &str) -> Option<Self> {
+        if let Some(stream) = token.strip_prefix('s') {
+            return stream.parse().ok().map(Self::Stream);
+        }
+        if let Some(rest) = token.strip_prefix('t') {
+            return match rest.split_once('-') {
+                Some((topological, stream)) => {
+                    Some(Self::Topological { topological: topological.parse().ok()?, stream: stream.parse().ok()? })
+                }
+                None => rest.parse().ok().map(Self::Timestamp),
+            };
+        }
+        token.parse().ok().map(Self::Timestamp)
+    }
+
+    /// Stream position of the token; `None` for timestamp tokens, which have
+    /// to be resolved against the room first.
+    pub fn stream(&self) -> Option<i64> {
+        match *self {
+            Self::Stream(stream) | Self::Topological { stream, .. } => Some(stream),
+            Self::Timestamp(_) => None,
+        }
+    }
+}
+
+impl FromStr for RoomPaginationToken {
+    type Err = ();
+
+    fn from_str(token: &str) -> Result<Self, Self::Err> {
+        Self::parse(token).ok_or(())
+    }
+}
+
+impl fmt::Display for RoomPaginationToken {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Stream(stream) => write!(f, "s{stream}"),
+            Self::Topological { topological, stream } => write!(f, "t{topological}-{stream}"),
+            Self::Timestamp(ts) => write!(f, "t{ts}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_each_token_form() {
+        assert_eq!(RoomPaginationToken::parse("s42"), Some(RoomPaginationToken::Stream(42)));
+        assert_eq!(
+            RoomPaginationToken::parse("t7-42"),
+            Some(RoomPaginationToken::Topological { topological: 7, stream: 42 })
+        );
+        assert_eq!(
+            RoomPaginationToken::parse("t1700000000000"),
+            Some(RoomPaginationToken::Timestamp(1_700_000_000_000))
+        );
+        assert_eq!(
+            RoomPaginationToken::parse("1700000000000"),
+            Some(RoomPaginationToken::Timestamp(1_700_000_000_000))
+        );
+    }
+
+    #[test]
+    fn rejects_malformed_tokens() {
+        for token in ["", "s", "sx", "t", "t1-", "t-1-2", "x12", "s1_2_3"] {
+            assert_eq!(RoomPaginationToken::parse(token), None, "{token}");
+        }
+    }
+
+    #[test]
+    fn display_round_trips() {
+        for token in ["s0", "t3-99", "t1700000000000"] {
+            assert_eq!(RoomPaginationToken::parse(token).map(|t| t.to_string()).as_deref(), Some(token));
+        }
+        assert_eq!(RoomPaginationToken::Topological { topological: 3, stream: 9 }.stream(), Some(9));
+        assert_eq!(RoomPaginationToken::Timestamp(5).stream(), None);
+    }
+}
@@ -101,6 +101,28 @@ pub fn can_federate_room_version(version: &str) -> bool {
     SUPPORTED_ROOM_VERSIONS.iter().any(|capability| capability.version == version && capability.can_federate)
 }
 
+/// Stable room versions are numeric; anything else (e.g. MSC prototypes) has
+/// no ordering relative to them.
+fn numeric_room_version(version: &str) -> Option<u32> {
+    version.parse().ok()
+}
+
+/// Whether the room version's auth rules understand the `knock` join rule (v7+).
+pub fn supports_knock(version: &str) -> bool {
+    numeric_room_version(version).is_some_and(|v| v >= 7)
+}
+
+/// Whether the room version's auth rules understand the `restricted` join
+/// rule and its `allow` conditions (v8+).
+pub fn supports_restricted_join(version: &str) -> bool {
+    numeric_room_version(version).is_some_and(|v| v >= 8)
+}
+
+/// Whether the room version's auth rules understand `knock_restricted` (v10+).
+pub fn supports_knock_restricted(version: &str) -> bool {
+    numeric_room_version(version).is_some_and(|v| v >= 10)
+}
+
 pub fn resolve_room_version(requested: Option<&str>) -> Option<&'static str> {
     let requested = requested.unwrap_or(DEFAULT_ROOM_VERSION);
 
@@ -121,7 +143,27 @@ pub fn client_room_versions_capability() -> Value {
 
     json!({
         "default": DEFAULT_ROOM_VERSION,
-        "available": available
+        "available": available,
+        "org.matrix.msc3244.room_capabilities": {
+            "knock": join_rule_capability(supports_knock),
+            "restricted": join_rule_capability(supports_restricted_join)
+        }
+    })
+}
+
+/// MSC3244 entry: the creatable versions supporting a feature, preferring the
+/// default room version when it qualifies.
+fn join_rule_capability(supports: fn(&str) -> bool) -> Value {
+    let support: Vec<&str> = SUPPORTED_ROOM_VERSIONS
+        .iter()
+        .filter(|capability| capability.can_create && supports(capability.version))
+        .map(|capability| capability.version)
+        .collect();
+    let preferred = if supports(DEFAULT_ROOM_VERSION) { Some(DEFAULT_ROOM_VERSION) } else { support.last().copied() };
+
+    json!({
+        "preferred": preferred,
+        "support": support
     })
 }
 
@@ -142,7 +184,8 @@ mod tests {
     use super::{
         can_create_room_version, can_federate_room_version, can_join_room_version, can_parse_room_version,
         client_room_versions_capability, federation_room_versions_capability, is_supported_room_version,
-        resolve_room_version, DEFAULT_ROOM_VERSION, SUPPORTED_ROOM_VERSIONS,
+        resolve_room_version, supports_knock, supports_knock_restricted, supports_restricted_join,
+        DEFAULT_ROOM_VERSION, SUPPORTED_ROOM_VERSIONS,
     };
 
     #[test]
@@ -202,6 +245,24 @@ mod tests {
         }
     }
 
+    #[test]
+    fn join_rule_support_follows_room_version_auth_rules() {
+        assert!(!supports_knock("6"));
+        assert!(supports_knock("7"));
+        assert!(!supports_restricted_join("7"));
+        assert!(supports_restricted_join("8"));
+        assert!(!supports_knock_restricted("9"));
+        assert!(supports_knock_restricted("10"));
+        assert!(supports_restricted_join("11"));
+        assert!(!supports_restricted_join("org.example.custom"));
+
+        let capabilities = &client_room_versions_capability()["org.matrix.msc3244.room_capabilities"];
+        assert_eq!(capabilities["restricted"]["preferred"], DEFAULT_ROOM_VERSION);
+        let restricted = capabilities["restricted"]["support"].as_array().expect("support should be an array");
+        assert!(restricted.iter().any(|v| v == "10") && restricted.iter().any(|v| v == "11"));
+        assert!(restricted.iter().all(|v| v != "7"));
+    }
+
     #[test]
     fn federation_room_versions_capability_matches_supported_matrix() {
         let capability = federation_room_versions_capability();
@@ -95,6 +95,13 @@ pub struct ServerMetrics {
     pub megolm_lazy_migration_sessions_promoted_total: Counter,
     pub megolm_pickle_persist_duration_ms: Histogram,
 
+    // Device verification (`m.key.verification.*` to-device flows)
+    pub e2ee_verification_started_total: Counter,
+    pub e2ee_verification_succeeded_total: Counter,
+    pub e2ee_verification_cancelled_total: Counter,
+    pub e2ee_verification_timed_out_total: Counter,
+    pub e2ee_verification_in_flight: Gauge,
+
     collector: Arc<MetricsCollector>,
 }
 
@@ -233,6 +240,15 @@ impl ServerMetrics {
                 Self::labels(&[("unit", "ms")]),
             ),
 
+            e2ee_verification_started_total: collector.register_counter("e2ee_verification_started_total".to_string()),
+            e2ee_verification_succeeded_total: collector
+                .register_counter("e2ee_verification_succeeded_total".to_string()),
+            e2ee_verification_cancelled_total: collector
+                .register_counter("e2ee_verification_cancelled_total".to_string()),
+            e2ee_verification_timed_out_total: collector
+                .register_counter("e2ee_verification_timed_out_total".to_string()),
+            e2ee_verification_in_flight: collector.register_gauge("e2ee_verification_in_flight".to_string()),
+
             collector,
         }
     }
@@ -429,6 +445,22 @@ impl ServerMetrics {
         self.megolm_lazy_migration_sessions_promoted_total.inc_by(promoted);
     }
 
+    /// Record a device verification flow being opened.
+    pub fn record_verification_started(&self) {
+        self.e2ee_verification_started_total.inc();
+        self.e2ee_verification_in_flight.inc();
+    }
+
+    /// Record how a device verification flow ended.
+    pub fn record_verification_finished(&self, outcome: VerificationOutcome) {
+        match outcome {
+            VerificationOutcome::Succeeded => self.e2ee_verification_succeeded_total.inc(),
+            VerificationOutcome::Cancelled => self.e2ee_verification_cancelled_total.inc(),
+            VerificationOutcome::TimedOut => self.e2ee_verification_timed_out_total.inc(),
+        }
+        self.e2ee_verification_in_flight.dec();
+    }
+
     pub fn get_collector(&self) -> &Arc<MetricsCollector> {
         &self.collector
     }
@@ -480,6 +512,13 @@ pub enum SecurityValidationType {
     Timestamp,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerificationOutcome {
+    Succeeded,
+    Cancelled,
+    TimedOut,
+}
+
 #[derive(Debug, Clone)]
 pub struct MetricsSummary {
     pub auth_attempts: u64,
@@ -0,0 +1,115 @@
+//! Attribute-to-MXID mapping shared by the SSO providers.
+//!
+//! Templates such as `"{uid}"` or `"{givenName} {sn}"` name attributes of
+//! the identity provider's assertion in braces. Localparts rendered from them
+//! go through [`map_to_localpart`] so any IdP value yields a valid Matrix ID.
+
+use std::collections::HashMap;
+
+/// Names referenced by `template`, in order.
+pub fn template_placeholders(template: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| format!("unterminated placeholder in '{template}'"))?;
+        let name = after[..end].trim();
+        if name.is_empty() {
+            return Err(format!("empty placeholder in '{template}'"));
+        }
+        names.push(name);
+        rest = &after[end + 1..];
+    }
+    if rest.contains('}') {
+        return Err(format!("unmatched '}}' in '{template}'"));
+    }
+    Ok(names)
+}
+
+/// Substitutes each `{name}` with `lookup(name)`. Fails with the name of
+/// the first placeholder `lookup` has no value for.
+pub fn render_template<'a>(template: &str, lookup: impl Fn(&str) -> Option<&'a str>) -> Result<String, String> {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 1..];
+        let end = after.find('}').ok_or_else(|| format!("unterminated placeholder in '{template}'"))?;
+        let name = after[..end].trim();
+        rendered.push_str(lookup(name).ok_or_else(|| name.to_string())?);
+        rest = &after[end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(rendered)
+}
+
+/// Lowercases `value` and hex-encodes (`=xx`) every byte that is not
+/// allowed in a localpart, as Synapse's `hexencode` mapping does.
+pub fn map_to_localpart(value: &str) -> String {
+    let mut localpart = String::with_capacity(value.len());
+    for byte in value.trim().to_lowercase().bytes() {
+        match byte {
+            b'a'..=b'z' | b'0'..=b'9' | b'.' | b'_' | b'-' | b'/' => localpart.push(byte as char),
+            _ => localpart.push_str(&format!("={byte:02x}")),
+        }
+    }
+    localpart
+}
+
+/// First attribute in `required` the assertion does not satisfy. A `None`
+/// value only requires the attribute to be present; `Some(v)` requires one
+/// of its values to equal `v`.
+pub fn missing_required_attribute<'a>(
+    required: &'a HashMap<String, Option<String>>,
+    attributes: &HashMap<String, Vec<String>>,
+) -> Option<&'a str> {
+    let mut names: Vec<&String> = required.keys().collect();
+    names.sort();
+    names.into_iter().find_map(|name| {
+        let values = attributes.get(name).filter(|values| !values.is_empty());
+        let satisfied = match (values, &required[name]) {
+            (None, _) => false,
+            (Some(_), None) => true,
+            (Some(values), Some(expected)) => values.iter().any(|v| v == expected),
+        };
+        (!satisfied).then_some(name.as_str())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_render_attributes_and_report_missing_ones() {
+        let attrs = HashMap::from([("givenName", "Alice"), ("sn", "Liddell")]);
+        let lookup = |name: &str| attrs.get(name).copied();
+        assert_eq!(render_template("{givenName} { sn }", lookup), Ok("Alice Liddell".to_string()));
+        assert_eq!(render_template("{givenName}.{mail}", lookup), Err("mail".to_string()));
+        assert_eq!(template_placeholders("{a}-{ b }"), Ok(vec!["a", "b"]));
+        assert!(template_placeholders("{a").is_err());
+        assert!(template_placeholders("a}").is_err());
+        assert!(template_placeholders("{}").is_err());
+    }
+
+    #[test]
+    fn localparts_are_lowercased_and_hex_encoded() {
+        assert_eq!(map_to_localpart(" Alice.Smith "), "alice.smith");
+        assert_eq!(map_to_localpart("bob@example.com"), "bob=40example.com");
+        assert_eq!(map_to_localpart("José"), "jos=c3=a9");
+    }
+
+    #[test]
+    fn required_attributes_match_presence_or_value() {
+        let required = HashMap::from([("group".to_string(), Some("staff".to_string())), ("mail".to_string(), None)]);
+        let mut attrs = HashMap::from([
+            ("group".to_string(), vec!["students".to_string(), "staff".to_string()]),
+            ("mail".to_string(), vec!["a@example.com".to_string()]),
+        ]);
+        assert_eq!(missing_required_attribute(&required, &attrs), None);
+        attrs.insert("group".to_string(), vec!["students".to_string()]);
+        assert_eq!(missing_required_attribute(&required, &attrs), Some("group"));
+        attrs.remove("mail");
+        assert_eq!(missing_required_attribute(&required, &attrs), Some("group"));
+    }
+}
@@ -32,12 +32,167 @@ impl From<ValidationError> for ApiError {
     }
 }
 
+// ============================================================================
+// Matrix identifier grammar (client-server spec, "Appendices: Identifier Grammar")
+// ============================================================================
+
+/// Maximum length in bytes of a user ID, room ID, room alias or event ID.
+pub const MAX_IDENTIFIER_LENGTH: usize = 255;
+
+/// Length of a room-version-12 room ID localpart: the unpadded URL-safe
+/// base64 reference hash of the create event.
+const HASHED_ROOM_ID_LENGTH: usize = 43;
+
+fn invalid(field: &str, message: &str) -> ValidationError {
+    ValidationError::new(field, message, "INVALID_FORMAT")
+}
+
+/// Split a server name into its host and optional port. The host is a DNS
+/// name, an IPv4 address or a bracketed IPv6 literal such as `[::1]:8448`.
+pub fn parse_server_name(server_name: &str) -> Result<(&str, Option<u16>), ValidationError> {
+    const FIELD: &str = "server_name";
+    if server_name.is_empty() {
+        return Err(ValidationError::new(FIELD, "Server name cannot be empty", "EMPTY"));
+    }
+    let (host, port) = if let Some(rest) = server_name.strip_prefix('[') {
+        let (literal, rest) = rest.split_once(']').ok_or_else(|| invalid(FIELD, "Unterminated IPv6 literal"))?;
+        if literal.parse::<std::net::Ipv6Addr>().is_err() {
+            return Err(invalid(FIELD, "Invalid IPv6 literal"));
+        }
+        let port = match rest {
+            "" => None,
+            rest => Some(rest.strip_prefix(':').ok_or_else(|| invalid(FIELD, "Unexpected text after IPv6 literal"))?),
+        };
+        (&server_name[..literal.len() + 2], port)
+    } else {
+        let (host, port) = match server_name.split_once(':') {
+            Some((host, port)) => (host, Some(port)),
+            None => (server_name, None),
+        };
+        if host.is_empty() || host.len() > MAX_IDENTIFIER_LENGTH {
+            return Err(invalid(FIELD, "Host name must be 1 to 255 characters"));
+        }
+        if !host.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'.') {
+            return Err(invalid(FIELD, "Host name contains invalid characters"));
+        }
+        (host, port)
+    };
+    let port = port
+        .map(|port| {
+            if port.is_empty() || port.len() > 5 || !port.bytes().all(|b| b.is_ascii_digit()) {
+                return Err(invalid(FIELD, "Port must be 1 to 5 digits"));
+            }
+            port.parse::<u16>().map_err(|_| invalid(FIELD, "Port out of range"))
+        })
+        .transpose()?;
+    Ok((host, port))
+}
+
+pub fn validate_server_name(server_name: &str) -> ValidationResult {
+    parse_server_name(server_name).map(|_| ())
+}
+
+/// Common checks for `<sigil><localpart>:<server_name>` identifiers; returns
+/// the localpart. Localparts never contain `:`, so the first colon separates
+/// the server name, which may itself carry a port.
+fn split_identifier<'a>(
+    field: &str,
+    sigil: char,
+    shape: &str,
+    id: &'a str,
+) -> Result<(&'a str, &'a str), ValidationError> {
+    if id.is_empty() {
+        return Err(ValidationError::new(field, &format!("{field} is required"), "EMPTY"));
+    }
+    if id.len() > MAX_IDENTIFIER_LENGTH {
+        return Err(ValidationError::new(
+            field,
+            &format!("{field} too long (max {MAX_IDENTIFIER_LENGTH} bytes)"),
+            "TOO_LONG",
+        ));
+    }
+    let rest = id.strip_prefix(sigil).ok_or_else(|| invalid(field, &format!("{field} must start with {sigil}")))?;
+    let (localpart, server_name) = rest
+        .split_once(':')
+        .filter(|(localpart, server_name)| !localpart.is_empty() && !server_name.is_empty())
+        .ok_or_else(|| invalid(field, &format!("{field} must be {shape}")))?;
+    validate_server_name(server_name).map_err(|e| invalid(field, &format!("{field} server name: {}", e.message)))?;
+    Ok((localpart, server_name))
+}
+
+/// Historical user ID localparts may use any printable ASCII except `:`.
+fn is_historical_localpart(localpart: &str) -> bool {
+    localpart.bytes().all(|b| (0x21..=0x7e).contains(&b) && b != b':')
+}
+
+/// `@localpart:server_name`, accepting historical localparts so that users
+/// from older servers still validate.
+pub fn validate_user_id(user_id: &str) -> ValidationResult {
+    let (localpart, _) = split_identifier("user_id", '@', "@localpart:server", user_id)?;
+    if !is_historical_localpart(localpart) {
+        return Err(invalid("user_id", "user_id localpart contains invalid characters"));
+    }
+    Ok(())
+}
+
+/// `!opaque:server_name`, or the server-less `!<hash>` of room version 12.
+pub fn validate_room_id(room_id: &str) -> ValidationResult {
+    if let Some(hash) = room_id.strip_prefix('!').filter(|rest| !rest.contains(':')) {
+        let is_hash = hash.len() == HASHED_ROOM_ID_LENGTH
+            && hash.bytes().all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_');
+        if is_hash {
+            return Ok(());
+        }
+    }
+    let (localpart, _) = split_identifier("room_id", '!', "!opaque:server", room_id)?;
+    if !is_historical_localpart(localpart) {
+        return Err(invalid("room_id", "room_id localpart contains invalid characters"));
+    }
+    Ok(())
+}
+
+/// `#localpart:server_name`; the localpart may be any Unicode except `:` and NUL.
+pub fn validate_room_alias(room_alias: &str) -> ValidationResult {
+    let (localpart, _) = split_identifier("room_alias", '#', "#alias:server", room_alias)?;
+    if localpart.contains('\0') {
+        return Err(invalid("room_alias", "room_alias localpart contains invalid characters"));
+    }
+    Ok(())
+}
+
+/// `$opaque:server_name` (room versions 1 and 2) or the bare `$<hash>` of
+/// later versions, in standard or URL-safe base64.
+pub fn validate_event_id(event_id: &str) -> ValidationResult {
+    if event_id.contains(':') {
+        let (localpart, _) = split_identifier("event_id", '$', "$opaque:server", event_id)?;
+        if !is_historical_localpart(localpart) {
+            return Err(invalid("event_id", "event_id localpart contains invalid characters"));
+        }
+        return Ok(());
+    }
+    if event_id.is_empty() {
+        return Err(ValidationError::new("event_id", "event_id is required", "EMPTY"));
+    }
+    if event_id.len() > MAX_IDENTIFIER_LENGTH {
+        return Err(ValidationError::new("event_id", "event_id too long (max 255 bytes)", "TOO_LONG"));
+    }
+    let hash = event_id.strip_prefix('$').ok_or_else(|| invalid("event_id", "event_id must start with $"))?;
+    if hash.is_empty() || !hash.bytes().all(|b| b.is_ascii_alphanumeric() || b"+/-_".contains(&b)) {
+        return Err(invalid("event_id", "event_id hash contains invalid characters"));
+    }
+    Ok(())
+}
+
+/// Server name of a `@user:server` ID, if it parses.
+pub fn user_id_server_name(user_id: &str) -> Option<&str> {
+    let (_, server_name) = user_id.strip_prefix('@')?.split_once(':')?;
+    validate_server_name(server_name).ok().map(|_| server_name)
+}
+
 #[derive(Debug, Clone)]
 pub struct Validator {
     username_regex: Regex,
     email_regex: Regex,
-    matrix_id_regex: Regex,
-    room_id_regex: Regex,
     device_id_regex: Regex,
     url_regex: Regex,
 }
@@ -48,8 +203,6 @@ impl Validator {
             // Matrix localpart: [a-z0-9._=-]+
             username_regex: Regex::new(r"^[a-z0-9._=\-]{1,255}$")?,
             email_regex: Regex::new(r"^[a-zA-Z0-9._%+-]+@[a-zA-Z0-9.-]+\.[a-zA-Z]{2,}$")?,
-            matrix_id_regex: Regex::new(r"^@[a-z0-9._=\-]+:[a-zA-Z0-9.-]+$")?,
-            room_id_regex: Regex::new(r"^![a-zA-Z0-9._=\-]+:[a-zA-Z0-9.-]+$")?,
             device_id_regex: Regex::new(r"^[a-zA-Z0-9._\-]{1,255}$")?,
             url_regex: Regex::new(r"^https?://[a-zA-Z0-9.-]+(:[0-9]+)?(/.*)?$")?,
         })
@@ -153,27 +306,11 @@ impl Validator {
     }
 
     pub fn validate_matrix_id(&self, user_id: &str) -> ValidationResult {
-        if user_id.is_empty() {
-            return Err(ValidationError::new("user_id", "User ID cannot be empty", "EMPTY"));
-        }
-
-        if !self.matrix_id_regex.is_match(user_id) {
-            return Err(ValidationError::new("user_id", "Invalid Matrix ID format", "INVALID_FORMAT"));
-        }
-
-        Ok(())
+        validate_user_id(user_id)
     }
 
     pub fn validate_room_id(&self, room_id: &str) -> ValidationResult {
-        if room_id.is_empty() {
-            return Err(ValidationError::new("room_id", "Room ID cannot be empty", "EMPTY"));
-        }
-
-        if !self.room_id_regex.is_match(room_id) {
-            return Err(ValidationError::new("room_id", "Invalid room ID format", "INVALID_FORMAT"));
-        }
-
-        Ok(())
+        validate_room_id(room_id)
     }
 
     pub fn validate_device_id(&self, device_id: &str) -> ValidationResult {
@@ -298,8 +435,6 @@ impl Validator {
         Self {
             username_regex: Regex::new(r"^[a-zA-Z0-9_.-]+$").expect("hardcoded fallback regex is syntactically valid"),
             email_regex: Regex::new(r"^[^@]+@[^@]+\.[^@]+$").expect("hardcoded fallback regex is syntactically valid"),
-            matrix_id_regex: Regex::new(r"^@[^:]+:[^:]+$").expect("hardcoded fallback regex is syntactically valid"),
-            room_id_regex: Regex::new(r"^![^:]+:[^:]+$").expect("hardcoded fallback regex is syntactically valid"),
             device_id_regex: Regex::new(r"^[a-zA-Z0-9._\-]+$")
                 .expect("hardcoded fallback regex is syntactically valid"),
             url_regex: Regex::new(r"^https?://.+").expect("hardcoded fallback regex is syntactically valid"),
@@ -530,6 +665,55 @@ mod tests {
         assert!(validator.validate_room_id("!abc123").is_err());
     }
 
+    #[test]
+    fn test_parse_server_name_grammar() {
+        assert_eq!(parse_server_name("example.com").unwrap(), ("example.com", None));
+        assert_eq!(parse_server_name("example.com:8448").unwrap(), ("example.com", Some(8448)));
+        assert_eq!(parse_server_name("1.2.3.4:80").unwrap(), ("1.2.3.4", Some(80)));
+        assert_eq!(parse_server_name("[::1]").unwrap(), ("[::1]", None));
+        assert_eq!(parse_server_name("[2001:db8::1]:8448").unwrap(), ("[2001:db8::1]", Some(8448)));
+
+        for bad in [
+            "",
+            ":80",
+            "example.com:",
+            "example.com:99999",
+            "example.com:8a",
+            "exa mple.com",
+            "[::1",
+            "[zz]",
+            "[::1]x",
+            "a:b:c",
+        ] {
+            assert!(parse_server_name(bad).is_err(), "{bad:?} accepted");
+        }
+    }
+
+    #[test]
+    fn test_identifier_grammar() {
+        assert!(validate_user_id("@alice:example.com:8448").is_ok());
+        assert!(validate_user_id("@Historic_User!:[::1]").is_ok());
+        assert!(validate_user_id("@al ice:example.com").is_err());
+        assert!(validate_user_id(&format!("@{}:example.com", "a".repeat(250))).is_err());
+
+        assert!(validate_room_id("!opaque:example.com").is_ok());
+        assert!(validate_room_id(&format!("!{}", "A".repeat(43))).is_ok());
+        assert!(validate_room_id("!short").is_err());
+        assert!(validate_room_id("!room:bad_host").is_err());
+
+        assert!(validate_room_alias("#caf\u{e9}:example.com").is_ok());
+        assert!(validate_room_alias("#room:example.com:notaport").is_err());
+
+        assert!(validate_event_id("$abc:example.com").is_ok());
+        assert!(validate_event_id("$acR1l0raoZnm60CBwAVgqbZqoO/mYU81xysh1u7XcJk").is_ok());
+        assert!(validate_event_id("$Rqnc-F-dvnEYJTyHq_iKxU2bZ1CI92-kuZq3a5lr5Zg").is_ok());
+        assert!(validate_event_id("$").is_err());
+        assert!(validate_event_id("$bad hash").is_err());
+
+        assert_eq!(user_id_server_name("@a:example.com:8448"), Some("example.com:8448"));
+        assert_eq!(user_id_server_name("@a:"), None);
+    }
+
     #[test]
     fn test_validation_context() {
         let validator = Arc::new(Validator::new().unwrap());
@@ -1,6 +1,7 @@
 use super::models::*;
 use sqlx::PgPool;
 use std::sync::Arc;
+use synapse_common::column_encryption::{self, columns};
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
 
@@ -16,6 +17,13 @@ pub struct BackupKeyInsertParams {
     pub backup_data: serde_json::Value,
 }
 
+/// The backup with its auth data decrypted.
+fn open_backup(mut row: KeyBackupRow) -> Result<KeyBackup, ApiError> {
+    row.backup_data =
+        row.backup_data.map(|data| column_encryption::open_json(columns::KEY_BACKUP_AUTH_DATA, data)).transpose()?;
+    Ok(row.into())
+}
+
 #[derive(Clone)]
 pub struct KeyBackupStorage {
     pub pool: Arc<PgPool>,
@@ -28,6 +36,7 @@ impl KeyBackupStorage {
 
     pub async fn create_backup(&self, backup: &KeyBackup) -> Result<(), ApiError> {
         let now = current_timestamp_millis();
+        let auth_data = column_encryption::seal_json(columns::KEY_BACKUP_AUTH_DATA, &backup.backup_data)?;
         sqlx::query(
             r"
             INSERT INTO key_backups (
@@ -59,7 +68,7 @@ impl KeyBackupStorage {
         .bind(&backup.algorithm)
         .bind(&backup.auth_key)
         .bind(&backup.mgmt_key)
-        .bind(&backup.backup_data)
+        .bind(&auth_data)
         .bind(&backup.etag)
         .bind(now)
         .execute(&*self.pool)
@@ -90,7 +99,7 @@ impl KeyBackupStorage {
         .fetch_optional(&*self.pool)
         .await?;
 
-        Ok(row.map(KeyBackup::from))
+        row.map(open_backup).transpose()
     }
 
     pub async fn get_all_backup_versions(&self, user_id: &str) -> Result<Vec<KeyBackup>, ApiError> {
@@ -114,7 +123,7 @@ impl KeyBackupStorage {
         .fetch_all(&*self.pool)
         .await?;
 
-        Ok(rows.into_iter().map(KeyBackup::from).collect())
+        rows.into_iter().map(open_backup).collect()
     }
 
     pub async fn get_backup_version(&self, user_id: &str, version: &str) -> Result<Option<KeyBackup>, ApiError> {
@@ -139,7 +148,7 @@ impl KeyBackupStorage {
         .fetch_optional(&*self.pool)
         .await?;
 
-        Ok(row.map(KeyBackup::from))
+        row.map(open_backup).transpose()
     }
 
     pub async fn delete_backup(&self, user_id: &str, version: &str) -> Result<(), ApiError> {
@@ -3,6 +3,7 @@ use chrono::Utc;
 use sqlx::PgPool;
 use std::str::FromStr;
 use std::sync::Arc;
+use synapse_common::column_encryption::{self, columns};
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
 
@@ -48,6 +49,26 @@ impl From<MegolmSessionRow> for MegolmSession {
     }
 }
 
+impl MegolmSessionRow {
+    /// The session with its pickles decrypted.
+    fn open(mut self) -> Result<MegolmSession, ApiError> {
+        self.session_key = column_encryption::open(columns::MEGOLM_SESSION_KEY, &self.session_key)?;
+        self.vodozemac_pickle = self
+            .vodozemac_pickle
+            .map(|pickle| column_encryption::open(columns::MEGOLM_VODOZEMAC_PICKLE, &pickle))
+            .transpose()?;
+        Ok(self.into())
+    }
+}
+
+fn seal_session_key(session_key: &str) -> Result<String, ApiError> {
+    Ok(column_encryption::seal(columns::MEGOLM_SESSION_KEY, session_key)?)
+}
+
+fn seal_vodozemac_pickle(pickle: Option<&str>) -> Result<Option<String>, ApiError> {
+    Ok(pickle.map(|pickle| column_encryption::seal(columns::MEGOLM_VODOZEMAC_PICKLE, pickle)).transpose()?)
+}
+
 #[derive(Clone)]
 pub struct MegolmSessionStorage {
     pub pool: Arc<PgPool>,
@@ -59,6 +80,8 @@ impl MegolmSessionStorage {
     }
 
     pub async fn create_session(&self, session: &MegolmSession) -> Result<(), ApiError> {
+        let session_key = seal_session_key(&session.session_key)?;
+        let vodozemac_pickle = seal_vodozemac_pickle(session.vodozemac_pickle.as_deref())?;
         sqlx::query(
             r"
             INSERT INTO megolm_sessions (
@@ -73,14 +96,14 @@ impl MegolmSessionStorage {
         .bind(&session.session_id)
         .bind(&session.room_id)
         .bind(&session.sender_key)
-        .bind(&session.session_key)
+        .bind(&session_key)
         .bind(&session.algorithm)
         .bind(session.message_index)
         .bind(session.created_ts.timestamp_millis())
         .bind(session.last_used_ts.timestamp_millis())
         .bind(session.expires_at.map(|t| t.timestamp_millis()))
         .bind(session.pickle_format.as_str())
-        .bind(session.vodozemac_pickle.as_deref())
+        .bind(vodozemac_pickle.as_deref())
         .execute(&*self.pool)
         .await
         .map_err(|e| {
@@ -119,7 +142,7 @@ impl MegolmSessionStorage {
             ApiError::database("A database error occurred".to_string())
         })?;
 
-        Ok(row.map(Into::into))
+        row.map(MegolmSessionRow::open).transpose()
     }
 
     pub async fn get_room_sessions(&self, room_id: &str) -> Result<Vec<MegolmSession>, ApiError> {
@@ -150,10 +173,12 @@ impl MegolmSessionStorage {
             ApiError::database("A database error occurred".to_string())
         })?;
 
-        Ok(rows.into_iter().map(Into::into).collect())
+        rows.into_iter().map(MegolmSessionRow::open).collect()
     }
 
     pub async fn update_session(&self, session: &MegolmSession) -> Result<(), ApiError> {
+        let session_key = seal_session_key(&session.session_key)?;
+        let vodozemac_pickle = seal_vodozemac_pickle(session.vodozemac_pickle.as_deref())?;
         sqlx::query(
             r"
             UPDATE megolm_sessions
@@ -167,12 +192,12 @@ impl MegolmSessionStorage {
             ",
         )
         .bind(&session.session_id)
-        .bind(&session.session_key)
+        .bind(&session_key)
         .bind(session.message_index)
         .bind(session.last_used_ts.timestamp_millis())
         .bind(session.expires_at.map(|t| t.timestamp_millis()))
         .bind(session.pickle_format.as_str())
-        .bind(session.vodozemac_pickle.as_deref())
+        .bind(vodozemac_pickle.as_deref())
         .execute(&*self.pool)
         .await
         .map_err(|e| {
@@ -243,6 +268,7 @@ impl MegolmSessionStorage {
         vodozemac_pickle: &str,
         now_ms: i64,
     ) -> Result<bool, ApiError> {
+        let vodozemac_pickle = seal_vodozemac_pickle(Some(vodozemac_pickle))?;
         let result = sqlx::query(
             r"
             UPDATE megolm_sessions
@@ -252,7 +278,7 @@ impl MegolmSessionStorage {
             ",
         )
         .bind(session_id)
-        .bind(vodozemac_pickle)
+        .bind(vodozemac_pickle.as_deref())
         .bind(now_ms)
         .execute(&*self.pool)
         .await
@@ -337,6 +363,7 @@ impl MegolmSessionStorage {
         vodozemac_pickle: &str,
         now_ms: i64,
     ) -> Result<bool, ApiError> {
+        let vodozemac_pickle = seal_vodozemac_pickle(Some(vodozemac_pickle))?;
         let result = sqlx::query(
             r"
             UPDATE megolm_sessions
@@ -349,7 +376,7 @@ impl MegolmSessionStorage {
             ",
         )
         .bind(session_id)
-        .bind(vodozemac_pickle)
+        .bind(vodozemac_pickle.as_deref())
         .bind(now_ms)
         .execute(&*self.pool)
         .await
@@ -431,7 +458,7 @@ impl MegolmSessionStorage {
             })?,
         };
 
-        Ok(rows.into_iter().map(Into::into).collect())
+        rows.into_iter().map(MegolmSessionRow::open).collect()
     }
 
     /// 统计各 pickle_format 的 session 数量（监控/迁移进度）
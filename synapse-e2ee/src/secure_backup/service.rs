@@ -60,7 +60,10 @@ impl SecureBackupService {
         .bind(&backup_id)
         .bind(&version)
         .bind("m.megolm_backup.v1.secure")
-        .bind(serde_json::to_string(&auth_data).map_err(|e| ApiError::internal(e.to_string()))?)
+        .bind(
+            serde_json::to_string(&auth_data)
+                .map_err(|e| ApiError::internal_with_log("Failed to serialize backup auth data", &e))?,
+        )
         .execute(&*self.pool)
         .await
         .map_err(|e| {
@@ -112,7 +115,10 @@ impl SecureBackupService {
         .bind(&backup_id)
         .bind(&version)
         .bind(algorithm)
-        .bind(serde_json::to_string(&auth_data).map_err(|e| ApiError::internal(e.to_string()))?)
+        .bind(
+            serde_json::to_string(&auth_data)
+                .map_err(|e| ApiError::internal_with_log("Failed to serialize backup auth data", &e))?,
+        )
         .execute(&*self.pool)
         .await
         .map_err(|e| {
@@ -1,5 +1,7 @@
 pub mod service;
 pub mod storage;
+pub mod verification_tracker;
 
 pub use service::ToDeviceService;
 pub use storage::ToDeviceStorage;
+pub use verification_tracker::VerificationFlowTracker;
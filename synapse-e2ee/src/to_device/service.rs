@@ -1,4 +1,5 @@
 use super::storage::{ToDeviceMessage, ToDeviceStorage};
+use super::verification_tracker::VerificationFlowTracker;
 use serde_json::Value;
 use std::sync::Arc;
 use synapse_common::ApiError;
@@ -10,11 +11,12 @@ const TRANSACTION_MAX_AGE_MS: i64 = 24 * 60 * 60 * 1000;
 pub struct ToDeviceService {
     storage: ToDeviceStorage,
     user_storage: Option<Arc<dyn UserStore>>,
+    verification_tracker: Arc<VerificationFlowTracker>,
 }
 
 impl ToDeviceService {
     pub fn new(storage: ToDeviceStorage) -> Self {
-        Self { storage, user_storage: None }
+        Self { storage, user_storage: None, verification_tracker: Arc::new(VerificationFlowTracker::new()) }
     }
 
     pub fn with_user_storage(mut self, user_storage: Arc<dyn UserStore>) -> Self {
@@ -22,6 +24,15 @@ impl ToDeviceService {
         self
     }
 
+    pub fn with_verification_tracker(mut self, tracker: Arc<VerificationFlowTracker>) -> Self {
+        self.verification_tracker = tracker;
+        self
+    }
+
+    pub fn verification_tracker(&self) -> &Arc<VerificationFlowTracker> {
+        &self.verification_tracker
+    }
+
     pub async fn send_messages(
         &self,
         sender_user_id: &str,
@@ -53,6 +64,7 @@ impl ToDeviceService {
 
                 if let Some(device_map) = devices.as_object() {
                     for (device_id, content) in device_map {
+                        self.verification_tracker.observe(sender_user_id, user_id, event_type, content);
                         self.storage
                             .add_message(ToDeviceMessage {
                                 sender_user_id,
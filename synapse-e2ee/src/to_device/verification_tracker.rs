@@ -0,0 +1,199 @@
+//! In-flight tracking of `m.key.verification.*` to-device exchanges.
+//!
+//! The server only relays verification messages, so it cannot tell whether a
+//! verification actually succeeded cryptographically. It can however see the
+//! shape of each exchange: a `request`/`start` opens a flow, `done` closes it
+//! successfully, `cancel` aborts it, and a flow with no traffic for
+//! [`VERIFICATION_FLOW_TIMEOUT`] is treated as abandoned, matching the timeout
+//! clients apply to verification requests.
+
+use dashmap::DashMap;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use synapse_common::current_timestamp_millis;
+use synapse_common::server_metrics::{ServerMetrics, VerificationOutcome};
+
+const VERIFICATION_EVENT_PREFIX: &str = "m.key.verification.";
+pub const VERIFICATION_FLOW_TIMEOUT: Duration = Duration::from_secs(10 * 60);
+/// Flows beyond this many are relayed but not tracked.
+const MAX_TRACKED_FLOWS: usize = 10_000;
+
+/// A flow is shared by both participants, whichever of them sends a message.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct FlowKey {
+    users: (String, String),
+    transaction_id: String,
+}
+
+impl FlowKey {
+    fn new(sender: &str, recipient: &str, transaction_id: &str) -> Self {
+        let users = if sender <= recipient {
+            (sender.to_string(), recipient.to_string())
+        } else {
+            (recipient.to_string(), sender.to_string())
+        };
+        Self { users, transaction_id: transaction_id.to_string() }
+    }
+}
+
+#[derive(Default)]
+pub struct VerificationFlowTracker {
+    /// Last activity of each open flow, in milliseconds.
+    flows: DashMap<FlowKey, i64>,
+    metrics: Option<Arc<ServerMetrics>>,
+}
+
+impl VerificationFlowTracker {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_metrics(mut self, metrics: Arc<ServerMetrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Records a to-device message from `sender` to `recipient`. Messages
+    /// other than `m.key.verification.*` are ignored.
+    pub fn observe(&self, sender: &str, recipient: &str, event_type: &str, content: &Value) {
+        let Some(step) = event_type.strip_prefix(VERIFICATION_EVENT_PREFIX) else {
+            return;
+        };
+        let Some(transaction_id) = content.get("transaction_id").and_then(Value::as_str) else {
+            return;
+        };
+        let key = FlowKey::new(sender, recipient, transaction_id);
+        let now = current_timestamp_millis();
+
+        match step {
+            "request" | "start" => {
+                if let Some(mut last_activity) = self.flows.get_mut(&key) {
+                    *last_activity = now;
+                } else if self.flows.len() < MAX_TRACKED_FLOWS {
+                    self.flows.insert(key, now);
+                    self.record_started();
+                }
+            }
+            "done" => {
+                if self.flows.remove(&key).is_some() {
+                    self.record_finished(VerificationOutcome::Succeeded);
+                }
+            }
+            // A request fans out to every device of the other user; once one
+            // of them accepts, the rest are told `m.accepted`. That is not
+            // the flow being cancelled.
+            "cancel" if content.get("code").and_then(Value::as_str) == Some("m.accepted") => self.touch(&key, now),
+            "cancel" => {
+                if self.flows.remove(&key).is_some() {
+                    self.record_finished(VerificationOutcome::Cancelled);
+                }
+            }
+            _ => self.touch(&key, now),
+        }
+    }
+
+    /// Drops flows idle for longer than [`VERIFICATION_FLOW_TIMEOUT`] and
+    /// returns how many were dropped.
+    pub fn expire_stale(&self) -> usize {
+        self.expire_idle_since(current_timestamp_millis() - VERIFICATION_FLOW_TIMEOUT.as_millis() as i64)
+    }
+
+    pub fn in_flight(&self) -> usize {
+        self.flows.len()
+    }
+
+    fn expire_idle_since(&self, cutoff_ms: i64) -> usize {
+        let before = self.flows.len();
+        self.flows.retain(|_, last_activity| *last_activity >= cutoff_ms);
+        let expired = before.saturating_sub(self.flows.len());
+        for _ in 0..expired {
+            self.record_finished(VerificationOutcome::TimedOut);
+        }
+        expired
+    }
+
+    fn touch(&self, key: &FlowKey, now: i64) {
+        if let Some(mut last_activity) = self.flows.get_mut(key) {
+            *last_activity = now;
+        }
+    }
+
+    fn record_started(&self) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_verification_started();
+        }
+    }
+
+    fn record_finished(&self, outcome: VerificationOutcome) {
+        if let Some(metrics) = &self.metrics {
+            metrics.record_verification_finished(outcome);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use synapse_common::metrics::MetricsCollector;
+
+    const ALICE: &str = "@alice:example.com";
+    const BOB: &str = "@bob:example.com";
+
+    fn tracker() -> (VerificationFlowTracker, Arc<ServerMetrics>) {
+        let metrics = Arc::new(ServerMetrics::new(Arc::new(MetricsCollector::new())));
+        (VerificationFlowTracker::new().with_metrics(metrics.clone()), metrics)
+    }
+
+    fn txn(id: &str) -> Value {
+        json!({ "transaction_id": id })
+    }
+
+    #[test]
+    fn flow_is_shared_by_both_participants() {
+        let (tracker, metrics) = tracker();
+        tracker.observe(ALICE, BOB, "m.key.verification.request", &txn("t1"));
+        tracker.observe(ALICE, BOB, "m.key.verification.request", &txn("t1"));
+        tracker.observe(BOB, ALICE, "m.key.verification.ready", &txn("t1"));
+        tracker.observe(ALICE, BOB, "m.key.verification.start", &txn("t1"));
+        assert_eq!(tracker.in_flight(), 1);
+        assert_eq!(metrics.e2ee_verification_started_total.get(), 1);
+
+        tracker.observe(BOB, ALICE, "m.key.verification.done", &txn("t1"));
+        tracker.observe(ALICE, BOB, "m.key.verification.done", &txn("t1"));
+        assert_eq!(tracker.in_flight(), 0);
+        assert_eq!(metrics.e2ee_verification_succeeded_total.get(), 1);
+        assert_eq!(metrics.e2ee_verification_in_flight.get(), 0.0);
+    }
+
+    #[test]
+    fn accepted_elsewhere_is_not_a_cancellation() {
+        let (tracker, metrics) = tracker();
+        tracker.observe(ALICE, BOB, "m.key.verification.request", &txn("t1"));
+        tracker.observe(
+            ALICE,
+            BOB,
+            "m.key.verification.cancel",
+            &json!({ "transaction_id": "t1", "code": "m.accepted" }),
+        );
+        assert_eq!(tracker.in_flight(), 1);
+
+        tracker.observe(BOB, ALICE, "m.key.verification.cancel", &json!({ "transaction_id": "t1", "code": "m.user" }));
+        assert_eq!(tracker.in_flight(), 0);
+        assert_eq!(metrics.e2ee_verification_cancelled_total.get(), 1);
+    }
+
+    #[test]
+    fn idle_flows_time_out() {
+        let (tracker, metrics) = tracker();
+        tracker.observe(ALICE, BOB, "m.key.verification.request", &txn("t1"));
+        tracker.observe(ALICE, BOB, "m.room_key", &txn("t2"));
+        assert_eq!(tracker.expire_stale(), 0);
+
+        assert_eq!(tracker.expire_idle_since(current_timestamp_millis() + 1), 1);
+        assert_eq!(tracker.in_flight(), 0);
+        assert_eq!(metrics.e2ee_verification_timed_out_total.get(), 1);
+        assert_eq!(metrics.e2ee_verification_in_flight.get(), 0.0);
+    }
+}
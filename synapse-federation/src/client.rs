@@ -1,22 +1,28 @@
 use crate::key_rotation::KeyRotationManager;
+use crate::resolver::DualStackResolver;
 use crate::signing::canonical_federation_request_bytes;
 use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine as _};
 use ed25519_dalek::Signer;
 use ed25519_dalek::SigningKey as DalekSigningKey;
 use reqwest::{Client, StatusCode};
 use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
 use std::collections::HashMap;
+use std::net::IpAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
-use tokio::sync::RwLock;
+use synapse_common::FederationOutboundConfig;
+use tokio::sync::{OwnedSemaphorePermit, RwLock, Semaphore};
 
 const MAX_RETRIES: u32 = 3;
 const RETRY_BASE_DELAY_MS: u64 = 500;
 const MAX_RETRY_DELAY_MS: u64 = 30000;
 const KEY_CACHE_TTL_SECS: u64 = 3600;
 const WELL_KNOWN_TIMEOUT_SECS: u64 = 5;
+/// Per-destination limiters unused for this long are dropped.
+const DESTINATION_LIMITER_IDLE_SECS: u64 = 600;
 
 /// Effective cache TTL (seconds) for a set of server keys: never longer than the
 /// default window, and never past the key's own `valid_until_ts` validity window.
@@ -26,6 +32,29 @@ fn effective_cache_ttl_secs(keys: &ServerKeys, now_ms: i64) -> u64 {
 }
 const DEFAULT_FEDERATION_PORT: u16 = 8448;
 
+/// Splits `host[:port]` or `[ipv6][:port]`; the host comes back unbracketed.
+fn split_host_port(name: &str) -> (&str, Option<&str>) {
+    if let Some(rest) = name.strip_prefix('[') {
+        if let Some((host, after)) = rest.split_once(']') {
+            return (host, after.strip_prefix(':'));
+        }
+        return (name, None);
+    }
+    match name.rsplit_once(':') {
+        Some((host, port)) if !host.contains(':') => (host, Some(port)),
+        _ => (name, None),
+    }
+}
+
+/// `host` as it must appear in a URL authority: IPv6 literals are bracketed.
+fn url_host(host: &str) -> Cow<'_, str> {
+    if host.contains(':') {
+        Cow::Owned(format!("[{host}]"))
+    } else {
+        Cow::Borrowed(host)
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServerKeys {
     pub server_name: String,
@@ -183,6 +212,54 @@ pub struct FederationClient {
     key_rotation_manager: Arc<KeyRotationManager>,
     key_cache: Arc<RwLock<HashMap<String, CachedKeys>>>,
     server_resolution_cache: Arc<RwLock<HashMap<String, ResolvedServer>>>,
+    limits: RequestLimits,
+    resolver: Arc<DualStackResolver>,
+}
+
+/// Semaphores bounding outbound requests globally and per destination.
+struct RequestLimits {
+    global: Arc<Semaphore>,
+    per_destination: moka::sync::Cache<String, Arc<Semaphore>>,
+    per_destination_permits: usize,
+    /// How long a request may wait for its permits.
+    acquire_timeout: Duration,
+}
+
+impl RequestLimits {
+    fn new(config: &FederationOutboundConfig) -> Self {
+        Self {
+            global: Arc::new(Semaphore::new(config.max_concurrent_requests.max(1))),
+            per_destination: moka::sync::Cache::builder()
+                .time_to_idle(Duration::from_secs(DESTINATION_LIMITER_IDLE_SECS))
+                .build(),
+            per_destination_permits: config.max_concurrent_requests_per_destination.max(1),
+            acquire_timeout: Duration::from_millis(config.request_timeout_ms.max(1)),
+        }
+    }
+
+    /// Waits for a destination slot, then a global one, so requests queued
+    /// behind a slow destination do not hold global slots.
+    async fn acquire(
+        &self,
+        destination: &str,
+    ) -> Result<(OwnedSemaphorePermit, OwnedSemaphorePermit), FederationClientError> {
+        let destination_limiter = self
+            .per_destination
+            .get_with_by_ref(destination, || Arc::new(Semaphore::new(self.per_destination_permits)));
+        let acquire = async {
+            let destination_permit = destination_limiter.acquire_owned().await;
+            let global_permit = self.global.clone().acquire_owned().await;
+            (destination_permit, global_permit)
+        };
+        match tokio::time::timeout(self.acquire_timeout, acquire).await {
+            Ok((Ok(destination_permit), Ok(global_permit))) => Ok((destination_permit, global_permit)),
+            Ok(_) => Err(FederationClientError::Connection("Request limiter closed".into())),
+            Err(_) => {
+                tracing::warn!(destination, "Timed out waiting for an outbound federation request slot");
+                Err(FederationClientError::Timeout)
+            }
+        }
+    }
 }
 
 impl std::fmt::Debug for FederationClient {
@@ -193,9 +270,19 @@ impl std::fmt::Debug for FederationClient {
 
 impl FederationClient {
     pub fn new(server_name: String, key_rotation_manager: Arc<KeyRotationManager>) -> Self {
-        let http_client = Client::builder()
-            .timeout(Duration::from_secs(30))
-            .connect_timeout(Duration::from_secs(10))
+        Self::with_outbound_config(server_name, key_rotation_manager, &FederationOutboundConfig::default())
+    }
+
+    pub fn with_outbound_config(
+        server_name: String,
+        key_rotation_manager: Arc<KeyRotationManager>,
+        config: &FederationOutboundConfig,
+    ) -> Self {
+        let resolver = Arc::new(DualStackResolver::new());
+        let http_client = synapse_common::outbound_http::client_builder()
+            .dns_resolver(resolver.clone())
+            .timeout(Duration::from_millis(config.request_timeout_ms.max(1)))
+            .connect_timeout(Duration::from_millis(config.connect_timeout_ms.max(1)))
             .pool_max_idle_per_host(20)
             .pool_idle_timeout(Duration::from_secs(90))
             .tcp_keepalive(Duration::from_secs(60))
@@ -211,6 +298,8 @@ impl FederationClient {
             key_rotation_manager,
             key_cache: Arc::new(RwLock::new(HashMap::new())),
             server_resolution_cache: Arc::new(RwLock::new(HashMap::new())),
+            limits: RequestLimits::new(config),
+            resolver,
         }
     }
 
@@ -269,32 +358,24 @@ impl FederationClient {
             }
         }
 
-        let resolved = if server_name.starts_with('[') {
-            if let Some(close) = server_name.find(']') {
-                let host = server_name[1..close].to_string();
-                let port = if close + 1 < server_name.len() && server_name[close + 1..].starts_with(':') {
-                    server_name[close + 2..].parse().unwrap_or(DEFAULT_FEDERATION_PORT)
-                } else {
-                    DEFAULT_FEDERATION_PORT
-                };
-                ResolvedServer { server_name: server_name.to_string(), host, port }
-            } else {
-                ResolvedServer {
-                    server_name: server_name.to_string(),
-                    host: server_name.to_string(),
-                    port: DEFAULT_FEDERATION_PORT,
-                }
-            }
-        } else if let Some(colon_pos) = server_name.rfind(':') {
-            let host = server_name[..colon_pos].to_string();
-            let port = server_name[colon_pos + 1..].parse().unwrap_or(DEFAULT_FEDERATION_PORT);
-            ResolvedServer { server_name: server_name.to_string(), host, port }
-        } else {
-            self.resolve_via_well_known(server_name).await.unwrap_or_else(|| ResolvedServer {
+        let (host, port) = split_host_port(server_name);
+        let resolved = match port {
+            Some(port) => ResolvedServer {
+                server_name: server_name.to_string(),
+                host: host.to_string(),
+                port: port.parse().unwrap_or(DEFAULT_FEDERATION_PORT),
+            },
+            // IP literals are connected to directly, without delegation.
+            None if host.parse::<IpAddr>().is_ok() => ResolvedServer {
+                server_name: server_name.to_string(),
+                host: host.to_string(),
+                port: DEFAULT_FEDERATION_PORT,
+            },
+            None => self.resolve_via_well_known(server_name).await.unwrap_or_else(|| ResolvedServer {
                 server_name: server_name.to_string(),
                 host: server_name.to_string(),
                 port: DEFAULT_FEDERATION_PORT,
-            })
+            }),
         };
 
         self.server_resolution_cache.write().await.insert(server_name.to_string(), resolved.clone());
@@ -304,7 +385,11 @@ impl FederationClient {
 
     async fn resolve_via_well_known(&self, server_name: &str) -> Option<ResolvedServer> {
         let url = format!("https://{server_name}/.well-known/matrix/server");
-        let client = Client::builder().timeout(Duration::from_secs(WELL_KNOWN_TIMEOUT_SECS)).build().ok()?;
+        let client = synapse_common::outbound_http::client_builder()
+            .timeout(Duration::from_secs(WELL_KNOWN_TIMEOUT_SECS))
+            .dns_resolver(self.resolver.clone())
+            .build()
+            .ok()?;
 
         let response = client.get(&url).send().await.ok()?;
         if !response.status().is_success() {
@@ -314,24 +399,20 @@ impl FederationClient {
         let body: serde_json::Value = response.json().await.ok()?;
         let delegated = body.get("m.server")?.as_str()?.to_string();
 
-        if let Some(colon_pos) = delegated.rfind(':') {
-            let host = delegated[..colon_pos].to_string();
-            let port = delegated[colon_pos + 1..].parse().ok()?;
-            Some(ResolvedServer { server_name: server_name.to_string(), host, port })
-        } else {
-            Some(ResolvedServer {
-                server_name: server_name.to_string(),
-                host: delegated,
-                port: DEFAULT_FEDERATION_PORT,
-            })
-        }
+        let (host, port) = split_host_port(&delegated);
+        let port = match port {
+            Some(port) => port.parse().ok()?,
+            None => DEFAULT_FEDERATION_PORT,
+        };
+        Some(ResolvedServer { server_name: server_name.to_string(), host: host.to_string(), port })
     }
 
     fn build_url(resolved: &ResolvedServer, path: &str) -> String {
+        let host = url_host(&resolved.host);
         if resolved.port == 443 {
-            format!("https://{}{}", resolved.host, path)
+            format!("https://{host}{path}")
         } else {
-            format!("https://{}:{}{}", resolved.host, resolved.port, path)
+            format!("https://{}:{}{}", host, resolved.port, path)
         }
     }
 
@@ -353,13 +434,15 @@ impl FederationClient {
                 tokio::time::sleep(Duration::from_millis(delay)).await;
             }
 
+            let _permits = self.limits.acquire(destination).await?;
             let retry_request = match method {
                 "GET" => self.http_client.get(&url),
                 "PUT" => self.http_client.put(&url),
                 "POST" => self.http_client.post(&url),
                 _ => return Err(FederationClientError::Connection(format!("Unsupported method: {method}"))),
             };
-            let retry_request = retry_request.header("Authorization", &auth_header).header("Host", &resolved.host);
+            let retry_request =
+                retry_request.header("Authorization", &auth_header).header("Host", url_host(&resolved.host).as_ref());
             let retry_request = if let Some(content) = body {
                 retry_request.header("Content-Type", "application/json").body(content.to_string())
             } else {
@@ -368,6 +451,9 @@ impl FederationClient {
 
             match retry_request.send().await {
                 Ok(response) => {
+                    if let Some(addr) = response.remote_addr() {
+                        self.resolver.record_connected(&resolved.host, addr);
+                    }
                     let status = response.status();
                     if status == StatusCode::TOO_MANY_REQUESTS {
                         let retry_after = response
@@ -704,6 +790,21 @@ impl FederationClient {
         self.handle_response(response).await
     }
 
+    pub async fn get_hierarchy(
+        &self,
+        destination: &str,
+        room_id: &str,
+        suggested_only: bool,
+    ) -> Result<serde_json::Value, FederationClientError> {
+        let path = format!(
+            "/_matrix/federation/v1/hierarchy/{}?suggested_only={}",
+            urlencoding::encode(room_id),
+            suggested_only
+        );
+        let response = self.send_signed_request("GET", &path, destination, None).await?;
+        self.handle_response(response).await
+    }
+
     pub async fn knock_room(
         &self,
         destination: &str,
@@ -916,6 +1017,41 @@ mod tests {
         );
     }
 
+    #[tokio::test]
+    async fn slow_destination_does_not_consume_other_destinations_slots() {
+        let limits = RequestLimits::new(&FederationOutboundConfig {
+            request_timeout_ms: 50,
+            max_concurrent_requests: 3,
+            max_concurrent_requests_per_destination: 2,
+            ..Default::default()
+        });
+
+        let _slow_a = limits.acquire("slow.example").await.unwrap();
+        let _slow_b = limits.acquire("slow.example").await.unwrap();
+        assert!(matches!(limits.acquire("slow.example").await, Err(FederationClientError::Timeout)));
+        assert_eq!(limits.global.available_permits(), 1);
+
+        let fast = limits.acquire("fast.example").await.unwrap();
+        assert!(matches!(limits.acquire("other.example").await, Err(FederationClientError::Timeout)));
+        drop(fast);
+        assert!(limits.acquire("other.example").await.is_ok());
+    }
+
+    #[test]
+    fn ipv6_literals_are_bracketed_in_urls() {
+        assert_eq!(split_host_port("[2001:db8::1]:8448"), ("2001:db8::1", Some("8448")));
+        assert_eq!(split_host_port("[2001:db8::1]"), ("2001:db8::1", None));
+        assert_eq!(split_host_port("2001:db8::1"), ("2001:db8::1", None));
+        assert_eq!(split_host_port("example.com:443"), ("example.com", Some("443")));
+
+        let resolved =
+            ResolvedServer { server_name: "[2001:db8::1]".to_string(), host: "2001:db8::1".to_string(), port: 8448 };
+        assert_eq!(
+            FederationClient::build_url(&resolved, "/_matrix/key/v2/server"),
+            "https://[2001:db8::1]:8448/_matrix/key/v2/server"
+        );
+    }
+
     #[test]
     fn test_version_response_deserialization() {
         let json = r#"{"server": {"name": "synapse-rust", "version": "0.1.0"}}"#;
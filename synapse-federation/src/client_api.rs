@@ -175,6 +175,15 @@ pub trait FederationClientApi: Send + Sync {
         since: Option<&str>,
     ) -> Result<serde_json::Value, FederationClientError>;
 
+    /// Fetch the space hierarchy of a room from a remote server
+    /// (`GET /_matrix/federation/v1/hierarchy/{roomId}`).
+    async fn get_hierarchy(
+        &self,
+        destination: &str,
+        room_id: &str,
+        suggested_only: bool,
+    ) -> Result<serde_json::Value, FederationClientError>;
+
     /// Send a knock event to a remote server.
     async fn knock_room(
         &self,
@@ -412,6 +421,15 @@ impl FederationClientApi for FederationClient {
         FederationClient::get_public_rooms(self, destination, limit, since).await
     }
 
+    async fn get_hierarchy(
+        &self,
+        destination: &str,
+        room_id: &str,
+        suggested_only: bool,
+    ) -> Result<serde_json::Value, FederationClientError> {
+        FederationClient::get_hierarchy(self, destination, room_id, suggested_only).await
+    }
+
     async fn knock_room(
         &self,
         destination: &str,
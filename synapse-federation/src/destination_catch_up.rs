@@ -0,0 +1,332 @@
+//! Per-destination catch-up for the federation sender.
+//!
+//! For every destination the sender remembers the newest event it owes each
+//! shared room (`destination_rooms`) and the stream position of the last
+//! transaction the destination accepted
+//! (`destination_retry_timings.last_successful_stream_ordering`). While a
+//! destination is unreachable nothing is queued per event; once its backoff
+//! expires the sender replays only the latest event per room newer than that
+//! position, and the remote fills any gaps with `/get_missing_events`.
+
+use serde_json::Value;
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Maximum number of rooms replayed to one destination per catch-up pass.
+pub const CATCH_UP_BATCH_SIZE: i64 = 50;
+
+/// A room whose newest event has not yet been acknowledged by a destination.
+#[derive(Debug, Clone, PartialEq, sqlx::FromRow)]
+pub struct CatchUpRoom {
+    pub room_id: String,
+    pub event_id: String,
+    pub stream_ordering: i64,
+    pub pdu: Value,
+}
+
+/// Retry bookkeeping for one destination.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, sqlx::FromRow)]
+pub struct DestinationRetryState {
+    pub failure_count: i32,
+    pub retry_last_ts: i64,
+    pub retry_interval: i64,
+    pub last_successful_stream_ordering: i64,
+}
+
+impl DestinationRetryState {
+    /// A destination with outstanding failures receives no live PDUs; its
+    /// rooms are replayed by catch-up instead.
+    pub fn is_catching_up(&self) -> bool {
+        self.failure_count > 0
+    }
+
+    /// Whether the backoff for a failing destination has expired.
+    pub fn catch_up_due(&self, now: i64) -> bool {
+        self.is_catching_up() && self.retry_last_ts.saturating_add(self.retry_interval) <= now
+    }
+}
+
+/// Highest `stream_ordering` among `rooms`, which is the position a
+/// destination has caught up to once all of them were delivered.
+pub fn max_stream_ordering(rooms: &[CatchUpRoom]) -> Option<i64> {
+    rooms.iter().map(|room| room.stream_ordering).max()
+}
+
+/// Keep only the newest entry per room, ordered by stream position.
+pub fn latest_per_room(rooms: Vec<CatchUpRoom>) -> Vec<CatchUpRoom> {
+    let mut latest: HashMap<String, CatchUpRoom> = HashMap::new();
+    for room in rooms {
+        match latest.get(&room.room_id) {
+            Some(existing) if existing.stream_ordering >= room.stream_ordering => {}
+            _ => {
+                latest.insert(room.room_id.clone(), room);
+            }
+        }
+    }
+    let mut rooms: Vec<CatchUpRoom> = latest.into_values().collect();
+    rooms.sort_by_key(|room| room.stream_ordering);
+    rooms
+}
+
+/// Record `event` as the newest event `destination` is owed for its room.
+/// The stored position only ever moves forward.
+pub async fn record_destination_room(
+    pool: &PgPool,
+    destination: &str,
+    room_id: &str,
+    event_id: &str,
+    pdu: &Value,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r"
+        INSERT INTO destination_rooms (destination, room_id, event_id, stream_ordering, pdu, updated_ts)
+        VALUES (
+            $1, $2, $3,
+            COALESCE(
+                (SELECT stream_ordering FROM events WHERE event_id = $3),
+                (SELECT COALESCE(MAX(stream_ordering), 0) FROM events)
+            ),
+            $4, $5
+        )
+        ON CONFLICT (destination, room_id) DO UPDATE
+        SET event_id = EXCLUDED.event_id,
+            stream_ordering = EXCLUDED.stream_ordering,
+            pdu = EXCLUDED.pdu,
+            updated_ts = EXCLUDED.updated_ts
+        WHERE destination_rooms.stream_ordering <= EXCLUDED.stream_ordering
+        ",
+    )
+    .bind(destination)
+    .bind(room_id)
+    .bind(event_id)
+    .bind(pdu)
+    .bind(now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+pub async fn get_retry_state(pool: &PgPool, destination: &str) -> Result<Option<DestinationRetryState>, sqlx::Error> {
+    sqlx::query_as::<_, DestinationRetryState>(
+        r"
+        SELECT failure_count, retry_last_ts, retry_interval, last_successful_stream_ordering
+        FROM destination_retry_timings
+        WHERE destination = $1
+        ",
+    )
+    .bind(destination)
+    .fetch_optional(pool)
+    .await
+}
+
+/// Destinations with outstanding failures, soonest-due first.
+pub async fn get_failing_destinations(
+    pool: &PgPool,
+    limit: i64,
+) -> Result<Vec<(String, DestinationRetryState)>, sqlx::Error> {
+    let rows: Vec<(String, i32, i64, i64, i64)> = sqlx::query_as(
+        r"
+        SELECT destination, failure_count, retry_last_ts, retry_interval, last_successful_stream_ordering
+        FROM destination_retry_timings
+        WHERE failure_count > 0
+        ORDER BY retry_last_ts + retry_interval ASC
+        LIMIT $1
+        ",
+    )
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(rows
+        .into_iter()
+        .map(|(destination, failure_count, retry_last_ts, retry_interval, last_successful_stream_ordering)| {
+            (
+                destination,
+                DestinationRetryState { failure_count, retry_last_ts, retry_interval, last_successful_stream_ordering },
+            )
+        })
+        .collect())
+}
+
+/// Highest `stream_ordering` among the given events, if any are persisted.
+pub async fn get_max_stream_ordering_for_events(
+    pool: &PgPool,
+    event_ids: &[String],
+) -> Result<Option<i64>, sqlx::Error> {
+    if event_ids.is_empty() {
+        return Ok(None);
+    }
+    sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(stream_ordering) FROM events WHERE event_id = ANY($1)")
+        .bind(event_ids)
+        .fetch_one(pool)
+        .await
+}
+
+/// Rooms `destination` has not been sent since `after_stream_ordering`.
+pub async fn get_catch_up_rooms(
+    pool: &PgPool,
+    destination: &str,
+    after_stream_ordering: i64,
+    limit: i64,
+) -> Result<Vec<CatchUpRoom>, sqlx::Error> {
+    sqlx::query_as::<_, CatchUpRoom>(
+        r"
+        SELECT room_id, event_id, stream_ordering, pdu
+        FROM destination_rooms
+        WHERE destination = $1 AND stream_ordering > $2
+        ORDER BY stream_ordering ASC
+        LIMIT $3
+        ",
+    )
+    .bind(destination)
+    .bind(after_stream_ordering)
+    .bind(limit)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record a successful transaction: clear the failure state and advance the
+/// caught-up position to `stream_ordering` when given.
+pub async fn mark_destination_success(
+    pool: &PgPool,
+    destination: &str,
+    stream_ordering: Option<i64>,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r"
+        INSERT INTO destination_retry_timings
+            (destination, retry_interval, retry_last_ts, failure_count, last_successful_stream_ordering, created_ts, updated_ts)
+        VALUES ($1, 0, 0, 0, COALESCE($2, 0), $3, $3)
+        ON CONFLICT (destination) DO UPDATE
+        SET retry_interval = 0,
+            retry_last_ts = 0,
+            failure_count = 0,
+            last_successful_stream_ordering = GREATEST(
+                destination_retry_timings.last_successful_stream_ordering,
+                COALESCE($2, destination_retry_timings.last_successful_stream_ordering)
+            ),
+            updated_ts = $3
+        ",
+    )
+    .bind(destination)
+    .bind(stream_ordering)
+    .bind(now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Move the caught-up position of a destination still in catch-up mode.
+pub async fn advance_stream_position(
+    pool: &PgPool,
+    destination: &str,
+    stream_ordering: i64,
+    now: i64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r"
+        UPDATE destination_retry_timings
+        SET last_successful_stream_ordering = GREATEST(last_successful_stream_ordering, $2),
+            updated_ts = $3
+        WHERE destination = $1
+        ",
+    )
+    .bind(destination)
+    .bind(stream_ordering)
+    .bind(now)
+    .execute(pool)
+    .await?;
+    Ok(())
+}
+
+/// Record a failed transaction and schedule the next catch-up attempt
+/// `retry_interval` milliseconds from `now`. Returns the new failure count.
+pub async fn mark_destination_failure(
+    pool: &PgPool,
+    destination: &str,
+    retry_interval: i64,
+    now: i64,
+) -> Result<i32, sqlx::Error> {
+    sqlx::query_scalar::<_, i32>(
+        r"
+        INSERT INTO destination_retry_timings
+            (destination, retry_interval, retry_last_ts, failure_count, last_successful_stream_ordering, created_ts, updated_ts)
+        VALUES ($1, $2, $3, 1, 0, $3, $3)
+        ON CONFLICT (destination) DO UPDATE
+        SET retry_interval = $2,
+            retry_last_ts = $3,
+            failure_count = destination_retry_timings.failure_count + 1,
+            updated_ts = $3
+        RETURNING failure_count
+        ",
+    )
+    .bind(destination)
+    .bind(retry_interval)
+    .bind(now)
+    .fetch_one(pool)
+    .await
+}
+
+/// Drop queued retries of PDU transactions for `destination`; catch-up has
+/// delivered a newer event for every room they covered.
+pub async fn supersede_pending_pdus(pool: &PgPool, destination: &str) -> Result<u64, sqlx::Error> {
+    sqlx::query(
+        r"
+        UPDATE federation_queue
+        SET status = 'superseded'
+        WHERE destination = $1 AND status = 'pending' AND event_type = 'm.room.event'
+        ",
+    )
+    .bind(destination)
+    .execute(pool)
+    .await
+    .map(|r| r.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn room(room_id: &str, event_id: &str, stream_ordering: i64) -> CatchUpRoom {
+        CatchUpRoom {
+            room_id: room_id.to_string(),
+            event_id: event_id.to_string(),
+            stream_ordering,
+            pdu: json!({"event_id": event_id, "room_id": room_id}),
+        }
+    }
+
+    #[test]
+    fn latest_per_room_keeps_newest_event_in_stream_order() {
+        let rooms = latest_per_room(vec![
+            room("!b:remote", "$b1", 7),
+            room("!a:remote", "$a1", 3),
+            room("!a:remote", "$a2", 9),
+            room("!b:remote", "$b0", 5),
+        ]);
+        let ids: Vec<&str> = rooms.iter().map(|r| r.event_id.as_str()).collect();
+        assert_eq!(ids, vec!["$b1", "$a2"]);
+        assert_eq!(max_stream_ordering(&rooms), Some(9));
+        assert_eq!(max_stream_ordering(&[]), None);
+    }
+
+    #[test]
+    fn catch_up_due_waits_for_backoff() {
+        let state = DestinationRetryState {
+            failure_count: 2,
+            retry_last_ts: 1_000,
+            retry_interval: 5_000,
+            last_successful_stream_ordering: 42,
+        };
+        assert!(state.is_catching_up());
+        assert!(!state.catch_up_due(5_999));
+        assert!(state.catch_up_due(6_000));
+
+        let healthy = DestinationRetryState { failure_count: 0, ..state };
+        assert!(!healthy.is_catching_up());
+        assert!(!healthy.catch_up_due(i64::MAX));
+    }
+}
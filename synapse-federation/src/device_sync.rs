@@ -45,10 +45,13 @@ impl DeviceSyncManager {
         cache_manager: Option<Arc<CacheManager>>,
         task_queue: Option<Arc<RedisTaskQueue>>,
     ) -> Self {
-        let http_client = Client::builder().timeout(std::time::Duration::from_secs(10)).build().unwrap_or_else(|e| {
-            tracing::warn!("Failed to build HTTP client, using default: {}", e);
-            Client::new()
-        });
+        let http_client = synapse_common::outbound_http::client_builder()
+            .timeout(std::time::Duration::from_secs(10))
+            .build()
+            .unwrap_or_else(|e| {
+                tracing::warn!("Failed to build HTTP client, using default: {}", e);
+                Client::new()
+            });
 
         Self {
             pool: pool.clone(),
@@ -5,6 +5,7 @@
 //! `AppState` and the service container.
 
 use std::str::FromStr;
+use synapse_storage::Receipt;
 
 // ---------------------------------------------------------------------------
 // EduType — discriminant for Matrix federation EDU types
@@ -17,6 +18,8 @@ pub enum EduType {
     DeviceListUpdate,
     /// `m.direct_to_device` — to-device messages relayed via federation.
     DirectToDevice,
+    /// `m.receipt` — read receipts of the origin's users.
+    Receipt,
 }
 
 #[derive(Debug, Clone)]
@@ -39,6 +42,7 @@ impl FromStr for EduType {
             "m.presence" => Ok(Self::Presence),
             "m.device_list_update" => Ok(Self::DeviceListUpdate),
             "m.direct_to_device" => Ok(Self::DirectToDevice),
+            "m.receipt" => Ok(Self::Receipt),
             other => Err(UnknownEduType(other.to_string())),
         }
     }
@@ -70,6 +74,59 @@ pub fn user_matches_origin(user_id: &str, origin: &str) -> bool {
     user_id.rsplit_once(':').is_some_and(|(_, server_name)| server_name == origin)
 }
 
+/// Receipts of one room, as carried by an `m.receipt` EDU.
+pub type RoomReceipts = (String, Vec<Receipt>);
+
+/// Receipts of an `m.receipt` EDU grouped by room, with the number of
+/// receipts dropped. Only public `m.read` receipts of the origin's users are
+/// kept, at most `max` of them; a receipt without an event ID is dropped and
+/// one without a `ts` gets `now`.
+pub fn parse_receipt_edu(
+    origin: &str,
+    content: &serde_json::Value,
+    max: usize,
+    now: i64,
+) -> (Vec<RoomReceipts>, usize) {
+    let mut rooms = Vec::new();
+    let mut kept = 0;
+    let mut dropped = 0;
+
+    for (room_id, by_type) in content.as_object().into_iter().flatten() {
+        let mut receipts = Vec::new();
+        for (receipt_type, by_user) in by_type.as_object().into_iter().flatten() {
+            for (user_id, receipt) in by_user.as_object().into_iter().flatten() {
+                let event_id = receipt
+                    .get("event_ids")
+                    .and_then(|v| v.as_array())
+                    .and_then(|ids| ids.first())
+                    .and_then(|v| v.as_str());
+                let Some(event_id) =
+                    event_id.filter(|_| receipt_type == "m.read" && user_matches_origin(user_id, origin) && kept < max)
+                else {
+                    dropped += 1;
+                    continue;
+                };
+
+                let data =
+                    receipt.get("data").filter(|v| v.is_object()).cloned().unwrap_or_else(|| serde_json::json!({}));
+                receipts.push(Receipt {
+                    user_id: user_id.clone(),
+                    event_id: event_id.to_string(),
+                    receipt_type: receipt_type.clone(),
+                    ts: data.get("ts").and_then(|v| v.as_i64()).unwrap_or(now),
+                    data,
+                });
+                kept += 1;
+            }
+        }
+        if !receipts.is_empty() {
+            rooms.push((room_id.clone(), receipts));
+        }
+    }
+
+    (rooms, dropped)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -82,6 +139,7 @@ mod tests {
         assert_eq!("m.presence".parse::<EduType>().unwrap(), EduType::Presence);
         assert_eq!("m.device_list_update".parse::<EduType>().unwrap(), EduType::DeviceListUpdate);
         assert_eq!("m.direct_to_device".parse::<EduType>().unwrap(), EduType::DirectToDevice);
+        assert_eq!("m.receipt".parse::<EduType>().unwrap(), EduType::Receipt);
     }
 
     #[test]
@@ -182,4 +240,45 @@ mod tests {
     fn test_user_matches_origin_empty_origin() {
         assert!(!user_matches_origin("@alice:example.com", ""));
     }
+
+    // --- parse_receipt_edu ---
+
+    #[test]
+    fn test_parse_receipt_edu_groups_by_room() {
+        let content = serde_json::json!({
+            "!a:remote.test": { "m.read": {
+                "@u1:remote.test": { "event_ids": ["$e1"], "data": { "ts": 5, "thread_id": "main" } },
+                "@u2:remote.test": { "event_ids": ["$e2"], "data": {} },
+            }},
+            "!b:remote.test": { "m.read": { "@u3:remote.test": { "event_ids": ["$e3"], "data": { "ts": 7 } } } },
+        });
+
+        let (rooms, dropped) = parse_receipt_edu("remote.test", &content, 100, 42);
+        assert_eq!(dropped, 0);
+        assert_eq!(rooms.len(), 2);
+        let (room_id, receipts) = &rooms[0];
+        assert_eq!(room_id, "!a:remote.test");
+        assert_eq!(receipts[0].ts, 5);
+        assert_eq!(receipts[0].data["thread_id"], "main");
+        assert_eq!(receipts[1].event_id, "$e2");
+        assert_eq!(receipts[1].ts, 42);
+    }
+
+    #[test]
+    fn test_parse_receipt_edu_drops_foreign_private_and_excess_receipts() {
+        let content = serde_json::json!({ "!a:remote.test": {
+            "m.read": {
+                "@u1:remote.test": { "event_ids": ["$e1"] },
+                "@u2:remote.test": { "event_ids": ["$e2"] },
+                "@u3:remote.test": { "event_ids": [] },
+                "@spoof:other.test": { "event_ids": ["$e4"] },
+            },
+            "m.read.private": { "@u1:remote.test": { "event_ids": ["$e5"] } },
+        }});
+
+        let (rooms, dropped) = parse_receipt_edu("remote.test", &content, 1, 0);
+        assert_eq!(rooms[0].1.len(), 1);
+        assert_eq!(rooms[0].1[0].user_id, "@u1:remote.test");
+        assert_eq!(dropped, 4);
+    }
 }
@@ -1,11 +1,13 @@
 use crate::client::FederationTransaction;
 use crate::client_api::FederationClientApi;
+use crate::destination_catch_up as catch_up;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Arc;
 use synapse_common::current_timestamp_millis;
 use synapse_storage::membership::MemberStoreApi;
 use tokio::sync::{mpsc, RwLock};
+use tokio::task::JoinSet;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FederationEvent {
@@ -40,6 +42,163 @@ struct TransactionBatch {
     origin: String,
 }
 
+impl TransactionBatch {
+    fn new(origin: String) -> Self {
+        Self { pdus: Vec::new(), edus: Vec::new(), origin }
+    }
+
+    fn len(&self) -> usize {
+        self.pdus.len() + self.edus.len()
+    }
+
+    fn is_empty(&self) -> bool {
+        self.pdus.is_empty() && self.edus.is_empty()
+    }
+
+    /// Up to `max` PDUs and `max` EDUs off the front of the batch.
+    fn take(&mut self, max: usize) -> Self {
+        let pdus = self.pdus.drain(..self.pdus.len().min(max)).collect();
+        let edus = self.edus.drain(..self.edus.len().min(max)).collect();
+        Self { pdus, edus, origin: self.origin.clone() }
+    }
+}
+
+/// Capacity of the channel between the broadcaster and the batch sender.
+const BATCH_CHANNEL_CAPACITY: usize = 10_000;
+
+/// What the batch sender needs to send a transaction.
+#[derive(Clone)]
+struct BatchSendContext {
+    client: Arc<dyn FederationClientApi>,
+    retry_queue: Arc<RwLock<Vec<PendingTransaction>>>,
+    pool: Option<sqlx::PgPool>,
+    backoff: Arc<[u64]>,
+}
+
+/// Per-destination outbound queues of the batch sender.
+///
+/// Each destination has at most one transaction in flight, so a slow
+/// destination only holds up its own queue. A queue holds at most
+/// `queue_size` PDUs and `queue_size` EDUs: once a destination's PDUs
+/// overflow they are dropped and, with a database, the destination is put
+/// into catch-up mode, which replays what it missed from `destination_rooms`.
+/// Overflowing EDUs drop the oldest.
+struct DestinationQueues {
+    origin: String,
+    batch_max_size: usize,
+    queue_size: usize,
+    batches: HashMap<String, TransactionBatch>,
+    in_flight: JoinSet<()>,
+    sending: HashMap<tokio::task::Id, String>,
+}
+
+impl DestinationQueues {
+    fn new(origin: String, batch_max_size: usize, queue_size: usize) -> Self {
+        let batch_max_size = batch_max_size.max(1);
+        Self {
+            origin,
+            batch_max_size,
+            queue_size: queue_size.max(batch_max_size),
+            batches: HashMap::new(),
+            in_flight: JoinSet::new(),
+            sending: HashMap::new(),
+        }
+    }
+
+    fn is_sending(&self, destination: &str) -> bool {
+        self.sending.values().any(|sending| sending == destination)
+    }
+
+    /// Queue `item` for `destination`, sending a full batch right away.
+    async fn push(&mut self, ctx: Option<&BatchSendContext>, destination: String, item: OutgoingItem) {
+        let batch =
+            self.batches.entry(destination.clone()).or_insert_with(|| TransactionBatch::new(self.origin.clone()));
+        match item {
+            OutgoingItem::Pdu(pdu) => {
+                if batch.pdus.len() < self.queue_size {
+                    batch.pdus.push(pdu);
+                } else if let Some((pool, backoff)) = ctx.and_then(|ctx| Some((ctx.pool.as_ref()?, &ctx.backoff))) {
+                    // Every queued PDU is already in destination_rooms.
+                    ::tracing::warn!(
+                        "Outbound queue for {} overflowed ({} PDUs), falling back to catch-up",
+                        destination,
+                        batch.pdus.len()
+                    );
+                    batch.pdus.clear();
+                    record_send_failure(pool, backoff, &destination).await;
+                } else {
+                    ::tracing::warn!("Outbound queue for {} overflowed, dropping its oldest PDU", destination);
+                    batch.pdus.remove(0);
+                    batch.pdus.push(pdu);
+                }
+            }
+            OutgoingItem::Edu(edu) => {
+                if batch.edus.len() >= self.queue_size {
+                    ::tracing::warn!("Outbound queue for {} overflowed, dropping its oldest EDU", destination);
+                    batch.edus.remove(0);
+                }
+                batch.edus.push(edu);
+            }
+        }
+
+        if batch.len() >= self.batch_max_size {
+            self.flush(ctx, &destination);
+        }
+    }
+
+    /// Start sending the queued items of `destination` unless a transaction
+    /// to it is already in flight.
+    fn flush(&mut self, ctx: Option<&BatchSendContext>, destination: &str) {
+        let Some(ctx) = ctx else {
+            return;
+        };
+        if self.is_sending(destination) {
+            return;
+        }
+        let Some(batch) = self.batches.get_mut(destination) else {
+            return;
+        };
+        let next = batch.take(self.batch_max_size);
+        if batch.is_empty() {
+            self.batches.remove(destination);
+        }
+        if next.is_empty() {
+            return;
+        }
+
+        let ctx = ctx.clone();
+        let target = destination.to_string();
+        let handle = self.in_flight.spawn(async move { send_batch(&ctx, &target, next).await });
+        self.sending.insert(handle.id(), destination.to_string());
+    }
+
+    fn flush_all(&mut self, ctx: Option<&BatchSendContext>) {
+        let destinations: Vec<String> = self.batches.keys().cloned().collect();
+        for destination in &destinations {
+            self.flush(ctx, destination);
+        }
+    }
+
+    /// Wait for the next in-flight transaction and send what its destination
+    /// queued meanwhile if that already fills a batch.
+    async fn complete_next(&mut self, ctx: Option<&BatchSendContext>) {
+        let id = match self.in_flight.join_next_with_id().await {
+            Some(Ok((id, ()))) => id,
+            Some(Err(e)) => {
+                ::tracing::warn!("Federation batch send task failed: {}", e);
+                e.id()
+            }
+            None => return,
+        };
+        let Some(destination) = self.sending.remove(&id) else {
+            return;
+        };
+        if self.batches.get(&destination).is_some_and(|batch| batch.len() >= self.batch_max_size) {
+            self.flush(ctx, &destination);
+        }
+    }
+}
+
 #[derive(Clone)]
 pub struct EventBroadcaster {
     server_name: String,
@@ -94,18 +253,29 @@ impl EventBroadcaster {
         self.pool = Some(pool);
     }
 
-    pub async fn start_batch_sender(&self, origin: String, batch_max_size: usize, flush_interval_ms: u64) {
-        let (tx, mut rx) = mpsc::channel::<(String, OutgoingItem)>(10000);
+    /// Start the task that batches queued PDUs and EDUs into transactions.
+    /// Each destination queues at most `queue_size` PDUs and EDUs; see
+    /// [`DestinationQueues`] for what happens beyond that.
+    pub async fn start_batch_sender(
+        &self,
+        origin: String,
+        batch_max_size: usize,
+        flush_interval_ms: u64,
+        queue_size: usize,
+    ) {
+        let (tx, mut rx) = mpsc::channel::<(String, OutgoingItem)>(BATCH_CHANNEL_CAPACITY);
         *self.batch_tx.lock().await = Some(tx);
 
-        let client = self.federation_client.clone();
-        let retry_queue = self.pending_queue.clone();
-        let pool_opt = self.pool.clone();
-        let backoff = self.backoff_schedule.clone();
+        let ctx = self.federation_client.clone().map(|client| BatchSendContext {
+            client,
+            retry_queue: self.pending_queue.clone(),
+            pool: self.pool.clone(),
+            backoff: self.backoff_schedule.clone().into(),
+        });
         let server_name_clone = self.server_name.clone();
 
         tokio::spawn(async move {
-            let mut batches: HashMap<String, TransactionBatch> = HashMap::new();
+            let mut queues = DestinationQueues::new(origin, batch_max_size, queue_size);
             let mut interval = tokio::time::interval(tokio::time::Duration::from_millis(flush_interval_ms));
 
             loop {
@@ -119,75 +289,39 @@ impl EventBroadcaster {
                             continue;
                         }
 
-                        let batch = batches
-                            .entry(destination.clone())
-                            .or_insert_with(|| TransactionBatch {
-                                pdus: Vec::new(),
-                                edus: Vec::new(),
-                                origin: origin.clone(),
-                            });
-
-                        match item {
-                            OutgoingItem::Pdu(pdu) => batch.pdus.push(pdu),
-                            OutgoingItem::Edu(edu) => batch.edus.push(edu),
-                        }
-
-                        let total = batch.pdus.len() + batch.edus.len();
-                        if total >= batch_max_size {
-                            if let Some(client) = &client {
-                                send_batch(
-                                    client,
-                                    &retry_queue,
-                                    &pool_opt,
-                                    &backoff,
-                                    &batches,
-                                    &destination,
-                                ).await;
-                                batches.remove(&destination);
-                            }
-                        }
+                        queues.push(ctx.as_ref(), destination, item).await;
                     }
 
-                    _ = interval.tick() => {
-                        if !batches.is_empty() {
-                            let destinations: Vec<String> = batches.keys().cloned().collect();
-                            let c = match &client {
-                                Some(c) => c,
-                                None => continue,
-                            };
-                            for dest in &destinations {
-                                send_batch(
-                                    c,
-                                    &retry_queue,
-                                    &pool_opt,
-                                    &backoff,
-                                    &batches,
-                                    dest,
-                                ).await;
-                                batches.remove(dest);
-                            }
-                        }
-                    }
+                    _ = interval.tick() => queues.flush_all(ctx.as_ref()),
+
+                    () = queues.complete_next(ctx.as_ref()), if !queues.in_flight.is_empty() => {}
                 }
             }
         });
     }
 
     async fn push_pdu(&self, destination: &str, pdu: serde_json::Value) {
-        let guard = self.batch_tx.lock().await;
-        if let Some(tx) = guard.as_ref() {
-            if let Err(e) = tx.try_send((destination.to_string(), OutgoingItem::Pdu(pdu))) {
-                ::tracing::warn!("Failed to queue PDU for federation broadcast to {}: {}", destination, e);
+        let Some(tx) = self.batch_tx.lock().await.clone() else {
+            return;
+        };
+        if let Err(e) = tx.try_send((destination.to_string(), OutgoingItem::Pdu(pdu))) {
+            match &self.pool {
+                // The event is already in destination_rooms; catch-up resends it.
+                Some(pool) => {
+                    ::tracing::warn!("Federation send queue full, deferring {} to catch-up: {}", destination, e);
+                    record_send_failure(pool, &self.backoff_schedule, destination).await;
+                }
+                None => ::tracing::warn!("Failed to queue PDU for federation broadcast to {}: {}", destination, e),
             }
         }
     }
 
     async fn push_edu(&self, destination: &str, edu: serde_json::Value) {
-        let guard = self.batch_tx.lock().await;
-        if let Some(tx) = guard.as_ref() {
-            if let Err(e) = tx.try_send((destination.to_string(), OutgoingItem::Edu(edu))) {
-                ::tracing::warn!("Failed to queue EDU for federation broadcast to {}: {}", destination, e);
-            }
+        let Some(tx) = self.batch_tx.lock().await.clone() else {
+            return;
+        };
+        if let Err(e) = tx.try_send((destination.to_string(), OutgoingItem::Edu(edu))) {
+            ::tracing::warn!("Failed to queue EDU for federation broadcast to {}: {}", destination, e);
         }
     }
 
@@ -251,18 +385,28 @@ impl EventBroadcaster {
     ) -> Result<(), FederationBroadcastError> {
         let event_id = event.get("event_id").and_then(|v| v.as_str()).unwrap_or("unknown");
 
-        let destinations = self.get_eligible_destinations(room_id).await;
+        let mut destinations = self.get_eligible_destinations(room_id).await;
+        destinations.retain(|destination| destination != &self.server_name);
 
         if destinations.is_empty() {
             return Ok(());
         }
 
+        if self.pool.is_some() {
+            let mut live = Vec::with_capacity(destinations.len());
+            for destination in destinations {
+                if self.record_for_catch_up(&destination, room_id, event_id, event).await {
+                    ::tracing::debug!("Deferring event {} to catch-up for {}", event_id, destination);
+                } else {
+                    live.push(destination);
+                }
+            }
+            destinations = live;
+        }
+
         let has_batch = self.batch_tx.lock().await.is_some();
         if has_batch {
             for destination in &destinations {
-                if destination == &self.server_name {
-                    continue;
-                }
                 self.push_pdu(destination, event.clone()).await;
             }
             ::tracing::debug!("Pushed event {} to batch channel ({} destinations)", event_id, destinations.len());
@@ -277,10 +421,6 @@ impl EventBroadcaster {
         let txn_id = format!("txn_{}_{}", current_timestamp_millis(), uuid::Uuid::new_v4());
 
         for destination in &destinations {
-            if destination == &self.server_name {
-                continue;
-            }
-
             let transaction = FederationTransaction {
                 transaction_id: txn_id.clone(),
                 origin: origin.to_string(),
@@ -293,10 +433,17 @@ impl EventBroadcaster {
             match client.send_transaction(destination, &transaction).await {
                 Ok(_) => {
                     ::tracing::info!("Successfully sent event {} to {}", event_id, destination);
+                    if let Some(pool) = &self.pool {
+                        record_send_success(pool, destination, &transaction.pdus).await;
+                    }
                 }
                 Err(e) => {
                     ::tracing::warn!("Failed to send event {} to {}: {}", event_id, destination, e);
-                    self.enqueue_for_retry(destination.clone(), transaction, 0).await;
+                    match &self.pool {
+                        // The event is already in destination_rooms; catch-up resends it.
+                        Some(pool) => record_send_failure(pool, &self.backoff_schedule, destination).await,
+                        None => self.enqueue_for_retry(destination.clone(), transaction, 0).await,
+                    }
                 }
             }
         }
@@ -304,6 +451,118 @@ impl EventBroadcaster {
         Ok(())
     }
 
+    /// Remember `event` as the newest event `destination` is owed for
+    /// `room_id`. Returns `true` when the destination is in catch-up mode and
+    /// the event should not be sent live.
+    async fn record_for_catch_up(
+        &self,
+        destination: &str,
+        room_id: &str,
+        event_id: &str,
+        event: &serde_json::Value,
+    ) -> bool {
+        let Some(pool) = &self.pool else {
+            return false;
+        };
+
+        let now = current_timestamp_millis();
+        if let Err(e) = catch_up::record_destination_room(pool, destination, room_id, event_id, event, now).await {
+            ::tracing::warn!("Failed to record catch-up position for {} in {}: {}", destination, room_id, e);
+        }
+
+        match catch_up::get_retry_state(pool, destination).await {
+            Ok(state) => state.is_some_and(|state| state.is_catching_up()),
+            Err(e) => {
+                ::tracing::warn!("Failed to load retry state for {}: {}", destination, e);
+                false
+            }
+        }
+    }
+
+    /// Replay the newest event per room to every failing destination whose
+    /// backoff has expired. Returns the number of destinations that caught up.
+    pub async fn run_catch_up(&self) -> Result<usize, FederationBroadcastError> {
+        let Some(pool) = &self.pool else {
+            return Ok(0);
+        };
+        if self.federation_client.is_none() {
+            return Ok(0);
+        }
+
+        let now = current_timestamp_millis();
+        let destinations = catch_up::get_failing_destinations(pool, 100)
+            .await
+            .map_err(|e| FederationBroadcastError::SendFailed(e.to_string()))?;
+
+        let mut caught_up = 0;
+        for (destination, state) in destinations {
+            if !state.catch_up_due(now) {
+                continue;
+            }
+            match self.catch_up_destination(&destination, state).await {
+                Ok(true) => caught_up += 1,
+                Ok(false) => {}
+                Err(e) => ::tracing::warn!("Catch-up for {} failed: {}", destination, e),
+            }
+        }
+        Ok(caught_up)
+    }
+
+    /// Send `destination` the newest event of each room it missed since its
+    /// last successful transaction. Returns `true` once nothing is left to
+    /// replay and the destination is back to live sending.
+    async fn catch_up_destination(
+        &self,
+        destination: &str,
+        state: catch_up::DestinationRetryState,
+    ) -> Result<bool, FederationBroadcastError> {
+        let (Some(pool), Some(client)) = (&self.pool, &self.federation_client) else {
+            return Ok(false);
+        };
+
+        let mut position = state.last_successful_stream_ordering;
+        loop {
+            let rooms = catch_up::get_catch_up_rooms(pool, destination, position, catch_up::CATCH_UP_BATCH_SIZE)
+                .await
+                .map_err(|e| FederationBroadcastError::SendFailed(e.to_string()))?;
+            let rooms = catch_up::latest_per_room(rooms);
+
+            let Some(batch_position) = catch_up::max_stream_ordering(&rooms) else {
+                let now = current_timestamp_millis();
+                catch_up::mark_destination_success(pool, destination, None, now)
+                    .await
+                    .map_err(|e| FederationBroadcastError::SendFailed(e.to_string()))?;
+                if let Err(e) = catch_up::supersede_pending_pdus(pool, destination).await {
+                    ::tracing::warn!("Failed to drop superseded retries for {}: {}", destination, e);
+                }
+                ::tracing::info!("Destination {} caught up at stream position {}", destination, position);
+                return Ok(true);
+            };
+
+            let transaction = FederationTransaction {
+                transaction_id: format!("catchup_{}_{}", current_timestamp_millis(), uuid::Uuid::new_v4()),
+                origin: self.server_name.clone(),
+                origin_server_ts: current_timestamp_millis(),
+                destination: destination.to_string(),
+                pdus: rooms.into_iter().map(|room| room.pdu).collect(),
+                edus: vec![],
+            };
+
+            if let Err(e) = client.send_transaction(destination, &transaction).await {
+                ::tracing::warn!("Catch-up transaction to {} failed: {}", destination, e);
+                record_send_failure(pool, &self.backoff_schedule, destination).await;
+                return Ok(false);
+            }
+
+            // Stay in catch-up mode until the final (empty) page so live
+            // sends cannot overtake rooms that have not been replayed yet.
+            catch_up::advance_stream_position(pool, destination, batch_position, current_timestamp_millis())
+                .await
+                .map_err(|e| FederationBroadcastError::SendFailed(e.to_string()))?;
+            position = batch_position;
+        }
+    }
+
     pub async fn broadcast_edu(
         &self,
         destination: &str,
@@ -602,35 +861,24 @@ impl EventBroadcaster {
     }
 }
 
-async fn send_batch(
-    client: &Arc<dyn FederationClientApi>,
-    retry_queue: &Arc<RwLock<Vec<PendingTransaction>>>,
-    pool_opt: &Option<sqlx::PgPool>,
-    _backoff: &[u64],
-    batches: &HashMap<String, TransactionBatch>,
-    destination: &str,
-) {
-    let batch = match batches.get(destination) {
-        Some(b) => b,
-        None => return,
-    };
-
-    if batch.pdus.is_empty() && batch.edus.is_empty() {
-        return;
-    }
+async fn send_batch(ctx: &BatchSendContext, destination: &str, batch: TransactionBatch) {
+    let BatchSendContext { client, retry_queue, pool: pool_opt, backoff } = ctx;
 
     let txn = FederationTransaction {
         transaction_id: format!("batch_{}_{}", current_timestamp_millis(), uuid::Uuid::new_v4()),
-        origin: batch.origin.clone(),
+        origin: batch.origin,
         origin_server_ts: current_timestamp_millis(),
         destination: destination.to_string(),
-        pdus: batch.pdus.clone(),
-        edus: batch.edus.clone(),
+        pdus: batch.pdus,
+        edus: batch.edus,
     };
 
     match client.send_transaction(destination, &txn).await {
         Ok(_) => {
             ::tracing::debug!("Batch sent to {} ({} PDUs, {} EDUs)", destination, txn.pdus.len(), txn.edus.len());
+            if let Some(pool) = pool_opt {
+                record_send_success(pool, destination, &txn.pdus).await;
+            }
         }
         Err(e) => {
             ::tracing::warn!(
@@ -641,6 +889,18 @@ async fn send_batch(
                 txn.edus.len()
             );
 
+            let mut txn = txn;
+            if let Some(pool) = pool_opt {
+                // PDUs are already tracked in destination_rooms and will be
+                // replayed by catch-up; only the EDUs need a retry slot.
+                record_send_failure(pool, backoff, destination).await;
+                if txn.edus.is_empty() {
+                    return;
+                }
+                txn.pdus.clear();
+            }
+
+            let event_type = if txn.pdus.is_empty() { "m.edu" } else { "m.room.event" };
             let db_id = if let Some(pool) = pool_opt {
                 let content = match serde_json::to_value(&txn) {
                     Ok(v) => v,
@@ -661,12 +921,13 @@ async fn send_batch(
                 sqlx::query_as::<_, (i64,)>(
                     r"
                     INSERT INTO federation_queue (destination, event_id, event_type, room_id, content, created_ts, status)
-                    VALUES ($1, $2, 'm.room.event', NULL, $3, $4, 'pending')
+                    VALUES ($1, $2, $3, NULL, $4, $5, 'pending')
                     RETURNING id
                     ",
                 )
                 .bind(destination)
                 .bind(&event_id)
+                .bind(event_type)
                 .bind(&content)
                 .bind(current_timestamp_millis())
                 .fetch_one(pool)
@@ -692,6 +953,55 @@ async fn send_batch(
     }
 }
 
+/// Clear the failure state of `destination` after it accepted `pdus` and
+/// advance its caught-up position. A destination still in catch-up mode is
+/// left alone so the position cannot skip rooms that were never replayed.
+async fn record_send_success(pool: &sqlx::PgPool, destination: &str, pdus: &[serde_json::Value]) {
+    match catch_up::get_retry_state(pool, destination).await {
+        Ok(Some(state)) if state.is_catching_up() => return,
+        Ok(_) => {}
+        Err(e) => {
+            ::tracing::warn!("Failed to load retry state for {}: {}", destination, e);
+            return;
+        }
+    }
+
+    let event_ids: Vec<String> =
+        pdus.iter().filter_map(|pdu| pdu.get("event_id").and_then(|v| v.as_str()).map(String::from)).collect();
+    let position = match catch_up::get_max_stream_ordering_for_events(pool, &event_ids).await {
+        Ok(position) => position,
+        Err(e) => {
+            ::tracing::warn!("Failed to resolve stream position for {}: {}", destination, e);
+            None
+        }
+    };
+    if let Err(e) = catch_up::mark_destination_success(pool, destination, position, current_timestamp_millis()).await {
+        ::tracing::warn!("Failed to record successful send to {}: {}", destination, e);
+    }
+}
+
+/// Put `destination` into catch-up mode and schedule the next attempt from
+/// the backoff schedule.
+async fn record_send_failure(pool: &sqlx::PgPool, backoff: &[u64], destination: &str) {
+    let failures = match catch_up::get_retry_state(pool, destination).await {
+        Ok(state) => state.map_or(0, |state| state.failure_count.max(0) as usize),
+        Err(e) => {
+            ::tracing::warn!("Failed to load retry state for {}: {}", destination, e);
+            0
+        }
+    };
+    let retry_interval = backoff.get(failures.min(backoff.len().saturating_sub(1))).copied().unwrap_or(0) as i64;
+    match catch_up::mark_destination_failure(pool, destination, retry_interval, current_timestamp_millis()).await {
+        Ok(count) => ::tracing::info!(
+            "Destination {} entered catch-up (failure {}), next attempt in {}ms",
+            destination,
+            count,
+            retry_interval
+        ),
+        Err(e) => ::tracing::warn!("Failed to record failed send to {}: {}", destination, e),
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum FederationBroadcastError {
     #[error("Failed to send event: {0}")]
@@ -765,6 +1075,51 @@ mod tests {
         assert_eq!(broadcaster.get_backoff_delay(100), 900_000); // clamped
     }
 
+    #[tokio::test]
+    async fn destination_queue_drops_oldest_pdu_without_database() {
+        let mut queues = DestinationQueues::new("origin.test".into(), 3, 3);
+        for i in 0..5 {
+            queues.push(None, "remote.test".into(), OutgoingItem::Pdu(serde_json::json!({ "n": i }))).await;
+        }
+
+        let batch = &queues.batches["remote.test"];
+        assert_eq!(
+            batch.pdus,
+            vec![serde_json::json!({ "n": 2 }), serde_json::json!({ "n": 3 }), serde_json::json!({ "n": 4 })]
+        );
+    }
+
+    #[tokio::test]
+    async fn destination_queue_bounds_edus_per_destination() {
+        let mut queues = DestinationQueues::new("origin.test".into(), 2, 2);
+        for i in 0..4 {
+            queues.push(None, "a.test".into(), OutgoingItem::Edu(serde_json::json!({ "n": i }))).await;
+        }
+        queues.push(None, "b.test".into(), OutgoingItem::Edu(serde_json::json!({ "n": 0 }))).await;
+
+        assert_eq!(queues.batches["a.test"].edus, vec![serde_json::json!({ "n": 2 }), serde_json::json!({ "n": 3 })]);
+        assert_eq!(queues.batches["b.test"].edus.len(), 1);
+    }
+
+    #[test]
+    fn destination_queue_size_is_at_least_one_batch() {
+        let queues = DestinationQueues::new("origin.test".into(), 50, 0);
+        assert_eq!(queues.queue_size, 50);
+    }
+
+    #[test]
+    fn transaction_batch_take_splits_oversized_batches() {
+        let mut batch = TransactionBatch::new("origin.test".into());
+        batch.pdus = (0..5).map(|i| serde_json::json!(i)).collect();
+        batch.edus = vec![serde_json::json!("edu")];
+
+        let first = batch.take(3);
+        assert_eq!(first.pdus.len(), 3);
+        assert_eq!(first.edus.len(), 1);
+        assert_eq!(batch.pdus, vec![serde_json::json!(3), serde_json::json!(4)]);
+        assert!(batch.edus.is_empty());
+    }
+
     #[test]
     fn get_backoff_delay_middle_elements() {
         let broadcaster = EventBroadcaster::new("test.local".into());
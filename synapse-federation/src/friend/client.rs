@@ -2,17 +2,19 @@ use crate::signing::canonical_federation_request_bytes;
 use crate::KeyRotationManager;
 use base64::{engine::general_purpose::STANDARD_NO_PAD, Engine};
 use ed25519_dalek::{Signer, SigningKey};
+use parking_lot::RwLock;
 use reqwest::{Client, StatusCode};
 use serde_json::Value;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use synapse_cache::KeyRotationEvent;
 use synapse_common::{ApiError, ApiResult};
 
 pub struct FriendFederationClient {
     client: Client,
     server_name: String,
     signing_key_id: String,
-    signing_key: Option<SigningKey>,
+    signing_key: RwLock<Option<SigningKey>>,
     key_rotation_manager: Option<Arc<KeyRotationManager>>,
     missing_signing_key_logged: AtomicBool,
 }
@@ -25,10 +27,10 @@ impl FriendFederationClient {
             std::env::var("FEDERATION_SIGNING_KEY").ok().and_then(|key_b64| Self::decode_signing_key(&key_b64));
 
         Self {
-            client: Client::new(),
+            client: synapse_common::outbound_http::client(),
             server_name,
             signing_key_id,
-            signing_key,
+            signing_key: RwLock::new(signing_key),
             key_rotation_manager,
             missing_signing_key_logged: AtomicBool::new(false),
         }
@@ -56,7 +58,7 @@ impl FriendFederationClient {
         content: Option<&Value>,
     ) -> Result<String, ApiError> {
         let message = canonical_federation_request_bytes(method, path, &self.server_name, destination, content)
-            .map_err(|e| ApiError::internal(format!("Canonical JSON error: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Canonical JSON error", &e))?;
 
         let signature = signing_key.sign(&message);
         let sig_b64 = STANDARD_NO_PAD.encode(signature.to_bytes());
@@ -74,7 +76,7 @@ impl FriendFederationClient {
         destination: &str,
         content: Option<&Value>,
     ) -> Result<String, ApiError> {
-        if let Some(signing_key) = self.signing_key.as_ref() {
+        if let Some(signing_key) = self.signing_key.read().as_ref() {
             return self.build_auth_header(&self.signing_key_id, signing_key, method, path, destination, content);
         }
 
@@ -106,6 +108,23 @@ impl FriendFederationClient {
         Err(ApiError::internal("Federation signing key not configured".to_string()))
     }
 
+    /// Called when our own signing key rotates. A key pinned from
+    /// `FEDERATION_SIGNING_KEY` is outside the rotation, so it is dropped and
+    /// later requests are signed with the rotation manager's current key.
+    /// Without a rotation manager the pinned key is all there is and stays.
+    pub fn on_local_key_rotated(&self, event: &KeyRotationEvent) {
+        if self.key_rotation_manager.is_none() {
+            return;
+        }
+        if self.signing_key.write().take().is_some() {
+            tracing::info!(
+                pinned_key_id = %self.signing_key_id,
+                new_key_id = %event.new_key_id,
+                "Friend federation client now signs with the rotated federation key"
+            );
+        }
+    }
+
     pub async fn send_invite(&self, destination: &str, _room_id: &str, content: &Value) -> ApiResult<()> {
         let path = format!("/_matrix/federation/v1/send/{}", uuid::Uuid::new_v4());
         let url = format!("https://{destination}{path}");
@@ -302,7 +321,7 @@ mod tests {
         let _k = EnvVarGuard::set("FEDERATION_SIGNING_KEY", &valid_b64_signing_key());
         let _id = EnvVarGuard::set("FEDERATION_SIGNING_KEY_ID", "ed25519:test123");
         let client = FriendFederationClient::new("example.com".to_string(), None);
-        assert!(client.signing_key.is_some(), "signing_key should be populated from env");
+        assert!(client.signing_key.read().is_some(), "signing_key should be populated from env");
         assert_eq!(client.signing_key_id, "ed25519:test123");
     }
 
@@ -311,7 +330,7 @@ mod tests {
         let _guard = env_lock().lock().unwrap();
         let _k = EnvVarGuard::set("FEDERATION_SIGNING_KEY", "!!!not-base64!!!");
         let client = FriendFederationClient::new("example.com".to_string(), None);
-        assert!(client.signing_key.is_none(), "invalid base64 should leave signing_key None");
+        assert!(client.signing_key.read().is_none(), "invalid base64 should leave signing_key None");
     }
 
     #[test]
@@ -321,7 +340,7 @@ mod tests {
         let short = STANDARD_NO_PAD.encode([0u8; 16]);
         let _k = EnvVarGuard::set("FEDERATION_SIGNING_KEY", &short);
         let client = FriendFederationClient::new("example.com".to_string(), None);
-        assert!(client.signing_key.is_none(), "wrong-length key should leave signing_key None");
+        assert!(client.signing_key.read().is_none(), "wrong-length key should leave signing_key None");
     }
 
     #[test]
@@ -444,4 +463,40 @@ mod tests {
             "missing_signing_key_logged flag must be set after first call"
         );
     }
+
+    fn rotation_event() -> KeyRotationEvent {
+        KeyRotationEvent {
+            origin: "example.com".to_string(),
+            old_key_id: "ed25519:old".to_string(),
+            new_key_id: "ed25519:new".to_string(),
+            timestamp: std::time::Instant::now(),
+        }
+    }
+
+    #[tokio::test]
+    async fn local_key_rotation_drops_pinned_key_in_favour_of_rotation_manager() {
+        let _guard = env_lock().lock().unwrap();
+        let _k = EnvVarGuard::set("FEDERATION_SIGNING_KEY", &valid_b64_signing_key());
+        let manager = make_key_rotation_manager("example.com");
+        let client = FriendFederationClient::new("example.com".to_string(), Some(manager));
+        assert!(client.signing_key.read().is_some());
+
+        client.on_local_key_rotated(&rotation_event());
+
+        assert!(client.signing_key.read().is_none(), "pinned env key must not outlive a rotation");
+        // Signing now goes through the (empty) rotation manager.
+        let err = client.query_remote_friends("remote.example.com", "@alice:example.com").await.unwrap_err();
+        assert!(err.to_string().contains("not configured"), "got: {err}");
+    }
+
+    #[test]
+    fn local_key_rotation_keeps_pinned_key_without_rotation_manager() {
+        let _guard = env_lock().lock().unwrap();
+        let _k = EnvVarGuard::set("FEDERATION_SIGNING_KEY", &valid_b64_signing_key());
+        let client = FriendFederationClient::new("example.com".to_string(), None);
+
+        client.on_local_key_rotated(&rotation_event());
+
+        assert!(client.signing_key.read().is_some(), "the pinned key is the only signer left");
+    }
 }
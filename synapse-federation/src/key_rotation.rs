@@ -155,7 +155,7 @@ impl KeyRotationManager {
     ) -> Result<String, ApiError> {
         match master_key {
             Some(mk) => encrypt_key(secret_key, mk)
-                .map_err(|e| ApiError::internal(format!("Failed to encrypt signing key: {e}"))),
+                .map_err(|e| ApiError::internal_with_log("Failed to encrypt signing key", &e)),
             None if allow_plaintext => {
                 tracing::warn!(
                     "Storing federation signing key in plaintext (explicitly allowed) - configure signing_key_master_key for encryption at rest"
@@ -399,7 +399,7 @@ impl KeyRotationManager {
                     match &self.master_key {
                         Some(mk) => {
                             key.secret_key = decrypt_key(&key.secret_key, mk)
-                                .map_err(|e| ApiError::internal(format!("Failed to decrypt signing key: {e}")))?;
+                                .map_err(|e| ApiError::internal_with_log("Failed to decrypt signing key", &e))?;
                         }
                         None => {
                             return Err(ApiError::internal("Signing key is encrypted but no master key is configured"));
@@ -414,6 +414,7 @@ impl KeyRotationManager {
                 }
                 *self.current_key.write().await = Some(key.clone());
                 tracing::info!("Loaded existing signing key from database");
+                self.load_old_verify_keys(&key.key_id).await?;
                 if let Err(e) = self.export_signing_key_to_file(&key).await {
                     tracing::warn!("Failed to export signing key to file: {}", e);
                 }
@@ -532,21 +533,21 @@ impl KeyRotationManager {
     }
 
     pub async fn rotate_keys(&self, requested_key_id: Option<String>) -> Result<(), ApiError> {
-        let old_key_id = {
-            let current = self.current_key.read().await;
-            current.as_ref().map(|key| key.key_id.clone())
-        };
-
-        let current = self.current_key.read().await;
-        if let Some(key) = current.as_ref() {
-            self.historical_keys.write().await.insert(key.key_id.clone(), key.clone());
-        }
-        drop(current);
+        let previous = self.current_key.read().await.clone();
+        let old_key_id = previous.as_ref().map(|key| key.key_id.clone());
 
         let key_id = requested_key_id.unwrap_or_else(new_key_id);
+        if old_key_id.as_deref() == Some(key_id.as_str()) || self.historical_keys.read().await.contains_key(&key_id) {
+            return Err(ApiError::bad_request(format!("Signing key ID {key_id} has already been used")));
+        }
         let (key_id, secret_key) = self.generate_new_key_pair(&key_id);
 
+        // The new key is in place before the old one is retired, so a failed
+        // rotation never leaves the server without a usable key.
         self.initialize(&secret_key, &key_id).await?;
+        if let Some(previous) = previous {
+            self.retire_key(previous).await?;
+        }
 
         if let Err(e) = self.broadcast_key_change_to_federation().await {
             tracing::warn!("Failed to broadcast key change: {}", e);
@@ -568,6 +569,73 @@ impl KeyRotationManager {
         Ok(())
     }
 
+    /// Move a key that is being rotated out into `old_verify_keys`. Its
+    /// `expires_at` becomes the rotation time, which is advertised as
+    /// `expired_ts`; the secret is no longer needed once retired.
+    async fn retire_key(&self, mut key: SigningKey) -> Result<(), ApiError> {
+        let expired_ts = current_timestamp_millis();
+
+        sqlx::query(
+            r"
+            UPDATE federation_signing_keys
+            SET expires_at = $1,
+                ts_valid_until_ms = $1,
+                key_json = COALESCE(key_json, '{}'::jsonb) || jsonb_build_object('expired_ts', $1::bigint)
+            WHERE server_name = $2 AND key_id = $3
+            ",
+        )
+        .bind(expired_ts)
+        .bind(&self.server_name)
+        .bind(&key.key_id)
+        .execute(&*self.pool)
+        .await?;
+
+        key.secret_key.clear();
+        key.expires_at = expired_ts;
+        key.ts_valid_until_ms = expired_ts;
+        self.historical_keys.write().await.insert(key.key_id.clone(), key);
+        Ok(())
+    }
+
+    /// Load every previously used, non-revoked key of this server so it keeps
+    /// being advertised in `old_verify_keys` and accepted for events it signed
+    /// across restarts.
+    async fn load_old_verify_keys(&self, current_key_id: &str) -> Result<(), ApiError> {
+        let old_keys = sqlx::query_as::<_, SigningKey>(
+            r"
+            SELECT server_name, key_id, '' AS secret_key, public_key, created_ts, expires_at,
+                   key_json, ts_added_ms, ts_valid_until_ms
+            FROM federation_signing_keys
+            WHERE server_name = $1
+              AND key_id <> $2
+              AND NOT COALESCE((key_json->>'revoked')::boolean, false)
+            ",
+        )
+        .bind(&self.server_name)
+        .bind(current_key_id)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        let mut historical = self.historical_keys.write().await;
+        for key in old_keys {
+            historical.insert(key.key_id.clone(), key);
+        }
+        Ok(())
+    }
+
+    /// Public key for `key_id` if it is our current key or one of our old
+    /// (rotated, not revoked) keys. Old keys stay valid for signatures made
+    /// while they were current, which is what lets previously signed local
+    /// events keep verifying after a rotation.
+    pub async fn get_verify_key(&self, key_id: &str) -> Option<String> {
+        if let Some(current) = self.current_key.read().await.as_ref() {
+            if current.key_id == key_id {
+                return Some(current.public_key.clone());
+            }
+        }
+        self.historical_keys.read().await.get(key_id).map(|key| key.public_key.clone())
+    }
+
     fn generate_new_key_pair(&self, key_id: &str) -> (String, String) {
         let secret_key = base64::engine::general_purpose::STANDARD_NO_PAD.encode(rand::random::<[u8; 32]>());
 
@@ -727,7 +795,7 @@ impl KeyRotationManager {
         });
 
         sign_json(&self.server_name, &key_id_for_sign, &secret_key, &mut response)
-            .map_err(|e| ApiError::internal(format!("Failed to sign server keys: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign server keys", &e))?;
 
         Ok(response)
     }
@@ -955,4 +1023,41 @@ mod tests {
         assert_eq!(key.key_id, cloned.key_id);
         assert_eq!(key.secret_key, cloned.secret_key);
     }
+
+    #[tokio::test]
+    async fn rotated_out_keys_stay_verifiable_and_advertised() {
+        let pool = Arc::new(sqlx::PgPool::connect_lazy("postgres://localhost/test").unwrap());
+        let manager = KeyRotationManager::new(&pool, "test.example.com");
+        let (current_secret, current_public) = generate_test_signing_key(0x04);
+        let (_, old_public) = generate_test_signing_key(0x05);
+        let key = |key_id: &str, secret_key: String, public_key: String, expires_at: i64| SigningKey {
+            server_name: "test.example.com".to_string(),
+            key_id: key_id.to_string(),
+            secret_key,
+            public_key,
+            created_ts: 1000,
+            expires_at,
+            key_json: serde_json::json!({}),
+            ts_added_ms: 1000,
+            ts_valid_until_ms: expires_at,
+        };
+        *manager.current_key.write().await = Some(key("ed25519:b", current_secret, current_public.clone(), 9000));
+        manager
+            .historical_keys
+            .write()
+            .await
+            .insert("ed25519:a".to_string(), key("ed25519:a", String::new(), old_public.clone(), 5000));
+
+        assert_eq!(manager.get_verify_key("ed25519:b").await, Some(current_public));
+        assert_eq!(manager.get_verify_key("ed25519:a").await, Some(old_public.clone()));
+        assert_eq!(manager.get_verify_key("ed25519:unknown").await, None);
+
+        let response = manager.get_server_keys_response().await.unwrap();
+        assert_eq!(response["old_verify_keys"]["ed25519:a"], json!({ "key": old_public, "expired_ts": 5000 }));
+        assert!(response["verify_keys"].get("ed25519:a").is_none());
+        assert!(response["signatures"]["test.example.com"]["ed25519:b"].is_string());
+
+        let err = manager.rotate_keys(Some("ed25519:a".to_string())).await.unwrap_err();
+        assert!(err.to_string().contains("already been used"), "err was: {err}");
+    }
 }
@@ -4,6 +4,7 @@
 
 pub mod client;
 pub mod client_api;
+pub mod destination_catch_up;
 pub mod device_sync;
 pub mod edu;
 pub mod event_auth;
@@ -12,8 +13,10 @@ pub mod event_broadcaster;
 pub mod friend;
 pub mod key_rotation;
 pub mod memory_tracker;
+pub mod resolver;
 pub mod server_acl;
 pub mod signing;
+pub mod state_res;
 pub mod state_resolution;
 #[cfg(any(test, feature = "test-utils"))]
 pub mod test_mocks;
@@ -0,0 +1,150 @@
+//! Dual-stack name resolution for outbound federation.
+//!
+//! Many homeservers are reachable over IPv6 only, and many more have an
+//! AAAA record that does not actually route. The HTTP connector already races
+//! the two address families (it starts the fallback family 300ms after the
+//! first one), but it takes whichever family comes first in the resolved list
+//! as the preferred one. This resolver interleaves both families, IPv6 first
+//! as RFC 8305 recommends, and demotes a family for
+//! [`FAMILY_FAILURE_TTL`] once it has lost a race to a given host, so
+//! repeat requests skip the head-start delay.
+
+use reqwest::dns::{Addrs, Name, Resolve, Resolving};
+use std::net::SocketAddr;
+use std::time::Duration;
+
+pub const FAMILY_FAILURE_TTL: Duration = Duration::from_secs(10 * 60);
+const MAX_TRACKED_HOSTS: u64 = 10_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IpFamily {
+    V4,
+    V6,
+}
+
+impl IpFamily {
+    fn of(addr: &SocketAddr) -> Self {
+        if addr.is_ipv6() {
+            Self::V6
+        } else {
+            Self::V4
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            Self::V4 => Self::V6,
+            Self::V6 => Self::V4,
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct DualStackResolver {
+    /// Family that last lost a connection race, per host.
+    failed_family: moka::sync::Cache<String, IpFamily>,
+}
+
+impl Default for DualStackResolver {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DualStackResolver {
+    pub fn new() -> Self {
+        Self {
+            failed_family: moka::sync::Cache::builder()
+                .max_capacity(MAX_TRACKED_HOSTS)
+                .time_to_live(FAMILY_FAILURE_TTL)
+                .build(),
+        }
+    }
+
+    /// Records which address a request to `host` ended up connected to.
+    /// The other family is treated as failing for that host.
+    pub fn record_connected(&self, host: &str, addr: SocketAddr) {
+        let host = host.to_ascii_lowercase();
+        let failed = IpFamily::of(&addr).other();
+        if self.failed_family.get(&host) != Some(failed) {
+            self.failed_family.insert(host, failed);
+        }
+    }
+
+    pub fn failed_family(&self, host: &str) -> Option<IpFamily> {
+        self.failed_family.get(&host.to_ascii_lowercase())
+    }
+}
+
+impl Resolve for DualStackResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        let host = name.as_str().to_string();
+        let demoted = self.failed_family(&host);
+        Box::pin(async move {
+            let resolved: Vec<SocketAddr> = tokio::net::lookup_host((host.as_str(), 0)).await?.collect();
+            let addrs: Addrs = Box::new(order_addresses(resolved, demoted).into_iter());
+            Ok(addrs)
+        })
+    }
+}
+
+/// Interleaves the two families, starting with IPv6 unless it is `demoted`.
+/// Relative order within a family is kept.
+fn order_addresses(addrs: Vec<SocketAddr>, demoted: Option<IpFamily>) -> Vec<SocketAddr> {
+    let first_family = if demoted == Some(IpFamily::V6) { IpFamily::V4 } else { IpFamily::V6 };
+    let (first, second): (Vec<_>, Vec<_>) = addrs.into_iter().partition(|addr| IpFamily::of(addr) == first_family);
+
+    let mut ordered = Vec::with_capacity(first.len() + second.len());
+    let mut first = first.into_iter();
+    let mut second = second.into_iter();
+    loop {
+        match (first.next(), second.next()) {
+            (None, None) => break,
+            (a, b) => ordered.extend(a.into_iter().chain(b)),
+        }
+    }
+    ordered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> SocketAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn ipv6_first_and_interleaved() {
+        let ordered = order_addresses(
+            vec![addr("192.0.2.1:0"), addr("192.0.2.2:0"), addr("[2001:db8::1]:0"), addr("[2001:db8::2]:0")],
+            None,
+        );
+        assert_eq!(
+            ordered,
+            vec![addr("[2001:db8::1]:0"), addr("192.0.2.1:0"), addr("[2001:db8::2]:0"), addr("192.0.2.2:0")]
+        );
+    }
+
+    #[test]
+    fn demoted_family_goes_last_but_is_kept() {
+        let ordered = order_addresses(vec![addr("[2001:db8::1]:0"), addr("192.0.2.1:0")], Some(IpFamily::V6));
+        assert_eq!(ordered, vec![addr("192.0.2.1:0"), addr("[2001:db8::1]:0")]);
+
+        let v6_only = order_addresses(vec![addr("[2001:db8::1]:0")], Some(IpFamily::V6));
+        assert_eq!(v6_only, vec![addr("[2001:db8::1]:0")]);
+    }
+
+    #[test]
+    fn losing_family_is_remembered_per_host() {
+        let resolver = DualStackResolver::new();
+        assert_eq!(resolver.failed_family("example.org"), None);
+
+        resolver.record_connected("Example.org", addr("192.0.2.1:8448"));
+        assert_eq!(resolver.failed_family("example.org"), Some(IpFamily::V6));
+        assert_eq!(resolver.failed_family("other.org"), None);
+
+        resolver.record_connected("example.org", addr("[2001:db8::1]:8448"));
+        assert_eq!(resolver.failed_family("example.org"), Some(IpFamily::V4));
+    }
+}
@@ -0,0 +1,374 @@
+//! Event authorization rules used by state resolution's iterative auth
+//! checks.  Only the state-dependent rules are applied: signatures, hashes
+//! and the shape of `auth_events` are checked when a PDU is received.
+
+use super::{StateKey, StateResEvent};
+use serde_json::Value;
+use std::collections::HashMap;
+
+/// The events an event is authorized against, keyed by `(type, state_key)`.
+pub type AuthState<'a> = HashMap<StateKey, &'a StateResEvent>;
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("{0}")]
+pub struct AuthError(pub String);
+
+fn reject<T>(reason: impl Into<String>) -> Result<T, AuthError> {
+    Err(AuthError(reason.into()))
+}
+
+/// Numeric room version whose rules apply; unknown identifiers use the
+/// newest rules.
+pub(crate) fn rules_version(room_version: &str) -> u32 {
+    room_version.parse().unwrap_or(u32::MAX)
+}
+
+/// Power level values are integers, or numeric strings before room v10.
+fn as_power(value: &Value) -> Option<i64> {
+    value.as_i64().or_else(|| value.as_str().and_then(|s| s.trim().parse().ok()))
+}
+
+fn server_of(user_id: &str) -> &str {
+    user_id.split_once(':').map_or("", |(_, server)| server)
+}
+
+/// The `(type, state_key)` entries of the room state that `event` is
+/// authorized against.
+pub fn auth_types_for_event(room_version: &str, event: &StateResEvent) -> Vec<StateKey> {
+    if event.event_type == "m.room.create" {
+        return Vec::new();
+    }
+    let mut types = vec![
+        ("m.room.create".to_string(), String::new()),
+        ("m.room.power_levels".to_string(), String::new()),
+        ("m.room.member".to_string(), event.sender.clone()),
+    ];
+    if event.event_type == "m.room.member" {
+        if let Some(target) = &event.state_key {
+            types.push(("m.room.member".to_string(), target.clone()));
+        }
+        let membership = event.membership().unwrap_or_default();
+        if matches!(membership, "join" | "invite" | "knock") {
+            types.push(("m.room.join_rules".to_string(), String::new()));
+        }
+        if membership == "invite" {
+            if let Some(token) = event.content["third_party_invite"]["signed"]["token"].as_str() {
+                types.push(("m.room.third_party_invite".to_string(), token.to_string()));
+            }
+        }
+        if membership == "join" && rules_version(room_version) >= 8 {
+            if let Some(authoriser) = event.content["join_authorised_via_users_server"].as_str() {
+                types.push(("m.room.member".to_string(), authoriser.to_string()));
+            }
+        }
+    }
+    types
+}
+
+/// Power levels in effect for an auth state.
+struct PowerLevels<'a> {
+    content: Option<&'a Value>,
+    creator: Option<&'a str>,
+}
+
+impl<'a> PowerLevels<'a> {
+    fn new(room_version: &str, auth_state: &AuthState<'a>) -> Self {
+        let content = auth_state.get(&("m.room.power_levels".to_string(), String::new())).map(|e| &e.content);
+        let creator = auth_state.get(&("m.room.create".to_string(), String::new())).and_then(|create| {
+            if rules_version(room_version) >= 11 {
+                Some(create.sender.as_str())
+            } else {
+                create.content["creator"].as_str()
+            }
+        });
+        Self { content, creator }
+    }
+
+    fn user(&self, user_id: &str) -> i64 {
+        match self.content {
+            Some(content) => {
+                as_power(&content["users"][user_id]).or_else(|| as_power(&content["users_default"])).unwrap_or(0)
+            }
+            None if self.creator == Some(user_id) => 100,
+            None => 0,
+        }
+    }
+
+    /// A top-level level such as `ban` or `state_default`.
+    fn named(&self, key: &str) -> i64 {
+        let default = match key {
+            "ban" | "kick" | "redact" | "state_default" => 50,
+            _ => 0,
+        };
+        match self.content {
+            Some(content) => as_power(&content[key]).unwrap_or(default),
+            // Without a power levels event, state events need no power.
+            None if key == "state_default" => 0,
+            None => default,
+        }
+    }
+
+    fn for_event(&self, event: &StateResEvent) -> i64 {
+        let default =
+            if event.state_key.is_some() { self.named("state_default") } else { self.named("events_default") };
+        self.content.and_then(|c| as_power(&c["events"][&event.event_type])).unwrap_or(default)
+    }
+}
+
+fn membership_of<'a>(auth_state: &AuthState<'a>, user_id: &str) -> &'a str {
+    auth_state
+        .get(&("m.room.member".to_string(), user_id.to_string()))
+        .and_then(|member| member.membership())
+        .unwrap_or("leave")
+}
+
+/// Checks `event` against the room state in `auth_state` under the auth
+/// rules of `room_version`.
+pub fn check_auth(room_version: &str, event: &StateResEvent, auth_state: &AuthState<'_>) -> Result<(), AuthError> {
+    let version = rules_version(room_version);
+
+    if event.event_type == "m.room.create" {
+        if !event.auth_events.is_empty() {
+            return reject("m.room.create must not have auth events");
+        }
+        if version <= 10 && event.content["creator"].as_str().is_none() {
+            return reject("m.room.create has no creator");
+        }
+        return Ok(());
+    }
+
+    let Some(create) = auth_state.get(&("m.room.create".to_string(), String::new())) else {
+        return reject("No m.room.create event in auth state");
+    };
+    if event.room_id != create.room_id {
+        return reject("Auth state belongs to another room");
+    }
+    if create.content["m.federate"] == Value::Bool(false) && server_of(&event.sender) != server_of(&create.sender) {
+        return reject("Room is not federated");
+    }
+
+    if event.event_type == "m.room.aliases" && version <= 5 {
+        return if event.state_key.as_deref() == Some(server_of(&event.sender)) {
+            Ok(())
+        } else {
+            reject("m.room.aliases state key must be the sender's server")
+        };
+    }
+
+    let power = PowerLevels::new(room_version, auth_state);
+
+    if event.event_type == "m.room.member" {
+        return check_member(version, event, auth_state, &power);
+    }
+
+    if membership_of(auth_state, &event.sender) != "join" {
+        return reject("Sender is not joined to the room");
+    }
+    let sender_power = power.user(&event.sender);
+
+    if event.event_type == "m.room.third_party_invite" {
+        return if sender_power >= power.named("invite") { Ok(()) } else { reject("Sender cannot invite") };
+    }
+    if power.for_event(event) > sender_power {
+        return reject(format!("Sender lacks power to send {}", event.event_type));
+    }
+    if let Some(state_key) = &event.state_key {
+        if state_key.starts_with('@') && state_key != &event.sender {
+            return reject("State key belongs to another user");
+        }
+    }
+    if event.event_type == "m.room.power_levels" && event.state_key.as_deref() == Some("") {
+        return check_power_levels(version, event, &power, sender_power);
+    }
+    Ok(())
+}
+
+fn check_member(
+    version: u32,
+    event: &StateResEvent,
+    auth_state: &AuthState<'_>,
+    power: &PowerLevels<'_>,
+) -> Result<(), AuthError> {
+    let Some(target) = event.state_key.as_deref() else {
+        return reject("m.room.member has no state key");
+    };
+    let Some(membership) = event.membership() else {
+        return reject("m.room.member has no membership");
+    };
+    let sender_membership = membership_of(auth_state, &event.sender);
+    let target_membership = membership_of(auth_state, target);
+    let join_rule = auth_state
+        .get(&("m.room.join_rules".to_string(), String::new()))
+        .and_then(|e| e.content["join_rule"].as_str())
+        .unwrap_or("invite");
+    let sender_power = power.user(&event.sender);
+    let target_power = power.user(target);
+
+    match membership {
+        "join" => {
+            let create_key = ("m.room.create".to_string(), String::new());
+            let is_creator_join = power.creator == Some(target)
+                && event.auth_events.len() == 1
+                && auth_state.get(&create_key).is_some_and(|c| event.auth_events.contains(&c.event_id));
+            if is_creator_join {
+                return Ok(());
+            }
+            if event.sender != target {
+                return reject("Cannot join on behalf of another user");
+            }
+            if target_membership == "ban" {
+                return reject("User is banned from the room");
+            }
+            match join_rule {
+                "public" => Ok(()),
+                "invite" | "knock" if target_membership == "join" || target_membership == "invite" => Ok(()),
+                "restricted" | "knock_restricted" if version >= 8 => {
+                    if target_membership == "join" || target_membership == "invite" {
+                        return Ok(());
+                    }
+                    let Some(authoriser) = event.content["join_authorised_via_users_server"].as_str() else {
+                        return reject("Restricted join was not authorised by a resident server");
+                    };
+                    if membership_of(auth_state, authoriser) == "join"
+                        && power.user(authoriser) >= power.named("invite")
+                    {
+                        Ok(())
+                    } else {
+                        reject("Restricted join authoriser cannot invite")
+                    }
+                }
+                _ => reject("Room is not joinable"),
+            }
+        }
+        "invite" => {
+            if !event.content["third_party_invite"].is_null() {
+                if target_membership == "ban" {
+                    return reject("Invitee is banned");
+                }
+                let token = event.content["third_party_invite"]["signed"]["token"].as_str().unwrap_or_default();
+                let invite_key = ("m.room.third_party_invite".to_string(), token.to_string());
+                return match auth_state.get(&invite_key) {
+                    Some(invite) if invite.sender == event.sender => Ok(()),
+                    _ => reject("No matching m.room.third_party_invite"),
+                };
+            }
+            if sender_membership != "join" {
+                return reject("Inviter is not joined to the room");
+            }
+            if target_membership == "join" || target_membership == "ban" {
+                return reject("Invitee is already joined or banned");
+            }
+            if sender_power >= power.named("invite") {
+                Ok(())
+            } else {
+                reject("Sender cannot invite")
+            }
+        }
+        "leave" => {
+            if event.sender == target {
+                let may_leave =
+                    matches!(target_membership, "join" | "invite") || (version >= 7 && target_membership == "knock");
+                return if may_leave { Ok(()) } else { reject("User is not in the room") };
+            }
+            if sender_membership != "join" {
+                return reject("Sender is not joined to the room");
+            }
+            if target_membership == "ban" && sender_power < power.named("ban") {
+                return reject("Sender cannot unban");
+            }
+            if sender_power >= power.named("kick") && target_power < sender_power {
+                Ok(())
+            } else {
+                reject("Sender cannot kick the target")
+            }
+        }
+        "ban" => {
+            if sender_membership != "join" {
+                return reject("Sender is not joined to the room");
+            }
+            if sender_power >= power.named("ban") && target_power < sender_power {
+                Ok(())
+            } else {
+                reject("Sender cannot ban the target")
+            }
+        }
+        "knock" if version >= 7 => {
+            let knockable = join_rule == "knock" || (version >= 10 && join_rule == "knock_restricted");
+            if !knockable {
+                return reject("Room does not allow knocking");
+            }
+            if event.sender != target {
+                return reject("Cannot knock on behalf of another user");
+            }
+            if matches!(sender_membership, "ban" | "invite" | "join") {
+                return reject("User cannot knock from their current membership");
+            }
+            Ok(())
+        }
+        _ => reject(format!("Unknown membership {membership}")),
+    }
+}
+
+fn check_power_levels(
+    version: u32,
+    event: &StateResEvent,
+    current: &PowerLevels<'_>,
+    sender_power: i64,
+) -> Result<(), AuthError> {
+    let Some(old) = current.content else {
+        return Ok(());
+    };
+    let new = &event.content;
+    if version >= 10 {
+        let all_integers = ["users_default", "events_default", "state_default", "ban", "redact", "kick", "invite"]
+            .iter()
+            .all(|key| new[key].is_null() || new[key].is_i64())
+            && new["users"].as_object().is_none_or(|users| users.values().all(Value::is_i64))
+            && new["events"].as_object().is_none_or(|events| events.values().all(Value::is_i64));
+        if !all_integers {
+            return reject("Power levels must be integers");
+        }
+    }
+
+    let check_change = |old_value: Option<i64>, new_value: Option<i64>| -> Result<(), AuthError> {
+        if old_value == new_value {
+            return Ok(());
+        }
+        if old_value.is_some_and(|v| v > sender_power) || new_value.is_some_and(|v| v > sender_power) {
+            return reject("Sender cannot change a power level above their own");
+        }
+        Ok(())
+    };
+
+    for key in ["users_default", "events_default", "state_default", "ban", "redact", "kick", "invite"] {
+        check_change(as_power(&old[key]), as_power(&new[key]))?;
+    }
+    let mut notification_keys = vec![];
+    if version >= 6 {
+        notification_keys.push("notifications");
+    }
+    for map_key in std::iter::once("events").chain(notification_keys) {
+        let empty = serde_json::Map::new();
+        let old_map = old[map_key].as_object().unwrap_or(&empty);
+        let new_map = new[map_key].as_object().unwrap_or(&empty);
+        for key in old_map.keys().chain(new_map.keys()) {
+            check_change(old_map.get(key).and_then(as_power), new_map.get(key).and_then(as_power))?;
+        }
+    }
+
+    let empty = serde_json::Map::new();
+    let old_users = old["users"].as_object().unwrap_or(&empty);
+    let new_users = new["users"].as_object().unwrap_or(&empty);
+    for user_id in old_users.keys().chain(new_users.keys()) {
+        let old_value = old_users.get(user_id).and_then(as_power);
+        let new_value = new_users.get(user_id).and_then(as_power);
+        if old_value == new_value {
+            continue;
+        }
+        if user_id != &event.sender && old_value.is_some_and(|v| v >= sender_power) {
+            return reject("Sender cannot change the power of a user at or above their level");
+        }
+        check_change(old_value, new_value)?;
+    }
+    Ok(())
+}
@@ -0,0 +1,470 @@
+//! State resolution v2, used by room versions 2 and later.
+//!
+//! Given the state of a room at several forks of the DAG, [`resolve`]
+//! deterministically picks the state every server agrees on: power events
+//! are replayed first in reverse topological power order, the remaining
+//! conflicted events are replayed in mainline order, and each event is only
+//! kept if it passes the auth rules against the state resolved so far.
+
+pub mod auth;
+
+pub use auth::{auth_types_for_event, check_auth, AuthError, AuthState};
+
+use serde_json::Value;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// `(event type, state key)`.
+pub type StateKey = (String, String);
+
+/// Room state, mapping each state key to an event ID.
+pub type StateMap = HashMap<StateKey, String>;
+
+/// The fields of a PDU that state resolution and the auth rules need.
+#[derive(Debug, Clone, PartialEq)]
+pub struct StateResEvent {
+    pub event_id: String,
+    pub room_id: String,
+    pub event_type: String,
+    pub state_key: Option<String>,
+    pub sender: String,
+    pub content: Value,
+    pub auth_events: Vec<String>,
+    pub origin_server_ts: i64,
+}
+
+impl StateResEvent {
+    /// Builds an event from a PDU.  `auth_events` may use either the
+    /// `[event_id, hashes]` pairs of room v1/v2 or plain event IDs.
+    pub fn from_pdu(pdu: &Value) -> Option<Self> {
+        let auth_events = pdu["auth_events"]
+            .as_array()
+            .map(|entries| {
+                entries
+                    .iter()
+                    .filter_map(|entry| entry.as_str().or_else(|| entry[0].as_str()))
+                    .map(str::to_string)
+                    .collect()
+            })
+            .unwrap_or_default();
+        Some(Self {
+            event_id: pdu["event_id"].as_str()?.to_string(),
+            room_id: pdu["room_id"].as_str()?.to_string(),
+            event_type: pdu["type"].as_str()?.to_string(),
+            state_key: pdu["state_key"].as_str().map(str::to_string),
+            sender: pdu["sender"].as_str()?.to_string(),
+            content: pdu.get("content").cloned().unwrap_or_else(|| Value::Object(Default::default())),
+            auth_events,
+            origin_server_ts: pdu["origin_server_ts"].as_i64().unwrap_or(0),
+        })
+    }
+
+    pub fn key(&self) -> Option<StateKey> {
+        self.state_key.as_ref().map(|state_key| (self.event_type.clone(), state_key.clone()))
+    }
+
+    pub fn membership(&self) -> Option<&str> {
+        if self.event_type == "m.room.member" {
+            self.content["membership"].as_str()
+        } else {
+            None
+        }
+    }
+
+    /// Power events are resolved before all other conflicted events.
+    fn is_power_event(&self) -> bool {
+        match self.event_type.as_str() {
+            "m.room.create" | "m.room.power_levels" | "m.room.join_rules" => self.state_key.as_deref() == Some(""),
+            "m.room.member" => {
+                matches!(self.membership(), Some("leave" | "ban")) && self.state_key.as_deref() != Some(&self.sender)
+            }
+            _ => false,
+        }
+    }
+}
+
+/// Resolves `state_sets` into a single state.  `events` must contain the
+/// events referenced by the state sets together with their auth chains;
+/// events missing from it are ignored.
+pub fn resolve(room_version: &str, state_sets: &[StateMap], events: &HashMap<String, StateResEvent>) -> StateMap {
+    let (unconflicted, conflicted) = separate(state_sets);
+    if conflicted.is_empty() {
+        return unconflicted;
+    }
+
+    let full_conflicted: HashSet<&str> = conflicted
+        .into_iter()
+        .chain(auth_difference(state_sets, events))
+        .filter(|id| events.contains_key(*id))
+        .collect();
+
+    // Power events and their auth ancestry within the full conflicted set.
+    let mut power_set = HashSet::new();
+    let mut stack: Vec<&str> = full_conflicted.iter().copied().filter(|id| events[*id].is_power_event()).collect();
+    while let Some(id) = stack.pop() {
+        if power_set.insert(id) {
+            stack.extend(events[id].auth_events.iter().map(String::as_str).filter(|a| full_conflicted.contains(a)));
+        }
+    }
+
+    let power_order = reverse_topological_power_sort(&power_set, events);
+    let mut resolved = iterative_auth_checks(room_version, &power_order, unconflicted.clone(), events);
+
+    let power_levels_key = ("m.room.power_levels".to_string(), String::new());
+    let mainline = mainline_positions(resolved.get(&power_levels_key).map(String::as_str), events);
+    let mut others: Vec<&str> = full_conflicted.difference(&power_set).copied().collect();
+    others.sort_by_cached_key(|id| {
+        let event = &events[*id];
+        (mainline_depth(event, &mainline, events), event.origin_server_ts, event.event_id.clone())
+    });
+    resolved = iterative_auth_checks(room_version, &others, resolved, events);
+
+    resolved.extend(unconflicted);
+    resolved
+}
+
+/// Splits the state keys into those every set agrees on and the event IDs
+/// of those that differ (including keys missing from some sets).
+fn separate(state_sets: &[StateMap]) -> (StateMap, HashSet<&str>) {
+    let keys: HashSet<&StateKey> = state_sets.iter().flat_map(HashMap::keys).collect();
+    let mut unconflicted = StateMap::new();
+    let mut conflicted = HashSet::new();
+    for key in keys {
+        let values: Vec<Option<&String>> = state_sets.iter().map(|set| set.get(key)).collect();
+        match values[0] {
+            Some(first) if values.iter().all(|v| *v == Some(first)) => {
+                unconflicted.insert(key.clone(), first.clone());
+            }
+            _ => conflicted.extend(values.into_iter().flatten().map(String::as_str)),
+        }
+    }
+    (unconflicted, conflicted)
+}
+
+/// Events in the auth chain of some state sets but not of all of them.
+fn auth_difference<'a>(state_sets: &'a [StateMap], events: &'a HashMap<String, StateResEvent>) -> HashSet<&'a str> {
+    let chains: Vec<HashSet<&str>> = state_sets
+        .iter()
+        .map(|set| {
+            let mut chain = HashSet::new();
+            let mut stack: Vec<&str> = set.values().map(String::as_str).collect();
+            while let Some(id) = stack.pop() {
+                if chain.insert(id) {
+                    if let Some(event) = events.get(id) {
+                        stack.extend(event.auth_events.iter().map(String::as_str));
+                    }
+                }
+            }
+            chain
+        })
+        .collect();
+    let union: HashSet<&str> = chains.iter().flatten().copied().collect();
+    union.into_iter().filter(|id| !chains.iter().all(|chain| chain.contains(id))).collect()
+}
+
+/// The sender's power level according to the event's own auth events.
+fn sender_power_level(event: &StateResEvent, events: &HashMap<String, StateResEvent>) -> i64 {
+    let auth_event = |event_type: &str| {
+        event
+            .auth_events
+            .iter()
+            .filter_map(|id| events.get(id))
+            .find(|e| e.event_type == event_type && e.state_key.as_deref() == Some(""))
+    };
+    if let Some(power_levels) = auth_event("m.room.power_levels") {
+        let content = &power_levels.content;
+        let level = content["users"].get(&event.sender).or_else(|| content.get("users_default"));
+        return level.and_then(|v| v.as_i64().or_else(|| v.as_str().and_then(|s| s.parse().ok()))).unwrap_or(0);
+    }
+    match auth_event("m.room.create") {
+        Some(create) if create.content["creator"].as_str().unwrap_or(&create.sender) == event.sender => 100,
+        _ => 0,
+    }
+}
+
+/// Kahn's algorithm over the auth graph, breaking ties by descending sender
+/// power level, then ascending timestamp, then event ID.
+fn reverse_topological_power_sort<'a>(
+    power_set: &HashSet<&'a str>,
+    events: &'a HashMap<String, StateResEvent>,
+) -> Vec<&'a str> {
+    let mut pending: HashMap<&str, usize> = HashMap::new();
+    let mut dependents: HashMap<&str, Vec<&str>> = HashMap::new();
+    for &id in power_set {
+        let parents: HashSet<&str> =
+            events[id].auth_events.iter().map(String::as_str).filter(|a| power_set.contains(a)).collect();
+        pending.insert(id, parents.len());
+        for parent in parents {
+            dependents.entry(parent).or_default().push(id);
+        }
+    }
+
+    let sort_key = |id: &'a str| {
+        let event = &events[id];
+        Reverse((-sender_power_level(event, events), event.origin_server_ts, id))
+    };
+    let mut ready: BinaryHeap<_> = pending.iter().filter(|(_, n)| **n == 0).map(|(id, _)| sort_key(id)).collect();
+    let mut order = Vec::with_capacity(power_set.len());
+    while let Some(Reverse((_, _, id))) = ready.pop() {
+        order.push(id);
+        for &child in dependents.get(id).into_iter().flatten() {
+            if let Some(remaining) = pending.get_mut(child) {
+                *remaining -= 1;
+                if *remaining == 0 {
+                    ready.push(sort_key(child));
+                }
+            }
+        }
+    }
+    order
+}
+
+/// Applies each event on top of `state`, skipping those that fail the auth
+/// rules against their own auth events overlaid with the resolved state.
+fn iterative_auth_checks(
+    room_version: &str,
+    order: &[&str],
+    mut state: StateMap,
+    events: &HashMap<String, StateResEvent>,
+) -> StateMap {
+    for id in order {
+        let event = &events[*id];
+        let Some(key) = event.key() else {
+            continue;
+        };
+        let mut auth_state: AuthState<'_> =
+            event.auth_events.iter().filter_map(|a| events.get(a)).filter_map(|a| Some((a.key()?, a))).collect();
+        for auth_key in auth_types_for_event(room_version, event) {
+            if let Some(current) = state.get(&auth_key).and_then(|a| events.get(a)) {
+                auth_state.insert(auth_key, current);
+            }
+        }
+        if check_auth(room_version, event, &auth_state).is_ok() {
+            state.insert(key, event.event_id.clone());
+        }
+    }
+    state
+}
+
+/// Positions of the power levels events on the mainline of `power_levels`,
+/// counted from the oldest (1) upwards.
+fn mainline_positions<'a>(
+    power_levels: Option<&'a str>,
+    events: &'a HashMap<String, StateResEvent>,
+) -> HashMap<&'a str, usize> {
+    let mut mainline = Vec::new();
+    let mut current = power_levels;
+    while let Some(id) = current {
+        if mainline.contains(&id) {
+            break;
+        }
+        mainline.push(id);
+        current = events.get(id).and_then(|event| power_levels_auth_event(event, events));
+    }
+    mainline.iter().rev().enumerate().map(|(position, id)| (*id, position + 1)).collect()
+}
+
+fn power_levels_auth_event<'a>(
+    event: &'a StateResEvent,
+    events: &'a HashMap<String, StateResEvent>,
+) -> Option<&'a str> {
+    event.auth_events.iter().map(String::as_str).find(|id| {
+        events.get(*id).is_some_and(|e| e.event_type == "m.room.power_levels" && e.state_key.as_deref() == Some(""))
+    })
+}
+
+/// The mainline position of the closest mainline event in `event`'s power
+/// levels ancestry, or 0 when there is none.
+fn mainline_depth(
+    event: &StateResEvent,
+    mainline: &HashMap<&str, usize>,
+    events: &HashMap<String, StateResEvent>,
+) -> usize {
+    let mut current = Some(event.event_id.as_str());
+    let mut seen = HashSet::new();
+    while let Some(id) = current {
+        if let Some(position) = mainline.get(id) {
+            return *position;
+        }
+        if !seen.insert(id) {
+            break;
+        }
+        current = events.get(id).and_then(|e| power_levels_auth_event(e, events));
+    }
+    0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ROOM: &str = "!room:a.test";
+    const ALICE: &str = "@alice:a.test";
+    const BOB: &str = "@bob:b.test";
+
+    struct Room {
+        events: HashMap<String, StateResEvent>,
+        ts: i64,
+    }
+
+    impl Room {
+        fn new() -> Self {
+            Self { events: HashMap::new(), ts: 0 }
+        }
+
+        fn add(
+            &mut self,
+            id: &str,
+            sender: &str,
+            event_type: &str,
+            state_key: &str,
+            content: Value,
+            auth: &[&str],
+        ) -> String {
+            self.ts += 1;
+            let event = StateResEvent::from_pdu(&json!({
+                "event_id": id,
+                "room_id": ROOM,
+                "type": event_type,
+                "state_key": state_key,
+                "sender": sender,
+                "content": content,
+                "auth_events": auth,
+                "origin_server_ts": self.ts,
+            }))
+            .unwrap();
+            self.events.insert(id.to_string(), event);
+            id.to_string()
+        }
+
+        fn state(&self, ids: &[&str]) -> StateMap {
+            ids.iter().map(|id| (self.events[*id].key().unwrap(), id.to_string())).collect()
+        }
+
+        fn auth_state(&self, ids: &[&str]) -> HashMap<StateKey, &StateResEvent> {
+            ids.iter().map(|id| (self.events[*id].key().unwrap(), &self.events[*id])).collect()
+        }
+    }
+
+    /// Alice creates a room with Bob joined at power level 50.
+    fn base_room() -> Room {
+        let mut room = Room::new();
+        room.add("$create", ALICE, "m.room.create", "", json!({"creator": ALICE}), &[]);
+        room.add("$alice", ALICE, "m.room.member", ALICE, json!({"membership": "join"}), &["$create"]);
+        room.add(
+            "$pl",
+            ALICE,
+            "m.room.power_levels",
+            "",
+            json!({"users": {ALICE: 100, BOB: 50}, "state_default": 50, "ban": 50, "kick": 50}),
+            &["$create", "$alice"],
+        );
+        room.add("$jr", ALICE, "m.room.join_rules", "", json!({"join_rule": "public"}), &["$create", "$alice", "$pl"]);
+        room.add("$bob", BOB, "m.room.member", BOB, json!({"membership": "join"}), &["$create", "$pl", "$jr"]);
+        room
+    }
+
+    const BASE: [&str; 5] = ["$create", "$alice", "$pl", "$jr", "$bob"];
+
+    fn with(extra: &[&'static str]) -> Vec<&'static str> {
+        BASE.iter().chain(extra).copied().collect()
+    }
+
+    #[test]
+    fn test_identical_state_sets_resolve_to_themselves() {
+        let room = base_room();
+        let state = room.state(&BASE);
+        assert_eq!(resolve("10", &[state.clone(), state.clone()], &room.events), state);
+    }
+
+    #[test]
+    fn test_concurrent_topics_resolve_to_the_later_one() {
+        let mut room = base_room();
+        room.add("$t1", ALICE, "m.room.topic", "", json!({"topic": "one"}), &["$create", "$alice", "$pl"]);
+        room.add("$t2", BOB, "m.room.topic", "", json!({"topic": "two"}), &["$create", "$bob", "$pl"]);
+
+        let resolved = resolve("10", &[room.state(&with(&["$t1"])), room.state(&with(&["$t2"]))], &room.events);
+
+        assert_eq!(resolved[&("m.room.topic".to_string(), String::new())], "$t2");
+        assert_eq!(resolved[&("m.room.member".to_string(), BOB.to_string())], "$bob");
+    }
+
+    #[test]
+    fn test_ban_wins_over_concurrent_event_from_banned_user() {
+        let mut room = base_room();
+        room.add("$topic", ALICE, "m.room.topic", "", json!({"topic": "old"}), &["$create", "$alice", "$pl"]);
+        room.add(
+            "$ban",
+            ALICE,
+            "m.room.member",
+            BOB,
+            json!({"membership": "ban"}),
+            &["$create", "$alice", "$pl", "$bob"],
+        );
+        room.add("$bob_topic", BOB, "m.room.topic", "", json!({"topic": "new"}), &["$create", "$bob", "$pl"]);
+
+        let banned = room.state(&with(&["$topic", "$ban"]));
+        let retitled = room.state(&with(&["$topic", "$bob_topic"]));
+        let resolved = resolve("10", &[banned, retitled], &room.events);
+
+        assert_eq!(resolved[&("m.room.member".to_string(), BOB.to_string())], "$ban");
+        assert_eq!(resolved[&("m.room.topic".to_string(), String::new())], "$topic");
+    }
+
+    #[test]
+    fn test_demotion_invalidates_concurrent_power_change() {
+        let mut room = base_room();
+        room.add(
+            "$demote",
+            ALICE,
+            "m.room.power_levels",
+            "",
+            json!({"users": {ALICE: 100}, "state_default": 50}),
+            &["$create", "$alice", "$pl"],
+        );
+        room.add("$bob_jr", BOB, "m.room.join_rules", "", json!({"join_rule": "invite"}), &["$create", "$bob", "$pl"]);
+
+        let resolved = resolve("10", &[room.state(&with(&["$demote"])), room.state(&with(&["$bob_jr"]))], &room.events);
+
+        assert_eq!(resolved[&("m.room.power_levels".to_string(), String::new())], "$demote");
+        assert_eq!(resolved[&("m.room.join_rules".to_string(), String::new())], "$jr");
+    }
+
+    #[test]
+    fn test_check_auth_requires_membership_and_power() {
+        let mut room = base_room();
+        room.add("$eve_topic", "@eve:c.test", "m.room.topic", "", json!({"topic": "x"}), &[]);
+        room.add("$bob_pl", BOB, "m.room.power_levels", "", json!({"users": {ALICE: 100, BOB: 100}}), &[]);
+        room.add("$bob_kick", BOB, "m.room.member", ALICE, json!({"membership": "leave"}), &[]);
+        room.add("$alice_kick", ALICE, "m.room.member", BOB, json!({"membership": "leave"}), &[]);
+        let state = room.auth_state(&BASE);
+
+        assert!(check_auth("10", &room.events["$eve_topic"], &state).is_err());
+        assert!(check_auth("10", &room.events["$bob_pl"], &state).is_err());
+        assert!(check_auth("10", &room.events["$bob_kick"], &state).is_err());
+        assert!(check_auth("10", &room.events["$alice_kick"], &state).is_ok());
+    }
+
+    #[test]
+    fn test_check_auth_join_rules() {
+        let mut room = base_room();
+        room.add("$invite_jr", ALICE, "m.room.join_rules", "", json!({"join_rule": "invite"}), &[]);
+        room.add("$carol", "@carol:c.test", "m.room.member", "@carol:c.test", json!({"membership": "join"}), &[]);
+        let mut state = room.auth_state(&BASE);
+        assert!(check_auth("10", &room.events["$carol"], &state).is_ok());
+
+        state.insert(("m.room.join_rules".to_string(), String::new()), &room.events["$invite_jr"]);
+        assert!(check_auth("10", &room.events["$carol"], &state).is_err());
+    }
+
+    #[test]
+    fn test_from_pdu_accepts_v1_auth_event_pairs() {
+        let event = StateResEvent::from_pdu(&json!({
+            "event_id": "$e", "room_id": ROOM, "type": "m.room.name", "state_key": "", "sender": ALICE,
+            "content": {}, "auth_events": [["$create", {"sha256": "x"}], "$pl"],
+        }))
+        .unwrap();
+        assert_eq!(event.auth_events, vec!["$create", "$pl"]);
+    }
+}
@@ -329,6 +329,15 @@ impl crate::client_api::FederationClientApi for MockFederationClient {
         Err(FederationClientError::InvalidResponse("mock: get_public_rooms not configured".into()))
     }
 
+    async fn get_hierarchy(
+        &self,
+        _destination: &str,
+        _room_id: &str,
+        _suggested_only: bool,
+    ) -> Result<serde_json::Value, FederationClientError> {
+        Err(FederationClientError::InvalidResponse("mock: get_hierarchy not configured".into()))
+    }
+
     async fn knock_room(
         &self,
         _destination: &str,
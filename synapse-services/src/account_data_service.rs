@@ -4,6 +4,7 @@ use synapse_cache::CacheManager;
 use synapse_common::crypto::random_string;
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
+use synapse_common::USER_DIRECTORY_ACCOUNT_DATA_TYPE;
 use synapse_storage::account_data::AccountDataStoreApi;
 use synapse_storage::filter::{CreateFilterRequest, FilterStoreApi};
 use synapse_storage::openid_token::{CreateOpenIdTokenRequest, OpenIdToken, OpenIdTokenStoreApi};
@@ -74,6 +75,39 @@ impl AccountDataService {
             .map_err(|e| ApiError::internal_with_log("Database error", &e))
     }
 
+    /// Users among `user_ids` that opted out of the user directory through
+    /// [`USER_DIRECTORY_ACCOUNT_DATA_TYPE`] account data.
+    #[instrument(skip(self, user_ids))]
+    pub async fn users_hidden_from_directory(
+        &self,
+        user_ids: &[String],
+    ) -> Result<std::collections::HashSet<String>, ApiError> {
+        let contents = self
+            .account_data_storage
+            .get_account_data_content_for_users(user_ids, USER_DIRECTORY_ACCOUNT_DATA_TYPE)
+            .await?;
+        Ok(contents.into_iter().filter(|(_, content)| is_hidden_from_directory(content)).map(|(id, _)| id).collect())
+    }
+
+    pub async fn is_hidden_from_directory(&self, user_id: &str) -> Result<bool, ApiError> {
+        Ok(self
+            .get_account_data(user_id, USER_DIRECTORY_ACCOUNT_DATA_TYPE)
+            .await?
+            .is_some_and(|content| is_hidden_from_directory(&content)))
+    }
+
+    /// Set the directory opt-out on behalf of `user_id`, keeping any other
+    /// keys stored alongside it.
+    pub async fn set_hidden_from_directory(&self, user_id: &str, hidden: bool) -> Result<(), ApiError> {
+        let mut content = self
+            .get_account_data(user_id, USER_DIRECTORY_ACCOUNT_DATA_TYPE)
+            .await?
+            .filter(Value::is_object)
+            .unwrap_or_else(|| serde_json::json!({}));
+        content["hidden"] = Value::Bool(hidden);
+        self.set_account_data(user_id, USER_DIRECTORY_ACCOUNT_DATA_TYPE, &content).await
+    }
+
     /// Get the set of user IDs that `user_id` has ignored via the
     /// `m.ignored_user_list` account_data event.
     ///
@@ -160,6 +194,7 @@ impl AccountDataService {
 
     #[instrument(skip(self, content))]
     pub async fn create_filter(&self, user_id: &str, content: Value) -> Result<String, ApiError> {
+        validate_filter(&content)?;
         let filter_id = random_string(16);
         self.filter_storage
             .create_filter(CreateFilterRequest { user_id: user_id.to_string(), filter_id: filter_id.clone(), content })
@@ -237,9 +272,44 @@ fn validate_account_data_payload(data_type: &str, body: &Value) -> Result<(), Ap
         }
     }
 
+    if data_type == USER_DIRECTORY_ACCOUNT_DATA_TYPE && body.get("hidden").is_some_and(|hidden| !hidden.is_boolean()) {
+        return Err(ApiError::bad_request(format!("{USER_DIRECTORY_ACCOUNT_DATA_TYPE} 'hidden' must be a boolean")));
+    }
+
     Ok(())
 }
 
+fn is_hidden_from_directory(content: &Value) -> bool {
+    content.get("hidden").and_then(Value::as_bool).unwrap_or(false)
+}
+
+/// Shape checks on an uploaded filter: an object whose `limit`s are
+/// positive integers and whose type and room lists are string arrays.
+fn validate_filter(filter: &Value) -> Result<(), ApiError> {
+    fn walk(value: &Value, path: &str) -> Result<(), ApiError> {
+        let Some(object) = value.as_object() else {
+            return Err(ApiError::bad_request(format!("Filter section '{path}' must be an object")));
+        };
+        for (key, value) in object {
+            let path = if path.is_empty() { key.clone() } else { format!("{path}.{key}") };
+            match key.as_str() {
+                "limit" if value.as_u64().is_none_or(|limit| limit == 0) => {
+                    return Err(ApiError::bad_request(format!("'{path}' must be a positive integer")));
+                }
+                "types" | "not_types" | "rooms" | "not_rooms" | "senders" | "not_senders" | "event_fields"
+                    if !value.as_array().is_some_and(|values| values.iter().all(Value::is_string)) =>
+                {
+                    return Err(ApiError::bad_request(format!("'{path}' must be an array of strings")));
+                }
+                "room" | "presence" | "account_data" | "timeline" | "state" | "ephemeral" => walk(value, &path)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+    walk(filter, "")
+}
+
 #[cfg(test)]
 mod tests {
     #![allow(clippy::unwrap_used, clippy::expect_used)]
@@ -379,6 +449,13 @@ mod tests {
         assert!(result.unwrap_err().to_string().contains("Invalid user ID"));
     }
 
+    #[test]
+    fn test_validate_user_directory_hidden_must_be_boolean() {
+        assert!(validate_account_data_payload(USER_DIRECTORY_ACCOUNT_DATA_TYPE, &json!({"hidden": true})).is_ok());
+        assert!(validate_account_data_payload(USER_DIRECTORY_ACCOUNT_DATA_TYPE, &json!({"hidden": "yes"})).is_err());
+        assert!(!is_hidden_from_directory(&json!({})));
+    }
+
     // ========== AccountDataService mock-backed behavioural tests ==========
 
     // ── Filter round-trip ──
@@ -404,6 +481,22 @@ mod tests {
         assert!(result.is_none(), "unknown filter_id should return None");
     }
 
+    #[tokio::test]
+    async fn create_filter_rejects_malformed_sections() {
+        let service = make_service();
+
+        for bad in [
+            json!([]),
+            json!({"room": {"timeline": {"limit": 0}}}),
+            json!({"room": {"rooms": "!a:localhost"}}),
+            json!({"presence": "none"}),
+        ] {
+            assert!(service.create_filter("@alice:localhost", bad.clone()).await.is_err(), "{bad} accepted");
+        }
+        let ok = json!({"room": {"rooms": ["!a:localhost"], "timeline": {"limit": 10, "lazy_load_members": true}}});
+        assert!(service.create_filter("@alice:localhost", ok).await.is_ok());
+    }
+
     #[tokio::test]
     async fn get_filter_scoped_to_user() {
         let service = make_service();
@@ -164,6 +164,17 @@ impl AccountIdentityService {
         }
     }
 
+    /// Local user a verified 3PID is bound to, used to turn a 3PID invite
+    /// into a direct invite. Email addresses are matched case-insensitively.
+    pub async fn resolve_local_threepid_user(&self, medium: &str, address: &str) -> Result<Option<String>, ApiError> {
+        let address = if medium == "email" { address.trim().to_lowercase() } else { address.trim().to_string() };
+        Ok(self
+            .threepid_storage
+            .get_verified_threepid_by_address(medium, &address)
+            .await?
+            .map(|threepid| threepid.user_id))
+    }
+
     pub async fn require_deactivate_account_uia(
         &self,
         uia_service: &UiaService,
@@ -298,6 +309,18 @@ mod tests {
         assert_eq!(result, None);
     }
 
+    // ── resolve_local_threepid_user ─────────────────────────────────
+
+    #[tokio::test]
+    async fn resolve_local_threepid_user_matches_email_case_insensitively() {
+        let store = Arc::new(InMemoryThreepidStore::new());
+        store.seed_threepid("@alice:example.com", "email", "alice@example.com").await;
+        let svc = make_service(store);
+        let user_id = svc.resolve_local_threepid_user("email", " Alice@Example.com").await.unwrap();
+        assert_eq!(user_id, Some("@alice:example.com".to_string()));
+        assert!(svc.resolve_local_threepid_user("msisdn", "alice@example.com").await.unwrap().is_none());
+    }
+
     // ── threepid passthrough methods ────────────────────────────────
 
     #[tokio::test]
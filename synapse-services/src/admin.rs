@@ -8,6 +8,7 @@
 //! - `synapse_services::AdminAuditService`      (legacy flat path, via `pub use admin::*` in lib.rs)
 
 pub use crate::admin_audit_service::AdminAuditService;
+pub use crate::admin_bootstrap_service::{AdminBootstrapService, BootstrapAdminResponse, BootstrapOutcome};
 pub use crate::admin_federation_service::{
     decode_destination_cursor, decode_pending_federation_cursor, encode_destination_cursor,
     encode_pending_federation_cursor, AdminFederationService, ConfirmFederationResult, DestinationCursor,
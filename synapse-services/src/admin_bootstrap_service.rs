@@ -0,0 +1,181 @@
+//! First-run creation of an admin account.
+//!
+//! A fresh deployment has no admin, and without one nothing can be managed
+//! through the admin API. At startup, if no active admin exists, this either
+//! creates the admin named in `admin_bootstrap` or issues a one-time setup
+//! token that `POST /_synapse/admin/v1/bootstrap` exchanges for an admin
+//! account. The token lives only in this process and is never stored.
+
+use crate::auth::CredentialAuth;
+use crate::*;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine as _};
+use rand::RngCore;
+use serde::Serialize;
+use std::sync::{Arc, Mutex};
+use synapse_common::config::AdminBootstrapConfig;
+use synapse_common::*;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum BootstrapOutcome {
+    Disabled,
+    AdminExists,
+    Created { user_id: String },
+    SetupToken(String),
+}
+
+#[derive(Debug, Serialize)]
+pub struct BootstrapAdminResponse {
+    pub access_token: String,
+    pub device_id: String,
+    pub user_id: String,
+    pub home_server: String,
+}
+
+pub struct AdminBootstrapService {
+    credential_auth: Arc<dyn CredentialAuth>,
+    user_storage: Arc<dyn UserStore>,
+    config: AdminBootstrapConfig,
+    server_name: String,
+    setup_token: Mutex<Option<String>>,
+}
+
+impl AdminBootstrapService {
+    pub fn new(
+        credential_auth: Arc<dyn CredentialAuth>,
+        user_storage: Arc<dyn UserStore>,
+        config: AdminBootstrapConfig,
+        server_name: String,
+    ) -> Self {
+        Self { credential_auth, user_storage, config, server_name, setup_token: Mutex::new(None) }
+    }
+
+    /// Runs once at startup.
+    pub async fn run(&self) -> ApiResult<BootstrapOutcome> {
+        if !self.config.enabled {
+            return Ok(BootstrapOutcome::Disabled);
+        }
+        if self.admin_exists().await? {
+            return Ok(BootstrapOutcome::AdminExists);
+        }
+
+        match (&self.config.username, &self.config.password_file) {
+            (Some(username), Some(password_file)) => {
+                let password = read_password_file(password_file).await?;
+                let (user, ..) = self.credential_auth.register(username, &password, true, None).await?;
+                Ok(BootstrapOutcome::Created { user_id: user.user_id() })
+            }
+            (None, None) => {
+                let token = generate_setup_token();
+                *self.lock_token() = Some(token.clone());
+                Ok(BootstrapOutcome::SetupToken(token))
+            }
+            _ => Err(ApiError::bad_request(
+                "admin_bootstrap.username and admin_bootstrap.password_file must be set together".to_string(),
+            )),
+        }
+    }
+
+    /// Exchanges the setup token for a new admin account. The token is
+    /// single-use; it is given back only when the account could not be
+    /// created, e.g. because the password was rejected.
+    pub async fn redeem(&self, token: &str, username: &str, password: &str) -> ApiResult<BootstrapAdminResponse> {
+        let issued = {
+            let mut guard = self.lock_token();
+            match guard.as_deref() {
+                Some(issued) if crypto::secure_compare(issued, token) => guard.take(),
+                _ => None,
+            }
+        };
+        let Some(issued) = issued else {
+            return Err(ApiError::forbidden("Invalid or expired setup token".to_string()));
+        };
+
+        if self.admin_exists().await? {
+            return Err(ApiError::forbidden("An admin account already exists".to_string()));
+        }
+
+        match self.credential_auth.register(username, password, true, None).await {
+            Ok((user, access_token, _refresh_token, device_id)) => Ok(BootstrapAdminResponse {
+                access_token,
+                device_id,
+                user_id: user.user_id(),
+                home_server: self.server_name.clone(),
+            }),
+            Err(e) => {
+                *self.lock_token() = Some(issued);
+                Err(e)
+            }
+        }
+    }
+
+    async fn admin_exists(&self) -> ApiResult<bool> {
+        self.user_storage
+            .has_active_admin()
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to look up admin accounts", &e))
+    }
+
+    fn lock_token(&self) -> std::sync::MutexGuard<'_, Option<String>> {
+        self.setup_token.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+fn generate_setup_token() -> String {
+    let mut bytes = [0u8; 32];
+    rand::rng().fill_bytes(&mut bytes);
+    URL_SAFE_NO_PAD.encode(bytes)
+}
+
+async fn read_password_file(path: &str) -> ApiResult<String> {
+    let contents = tokio::fs::read_to_string(path)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Failed to read admin_bootstrap.password_file", &e))?;
+    let password = contents.trim_end_matches(['\r', '\n']);
+    if password.is_empty() {
+        return Err(ApiError::bad_request("admin_bootstrap.password_file is empty".to_string()));
+    }
+    Ok(password.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn password_file_drops_trailing_newline_only() {
+        let path = std::env::temp_dir().join(format!("admin-bootstrap-{}", generate_setup_token()));
+        tokio::fs::write(&path, " s3cret pass \n").await.unwrap();
+        let password = read_password_file(path.to_str().unwrap()).await.unwrap();
+        let _ = tokio::fs::remove_file(&path).await;
+        assert_eq!(password, " s3cret pass ");
+    }
+
+    #[tokio::test]
+    async fn setup_token_is_returned_after_failed_registration() {
+        let service = AdminBootstrapService::new(
+            Arc::new(crate::test_mocks::FakeCredentialAuth::new()),
+            Arc::new(synapse_storage::FakeUserStore::new()),
+            AdminBootstrapConfig::default(),
+            "example.com".to_string(),
+        );
+        let BootstrapOutcome::SetupToken(token) = service.run().await.unwrap() else {
+            panic!("expected a setup token when no admin exists");
+        };
+
+        let wrong = service.redeem("not-the-token", "admin", "password").await.unwrap_err();
+        assert_eq!(wrong.http_status().as_u16(), 403);
+
+        // The fake refuses every registration, so the token must be handed back.
+        for _ in 0..2 {
+            let err = service.redeem(&token, "admin", "password").await.unwrap_err();
+            assert_eq!(err.http_status().as_u16(), 401);
+        }
+    }
+
+    #[test]
+    fn setup_tokens_are_unique() {
+        let token = generate_setup_token();
+        assert_eq!(token.len(), 43);
+        assert_ne!(token, generate_setup_token());
+    }
+}
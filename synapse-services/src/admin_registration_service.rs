@@ -144,18 +144,17 @@ impl AdminRegistrationService {
         })
     }
 
+    /// Consumes `nonce`. A nonce is accepted once, and only within
+    /// `nonce_timeout_seconds` of being issued.
     async fn validate_and_consume_nonce(&self, nonce: &str) -> ApiResult<()> {
         let key = format!("admin:register:nonce:{nonce}");
-        let existing = self.cache.get::<i64>(&key).await?;
-        if existing.is_none() {
-            return Err(ApiError::bad_request("Unrecognised nonce".to_string()));
+        let issued_at = self.cache.take::<i64>(&key).await?;
+        match issued_at {
+            Some(issued_at) if !nonce_expired(issued_at, Utc::now().timestamp(), self.config.nonce_timeout_seconds) => {
+                Ok(())
+            }
+            _ => Err(ApiError::bad_request("Unrecognised nonce".to_string())),
         }
-        self.cache.delete(&key).await;
-        let after_delete = self.cache.get::<i64>(&key).await?;
-        if after_delete.is_some() {
-            return Err(ApiError::internal("Failed to consume nonce".to_string()));
-        }
-        Ok(())
     }
 
     fn verify_hmac(&self, request: &AdminRegisterRequest) -> ApiResult<()> {
@@ -166,29 +165,45 @@ impl AdminRegistrationService {
         let provided = synapse_common::crypto::decode_hex(&request.mac)
             .map_err(|_| ApiError::forbidden("HMAC incorrect".to_string()))?;
 
-        let mut mac = HmacSha256::new_from_slice(self.config.shared_secret.as_bytes())
-            .map_err(|e| ApiError::internal_with_log("Invalid shared secret", &e))?;
+        let tags: &[&[u8]] = if request.admin.unwrap_or(false) { &ADMIN_TAGS } else { &[b"notadmin"] };
+        for tag in tags {
+            let mac = registration_mac(&self.config.shared_secret, request, tag)?;
+            if mac.verify_slice(&provided).is_ok() {
+                return Ok(());
+            }
+        }
+        Err(ApiError::forbidden("HMAC incorrect".to_string()))
+    }
+}
 
-        mac.update(request.nonce.as_bytes());
-        mac.update(b"\0");
-        mac.update(request.username.as_bytes());
-        mac.update(b"\0");
-        mac.update(request.password.as_bytes());
+/// `register_new_matrix_user` tags admins with `admin`. Earlier releases of
+/// this server expected the tag NUL-padded to the length of `notadmin`, which
+/// older deployment scripts still send.
+const ADMIN_TAGS: [&[u8]; 2] = [b"admin", b"admin\x00\x00\x00"];
+
+/// HMAC-SHA256 over `nonce\0username\0password\0<tag>[\0user_type]`.
+fn registration_mac(secret: &str, request: &AdminRegisterRequest, admin_tag: &[u8]) -> ApiResult<HmacSha256> {
+    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
+        .map_err(|e| ApiError::internal_with_log("Invalid shared secret", &e))?;
+
+    mac.update(request.nonce.as_bytes());
+    mac.update(b"\0");
+    mac.update(request.username.as_bytes());
+    mac.update(b"\0");
+    mac.update(request.password.as_bytes());
+    mac.update(b"\0");
+    mac.update(admin_tag);
+
+    if let Some(user_type) = &request.user_type {
         mac.update(b"\0");
+        mac.update(user_type.as_bytes());
+    }
 
-        if request.admin.unwrap_or(false) {
-            mac.update(b"admin\x00\x00\x00");
-        } else {
-            mac.update(b"notadmin");
-        }
-
-        if let Some(user_type) = &request.user_type {
-            mac.update(b"\0");
-            mac.update(user_type.as_bytes());
-        }
+    Ok(mac)
+}
 
-        mac.verify_slice(&provided).map_err(|_| ApiError::forbidden("HMAC incorrect".to_string()))
-    }
+fn nonce_expired(issued_at: i64, now: i64, timeout_seconds: u64) -> bool {
+    now.saturating_sub(issued_at) > i64::try_from(timeout_seconds).unwrap_or(i64::MAX)
 }
 
 #[cfg(test)]
@@ -261,4 +276,29 @@ mod tests {
         assert_eq!(request.user_type, Some("bot".to_string()));
         assert_eq!(request.displayname, Some("Admin User".to_string()));
     }
+
+    #[test]
+    fn test_registration_mac_matches_register_new_matrix_user() {
+        let request = AdminRegisterRequest {
+            nonce: "abc".to_string(),
+            username: "alice".to_string(),
+            password: "hunter22".to_string(),
+            admin: Some(true),
+            user_type: None,
+            displayname: None,
+            mac: String::new(),
+        };
+        let mut expected = HmacSha256::new_from_slice(b"secret").unwrap();
+        expected.update(b"abc\0alice\0hunter22\0admin");
+
+        let mac = registration_mac("secret", &request, ADMIN_TAGS[0]).unwrap();
+        assert_eq!(mac.finalize().into_bytes(), expected.finalize().into_bytes());
+    }
+
+    #[test]
+    fn test_nonce_expiry() {
+        assert!(!nonce_expired(1_000, 1_060, 60));
+        assert!(nonce_expired(1_000, 1_061, 60));
+        assert!(!nonce_expired(1_000, 1_000, u64::MAX));
+    }
 }
@@ -1,8 +1,9 @@
 use sqlx::PgPool;
+use std::path::Path;
 use std::sync::Arc;
 use synapse_common::health::{DatabaseHealthCheck, HealthCheck};
 use synapse_common::ApiError;
-use synapse_storage::schema_validator::SchemaValidator;
+use synapse_storage::schema_validator::{SchemaDriftReport, SchemaValidator};
 use tracing::{instrument, warn};
 
 /// AdminServerService requires direct PgPool access for infrastructure-level
@@ -39,4 +40,12 @@ impl AdminServerService {
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to validate required tables", &e))
     }
+
+    #[instrument(skip(self))]
+    pub async fn schema_drift_report(&self, migrations_dir: &str) -> Result<SchemaDriftReport, ApiError> {
+        SchemaValidator::new(self.pool.clone())
+            .detect_drift(Path::new(migrations_dir))
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to compute schema drift report", &e))
+    }
 }
@@ -130,6 +130,41 @@ pub struct BatchUsersResult {
     pub failed: Vec<String>,
 }
 
+/// One account in a bulk provisioning request.
+#[derive(Debug, Clone)]
+pub struct ProvisionUserRow {
+    pub username: String,
+    pub password: Option<String>,
+    pub displayname: Option<String>,
+    pub admin: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProvisionStatus {
+    Created,
+    /// The account already existed and was left untouched, so re-running an
+    /// import is safe.
+    Exists,
+    Failed,
+}
+
+impl ProvisionStatus {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Created => "created",
+            Self::Exists => "exists",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ProvisionUserOutcome {
+    pub user_id: String,
+    pub status: ProvisionStatus,
+    pub error: Option<String>,
+}
+
 pub struct AdminUserService {
     user_service: Arc<crate::UserService>,
     user_storage: Arc<dyn UserStore>,
@@ -489,8 +524,51 @@ impl AdminUserService {
         Ok(BatchUsersResult { succeeded, failed })
     }
 
+    /// Creates `row`'s account unless it already exists. Failures are
+    /// reported in the outcome rather than returned, so one bad row does not
+    /// abort the rest of an import.
+    #[instrument(skip(self, row), fields(username = %row.username))]
+    pub async fn provision_user(&self, row: &ProvisionUserRow) -> ProvisionUserOutcome {
+        let user_id = format!("@{}:{}", row.username, self.server_name);
+        let failed = |error: &str| ProvisionUserOutcome {
+            user_id: user_id.clone(),
+            status: ProvisionStatus::Failed,
+            error: Some(error.to_string()),
+        };
+
+        match self.user_storage.user_exists(&user_id).await {
+            Ok(true) => return ProvisionUserOutcome { user_id, status: ProvisionStatus::Exists, error: None },
+            Ok(false) => {}
+            Err(e) => {
+                tracing::warn!(user_id = %user_id, error = %e, "Provisioning existence check failed");
+                return failed("Database error");
+            }
+        }
+
+        let password = row.password.clone().unwrap_or_else(|| random_string(32));
+        let Ok(password_hash) = hash_password(&password) else {
+            return failed("Failed to hash password");
+        };
+        if let Err(e) = self.user_storage.create_user(&user_id, &row.username, Some(&password_hash), row.admin).await {
+            tracing::warn!(user_id = %user_id, error = %e, "Provisioning user creation failed");
+            return failed("Failed to create user");
+        }
+        if let Some(displayname) = row.displayname.as_deref() {
+            if let Err(e) = self.user_service.update_displayname(&user_id, Some(displayname)).await {
+                tracing::warn!(user_id = %user_id, error = %e, "Provisioned user without display name");
+                return ProvisionUserOutcome {
+                    user_id,
+                    status: ProvisionStatus::Created,
+                    error: Some("Display name could not be set".to_string()),
+                };
+            }
+        }
+
+        ProvisionUserOutcome { user_id, status: ProvisionStatus::Created, error: None }
+    }
+
     #[instrument(skip(self))]
-    pub async fn batch_deactivate_users(&self, user_ids: &[String]) -> Result<BatchUsersResult, ApiError> {
+    pub async fn batch_deactivate_users(&self, user_ids: &[String], erase: bool) -> Result<BatchUsersResult, ApiError> {
         let mut succeeded = Vec::new();
         let mut failed = Vec::new();
 
@@ -501,6 +579,10 @@ impl AdminUserService {
             }
 
             match self.user_storage.set_deactivation_status(user_id, true).await {
+                Ok(true) if erase => match self.user_storage.mark_user_erased(user_id).await {
+                    Ok(()) => succeeded.push(user_id.clone()),
+                    Err(_) => failed.push(user_id.clone()),
+                },
                 Ok(true) => succeeded.push(user_id.clone()),
                 _ => failed.push(user_id.clone()),
             }
@@ -6,6 +6,7 @@ use std::time::Duration;
 use synapse_common::ApiError;
 use synapse_storage::application_service::*;
 use tokio::fs;
+use tokio::sync::Notify;
 use tracing::{info, instrument, warn};
 
 pub mod scheduler;
@@ -18,11 +19,17 @@ mod transaction;
 
 pub use models::NamespacesInfo;
 
+/// Registration key that opts a service into membership auto-provisioning.
+pub const AUTO_PROVISION_MEMBERSHIP_KEY: &str = "auto_provision_membership";
+
 pub struct ApplicationServiceManager {
     storage: Arc<dyn ApplicationServiceStoreApi>,
     event_reader: Arc<dyn synapse_storage::event::EventReader>,
     http_client: Client,
     server_name: String,
+    /// Signalled whenever `enqueue_matching_event` queues at least one event,
+    /// so the scheduler can dispatch without waiting for its next tick.
+    queued: Arc<Notify>,
 }
 
 impl ApplicationServiceManager {
@@ -31,10 +38,11 @@ impl ApplicationServiceManager {
         event_reader: Arc<dyn synapse_storage::event::EventReader>,
         server_name: String,
     ) -> Self {
-        let http_client = Client::builder()
+        let http_client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(15))
             .connect_timeout(Duration::from_secs(5))
             .pool_idle_timeout(Duration::from_secs(60))
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .unwrap_or_else(|e| {
                 tracing::warn!(
@@ -47,7 +55,7 @@ impl ApplicationServiceManager {
                 Client::new()
             });
 
-        Self { storage, event_reader, http_client, server_name }
+        Self { storage, event_reader, http_client, server_name, queued: Arc::new(Notify::new()) }
     }
 
     #[instrument(skip(self, config_files))]
@@ -262,9 +270,19 @@ impl ApplicationServiceManager {
             enqueued += 1;
         }
 
+        if enqueued > 0 {
+            self.queued.notify_one();
+        }
         Ok(enqueued)
     }
 
+    /// Resolves once an event has been queued since the last call returned.
+    /// A notification that arrives while nobody is waiting is kept, so the
+    /// scheduler never misses one between ticks.
+    pub async fn event_queued(&self) {
+        self.queued.notified().await;
+    }
+
     #[instrument(skip(self))]
     pub async fn get_pending_events(&self, as_id: &str, limit: i64) -> Result<Vec<ApplicationServiceEvent>, ApiError> {
         self.storage
@@ -306,6 +324,26 @@ impl ApplicationServiceManager {
             .map_err(|e| ApiError::internal_with_log("Failed to query user namespace", &e))
     }
 
+    /// Whether `user_id` is in an exclusive namespace of an enabled service
+    /// whose registration sets `auto_provision_membership: true`, so that the
+    /// user is joined to rooms it writes to without joining first.
+    #[instrument(skip(self))]
+    pub async fn provisions_membership_for(&self, user_id: &str) -> Result<bool, ApiError> {
+        let Some(as_id) = self.query_user(user_id).await? else {
+            return Ok(false);
+        };
+        let Some(service) = self.get(&as_id).await? else {
+            return Ok(false);
+        };
+        Ok(service.is_enabled
+            && Self::auto_provisions_membership(&service)
+            && Self::namespace_matches(&service.namespaces, "users", user_id, true))
+    }
+
+    fn auto_provisions_membership(service: &ApplicationService) -> bool {
+        service.config.get(AUTO_PROVISION_MEMBERSHIP_KEY).and_then(serde_json::Value::as_bool) == Some(true)
+    }
+
     #[instrument(skip(self))]
     pub async fn query_room_alias(&self, alias: &str) -> Result<Option<String>, ApiError> {
         self.storage
@@ -430,6 +468,10 @@ impl ApplicationServiceManager {
             .ok_or_else(|| ApiError::not_found("Application service not found"))?;
 
         let url = format!("{}/_matrix/app/v1/ping", service.url);
+        if let Err(e) = synapse_common::egress::check_url(&url).await {
+            warn!(as_id, error = %e, "Application service URL blocked by egress policy");
+            return Ok(false);
+        }
 
         let response = self
             .http_client
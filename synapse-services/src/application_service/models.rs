@@ -260,7 +260,7 @@ impl ApplicationServiceManager {
             || state_key.is_some_and(|key| Self::namespace_matches(&service.namespaces, "users", key, false))
     }
 
-    pub(super) fn namespace_matches(
+    pub(crate) fn namespace_matches(
         namespaces: &serde_json::Value,
         namespace_kind: &str,
         candidate: &str,
@@ -340,7 +340,7 @@ impl ApplicationServiceManager {
             .collect()
     }
 
-    pub(super) fn is_local_user_id(user_id: &str, server_name: &str) -> bool {
+    pub(crate) fn is_local_user_id(user_id: &str, server_name: &str) -> bool {
         user_id
             .strip_prefix('@')
             .and_then(|stripped| stripped.split_once(':'))
@@ -389,7 +389,9 @@ impl ApplicationServiceScheduler {
     /// spawned `tokio` task.
     ///
     /// The ticker uses `MissedTickBehavior::Delay` so that backpressure
-    /// (slow I/O) does not cause bursts.
+    /// (slow I/O) does not cause bursts. Queuing an event through the manager
+    /// also triggers a tick straight away, so the interval only bounds retry
+    /// and recovery latency.
     pub fn start(
         self: Arc<Self>,
         shutdown: tokio_util::sync::CancellationToken,
@@ -419,6 +421,11 @@ impl ApplicationServiceScheduler {
                             warn!(error = %e, "AS scheduler tick failed");
                         }
                     }
+                    _ = scheduler.manager.event_queued() => {
+                        if let Err(e) = scheduler.tick().await {
+                            warn!(error = %e, "AS scheduler tick failed");
+                        }
+                    }
                 }
             }
         });
@@ -197,6 +197,46 @@ receive_ephemeral: true
     assert_eq!(request.config.unwrap()["receive_ephemeral"], serde_json::json!(true));
 }
 
+#[test]
+fn test_auto_provision_membership_is_read_from_registration() {
+    let manager = test_manager();
+    let raw_config = r#"
+id: irc-bridge
+url: http://localhost:9999
+as_token: appservice-token
+hs_token: homeserver-token
+sender_localpart: ircbot
+namespaces:
+  users:
+    - exclusive: true
+      regex: '@_irc_.*:example\.com'
+auto_provision_membership: true
+"#;
+    let request = manager.parse_config_file_contents(raw_config, "inline").expect("valid config");
+    let service = ApplicationService {
+        id: 1,
+        as_id: request.as_id,
+        url: request.url,
+        as_token: request.as_token,
+        hs_token: request.hs_token,
+        sender_localpart: request.sender,
+        is_enabled: true,
+        is_rate_limited: false,
+        protocols: Vec::new(),
+        namespaces: request.namespaces.unwrap_or_default(),
+        created_ts: 0,
+        updated_ts: None,
+        description: None,
+        api_key: None,
+        config: request.config.unwrap_or_default(),
+    };
+    assert!(ApplicationServiceManager::auto_provisions_membership(&service));
+    assert!(!ApplicationServiceManager::auto_provisions_membership(&ApplicationService {
+        config: serde_json::json!({}),
+        ..service
+    }));
+}
+
 #[test]
 fn test_parse_config_file_contents_rejects_invalid_namespace_regex() {
     let manager = test_manager();
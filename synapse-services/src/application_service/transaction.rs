@@ -139,6 +139,13 @@ impl ApplicationServiceManager {
     ) -> Result<(), ApiError> {
         let url = format!("{}/transactions/{}", service.url, transaction_id);
 
+        if let Err(e) = synapse_common::egress::check_url(&url).await {
+            warn!(as_id = %service.as_id, transaction_id, error = %e, "Application service URL blocked by egress policy");
+            self.handle_transaction_failure(service, transaction_id, &e.to_string(), TransactionFailureKind::Retryable)
+                .await;
+            return Err(ApiError::forbidden(format!("Application service URL not allowed: {e}")));
+        }
+
         let response = self
             .http_client
             .put(&url)
@@ -0,0 +1,213 @@
+//! Application service authentication.
+//!
+//! An application service authenticates with its `as_token` and acts as its
+//! sender or, through the `user_id` query parameter, as any local user in
+//! one of its exclusive user namespaces. Users it acts as are created on
+//! first use, so a bridge needs no registration call per ghost.
+//!
+//! For double puppeting a service can also log in, with the
+//! `m.login.application_service` login type, as an existing user in any of
+//! its user namespaces and receive a device and access token of that user.
+
+use super::{AuthService, TokenValidation};
+use crate::application_service::ApplicationServiceManager;
+use synapse_common::{ApiError, ApiResult};
+use synapse_storage::application_service::ApplicationService;
+use synapse_storage::User;
+
+impl AuthService {
+    /// Validates `token` like [`AuthService::validate_token`]. An application
+    /// service token instead resolves to `user_id`, or to the service's
+    /// sender when no user is given.
+    pub async fn validate_token_as(&self, token: &str, user_id: Option<&str>) -> ApiResult<TokenValidation> {
+        // Access tokens are JWTs; only other tokens can be an as_token.
+        if jsonwebtoken::decode_header(token).is_err() {
+            let service = self
+                .app_service_storage
+                .get_by_token(token)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to look up application service", &e))?;
+            if let Some(service) = service {
+                return self.validate_app_service_user(&service, user_id).await;
+            }
+        }
+        self.validate_token(token).await
+    }
+
+    /// `m.login.application_service`: issues a new device and tokens for
+    /// `user`, a user ID or localpart, on behalf of the service owning
+    /// `as_token`.
+    pub async fn login_as_app_service(
+        &self,
+        as_token: &str,
+        user: &str,
+        device_id: Option<&str>,
+        initial_display_name: Option<&str>,
+    ) -> ApiResult<(User, String, String, String)> {
+        let service = self
+            .app_service_storage
+            .get_by_token(as_token)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to look up application service", &e))?
+            .ok_or_else(|| ApiError::unauthorized("Invalid application service token"))?;
+
+        let user_id = if user.starts_with('@') { user.to_string() } else { format!("@{user}:{}", self.server_name) };
+        if user_id != app_service_sender(&service, &self.server_name)
+            && !may_log_in_as(&service, &user_id, &self.server_name)
+        {
+            return Err(ApiError::forbidden(format!(
+                "Application service '{}' cannot log in as {user_id}: not in its user namespaces",
+                service.as_id
+            )));
+        }
+
+        let user = self
+            .user_storage
+            .get_user_by_id(&user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Database error", &e))?
+            .filter(|user| !user.is_deactivated)
+            .ok_or_else(|| ApiError::forbidden("Invalid username".to_string()))?;
+
+        ::tracing::info!(
+            target: "security_audit",
+            event = "app_service_login",
+            as_id = %service.as_id,
+            user_id = %user.user_id,
+            "Application service logged in as user"
+        );
+        let device_id = self.get_or_create_device_id(device_id, &user, initial_display_name).await?;
+        let access_token = self.generate_access_token(&user.user_id, &device_id, user.is_admin).await?;
+        let refresh_token = self.generate_refresh_token(&user.user_id, &device_id).await?;
+
+        Ok((user, access_token, refresh_token, device_id))
+    }
+
+    async fn validate_app_service_user(
+        &self,
+        service: &ApplicationService,
+        user_id: Option<&str>,
+    ) -> ApiResult<TokenValidation> {
+        let sender = app_service_sender(service, &self.server_name);
+        let target = user_id.unwrap_or(&sender);
+        if target != sender && !may_masquerade(service, target, &self.server_name) {
+            return Err(ApiError::forbidden(format!(
+                "Application service '{}' cannot act as {target}: not in its exclusive user namespaces",
+                service.as_id
+            )));
+        }
+
+        let user = match self
+            .user_storage
+            .get_user_by_id(target)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Database error", &e))?
+        {
+            Some(user) => user,
+            None => self.create_app_service_user(service, target).await?,
+        };
+        if user.is_deactivated {
+            return Err(ApiError::user_deactivated("User is deactivated"));
+        }
+
+        Ok((user.user_id, None, user.is_admin, user.is_shadow_banned, false))
+    }
+
+    async fn create_app_service_user(&self, service: &ApplicationService, user_id: &str) -> ApiResult<User> {
+        let localpart = user_id.trim_start_matches('@').split(':').next().unwrap_or_default();
+        let user = match self.user_storage.create_user(user_id, localpart, None, false).await {
+            Ok(user) => user,
+            // Another request for the same ghost created it first.
+            Err(e) if e.as_database_error().is_some_and(|db| db.is_unique_violation()) => self
+                .user_storage
+                .get_user_by_id(user_id)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Database error", &e))?
+                .ok_or_else(|| ApiError::internal("Application service user vanished after creation".to_string()))?,
+            Err(e) => return Err(ApiError::internal_with_log("Failed to create application service user", &e)),
+        };
+
+        self.app_service_storage
+            .register_virtual_user(&service.as_id, user_id, None, None)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to record application service user", &e))?;
+        ::tracing::info!(as_id = %service.as_id, user_id = %user_id, "Created application service user on first use");
+        Ok(user)
+    }
+}
+
+/// The sender is stored either as a full user ID or as a bare localpart.
+fn app_service_sender(service: &ApplicationService, server_name: &str) -> String {
+    if service.sender_localpart.starts_with('@') {
+        service.sender_localpart.clone()
+    } else {
+        format!("@{}:{}", service.sender_localpart, server_name)
+    }
+}
+
+fn may_masquerade(service: &ApplicationService, user_id: &str, server_name: &str) -> bool {
+    ApplicationServiceManager::is_local_user_id(user_id, server_name)
+        && ApplicationServiceManager::namespace_matches(&service.namespaces, "users", user_id, true)
+}
+
+/// Login, unlike acting as a user, is also allowed in non-exclusive
+/// namespaces: double-puppeting services claim real users they do not own.
+fn may_log_in_as(service: &ApplicationService, user_id: &str, server_name: &str) -> bool {
+    ApplicationServiceManager::is_local_user_id(user_id, server_name)
+        && ApplicationServiceManager::namespace_matches(&service.namespaces, "users", user_id, false)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn service(sender: &str) -> ApplicationService {
+        ApplicationService {
+            id: 1,
+            as_id: "bridge".to_string(),
+            url: "http://localhost:9000".to_string(),
+            as_token: "as_token".to_string(),
+            hs_token: "hs_token".to_string(),
+            sender_localpart: sender.to_string(),
+            is_enabled: true,
+            is_rate_limited: false,
+            protocols: Vec::new(),
+            namespaces: json!({
+                "users": [
+                    {"exclusive": true, "regex": "@bridge_.*:example\\.com"},
+                    {"exclusive": false, "regex": "@shared_.*:example\\.com"},
+                ]
+            }),
+            created_ts: 0,
+            updated_ts: None,
+            description: None,
+            api_key: None,
+            config: json!({}),
+        }
+    }
+
+    #[test]
+    fn sender_localpart_is_qualified_with_server_name() {
+        assert_eq!(app_service_sender(&service("bridgebot"), "example.com"), "@bridgebot:example.com");
+        assert_eq!(app_service_sender(&service("@bridgebot:example.com"), "example.com"), "@bridgebot:example.com");
+    }
+
+    #[test]
+    fn masquerade_requires_local_exclusive_namespace() {
+        let service = service("bridgebot");
+        assert!(may_masquerade(&service, "@bridge_alice:example.com", "example.com"));
+        assert!(!may_masquerade(&service, "@shared_alice:example.com", "example.com"));
+        assert!(!may_masquerade(&service, "@alice:example.com", "example.com"));
+        assert!(!may_masquerade(&service, "@bridge_alice:example.com", "other.com"));
+    }
+
+    #[test]
+    fn login_allows_non_exclusive_namespace() {
+        let service = service("bridgebot");
+        assert!(may_log_in_as(&service, "@shared_alice:example.com", "example.com"));
+        assert!(may_log_in_as(&service, "@bridge_alice:example.com", "example.com"));
+        assert!(!may_log_in_as(&service, "@alice:example.com", "example.com"));
+        assert!(!may_log_in_as(&service, "@shared_alice:other.com", "example.com"));
+    }
+}
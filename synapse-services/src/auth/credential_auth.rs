@@ -14,6 +14,16 @@ pub trait CredentialAuth: Send + Sync {
         initial_display_name: Option<&str>,
     ) -> ApiResult<(User, String, String, String)>;
 
+    /// `m.login.application_service`: log in as `user` with the `as_token`
+    /// of an application service whose user namespaces cover it.
+    async fn login_as_app_service(
+        &self,
+        as_token: &str,
+        user: &str,
+        device_id: Option<&str>,
+        initial_display_name: Option<&str>,
+    ) -> ApiResult<(User, String, String, String)>;
+
     async fn register(
         &self,
         username: &str,
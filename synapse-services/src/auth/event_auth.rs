@@ -0,0 +1,166 @@
+//! Matrix auth rules for locally created events.
+//!
+//! Events are checked against the room's current state with the same rules
+//! state resolution applies to federated events, so a client cannot create
+//! an event that other servers would reject.
+
+use std::sync::Arc;
+
+use serde_json::Value;
+use synapse_common::{current_timestamp_millis, ApiError, ApiResult};
+use synapse_federation::state_res::{auth_types_for_event, check_auth, AuthState, StateResEvent};
+use synapse_storage::event::{EventReader, StateEvent};
+
+/// Checks proposed events against the room's auth rules: membership, join
+/// rules, bans and power levels.
+#[derive(Clone)]
+pub struct EventAuthChecker {
+    event_reader: Arc<dyn EventReader>,
+}
+
+impl EventAuthChecker {
+    pub fn new(event_reader: Arc<dyn EventReader>) -> Self {
+        Self { event_reader }
+    }
+
+    /// Checks that `sender` may send the event. Rooms whose `m.room.create`
+    /// event is not in the event graph cannot be evaluated and are left to
+    /// the power level checks of [`crate::auth::RoomAuth`].
+    pub async fn check(
+        &self,
+        room_id: &str,
+        sender: &str,
+        event_type: &str,
+        state_key: Option<&str>,
+        content: &Value,
+    ) -> ApiResult<()> {
+        let Some(create) = self.state_event(room_id, "m.room.create", "").await? else {
+            return Ok(());
+        };
+        let room_version = create.content["room_version"].as_str().unwrap_or("1").to_string();
+
+        let event = StateResEvent {
+            event_id: String::new(),
+            room_id: room_id.to_string(),
+            event_type: event_type.to_string(),
+            state_key: state_key.map(str::to_string),
+            sender: sender.to_string(),
+            content: content.clone(),
+            auth_events: Vec::new(),
+            origin_server_ts: current_timestamp_millis(),
+        };
+
+        let mut auth_events = vec![create];
+        for (auth_type, auth_state_key) in auth_types_for_event(&room_version, &event) {
+            if auth_type == "m.room.create" {
+                continue;
+            }
+            if let Some(auth_event) = self.state_event(room_id, &auth_type, &auth_state_key).await? {
+                auth_events.push(auth_event);
+            }
+        }
+        let auth_state: AuthState<'_> = auth_events.iter().filter_map(|e| Some((e.key()?, e))).collect();
+
+        check_auth(&room_version, &event, &auth_state).map_err(|e| ApiError::forbidden(e.to_string()))
+    }
+
+    async fn state_event(&self, room_id: &str, event_type: &str, state_key: &str) -> ApiResult<Option<StateResEvent>> {
+        let event = self
+            .event_reader
+            .get_state_event(room_id, event_type, state_key)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load auth events", &e))?;
+        Ok(event.map(|e| to_state_res_event(e, event_type)))
+    }
+}
+
+fn to_state_res_event(event: StateEvent, event_type: &str) -> StateResEvent {
+    StateResEvent {
+        event_id: event.event_id,
+        room_id: event.room_id,
+        event_type: event.event_type.unwrap_or_else(|| event_type.to_string()),
+        state_key: Some(event.state_key.unwrap_or_default()),
+        sender: event.sender,
+        content: event.content,
+        auth_events: Vec::new(),
+        origin_server_ts: event.origin_server_ts,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use synapse_storage::event::EventWriter;
+    use synapse_storage::test_mocks::InMemoryEventStore;
+    use synapse_storage::CreateEventParams;
+
+    const ROOM_ID: &str = "!auth:localhost";
+    const ALICE: &str = "@alice:localhost";
+    const BOB: &str = "@bob:localhost";
+
+    async fn seed(store: &InMemoryEventStore, sender: &str, event_type: &str, state_key: &str, content: Value) {
+        EventWriter::create_event(
+            store,
+            CreateEventParams {
+                event_id: format!("${}:localhost", uuid::Uuid::new_v4()),
+                room_id: ROOM_ID.to_string(),
+                user_id: sender.to_string(),
+                event_type: event_type.to_string(),
+                content,
+                state_key: Some(state_key.to_string()),
+                origin_server_ts: current_timestamp_millis(),
+                redacts: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+    }
+
+    /// Alice's invite-only room with Bob joined at the default power level.
+    async fn checker() -> EventAuthChecker {
+        let store = Arc::new(InMemoryEventStore::new());
+        seed(&store, ALICE, "m.room.create", "", json!({"creator": ALICE, "room_version": "10"})).await;
+        seed(&store, ALICE, "m.room.member", ALICE, json!({"membership": "join"})).await;
+        seed(
+            &store,
+            ALICE,
+            "m.room.power_levels",
+            "",
+            json!({"users": {ALICE: 100}, "events": {"m.room.name": 50}, "invite": 50}),
+        )
+        .await;
+        seed(&store, ALICE, "m.room.join_rules", "", json!({"join_rule": "invite"})).await;
+        seed(&store, BOB, "m.room.member", BOB, json!({"membership": "join"})).await;
+        EventAuthChecker::new(store)
+    }
+
+    #[tokio::test]
+    async fn test_unprivileged_user_cannot_bypass_power_levels() {
+        let checker = checker().await;
+        let name = json!({"name": "x"});
+        let invite = json!({"membership": "invite"});
+        let ban = json!({"membership": "ban"});
+
+        assert!(checker.check(ROOM_ID, BOB, "m.room.message", None, &json!({"body": "hi"})).await.is_ok());
+        assert!(checker.check(ROOM_ID, BOB, "m.room.name", Some(""), &name).await.is_err());
+        assert!(checker.check(ROOM_ID, BOB, "m.room.member", Some("@carol:localhost"), &invite).await.is_err());
+        assert!(checker.check(ROOM_ID, BOB, "m.room.member", Some(ALICE), &ban).await.is_err());
+        assert!(checker.check(ROOM_ID, ALICE, "m.room.name", Some(""), &name).await.is_ok());
+        assert!(checker.check(ROOM_ID, ALICE, "m.room.member", Some(BOB), &ban).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_non_member_cannot_send() {
+        let checker = checker().await;
+        let result = checker.check(ROOM_ID, "@eve:localhost", "m.room.message", None, &json!({"body": "hi"})).await;
+        assert!(result.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_room_without_create_event_is_not_evaluated() {
+        let checker = EventAuthChecker::new(Arc::new(InMemoryEventStore::new()));
+        assert!(checker.check(ROOM_ID, BOB, "m.room.message", None, &json!({})).await.is_ok());
+    }
+}
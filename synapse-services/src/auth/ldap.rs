@@ -0,0 +1,220 @@
+//! LDAP password provider.
+//!
+//! Verifies credentials by binding to the directory as the user and reads
+//! the attributes used for auto-provisioning and admin group mapping.
+
+use std::time::Duration;
+
+use ldap3::{dn_escape, ldap_escape, LdapConnAsync, LdapConnSettings, LdapError, Scope, SearchEntry};
+use synapse_common::config::{LdapConfig, LdapFallback};
+
+/// `invalidCredentials` result code (RFC 4511 §4.1.9).
+const LDAP_INVALID_CREDENTIALS: u32 = 49;
+
+/// Directory entry of a successfully bound user.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LdapUser {
+    pub dn: String,
+    pub displayname: Option<String>,
+    pub is_admin: bool,
+}
+
+/// Outcome of one bind attempt.
+#[derive(Debug)]
+pub enum LdapAuthResult {
+    Authenticated(LdapUser),
+    /// The directory answered and rejected the credentials or has no such user.
+    Rejected,
+    /// No configured server could be reached or the search failed.
+    Unavailable(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct LdapAuthProvider {
+    config: LdapConfig,
+}
+
+impl LdapAuthProvider {
+    pub fn new(config: LdapConfig) -> Self {
+        Self { config }
+    }
+
+    pub fn fallback(&self) -> LdapFallback {
+        self.config.fallback
+    }
+
+    pub fn auto_provision(&self) -> bool {
+        self.config.auto_provision
+    }
+
+    /// Whether the admin flag is owned by the directory.
+    pub fn syncs_admin(&self) -> bool {
+        !self.config.admin_groups.is_empty()
+    }
+
+    /// Bind as `localpart`, trying each configured server in turn.
+    pub async fn authenticate(&self, localpart: &str, password: &str) -> LdapAuthResult {
+        // An empty password would be an unauthenticated bind, which most
+        // servers accept for any DN.
+        if password.is_empty() || localpart.is_empty() {
+            return LdapAuthResult::Rejected;
+        }
+
+        let mut last_error = String::from("no LDAP servers configured");
+        for uri in &self.config.uris {
+            match self.authenticate_against(uri, localpart, password).await {
+                Ok(Some(user)) => return LdapAuthResult::Authenticated(user),
+                Ok(None) => return LdapAuthResult::Rejected,
+                Err(e) => {
+                    ::tracing::warn!(uri = %uri, error = %e, "LDAP server unavailable");
+                    last_error = e.to_string();
+                }
+            }
+        }
+        LdapAuthResult::Unavailable(last_error)
+    }
+
+    async fn authenticate_against(
+        &self,
+        uri: &str,
+        localpart: &str,
+        password: &str,
+    ) -> Result<Option<LdapUser>, LdapError> {
+        let timeout = Duration::from_secs(self.config.timeout);
+        let settings = LdapConnSettings::new()
+            .set_conn_timeout(timeout)
+            .set_starttls(self.config.start_tls && uri.starts_with("ldap://"));
+        let (conn, mut ldap) = LdapConnAsync::with_settings(settings, uri).await?;
+        ldap3::drive!(conn);
+        ldap.with_timeout(timeout);
+
+        let attrs = self.requested_attributes();
+        let (dn, entry) = match (&self.config.bind_dn, &self.config.bind_password) {
+            (Some(bind_dn), Some(bind_password)) => {
+                ldap.simple_bind(bind_dn, bind_password).await?.success()?;
+                let (entries, _) = ldap
+                    .search(&self.config.base, Scope::Subtree, &user_filter(&self.config, localpart), &attrs)
+                    .await?
+                    .success()?;
+                // Zero or ambiguous matches are treated like a wrong password.
+                let mut entries = entries.into_iter();
+                let (Some(entry), None) = (entries.next(), entries.next()) else {
+                    let _ = ldap.unbind().await;
+                    return Ok(None);
+                };
+                let entry = SearchEntry::construct(entry);
+                (entry.dn.clone(), Some(entry))
+            }
+            _ => (direct_bind_dn(&self.config, localpart), None),
+        };
+
+        let bind = ldap.simple_bind(&dn, password).await?;
+        if bind.rc == LDAP_INVALID_CREDENTIALS {
+            let _ = ldap.unbind().await;
+            return Ok(None);
+        }
+        bind.success()?;
+
+        let entry = match entry {
+            Some(entry) => entry,
+            None => {
+                let (entries, _) = ldap.search(&dn, Scope::Base, "(objectClass=*)", &attrs).await?.success()?;
+                match entries.into_iter().next() {
+                    Some(e) => SearchEntry::construct(e),
+                    None => {
+                        let _ = ldap.unbind().await;
+                        return Ok(None);
+                    }
+                }
+            }
+        };
+        let _ = ldap.unbind().await;
+
+        let first = |name: &str| entry.attrs.get(name).and_then(|v| v.first()).cloned();
+        let groups = entry.attrs.get(&self.config.attributes.groups).map(Vec::as_slice).unwrap_or_default();
+        Ok(Some(LdapUser {
+            dn,
+            displayname: first(&self.config.attributes.name),
+            is_admin: is_admin_member(&self.config.admin_groups, groups),
+        }))
+    }
+
+    fn requested_attributes(&self) -> Vec<&str> {
+        let a = &self.config.attributes;
+        vec![a.uid.as_str(), a.name.as_str(), a.groups.as_str()]
+    }
+}
+
+/// Localpart to bind as: a bare username, or a full user ID on this server.
+pub(crate) fn ldap_localpart<'a>(username: &'a str, server_name: &str) -> Option<&'a str> {
+    match username.strip_prefix('@') {
+        Some(rest) => rest.split_once(':').filter(|(_, server)| *server == server_name).map(|(local, _)| local),
+        None if username.contains('@') || username.contains(':') => None,
+        None => Some(username),
+    }
+}
+
+/// Search filter locating `localpart`, ANDed with the configured filter.
+fn user_filter(config: &LdapConfig, localpart: &str) -> String {
+    let uid = format!("({}={})", config.attributes.uid, ldap_escape(localpart));
+    match &config.filter {
+        Some(extra) => format!("(&{uid}{extra})"),
+        None => uid,
+    }
+}
+
+fn direct_bind_dn(config: &LdapConfig, localpart: &str) -> String {
+    format!("{}={},{}", config.attributes.uid, dn_escape(localpart), config.base)
+}
+
+/// Group DNs are compared case-insensitively.
+fn is_admin_member(admin_groups: &[String], groups: &[String]) -> bool {
+    groups.iter().any(|g| admin_groups.iter().any(|a| a.eq_ignore_ascii_case(g)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LdapConfig {
+        LdapConfig {
+            enabled: true,
+            uris: vec!["ldap://localhost".into()],
+            base: "ou=people,dc=example,dc=com".into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_ldap_localpart() {
+        assert_eq!(ldap_localpart("alice", "example.com"), Some("alice"));
+        assert_eq!(ldap_localpart("@alice:example.com", "example.com"), Some("alice"));
+        assert_eq!(ldap_localpart("@alice:other.org", "example.com"), None);
+        assert_eq!(ldap_localpart("alice@example.com", "example.com"), None);
+    }
+
+    #[test]
+    fn test_user_filter_escapes_localpart() {
+        let mut config = config();
+        assert_eq!(user_filter(&config, "alice"), "(uid=alice)");
+        assert_eq!(user_filter(&config, "a*)(uid=*"), "(uid=a\\2a\\29\\28uid=\\2a)");
+
+        config.filter = Some("(objectClass=person)".into());
+        assert_eq!(user_filter(&config, "alice"), "(&(uid=alice)(objectClass=person))");
+    }
+
+    #[test]
+    fn test_direct_bind_dn_escapes_localpart() {
+        let config = config();
+        assert_eq!(direct_bind_dn(&config, "alice"), "uid=alice,ou=people,dc=example,dc=com");
+        assert_eq!(direct_bind_dn(&config, "a,b"), "uid=a\\2cb,ou=people,dc=example,dc=com");
+    }
+
+    #[test]
+    fn test_admin_group_membership() {
+        let admins = vec!["cn=Admins,ou=groups,dc=example,dc=com".to_string()];
+        assert!(is_admin_member(&admins, &["CN=admins,OU=groups,DC=example,DC=com".to_string()]));
+        assert!(!is_admin_member(&admins, &["cn=staff,ou=groups,dc=example,dc=com".to_string()]));
+        assert!(!is_admin_member(&[], &["cn=admins,ou=groups,dc=example,dc=com".to_string()]));
+    }
+}
@@ -1,7 +1,9 @@
 use super::auth_generate_token;
 use super::AuthService;
+use crate::module_service::PasswordAuthContext;
 use chrono::Utc;
 use std::sync::Arc;
+use synapse_common::config::LdapFallback;
 use synapse_common::crypto::hash_password_with_params;
 use synapse_common::*;
 use synapse_storage::User;
@@ -62,7 +64,28 @@ impl AuthService {
             None => false,
         };
 
-        let password_ok = self.verify_user_password(password, &password_hash_owned).await?;
+        let (mut verified, others_allowed) =
+            self.check_password_providers(username, password, device_id, initial_display_name).await?;
+
+        let fallback = self.ldap_fallback();
+        if verified.is_none()
+            && others_allowed
+            && matches!(fallback, Some(LdapFallback::LdapFirst | LdapFallback::LdapOnly))
+        {
+            verified = self.ldap_login(username, password).await?;
+        }
+
+        let mut password_ok = false;
+        if verified.is_none() && others_allowed && fallback != Some(LdapFallback::LdapOnly) {
+            password_ok = self.verify_user_password(password, &password_hash_owned).await?;
+            if password_ok {
+                verified = user_for_success;
+            }
+        }
+
+        if verified.is_none() && others_allowed && fallback == Some(LdapFallback::LocalFirst) {
+            verified = self.ldap_login(username, password).await?;
+        }
 
         if is_locked {
             Self::log_login_failure(username, "account_locked");
@@ -72,8 +95,8 @@ impl AuthService {
             ));
         }
 
-        let user = match user_for_success {
-            Some(u) if password_ok => u,
+        let user = match verified {
+            Some(u) => u,
             _ => {
                 if let Some(uid) = lock_user_id.as_deref() {
                     self.record_login_failure(uid).await?;
@@ -85,7 +108,7 @@ impl AuthService {
 
         self.clear_login_failures(&user.user_id).await?;
 
-        if is_legacy_hash(&password_hash_owned) {
+        if password_ok && is_legacy_hash(&password_hash_owned) {
             if let Err(e) = self.migrate_password(&user.user_id, password).await {
                 ::tracing::warn!(
                     target: "password_migration",
@@ -104,10 +127,185 @@ impl AuthService {
 
         let access_token = self.generate_access_token(&user.user_id, &device_id, user.is_admin).await?;
         let refresh_token = self.generate_refresh_token(&user.user_id, &device_id).await?;
+        self.run_logged_in_hooks(&user.user_id, &device_id).await;
 
         Ok((user, access_token, refresh_token, device_id))
     }
 
+    /// Ask the module password providers about `username`, in registration
+    /// order. Returns the local user of the first provider accepting the
+    /// password, and whether LDAP and the local password may still be tried.
+    async fn check_password_providers(
+        &self,
+        username: &str,
+        password: &str,
+        device_id: Option<&str>,
+        initial_display_name: Option<&str>,
+    ) -> ApiResult<(Option<User>, bool)> {
+        let providers = self.module_registry.read().await.password_providers().to_vec();
+        if providers.is_empty() {
+            return Ok((None, true));
+        }
+        // Third-party identifiers are left to the local lookup.
+        let user_id = match username.strip_prefix('@') {
+            Some(_) => username.to_string(),
+            None if username.contains('@') => return Ok((None, true)),
+            None => format!("@{}:{}", username.to_lowercase(), self.server_name),
+        };
+
+        let context = PasswordAuthContext {
+            user_id: user_id.clone(),
+            password: password.to_string(),
+            device_id: device_id.map(str::to_string),
+            initial_device_display_name: initial_display_name.map(str::to_string),
+        };
+        let mut others_allowed = true;
+        for provider in providers.iter().filter(|p| p.handles_user(&user_id)) {
+            match provider.check_password(&context).await {
+                Ok(output) if output.valid => {
+                    let user_id = output.user_id.unwrap_or_else(|| user_id.clone());
+                    return Ok((self.provider_login_user(provider.name(), &user_id).await?, false));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    ::tracing::warn!(provider = provider.name(), user_id = %user_id, error = %e, "Password provider failed");
+                }
+            }
+            others_allowed &= provider.allows_local_password(&user_id);
+        }
+        Ok((None, others_allowed))
+    }
+
+    /// Local account for a user a password provider accepted, registered on
+    /// first login.
+    async fn provider_login_user(&self, provider: &str, user_id: &str) -> ApiResult<Option<User>> {
+        let existing = self
+            .user_storage
+            .get_user_by_id(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        match existing {
+            Some(user) if user.is_deactivated => Ok(None),
+            Some(user) => Ok(Some(user)),
+            None => {
+                let localpart = user_id
+                    .strip_prefix('@')
+                    .and_then(|rest| rest.split_once(':'))
+                    .filter(|(_, server)| *server == self.server_name)
+                    .map(|(localpart, _)| localpart);
+                let Some(localpart) = localpart.filter(|l| self.validator.validate_username(l).is_ok()) else {
+                    ::tracing::warn!(
+                        provider,
+                        user_id,
+                        "Password provider accepted a user that cannot be registered here"
+                    );
+                    return Ok(None);
+                };
+                let user = self
+                    .user_storage
+                    .create_user(user_id, localpart, None, false)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to register provider user", &e))?;
+                ::tracing::info!(target: "security_audit", event = "provider_user_registered", provider, user_id);
+                Ok(Some(user))
+            }
+        }
+    }
+
+    async fn run_logged_in_hooks(&self, user_id: &str, device_id: &str) {
+        let providers = self.module_registry.read().await.password_providers().to_vec();
+        for provider in providers.iter().filter(|p| p.handles_user(user_id)) {
+            if let Err(e) = provider.on_logged_in(user_id, device_id).await {
+                ::tracing::warn!(provider = provider.name(), user_id, error = %e, "Password provider on_logged_in failed");
+            }
+        }
+    }
+
+    fn ldap_fallback(&self) -> Option<LdapFallback> {
+        #[cfg(feature = "ldap-auth")]
+        {
+            self.ldap.as_ref().map(|ldap| ldap.fallback())
+        }
+        #[cfg(not(feature = "ldap-auth"))]
+        {
+            None
+        }
+    }
+
+    /// Verify `username` against LDAP and return the matching local user,
+    /// provisioning it on first login. `None` means LDAP did not accept the
+    /// login; an unreachable directory is treated the same so that the
+    /// configured fallback still applies.
+    #[cfg(feature = "ldap-auth")]
+    async fn ldap_login(&self, username: &str, password: &str) -> ApiResult<Option<User>> {
+        use super::ldap::LdapAuthResult;
+
+        let Some(ldap) = &self.ldap else {
+            return Ok(None);
+        };
+        let Some(localpart) = super::ldap::ldap_localpart(username, &self.server_name) else {
+            return Ok(None);
+        };
+
+        let entry = match ldap.authenticate(localpart, password).await {
+            LdapAuthResult::Authenticated(entry) => entry,
+            LdapAuthResult::Rejected => return Ok(None),
+            LdapAuthResult::Unavailable(reason) => {
+                ::tracing::warn!(target: "security_audit", username = username, reason = %reason, "LDAP unavailable for login");
+                return Ok(None);
+            }
+        };
+
+        let localpart = localpart.to_lowercase();
+        let user_id = format!("@{}:{}", localpart, self.server_name);
+        let existing = self
+            .user_storage
+            .get_user_by_id(&user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+
+        let mut user = match existing {
+            Some(u) if u.is_deactivated => return Ok(None),
+            Some(u) => u,
+            None if !ldap.auto_provision() => return Ok(None),
+            None => {
+                if self.validator.validate_username(&localpart).is_err() {
+                    ::tracing::warn!(dn = %entry.dn, "LDAP uid is not a valid Matrix localpart; not provisioning");
+                    return Ok(None);
+                }
+                let mut user = self
+                    .user_storage
+                    .create_user(&user_id, &localpart, None, ldap.syncs_admin() && entry.is_admin)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to provision LDAP user", &e))?;
+                if let Some(name) = entry.displayname.as_deref() {
+                    self.user_storage
+                        .update_displayname(&user_id, Some(name))
+                        .await
+                        .map_err(|e| ApiError::internal_with_log("Failed to set displayname", &e))?;
+                    user.displayname = Some(name.to_string());
+                }
+                ::tracing::info!(target: "security_audit", event = "ldap_user_provisioned", user_id = %user_id, dn = %entry.dn);
+                user
+            }
+        };
+
+        if ldap.syncs_admin() && user.is_admin != entry.is_admin {
+            self.user_storage
+                .set_admin_status(&user.user_id, entry.is_admin)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to sync admin status", &e))?;
+            user.is_admin = entry.is_admin;
+        }
+
+        Ok(Some(user))
+    }
+
+    #[cfg(not(feature = "ldap-auth"))]
+    async fn ldap_login(&self, _username: &str, _password: &str) -> ApiResult<Option<User>> {
+        Ok(None)
+    }
+
     #[allow(clippy::expect_used)]
     fn dummy_password_hash() -> &'static str {
         use std::sync::OnceLock;
@@ -271,6 +469,14 @@ impl AuthService {
             return Err(ApiError::forbidden("Invalid credentials".to_string()));
         }
 
+        let (accepted, others_allowed) = self.check_password_providers(&user.user_id, password, None, None).await?;
+        if accepted.is_some_and(|accepted| accepted.user_id == user.user_id) {
+            return Ok(());
+        }
+        if !others_allowed {
+            return Err(ApiError::forbidden("Invalid credentials".to_string()));
+        }
+
         let password_hash = user.password_hash.ok_or_else(|| ApiError::forbidden("Invalid credentials".to_string()))?;
 
         let password_ok = self.verify_user_password(password, &password_hash).await?;
@@ -1,5 +1,9 @@
 mod account;
+mod appservice;
 pub mod credential_auth;
+pub mod event_auth;
+#[cfg(feature = "ldap-auth")]
+pub mod ldap;
 mod login;
 pub mod password_policy;
 mod power_levels;
@@ -10,23 +14,27 @@ mod session;
 mod tests;
 mod token;
 pub mod token_auth;
+mod token_cache;
 
 use rand::RngCore;
 use std::sync::Arc;
 use synapse_cache::*;
-use synapse_common::config::SecurityConfig;
+use synapse_common::config::{LdapConfig, SecurityConfig};
 use synapse_common::metrics::MetricsCollector;
 use synapse_common::validation::Validator;
 use synapse_common::{ApiError, ApiResult};
 use synapse_storage::*;
 
 pub use credential_auth::CredentialAuth;
+pub use event_auth::EventAuthChecker;
 pub use room_auth::RoomAuth;
 pub use token_auth::TokenAuth;
+pub use token_cache::{TokenValidation, TokenValidationCache};
 
 pub use password_policy::{PasswordPolicy, PasswordPolicyService, PasswordValidationResult};
 pub use synapse_common::claims::{Claims, ClaimsBuilder};
 
+use crate::module_service::ModuleRegistry;
 use crate::UserService;
 
 const TOKEN_CACHE_TTL_SECS: u64 = 300; // 5 min - must be short to respect revocation
@@ -44,7 +52,9 @@ pub struct AuthService {
     pub room_storage: RoomStorage,
     pub member_storage: Arc<dyn synapse_storage::membership::MemberStoreApi>,
     pub event_reader: Arc<dyn synapse_storage::event::EventReader>,
+    pub app_service_storage: Arc<dyn synapse_storage::application_service::ApplicationServiceStoreApi>,
     pub cache: Arc<CacheManager>,
+    pub token_validations: TokenValidationCache,
     pub metrics: Arc<MetricsCollector>,
     pub validator: Arc<Validator>,
     pub jwt_secret: Vec<u8>,
@@ -57,6 +67,11 @@ pub struct AuthService {
     pub allow_legacy_hashes: bool,
     pub login_failure_lockout_threshold: u32,
     pub login_lockout_duration_seconds: u64,
+    #[cfg(feature = "ldap-auth")]
+    pub ldap: Option<Arc<ldap::LdapAuthProvider>>,
+    /// Shared with `ModuleService`; its password providers are consulted on
+    /// every password login.
+    pub module_registry: Arc<tokio::sync::RwLock<ModuleRegistry>>,
 }
 
 impl AuthService {
@@ -89,7 +104,9 @@ impl AuthService {
             room_storage: RoomStorage::new(pool),
             member_storage: Arc::new(RoomMemberStorage::new(pool, &server_name_for_storage)),
             event_reader: Arc::new(EventStorage::new(pool, server_name_for_storage.clone())),
+            app_service_storage: Arc::new(synapse_storage::application_service::ApplicationServiceStorage::new(pool)),
             cache,
+            token_validations: TokenValidationCache::new(),
             metrics,
             validator: Arc::new(Validator::default()),
             jwt_secret: security.secret.as_bytes().to_vec(),
@@ -102,6 +119,27 @@ impl AuthService {
             allow_legacy_hashes: security.allow_legacy_hashes,
             login_failure_lockout_threshold: security.login_failure_lockout_threshold,
             login_lockout_duration_seconds: security.login_lockout_duration_seconds,
+            #[cfg(feature = "ldap-auth")]
+            ldap: None,
+            module_registry: Arc::new(tokio::sync::RwLock::new(ModuleRegistry::new())),
+        }
+    }
+
+    /// Attach the LDAP password provider when it is enabled in config.
+    pub fn with_ldap(self, config: &LdapConfig) -> Self {
+        if !config.is_enabled() {
+            return self;
+        }
+        #[cfg(feature = "ldap-auth")]
+        {
+            Self { ldap: Some(Arc::new(ldap::LdapAuthProvider::new(config.clone()))), ..self }
+        }
+        #[cfg(not(feature = "ldap-auth"))]
+        {
+            ::tracing::warn!(
+                "ldap.enabled is set but this build lacks the `ldap-auth` feature; LDAP login is disabled"
+            );
+            self
         }
     }
 }
@@ -201,6 +239,10 @@ impl crate::auth::TokenAuth for AuthService {
         self.validate_token(token).await
     }
 
+    async fn validate_token_as(&self, token: &str, user_id: Option<&str>) -> ApiResult<TokenValidation> {
+        self.validate_token_as(token, user_id).await
+    }
+
     async fn generate_access_token(&self, user_id: &str, device_id: &str, admin: bool) -> ApiResult<String> {
         self.generate_access_token(user_id, device_id, admin).await
     }
@@ -248,6 +290,16 @@ impl crate::auth::CredentialAuth for AuthService {
         self.login(username, password, device_id, initial_display_name).await
     }
 
+    async fn login_as_app_service(
+        &self,
+        as_token: &str,
+        user: &str,
+        device_id: Option<&str>,
+        initial_display_name: Option<&str>,
+    ) -> ApiResult<(User, String, String, String)> {
+        self.login_as_app_service(as_token, user, device_id, initial_display_name).await
+    }
+
     async fn register(
         &self,
         username: &str,
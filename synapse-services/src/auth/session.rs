@@ -23,12 +23,14 @@ impl AuthService {
         // always hit the blacklist/revoked checks in the DB. This also frees the
         // cache memory and keeps the cache consistent with the DB state.
         self.cache.delete_token(access_token).await;
+        self.token_validations.invalidate(&Self::hash_token(access_token)).await;
 
         if let Some(d_id) = device_id {
             self.token_storage
                 .delete_device_tokens(d_id)
                 .await
                 .map_err(|e| ApiError::internal_with_log("Failed to delete device tokens", &e))?;
+            self.token_validations.invalidate_user(user_id);
 
             if let Some(c) = claims.as_ref() {
                 if let Err(e) = self.refresh_token_storage.revoke_device_tokens(&c.sub, d_id, "user_logout").await {
@@ -80,6 +82,7 @@ impl AuthService {
             .delete_user_tokens(user_id)
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to delete tokens", &e))?;
+        self.token_validations.invalidate_user(user_id);
 
         self.refresh_token_storage
             .revoke_all_user_tokens(user_id, "Logout all devices")
@@ -9,7 +9,11 @@ use synapse_common::*;
 use synapse_storage::refresh_token::CreateRefreshTokenRequest;
 
 impl AuthService {
-    pub async fn validate_token(&self, token: &str) -> ApiResult<(String, Option<String>, bool, bool, bool)> {
+    pub async fn validate_token(&self, token: &str) -> ApiResult<super::TokenValidation> {
+        self.token_validations.get_or_validate(Self::hash_token(token), self.validate_token_uncached(token)).await
+    }
+
+    async fn validate_token_uncached(&self, token: &str) -> ApiResult<super::TokenValidation> {
         ::tracing::debug!(target: "token_validation", "Validating token");
 
         if self
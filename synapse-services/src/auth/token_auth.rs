@@ -6,6 +6,16 @@ use synapse_common::ApiResult;
 pub trait TokenAuth: Send + Sync {
     async fn validate_token(&self, token: &str) -> ApiResult<(String, Option<String>, bool, bool, bool)>;
 
+    /// Like `validate_token`, but an application service token acts as
+    /// `user_id` (the `user_id` query parameter) or as its sender.
+    async fn validate_token_as(
+        &self,
+        token: &str,
+        _user_id: Option<&str>,
+    ) -> ApiResult<(String, Option<String>, bool, bool, bool)> {
+        self.validate_token(token).await
+    }
+
     async fn generate_access_token(&self, user_id: &str, device_id: &str, admin: bool) -> ApiResult<String>;
 
     async fn generate_refresh_token(&self, user_id: &str, device_id: &str) -> ApiResult<String>;
@@ -0,0 +1,165 @@
+//! Short-lived cache of access-token validation outcomes.
+//!
+//! Entries are keyed by the keyed hash of the token, never the token itself.
+//! Concurrent validations of the same cold token share one load, and
+//! rejections are remembered briefly so a client retrying a bad token does
+//! not reach the database on every request. Logout drops the entry on this
+//! worker; other workers may accept a revoked token for up to
+//! [`VALID_TOKEN_TTL`].
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use moka::future::Cache;
+use moka::Expiry;
+use synapse_common::{ApiError, ApiResult};
+
+/// `(user_id, device_id, is_admin, is_shadow_banned, is_guest)`.
+pub type TokenValidation = (String, Option<String>, bool, bool, bool);
+
+const VALID_TOKEN_TTL: Duration = Duration::from_secs(5);
+const REJECTED_TOKEN_TTL: Duration = Duration::from_secs(2);
+const MAX_CACHED_TOKENS: u64 = 100_000;
+
+#[derive(Clone)]
+enum CachedValidation {
+    Valid(TokenValidation),
+    Rejected(ApiError),
+}
+
+struct ValidationExpiry;
+
+impl Expiry<String, CachedValidation> for ValidationExpiry {
+    fn expire_after_create(&self, _key: &String, value: &CachedValidation, _created_at: Instant) -> Option<Duration> {
+        Some(match value {
+            CachedValidation::Valid(_) => VALID_TOKEN_TTL,
+            CachedValidation::Rejected(_) => REJECTED_TOKEN_TTL,
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct TokenValidationCache {
+    entries: Cache<String, CachedValidation>,
+}
+
+impl Default for TokenValidationCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl TokenValidationCache {
+    pub fn new() -> Self {
+        Self {
+            entries: Cache::builder()
+                .max_capacity(MAX_CACHED_TOKENS)
+                .expire_after(ValidationExpiry)
+                .support_invalidation_closures()
+                .build(),
+        }
+    }
+
+    /// The cached outcome for `token_hash`, or the result of `validate`.
+    /// Only one `validate` runs at a time per hash; callers arriving while it
+    /// runs wait for its result. Internal errors are returned but not cached.
+    pub async fn get_or_validate<F>(&self, token_hash: String, validate: F) -> ApiResult<TokenValidation>
+    where
+        F: std::future::Future<Output = ApiResult<TokenValidation>>,
+    {
+        let outcome = self
+            .entries
+            .try_get_with(token_hash, async {
+                match validate.await {
+                    Ok(validation) => Ok(CachedValidation::Valid(validation)),
+                    Err(e) if e.is_internal() || e.is_timeout() => Err(e),
+                    Err(e) => Ok(CachedValidation::Rejected(e)),
+                }
+            })
+            .await
+            .map_err(|e: Arc<ApiError>| (*e).clone())?;
+
+        match outcome {
+            CachedValidation::Valid(validation) => Ok(validation),
+            CachedValidation::Rejected(e) => Err(e),
+        }
+    }
+
+    pub async fn invalidate(&self, token_hash: &str) {
+        self.entries.invalidate(token_hash).await;
+    }
+
+    /// Drops every cached token that validated as `user_id`.
+    pub fn invalidate_user(&self, user_id: &str) {
+        let user_id = user_id.to_string();
+        if let Err(e) = self.entries.invalidate_entries_if(
+            move |_, value| matches!(value, CachedValidation::Valid(validation) if validation.0 == user_id),
+        ) {
+            ::tracing::warn!(error = %e, "Failed to invalidate cached token validations");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn alice() -> TokenValidation {
+        ("@alice:example.com".to_string(), Some("DEVICE".to_string()), false, false, false)
+    }
+
+    #[tokio::test]
+    async fn concurrent_validations_share_one_load() {
+        let cache = TokenValidationCache::new();
+        let loads = AtomicUsize::new(0);
+        let validate = || async {
+            loads.fetch_add(1, Ordering::SeqCst);
+            tokio::time::sleep(Duration::from_millis(20)).await;
+            Ok(alice())
+        };
+
+        let (a, b) = tokio::join!(
+            cache.get_or_validate("hash".to_string(), validate()),
+            cache.get_or_validate("hash".to_string(), validate()),
+        );
+        assert_eq!(a.unwrap(), alice());
+        assert_eq!(b.unwrap(), alice());
+        assert_eq!(loads.load(Ordering::SeqCst), 1);
+    }
+
+    #[tokio::test]
+    async fn rejections_are_cached_but_internal_errors_are_not() {
+        let cache = TokenValidationCache::new();
+
+        let err =
+            cache.get_or_validate("bad".to_string(), async { Err(ApiError::unauthorized("Invalid token")) }).await;
+        assert!(err.unwrap_err().is_unauthorized());
+        let err = cache.get_or_validate("bad".to_string(), async { Ok(alice()) }).await;
+        assert!(err.unwrap_err().is_unauthorized());
+
+        let err = cache.get_or_validate("flaky".to_string(), async { Err(ApiError::internal("Database error")) }).await;
+        assert!(err.unwrap_err().is_internal());
+        assert_eq!(cache.get_or_validate("flaky".to_string(), async { Ok(alice()) }).await.unwrap(), alice());
+    }
+
+    #[tokio::test]
+    async fn invalidated_tokens_are_validated_again() {
+        let cache = TokenValidationCache::new();
+        cache.get_or_validate("hash".to_string(), async { Ok(alice()) }).await.unwrap();
+
+        cache.invalidate("hash").await;
+        let err = cache
+            .get_or_validate("hash".to_string(), async { Err(ApiError::unauthorized("Token has been revoked")) })
+            .await;
+        assert!(err.is_err());
+
+        cache.get_or_validate("other".to_string(), async { Ok(alice()) }).await.unwrap();
+        cache.invalidate_user("@alice:example.com");
+        cache.entries.run_pending_tasks().await;
+        let err = cache
+            .get_or_validate("other".to_string(), async { Err(ApiError::unauthorized("Token has been revoked")) })
+            .await;
+        assert!(err.is_err());
+    }
+}
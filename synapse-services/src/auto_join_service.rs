@@ -0,0 +1,173 @@
+//! Post-registration auto-join (`server.auto_join_rooms`).
+//!
+//! Every entry is a room ID or alias. Local aliases that do not resolve are
+//! created when `autocreate_auto_join_rooms` is set. The work runs on a
+//! detached task so a slow or failing room never delays registration; errors
+//! are logged per room and the remaining rooms are still processed.
+
+use std::sync::Arc;
+
+use synapse_common::config::ServerConfig;
+
+use crate::common::error::{ApiError, ApiResult};
+use crate::room::{CreateRoomConfig, RoomServiceApi};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum AutoJoinTarget<'a> {
+    RoomId(&'a str),
+    LocalAlias { alias: &'a str, localpart: &'a str },
+    RemoteAlias(&'a str),
+}
+
+impl<'a> AutoJoinTarget<'a> {
+    fn parse(entry: &'a str, server_name: &str) -> Option<Self> {
+        if entry.starts_with('!') {
+            return Some(Self::RoomId(entry));
+        }
+        let (localpart, server) = entry.strip_prefix('#')?.split_once(':')?;
+        if localpart.is_empty() {
+            return None;
+        }
+        if server == server_name {
+            Some(Self::LocalAlias { alias: entry, localpart })
+        } else {
+            Some(Self::RemoteAlias(entry))
+        }
+    }
+}
+
+pub struct AutoJoinService {
+    room_service: Arc<dyn RoomServiceApi>,
+    server_name: String,
+    rooms: Vec<String>,
+    autocreate: bool,
+    /// Full user ID built from `auto_join_mxid_localpart`.
+    room_owner: Option<String>,
+}
+
+impl AutoJoinService {
+    pub fn new(room_service: Arc<dyn RoomServiceApi>, server: &ServerConfig) -> Self {
+        let server_name = server.get_server_name().to_string();
+        let room_owner = server
+            .auto_join_mxid_localpart
+            .as_deref()
+            .filter(|localpart| !localpart.is_empty())
+            .map(|localpart| format!("@{localpart}:{server_name}"));
+        Self {
+            room_service,
+            server_name,
+            rooms: server.auto_join_rooms.clone(),
+            autocreate: server.autocreate_auto_join_rooms,
+            room_owner,
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        !self.rooms.is_empty()
+    }
+
+    /// Join `user_id` to the configured rooms in the background.
+    pub fn spawn_for_new_user(self: &Arc<Self>, user_id: String) {
+        if !self.is_enabled() {
+            return;
+        }
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            service.join_configured_rooms(&user_id).await;
+        });
+    }
+
+    pub async fn join_configured_rooms(&self, user_id: &str) {
+        for entry in &self.rooms {
+            if let Err(e) = self.join_one(entry, user_id).await {
+                ::tracing::warn!(
+                    user_id = %user_id,
+                    room = %entry,
+                    error = %e,
+                    "Failed to auto-join new user to room"
+                );
+            }
+        }
+    }
+
+    async fn join_one(&self, entry: &str, user_id: &str) -> ApiResult<()> {
+        let target = AutoJoinTarget::parse(entry, &self.server_name)
+            .ok_or_else(|| ApiError::invalid_param(format!("Invalid auto_join_rooms entry: {entry}")))?;
+
+        let room_id = match target {
+            AutoJoinTarget::RoomId(room_id) => room_id.to_string(),
+            AutoJoinTarget::RemoteAlias(alias) => {
+                return Err(ApiError::bad_request(format!("Remote alias {alias} cannot be resolved locally")));
+            }
+            AutoJoinTarget::LocalAlias { alias, localpart } => {
+                match self.room_service.state().get_room_by_alias(alias).await? {
+                    Some(room_id) => room_id,
+                    None if self.autocreate => {
+                        if self.create_room(localpart, user_id).await? {
+                            return Ok(());
+                        }
+                        self.room_service
+                            .state()
+                            .get_room_by_alias(alias)
+                            .await?
+                            .ok_or_else(|| ApiError::not_found(format!("Alias {alias} was not created")))?
+                    }
+                    None => return Err(ApiError::not_found(format!("Alias {alias} does not exist"))),
+                }
+            }
+        };
+
+        self.join_or_invite(&room_id, user_id).await
+    }
+
+    /// Create the room for a missing local alias. Returns `true` when the new
+    /// user created it, and so is already joined.
+    async fn create_room(&self, localpart: &str, user_id: &str) -> ApiResult<bool> {
+        let creator = self.room_owner.as_deref().unwrap_or(user_id);
+        let config = CreateRoomConfig {
+            room_alias_name: Some(localpart.to_string()),
+            preset: Some("public_chat".to_string()),
+            ..Default::default()
+        };
+        let response = self.room_service.lifecycle().create_room(creator, config).await?;
+        ::tracing::info!(
+            room_id = ?response.get("room_id"),
+            creator = %creator,
+            "Created auto-join room"
+        );
+        Ok(creator == user_id)
+    }
+
+    /// Join directly; if the room's join rules refuse that, have the room
+    /// owner invite the user first.
+    async fn join_or_invite(&self, room_id: &str, user_id: &str) -> ApiResult<()> {
+        let membership = self.room_service.membership();
+        match membership.join_room_with_via_servers(room_id, user_id, &[]).await {
+            Err(e) if e.is_forbidden() => {
+                let Some(owner) = self.room_owner.as_deref() else {
+                    return Err(e);
+                };
+                membership.invite_user(room_id, owner, user_id).await?;
+                membership.join_room(room_id, user_id).await
+            }
+            result => result,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_classifies_entries() {
+        assert_eq!(AutoJoinTarget::parse("!abc:hs", "hs"), Some(AutoJoinTarget::RoomId("!abc:hs")));
+        assert_eq!(
+            AutoJoinTarget::parse("#welcome:hs", "hs"),
+            Some(AutoJoinTarget::LocalAlias { alias: "#welcome:hs", localpart: "welcome" })
+        );
+        assert_eq!(AutoJoinTarget::parse("#welcome:other", "hs"), Some(AutoJoinTarget::RemoteAlias("#welcome:other")));
+        assert_eq!(AutoJoinTarget::parse("#:hs", "hs"), None);
+        assert_eq!(AutoJoinTarget::parse("welcome", "hs"), None);
+    }
+}
@@ -1,6 +1,10 @@
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 use synapse_common::ApiError;
 use synapse_storage::background_update::*;
+use synapse_storage::online_migration::{
+    find_online_migration, OnlineMigrationExecutor, OnlineMigrationProgress, ONLINE_MIGRATION_JOB_TYPE,
+};
 use tracing::{info, instrument, warn};
 use uuid::Uuid;
 
@@ -14,6 +18,8 @@ pub struct BackgroundUpdateService {
     lock_max_retries: u32,
     /// Maximum interval between lock retries in ms (from WorkerConfig).
     lock_max_retry_interval_ms: u64,
+    /// Runs `online_migration` jobs; `None` leaves them queued.
+    online_migrations: Option<Arc<dyn OnlineMigrationExecutor>>,
 }
 
 impl BackgroundUpdateService {
@@ -22,9 +28,15 @@ impl BackgroundUpdateService {
             storage,
             lock_max_retries: DEFAULT_LOCK_MAX_RETRIES,
             lock_max_retry_interval_ms: DEFAULT_LOCK_MAX_RETRY_INTERVAL_MS,
+            online_migrations: None,
         }
     }
 
+    pub fn with_online_migrations(mut self, executor: Arc<dyn OnlineMigrationExecutor>) -> Self {
+        self.online_migrations = Some(executor);
+        self
+    }
+
     /// Configure lock retry parameters from WorkerConfig.
     ///
     /// Aligned with Synapse v1.153.0 which lowered
@@ -330,6 +342,81 @@ impl BackgroundUpdateService {
         Ok(locked)
     }
 
+    /// Run batches of queued `online_migration` jobs until `budget` is spent
+    /// or none are left. A running job is resumed before a pending one is
+    /// started. Returns the number of batches executed.
+    #[instrument(skip(self))]
+    pub async fn run_online_migrations(&self, budget: Duration) -> Result<u32, ApiError> {
+        let Some(executor) = self.online_migrations.clone() else {
+            return Ok(0);
+        };
+        let deadline = Instant::now() + budget;
+        let mut batches = 0;
+
+        while Instant::now() < deadline {
+            let Some(update) = self.next_online_migration().await? else {
+                break;
+            };
+            let Some(migration) = find_online_migration(&update.job_name) else {
+                break;
+            };
+
+            let mut total_items = None;
+            if update.status == "pending" {
+                self.start_update(&update.job_name).await?;
+                total_items =
+                    executor.estimate_rows(migration).await.ok().map(|rows| i32::try_from(rows).unwrap_or(i32::MAX));
+            }
+
+            let progress: OnlineMigrationProgress = serde_json::from_value(update.progress.clone()).unwrap_or_default();
+            let batch =
+                match executor.run_batch(migration, progress.last_key, i64::from(update.batch_size.max(1))).await {
+                    Ok(batch) => batch,
+                    Err(e) => {
+                        warn!(job_name = %update.job_name, error = %e, "Online migration batch failed");
+                        self.fail_update(&update.job_name, &e.to_string()).await?;
+                        continue;
+                    }
+                };
+            batches += 1;
+
+            let progress = OnlineMigrationProgress {
+                last_key: batch.last_key.or(progress.last_key),
+                rows_updated: progress.rows_updated + batch.rows_updated,
+            };
+            self.storage
+                .save_batch_progress(
+                    &update.job_name,
+                    serde_json::to_value(&progress).unwrap_or_default(),
+                    i32::try_from(batch.rows_scanned).unwrap_or(i32::MAX),
+                    total_items,
+                )
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to save online migration progress", &e))?;
+
+            if batch.done {
+                info!(job_name = %update.job_name, rows_updated = progress.rows_updated, "Online migration finished");
+                self.complete_update(&update.job_name).await?;
+            } else if update.sleep_ms > 0 {
+                tokio::time::sleep(Duration::from_millis(update.sleep_ms.unsigned_abs().into())).await;
+            }
+        }
+
+        Ok(batches)
+    }
+
+    async fn next_online_migration(&self) -> Result<Option<BackgroundUpdate>, ApiError> {
+        let is_online_migration = |update: &BackgroundUpdate| {
+            update.job_type == ONLINE_MIGRATION_JOB_TYPE && find_online_migration(&update.job_name).is_some()
+        };
+        if let Some(running) = self.get_running_updates().await?.into_iter().find(is_online_migration) {
+            return Ok(Some(running));
+        }
+        let mut pending: Vec<_> = self.get_pending_updates().await?.into_iter().filter(is_online_migration).collect();
+        pending.sort_by_key(|update| update.created_ts);
+        Ok(pending.into_iter().next())
+    }
+
     pub async fn get_next_pending_update(&self) -> Result<Option<BackgroundUpdate>, ApiError> {
         let pending = self.get_pending_updates().await?;
 
@@ -667,4 +754,89 @@ mod tests {
         svc.create_update(create_request("b")).await.unwrap();
         assert_eq!(svc.count_all().await.unwrap(), 2);
     }
+
+    // ── online migrations ─────────────────────────────────────────
+
+    /// Pretends the table holds `rows` keys 1..=rows, every one needing an update.
+    struct FakeExecutor {
+        rows: i64,
+    }
+
+    #[async_trait::async_trait]
+    impl OnlineMigrationExecutor for FakeExecutor {
+        async fn run_batch(
+            &self,
+            _migration: &synapse_storage::online_migration::OnlineMigration,
+            after_key: Option<i64>,
+            batch_size: i64,
+        ) -> Result<synapse_storage::online_migration::OnlineMigrationBatch, sqlx::Error> {
+            let start = after_key.unwrap_or(0);
+            let end = (start + batch_size).min(self.rows);
+            let scanned = end - start;
+            Ok(synapse_storage::online_migration::OnlineMigrationBatch {
+                rows_scanned: scanned,
+                rows_updated: scanned,
+                last_key: (scanned > 0).then_some(end),
+                done: scanned < batch_size,
+            })
+        }
+
+        async fn estimate_rows(
+            &self,
+            _migration: &synapse_storage::online_migration::OnlineMigration,
+        ) -> Result<i64, sqlx::Error> {
+            Ok(self.rows)
+        }
+    }
+
+    fn online_migration_request(batch_size: i32) -> CreateBackgroundUpdateRequest {
+        CreateBackgroundUpdateRequest {
+            job_type: ONLINE_MIGRATION_JOB_TYPE.to_string(),
+            batch_size: Some(batch_size),
+            sleep_ms: Some(0),
+            ..create_request("events_populate_redacts")
+        }
+    }
+
+    #[tokio::test]
+    async fn run_online_migrations_is_noop_without_executor() {
+        let svc = test_service();
+        svc.create_update(online_migration_request(10)).await.unwrap();
+        assert_eq!(svc.run_online_migrations(Duration::from_secs(1)).await.unwrap(), 0);
+        assert_eq!(svc.get_update("events_populate_redacts").await.unwrap().unwrap().status, "pending");
+    }
+
+    #[tokio::test]
+    async fn run_online_migrations_batches_until_complete() {
+        let svc = test_service().with_online_migrations(Arc::new(FakeExecutor { rows: 25 }));
+        svc.create_update(online_migration_request(10)).await.unwrap();
+        svc.create_update(create_request("unrelated")).await.unwrap();
+
+        assert_eq!(svc.run_online_migrations(Duration::from_secs(5)).await.unwrap(), 3);
+
+        let update = svc.get_update("events_populate_redacts").await.unwrap().unwrap();
+        assert_eq!(update.status, "completed");
+        assert_eq!(update.processed_items, 25);
+        assert_eq!(update.total_items, 25);
+        let progress: OnlineMigrationProgress = serde_json::from_value(update.progress).unwrap();
+        assert_eq!(progress, OnlineMigrationProgress { last_key: Some(25), rows_updated: 25 });
+        assert_eq!(svc.get_update("unrelated").await.unwrap().unwrap().status, "pending");
+    }
+
+    #[tokio::test]
+    async fn run_online_migrations_resumes_from_saved_progress() {
+        let svc = test_service().with_online_migrations(Arc::new(FakeExecutor { rows: 25 }));
+        svc.create_update(online_migration_request(10)).await.unwrap();
+        svc.start_update("events_populate_redacts").await.unwrap();
+        let resume = OnlineMigrationProgress { last_key: Some(20), rows_updated: 20 };
+        svc.storage
+            .save_batch_progress("events_populate_redacts", serde_json::to_value(&resume).unwrap(), 20, Some(25))
+            .await
+            .unwrap();
+
+        assert_eq!(svc.run_online_migrations(Duration::from_secs(5)).await.unwrap(), 1);
+        let update = svc.get_update("events_populate_redacts").await.unwrap().unwrap();
+        assert_eq!(update.status, "completed");
+        assert_eq!(update.processed_items, 25);
+    }
 }
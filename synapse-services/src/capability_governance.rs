@@ -225,6 +225,20 @@ impl CapabilityGovernance {
         unstable_features.insert("org.matrix.msc3983".to_string(), json!(self.msc3983_capability().enabled()));
         unstable_features.insert("org.matrix.msc3814".to_string(), json!(self.msc3814_capability().enabled()));
         unstable_features.insert("org.matrix.msc4143".to_string(), json!(self.msc4143_capability().enabled()));
+        // The experimental streaming transports are only declared while they
+        // are switched on, so the default surface stays unchanged.
+        if self.config.experimental.sync_websocket_enabled {
+            unstable_features.insert("org.synapse_rust.sync_ws".to_string(), json!(true));
+        }
+        if self.config.experimental.events_sse_enabled {
+            unstable_features.insert("org.synapse_rust.events_sse".to_string(), json!(true));
+        }
+        if self.config.experimental.msc4108_enabled {
+            unstable_features.insert("org.matrix.msc4108".to_string(), json!(true));
+        }
+        if self.config.experimental.msc4140_max_delay_ms.is_some() {
+            unstable_features.insert("org.matrix.msc4140".to_string(), json!(true));
+        }
         // Private `io.hula.*` extensions are intentionally NOT declared in
         // `/versions.unstable_features` — that surface is unauthenticated and
         // consumed by stock Matrix clients which do not understand the
@@ -245,11 +259,18 @@ impl CapabilityGovernance {
         CapabilityFlag::route_surface(self.manifest_has_route("GET", "/_matrix/client/v3/rooms/{room_id}/summary"))
     }
 
-    /// MSC3266 (Room summary batch) capability is driven by the route surface:
-    /// the `org.matrix.msc3266` unstable feature is declared only when the
-    /// `POST /_synapse/room_summary/v1/summaries/batch` endpoint is registered.
+    /// MSC3266 (Room summary) capability is driven by the route surface:
+    /// the `org.matrix.msc3266` unstable feature is declared when either the
+    /// `GET /_matrix/client/unstable/im.nheko.summary/rooms/{room_id_or_alias}/summary`
+    /// endpoint or the `POST /_synapse/room_summary/v1/summaries/batch`
+    /// endpoint is registered.
     fn msc3266_capability(&self) -> CapabilityFlag {
-        CapabilityFlag::route_surface(self.manifest_has_route("POST", "/_synapse/room_summary/v1/summaries/batch"))
+        CapabilityFlag::route_surface(
+            self.manifest_has_route(
+                "GET",
+                "/_matrix/client/unstable/im.nheko.summary/rooms/{room_id_or_alias}/summary",
+            ) || self.manifest_has_route("POST", "/_synapse/room_summary/v1/summaries/batch"),
+        )
     }
 
     /// MSC3814 (Dehydrated device) capability is driven by the route surface:
@@ -637,6 +658,42 @@ mod tests {
         assert!(!unstable.contains_key("io.hula.friends"), "private io.hula.friends must not leak to /versions");
     }
 
+    #[test]
+    fn test_versions_declares_msc4108_only_when_enabled() {
+        let body = governance_with_default_config().build_client_versions();
+        assert!(body["unstable_features"].get("org.matrix.msc4108").is_none());
+
+        let mut config = Config::default();
+        config.experimental.msc4108_enabled = true;
+        let body = CapabilityGovernance::new(&config, vec![]).build_client_versions();
+        assert_eq!(body["unstable_features"]["org.matrix.msc4108"], true);
+    }
+
+    #[test]
+    fn test_versions_declares_msc4140_only_when_enabled() {
+        let body = governance_with_default_config().build_client_versions();
+        assert!(body["unstable_features"].get("org.matrix.msc4140").is_none());
+
+        let mut config = Config::default();
+        config.experimental.msc4140_max_delay_ms = Some(60_000);
+        let body = CapabilityGovernance::new(&config, vec![]).build_client_versions();
+        assert_eq!(body["unstable_features"]["org.matrix.msc4140"], true);
+    }
+
+    #[test]
+    fn test_versions_declares_streaming_transports_only_when_enabled() {
+        let body = governance_with_default_config().build_client_versions();
+        assert!(body["unstable_features"].get("org.synapse_rust.sync_ws").is_none());
+        assert!(body["unstable_features"].get("org.synapse_rust.events_sse").is_none());
+
+        let mut config = Config::default();
+        config.experimental.sync_websocket_enabled = true;
+        config.experimental.events_sse_enabled = true;
+        let body = CapabilityGovernance::new(&config, vec![]).build_client_versions();
+        assert_eq!(body["unstable_features"]["org.synapse_rust.sync_ws"], true);
+        assert_eq!(body["unstable_features"]["org.synapse_rust.events_sse"], true);
+    }
+
     // -----------------------------------------------------------------------
     // SSO provider tests
     // -----------------------------------------------------------------------
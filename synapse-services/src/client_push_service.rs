@@ -31,6 +31,18 @@ pub struct UpsertPushRuleRequest {
     pub actions: Value,
 }
 
+const PUSH_RULE_KINDS: [&str; 5] = ["override", "content", "room", "sender", "underride"];
+
+fn validate_rule_path(scope: &str, kind: &str) -> Result<(), ApiError> {
+    if scope != "global" {
+        return Err(ApiError::invalid_param(format!("Unsupported push rules scope: {scope}")));
+    }
+    if !PUSH_RULE_KINDS.contains(&kind) {
+        return Err(ApiError::invalid_param(format!("Unknown push rule kind: {kind}")));
+    }
+    Ok(())
+}
+
 pub struct ClientPushService {
     account_data_storage: Arc<dyn AccountDataStoreApi>,
     push_storage: Arc<dyn PushStoreApi>,
@@ -113,19 +125,32 @@ impl ClientPushService {
             .iter()
             .map(|row| {
                 let actions = row.try_get::<Option<Value>, _>("actions").ok().flatten().unwrap_or_else(|| json!([]));
-                json!({
+                let mut rule = json!({
                     "rule_id": row.get::<String, _>("rule_id"),
                     "default": row.get::<bool, _>("is_default"),
                     "enabled": row.get::<bool, _>("is_enabled"),
-                    "pattern": row.try_get::<Option<String>, _>("pattern").ok().flatten(),
-                    "conditions": row.try_get::<Option<Value>, _>("conditions").ok().flatten(),
                     "actions": actions
-                })
+                });
+                if let Some(pattern) = row.try_get::<Option<String>, _>("pattern").ok().flatten() {
+                    rule["pattern"] = Value::String(pattern);
+                } else {
+                    let conditions = row.try_get::<Option<Value>, _>("conditions").ok().flatten();
+                    rule["conditions"] = conditions.unwrap_or_else(|| json!([]));
+                }
+                rule
             })
             .collect())
     }
 
     pub async fn upsert_push_rule(&self, request: UpsertPushRuleRequest) -> Result<i64, ApiError> {
+        validate_rule_path(&request.scope, &request.kind)?;
+        if request.rule_id.starts_with('.') {
+            return Err(ApiError::invalid_param("Rule IDs starting with '.' are reserved for server-default rules"));
+        }
+        if request.kind == "content" && request.pattern.as_deref().is_none_or(str::is_empty) {
+            return Err(ApiError::missing_param("Content push rules require a pattern"));
+        }
+
         let now = current_timestamp_millis();
         self.push_storage
             .upsert_push_rule(
@@ -166,11 +191,16 @@ impl ClientPushService {
         rule_id: &str,
         actions: &Value,
     ) -> Result<(), ApiError> {
-        self.push_storage
+        validate_rule_path(scope, kind)?;
+        let updated = self
+            .push_storage
             .update_push_rule_actions(user_id, scope, kind, rule_id, actions)
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to update push rule actions", &e))?;
-        Ok(())
+        if updated > 0 {
+            return Ok(());
+        }
+        self.update_default_rule(user_id, kind, rule_id, "actions", actions.clone()).await
     }
 
     pub async fn get_push_rule_enabled(
@@ -194,10 +224,79 @@ impl ClientPushService {
         rule_id: &str,
         enabled: bool,
     ) -> Result<(), ApiError> {
-        self.push_storage
+        validate_rule_path(scope, kind)?;
+        let updated = self
+            .push_storage
             .set_push_rule_enabled(user_id, scope, kind, rule_id, enabled)
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to update push rule enabled", &e))?;
+        if updated > 0 {
+            return Ok(());
+        }
+        self.update_default_rule(user_id, kind, rule_id, "enabled", Value::Bool(enabled)).await
+    }
+
+    /// Server-default rules (`.m.rule.*`) have no `push_rules` row; the
+    /// user's `enabled`/`actions` overrides are kept in `m.push_rules`
+    /// account data, where the default rule set is merged on read.
+    async fn update_default_rule(
+        &self,
+        user_id: &str,
+        kind: &str,
+        rule_id: &str,
+        field: &str,
+        value: Value,
+    ) -> Result<(), ApiError> {
+        if !rule_id.starts_with('.') {
+            return Err(ApiError::not_found("Push rule not found".to_string()));
+        }
+
+        let mut content = self.get_push_rules_content(user_id).await?.unwrap_or_else(|| json!({"global": {}}));
+        let Some(global) =
+            content.as_object_mut().and_then(|m| m.entry("global").or_insert_with(|| json!({})).as_object_mut())
+        else {
+            return Err(ApiError::internal("Stored push rules are malformed"));
+        };
+        let Some(rules) = global.entry(kind.to_string()).or_insert_with(|| json!([])).as_array_mut() else {
+            return Err(ApiError::internal("Stored push rules are malformed"));
+        };
+
+        match rules.iter_mut().find(|r| r.get("rule_id").and_then(Value::as_str) == Some(rule_id)) {
+            Some(rule) => rule[field] = value,
+            None => {
+                let mut rule = json!({"rule_id": rule_id, "default": true});
+                rule[field] = value;
+                rules.push(rule);
+            }
+        }
+
+        self.account_data_storage
+            .upsert_account_data(user_id, "m.push_rules", content)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to save push rules", &e))
+    }
+
+    /// Adds the user's rules from the `push_rules` table to a `m.push_rules`
+    /// rule set, replacing any stored copy with the same rule ID.
+    pub async fn merge_custom_push_rules(&self, user_id: &str, content: &mut Value) -> Result<(), ApiError> {
+        for kind in PUSH_RULE_KINDS {
+            let custom = self.get_user_push_rules(user_id, "global", kind).await?;
+            if custom.is_empty() {
+                continue;
+            }
+            let Some(rules) = content
+                .as_object_mut()
+                .and_then(|m| m.entry("global").or_insert_with(|| json!({})).as_object_mut())
+                .and_then(|g| g.entry(kind.to_string()).or_insert_with(|| json!([])).as_array_mut())
+            else {
+                continue;
+            };
+            for rule in custom {
+                let rule_id = rule.get("rule_id").cloned();
+                rules.retain(|r| r.get("rule_id") != rule_id.as_ref());
+                rules.push(rule);
+            }
+        }
         Ok(())
     }
 
@@ -232,3 +331,55 @@ impl ClientPushService {
         Ok(result.is_some())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synapse_storage::test_mocks::{InMemoryAccountDataStore, InMemoryPushStore};
+
+    const USER: &str = "@alice:localhost";
+
+    fn service() -> ClientPushService {
+        ClientPushService::new(Arc::new(InMemoryAccountDataStore::new()), Arc::new(InMemoryPushStore::new()))
+    }
+
+    #[tokio::test]
+    async fn test_default_rule_overrides_are_kept_in_account_data() {
+        let service = service();
+        service.set_push_rule_enabled(USER, "global", "override", ".m.rule.master", true).await.unwrap();
+        service.set_push_rule_actions(USER, "global", "override", ".m.rule.master", &json!(["notify"])).await.unwrap();
+
+        let content = service.get_push_rules_content(USER).await.unwrap().unwrap();
+        let rules = content["global"]["override"].as_array().unwrap();
+        assert_eq!(rules.len(), 1);
+        assert_eq!(rules[0]["rule_id"], ".m.rule.master");
+        assert_eq!(rules[0]["default"], true);
+        assert_eq!(rules[0]["enabled"], true);
+        assert_eq!(rules[0]["actions"], json!(["notify"]));
+    }
+
+    #[tokio::test]
+    async fn test_updating_unknown_custom_rule_is_not_found() {
+        let result = service().set_push_rule_enabled(USER, "global", "content", "missing", false).await;
+        assert!(result.is_err());
+        assert!(service().get_push_rules_content(USER).await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_content_rules_require_a_pattern() {
+        let request = |rule_id: &str, pattern: Option<&str>| UpsertPushRuleRequest {
+            user_id: USER.to_string(),
+            scope: "global".to_string(),
+            kind: "content".to_string(),
+            rule_id: rule_id.to_string(),
+            pattern: pattern.map(str::to_string),
+            conditions: None,
+            actions: json!(["notify", {"set_tweak": "sound", "value": "default"}]),
+        };
+
+        let service = service();
+        assert!(service.upsert_push_rule(request("standup", None)).await.is_err());
+        assert!(service.upsert_push_rule(request(".m.rule.contains_user_name", Some("alice"))).await.is_err());
+        assert!(service.upsert_push_rule(request("standup", Some("standup"))).await.is_ok());
+    }
+}
@@ -4,10 +4,9 @@ use synapse_cache::*;
 use synapse_common::config::Config;
 use synapse_common::metrics::MetricsCollector;
 
+use crate::worker::topology_validator::should_run_global_maintenance;
 #[cfg(feature = "burn-after-read")]
-use crate::worker::topology_validator::{
-    current_instance_worker_type, global_maintenance_owner, should_run_global_maintenance,
-};
+use crate::worker::topology_validator::{current_instance_worker_type, global_maintenance_owner};
 
 use std::sync::Arc;
 use synapse_common::server_metrics::ServerMetrics;
@@ -74,6 +73,7 @@ struct StoragePhase {
     invite_blocklist_storage: Arc<dyn InviteBlocklistStoreApi>,
     sticky_event_storage: Arc<dyn StickyEventStoreApi>,
     user_service: Arc<UserService>,
+    module_registry: Arc<tokio::sync::RwLock<crate::module_service::ModuleRegistry>>,
 }
 
 /// Phase 3 output: domain assemblies + media service.
@@ -100,7 +100,7 @@ struct DomainPhase {
 //              e2ee → admin → federation → member_storage → event_broadcaster
 //              → rooms → sso → core → media
 //   Phase 4: Extensions + Final  — extensions, account services, container assembly
-//   Phase 5: Side effects        — burn-after-read processor startup
+//   Phase 5: Side effects        — burn-after-read processor and policy list startup
 //
 // The 4 services RoomService depends on (EventBroadcaster,
 // ApplicationServiceManager, KeyRotationManager, FederationClient) are built
@@ -138,7 +138,9 @@ impl ServiceContainer {
         // Phase 4: Build extensions + account services + assemble container
         let container = Self::build_container(&infra_phase, &storage_phase, domain_phase).await;
 
-        // Phase 5: Post-construction side effects (burn-after-read processor)
+        // Phase 5: Post-construction side effects (event bus subscribers, policy lists, burn-after-read processor)
+        Self::start_event_bus_subscribers(&container);
+        Self::start_policy_list_enforcement(&container, &infra_phase.infra.config);
         Self::start_burn_after_read_processor(&container, &infra_phase.infra.config).await;
 
         container
@@ -181,14 +183,17 @@ impl ServiceContainer {
         // Auth — must be initialized first; downstream services depend on it.
         // Produce all four trait-object lenses from the same concrete AuthService
         // so consumers can depend on the narrowest trait they need.
-        let auth_concrete: std::sync::Arc<AuthService> = std::sync::Arc::new(AuthService::new_with_lifetime(
-            pool,
-            cache.clone(),
-            metrics.clone(),
-            &config.security,
-            &config.server.name,
-            config.access_token_lifetime_seconds(),
-        ));
+        let auth_concrete: std::sync::Arc<AuthService> = std::sync::Arc::new(
+            AuthService::new_with_lifetime(
+                pool,
+                cache.clone(),
+                metrics.clone(),
+                &config.security,
+                &config.server.name,
+                config.access_token_lifetime_seconds(),
+            )
+            .with_ldap(&config.ldap),
+        );
         let token_auth: Arc<dyn TokenAuth> = auth_concrete.clone();
         let credential_auth: Arc<dyn CredentialAuth> = auth_concrete.clone();
         let room_auth: Arc<dyn RoomAuth> = auth_concrete.clone();
@@ -221,6 +226,7 @@ impl ServiceContainer {
             invite_blocklist_storage,
             sticky_event_storage,
             user_service,
+            module_registry: auth_concrete.module_registry.clone(),
         }
     }
 
@@ -238,6 +244,7 @@ impl ServiceContainer {
             pool,
             cache,
             &storage.user_storage,
+            &infra.server_metrics,
             config.server.megolm_encryption_key_path.as_deref(),
         )
         .await;
@@ -253,6 +260,7 @@ impl ServiceContainer {
             &storage.credential_auth,
             &storage.room_auth,
             &storage.user_storage,
+            &storage.module_registry,
             &infra.shutdown_token,
         )
         .await;
@@ -272,11 +280,28 @@ impl ServiceContainer {
                 .with_pool(pool.as_ref().clone())
                 .with_membership_storage(member_storage.clone());
             broadcaster
-                .start_batch_sender(server_name_for_storage, config.federation.event_broadcast_batch_size, 100)
+                .start_batch_sender(
+                    server_name_for_storage,
+                    config.federation.event_broadcast_batch_size,
+                    100,
+                    config.federation.event_broadcast_queue_size,
+                )
                 .await;
             Arc::new(broadcaster)
         };
 
+        // Content filter — pre-send hook; consults the admin module registry and audits there
+        let content_filter = config.content_filter.enabled.then(|| {
+            Arc::new(
+                crate::content_filter_service::ContentFilterService::new(config.content_filter.clone())
+                    .with_module_hooks(admin.modules.module_service.clone(), admin.modules.module_storage.clone()),
+            )
+        });
+
+        // Persisted-event bus and the sync notifier it feeds — shared by rooms (publisher) and core
+        let event_bus = crate::event_bus::EventBus::default();
+        let event_notifier = crate::event_notifier::EventNotifier::new();
+
         // Rooms — receives member_storage + the 4 injected services directly
         let rooms = wiring::RoomSyncServices::new(
             &infra.infra,
@@ -291,6 +316,9 @@ impl ServiceContainer {
             federation.federation_client.clone(),
             storage.sticky_event_storage.clone(),
             storage.user_service.clone(),
+            content_filter,
+            &event_bus,
+            &event_notifier,
         )
         .await;
 
@@ -307,6 +335,8 @@ impl ServiceContainer {
             &storage.user_storage,
             &infra.server_metrics,
             event_broadcaster,
+            event_notifier,
+            event_bus,
         )
         .await;
 
@@ -342,6 +372,7 @@ impl ServiceContainer {
             threepid_storage: storage.threepid_storage.clone(),
             presence_storage: &storage.presence_storage,
             federation: &federation,
+            federation_blacklist_service: admin.federation.federation_blacklist_service.clone(),
             media_service: &core.media_service,
             media_domain_service: &media_domain_service,
             ui_auth_session_timeout: infra.ui_auth_session_timeout,
@@ -395,6 +426,67 @@ impl ServiceContainer {
     // Phase 5: Post-construction side effects
     // -------------------------------------------------------------------------
 
+    /// Subscribes the sync notifier to the persisted-event bus so long-polling
+    /// `/sync` requests wake as soon as an event lands in one of their rooms.
+    /// Membership events also wake the target user, whose room list changes.
+    fn start_event_bus_subscribers(container: &Self) {
+        let notifier = container.core.event_notifier.clone();
+        container.core.event_bus.spawn_subscriber("sync_notifier", container.shutdown_token.clone(), move |event| {
+            notifier.notify_room(&event.room_id);
+            if event.event_type == "m.room.member" {
+                if let Some(state_key) = event.state_key.as_deref() {
+                    notifier.notify_user(state_key);
+                }
+            }
+        });
+    }
+
+    /// Keeps policy list rules applied on the global maintenance owner: a
+    /// full resync at startup and every `sync_interval_secs`, another when a
+    /// rule event lands in a policy room, and a check of each membership
+    /// change in a protected room.
+    fn start_policy_list_enforcement(container: &Self, config: &Config) {
+        let service = container.extensions.policy_list_service.clone();
+        if !service.is_active() || !should_run_global_maintenance(&config.worker) {
+            return;
+        }
+
+        let subscriber = service.clone();
+        container.core.event_bus.spawn_subscriber("policy_lists", container.shutdown_token.clone(), move |event| {
+            if subscriber.is_rule_event(&event.room_id, &event.event_type) {
+                let service = subscriber.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = service.sync().await {
+                        ::tracing::warn!(error = %e, "Policy list sync failed");
+                    }
+                });
+            } else if subscriber.is_protected_membership(&event.room_id, &event.event_type) {
+                if let Some(user_id) = event.state_key.clone() {
+                    let service = subscriber.clone();
+                    let room_id = event.room_id.clone();
+                    tokio::spawn(async move { service.enforce_member(&room_id, &user_id).await });
+                }
+            }
+        });
+
+        let shutdown = container.shutdown_token.clone();
+        tokio::spawn(async move {
+            let mut interval_timer =
+                tokio::time::interval(std::time::Duration::from_secs(service.sync_interval_secs().max(1)));
+            interval_timer.set_missed_tick_behavior(tokio::time::MissedTickBehavior::Skip);
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    _ = interval_timer.tick() => {
+                        if let Err(e) = service.sync().await {
+                            ::tracing::warn!(error = %e, "Policy list sync failed");
+                        }
+                    }
+                }
+            }
+        });
+    }
+
     /// Starts the burn-after-read processor if this worker instance is
     /// designated as the global maintenance owner and the feature is enabled.
     #[cfg(feature = "burn-after-read")]
@@ -0,0 +1,289 @@
+//! Pre-send content filtering.
+//!
+//! Messages are checked against the configured banned patterns (server-wide
+//! and per room) and, optionally, the spam checkers registered through the
+//! module API before they are persisted. Pattern hits are written to the
+//! spam-check audit table under the `content_filter` checker name, next to
+//! the results recorded by module spam checkers.
+
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use regex::{Regex, RegexBuilder};
+use serde_json::Value;
+use synapse_common::config::{ContentFilterAction, ContentFilterConfig};
+use synapse_common::error::ApiError;
+use synapse_storage::module::{CreateSpamCheckRequest, ModuleStoreApi};
+use tracing::{info, warn};
+
+use crate::module_service::{ModuleService, SpamCheckContext, SpamCheckResultType};
+
+const CHECKER_NAME: &str = "content_filter";
+
+/// Outcome of running the pre-send filter over an event.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ContentFilterDecision {
+    /// Persist the event unchanged.
+    Allow,
+    /// Persist the event with this content instead of the original.
+    Redact { content: Value },
+    /// Refuse the send.
+    Reject { reason: String },
+    /// Pretend the send succeeded but persist nothing.
+    ShadowBan,
+}
+
+pub struct ContentFilterService {
+    config: ContentFilterConfig,
+    server_patterns: Vec<Regex>,
+    room_patterns: HashMap<String, Vec<Regex>>,
+    module_service: Option<Arc<ModuleService>>,
+    audit_storage: Option<Arc<dyn ModuleStoreApi>>,
+}
+
+impl ContentFilterService {
+    /// Compile the configured patterns. Patterns that fail to compile are
+    /// logged and skipped rather than taking the whole filter down.
+    pub fn new(config: ContentFilterConfig) -> Self {
+        let server_patterns = compile_patterns(&config.banned_patterns);
+        let room_patterns = config
+            .room_patterns
+            .iter()
+            .map(|(room_id, patterns)| (room_id.clone(), compile_patterns(patterns)))
+            .collect();
+        Self { config, server_patterns, room_patterns, module_service: None, audit_storage: None }
+    }
+
+    /// Route checks through the module system: registered spam checkers are
+    /// consulted after the pattern filter and pattern hits are audited in
+    /// `audit_storage`.
+    pub fn with_module_hooks(
+        mut self,
+        module_service: Arc<ModuleService>,
+        audit_storage: Arc<dyn ModuleStoreApi>,
+    ) -> Self {
+        self.module_service = Some(module_service);
+        self.audit_storage = Some(audit_storage);
+        self
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    fn patterns_for_room<'a>(&'a self, room_id: &str) -> impl Iterator<Item = &'a Regex> {
+        let server =
+            if self.config.exempt_rooms.iter().any(|r| r == room_id) { &[][..] } else { &self.server_patterns };
+        server.iter().chain(self.room_patterns.get(room_id).into_iter().flatten())
+    }
+
+    /// First banned pattern that matches any string in `content`.
+    pub fn find_match(&self, room_id: &str, content: &Value) -> Option<&str> {
+        let mut strings = Vec::new();
+        collect_strings(content, &mut strings);
+        self.patterns_for_room(room_id)
+            .find(|pattern| strings.iter().any(|s| pattern.is_match(s)))
+            .map(|pattern| pattern.as_str())
+    }
+
+    /// Copy of `content` with every banned-pattern match replaced by `*`.
+    pub fn mask_content(&self, room_id: &str, content: &Value) -> Value {
+        let patterns: Vec<&Regex> = self.patterns_for_room(room_id).collect();
+        let mut masked = content.clone();
+        mask_strings(&mut masked, &patterns);
+        masked
+    }
+
+    /// Run the filter for an event about to be sent.
+    pub async fn check_event(&self, context: &SpamCheckContext) -> Result<ContentFilterDecision, ApiError> {
+        if !self.config.applies_to(&context.event_type) {
+            return Ok(ContentFilterDecision::Allow);
+        }
+
+        if let Some(pattern) = self.find_match(&context.room_id, &context.content) {
+            let (decision, action_taken) = match self.config.action {
+                ContentFilterAction::Reject => (
+                    ContentFilterDecision::Reject { reason: "Message contains banned content".to_string() },
+                    "rejected",
+                ),
+                ContentFilterAction::Redact => (
+                    ContentFilterDecision::Redact { content: self.mask_content(&context.room_id, &context.content) },
+                    "redacted",
+                ),
+            };
+            info!(
+                event_id = %context.event_id,
+                room_id = %context.room_id,
+                sender = %context.sender,
+                action = action_taken,
+                "Content filter matched banned pattern"
+            );
+            self.record_audit(context, pattern, action_taken).await;
+            return Ok(decision);
+        }
+
+        if !self.config.consult_spam_checkers {
+            return Ok(ContentFilterDecision::Allow);
+        }
+        let Some(module_service) = &self.module_service else {
+            return Ok(ContentFilterDecision::Allow);
+        };
+
+        let output = module_service.check_spam(context).await?;
+        Ok(match output.result {
+            SpamCheckResultType::Allow => ContentFilterDecision::Allow,
+            SpamCheckResultType::Block => ContentFilterDecision::Reject {
+                reason: output.reason.unwrap_or_else(|| "Message rejected by spam checker".to_string()),
+            },
+            SpamCheckResultType::ShadowBan => ContentFilterDecision::ShadowBan,
+        })
+    }
+
+    async fn record_audit(&self, context: &SpamCheckContext, pattern: &str, action_taken: &str) {
+        let Some(storage) = &self.audit_storage else {
+            return;
+        };
+        if let Err(e) = storage
+            .create_spam_check_result(CreateSpamCheckRequest {
+                event_id: context.event_id.clone(),
+                room_id: context.room_id.clone(),
+                sender: context.sender.clone(),
+                event_type: context.event_type.clone(),
+                content: context.content.clone(),
+                result: SpamCheckResultType::Block.as_str().to_string(),
+                score: Some(100),
+                reason: Some(format!("Matched banned pattern: {pattern}")),
+                checker_module: CHECKER_NAME.to_string(),
+                action_taken: Some(action_taken.to_string()),
+            })
+            .await
+        {
+            warn!(error = %e, event_id = %context.event_id, "Failed to record content filter audit entry");
+        }
+    }
+}
+
+fn compile_patterns(patterns: &[String]) -> Vec<Regex> {
+    patterns
+        .iter()
+        .filter_map(|pattern| match RegexBuilder::new(pattern).case_insensitive(true).build() {
+            Ok(regex) => Some(regex),
+            Err(e) => {
+                warn!(pattern = %pattern, error = %e, "Ignoring invalid content filter pattern");
+                None
+            }
+        })
+        .collect()
+}
+
+fn collect_strings<'a>(value: &'a Value, out: &mut Vec<&'a str>) {
+    match value {
+        Value::String(s) => out.push(s),
+        Value::Array(items) => items.iter().for_each(|v| collect_strings(v, out)),
+        Value::Object(map) => map.values().for_each(|v| collect_strings(v, out)),
+        _ => {}
+    }
+}
+
+fn mask_strings(value: &mut Value, patterns: &[&Regex]) {
+    match value {
+        Value::String(s) => {
+            for pattern in patterns {
+                if pattern.is_match(s) {
+                    *s = pattern
+                        .replace_all(s, |caps: &regex::Captures| "*".repeat(caps[0].chars().count()))
+                        .into_owned();
+                }
+            }
+        }
+        Value::Array(items) => items.iter_mut().for_each(|v| mask_strings(v, patterns)),
+        Value::Object(map) => map.values_mut().for_each(|v| mask_strings(v, patterns)),
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn config(action: ContentFilterAction) -> ContentFilterConfig {
+        ContentFilterConfig {
+            enabled: true,
+            action,
+            banned_patterns: vec!["bad\\s*word".to_string(), "(unclosed".to_string()],
+            room_patterns: HashMap::from([("!strict:example.com".to_string(), vec!["heck".to_string()])]),
+            exempt_rooms: vec!["!lenient:example.com".to_string()],
+            ..Default::default()
+        }
+    }
+
+    fn context(room_id: &str, body: &str) -> SpamCheckContext {
+        SpamCheckContext {
+            event_id: "$ev:example.com".to_string(),
+            room_id: room_id.to_string(),
+            sender: "@alice:example.com".to_string(),
+            event_type: "m.room.message".to_string(),
+            content: json!({"msgtype": "m.text", "body": body}),
+        }
+    }
+
+    #[test]
+    fn test_find_match_is_case_insensitive_and_skips_invalid_patterns() {
+        let service = ContentFilterService::new(config(ContentFilterAction::Reject));
+        assert_eq!(service.find_match("!room:example.com", &json!({"body": "a BAD  Word here"})), Some("bad\\s*word"));
+        assert_eq!(service.find_match("!room:example.com", &json!({"body": "(unclosed"})), None);
+    }
+
+    #[test]
+    fn test_room_patterns_and_exemptions() {
+        let service = ContentFilterService::new(config(ContentFilterAction::Reject));
+        assert!(service.find_match("!room:example.com", &json!({"body": "heck"})).is_none());
+        assert!(service.find_match("!strict:example.com", &json!({"body": "heck"})).is_some());
+        assert!(service.find_match("!lenient:example.com", &json!({"body": "badword"})).is_none());
+    }
+
+    #[test]
+    fn test_mask_content_masks_nested_strings() {
+        let service = ContentFilterService::new(config(ContentFilterAction::Redact));
+        let masked = service.mask_content(
+            "!strict:example.com",
+            &json!({"body": "oh heck, badword", "m.new_content": {"body": "HECK"}, "n": 1}),
+        );
+        assert_eq!(masked, json!({"body": "oh ****, *******", "m.new_content": {"body": "****"}, "n": 1}));
+    }
+
+    #[tokio::test]
+    async fn test_check_event_applies_configured_action() {
+        let reject = ContentFilterService::new(config(ContentFilterAction::Reject));
+        assert!(matches!(
+            reject.check_event(&context("!room:example.com", "badword")).await.unwrap(),
+            ContentFilterDecision::Reject { .. }
+        ));
+        assert_eq!(
+            reject.check_event(&context("!room:example.com", "fine")).await.unwrap(),
+            ContentFilterDecision::Allow
+        );
+
+        let redact = ContentFilterService::new(config(ContentFilterAction::Redact));
+        assert_eq!(
+            redact.check_event(&context("!room:example.com", "badword")).await.unwrap(),
+            ContentFilterDecision::Redact { content: json!({"msgtype": "m.text", "body": "*******"}) }
+        );
+    }
+
+    #[tokio::test]
+    async fn test_check_event_ignores_disabled_filter_and_other_event_types() {
+        let disabled =
+            ContentFilterService::new(ContentFilterConfig { enabled: false, ..config(ContentFilterAction::Reject) });
+        assert_eq!(
+            disabled.check_event(&context("!room:example.com", "badword")).await.unwrap(),
+            ContentFilterDecision::Allow
+        );
+
+        let service = ContentFilterService::new(config(ContentFilterAction::Reject));
+        let mut reaction = context("!room:example.com", "badword");
+        reaction.event_type = "m.reaction".to_string();
+        assert_eq!(service.check_event(&reaction).await.unwrap(), ContentFilterDecision::Allow);
+    }
+}
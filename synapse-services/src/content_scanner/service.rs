@@ -10,7 +10,7 @@ pub struct ContentScanner {
 
 impl ContentScanner {
     pub fn new(config: ContentScannerConfig) -> Self {
-        Self { config, http_client: reqwest::Client::new() }
+        Self { config, http_client: synapse_common::outbound_http::client() }
     }
 
     pub fn is_enabled(&self) -> bool {
@@ -0,0 +1,286 @@
+//! Delayed events (MSC4140, `experimental.msc4140_max_delay_ms`).
+//!
+//! A client sends a message or state event with a delay and gets a delay ID
+//! back instead of an event ID. Until the delay runs out it can restart the
+//! delay, cancel the event or send it at once; the server loop sends due
+//! events through [`DelayedEventService::send_due_events`]. The event is
+//! authorised again when it is sent, since the sender may have lost the
+//! right to send it in the meantime.
+
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+use synapse_common::{current_timestamp_millis, generate_event_id, random_string, ApiError, ApiResult};
+use synapse_storage::delayed_event::{DelayedEvent, DelayedEventStoreApi, NewDelayedEvent};
+use synapse_storage::CreateEventParams;
+
+use crate::room::RoomServiceApi;
+
+/// How long a claimed event is held before another worker may send it.
+const SEND_LEASE_MS: i64 = 60_000;
+/// Due events sent per pass of the server loop.
+const SEND_BATCH_SIZE: i64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DelayedEventAction {
+    Restart,
+    Cancel,
+    Send,
+}
+
+impl DelayedEventAction {
+    pub fn parse(action: &str) -> ApiResult<Self> {
+        match action {
+            "restart" => Ok(Self::Restart),
+            "cancel" => Ok(Self::Cancel),
+            "send" => Ok(Self::Send),
+            other => Err(ApiError::invalid_param(format!("Unknown delayed event action: {other}"))),
+        }
+    }
+}
+
+/// An event to send once `delay_ms` has passed.
+#[derive(Debug, Clone)]
+pub struct DelayedEventRequest {
+    pub room_id: String,
+    pub user_id: String,
+    pub device_id: String,
+    pub event_type: String,
+    pub state_key: Option<String>,
+    pub content: Value,
+    pub delay_ms: u64,
+}
+
+pub struct DelayedEventService {
+    storage: Arc<dyn DelayedEventStoreApi>,
+    room_service: Arc<dyn RoomServiceApi>,
+    server_name: String,
+    max_delay_ms: Option<u64>,
+}
+
+impl DelayedEventService {
+    pub fn new(
+        storage: Arc<dyn DelayedEventStoreApi>,
+        room_service: Arc<dyn RoomServiceApi>,
+        server_name: String,
+        max_delay_ms: Option<u64>,
+    ) -> Self {
+        Self { storage, room_service, server_name, max_delay_ms }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.max_delay_ms.is_some()
+    }
+
+    /// Stores the event and returns its delay ID. The caller has already
+    /// checked that the sender may send it now.
+    pub async fn schedule(&self, request: DelayedEventRequest) -> ApiResult<String> {
+        let delay_ms = check_delay(request.delay_ms, self.max_delay_ms)?;
+        let event = NewDelayedEvent {
+            delay_id: format!("syd_{}", random_string(20)),
+            room_id: request.room_id,
+            user_id: request.user_id,
+            device_id: request.device_id,
+            event_type: request.event_type,
+            state_key: request.state_key,
+            content: request.content,
+            delay_ms,
+        };
+        let stored = self
+            .storage
+            .insert_delayed_event(&event, current_timestamp_millis())
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to schedule delayed event", &e))?;
+        Ok(stored.delay_id)
+    }
+
+    /// The user's delayed events that have not been sent yet.
+    pub async fn list(&self, user_id: &str) -> ApiResult<Vec<Value>> {
+        let events = self
+            .storage
+            .get_pending_delayed_events(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load delayed events", &e))?;
+        Ok(events.iter().map(delayed_event_json).collect())
+    }
+
+    pub async fn update(&self, user_id: &str, delay_id: &str, action: DelayedEventAction) -> ApiResult<()> {
+        let updated = match action {
+            DelayedEventAction::Restart => {
+                self.storage.restart_delayed_event(user_id, delay_id, current_timestamp_millis()).await
+            }
+            DelayedEventAction::Cancel => self.storage.cancel_delayed_event(user_id, delay_id).await,
+            DelayedEventAction::Send => {
+                let lease_until = current_timestamp_millis() + SEND_LEASE_MS;
+                let event = self
+                    .storage
+                    .take_delayed_event(user_id, delay_id, lease_until)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to load delayed event", &e))?
+                    .ok_or_else(|| ApiError::not_found("Delayed event not found".to_string()))?;
+                return self.send(&event).await.map(|_| ());
+            }
+        }
+        .map_err(|e| ApiError::internal_with_log("Failed to update delayed event", &e))?;
+
+        if updated == 0 {
+            return Err(ApiError::not_found("Delayed event not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Cancels delayed state events that a newly sent state event replaced.
+    pub async fn cancel_overwritten_state(&self, room_id: &str, event_type: &str, state_key: &str) {
+        if !self.is_enabled() {
+            return;
+        }
+        if let Err(e) = self.storage.cancel_delayed_state_events(room_id, event_type, state_key).await {
+            ::tracing::warn!(room_id, event_type, "Failed to cancel overwritten delayed state events: {e}");
+        }
+    }
+
+    /// Sends the events whose delay has run out and returns how many were
+    /// sent. Events that can no longer be sent are marked failed.
+    pub async fn send_due_events(&self) -> ApiResult<usize> {
+        let now = current_timestamp_millis();
+        let due = self
+            .storage
+            .claim_due_delayed_events(now, now + SEND_LEASE_MS, SEND_BATCH_SIZE)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to claim delayed events", &e))?;
+
+        let mut sent = 0;
+        for event in &due {
+            if self.send(event).await.is_ok() {
+                sent += 1;
+            }
+        }
+        Ok(sent)
+    }
+
+    async fn send(&self, event: &DelayedEvent) -> ApiResult<String> {
+        let result = self.send_event(event).await;
+        let marked = match &result {
+            Ok(event_id) => self.storage.mark_delayed_event_sent(event.id, event_id).await,
+            Err(error) => {
+                ::tracing::warn!(delay_id = %event.delay_id, room_id = %event.room_id, "Delayed event not sent: {error}");
+                self.storage.mark_delayed_event_failed(event.id, &error.to_string()).await
+            }
+        };
+        if let Err(e) = marked {
+            ::tracing::warn!(delay_id = %event.delay_id, "Failed to record delayed event outcome: {e}");
+        }
+        result
+    }
+
+    async fn send_event(&self, event: &DelayedEvent) -> ApiResult<String> {
+        let messaging = self.room_service.messaging();
+        let Some(state_key) = event.state_key.as_deref() else {
+            let result =
+                messaging.send_message(&event.room_id, &event.user_id, &event.event_type, &event.content).await?;
+            return result["event_id"]
+                .as_str()
+                .map(str::to_string)
+                .ok_or_else(|| ApiError::internal("Sent event has no event ID".to_string()));
+        };
+
+        messaging
+            .event_auth()
+            .check(&event.room_id, &event.user_id, &event.event_type, Some(state_key), &event.content)
+            .await?;
+        let event_id = generate_event_id(&self.server_name);
+        messaging
+            .create_event(
+                CreateEventParams {
+                    event_id: event_id.clone(),
+                    room_id: event.room_id.clone(),
+                    user_id: event.user_id.clone(),
+                    event_type: event.event_type.clone(),
+                    content: event.content.clone(),
+                    state_key: Some(state_key.to_string()),
+                    origin_server_ts: current_timestamp_millis(),
+                    redacts: None,
+                },
+                None,
+            )
+            .await?;
+        self.cancel_overwritten_state(&event.room_id, &event.event_type, state_key).await;
+        Ok(event_id)
+    }
+}
+
+fn check_delay(delay_ms: u64, max_delay_ms: Option<u64>) -> ApiResult<i64> {
+    let Some(max_delay_ms) = max_delay_ms else {
+        return Err(ApiError::unrecognized("Delayed events are not enabled".to_string()));
+    };
+    if delay_ms > max_delay_ms {
+        return Err(ApiError::invalid_param(format!("The maximum delay is {max_delay_ms}ms")));
+    }
+    i64::try_from(delay_ms).map_err(|_| ApiError::invalid_param("Delay is too long".to_string()))
+}
+
+fn delayed_event_json(event: &DelayedEvent) -> Value {
+    let mut body = json!({
+        "delay_id": event.delay_id,
+        "room_id": event.room_id,
+        "type": event.event_type,
+        "delay": event.delay_ms,
+        "running_since": event.running_since(),
+        "content": event.content,
+    });
+    if let Some(state_key) = &event.state_key {
+        body["state_key"] = json!(state_key);
+    }
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn delayed_event(state_key: Option<&str>) -> DelayedEvent {
+        DelayedEvent {
+            id: 1,
+            delay_id: "syd_abc".to_string(),
+            room_id: "!room:localhost".to_string(),
+            user_id: "@alice:localhost".to_string(),
+            device_id: "DEVICE".to_string(),
+            event_type: "m.room.topic".to_string(),
+            state_key: state_key.map(str::to_string),
+            content: json!({"topic": "later"}),
+            delay_ms: 5_000,
+            scheduled_ts: 15_000,
+            created_ts: 1_000,
+            status: "pending".to_string(),
+            event_id: None,
+            last_error: None,
+        }
+    }
+
+    #[test]
+    fn test_delay_must_be_enabled_and_within_the_maximum() {
+        assert!(check_delay(1_000, None).is_err());
+        assert_eq!(check_delay(1_000, Some(1_000)).ok(), Some(1_000));
+        assert!(check_delay(1_001, Some(1_000)).is_err());
+        assert!(check_delay(u64::MAX, Some(u64::MAX)).is_err());
+    }
+
+    #[test]
+    fn test_parse_action() {
+        assert_eq!(DelayedEventAction::parse("restart").ok(), Some(DelayedEventAction::Restart));
+        assert_eq!(DelayedEventAction::parse("cancel").ok(), Some(DelayedEventAction::Cancel));
+        assert_eq!(DelayedEventAction::parse("send").ok(), Some(DelayedEventAction::Send));
+        assert!(DelayedEventAction::parse("refresh").is_err());
+    }
+
+    #[test]
+    fn test_json_reports_when_the_current_delay_started() {
+        let body = delayed_event_json(&delayed_event(Some("")));
+        assert_eq!(body["running_since"], 10_000);
+        assert_eq!(body["delay"], 5_000);
+        assert_eq!(body["state_key"], "");
+
+        let body = delayed_event_json(&delayed_event(None));
+        assert!(body.get("state_key").is_none());
+    }
+}
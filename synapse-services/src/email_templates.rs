@@ -0,0 +1,281 @@
+//! Subjects and bodies of outgoing email, rendered from built-in English
+//! templates or per-language overrides under `smtp.templates.template_dir`
+//! (see [`EmailTemplatesConfig`]).
+
+use std::collections::HashMap;
+use std::path::Path;
+
+use synapse_common::config::{accept_language_tags, EmailTemplatesConfig};
+
+/// The emails the server sends.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum EmailTemplateKind {
+    /// Confirms an address being registered or added to an account.
+    /// Variables: `token`, `sid`, `email`, `expires_in_minutes`.
+    Verification,
+    /// Confirms a password reset. Same variables as `Verification`.
+    PasswordReset,
+    /// Sent after registration. Variables: `user_id`, `displayname`.
+    Welcome,
+    /// Unread notification summary. Variables: `user_id`,
+    /// `unread_count`, `summary`.
+    Notification,
+    /// A new device signed in. Variables: `user_id`, `device_id`,
+    /// `device_name`, `ip`.
+    NewDevice,
+}
+
+impl EmailTemplateKind {
+    pub const ALL: [Self; 5] =
+        [Self::Verification, Self::PasswordReset, Self::Welcome, Self::Notification, Self::NewDevice];
+
+    /// File stem of the template under `template_dir`.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Verification => "verification",
+            Self::PasswordReset => "password_reset",
+            Self::Welcome => "welcome",
+            Self::Notification => "notification",
+            Self::NewDevice => "new_device",
+        }
+    }
+
+    fn builtin(&self) -> (&'static str, &'static str) {
+        match self {
+            Self::Verification => (
+                "Verify your email address for {{app_name}}",
+                "Hello,\n\n\
+                 Use this code to confirm {{email}} on {{server_name}}:\n\n\
+                 {{token}}\n\n\
+                 The code expires in {{expires_in_minutes}} minutes. If you did not ask for it, you can \
+                 ignore this email.\n\n\
+                 {{app_name}}\n",
+            ),
+            Self::PasswordReset => (
+                "Reset your {{app_name}} password",
+                "Hello,\n\n\
+                 Someone asked to reset the password of the account using {{email}} on {{server_name}}. \
+                 Use this code to confirm:\n\n\
+                 {{token}}\n\n\
+                 The code expires in {{expires_in_minutes}} minutes. If this was not you, ignore this email; \
+                 your password stays the same.\n\n\
+                 {{app_name}}\n",
+            ),
+            Self::Welcome => (
+                "Welcome to {{app_name}}",
+                "Hello {{displayname}},\n\n\
+                 Your account {{user_id}} on {{server_name}} is ready.\n\n\
+                 {{app_name}}\n",
+            ),
+            Self::Notification => (
+                "You have {{unread_count}} unread notifications on {{app_name}}",
+                "Hello,\n\n\
+                 {{user_id}} has {{unread_count}} unread notifications on {{server_name}}.\n\n\
+                 {{summary}}\n\n\
+                 {{app_name}}\n",
+            ),
+            Self::NewDevice => (
+                "New sign-in to your {{app_name}} account",
+                "Hello,\n\n\
+                 A new device, {{device_name}} ({{device_id}}), signed in to {{user_id}} from {{ip}}.\n\n\
+                 If this was not you, change your password and sign out the device.\n\n\
+                 {{app_name}}\n",
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub body: String,
+}
+
+/// Override read from `template_dir`. A missing `.subject` file keeps the
+/// subject of the next fallback.
+#[derive(Debug, Clone)]
+struct TemplateOverride {
+    subject: Option<String>,
+    body: String,
+}
+
+pub struct EmailTemplates {
+    config: EmailTemplatesConfig,
+    server_name: String,
+    public_baseurl: String,
+    /// Keyed by lowercased locale; `""` holds the language-neutral files.
+    overrides: HashMap<(String, EmailTemplateKind), TemplateOverride>,
+}
+
+impl EmailTemplates {
+    /// Built-in templates only; `config.template_dir` is not read.
+    pub fn builtin(config: EmailTemplatesConfig, server_name: &str, public_baseurl: &str) -> Self {
+        Self {
+            config,
+            server_name: server_name.to_string(),
+            public_baseurl: public_baseurl.to_string(),
+            overrides: HashMap::new(),
+        }
+    }
+
+    /// Reads the overrides in `config.template_dir`, if set. Call at startup:
+    /// the directory is not watched.
+    pub fn load(config: EmailTemplatesConfig, server_name: &str, public_baseurl: &str) -> std::io::Result<Self> {
+        let mut templates = Self::builtin(config, server_name, public_baseurl);
+        let Some(dir) = templates.config.template_dir.clone() else {
+            return Ok(templates);
+        };
+
+        templates.read_overrides(&dir, "")?;
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            if entry.file_type()?.is_dir() {
+                let locale = entry.file_name().to_string_lossy().to_ascii_lowercase();
+                templates.read_overrides(&entry.path(), &locale)?;
+            }
+        }
+        Ok(templates)
+    }
+
+    fn read_overrides(&mut self, dir: &Path, locale: &str) -> std::io::Result<()> {
+        for kind in EmailTemplateKind::ALL {
+            let body = match std::fs::read_to_string(dir.join(format!("{}.txt", kind.name()))) {
+                Ok(body) => body,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+                Err(e) => return Err(e),
+            };
+            let subject = match std::fs::read_to_string(dir.join(format!("{}.subject", kind.name()))) {
+                Ok(subject) => Some(subject.trim().to_string()),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+                Err(e) => return Err(e),
+            };
+            self.overrides.insert((locale.to_string(), kind), TemplateOverride { subject, body });
+        }
+        Ok(())
+    }
+
+    /// Renders `kind` in the best language for `accept_language`, with
+    /// `variables` substituted alongside the branding placeholders.
+    pub fn render(
+        &self,
+        kind: EmailTemplateKind,
+        accept_language: Option<&str>,
+        variables: &[(&str, &str)],
+    ) -> RenderedEmail {
+        let mut locales = Vec::new();
+        for tag in accept_language_tags(accept_language) {
+            let primary = tag.split('-').next().unwrap_or_default().to_string();
+            locales.push(tag);
+            locales.push(primary);
+        }
+        locales.push(self.config.default_locale.to_ascii_lowercase());
+        locales.push(String::new());
+
+        let (builtin_subject, builtin_body) = kind.builtin();
+        let mut subject = None;
+        let mut body = None;
+        for locale in &locales {
+            if let Some(template) = self.overrides.get(&(locale.clone(), kind)) {
+                body = body.or(Some(template.body.as_str()));
+                subject = subject.or(template.subject.as_deref());
+                if subject.is_some() {
+                    break;
+                }
+            }
+        }
+
+        let support_email = self.config.support_email.as_deref().unwrap_or_default();
+        let mut values: HashMap<&str, &str> = HashMap::from([
+            ("app_name", self.config.app_name.as_str()),
+            ("server_name", self.server_name.as_str()),
+            ("public_baseurl", self.public_baseurl.as_str()),
+            ("support_email", support_email),
+        ]);
+        values.extend(self.config.branding.iter().map(|(key, value)| (key.as_str(), value.as_str())));
+        values.extend(variables.iter().copied());
+
+        RenderedEmail {
+            subject: render_placeholders(subject.unwrap_or(builtin_subject), &values),
+            body: render_placeholders(body.unwrap_or(builtin_body), &values),
+        }
+    }
+}
+
+/// Replaces `{{name}}` (spaces inside the braces allowed) with its value.
+/// Unknown names are left in place so a typo shows up in the sent email.
+fn render_placeholders(template: &str, values: &HashMap<&str, &str>) -> String {
+    let mut rendered = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let Some(end) = after.find("}}") else {
+            rest = &rest[start..];
+            break;
+        };
+        match values.get(after[..end].trim()) {
+            Some(value) => rendered.push_str(value),
+            None => rendered.push_str(&rest[start..start + end + 4]),
+        }
+        rest = &after[end + 2..];
+    }
+    rendered.push_str(rest);
+    rendered
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn templates_in(dir: &Path) -> EmailTemplates {
+        let config = EmailTemplatesConfig {
+            template_dir: Some(dir.to_path_buf()),
+            app_name: "Acme Chat".to_string(),
+            branding: HashMap::from([("footer".to_string(), "Acme Inc.".to_string())]),
+            ..EmailTemplatesConfig::default()
+        };
+        EmailTemplates::load(config, "example.org", "https://example.org").expect("templates should load")
+    }
+
+    #[test]
+    fn builtin_templates_substitute_branding_and_variables() {
+        let templates = EmailTemplates::builtin(EmailTemplatesConfig::default(), "example.org", "https://example.org");
+        let email = templates.render(
+            EmailTemplateKind::Verification,
+            None,
+            &[("token", "123456"), ("email", "alice@example.com"), ("expires_in_minutes", "60")],
+        );
+        assert_eq!(email.subject, "Verify your email address for Matrix");
+        assert!(email.body.contains("confirm alice@example.com on example.org"));
+        assert!(email.body.contains("123456"));
+        assert!(!email.body.contains("{{"));
+    }
+
+    #[test]
+    fn overrides_are_picked_by_language_with_fallbacks() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("welcome.txt"), "Hi {{ displayname }} - {{footer}}").unwrap();
+        std::fs::create_dir(dir.path().join("de")).unwrap();
+        std::fs::write(dir.path().join("de/welcome.subject"), "Willkommen bei {{app_name}}\n").unwrap();
+        std::fs::write(dir.path().join("de/welcome.txt"), "Hallo {{displayname}}").unwrap();
+        let templates = templates_in(dir.path());
+        let vars = [("displayname", "Alice")];
+
+        let german = templates.render(EmailTemplateKind::Welcome, Some("de-AT, en;q=0.5"), &vars);
+        assert_eq!(german.subject, "Willkommen bei Acme Chat");
+        assert_eq!(german.body, "Hallo Alice");
+
+        let other = templates.render(EmailTemplateKind::Welcome, Some("fr"), &vars);
+        assert_eq!(other.subject, "Welcome to Acme Chat");
+        assert_eq!(other.body, "Hi Alice - Acme Inc.");
+
+        let builtin = templates.render(EmailTemplateKind::NewDevice, Some("de"), &[]);
+        assert_eq!(builtin.subject, "New sign-in to your Acme Chat account");
+    }
+
+    #[test]
+    fn unknown_and_unterminated_placeholders_are_kept() {
+        let values = HashMap::from([("name", "Alice")]);
+        assert_eq!(render_placeholders("{{name}} {{ nope }} {{name", &values), "Alice {{ nope }} {{name");
+    }
+}
@@ -1,8 +1,9 @@
 //! Event services domain group.
 //!
 //! Re-exports event-related service modules (event_broadcaster_trait,
-//! event_notifier, event_report_service) under a single namespace so that new
-//! event services can be added here without touching `lib.rs`.
+//! event_bus, event_notifier, event_report_service, event_visibility_service)
+//! under a single namespace so that new event services can be added here
+//! without touching `lib.rs`.
 //!
 //! Consumers may use either:
 //! - `synapse_services::event::EventNotifier` (preferred, grouped path)
@@ -14,5 +15,7 @@
 //! glob here, so both paths resolve to the same underlying trait.
 
 pub use crate::event_broadcaster_trait::{BroadcastError, EventBroadcaster};
+pub use crate::event_bus::{EventBus, NotifyingEventWriter, DEFAULT_EVENT_BUS_CAPACITY};
 pub use crate::event_notifier::{EventNotifier, EventNotifyKind, EventNotifyMessage};
 pub use crate::event_report_service::EventReportService;
+pub use crate::event_visibility_service::{EventVisibilityService, VisibleEvent};
@@ -8,6 +8,7 @@
 //! | Implementation | Location | Purpose |
 //! |----------------|----------|---------|
 //! | [`crate::event_notifier::EventNotifier`] | `services/event_notifier.rs` | Local sync wake-up — instantly unblocks long-polling `/sync` and sliding-sync connections when new data arrives for a room or user. |
+//! | [`crate::event_bus::EventBus`] | `services/event_bus.rs` | Persisted-event fan-out — `tokio::sync::broadcast` of every event written through `NotifyingEventWriter`, consumed by the sync notifier and the appservice scheduler. |
 //! | [`synapse_federation::event_broadcaster::EventBroadcaster`] | `federation/event_broadcaster.rs` | Federation outbound — batches and sends PDU/EDU transactions to remote homeservers with retry and persistence. |
 //! | [`crate::worker::bus::WorkerBus`] | `worker/bus.rs` | Inter-worker messaging — pub/sub channel for replication commands, stream positions, and worker coordination. |
 //!
@@ -16,6 +17,9 @@
 //! * **Client sync wake-up** → `EventNotifier` — it is optimised for
 //!   per-room/per-user `tokio::sync::Notify` signalling with optional Redis
 //!   cross-instance fan-out.
+//! * **Reacting to newly persisted room events** → `EventBus` — subscribe
+//!   instead of polling the events table; treat each message as a hint and
+//!   re-read storage.
 //! * **Sending events to remote servers** → `federation::EventBroadcaster` —
 //!   it handles batching, back-off, DB persistence, and retry for federation
 //!   transactions.
@@ -0,0 +1,372 @@
+//! In-process bus for freshly persisted room events.
+//!
+//! [`NotifyingEventWriter`] wraps the room services' [`EventWriter`] and
+//! publishes every event it persists on an [`EventBus`]. Consumers that used
+//! to find new events by polling the database subscribe instead; the container
+//! wires the `/sync` long-poll up through
+//! [`EventNotifier`][crate::event_notifier::EventNotifier].
+//!
+//! The bus carries wake-ups, not a durable log. A subscriber that falls more
+//! than the channel capacity behind loses the oldest messages, and an event
+//! written inside a caller's transaction is published before that transaction
+//! commits, so subscribers always re-read storage and keep a slow fallback
+//! poll.
+//!
+//! Two consumers deliberately sit elsewhere. Application service queues are
+//! filled after the event is written, so the scheduler is woken by
+//! `ApplicationServiceManager::enqueue_matching_event` instead; a wake-up from
+//! here could run before the queue row exists. Federation needs the signed
+//! PDU, which the room services hand to the federation `EventBroadcaster`
+//! directly.
+
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use synapse_storage::event::{EventWriter, RejectedEventParams, SoftFailKind};
+use synapse_storage::{CreateEventParams, RoomEvent};
+use tokio::sync::broadcast;
+use tokio::sync::broadcast::error::RecvError;
+use tokio_util::sync::CancellationToken;
+
+use crate::event_broadcaster_trait::{BroadcastError, EventBroadcaster};
+use crate::event_notifier::EventNotifier;
+
+/// Events buffered per subscriber before the slowest one starts lagging.
+pub const DEFAULT_EVENT_BUS_CAPACITY: usize = 1024;
+
+/// Fan-out of persisted events to in-process subscribers.
+#[derive(Debug, Clone)]
+pub struct EventBus {
+    sender: broadcast::Sender<Arc<RoomEvent>>,
+}
+
+impl EventBus {
+    pub fn new(capacity: usize) -> Self {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        Self { sender }
+    }
+
+    /// Publish an event to every current subscriber. Having none is not an
+    /// error; the event is simply dropped.
+    pub fn publish(&self, event: RoomEvent) {
+        let _ = self.sender.send(Arc::new(event));
+    }
+
+    pub fn subscribe(&self) -> broadcast::Receiver<Arc<RoomEvent>> {
+        self.sender.subscribe()
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.sender.receiver_count()
+    }
+
+    /// Run `handler` for every published event on a background task until
+    /// `shutdown` fires. Lagging only costs the skipped wake-ups, which the
+    /// subscribers' fallback polling covers, so it is logged and ignored.
+    pub fn spawn_subscriber<F>(
+        &self,
+        name: &'static str,
+        shutdown: CancellationToken,
+        handler: F,
+    ) -> tokio::task::JoinHandle<()>
+    where
+        F: Fn(&RoomEvent) + Send + 'static,
+    {
+        let mut receiver = self.subscribe();
+        tokio::spawn(async move {
+            loop {
+                tokio::select! {
+                    _ = shutdown.cancelled() => break,
+                    received = receiver.recv() => match received {
+                        Ok(event) => handler(&event),
+                        Err(RecvError::Lagged(skipped)) => {
+                            ::tracing::warn!(subscriber = name, skipped, "Event bus subscriber lagged");
+                        }
+                        Err(RecvError::Closed) => break,
+                    },
+                }
+            }
+        })
+    }
+}
+
+impl Default for EventBus {
+    fn default() -> Self {
+        Self::new(DEFAULT_EVENT_BUS_CAPACITY)
+    }
+}
+
+impl EventBroadcaster for EventBus {
+    type Message = Arc<RoomEvent>;
+
+    async fn broadcast_publish(&self, message: Self::Message) -> Result<(), BroadcastError> {
+        let _ = self.sender.send(message);
+        Ok(())
+    }
+
+    fn broadcast_subscriber_count(&self) -> usize {
+        self.subscriber_count()
+    }
+}
+
+/// [`EventWriter`] decorator that publishes created events on an [`EventBus`].
+///
+/// Ephemeral writes (receipts, typing) are not room events and never reach
+/// the bus; with [`NotifyingEventWriter::with_notifier`] they wake the room's
+/// `/sync` long-polls directly.
+pub struct NotifyingEventWriter {
+    inner: Arc<dyn EventWriter>,
+    bus: EventBus,
+    notifier: Option<EventNotifier>,
+}
+
+impl NotifyingEventWriter {
+    pub fn new(inner: Arc<dyn EventWriter>, bus: EventBus) -> Self {
+        Self { inner, bus, notifier: None }
+    }
+
+    pub fn with_notifier(mut self, notifier: EventNotifier) -> Self {
+        self.notifier = Some(notifier);
+        self
+    }
+
+    fn notify_ephemeral(&self, room_id: &str) {
+        if let Some(notifier) = &self.notifier {
+            notifier.notify_room(room_id);
+        }
+    }
+}
+
+#[async_trait]
+impl EventWriter for NotifyingEventWriter {
+    fn pool(&self) -> &Arc<sqlx::PgPool> {
+        self.inner.pool()
+    }
+
+    async fn create_event(
+        &self,
+        params: CreateEventParams,
+        tx: Option<&mut sqlx::Transaction<'_, sqlx::Postgres>>,
+    ) -> Result<RoomEvent, sqlx::Error> {
+        let event = self.inner.create_event(params, tx).await?;
+        self.bus.publish(event.clone());
+        Ok(event)
+    }
+
+    async fn update_event_signatures_and_hashes(
+        &self,
+        event_id: &str,
+        signatures: &serde_json::Value,
+        hashes: &serde_json::Value,
+    ) -> Result<(), sqlx::Error> {
+        self.inner.update_event_signatures_and_hashes(event_id, signatures, hashes).await
+    }
+
+    async fn redact_event_content(&self, event_id: &str, redacted_by: Option<&str>) -> Result<(), sqlx::Error> {
+        self.inner.redact_event_content(event_id, redacted_by).await
+    }
+
+    async fn create_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        tx: Option<&mut sqlx::Transaction<'_, sqlx::Postgres>>,
+    ) -> Result<RoomEvent, sqlx::Error> {
+        let event = self.inner.create_event_with_graph(params, prev_events, auth_events, depth, tx).await?;
+        self.bus.publish(event.clone());
+        Ok(event)
+    }
+
+    async fn record_local_event_graph(
+        &self,
+        room_id: &str,
+        event_id: &str,
+        prev_events: &[String],
+    ) -> Result<i64, sqlx::Error> {
+        self.inner.record_local_event_graph(room_id, event_id, prev_events).await
+    }
+
+    /// Not published: soft-failed events must not wake client syncs.
+    async fn create_soft_failed_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        kind: SoftFailKind,
+        reason: &str,
+    ) -> Result<RoomEvent, sqlx::Error> {
+        self.inner.create_soft_failed_event_with_graph(params, prev_events, auth_events, depth, kind, reason).await
+    }
+
+    async fn record_rejected_event(&self, params: RejectedEventParams, max_rows: i64) -> Result<(), sqlx::Error> {
+        self.inner.record_rejected_event(params, max_rows).await
+    }
+
+    async fn save_event_signature(
+        &self,
+        event_id: &str,
+        user_id: &str,
+        device_id: &str,
+        signature: &str,
+        key_id: &str,
+        algorithm: &str,
+        created_ts: i64,
+    ) -> Result<(), sqlx::Error> {
+        self.inner.save_event_signature(event_id, user_id, device_id, signature, key_id, algorithm, created_ts).await
+    }
+
+    async fn report_event(
+        &self,
+        event_id: &str,
+        room_id: &str,
+        reported_user_id: &str,
+        reporter_user_id: &str,
+        reason: Option<&str>,
+        score: i32,
+    ) -> Result<i64, sqlx::Error> {
+        self.inner.report_event(event_id, room_id, reported_user_id, reporter_user_id, reason, score).await
+    }
+
+    async fn add_ephemeral_event(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        event_type: &str,
+        content: &serde_json::Value,
+        stream_id: i64,
+    ) -> Result<(), sqlx::Error> {
+        self.inner.add_ephemeral_event(room_id, user_id, event_type, content, stream_id).await?;
+        self.notify_ephemeral(room_id);
+        Ok(())
+    }
+
+    async fn upsert_ephemeral_event(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        event_type: &str,
+        content: &serde_json::Value,
+        stream_id: i64,
+        created_ts: i64,
+        expires_at: Option<i64>,
+    ) -> Result<(), sqlx::Error> {
+        self.inner
+            .upsert_ephemeral_event(room_id, user_id, event_type, content, stream_id, created_ts, expires_at)
+            .await?;
+        self.notify_ephemeral(room_id);
+        Ok(())
+    }
+
+    async fn delete_ephemeral_event(&self, room_id: &str, event_type: &str, user_id: &str) -> Result<(), sqlx::Error> {
+        self.inner.delete_ephemeral_event(room_id, event_type, user_id).await
+    }
+
+    async fn delete_events_before(&self, room_id: &str, timestamp: i64) -> Result<u64, sqlx::Error> {
+        self.inner.delete_events_before(room_id, timestamp).await
+    }
+
+    async fn upsert_power_levels_event(
+        &self,
+        event_id: &str,
+        room_id: &str,
+        user_id: &str,
+        content: serde_json::Value,
+        origin_server_ts: i64,
+        sender: &str,
+    ) -> Result<(), sqlx::Error> {
+        self.inner.upsert_power_levels_event(event_id, room_id, user_id, content, origin_server_ts, sender).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+    use synapse_storage::test_mocks::InMemoryEventStore;
+
+    fn message_params(event_id: &str) -> CreateEventParams {
+        CreateEventParams {
+            event_id: event_id.to_string(),
+            room_id: "!room:example.com".to_string(),
+            user_id: "@alice:example.com".to_string(),
+            event_type: "m.room.message".to_string(),
+            content: json!({ "msgtype": "m.text", "body": "hi" }),
+            state_key: None,
+            origin_server_ts: 1_000,
+            redacts: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn created_events_reach_every_subscriber() {
+        let bus = EventBus::new(8);
+        let mut first = bus.subscribe();
+        let mut second = bus.subscribe();
+        let writer = NotifyingEventWriter::new(Arc::new(InMemoryEventStore::new()), bus.clone());
+
+        writer.create_event(message_params("$one"), None).await.unwrap();
+
+        assert_eq!(first.recv().await.unwrap().event_id, "$one");
+        assert_eq!(second.recv().await.unwrap().event_id, "$one");
+    }
+
+    #[tokio::test]
+    async fn only_event_creation_is_published() {
+        let bus = EventBus::new(8);
+        let mut receiver = bus.subscribe();
+        let writer = NotifyingEventWriter::new(Arc::new(InMemoryEventStore::new()), bus.clone());
+
+        writer.create_event(message_params("$kept"), None).await.unwrap();
+        writer.redact_event_content("$kept", Some("$redaction")).await.unwrap();
+        writer.delete_ephemeral_event("!room:example.com", "m.typing", "@alice:example.com").await.unwrap();
+
+        assert_eq!(receiver.recv().await.unwrap().event_id, "$kept");
+        assert!(matches!(receiver.try_recv(), Err(broadcast::error::TryRecvError::Empty)));
+    }
+
+    #[tokio::test]
+    async fn ephemeral_writes_wake_room_listeners() {
+        let notifier = EventNotifier::new();
+        let writer = NotifyingEventWriter::new(Arc::new(InMemoryEventStore::new()), EventBus::new(8))
+            .with_notifier(notifier.clone());
+        let listener = notifier.listener(&["!room:example.com".to_string()], "@bob:example.com");
+
+        let armed = listener.arm();
+        writer
+            .add_ephemeral_event("!room:example.com", "@alice:example.com", "m.receipt", &json!({}), 1)
+            .await
+            .unwrap();
+        assert!(armed.wait(std::time::Duration::from_secs(5)).await);
+    }
+
+    #[tokio::test]
+    async fn publishing_without_subscribers_is_harmless() {
+        let bus = EventBus::default();
+        assert_eq!(bus.subscriber_count(), 0);
+        bus.broadcast_publish(Arc::new(
+            EventWriter::create_event(&InMemoryEventStore::new(), message_params("$lonely"), None).await.unwrap(),
+        ))
+        .await
+        .unwrap();
+    }
+
+    #[tokio::test]
+    async fn spawned_subscriber_sees_events_and_stops_on_shutdown() {
+        let bus = EventBus::new(8);
+        let shutdown = CancellationToken::new();
+        let (seen_tx, mut seen_rx) = tokio::sync::mpsc::unbounded_channel();
+        let handle = bus.spawn_subscriber("test", shutdown.clone(), move |event| {
+            let _ = seen_tx.send(event.event_id.clone());
+        });
+
+        let writer = NotifyingEventWriter::new(Arc::new(InMemoryEventStore::new()), bus.clone());
+        writer.create_event(message_params("$seen"), None).await.unwrap();
+        assert_eq!(seen_rx.recv().await.as_deref(), Some("$seen"));
+
+        shutdown.cancel();
+        handle.await.unwrap();
+    }
+}
@@ -2,7 +2,9 @@ use crate::event_broadcaster_trait::{BroadcastError, EventBroadcaster};
 use dashmap::DashMap;
 use deadpool_redis::Pool;
 use serde::{Deserialize, Serialize};
+use std::pin::Pin;
 use std::sync::Arc;
+use tokio::sync::futures::Notified;
 use tokio::sync::Notify;
 use tracing::{debug, warn};
 
@@ -116,6 +118,37 @@ impl EventNotifier {
         }
     }
 
+    /// Wait until one of the given rooms or the user receives a
+    /// notification, or the timeout elapses.
+    pub async fn wait_for_rooms_or_user(&self, room_ids: &[String], user_id: &str, timeout: tokio::time::Duration) {
+        let notifiers: Vec<Arc<Notify>> = room_ids
+            .iter()
+            .map(|room_id| self.get_or_create_room_notify(room_id))
+            .chain(std::iter::once(self.get_or_create_user_notify(user_id)))
+            .collect();
+
+        let futures: Vec<_> = notifiers.iter().map(|n| Box::pin(n.notified())).collect();
+
+        tokio::select! {
+            _ = futures::future::select_all(futures) => {}
+            _ = tokio::time::sleep(timeout) => {}
+        }
+    }
+
+    /// Subscribe to the given rooms and user for a long-poll loop.
+    ///
+    /// Unlike the `wait_for_*` helpers, a [`NotifyListener`] is armed before
+    /// storage is checked, so a notification fired between the check and the
+    /// wait still ends the wait.
+    pub fn listener(&self, room_ids: &[String], user_id: &str) -> NotifyListener {
+        let notifies = room_ids
+            .iter()
+            .map(|room_id| self.get_or_create_room_notify(room_id))
+            .chain(std::iter::once(self.get_or_create_user_notify(user_id)))
+            .collect();
+        NotifyListener { notifies }
+    }
+
     /// Notify all connections waiting for events in the given room.
     pub fn notify_room(&self, room_id: &str) {
         if let Some(notify) = self.room_notifiers.get(room_id) {
@@ -183,6 +216,44 @@ impl EventNotifier {
     }
 }
 
+/// Room and user notifications one long-polling request is subscribed to.
+#[derive(Debug)]
+pub struct NotifyListener {
+    notifies: Vec<Arc<Notify>>,
+}
+
+impl NotifyListener {
+    /// Start listening. Notifications from this point on end the returned
+    /// [`ArmedNotify`]'s wait, even if they fire before it is awaited.
+    pub fn arm(&self) -> ArmedNotify<'_> {
+        let mut notified: Vec<Pin<Box<Notified<'_>>>> = self.notifies.iter().map(|n| Box::pin(n.notified())).collect();
+        for future in &mut notified {
+            future.as_mut().enable();
+        }
+        ArmedNotify { notified }
+    }
+}
+
+/// A [`NotifyListener`] registered for the next notification.
+pub struct ArmedNotify<'a> {
+    notified: Vec<Pin<Box<Notified<'a>>>>,
+}
+
+impl ArmedNotify<'_> {
+    /// Wait for a notification since [`NotifyListener::arm`] or for
+    /// `timeout`. Returns `true` when woken by a notification.
+    pub async fn wait(self, timeout: tokio::time::Duration) -> bool {
+        if self.notified.is_empty() {
+            tokio::time::sleep(timeout).await;
+            return false;
+        }
+        tokio::select! {
+            _ = futures::future::select_all(self.notified) => true,
+            _ = tokio::time::sleep(timeout) => false,
+        }
+    }
+}
+
 impl EventBroadcaster for EventNotifier {
     type Message = EventNotifyMessage;
 
@@ -240,6 +311,54 @@ mod tests {
         handle.await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_wait_for_rooms_or_user_wakes_on_either() {
+        let notifier = EventNotifier::new();
+        let rooms = vec!["!a:example.com".to_string(), "!b:example.com".to_string()];
+
+        let waiter = notifier.clone();
+        let waited_rooms = rooms.clone();
+        let handle = tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            waiter
+                .wait_for_rooms_or_user(&waited_rooms, "@alice:example.com", tokio::time::Duration::from_secs(5))
+                .await;
+            start.elapsed()
+        });
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        notifier.notify_room("!b:example.com");
+        assert!(handle.await.unwrap() < tokio::time::Duration::from_secs(5));
+
+        let waiter = notifier.clone();
+        let handle = tokio::spawn(async move {
+            let start = tokio::time::Instant::now();
+            waiter.wait_for_rooms_or_user(&rooms, "@alice:example.com", tokio::time::Duration::from_secs(5)).await;
+            start.elapsed()
+        });
+        tokio::time::sleep(tokio::time::Duration::from_millis(10)).await;
+        notifier.notify_user("@alice:example.com");
+        assert!(handle.await.unwrap() < tokio::time::Duration::from_secs(5));
+    }
+
+    #[tokio::test]
+    async fn test_armed_listener_keeps_notification_fired_before_wait() {
+        let notifier = EventNotifier::new();
+        let rooms = vec!["!a:example.com".to_string()];
+        let listener = notifier.listener(&rooms, "@alice:example.com");
+
+        let armed = listener.arm();
+        notifier.notify_room("!a:example.com");
+        assert!(armed.wait(tokio::time::Duration::from_secs(5)).await);
+
+        let armed = listener.arm();
+        notifier.notify_user("@alice:example.com");
+        assert!(armed.wait(tokio::time::Duration::from_secs(5)).await);
+
+        // Notifications before arming are not carried over.
+        notifier.notify_room("!a:example.com");
+        assert!(!listener.arm().wait(tokio::time::Duration::from_millis(20)).await);
+    }
+
     #[tokio::test]
     async fn test_notify_user_wakes_waiter() {
         let notifier = EventNotifier::new();
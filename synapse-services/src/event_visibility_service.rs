@@ -0,0 +1,325 @@
+//! Which room events a user may see.
+//!
+//! Every endpoint that hands room events to a client (sync, /messages,
+//! /context, search, /notifications) runs them through
+//! [`EventVisibilityService::filter_events_for_client`] so the rules below are
+//! applied the same way everywhere:
+//!
+//! - `m.room.history_visibility` in force before the event, combined with the
+//!   viewer's membership at that point (`shared` history is also visible to
+//!   current members);
+//! - non-state events from senders in the viewer's `m.ignored_user_list` are
+//!   dropped;
+//! - events from shadow-banned senders are dropped for everyone but the
+//!   sender;
+//! - content from erased senders is pruned as if redacted.
+//!
+//! The viewer's own membership events are always visible.
+
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+
+use serde_json::Value;
+use synapse_common::redaction::redact_content;
+use synapse_common::{ApiError, ApiResult};
+use synapse_storage::event::{EventReader, RoomEvent, StateEvent};
+use synapse_storage::user::UserStore;
+
+const IGNORED_USER_LIST_TYPE: &str = "m.ignored_user_list";
+
+/// The parts of an event the visibility rules look at.
+pub trait VisibleEvent {
+    fn room_id(&self) -> &str;
+    fn sender(&self) -> &str;
+    fn event_type(&self) -> &str;
+    fn state_key(&self) -> Option<&str>;
+    fn stream_ordering(&self) -> Option<i64>;
+    fn origin_server_ts(&self) -> i64;
+    fn content_mut(&mut self) -> &mut Value;
+}
+
+impl VisibleEvent for RoomEvent {
+    fn room_id(&self) -> &str {
+        &self.room_id
+    }
+
+    fn sender(&self) -> &str {
+        &self.user_id
+    }
+
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn state_key(&self) -> Option<&str> {
+        self.state_key.as_deref()
+    }
+
+    fn stream_ordering(&self) -> Option<i64> {
+        self.stream_ordering
+    }
+
+    fn origin_server_ts(&self) -> i64 {
+        self.origin_server_ts
+    }
+
+    fn content_mut(&mut self) -> &mut Value {
+        &mut self.content
+    }
+}
+
+pub struct EventVisibilityService {
+    event_reader: Arc<dyn EventReader>,
+    user_storage: Arc<dyn UserStore>,
+}
+
+impl EventVisibilityService {
+    pub fn new(event_reader: Arc<dyn EventReader>, user_storage: Arc<dyn UserStore>) -> Self {
+        Self { event_reader, user_storage }
+    }
+
+    /// `events` minus those `user_id` may not see, in the original order,
+    /// with erased senders' content pruned.
+    #[::tracing::instrument(skip_all, fields(user_id = %user_id, event_count = events.len()))]
+    pub async fn filter_events_for_client<E: VisibleEvent>(&self, user_id: &str, events: Vec<E>) -> ApiResult<Vec<E>> {
+        if events.is_empty() {
+            return Ok(events);
+        }
+
+        let room_ids: Vec<String> = events
+            .iter()
+            .map(|event| event.room_id())
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+        let senders: Vec<String> = events
+            .iter()
+            .map(|event| event.sender())
+            .filter(|sender| *sender != user_id)
+            .collect::<HashSet<_>>()
+            .into_iter()
+            .map(str::to_string)
+            .collect();
+
+        let (history, ignored_list, senders_by_id, erased) = tokio::try_join!(
+            async {
+                self.event_reader
+                    .get_visibility_history_batch(&room_ids, user_id)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to load room visibility history", &e))
+            },
+            async {
+                self.user_storage
+                    .get_account_data_content(user_id, IGNORED_USER_LIST_TYPE)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to load ignored users", &e))
+            },
+            async {
+                self.user_storage
+                    .get_users_map(&senders)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to load event senders", &e))
+            },
+            async {
+                self.user_storage
+                    .get_erased_users(&senders)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to load erased users", &e))
+            },
+        )?;
+
+        let ignored: HashSet<&str> = ignored_list
+            .as_ref()
+            .and_then(|content| content.get("ignored_users"))
+            .and_then(Value::as_object)
+            .map(|users| users.keys().map(String::as_str).collect())
+            .unwrap_or_default();
+        let shadow_banned: HashSet<&str> =
+            senders_by_id.values().filter(|user| user.is_shadow_banned).map(|user| user.user_id.as_str()).collect();
+
+        Ok(events
+            .into_iter()
+            .filter(|event| {
+                let sender = event.sender();
+                let foreign = sender != user_id;
+                if foreign && shadow_banned.contains(sender) {
+                    return false;
+                }
+                if foreign && event.state_key().is_none() && ignored.contains(sender) {
+                    return false;
+                }
+                let room_history = history.get(event.room_id()).map(Vec::as_slice).unwrap_or_default();
+                is_visible_in_history(user_id, event, room_history)
+            })
+            .map(|mut event| {
+                if event.sender() != user_id && erased.contains(event.sender()) {
+                    let event_type = event.event_type().to_string();
+                    let pruned = redact_content(&event_type, event.content_mut());
+                    *event.content_mut() = pruned;
+                }
+                event
+            })
+            .collect())
+    }
+
+    /// [`Self::filter_events_for_client`] over events grouped by room.
+    pub async fn filter_room_events_for_client(
+        &self,
+        user_id: &str,
+        room_events: &HashMap<String, Vec<RoomEvent>>,
+    ) -> ApiResult<HashMap<String, Vec<RoomEvent>>> {
+        let events = room_events.values().flatten().cloned().collect();
+        let mut visible: HashMap<String, Vec<RoomEvent>> =
+            room_events.keys().map(|room_id| (room_id.clone(), Vec::new())).collect();
+        for event in self.filter_events_for_client(user_id, events).await? {
+            visible.entry(event.room_id.clone()).or_default().push(event);
+        }
+        Ok(visible)
+    }
+
+    /// The subset of `event_ids` that exist and `user_id` may see.
+    pub async fn visible_event_ids(&self, user_id: &str, event_ids: &[String]) -> ApiResult<HashSet<String>> {
+        let events = self
+            .event_reader
+            .get_events_map(event_ids)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load events", &e))?;
+        Ok(self
+            .filter_events_for_client(user_id, events.into_values().collect())
+            .await?
+            .into_iter()
+            .map(|event| event.event_id)
+            .collect())
+    }
+}
+
+/// Whether `history` (the room's `m.room.history_visibility` events and
+/// `user_id`'s membership events, oldest first) lets `user_id` see `event`.
+fn is_visible_in_history<E: VisibleEvent>(user_id: &str, event: &E, history: &[StateEvent]) -> bool {
+    if event.event_type() == "m.room.member" && event.state_key() == Some(user_id) {
+        return true;
+    }
+
+    let mut visibility = "shared";
+    let mut membership = None;
+    for state in history.iter().take_while(|state| precedes(state, event)) {
+        if state.event_type.as_deref() == Some("m.room.history_visibility") {
+            visibility = state.content.get("history_visibility").and_then(Value::as_str).unwrap_or("shared");
+        } else {
+            membership = state.content.get("membership").and_then(Value::as_str);
+        }
+    }
+
+    match visibility {
+        "world_readable" => true,
+        "shared" => membership == Some("join") || current_membership(history) == Some("join"),
+        "invited" => matches!(membership, Some("join" | "invite")),
+        _ => membership == Some("join"),
+    }
+}
+
+fn precedes<E: VisibleEvent>(state: &StateEvent, event: &E) -> bool {
+    match (state.stream_ordering, event.stream_ordering()) {
+        (Some(state_ordering), Some(event_ordering)) => state_ordering < event_ordering,
+        _ => state.origin_server_ts < event.origin_server_ts(),
+    }
+}
+
+fn current_membership(history: &[StateEvent]) -> Option<&str> {
+    history
+        .iter()
+        .rev()
+        .find(|state| state.event_type.as_deref() == Some("m.room.member"))
+        .and_then(|state| state.content.get("membership").and_then(Value::as_str))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    const ALICE: &str = "@alice:example.com";
+
+    fn state(event_type: &str, content: Value, stream_ordering: i64) -> StateEvent {
+        StateEvent {
+            event_id: format!("$state{stream_ordering}"),
+            room_id: "!room:example.com".to_string(),
+            sender: ALICE.to_string(),
+            event_type: Some(event_type.to_string()),
+            content,
+            state_key: Some(if event_type == "m.room.member" { ALICE.to_string() } else { String::new() }),
+            unsigned: None,
+            is_redacted: Some(false),
+            origin_server_ts: stream_ordering,
+            depth: None,
+            processed_ts: None,
+            not_before: None,
+            status: None,
+            reference_image: None,
+            origin: None,
+            user_id: None,
+            stream_ordering: Some(stream_ordering),
+        }
+    }
+
+    fn message(stream_ordering: i64) -> RoomEvent {
+        RoomEvent {
+            event_id: format!("$msg{stream_ordering}"),
+            room_id: "!room:example.com".to_string(),
+            user_id: "@bob:example.com".to_string(),
+            event_type: "m.room.message".to_string(),
+            content: json!({ "msgtype": "m.text", "body": "hi" }),
+            state_key: None,
+            depth: stream_ordering,
+            origin_server_ts: stream_ordering,
+            processed_ts: stream_ordering,
+            not_before: 0,
+            status: None,
+            reference_image: None,
+            origin: "example.com".to_string(),
+            stream_ordering: Some(stream_ordering),
+            redacts: None,
+        }
+    }
+
+    #[test]
+    fn joined_history_hides_events_before_the_join() {
+        let history = vec![
+            state("m.room.history_visibility", json!({ "history_visibility": "joined" }), 1),
+            state("m.room.member", json!({ "membership": "join" }), 5),
+        ];
+        assert!(!is_visible_in_history(ALICE, &message(3), &history));
+        assert!(is_visible_in_history(ALICE, &message(7), &history));
+    }
+
+    #[test]
+    fn shared_history_is_visible_to_current_members_only() {
+        let mut history = vec![
+            state("m.room.history_visibility", json!({ "history_visibility": "shared" }), 1),
+            state("m.room.member", json!({ "membership": "join" }), 5),
+        ];
+        assert!(is_visible_in_history(ALICE, &message(3), &history));
+
+        history.push(state("m.room.member", json!({ "membership": "leave" }), 9));
+        assert!(!is_visible_in_history(ALICE, &message(3), &history));
+        assert!(is_visible_in_history(ALICE, &message(7), &history));
+        assert!(!is_visible_in_history(ALICE, &message(11), &history));
+    }
+
+    #[test]
+    fn invited_history_starts_at_the_invite() {
+        let history = vec![
+            state("m.room.history_visibility", json!({ "history_visibility": "invited" }), 1),
+            state("m.room.member", json!({ "membership": "invite" }), 5),
+        ];
+        assert!(!is_visible_in_history(ALICE, &message(3), &history));
+        assert!(is_visible_in_history(ALICE, &message(7), &history));
+    }
+
+    #[test]
+    fn world_readable_history_needs_no_membership() {
+        let history = vec![state("m.room.history_visibility", json!({ "history_visibility": "world_readable" }), 1)];
+        assert!(is_visible_in_history(ALICE, &message(3), &history));
+    }
+}
@@ -147,7 +147,7 @@ pub struct ExternalServiceIntegration {
 
 impl ExternalServiceIntegration {
     pub fn new(storage: Arc<dyn ApplicationServiceStoreApi>, server_name: String) -> Self {
-        let http_client = Client::builder()
+        let http_client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(30))
             .connect_timeout(Duration::from_secs(10))
             .pool_idle_timeout(Duration::from_secs(60))
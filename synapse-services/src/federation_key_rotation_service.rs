@@ -38,7 +38,7 @@ impl FederationKeyRotationService {
         self.manager
             .revoke_key(key_id, reason)
             .await
-            .map_err(|e| ApiError::internal(format!("Failed to revoke key: {e}")))
+            .map_err(|e| ApiError::internal_with_log("Failed to revoke key", &e))
     }
 
     pub async fn set_rotation_enabled(&self, enabled: bool) {
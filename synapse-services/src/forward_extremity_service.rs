@@ -0,0 +1,94 @@
+use crate::room::MessagingService;
+use std::sync::Arc;
+use synapse_common::config::ForwardExtremitiesConfig;
+use synapse_common::metrics::{Counter, Gauge, MetricsCollector};
+use synapse_common::ApiError;
+use tokio::sync::RwLock;
+use tracing::{instrument, warn};
+
+#[derive(Debug, Clone, Default)]
+pub struct ForwardExtremityRunSummary {
+    pub rooms_over_limit: u64,
+    pub dummy_events_sent: u64,
+    pub rooms_without_local_member: u64,
+    pub failed: u64,
+}
+
+#[derive(Clone)]
+struct ForwardExtremityMetrics {
+    runs_total: Counter,
+    dummy_events_sent_total: Counter,
+    dummy_events_failed_total: Counter,
+    rooms_over_limit: Gauge,
+    max_extremities: Gauge,
+}
+
+impl ForwardExtremityMetrics {
+    fn new(metrics: &Arc<MetricsCollector>) -> Self {
+        Self {
+            runs_total: metrics.register_counter("forward_extremities_runs_total".to_string()),
+            dummy_events_sent_total: metrics
+                .register_counter("forward_extremities_dummy_events_sent_total".to_string()),
+            dummy_events_failed_total: metrics
+                .register_counter("forward_extremities_dummy_events_failed_total".to_string()),
+            rooms_over_limit: metrics.register_gauge("forward_extremities_rooms_over_limit".to_string()),
+            max_extremities: metrics.register_gauge("forward_extremities_max".to_string()),
+        }
+    }
+}
+
+/// Finds rooms with too many forward extremities and merges them by sending
+/// dummy events (see [`ForwardExtremitiesConfig`]).
+pub struct ForwardExtremityService {
+    messaging: MessagingService,
+    config: ForwardExtremitiesConfig,
+    metrics: ForwardExtremityMetrics,
+    /// Rooms over the limit at the start of the last run, most extremities
+    /// first, for the per-room Prometheus series.
+    last_rooms: Arc<RwLock<Vec<(String, i64)>>>,
+}
+
+impl ForwardExtremityService {
+    pub fn new(messaging: MessagingService, config: ForwardExtremitiesConfig, metrics: &Arc<MetricsCollector>) -> Self {
+        Self {
+            messaging,
+            config,
+            metrics: ForwardExtremityMetrics::new(metrics),
+            last_rooms: Arc::new(RwLock::new(Vec::new())),
+        }
+    }
+
+    pub async fn rooms_over_limit(&self) -> Vec<(String, i64)> {
+        self.last_rooms.read().await.clone()
+    }
+
+    #[instrument(skip(self))]
+    pub async fn run_cycle(&self) -> Result<ForwardExtremityRunSummary, ApiError> {
+        let rooms = self
+            .messaging
+            .get_rooms_by_forward_extremities(self.config.max_extremities + 1, self.config.rooms_per_run.max(1))
+            .await?;
+
+        self.metrics.runs_total.inc();
+        self.metrics.rooms_over_limit.set(rooms.len() as f64);
+        self.metrics.max_extremities.set(rooms.first().map_or(0, |(_, count)| *count) as f64);
+        *self.last_rooms.write().await = rooms.clone();
+
+        let mut summary = ForwardExtremityRunSummary { rooms_over_limit: rooms.len() as u64, ..Default::default() };
+        for (room_id, extremities) in rooms {
+            match self.messaging.send_dummy_event(&room_id).await {
+                Ok(Some(_)) => {
+                    summary.dummy_events_sent += 1;
+                    self.metrics.dummy_events_sent_total.inc();
+                }
+                Ok(None) => summary.rooms_without_local_member += 1,
+                Err(e) => {
+                    summary.failed += 1;
+                    self.metrics.dummy_events_failed_total.inc();
+                    warn!(room_id = %room_id, extremities, error = %e, "Failed to send dummy event");
+                }
+            }
+        }
+        Ok(summary)
+    }
+}
@@ -744,7 +744,7 @@ impl FriendRoomService {
             .lifecycle()
             .create_room(owner_user_id, config.into())
             .await
-            .map_err(|e| ApiError::internal(e.to_string()))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to create friend room", &e))?;
 
         let room_id = result
             .get("room_id")
@@ -781,7 +781,7 @@ impl FriendRoomService {
             .lifecycle()
             .create_room(owner_user_id, config.into())
             .await
-            .map_err(|e| ApiError::internal(e.to_string()))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to create friend room", &e))?;
 
         let room_id = response
             .get("room_id")
@@ -20,7 +20,7 @@ impl GeoIpService {
     pub fn new(config: GeoIpConfig) -> Self {
         Self {
             config,
-            http_client: reqwest::Client::new(),
+            http_client: synapse_common::outbound_http::client(),
             cache: Arc::new(RwLock::new(HashMap::new())),
             rules: Arc::new(RwLock::new(Vec::new())),
             cache_ttl: Duration::from_secs(3600),
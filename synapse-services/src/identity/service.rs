@@ -12,7 +12,11 @@ pub struct IdentityService {
 
 impl IdentityService {
     pub fn new(storage: IdentityStorage, trusted_servers: Vec<String>) -> Self {
-        Self { storage, http_client: Client::new(), trusted_servers }
+        let http_client = synapse_common::outbound_http::client_builder()
+            .redirect(reqwest::redirect::Policy::none())
+            .build()
+            .unwrap_or_else(|_| Client::new());
+        Self { storage, http_client, trusted_servers }
     }
 
     pub async fn get_user_three_pids(&self, user_id: &str) -> ApiResult<Vec<ThirdPartyId>> {
@@ -38,6 +42,7 @@ impl IdentityService {
     ) -> ApiResult<()> {
         self.validate_id_server(id_server)?;
         let url = format!("https://{id_server}/_matrix/identity/v3/3pid/bind");
+        self.check_egress(&url).await?;
 
         let body = serde_json::json!({
             "sid": sid,
@@ -88,6 +93,7 @@ impl IdentityService {
     ) -> ApiResult<()> {
         self.validate_id_server(id_server)?;
         let url = format!("https://{id_server}/_matrix/identity/v3/3pid/unbind");
+        self.check_egress(&url).await?;
 
         let body = serde_json::json!({
             "address": address,
@@ -121,6 +127,7 @@ impl IdentityService {
     ) -> ApiResult<String> {
         self.validate_id_server(id_server)?;
         let url = format!("https://{id_server}/_matrix/identity/v3/3pid/requestAuth");
+        self.check_egress(&url).await?;
 
         let body = serde_json::json!({
             "medium": medium,
@@ -158,6 +165,7 @@ impl IdentityService {
     pub async fn check_3pid_validity(&self, id_server: &str, sid: &str, client_secret: &str) -> ApiResult<bool> {
         self.validate_id_server(id_server)?;
         let url = format!("https://{id_server}/_matrix/identity/v3/3pid/getValidationStatus");
+        self.check_egress(&url).await?;
 
         let body = serde_json::json!({
             "sid": sid,
@@ -214,6 +222,7 @@ impl IdentityService {
     ) -> ApiResult<InvitationResponse> {
         self.validate_id_server(id_server)?;
         let url = format!("https://{id_server}/_matrix/identity/v1/invite");
+        self.check_egress(&url).await?;
 
         let body = serde_json::json!({
             "room_id": room_id,
@@ -252,6 +261,12 @@ impl IdentityService {
         &self.trusted_servers
     }
 
+    async fn check_egress(&self, url: &str) -> ApiResult<()> {
+        synapse_common::egress::check_url(url)
+            .await
+            .map_err(|e| ApiError::bad_request(format!("id_server must not be a private/local address: {e}")))
+    }
+
     pub fn validate_id_server(&self, id_server: &str) -> ApiResult<()> {
         if id_server.is_empty() {
             return Err(ApiError::bad_request("id_server cannot be empty".to_string()));
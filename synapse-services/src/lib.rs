@@ -33,6 +33,7 @@ pub mod account_identity_service;
 /// Admin domain group — re-exports admin service types under `admin::`.
 pub mod admin;
 pub mod admin_audit_service;
+pub mod admin_bootstrap_service;
 pub mod admin_federation_service;
 pub mod admin_media_service;
 pub mod admin_registration_service;
@@ -43,22 +44,29 @@ pub mod admin_user_service;
 /// Application services domain group — re-exports application modules under `application::`.
 pub mod application;
 pub mod application_service;
+pub mod auto_join_service;
 pub mod background_update_service;
 pub mod captcha_service;
 pub mod client_push_service;
+pub mod content_filter_service;
 pub mod content_scanner;
 pub mod database_initializer;
 pub mod dehydrated_device_service;
+pub mod delayed_event_service;
 /// E2EE audit service (not the full e2ee crate — that is re-exported as `e2ee`).
 pub mod e2ee_audit;
+pub mod email_templates;
 /// Event services domain group — re-exports event service types under `event::`.
 pub mod event;
 pub mod event_broadcaster_trait;
+pub mod event_bus;
 pub mod event_notifier;
 pub mod event_report_service;
+pub mod event_visibility_service;
 pub mod feature_flag_service;
 pub mod federation_blacklist_service;
 pub mod federation_key_rotation_service;
+pub mod forward_extremity_service;
 /// Identity services domain group — re-exports identity service types under `identity::`.
 pub mod identity;
 /// Infrastructure services domain group — re-exports infra service types under `infra::`.
@@ -68,6 +76,7 @@ pub mod media_quota_service;
 pub mod media_service;
 pub mod module_service;
 pub mod oidc_service;
+pub mod policy_list_service;
 pub mod presence_service;
 pub mod push;
 pub use push::service as push_notification_service;
@@ -77,9 +86,11 @@ pub mod refresh_token_service;
 pub mod registration_service;
 pub mod registration_token_service;
 pub mod relations_service;
+pub mod rendezvous_channel_service;
 pub mod retention_service;
 pub mod room;
 pub mod search_service;
+pub mod send_quota_service;
 pub mod sliding_sync_service;
 pub mod sms_provider;
 /// Sync services domain group — re-exports sync service types under `sync::`.
@@ -111,7 +122,7 @@ pub use account::*; // account domain group (account_device_list_service, accoun
 pub use admin::*; // admin domain group (backward-compat flat re-export)
 pub use application::*; // application domain group (application_service, module_service)
 #[allow(ambiguous_glob_reexports)]
-pub use event::*; // event domain group (event_broadcaster_trait, event_notifier, event_report_service)
+pub use event::*; // event domain group (event_broadcaster_trait, event_bus, event_notifier, event_report_service, event_visibility_service)
 #[allow(ambiguous_glob_reexports)]
 pub use identity::*; // identity domain group (identity, oidc_service)
 #[allow(ambiguous_glob_reexports)]
@@ -195,6 +206,8 @@ pub mod widget_service;
 
 #[cfg(feature = "server-notifications")]
 pub mod server_notification_service;
+#[cfg(feature = "server-notifications")]
+pub mod welcome_service;
 
 #[cfg(feature = "burn-after-read")]
 pub mod burn_after_read_service;
@@ -38,7 +38,7 @@ pub struct McpProxyService {
 impl McpProxyService {
     pub fn new(cache: Arc<CacheManager>) -> Self {
         // 创建带有超时控制的 HTTP Client
-        let client = Client::builder()
+        let client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(30)) // 增加超时时间以适应大模型生成或爬虫
             .build()
             .unwrap_or_else(|e| {
@@ -619,7 +619,8 @@ mod tests {
                 uploader_user_id TEXT,
                 created_ts BIGINT NOT NULL,
                 last_accessed_at BIGINT,
-                quarantine_status TEXT
+                quarantine_status TEXT,
+                deleted_at BIGINT
             );
 
             CREATE TABLE upload_progress (
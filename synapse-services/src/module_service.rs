@@ -104,17 +104,41 @@ pub struct PasswordAuthOutput {
     pub user_id: Option<String>,
 }
 
+/// Custom password backend compiled into the server and registered through
+/// [`ModuleService::register_password_provider`]. Password logins consult the
+/// providers in registration order before LDAP and the local password
+/// database.
 #[async_trait]
-pub trait PasswordAuthProviderTrait: Send + Sync {
+pub trait PasswordAuthProvider: Send + Sync {
     fn name(&self) -> &str;
 
-    async fn check(&self, context: &PasswordAuthContext) -> Result<PasswordAuthOutput, ApiError>;
+    /// Whether this provider is responsible for `user_id`. Providers serving
+    /// only part of the namespace return `false` for the rest and are skipped.
+    fn handles_user(&self, _user_id: &str) -> bool {
+        true
+    }
+
+    /// Whether other backends may still be tried for `user_id` after this
+    /// provider rejects the password. Return `false` for accounts the
+    /// provider owns outright.
+    fn allows_local_password(&self, _user_id: &str) -> bool {
+        true
+    }
+
+    /// Verify `context.password`. A valid output may name the canonical
+    /// `user_id` to log in as; otherwise `context.user_id` is used.
+    async fn check_password(&self, context: &PasswordAuthContext) -> Result<PasswordAuthOutput, ApiError>;
+
+    /// Called after every successful password login, once the device exists.
+    async fn on_logged_in(&self, _user_id: &str, _device_id: &str) -> Result<(), ApiError> {
+        Ok(())
+    }
 }
 
 pub struct ModuleRegistry {
     spam_checkers: Vec<Arc<dyn SpamChecker>>,
     third_party_rules: Vec<Arc<dyn ThirdPartyRule>>,
-    password_providers: Vec<Arc<dyn PasswordAuthProviderTrait>>,
+    password_providers: Vec<Arc<dyn PasswordAuthProvider>>,
 }
 
 impl ModuleRegistry {
@@ -132,7 +156,7 @@ impl ModuleRegistry {
         self.third_party_rules.push(rule);
     }
 
-    pub fn register_password_provider(&mut self, provider: Arc<dyn PasswordAuthProviderTrait>) {
+    pub fn register_password_provider(&mut self, provider: Arc<dyn PasswordAuthProvider>) {
         info!(module_name = %provider.name(), module_type = %"password_provider", "Registering password provider");
         self.password_providers.push(provider);
     }
@@ -145,7 +169,7 @@ impl ModuleRegistry {
         &self.third_party_rules
     }
 
-    pub fn password_providers(&self) -> &[Arc<dyn PasswordAuthProviderTrait>] {
+    pub fn password_providers(&self) -> &[Arc<dyn PasswordAuthProvider>] {
         &self.password_providers
     }
 }
@@ -163,7 +187,16 @@ pub struct ModuleService {
 
 impl ModuleService {
     pub fn new(storage: Arc<dyn synapse_storage::module::ModuleStoreApi>) -> Self {
-        Self { storage, registry: Arc::new(tokio::sync::RwLock::new(ModuleRegistry::new())) }
+        Self::with_registry(storage, Arc::new(tokio::sync::RwLock::new(ModuleRegistry::new())))
+    }
+
+    /// Share `registry` with other consumers, e.g. the password providers
+    /// consulted by `AuthService::login`.
+    pub fn with_registry(
+        storage: Arc<dyn synapse_storage::module::ModuleStoreApi>,
+        registry: Arc<tokio::sync::RwLock<ModuleRegistry>>,
+    ) -> Self {
+        Self { storage, registry }
     }
 
     #[instrument(skip(self))]
@@ -466,11 +499,11 @@ impl ModuleService {
             return Ok(PasswordAuthOutput { valid: false, user_id: None });
         }
 
-        for provider in providers {
+        for provider in providers.iter().filter(|p| p.handles_user(&context.user_id)) {
             let start = Instant::now();
             let provider_name = provider.name().to_string();
 
-            match provider.check(context).await {
+            match provider.check_password(context).await {
                 Ok(output) => {
                     let execution_time = start.elapsed().as_millis() as i64;
 
@@ -542,7 +575,7 @@ impl ModuleService {
         registry.register_third_party_rule(rule);
     }
 
-    pub async fn register_password_provider(&self, provider: Arc<dyn PasswordAuthProviderTrait>) {
+    pub async fn register_password_provider(&self, provider: Arc<dyn PasswordAuthProvider>) {
         let mut registry = self.registry.write().await;
         registry.register_password_provider(provider);
     }
@@ -108,7 +108,7 @@ pub struct OidcService {
 
 impl OidcService {
     pub fn new(config: Arc<OidcConfig>) -> Self {
-        let http_client = reqwest::Client::builder()
+        let http_client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
@@ -147,10 +147,9 @@ impl OpenClawService {
 
     #[::tracing::instrument(skip_all, fields(base_url = %base_url))]
     pub async fn test_connection_health(&self, base_url: &str) -> bool {
-        use reqwest::Client;
         use std::time::Duration;
 
-        let client = match Client::builder().timeout(Duration::from_secs(5)).build() {
+        let client = match synapse_common::outbound_http::client_builder().timeout(Duration::from_secs(5)).build() {
             Ok(c) => c,
             Err(_) => return false,
         };
@@ -0,0 +1,369 @@
+//! Moderation policy lists: follows `m.policy.rule.*` state in the rooms
+//! configured under `policy_lists` and enforces their `m.ban`
+//! recommendations, the server-side counterpart of Mjolnir.
+//!
+//! User rules ban matching members of the protected rooms; server rules add
+//! matching servers to the federation blacklist. Rules are re-read on every
+//! [`PolicyListService::sync`], which runs periodically and whenever a rule
+//! event lands in a policy room.
+
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use serde_json::Value;
+use synapse_common::config::{glob_matches, PolicyListsConfig};
+use synapse_common::error::{ApiError, ApiResult};
+use synapse_storage::federation_blacklist::CreateRuleRequest;
+use tokio::sync::{Mutex, RwLock};
+
+use crate::federation_blacklist_service::{AddBlacklistRequest, FederationBlacklistService};
+use crate::room::RoomServiceApi;
+
+/// Rule event types, including the names used before MSC2313 was merged.
+const USER_RULE_TYPES: &[&str] = &["m.policy.rule.user", "m.room.rule.user", "org.matrix.mjolnir.rule.user"];
+const SERVER_RULE_TYPES: &[&str] = &["m.policy.rule.server", "m.room.rule.server", "org.matrix.mjolnir.rule.server"];
+const BAN_RECOMMENDATIONS: &[&str] = &["m.ban", "org.matrix.mjolnir.ban"];
+
+/// Prefix of federation blacklist rules created from glob server entities.
+const BLACKLIST_RULE_PREFIX: &str = "policy_list:";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PolicyEntityKind {
+    User,
+    Server,
+}
+
+/// An `m.ban` recommendation from a policy room.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyRule {
+    pub kind: PolicyEntityKind,
+    /// Glob over user IDs or server names.
+    pub entity: String,
+    pub reason: Option<String>,
+    pub source_room: String,
+}
+
+impl PolicyRule {
+    /// Parse a rule state event; `None` for other types, other
+    /// recommendations and rules removed by emptying their content.
+    pub fn parse(event_type: &str, source_room: &str, content: &Value) -> Option<Self> {
+        let kind = if USER_RULE_TYPES.contains(&event_type) {
+            PolicyEntityKind::User
+        } else if SERVER_RULE_TYPES.contains(&event_type) {
+            PolicyEntityKind::Server
+        } else {
+            return None;
+        };
+        let recommendation = content.get("recommendation").and_then(Value::as_str)?;
+        if !BAN_RECOMMENDATIONS.contains(&recommendation) {
+            return None;
+        }
+        let entity = content.get("entity").and_then(Value::as_str).map(str::trim).filter(|e| !e.is_empty())?;
+
+        Some(Self {
+            kind,
+            entity: entity.to_string(),
+            reason: content.get("reason").and_then(Value::as_str).map(str::to_string),
+            source_room: source_room.to_string(),
+        })
+    }
+
+    pub fn matches(&self, value: &str) -> bool {
+        glob_matches(&self.entity, value)
+    }
+
+    fn is_glob(&self) -> bool {
+        self.entity.contains(['*', '?'])
+    }
+
+    fn ban_reason(&self) -> String {
+        self.reason.clone().unwrap_or_else(|| format!("Banned by policy list {}", self.source_room))
+    }
+}
+
+/// Outcome of one [`PolicyListService::sync`].
+#[derive(Debug, Clone, Default, serde::Serialize)]
+pub struct PolicySyncReport {
+    pub rules: usize,
+    pub users_banned: usize,
+    pub servers_blocked: usize,
+}
+
+pub struct PolicyListService {
+    room_service: Arc<dyn RoomServiceApi>,
+    federation_blacklist_service: Arc<FederationBlacklistService>,
+    server_name: String,
+    config: PolicyListsConfig,
+    rules: RwLock<Vec<PolicyRule>>,
+    sync_lock: Mutex<()>,
+}
+
+impl PolicyListService {
+    pub fn new(
+        room_service: Arc<dyn RoomServiceApi>,
+        federation_blacklist_service: Arc<FederationBlacklistService>,
+        server_name: String,
+        config: PolicyListsConfig,
+    ) -> Self {
+        Self {
+            room_service,
+            federation_blacklist_service,
+            server_name,
+            config,
+            rules: RwLock::new(Vec::new()),
+            sync_lock: Mutex::new(()),
+        }
+    }
+
+    pub fn is_active(&self) -> bool {
+        self.config.is_active()
+    }
+
+    pub fn sync_interval_secs(&self) -> u64 {
+        self.config.sync_interval_secs
+    }
+
+    /// Whether a persisted event changes the rules.
+    pub fn is_rule_event(&self, room_id: &str, event_type: &str) -> bool {
+        self.config.policy_rooms.iter().any(|room| room == room_id)
+            && (USER_RULE_TYPES.contains(&event_type) || SERVER_RULE_TYPES.contains(&event_type))
+    }
+
+    /// Whether a persisted event is a membership change user rules apply to.
+    pub fn is_protected_membership(&self, room_id: &str, event_type: &str) -> bool {
+        self.config.ban_users
+            && event_type == "m.room.member"
+            && self.config.protected_rooms.iter().any(|room| room == room_id)
+    }
+
+    fn moderator(&self) -> Option<String> {
+        self.config.moderator_localpart.as_ref().map(|localpart| format!("@{localpart}:{}", self.server_name))
+    }
+
+    /// Re-read every policy room and apply the rules. Concurrent calls run
+    /// one after another.
+    pub async fn sync(&self) -> ApiResult<PolicySyncReport> {
+        let _guard = self.sync_lock.lock().await;
+
+        let rules = self.load_rules().await;
+        *self.rules.write().await = rules.clone();
+
+        let mut report = PolicySyncReport { rules: rules.len(), ..PolicySyncReport::default() };
+        if self.config.block_servers {
+            report.servers_blocked = self.apply_server_rules(&rules).await?;
+        }
+        if self.config.ban_users {
+            for room_id in &self.config.protected_rooms {
+                report.users_banned += self.enforce_room(room_id, &rules).await;
+            }
+        }
+
+        if report.users_banned > 0 || report.servers_blocked > 0 {
+            ::tracing::info!(
+                rules = report.rules,
+                users_banned = report.users_banned,
+                servers_blocked = report.servers_blocked,
+                "Applied policy list rules"
+            );
+        }
+        Ok(report)
+    }
+
+    /// Apply the current user rules to one member of a protected room, e.g.
+    /// right after they joined.
+    pub async fn enforce_member(&self, room_id: &str, user_id: &str) {
+        let rule = self
+            .rules
+            .read()
+            .await
+            .iter()
+            .find(|rule| rule.kind == PolicyEntityKind::User && rule.matches(user_id))
+            .cloned();
+        if let Some(rule) = rule {
+            self.ban_if_present(room_id, user_id, &rule).await;
+        }
+    }
+
+    async fn load_rules(&self) -> Vec<PolicyRule> {
+        let mut rules = Vec::new();
+        for room_id in &self.config.policy_rooms {
+            for event_type in USER_RULE_TYPES.iter().chain(SERVER_RULE_TYPES) {
+                match self.room_service.messaging().get_state_events_by_type(room_id, event_type).await {
+                    Ok(events) => rules.extend(events.iter().filter_map(|event| {
+                        PolicyRule::parse(event_type, room_id, event.get("content").unwrap_or(&Value::Null))
+                    })),
+                    Err(e) => {
+                        ::tracing::warn!(room_id = %room_id, event_type, error = %e, "Failed to read policy rules");
+                    }
+                }
+            }
+        }
+        rules
+    }
+
+    async fn apply_server_rules(&self, rules: &[PolicyRule]) -> Result<usize, ApiError> {
+        let existing: HashSet<String> =
+            self.federation_blacklist_service.get_rules().await?.into_iter().map(|rule| rule.rule_name).collect();
+
+        let mut blocked = 0;
+        for rule in rules.iter().filter(|rule| rule.kind == PolicyEntityKind::Server) {
+            if rule.matches(&self.server_name) {
+                ::tracing::warn!(entity = %rule.entity, source_room = %rule.source_room, "Ignoring policy rule that matches this server");
+                continue;
+            }
+
+            let result = if rule.is_glob() {
+                let rule_name = format!("{BLACKLIST_RULE_PREFIX}{}", rule.entity);
+                if existing.contains(&rule_name) {
+                    continue;
+                }
+                self.federation_blacklist_service
+                    .create_rule(CreateRuleRequest {
+                        rule_name,
+                        rule_type: "regex".to_string(),
+                        pattern: glob_to_regex(&rule.entity),
+                        action: "block".to_string(),
+                        priority: 0,
+                        description: rule.reason.clone(),
+                        created_by: rule.source_room.clone(),
+                    })
+                    .await
+                    .map(|_| ())
+            } else {
+                if self.federation_blacklist_service.check_server(&rule.entity).await?.is_blocked {
+                    continue;
+                }
+                self.federation_blacklist_service
+                    .add_to_blacklist(
+                        AddBlacklistRequest {
+                            server_name: rule.entity.clone(),
+                            block_type: "blacklist".to_string(),
+                            reason: Some(rule.ban_reason()),
+                            expires_in_days: None,
+                        },
+                        &rule.source_room,
+                    )
+                    .await
+                    .map(|_| ())
+            };
+
+            match result {
+                Ok(()) => blocked += 1,
+                Err(e) => {
+                    ::tracing::warn!(entity = %rule.entity, error = %e, "Failed to block server from policy list")
+                }
+            }
+        }
+        Ok(blocked)
+    }
+
+    async fn enforce_room(&self, room_id: &str, rules: &[PolicyRule]) -> usize {
+        let mut banned = 0;
+        for membership in ["join", "invite", "knock"] {
+            let members = match self.room_service.membership().get_room_members_by_membership(room_id, membership).await
+            {
+                Ok(members) => members,
+                Err(e) => {
+                    ::tracing::warn!(room_id = %room_id, error = %e, "Failed to list members of protected room");
+                    continue;
+                }
+            };
+            for member in members {
+                let rule =
+                    rules.iter().find(|rule| rule.kind == PolicyEntityKind::User && rule.matches(&member.user_id));
+                if let Some(rule) = rule {
+                    if self.ban(room_id, &member.user_id, rule).await {
+                        banned += 1;
+                    }
+                }
+            }
+        }
+        banned
+    }
+
+    async fn ban_if_present(&self, room_id: &str, user_id: &str, rule: &PolicyRule) {
+        match self.room_service.membership().get_room_membership(room_id, user_id).await {
+            Ok(Some(membership)) if matches!(membership.as_str(), "join" | "invite" | "knock") => {
+                self.ban(room_id, user_id, rule).await;
+            }
+            Ok(_) => {}
+            Err(e) => ::tracing::warn!(room_id = %room_id, user_id = %user_id, error = %e, "Failed to read membership"),
+        }
+    }
+
+    async fn ban(&self, room_id: &str, user_id: &str, rule: &PolicyRule) -> bool {
+        let Some(moderator) = self.moderator() else {
+            ::tracing::warn!(room_id = %room_id, "policy_lists.moderator_localpart is not set; cannot ban");
+            return false;
+        };
+        if moderator == user_id {
+            return false;
+        }
+
+        let reason = rule.ban_reason();
+        match self.room_service.membership().ban_user(room_id, user_id, &moderator, Some(&reason)).await {
+            Ok(()) => {
+                ::tracing::info!(room_id = %room_id, user_id = %user_id, entity = %rule.entity, "Banned user from policy list");
+                true
+            }
+            Err(e) => {
+                ::tracing::warn!(room_id = %room_id, user_id = %user_id, error = %e, "Failed to apply policy list ban");
+                false
+            }
+        }
+    }
+}
+
+/// Anchored regex for a glob, for the federation blacklist's regex rules.
+fn glob_to_regex(glob: &str) -> String {
+    format!("^{}$", regex::escape(glob).replace(r"\*", ".*").replace(r"\?", "."))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn parse_accepts_ban_rules_of_known_types() {
+        let rule = PolicyRule::parse(
+            "m.policy.rule.user",
+            "!list:example.com",
+            &json!({"entity": "@spam*:example.org", "recommendation": "m.ban", "reason": "spam"}),
+        )
+        .expect("ban rule should parse");
+        assert_eq!(rule.kind, PolicyEntityKind::User);
+        assert!(rule.matches("@spammer:example.org"));
+        assert!(!rule.matches("@alice:example.org"));
+
+        let legacy = PolicyRule::parse(
+            "org.matrix.mjolnir.rule.server",
+            "!list:example.com",
+            &json!({"entity": "evil.example", "recommendation": "org.matrix.mjolnir.ban"}),
+        )
+        .expect("legacy rule should parse");
+        assert_eq!(legacy.kind, PolicyEntityKind::Server);
+        assert_eq!(legacy.ban_reason(), "Banned by policy list !list:example.com");
+    }
+
+    #[test]
+    fn parse_skips_removed_and_unknown_rules() {
+        assert!(PolicyRule::parse("m.policy.rule.user", "!r:hs", &json!({})).is_none());
+        assert!(PolicyRule::parse(
+            "m.policy.rule.user",
+            "!r:hs",
+            &json!({"entity": "@a:hs", "recommendation": "m.mute"})
+        )
+        .is_none());
+        assert!(PolicyRule::parse("m.room.message", "!r:hs", &json!({"entity": "@a:hs", "recommendation": "m.ban"}))
+            .is_none());
+    }
+
+    #[test]
+    fn glob_to_regex_escapes_literals() {
+        let re = regex::Regex::new(&glob_to_regex("*.evil.example")).expect("valid regex");
+        assert!(re.is_match("chat.evil.example"));
+        assert!(!re.is_match("chat.evilxexample"));
+        assert!(!re.is_match("evil.example.org"));
+    }
+}
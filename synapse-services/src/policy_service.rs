@@ -51,7 +51,7 @@ pub struct PolicyService {
 
 impl PolicyService {
     pub fn new(config: PolicyServerConfig) -> Self {
-        let client = reqwest::Client::builder()
+        let client = synapse_common::outbound_http::client_builder()
             .timeout(std::time::Duration::from_secs(config.timeout_secs))
             .build()
             .unwrap_or_default();
@@ -80,8 +80,9 @@ pub struct PushGateway {
 
 impl PushGateway {
     pub fn new(config: &PushGatewayConfig) -> Self {
-        let client = Client::builder()
+        let client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout_secs))
+            .redirect(reqwest::redirect::Policy::none())
             .build()
             .unwrap_or_else(|_| Client::new());
 
@@ -95,6 +96,10 @@ impl PushGateway {
     ) -> Result<PushGatewayResponse, ApiError> {
         info!(has_gateway_url = !gateway_url.is_empty(), "Sending notification to push gateway");
 
+        synapse_common::egress::check_url(gateway_url)
+            .await
+            .map_err(|e| ApiError::forbidden(format!("Push gateway URL not allowed: {e}")))?;
+
         let response = self
             .client
             .post(gateway_url)
@@ -3,9 +3,17 @@ use async_trait::async_trait;
 use jsonwebtoken::{encode, Algorithm, EncodingKey, Header};
 use reqwest::Client;
 use serde::Serialize;
+use std::sync::Mutex;
 use std::time::Duration;
 use tracing::{debug, error, info};
 
+/// Provider tokens are valid for an hour and APNs rejects refreshes more
+/// often than every 20 minutes, so a signed JWT is reused for 50 minutes.
+const APNS_JWT_REFRESH_SECS: i64 = 50 * 60;
+
+/// Longest `apns-collapse-id` APNs accepts, in bytes.
+const APNS_MAX_COLLAPSE_ID_LEN: usize = 64;
+
 #[derive(Debug, Clone)]
 pub struct ApnsProviderConfig {
     pub topic: String,
@@ -42,7 +50,8 @@ struct ApnsPayload {
 
 #[derive(Debug, Clone, Serialize)]
 struct ApnsAps {
-    alert: ApnsAlert,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    alert: Option<ApnsAlert>,
     #[serde(skip_serializing_if = "Option::is_none")]
     badge: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -70,18 +79,20 @@ pub struct ApnsProvider {
     config: ApnsProviderConfig,
     client: Client,
     enabled: bool,
+    /// Last signed provider token and its `iat`.
+    jwt_cache: Mutex<Option<(String, i64)>>,
 }
 
 impl ApnsProvider {
     pub fn new(config: ApnsProviderConfig) -> Self {
         let enabled = !config.topic.is_empty();
 
-        let client = Client::builder()
+        let client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout_secs))
             .build()
             .unwrap_or_else(|_| Client::new());
 
-        Self { config, client, enabled }
+        Self { config, client, enabled, jwt_cache: Mutex::new(None) }
     }
 
     pub fn with_topic(topic: String) -> Self {
@@ -89,12 +100,58 @@ impl ApnsProvider {
         Self::new(config)
     }
 
+    /// Build a token-authenticated provider from the `push.apns` config,
+    /// reading the `.p8` signing key from `private_key_path`.
+    pub fn from_config(config: &synapse_common::config::ApnsConfig, timeout_secs: u64) -> Result<Self, String> {
+        let (Some(key_id), Some(team_id), Some(key_path)) = (&config.key_id, &config.team_id, &config.private_key_path)
+        else {
+            return Err("APNS token auth requires key_id, team_id and private_key_path".to_string());
+        };
+        let private_key =
+            std::fs::read_to_string(key_path).map_err(|e| format!("Failed to read APNS key {key_path}: {e}"))?;
+        EncodingKey::from_ec_pem(private_key.as_bytes()).map_err(|e| format!("Invalid APNS .p8 key: {e}"))?;
+
+        let defaults = if config.production { ApnsProviderConfig::default() } else { ApnsProviderConfig::sandbox() };
+        Ok(Self::new(ApnsProviderConfig {
+            topic: config.topic.clone(),
+            endpoint: config.endpoint.clone().unwrap_or(defaults.endpoint),
+            key_id: Some(key_id.clone()),
+            team_id: Some(team_id.clone()),
+            private_key: Some(private_key),
+            timeout_secs,
+        }))
+    }
+
+    /// `apns-collapse-id` for `payload`: APNs keeps only the newest pending
+    /// notification per id, so pushes for the same room (or tag) coalesce.
+    fn collapse_id(payload: &NotificationPayload) -> Option<&str> {
+        payload
+            .tag
+            .as_deref()
+            .or(payload.room_id.as_deref())
+            .filter(|id| !id.is_empty() && id.len() <= APNS_MAX_COLLAPSE_ID_LEN)
+    }
+
+    /// Whether an APNs error means the device token is dead and its pusher
+    /// should be removed rather than retried.
+    fn is_unregistered_error(error: &str) -> bool {
+        error.contains("410") || error.contains("Unregistered") || error.contains("BadDeviceToken")
+    }
+
     fn build_payload(payload: &NotificationPayload) -> ApnsPayload {
         let badge = payload.counts.as_ref().map(|c| c.unread);
 
+        // A badge-only update must not carry an alert: iOS would otherwise
+        // show an empty banner and wake the notification service extension.
+        if payload.is_badge_only() {
+            return ApnsPayload {
+                aps: ApnsAps { alert: None, badge, sound: None, content_available: None, mutable_content: None },
+            };
+        }
+
         ApnsPayload {
             aps: ApnsAps {
-                alert: ApnsAlert { title: payload.title.clone(), body: payload.body.clone() },
+                alert: Some(ApnsAlert { title: payload.title.clone(), body: payload.body.clone() }),
                 badge,
                 sound: payload.sound.clone(),
                 content_available: Some(1),
@@ -103,6 +160,21 @@ impl ApnsProvider {
         }
     }
 
+    /// The cached provider token, re-signed once it is older than
+    /// [`APNS_JWT_REFRESH_SECS`].
+    fn provider_token(&self) -> Result<String, String> {
+        let now = chrono::Utc::now().timestamp();
+        let mut cache = self.jwt_cache.lock().unwrap_or_else(|e| e.into_inner());
+        if let Some((jwt, issued_at)) = cache.as_ref() {
+            if now - issued_at < APNS_JWT_REFRESH_SECS {
+                return Ok(jwt.clone());
+            }
+        }
+        let jwt = self.generate_jwt()?;
+        *cache = Some((jwt.clone(), now));
+        Ok(jwt)
+    }
+
     fn generate_jwt(&self) -> Result<String, String> {
         let key_id = self.config.key_id.clone().ok_or_else(|| "APNS key_id not configured".to_string())?;
         let team_id = self.config.team_id.clone().ok_or_else(|| "APNS team_id not configured".to_string())?;
@@ -125,23 +197,24 @@ impl ApnsProvider {
         encode(&header, &claims, &encoding_key).map_err(|e| format!("Failed to sign APNS JWT: {e}"))
     }
 
-    async fn send_request(&self, token: &str, payload: &ApnsPayload) -> Result<(), String> {
+    async fn send_request(&self, token: &str, payload: &ApnsPayload, collapse_id: Option<&str>) -> Result<(), String> {
         let url = format!("{}/3/device/{}", self.config.endpoint, token);
 
-        let jwt = self.generate_jwt()?;
+        let jwt = self.provider_token()?;
 
-        let response = self
+        let mut request = self
             .client
             .post(&url)
             .header("authorization", format!("bearer {jwt}"))
             .header("apns-topic", &self.config.topic)
             .header("apns-push-type", "alert")
             .header("apns-priority", "10")
-            .header("content-type", "application/json")
-            .json(payload)
-            .send()
-            .await
-            .map_err(|e| format!("HTTP request failed: {e}"))?;
+            .header("content-type", "application/json");
+        if let Some(collapse_id) = collapse_id {
+            request = request.header("apns-collapse-id", collapse_id);
+        }
+
+        let response = request.json(payload).send().await.map_err(|e| format!("HTTP request failed: {e}"))?;
 
         let status = response.status();
 
@@ -183,7 +256,7 @@ impl PushProvider for ApnsProvider {
 
         let apns_payload = Self::build_payload(payload);
 
-        match self.send_request(token, &apns_payload).await {
+        match self.send_request(token, &apns_payload, Self::collapse_id(payload)).await {
             Ok(_) => {
                 debug!(
                     title_present = !payload.title.is_empty(),
@@ -200,7 +273,9 @@ impl PushProvider for ApnsProvider {
 
                 error!(%e, title_present = !payload.title.is_empty(), room_id = payload.room_id, event_id = payload.event_id, "APNS push error");
 
-                if should_retry {
+                if Self::is_unregistered_error(&e) {
+                    PushResult::unregistered(&e)
+                } else if should_retry {
                     PushResult::retryable_failure(&e)
                 } else {
                     PushResult::failure(&e)
@@ -279,11 +354,18 @@ v8PGbBpPXRyuIyQoooKWcdokN62hRANCAASrFgTXKOydK6UzmGQ/iGevi9IZWynS\n\
         };
 
         let apns_payload = ApnsProvider::build_payload(&payload);
-        assert_eq!(apns_payload.aps.alert.title, "Test");
+        assert_eq!(apns_payload.aps.alert.as_ref().map(|a| a.title.as_str()), Some("Test"));
         assert_eq!(apns_payload.aps.badge, Some(5));
         assert_eq!(apns_payload.aps.sound, Some("default".to_string()));
     }
 
+    #[test]
+    fn test_build_payload_badge_only_omits_alert() {
+        let apns_payload = ApnsProvider::build_payload(&NotificationPayload::badge_only(3));
+        let json = serde_json::to_value(&apns_payload).unwrap();
+        assert_eq!(json, serde_json::json!({"aps": {"badge": 3}}));
+    }
+
     #[test]
     fn test_generate_jwt_signs_real_token() {
         let provider = ApnsProvider::new(ApnsProviderConfig {
@@ -302,6 +384,63 @@ v8PGbBpPXRyuIyQoooKWcdokN62hRANCAASrFgTXKOydK6UzmGQ/iGevi9IZWynS\n\
         assert!(!jwt.contains("placeholder"));
     }
 
+    #[test]
+    fn test_provider_token_is_cached() {
+        let provider = ApnsProvider::new(ApnsProviderConfig {
+            topic: "com.example.app".to_string(),
+            key_id: Some("ABC123DEFG".to_string()),
+            team_id: Some("TEAM123456".to_string()),
+            private_key: Some(TEST_EC_PRIVATE_KEY.to_string()),
+            ..Default::default()
+        });
+
+        let first = provider.provider_token().unwrap();
+        assert_eq!(provider.provider_token().unwrap(), first);
+    }
+
+    #[test]
+    fn test_from_config_reads_p8_key() {
+        let key_path = std::env::temp_dir().join(format!("apns_test_{}.p8", std::process::id()));
+        std::fs::write(&key_path, TEST_EC_PRIVATE_KEY).unwrap();
+        let config = synapse_common::config::ApnsConfig {
+            cert_file: None,
+            key_file: None,
+            topic: "com.example.app".to_string(),
+            production: false,
+            key_id: Some("ABC123DEFG".to_string()),
+            team_id: Some("TEAM123456".to_string()),
+            private_key_path: Some(key_path.to_string_lossy().into_owned()),
+            endpoint: None,
+        };
+
+        let provider = ApnsProvider::from_config(&config, 10).unwrap();
+        std::fs::remove_file(&key_path).ok();
+        assert_eq!(provider.endpoint(), "https://api.sandbox.push.apple.com");
+        assert!(provider.generate_jwt().is_ok());
+
+        let missing = synapse_common::config::ApnsConfig { private_key_path: None, ..config };
+        assert!(ApnsProvider::from_config(&missing, 10).is_err());
+    }
+
+    #[test]
+    fn test_collapse_id_prefers_tag_then_room() {
+        let mut payload = NotificationPayload::badge_only(1);
+        assert_eq!(ApnsProvider::collapse_id(&payload), None);
+        payload.room_id = Some("!room:example.org".to_string());
+        assert_eq!(ApnsProvider::collapse_id(&payload), Some("!room:example.org"));
+        payload.tag = Some("thread".to_string());
+        assert_eq!(ApnsProvider::collapse_id(&payload), Some("thread"));
+        payload.tag = Some("x".repeat(APNS_MAX_COLLAPSE_ID_LEN + 1));
+        assert_eq!(ApnsProvider::collapse_id(&payload), None);
+    }
+
+    #[test]
+    fn test_unregistered_errors() {
+        assert!(ApnsProvider::is_unregistered_error("APNS error: 410 Gone - Unregistered"));
+        assert!(ApnsProvider::is_unregistered_error("APNS error: 400 Bad Request - BadDeviceToken"));
+        assert!(!ApnsProvider::is_unregistered_error("APNS error: 503 Service Unavailable - ServiceUnavailable"));
+    }
+
     #[tokio::test]
     async fn test_send_when_disabled() {
         let config = ApnsProviderConfig::default();
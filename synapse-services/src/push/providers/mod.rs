@@ -116,23 +116,49 @@ pub struct PushResult {
     pub error: Option<String>,
     pub provider_response: Option<String>,
     pub should_retry: bool,
+    /// The provider reported the device token as no longer registered; the
+    /// device should be pruned.
+    #[serde(rename = "unregistered", default)]
+    pub is_unregistered: bool,
 }
 
 impl PushResult {
     pub fn success() -> Self {
-        Self { is_success: true, error: None, provider_response: None, should_retry: false }
+        Self { is_success: true, error: None, provider_response: None, should_retry: false, is_unregistered: false }
     }
 
     pub fn success_with_response(response: &str) -> Self {
-        Self { is_success: true, error: None, provider_response: Some(response.to_string()), should_retry: false }
+        Self {
+            is_success: true,
+            error: None,
+            provider_response: Some(response.to_string()),
+            should_retry: false,
+            is_unregistered: false,
+        }
     }
 
     pub fn failure(error: &str) -> Self {
-        Self { is_success: false, error: Some(error.to_string()), provider_response: None, should_retry: false }
+        Self {
+            is_success: false,
+            error: Some(error.to_string()),
+            provider_response: None,
+            should_retry: false,
+            is_unregistered: false,
+        }
+    }
+
+    pub fn unregistered(error: &str) -> Self {
+        Self { is_unregistered: true, ..Self::failure(error) }
     }
 
     pub fn retryable_failure(error: &str) -> Self {
-        Self { is_success: false, error: Some(error.to_string()), provider_response: None, should_retry: true }
+        Self {
+            is_success: false,
+            error: Some(error.to_string()),
+            provider_response: None,
+            should_retry: true,
+            is_unregistered: false,
+        }
     }
 }
 
@@ -152,6 +178,32 @@ pub struct NotificationPayload {
     pub counts: Option<NotificationCounts>,
 }
 
+impl NotificationPayload {
+    /// A payload without alert text that only carries the recipient's total
+    /// unread count, so devices can update their badge after a read receipt.
+    pub fn badge_only(unread: u32) -> Self {
+        Self {
+            title: String::new(),
+            body: String::new(),
+            icon: None,
+            badge: Some(unread.to_string()),
+            sound: None,
+            tag: None,
+            data: serde_json::json!({ "unread_count": unread }),
+            event_id: None,
+            room_id: None,
+            room_name: None,
+            sender: None,
+            counts: Some(NotificationCounts { unread, missed_calls: 0 }),
+        }
+    }
+
+    /// Whether the payload has no text to display and only updates counts.
+    pub fn is_badge_only(&self) -> bool {
+        self.title.is_empty() && self.body.is_empty()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct NotificationCounts {
     pub unread: u32,
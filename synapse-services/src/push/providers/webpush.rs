@@ -66,7 +66,7 @@ impl WebPushProvider {
     pub fn new(config: WebPushProviderConfig) -> Self {
         let enabled = !config.vapid_public_key.is_empty() && !config.vapid_private_key.is_empty();
 
-        let client = Client::builder()
+        let client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout_secs))
             .build()
             .unwrap_or_else(|_| Client::new());
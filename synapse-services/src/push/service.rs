@@ -6,8 +6,9 @@ use super::providers::{
 use super::queue::{PushQueue, QueueConfig};
 use serde_json::Value as JsonValue;
 use std::sync::Arc;
-use std::time::Instant;
+use std::time::{Duration, Instant};
 use synapse_common::error::ApiError;
+use synapse_common::metrics::MetricsCollector;
 use synapse_storage::push_notification::*;
 use tracing::info;
 
@@ -22,6 +23,9 @@ pub struct PushNotificationService {
     /// Optional account_data storage for looking up `m.ignored_user_list`
     /// so that push notifications from ignored users are suppressed.
     account_data_storage: Option<Arc<dyn synapse_storage::account_data::AccountDataStoreApi>>,
+    /// Per-provider delivery counters and latency, exported with the server
+    /// metrics.
+    metrics: Option<Arc<MetricsCollector>>,
 }
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -69,6 +73,7 @@ impl PushNotificationService {
             push_gateway: None,
             queue: None,
             account_data_storage: None,
+            metrics: None,
         }
     }
 
@@ -110,6 +115,13 @@ impl PushNotificationService {
         self
     }
 
+    /// Record `push_provider_<provider>_{success,failure,unregistered}_total`
+    /// and `push_provider_<provider>_latency_ms` for every provider delivery.
+    pub fn with_metrics(mut self, metrics: Arc<MetricsCollector>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
     pub async fn initialize_providers(&mut self) -> Result<(), ApiError> {
         let fcm_enabled = self.storage.get_config_as_bool("fcm.enabled", false).await?;
         if fcm_enabled {
@@ -231,6 +243,33 @@ impl PushNotificationService {
         Ok(())
     }
 
+    /// Push the user's total unread count to every registered device without
+    /// any alert text, so badges clear as soon as messages are read elsewhere.
+    /// Devices whose provider is not configured are skipped. Returns the
+    /// number of devices that accepted the update.
+    pub async fn send_badge_update(&self, user_id: &str, unread: u32) -> Result<usize, ApiError> {
+        let devices = self.storage.get_user_devices(user_id).await?;
+        let payload = ProviderPayload::badge_only(unread);
+        let mut delivered = 0;
+
+        for device in devices {
+            let Some(result) = self.deliver(&device.push_type, &device.push_token, &payload).await else {
+                continue;
+            };
+
+            if result.is_success {
+                delivered += 1;
+            } else if result.is_unregistered {
+                self.prune_device(user_id, &device.device_id).await?;
+            } else if let Some(error) = &result.error {
+                self.storage.record_device_error(user_id, &device.device_id, error).await?;
+            }
+        }
+
+        info!(user_id = %user_id, unread, delivered, "Sent badge-only push update");
+        Ok(delivered)
+    }
+
     pub async fn process_pending_notifications(&self, batch_size: i32) -> Result<u64, ApiError> {
         let notifications = self.storage.get_pending_notifications(batch_size).await?;
         let mut processed = 0u64;
@@ -284,34 +323,20 @@ impl PushNotificationService {
                 .map(|c| NotificationCounts { unread: c.unread, missed_calls: c.missed_calls }),
         };
 
-        let result = match push_type {
-            "fcm" => {
-                if let Some(provider) = &self.fcm_provider {
-                    send_with_retry(provider.as_ref(), &push_token, &provider_payload).await
-                } else {
-                    self.send_fcm_fallback(&push_token, &content).await?
-                }
-            }
-            "apns" => {
-                if let Some(provider) = &self.apns_provider {
-                    send_with_retry(provider.as_ref(), &push_token, &provider_payload).await
-                } else {
-                    self.send_apns_fallback(&push_token, &content).await?
-                }
-            }
-            "webpush" => {
-                if let Some(provider) = &self.webpush_provider {
-                    send_with_retry(provider.as_ref(), &push_token, &provider_payload).await
-                } else {
-                    self.send_webpush_fallback(&push_token, &content).await?
-                }
-            }
-            "upstream" => self.send_upstream(&push_token, &content)?,
-            _ => return Err(ApiError::bad_request("Invalid push type")),
+        let result = match self.deliver(push_type, &push_token, &provider_payload).await {
+            Some(result) => result,
+            None => match push_type {
+                "fcm" => self.send_fcm_fallback(&push_token, &content).await?,
+                "apns" => self.send_apns_fallback(&push_token, &content).await?,
+                "webpush" => self.send_webpush_fallback(&push_token, &content).await?,
+                "upstream" => self.send_upstream(&push_token, &content)?,
+                _ => return Err(ApiError::bad_request("Invalid push type")),
+            },
         };
 
         let response_time_ms = start.elapsed().as_millis() as i32;
         let success = result.is_success;
+        let is_unregistered = result.is_unregistered;
         let error_message = result.error;
         let provider_response = result.provider_response;
 
@@ -341,13 +366,80 @@ impl PushNotificationService {
             self.storage.update_device_last_used(&notification.user_id, &notification.device_id).await?;
             Ok(())
         } else {
-            if let Some(error) = &error_message {
+            if is_unregistered {
+                self.prune_device(&notification.user_id, &notification.device_id).await?;
+            } else if let Some(error) = &error_message {
                 self.storage.record_device_error(&notification.user_id, &notification.device_id, error).await?;
             }
             Err(ApiError::internal(error_message.unwrap_or_else(|| "Push failed".to_string())))
         }
     }
 
+    /// Sends a test notification straight to one device's provider, bypassing
+    /// the queue, and logs the provider's full response.
+    pub async fn send_test_notification(&self, user_id: &str, device_id: &str) -> Result<PushResult, ApiError> {
+        let device = self
+            .storage
+            .get_device(user_id, device_id)
+            .await?
+            .ok_or_else(|| ApiError::not_found("Device not found"))?;
+        let payload = ProviderPayload {
+            title: "Test notification".to_string(),
+            body: "This is a test notification from your homeserver".to_string(),
+            icon: None,
+            badge: None,
+            sound: None,
+            tag: None,
+            data: serde_json::json!({}),
+            event_id: None,
+            room_id: None,
+            room_name: None,
+            sender: None,
+            counts: None,
+        };
+
+        let result = self
+            .deliver(&device.push_type, &device.push_token, &payload)
+            .await
+            .ok_or_else(|| ApiError::bad_request(format!("No {} push provider is configured", device.push_type)))?;
+
+        info!(
+            user_id = %user_id,
+            device_id = %device_id,
+            provider = %device.push_type,
+            success = result.is_success,
+            unregistered = result.is_unregistered,
+            error = ?result.error,
+            provider_response = ?result.provider_response,
+            "Sent test push notification"
+        );
+        Ok(result)
+    }
+
+    /// Sends `payload` through the configured provider for `push_type` and
+    /// records its delivery metrics. `None` when no such provider is
+    /// configured.
+    async fn deliver(&self, push_type: &str, token: &str, payload: &ProviderPayload) -> Option<PushResult> {
+        let start = Instant::now();
+        let result = match push_type {
+            "fcm" => send_with_retry(self.fcm_provider.as_deref()?, token, payload).await,
+            "apns" => send_with_retry(self.apns_provider.as_deref()?, token, payload).await,
+            "webpush" => send_with_retry(self.webpush_provider.as_deref()?, token, payload).await,
+            _ => return None,
+        };
+        if let Some(metrics) = &self.metrics {
+            record_delivery(metrics, push_type, &result, start.elapsed());
+        }
+        Some(result)
+    }
+
+    /// Drop a device whose push token the provider no longer accepts, so it
+    /// is not pushed to again.
+    async fn prune_device(&self, user_id: &str, device_id: &str) -> Result<(), ApiError> {
+        info!(user_id = %user_id, device_id = %device_id, "Pruning unregistered push device");
+        self.storage.unregister_device(user_id, device_id).await
+    }
+
     async fn send_fcm_fallback(&self, token: &str, _payload: &NotificationPayload) -> Result<PushResult, ApiError> {
         let enabled = self.storage.get_config_as_bool("fcm.enabled", false).await?;
 
@@ -490,8 +582,14 @@ impl PushNotificationService {
             }
         }
 
-        let rules = self.storage.get_user_push_rules(user_id).await?;
+        let mut rules = self.storage.get_user_push_rules(user_id).await?;
+        rules.sort_by_key(|rule| kind_rank(&rule.kind));
+        Self::evaluate_rules(rules, event)
+    }
 
+    /// Evaluate already-loaded `rules` in order against `event`; the first
+    /// matching rule decides the outcome.
+    pub fn evaluate_rules(rules: Vec<PushRule>, event: &JsonValue) -> Result<PushRuleResult, ApiError> {
         let mut tweaks = serde_json::json!({});
 
         for rule in rules {
@@ -529,6 +627,21 @@ impl PushNotificationService {
     }
 
     pub(crate) fn matches_rule(rule: &PushRule, event: &JsonValue) -> Result<bool, ApiError> {
+        // `content`, `room` and `sender` rules carry their condition implicitly
+        // in the pattern or rule ID rather than in `conditions`.
+        match rule.kind.as_str() {
+            "content" => {
+                let Some(pattern) = rule.pattern.as_deref() else {
+                    return Ok(false);
+                };
+                let body = Self::get_event_value(event, "content.body");
+                return Ok(body.is_some_and(|body| glob_matches(pattern, body, true)));
+            }
+            "room" => return Ok(Self::get_event_value(event, "room_id") == Some(rule.rule_id.as_str())),
+            "sender" => return Ok(Self::get_event_value(event, "sender") == Some(rule.rule_id.as_str())),
+            _ => {}
+        }
+
         let conditions: Vec<JsonValue> = serde_json::from_value(rule.conditions.clone())
             .map_err(|e| ApiError::internal_with_log("Invalid conditions", &e))?;
 
@@ -572,7 +685,7 @@ impl PushNotificationService {
         let pattern = condition.get("pattern").and_then(|p| p.as_str()).unwrap_or("");
 
         let value = Self::get_event_value(event, key);
-        value.is_some_and(|v| v.contains(pattern))
+        value.is_some_and(|v| glob_matches(pattern, v, key == "content.body"))
     }
 
     fn matches_contains_display_name(_event: &JsonValue) -> bool {
@@ -603,11 +716,71 @@ impl PushNotificationService {
     }
 }
 
+/// Evaluation order of the rule kinds defined by the push rules spec.
+fn kind_rank(kind: &str) -> u8 {
+    match kind {
+        "override" => 0,
+        "content" => 1,
+        "room" => 2,
+        "sender" => 3,
+        _ => 4,
+    }
+}
+
+/// Case-insensitive glob match where `*` matches any run of characters and
+/// `?` a single character. `content.body` patterns match whole words anywhere
+/// in the body; every other key must match the entire value.
+fn glob_matches(pattern: &str, value: &str, word_boundary: bool) -> bool {
+    let mut expr = String::with_capacity(pattern.len() + 16);
+    for c in pattern.chars() {
+        match c {
+            '*' => expr.push_str(".*?"),
+            '?' => expr.push('.'),
+            c => expr.push_str(&regex::escape(c.encode_utf8(&mut [0; 4]))),
+        }
+    }
+    let expr = if word_boundary { format!(r"(?i)(^|\W){expr}(\W|$)") } else { format!("(?is)^{expr}$") };
+    regex::Regex::new(&expr).is_ok_and(|re| re.is_match(value))
+}
+
+fn record_delivery(metrics: &MetricsCollector, provider: &str, result: &PushResult, elapsed: Duration) {
+    let outcome = if result.is_success {
+        "success"
+    } else if result.is_unregistered {
+        "unregistered"
+    } else {
+        "failure"
+    };
+    let counter_name = format!("push_provider_{provider}_{outcome}_total");
+    metrics.get_counter(&counter_name).unwrap_or_else(|| metrics.register_counter(counter_name)).inc();
+
+    let latency_name = format!("push_provider_{provider}_latency_ms");
+    metrics
+        .get_histogram(&latency_name)
+        .unwrap_or_else(|| metrics.register_histogram(latency_name))
+        .observe(elapsed.as_secs_f64() * 1000.0);
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use serde_json::json;
 
+    #[test]
+    fn record_delivery_counts_outcomes_per_provider() {
+        let metrics = MetricsCollector::new();
+        record_delivery(&metrics, "apns", &PushResult::success(), Duration::from_millis(12));
+        record_delivery(&metrics, "apns", &PushResult::unregistered("Unregistered"), Duration::ZERO);
+        record_delivery(&metrics, "fcm", &PushResult::failure("timeout"), Duration::ZERO);
+
+        let count = |name: &str| metrics.get_counter(name).map(|c| c.get());
+        assert_eq!(count("push_provider_apns_success_total"), Some(1));
+        assert_eq!(count("push_provider_apns_unregistered_total"), Some(1));
+        assert_eq!(count("push_provider_apns_failure_total"), None);
+        assert_eq!(count("push_provider_fcm_failure_total"), Some(1));
+        assert_eq!(metrics.get_histogram("push_provider_apns_latency_ms").map(|h| h.get_count()), Some(2));
+    }
+
     // -- get_event_value --
 
     #[test]
@@ -710,6 +883,44 @@ mod tests {
         assert!(!PushNotificationService::matches_rule(&rule, &event).unwrap());
     }
 
+    #[test]
+    fn matches_event_match_uses_globs() {
+        let body = json!({"kind": "event_match", "key": "content.body", "pattern": "lunch*"});
+        assert!(PushNotificationService::matches_event_match(&body, &json!({"content": {"body": "Lunchtime?"}})));
+        assert!(!PushNotificationService::matches_event_match(&body, &json!({"content": {"body": "brunch"}})));
+
+        let event_type = json!({"kind": "event_match", "key": "type", "pattern": "m.room.*"});
+        assert!(PushNotificationService::matches_event_match(&event_type, &json!({"type": "m.room.message"})));
+        assert!(!PushNotificationService::matches_event_match(&event_type, &json!({"type": "m.call.invite"})));
+    }
+
+    #[test]
+    fn matches_rule_content_pattern_matches_whole_words() {
+        let mut rule = make_test_rule(json!([]));
+        rule.kind = "content".into();
+        rule.pattern = Some("deploy".into());
+
+        assert!(PushNotificationService::matches_rule(&rule, &json!({"content": {"body": "Deploy is done"}})).unwrap());
+        assert!(!PushNotificationService::matches_rule(&rule, &json!({"content": {"body": "redeployed"}})).unwrap());
+
+        rule.pattern = None;
+        assert!(!PushNotificationService::matches_rule(&rule, &json!({"content": {"body": "deploy"}})).unwrap());
+    }
+
+    #[test]
+    fn evaluate_rules_applies_keyword_sound() {
+        let mut keyword = make_test_rule(json!([]));
+        keyword.kind = "content".into();
+        keyword.pattern = Some("standup".into());
+        keyword.actions = json!(["notify", {"set_tweak": "sound", "value": "bell"}]);
+
+        let result =
+            PushNotificationService::evaluate_rules(vec![keyword], &json!({"content": {"body": "standup now"}}))
+                .unwrap();
+        assert!(result.notify);
+        assert_eq!(result.tweaks["sound"], "bell");
+    }
+
     #[test]
     fn matches_rule_unknown_kind_ignored() {
         let rule = make_test_rule(json!([{"kind": "unknown_kind", "key": "x", "pattern": "y"}]));
@@ -5,6 +5,7 @@ use synapse_common::*;
 
 use std::sync::Arc;
 
+use crate::email_templates::{EmailTemplateKind, EmailTemplates};
 use crate::UserService;
 
 pub struct RegistrationService {
@@ -16,6 +17,7 @@ pub struct RegistrationService {
     base_url: String,
     enable_registration: bool,
     task_queue: Option<Arc<RedisTaskQueue>>,
+    email_templates: Arc<EmailTemplates>,
 }
 
 impl RegistrationService {
@@ -33,7 +35,54 @@ impl RegistrationService {
         // Default to HTTPS for production, can be overridden via environment variable
         let base_url = std::env::var("HOMESERVER_BASE_URL").unwrap_or_else(|_| format!("https://{server_name}"));
 
-        Self { user_service, token_auth, credential_auth, metrics, base_url, enable_registration, task_queue }
+        let email_templates =
+            Arc::new(EmailTemplates::builtin(Default::default(), server_name, &format!("https://{server_name}")));
+
+        Self {
+            user_service,
+            token_auth,
+            credential_auth,
+            metrics,
+            base_url,
+            enable_registration,
+            task_queue,
+            email_templates,
+        }
+    }
+
+    /// Use `email_templates` instead of the built-in English templates.
+    pub fn with_email_templates(mut self, email_templates: Arc<EmailTemplates>) -> Self {
+        self.email_templates = email_templates;
+        self
+    }
+
+    /// Queue the email carrying a verification `token` for `email`. A
+    /// missing task queue only logs, like the welcome email: the token stays
+    /// valid and can still be delivered out of band.
+    pub async fn send_verification_email(
+        &self,
+        kind: EmailTemplateKind,
+        email: &str,
+        token: &str,
+        sid: &str,
+        expires_in_secs: i64,
+        accept_language: Option<&str>,
+    ) -> ApiResult<()> {
+        let Some(queue) = &self.task_queue else {
+            ::tracing::warn!(template = kind.name(), "No task queue configured; verification email not sent");
+            return Ok(());
+        };
+        let expires_in_minutes = (expires_in_secs / 60).to_string();
+        let rendered = self.email_templates.render(
+            kind,
+            accept_language,
+            &[("token", token), ("sid", sid), ("email", email), ("expires_in_minutes", &expires_in_minutes)],
+        );
+        queue
+            .submit(BackgroundJob::SendEmail { to: email.to_string(), subject: rendered.subject, body: rendered.body })
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to enqueue verification email", &e))?;
+        Ok(())
     }
 
     #[::tracing::instrument(
@@ -80,10 +129,15 @@ impl RegistrationService {
 
         // Async Task: Send Welcome Email
         if let Some(queue) = &self.task_queue {
+            let rendered = self.email_templates.render(
+                EmailTemplateKind::Welcome,
+                None,
+                &[("user_id", &user.user_id), ("displayname", displayname.unwrap_or(username))],
+            );
             let email_job = BackgroundJob::SendEmail {
                 to: user.user_id.clone(), // Assuming user_id can be an email or we look it up
-                subject: "Welcome to Synapse!".to_string(),
-                body: format!("Hello {}, welcome to our Matrix server!", displayname.unwrap_or(username)),
+                subject: rendered.subject,
+                body: rendered.body,
             };
 
             if let Err(e) = queue.submit(email_job).await {
@@ -187,9 +241,14 @@ impl RegistrationService {
         Ok(())
     }
 
-    #[::tracing::instrument(skip_all, fields(user_id = %user_id))]
-    pub async fn deactivate_account(&self, user_id: &str) -> ApiResult<()> {
+    /// Deactivate `user_id`; with `erase`, their messages are also pruned
+    /// for other users.
+    #[::tracing::instrument(skip_all, fields(user_id = %user_id, erase = erase))]
+    pub async fn deactivate_account(&self, user_id: &str, erase: bool) -> ApiResult<()> {
         self.credential_auth.deactivate_user(user_id).await?;
+        if erase {
+            self.user_service.mark_erased(user_id).await?;
+        }
         Ok(())
     }
 
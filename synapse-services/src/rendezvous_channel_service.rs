@@ -0,0 +1,177 @@
+//! MSC4108 rendezvous sessions for signing in with a QR code.
+//!
+//! A rendezvous session is a small mailbox that two devices use to exchange
+//! the messages of their end-to-end encrypted secure channel: one device
+//! creates the session and shows its URL in a QR code, the other scans it,
+//! and they take turns replacing the payload. Each write gets a fresh ETag,
+//! and a write must name the ETag it replaces, so neither side can overwrite
+//! a message the other has not read. The server never sees the channel
+//! plaintext.
+//!
+//! Sessions expire [`RENDEZVOUS_SESSION_TTL`] after creation whatever their
+//! activity. They live in the memory of the process that created them, so
+//! every rendezvous request must be routed to the same process.
+
+use std::time::{Duration, Instant};
+
+use moka::ops::compute::Op;
+use moka::sync::Cache;
+use moka::Expiry;
+use synapse_common::current_timestamp_millis;
+
+pub const RENDEZVOUS_SESSION_TTL: Duration = Duration::from_secs(60);
+/// Largest payload a session accepts.
+pub const RENDEZVOUS_MAX_CONTENT_LENGTH: usize = 4096;
+const MAX_RENDEZVOUS_SESSIONS: u64 = 10_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RendezvousChannel {
+    pub content: Vec<u8>,
+    pub content_type: String,
+    pub etag: String,
+    pub last_modified_ms: i64,
+    pub expires_at_ms: i64,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RendezvousWriteError {
+    NotFound,
+    /// The session has been written since the caller last read it.
+    EtagMismatch,
+}
+
+struct CreatedAtExpiry;
+
+impl Expiry<String, RendezvousChannel> for CreatedAtExpiry {
+    fn expire_after_create(&self, _key: &String, _value: &RendezvousChannel, _created_at: Instant) -> Option<Duration> {
+        Some(RENDEZVOUS_SESSION_TTL)
+    }
+}
+
+pub struct RendezvousChannelService {
+    sessions: Cache<String, RendezvousChannel>,
+}
+
+impl Default for RendezvousChannelService {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RendezvousChannelService {
+    pub fn new() -> Self {
+        Self { sessions: Cache::builder().max_capacity(MAX_RENDEZVOUS_SESSIONS).expire_after(CreatedAtExpiry).build() }
+    }
+
+    /// Opens a session holding `content` and returns its id.
+    pub fn create(&self, content: Vec<u8>, content_type: String) -> (String, RendezvousChannel) {
+        let now = current_timestamp_millis();
+        let session_id = uuid::Uuid::new_v4().simple().to_string();
+        let channel = RendezvousChannel {
+            content,
+            content_type,
+            etag: new_etag(),
+            last_modified_ms: now,
+            expires_at_ms: now + RENDEZVOUS_SESSION_TTL.as_millis() as i64,
+        };
+        self.sessions.insert(session_id.clone(), channel.clone());
+        (session_id, channel)
+    }
+
+    pub fn get(&self, session_id: &str) -> Option<RendezvousChannel> {
+        self.sessions.get(session_id).filter(|channel| channel.expires_at_ms > current_timestamp_millis())
+    }
+
+    /// Replaces the payload if the session still carries `if_match`.
+    pub fn update(
+        &self,
+        session_id: &str,
+        if_match: &str,
+        content: Vec<u8>,
+        content_type: String,
+    ) -> Result<RendezvousChannel, RendezvousWriteError> {
+        let now = current_timestamp_millis();
+        let mut outcome = Err(RendezvousWriteError::NotFound);
+        self.sessions.entry_by_ref(session_id).and_compute_with(|entry| {
+            let Some(current) = entry.map(|entry| entry.into_value()).filter(|channel| channel.expires_at_ms > now)
+            else {
+                return Op::Nop;
+            };
+            if current.etag != if_match {
+                outcome = Err(RendezvousWriteError::EtagMismatch);
+                return Op::Nop;
+            }
+            let updated = RendezvousChannel {
+                content,
+                content_type,
+                etag: new_etag(),
+                last_modified_ms: now,
+                expires_at_ms: current.expires_at_ms,
+            };
+            outcome = Ok(updated.clone());
+            Op::Put(updated)
+        });
+        outcome
+    }
+
+    /// Ends the session. Returns whether it existed.
+    pub fn delete(&self, session_id: &str) -> bool {
+        self.sessions.remove(session_id).is_some_and(|channel| channel.expires_at_ms > current_timestamp_millis())
+    }
+}
+
+fn new_etag() -> String {
+    uuid::Uuid::new_v4().simple().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create(service: &RendezvousChannelService) -> (String, RendezvousChannel) {
+        service.create(b"hello".to_vec(), "text/plain".to_string())
+    }
+
+    #[test]
+    fn writes_must_name_the_current_etag() {
+        let service = RendezvousChannelService::new();
+        let (session_id, created) = create(&service);
+
+        let updated = service.update(&session_id, &created.etag, b"reply".to_vec(), "text/plain".to_string()).unwrap();
+        assert_ne!(updated.etag, created.etag);
+        assert_eq!(updated.expires_at_ms, created.expires_at_ms);
+        assert_eq!(service.get(&session_id).unwrap().content, b"reply");
+
+        let stale = service.update(&session_id, &created.etag, b"late".to_vec(), "text/plain".to_string());
+        assert_eq!(stale, Err(RendezvousWriteError::EtagMismatch));
+        assert_eq!(service.get(&session_id).unwrap().etag, updated.etag);
+    }
+
+    #[test]
+    fn deleted_and_unknown_sessions_are_not_found() {
+        let service = RendezvousChannelService::new();
+        let (session_id, created) = create(&service);
+
+        assert!(service.delete(&session_id));
+        assert!(service.get(&session_id).is_none());
+        assert!(!service.delete(&session_id));
+        assert_eq!(
+            service.update(&session_id, &created.etag, Vec::new(), "text/plain".to_string()),
+            Err(RendezvousWriteError::NotFound)
+        );
+    }
+
+    #[test]
+    fn expired_sessions_are_not_returned() {
+        let service = RendezvousChannelService::new();
+        let (session_id, mut channel) = create(&service);
+        channel.expires_at_ms = current_timestamp_millis() - 1;
+        service.sessions.insert(session_id.clone(), channel.clone());
+
+        assert!(service.get(&session_id).is_none());
+        assert_eq!(
+            service.update(&session_id, &channel.etag, Vec::new(), "text/plain".to_string()),
+            Err(RendezvousWriteError::NotFound)
+        );
+    }
+}
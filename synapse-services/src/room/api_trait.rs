@@ -9,6 +9,7 @@ use super::lifecycle::service::LifecycleService;
 use super::membership::service::MembershipService;
 use super::messaging::service::MessagingService;
 use super::service::RoomService;
+use super::shutdown::{ShutdownRoomOutcome, ShutdownRoomParams};
 use super::state::service::RoomStateService;
 use crate::room_summary_service::RoomSummaryService;
 
@@ -24,6 +25,8 @@ pub trait RoomServiceApi: Send + Sync {
 
     async fn upgrade_room(&self, old_room_id: &str, new_version: &str, user_id: &str) -> ApiResult<String>;
 
+    async fn shutdown_room(&self, room_id: &str, params: ShutdownRoomParams) -> ApiResult<ShutdownRoomOutcome>;
+
     async fn dispatch_appservice_event(
         &self,
         event_id: &str,
@@ -98,6 +101,10 @@ impl RoomServiceApi for RoomService {
         self.upgrade_room(old_room_id, new_version, user_id).await
     }
 
+    async fn shutdown_room(&self, room_id: &str, params: ShutdownRoomParams) -> ApiResult<ShutdownRoomOutcome> {
+        self.shutdown_room(room_id, params).await
+    }
+
     async fn dispatch_appservice_event(
         &self,
         event_id: &str,
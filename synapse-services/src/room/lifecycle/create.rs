@@ -165,6 +165,13 @@ impl LifecycleService {
             "redact": 50,
             "invite": 0,
         });
+        if let Some(template) = config.power_level_template {
+            if let (Some(target), Some(levels)) = (power_levels.as_object_mut(), template.levels().as_object()) {
+                for (k, v) in levels {
+                    target.insert(k.clone(), v.clone());
+                }
+            }
+        }
         if let Some(override_obj) = config.power_level_content_override.as_ref().and_then(|v| v.as_object()) {
             if let Some(target) = power_levels.as_object_mut() {
                 for (k, v) in override_obj {
@@ -57,6 +57,8 @@ impl MembershipService {
             return Err(ApiError::not_found("Room not found".to_string()));
         }
 
+        self.ensure_room_not_blocked(room_id).await?;
+
         if !self
             .user_storage
             .user_exists(user_id)
@@ -74,13 +76,67 @@ impl MembershipService {
             return Ok(());
         }
 
+        // An invite already admits the user; otherwise restricted rooms need a
+        // satisfied `allow` condition and a local member to vouch for the join.
+        let authorised_via = if from == Some(Membership::Invite) {
+            None
+        } else {
+            self.restricted_join_authoriser(room_id, user_id, join_rule).await?
+        };
+
         // Delegate the state-machine verdict to the single membership-transition
-        // rulebook. Joins need no power level, so the state-only ctx is exact;
-        // restricted-join authorization resolution is not yet wired, so
-        // restricted rooms fail closed (require an explicit invite).
-        let ctx = TransitionCtx::state_only(join_rule, /* actor_is_target */ true, target_is_banned, false);
+        // rulebook. Joins need no power level, so the state-only ctx is exact.
+        let ctx = TransitionCtx::state_only(
+            join_rule,
+            /* actor_is_target */ true,
+            target_is_banned,
+            authorised_via.is_some(),
+        );
         is_legal(from, Membership::Join, &ctx)?;
 
+        self.record_join(room_id, user_id, authorised_via.as_deref()).await
+    }
+
+    /// Join a user to a local room on an administrator's behalf. Join rules
+    /// are not consulted, so invite-only and restricted rooms need no invite;
+    /// a ban or a room block still keeps the user out.
+    #[::tracing::instrument(skip(self))]
+    pub async fn force_join_room(&self, room_id: &str, user_id: &str) -> ApiResult<()> {
+        if !self
+            .room_storage
+            .room_exists(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to check room", &e))?
+        {
+            return Err(ApiError::not_found("Room not found".to_string()));
+        }
+
+        self.ensure_room_not_blocked(room_id).await?;
+
+        if !self
+            .user_storage
+            .user_exists(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to check user existence", &e))?
+        {
+            return Err(ApiError::not_found("User not found".to_string()));
+        }
+
+        let (from, target_is_banned) = self.resolve_membership_from(room_id, user_id).await?;
+        if from == Some(Membership::Join) {
+            return Ok(());
+        }
+        if target_is_banned {
+            return Err(ApiError::forbidden("User is banned from this room".to_string()));
+        }
+
+        self.record_join(room_id, user_id, None).await
+    }
+
+    /// Store the membership row and `m.room.member` join event for a join that
+    /// has already been authorised, then fan it out. `authorised_via` is the
+    /// local member recorded as vouching for a restricted join.
+    async fn record_join(&self, room_id: &str, user_id: &str, authorised_via: Option<&str>) -> ApiResult<()> {
         self.member_storage
             .add_member(room_id, user_id, "join", None, None, None, None)
             .await
@@ -91,6 +147,14 @@ impl MembershipService {
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to update member count", &e))?;
 
+        let mut content = json!({
+            "membership": "join",
+            "displayname": user_id.trim_start_matches('@').split(':').next().unwrap_or(user_id),
+        });
+        if let Some(authoriser) = authorised_via {
+            content["join_authorised_via_users_server"] = json!(authoriser);
+        }
+
         let join_event = self
             .event_writer
             .create_event(
@@ -99,10 +163,7 @@ impl MembershipService {
                     room_id: room_id.to_string(),
                     user_id: user_id.to_string(),
                     event_type: "m.room.member".to_string(),
-                    content: json!({
-                        "membership": "join",
-                        "displayname": user_id.trim_start_matches('@').split(':').next().unwrap_or(user_id),
-                    }),
+                    content,
                     state_key: Some(user_id.to_string()),
                     origin_server_ts: current_timestamp_millis(),
                     redacts: None,
@@ -136,11 +197,7 @@ impl MembershipService {
         // If the room belongs to a remote server, use the federation leave
         // flow (make_leave / send_leave).
         if self.is_remote_room(room_id) {
-            let destination = room_id
-                .rsplit_once(':')
-                .map(|(_, srv)| srv.to_string())
-                .ok_or_else(|| ApiError::bad_request("Invalid room ID: missing server name".to_string()))?;
-            return self.leave_room_via_federation(&destination, room_id, user_id).await;
+            return self.leave_remote_room(room_id, user_id).await;
         }
 
         let existing_member = self
@@ -278,21 +335,59 @@ mod tests {
 
     const ROOM_ID: &str = "!enc:localhost";
     const USER_ID: &str = "@bob:localhost";
+    const PRIVATE_ROOM_ID: &str = "!private:localhost";
+    const OUTSIDER_ID: &str = "@carol:localhost";
+
+    fn local_user(user_id: &str) -> synapse_storage::User {
+        synapse_storage::User {
+            user_id: user_id.to_string(),
+            username: user_id.trim_start_matches('@').split(':').next().unwrap_or_default().to_string(),
+            password_hash: None,
+            is_admin: false,
+            is_guest: false,
+            is_shadow_banned: false,
+            is_deactivated: false,
+            created_ts: 1_700_000_000_000,
+            updated_ts: None,
+            displayname: None,
+            avatar_url: None,
+            email: None,
+            phone: None,
+            generation: None,
+            consent_version: None,
+            appservice_id: None,
+            user_type: None,
+            invalid_update_at: None,
+            migration_state: None,
+            password_changed_ts: None,
+            is_password_change_required: false,
+            password_expires_at: None,
+            failed_login_attempts: 0,
+            locked_until: None,
+            must_change_password: false,
+        }
+    }
 
     /// Build a [`MembershipService`] wired with in-memory mocks and the given
-    /// key-rotation spy, seeded with `@bob:localhost` joined to `!enc:localhost`.
+    /// key-rotation spy, seeded with `@bob:localhost` joined to `!enc:localhost`
+    /// and an invite-only `!private:localhost` that registered user
+    /// `@carol:localhost` has no membership in.
     async fn build_service(spy: Arc<InMemoryKeyRotationStorage>) -> MembershipService {
         let member_store = InMemoryMemberStore::new();
         member_store.add_member(ROOM_ID, USER_ID, "join", None).await.unwrap();
 
         let event_store = Arc::new(InMemoryEventStore::new());
         let room_store = InMemoryRoomStore::new();
+        room_store.create_room(PRIVATE_ROOM_ID, USER_ID, "invite", "10", false).await.unwrap();
+
+        let user_store = FakeUserStore::new();
+        user_store.seed_user(local_user(OUTSIDER_ID)).await;
 
         let event_reader: Arc<dyn EventReader> = event_store.clone();
         let event_writer: Arc<dyn EventWriter> = event_store.clone();
         let member_storage: Arc<dyn MemberStoreApi> = Arc::new(member_store);
         let room_storage: Arc<dyn RoomStoreApi> = Arc::new(room_store);
-        let user_storage: Arc<dyn UserStore> = Arc::new(FakeUserStore::new());
+        let user_storage: Arc<dyn UserStore> = Arc::new(user_store);
         let user_service = Arc::new(UserService::new(user_storage.clone()));
 
         let room_summary_service = Arc::new(RoomSummaryService::new(
@@ -361,4 +456,116 @@ mod tests {
 
         assert!(spy.marked_rotations().await.is_empty());
     }
+
+    #[tokio::test]
+    async fn reject_remote_invite_falls_back_to_local_rejection() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        let remote_room = "!remote:far.example";
+        svc.member_storage
+            .add_member(remote_room, USER_ID, "invite", None, None, Some("@carol:far.example"), None)
+            .await
+            .unwrap();
+
+        // No federation client is configured, so every remote server is unreachable.
+        svc.leave_room(remote_room, USER_ID).await.unwrap();
+
+        let member = svc.member_storage.get_room_member(remote_room, USER_ID).await.unwrap().unwrap();
+        assert_eq!(member.membership, "leave");
+    }
+
+    #[tokio::test]
+    async fn leave_remote_joined_room_reports_unreachable_servers() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        let remote_room = "!remote:far.example";
+        svc.member_storage.add_member(remote_room, USER_ID, "join", None, None, None, None).await.unwrap();
+
+        assert!(svc.leave_room(remote_room, USER_ID).await.is_err());
+        let member = svc.member_storage.get_room_member(remote_room, USER_ID).await.unwrap().unwrap();
+        assert_eq!(member.membership, "join");
+    }
+
+    #[tokio::test]
+    async fn force_join_bypasses_invite_only_join_rule() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+
+        assert!(svc.join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.is_err());
+        svc.force_join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.unwrap();
+
+        let member = svc.member_storage.get_room_member(PRIVATE_ROOM_ID, OUTSIDER_ID).await.unwrap().unwrap();
+        assert_eq!(member.membership, "join");
+    }
+
+    #[tokio::test]
+    async fn force_join_still_refuses_banned_users() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        svc.member_storage.add_member(PRIVATE_ROOM_ID, OUTSIDER_ID, "leave", None, None, None, None).await.unwrap();
+        svc.member_storage.ban_member(PRIVATE_ROOM_ID, OUTSIDER_ID, USER_ID).await.unwrap();
+
+        assert!(svc.force_join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.is_err());
+    }
+
+    /// Seed `PRIVATE_ROOM_ID`'s join rules, restricted to members of `SPACE_ID`.
+    async fn restrict_private_room(svc: &MembershipService, join_rule: &str) {
+        svc.member_storage.add_member(PRIVATE_ROOM_ID, USER_ID, "join", None, None, None, None).await.unwrap();
+        svc.event_writer
+            .create_event(
+                synapse_storage::CreateEventParams {
+                    event_id: "$join_rules:localhost".to_string(),
+                    room_id: PRIVATE_ROOM_ID.to_string(),
+                    user_id: USER_ID.to_string(),
+                    event_type: "m.room.join_rules".to_string(),
+                    content: serde_json::json!({
+                        "join_rule": join_rule,
+                        "allow": [{"type": "m.room_membership", "room_id": SPACE_ID}]
+                    }),
+                    state_key: Some("".to_string()),
+                    origin_server_ts: 1_000,
+                    redacts: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    const SPACE_ID: &str = "!space:localhost";
+
+    #[tokio::test]
+    async fn restricted_join_requires_membership_of_an_allowed_room() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        restrict_private_room(&svc, "restricted").await;
+
+        assert!(svc.join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.is_err());
+
+        svc.member_storage.add_member(SPACE_ID, OUTSIDER_ID, "join", None, None, None, None).await.unwrap();
+        svc.join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.unwrap();
+
+        let member = svc.member_storage.get_room_member(PRIVATE_ROOM_ID, OUTSIDER_ID).await.unwrap().unwrap();
+        assert_eq!(member.membership, "join");
+        let join =
+            svc.event_reader.get_state_event(PRIVATE_ROOM_ID, "m.room.member", OUTSIDER_ID).await.unwrap().unwrap();
+        assert_eq!(join.content["join_authorised_via_users_server"], USER_ID);
+    }
+
+    #[tokio::test]
+    async fn restricted_join_rule_is_ignored_before_room_version_8() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        let old_room = "!old:localhost";
+        svc.room_storage.create_room(old_room, USER_ID, "invite", "7", false).await.unwrap();
+        svc.member_storage.add_member(SPACE_ID, OUTSIDER_ID, "join", None, None, None, None).await.unwrap();
+
+        let authoriser =
+            svc.restricted_join_authoriser(old_room, OUTSIDER_ID, synapse_common::JoinRule::Restricted).await.unwrap();
+        assert!(authoriser.is_none());
+    }
+
+    #[tokio::test]
+    async fn blocked_room_refuses_joins_including_forced_ones() {
+        let svc = build_service(Arc::new(InMemoryKeyRotationStorage::new())).await;
+        svc.room_storage.block_room(PRIVATE_ROOM_ID, 1_000, USER_ID, None).await.unwrap();
+
+        assert!(svc.join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.is_err());
+        assert!(svc.force_join_room(PRIVATE_ROOM_ID, OUTSIDER_ID).await.is_err());
+        assert!(svc.member_storage.get_room_member(PRIVATE_ROOM_ID, OUTSIDER_ID).await.unwrap().is_none());
+    }
 }
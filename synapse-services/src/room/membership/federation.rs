@@ -8,7 +8,8 @@
 //! - **Join**: `GET /_matrix/federation/v1/make_join` → sign locally →
 //!   `PUT /_matrix/federation/v2/send_join` → persist returned state.
 //! - **Leave**: `GET /_matrix/federation/v1/make_leave` → sign locally →
-//!   `PUT /_matrix/federation/v2/send_leave`.
+//!   `PUT /_matrix/federation/v2/send_leave`, trying each candidate server
+//!   in turn. Invites nobody will take a rejection for are rejected locally.
 //! - **Invite**: build invite event → sign locally →
 //!   `PUT /_matrix/federation/v2/invite` → persist returned event.
 //!
@@ -63,7 +64,7 @@ impl MembershipService {
         // 2. Sign the template event locally.
         let signing_key = self.require_signing_key().await?;
         sign_and_hash_event(&self.server_name, &signing_key.key_id, &signing_key.secret_key, &mut event_template)
-            .map_err(|e| ApiError::internal(format!("Failed to sign join event: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign join event", &e))?;
 
         let event_id = event_template
             .get("event_id")
@@ -259,6 +260,94 @@ impl MembershipService {
     // Outbound federation leave
     // =========================================================================
 
+    /// Leave a room hosted on another server, or reject an invite to one.
+    ///
+    /// The leave is offered to each candidate server from
+    /// [`leave_destinations`] in turn. When none of them accepts it and the
+    /// user only held an invite, the rejection is recorded locally instead so
+    /// the invite stops appearing in sync; the inviting server never hears of
+    /// it, which matches Synapse's out-of-band invite rejection.
+    pub async fn leave_remote_room(&self, room_id: &str, user_id: &str) -> ApiResult<()> {
+        let member = self
+            .member_storage
+            .get_room_member(room_id, user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to check membership before federation leave", &e))?;
+        let inviter = member.as_ref().filter(|m| m.membership == "invite").and_then(|m| m.sender.as_deref());
+        let is_invite = member.as_ref().is_some_and(|m| m.membership == "invite");
+
+        let known_servers: Vec<String> = self
+            .member_storage
+            .get_joined_members(room_id)
+            .await
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|m| Self::server_name_from_id(&m.user_id).map(str::to_string))
+            .collect();
+
+        let destinations = leave_destinations(&self.server_name, room_id, inviter, &known_servers);
+        let mut last_error = None;
+        for destination in &destinations {
+            match self.leave_room_via_federation(destination, room_id, user_id).await {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    ::tracing::warn!(
+                        destination = %destination,
+                        room_id = %room_id,
+                        error = %e,
+                        "Federation leave failed; trying next server"
+                    );
+                    last_error = Some(e);
+                }
+            }
+        }
+
+        if is_invite {
+            return self.reject_invite_locally(room_id, user_id).await;
+        }
+
+        Err(last_error.unwrap_or_else(|| {
+            ApiError::bad_request("Cannot leave remote room: no destination server available".to_string())
+        }))
+    }
+
+    /// Record an invite rejection without involving the remote servers.
+    async fn reject_invite_locally(&self, room_id: &str, user_id: &str) -> ApiResult<()> {
+        ::tracing::info!(
+            room_id = %room_id,
+            user_id = %user_id,
+            "No remote server accepted the invite rejection; rejecting locally"
+        );
+
+        self.member_storage
+            .remove_member(room_id, user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to reject invite", &e))?;
+
+        if let Err(e) = self
+            .event_writer
+            .create_event(
+                CreateEventParams {
+                    event_id: generate_event_id(&self.server_name),
+                    room_id: room_id.to_string(),
+                    user_id: user_id.to_string(),
+                    event_type: "m.room.member".to_string(),
+                    content: json!({ "membership": "leave" }),
+                    state_key: Some(user_id.to_string()),
+                    origin_server_ts: current_timestamp_millis(),
+                    redacts: None,
+                },
+                None,
+            )
+            .await
+        {
+            ::tracing::warn!(error = %e, "Failed to persist out-of-band invite rejection");
+        }
+        let _ = self.cache.delete(&format!("room_state:{room_id}")).await;
+
+        Ok(())
+    }
+
     /// Leave a federated room via the make_leave / send_leave flow.
     ///
     /// 1. Call `make_leave` on `destination` to get a template PDU.
@@ -285,11 +374,12 @@ impl MembershipService {
         })?;
 
         let mut event_template = make_leave_response.event;
+        complete_leave_template(&mut event_template, room_id, user_id, &self.server_name);
 
         // 2. Sign the template event locally.
         let signing_key = self.require_signing_key().await?;
         sign_and_hash_event(&self.server_name, &signing_key.key_id, &signing_key.secret_key, &mut event_template)
-            .map_err(|e| ApiError::internal(format!("Failed to sign leave event: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign leave event", &e))?;
 
         let event_id = event_template
             .get("event_id")
@@ -414,7 +504,7 @@ impl MembershipService {
         // 2. Sign the event locally.
         let signing_key = self.require_signing_key().await?;
         sign_and_hash_event(&self.server_name, &signing_key.key_id, &signing_key.secret_key, &mut invite_event)
-            .map_err(|e| ApiError::internal(format!("Failed to sign invite event: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign invite event", &e))?;
 
         // 3. Call invite on the remote server.
         let invite_response =
@@ -552,3 +642,71 @@ impl MembershipService {
         Ok(signed_event)
     }
 }
+
+/// Servers to offer a leave to, in order: the inviter's server when
+/// rejecting an invite, the server that created the room, then any other
+/// server with joined members we know of. The local server is never a
+/// candidate.
+pub(crate) fn leave_destinations(
+    local_server: &str,
+    room_id: &str,
+    inviter: Option<&str>,
+    known_servers: &[String],
+) -> Vec<String> {
+    let inviter_server = inviter.and_then(MembershipService::server_name_from_id);
+    let room_server = MembershipService::server_name_from_id(room_id);
+
+    let mut destinations: Vec<String> = Vec::new();
+    for server in inviter_server.into_iter().chain(room_server).chain(known_servers.iter().map(String::as_str)) {
+        if server.is_empty() || server == local_server || destinations.iter().any(|d| d == server) {
+            continue;
+        }
+        destinations.push(server.to_string());
+    }
+    destinations
+}
+
+/// Fill in the fields a `make_leave` template may omit before it is signed.
+fn complete_leave_template(template: &mut Value, room_id: &str, user_id: &str, server_name: &str) {
+    let Some(event) = template.as_object_mut() else {
+        return;
+    };
+    event.entry("type").or_insert_with(|| json!("m.room.member"));
+    event.entry("room_id").or_insert_with(|| json!(room_id));
+    event.entry("sender").or_insert_with(|| json!(user_id));
+    event.entry("state_key").or_insert_with(|| json!(user_id));
+    event.entry("origin").or_insert_with(|| json!(server_name));
+    event.entry("origin_server_ts").or_insert_with(|| json!(current_timestamp_millis()));
+    event.entry("content").or_insert_with(|| json!({ "membership": "leave" }));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn leave_destinations_prefers_inviter_then_room_server() {
+        let known = vec!["c.example".to_string(), "remote.example".to_string(), "local.example".to_string()];
+        assert_eq!(
+            leave_destinations("local.example", "!room:remote.example", Some("@inviter:b.example"), &known),
+            vec!["b.example", "remote.example", "c.example"]
+        );
+        assert!(leave_destinations("local.example", "!room:local.example", None, &[]).is_empty());
+    }
+
+    #[test]
+    fn complete_leave_template_keeps_remote_fields() {
+        let mut template = json!({
+            "type": "m.room.member",
+            "sender": "@bob:local.example",
+            "state_key": "@bob:local.example",
+            "content": { "membership": "leave" },
+            "depth": 7
+        });
+        complete_leave_template(&mut template, "!room:remote.example", "@bob:local.example", "local.example");
+        assert_eq!(template["room_id"], "!room:remote.example");
+        assert_eq!(template["origin"], "local.example");
+        assert_eq!(template["depth"], 7);
+        assert!(template["origin_server_ts"].as_i64().is_some());
+    }
+}
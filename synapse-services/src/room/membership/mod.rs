@@ -36,6 +36,14 @@ impl MembershipService {
             return Err(ApiError::forbidden("You are not a member of this room".to_string()));
         }
 
+        self.get_joined_member_events(room_id).await
+    }
+
+    /// Joined `m.room.member` events for a room, without a membership check.
+    ///
+    /// Callers are responsible for authorization; this is used by peeking
+    /// paths where a `world_readable` room is viewed by a non-member.
+    pub async fn get_joined_member_events(&self, room_id: &str) -> ApiResult<serde_json::Value> {
         let members_with_profiles = self
             .member_storage
             .get_room_members_with_profiles(room_id, "join")
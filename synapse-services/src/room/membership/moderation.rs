@@ -19,6 +19,11 @@ impl MembershipService {
             return Err(ApiError::not_found("Room not found".to_string()));
         }
 
+        self.ensure_room_not_blocked(room_id).await?;
+        self.event_auth
+            .check(room_id, inviter_id, "m.room.member", Some(invitee_id), &json!({ "membership": "invite" }))
+            .await?;
+
         // If the invitee is on a remote server, use the federation invite
         // flow instead of the local invite path.
         if self.is_remote_user(invitee_id) {
@@ -139,6 +144,8 @@ impl MembershipService {
             return Err(ApiError::not_found("Room not found".to_string()));
         }
 
+        self.ensure_room_not_blocked(room_id).await?;
+
         let join_rule = self.resolve_join_rule(room_id).await?;
         let (from, target_is_banned) = self.resolve_membership_from(room_id, user_id).await?;
 
@@ -179,6 +186,9 @@ impl MembershipService {
         }
 
         self.room_auth.can_ban_user(room_id, banned_by, user_id).await?;
+        self.event_auth
+            .check(room_id, banned_by, "m.room.member", Some(user_id), &json!({ "membership": "ban" }))
+            .await?;
 
         // Validate membership transition: only join/invite/knock can be banned.
         let target_state = self
@@ -346,6 +356,9 @@ impl MembershipService {
         }
 
         self.room_auth.can_kick_user(room_id, kicked_by, target_user_id).await?;
+        self.event_auth
+            .check(room_id, kicked_by, "m.room.member", Some(target_user_id), &json!({ "membership": "leave" }))
+            .await?;
 
         // Validate membership transition: only joined members can be kicked.
         let target_state = self
@@ -9,7 +9,9 @@ use serde_json::json;
 use std::str::FromStr;
 use std::sync::Arc;
 use synapse_cache::CacheManager;
-use synapse_common::{is_legal, JoinRule, Membership, TransitionCtx};
+use synapse_common::{
+    is_legal, supports_knock_restricted, supports_restricted_join, JoinRule, Membership, TransitionCtx,
+};
 use synapse_federation::client_api::FederationClientApi;
 use synapse_federation::key_rotation::SigningKey;
 use synapse_federation::signing::sign_and_hash_event;
@@ -46,6 +48,8 @@ pub struct MembershipService {
     /// (join, leave, invite, ban) are enqueued for matching application
     /// services after they are persisted.
     pub(crate) app_service_manager: Option<Arc<crate::application_service::ApplicationServiceManager>>,
+    /// Auth rules checked before invite, kick and ban events are stored.
+    pub(crate) event_auth: crate::auth::EventAuthChecker,
 }
 
 /// Configuration for constructing a [`MembershipService`].
@@ -70,6 +74,7 @@ pub struct MembershipServiceConfig {
 impl MembershipService {
     pub fn new(config: MembershipServiceConfig) -> Self {
         Self {
+            event_auth: crate::auth::EventAuthChecker::new(config.event_reader.clone()),
             member_storage: config.member_storage,
             room_storage: config.room_storage,
             event_reader: config.event_reader,
@@ -224,6 +229,20 @@ impl MembershipService {
         }
     }
 
+    /// Refuse membership changes that would bring someone into a room an
+    /// administrator has blocked, e.g. after shutting it down.
+    pub(crate) async fn ensure_room_not_blocked(&self, room_id: &str) -> ApiResult<()> {
+        let blocked_at = self
+            .room_storage
+            .get_room_block_status(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to check room block status", &e))?;
+        if blocked_at.is_some() {
+            return Err(ApiError::forbidden("This room has been blocked on this server".to_string()));
+        }
+        Ok(())
+    }
+
     /// Resolve a target user's current membership (as the typed [`Membership`]
     /// enum) plus whether they are currently banned. `None` means the user has
     /// no membership record in the room. Used to build the `from` state for a
@@ -248,18 +267,9 @@ impl MembershipService {
     /// `join_rule`, then a `public`/`invite` default from `is_public`. Unknown
     /// rule strings resolve to [`JoinRule::Invite`] (fail-closed).
     pub(crate) async fn resolve_join_rule(&self, room_id: &str) -> ApiResult<JoinRule> {
-        let effective = if let Some(event) = self
-            .event_reader
-            .get_state_events_by_type(room_id, "m.room.join_rules")
-            .await
-            .map_err(|e| ApiError::internal_with_log("Failed to load room join rules", &e))?
-            .into_iter()
-            .find(|event| event.state_key.as_deref().unwrap_or_default().is_empty())
-        {
-            event.content.get("join_rule").and_then(|value| value.as_str()).map(|value| value.to_string())
-        } else {
-            None
-        };
+        let effective = self.join_rules_content(room_id).await?.and_then(|content| {
+            content.get("join_rule").and_then(|value| value.as_str()).map(|value| value.to_string())
+        });
 
         let room = self
             .room_storage
@@ -280,6 +290,81 @@ impl MembershipService {
         Ok(JoinRule::from_str(&raw).unwrap_or(JoinRule::Invite))
     }
 
+    async fn join_rules_content(&self, room_id: &str) -> ApiResult<Option<serde_json::Value>> {
+        Ok(self
+            .event_reader
+            .get_state_events_by_type(room_id, "m.room.join_rules")
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load room join rules", &e))?
+            .into_iter()
+            .find(|event| event.state_key.as_deref().unwrap_or_default().is_empty())
+            .map(|event| event.content))
+    }
+
+    /// Resolve a restricted join: returns the local member who authorises
+    /// `user_id` joining, or `None` when the join rule's `allow` conditions are
+    /// not met. The joiner must be in one of the allowed rooms (typically the
+    /// parent space) and the authoriser must be joined here with invite power.
+    /// Room versions whose auth rules predate the join rule never authorise.
+    pub(crate) async fn restricted_join_authoriser(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        join_rule: JoinRule,
+    ) -> ApiResult<Option<String>> {
+        let supports: fn(&str) -> bool = match join_rule {
+            JoinRule::Restricted => supports_restricted_join,
+            JoinRule::KnockRestricted => supports_knock_restricted,
+            _ => return Ok(None),
+        };
+        let room = self
+            .room_storage
+            .get_room(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load room", &e))?;
+        if !room.is_some_and(|room| supports(&room.room_version)) {
+            return Ok(None);
+        }
+
+        let content = self.join_rules_content(room_id).await?.unwrap_or_default();
+        let allowed_rooms =
+            content.get("allow").and_then(|allow| allow.as_array()).into_iter().flatten().filter_map(|condition| {
+                (condition.get("type").and_then(|t| t.as_str()) == Some("m.room_membership"))
+                    .then(|| condition.get("room_id").and_then(|r| r.as_str()))
+                    .flatten()
+            });
+
+        let mut satisfied = false;
+        for allowed_room in allowed_rooms {
+            let membership = self
+                .member_storage
+                .get_membership_state(allowed_room, user_id)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to check allowed room membership", &e))?;
+            if membership.as_deref() == Some("join") {
+                satisfied = true;
+                break;
+            }
+        }
+        if !satisfied {
+            return Ok(None);
+        }
+
+        let members = self
+            .member_storage
+            .get_joined_members(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load joined members", &e))?;
+        for member in members {
+            if !self.is_remote_user(&member.user_id)
+                && self.room_auth.can_invite_user(room_id, &member.user_id).await.is_ok()
+            {
+                return Ok(Some(member.user_id));
+            }
+        }
+        Ok(None)
+    }
+
     /// Authorize an inbound federation `m.room.member` transition against our
     /// current room state — closes AUDIT-2026-07 S5 gap 2, where inbound member
     /// events skipped the transition table the client path enforces.
@@ -323,12 +408,32 @@ impl MembershipService {
             return Ok(());
         };
 
-        // 1. Fetch prev_events (forward extremities of the room).
-        let prev_events = self.event_reader.get_latest_event_ids_in_room(&event.room_id, 10).await.unwrap_or_default();
+        // 1. Fetch prev_events (forward extremities of the room), falling back
+        // to the most recent events while the room has none tracked yet.
+        let mut prev_events = self.event_reader.get_forward_extremities(&event.room_id, 10).await.unwrap_or_default();
+        if prev_events.is_empty() {
+            prev_events = self.event_reader.get_latest_event_ids_in_room(&event.room_id, 10).await.unwrap_or_default();
+        }
 
         // Exclude the event itself.
         let prev_events: Vec<String> = prev_events.into_iter().filter(|id| id != &event.event_id).collect();
 
+        // Link the event into the DAG; it replaces its prev_events as a
+        // forward extremity.
+        let depth =
+            match self.event_writer.record_local_event_graph(&event.room_id, &event.event_id, &prev_events).await {
+                Ok(depth) => depth,
+                Err(e) => {
+                    ::tracing::warn!(
+                        event_id = %event.event_id,
+                        room_id = %event.room_id,
+                        error = %e,
+                        "Failed to record event prev_events"
+                    );
+                    event.depth.max(1)
+                }
+            };
+
         // 2. Build the PDU JSON.
         let mut pdu = json!({
             "event_id": event.event_id,
@@ -340,6 +445,7 @@ impl MembershipService {
             "origin_server_ts": event.origin_server_ts,
             "origin": self.server_name,
             "prev_events": prev_events,
+            "depth": depth,
         });
 
         if let Some(ref state_key) = event.state_key {
@@ -358,7 +464,7 @@ impl MembershipService {
             .ok_or_else(|| ApiError::internal("No signing key available".to_string()))?;
 
         sign_and_hash_event(&self.server_name, &signing_key.key_id, &signing_key.secret_key, &mut pdu)
-            .map_err(|e| ApiError::internal(format!("Failed to sign event: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign event", &e))?;
 
         // 4. Persist signatures and hashes back to the events table.
         let signatures = pdu.get("signatures").cloned().unwrap_or(serde_json::Value::Null);
@@ -130,6 +130,8 @@ mod tests {
             key_rotation_manager: None,
             room_summary_service,
             cache,
+            content_filter: None,
+            event_visibility: None,
         })
     }
 
@@ -4,6 +4,7 @@ use crate::common::error::{ApiError, ApiResult};
 use serde_json::json;
 use synapse_common::current_timestamp_millis;
 use synapse_common::generate_event_id;
+use synapse_storage::event::{RejectedEventFilter, RejectedEventParams, SoftFailKind};
 use synapse_storage::CreateEventParams;
 
 use super::service::MessagingService;
@@ -99,6 +100,16 @@ impl MessagingService {
             .map_err(|e| ApiError::database_with_log("Failed to get room state", &e))
     }
 
+    pub async fn get_state_events_at_event(
+        &self,
+        event: &synapse_storage::RoomEvent,
+    ) -> ApiResult<Vec<synapse_storage::StateEvent>> {
+        self.event_reader
+            .get_state_events_at_event(event)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to get room state", &e))
+    }
+
     pub async fn create_event(
         &self,
         params: CreateEventParams,
@@ -258,6 +269,62 @@ impl MessagingService {
         Ok(event)
     }
 
+    /// Stores an inbound event as soft-failed. It is not sent to clients or
+    /// application services and does not change the room's state.
+    #[allow(clippy::too_many_arguments)]
+    pub async fn create_soft_failed_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        kind: SoftFailKind,
+        reason: &str,
+    ) -> ApiResult<synapse_storage::RoomEvent> {
+        self.event_writer
+            .create_soft_failed_event_with_graph(params, prev_events, auth_events, depth, kind, reason)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to store soft-failed event", &e))
+    }
+
+    pub async fn get_soft_failed_events(
+        &self,
+        room_id: &str,
+        kind: Option<SoftFailKind>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> ApiResult<Vec<synapse_storage::event::SoftFailedEvent>> {
+        self.event_reader
+            .get_soft_failed_events(room_id, kind, before, limit)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to get soft-failed events", &e))
+    }
+
+    pub async fn count_soft_failed_events(&self, room_id: &str) -> ApiResult<Vec<(String, i64)>> {
+        self.event_reader
+            .count_soft_failed_events(room_id)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to count soft-failed events", &e))
+    }
+
+    pub async fn record_rejected_event(&self, params: RejectedEventParams, max_rows: i64) -> ApiResult<()> {
+        self.event_writer
+            .record_rejected_event(params, max_rows)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to record rejected event", &e))
+    }
+
+    pub async fn get_rejected_events(
+        &self,
+        filter: &RejectedEventFilter,
+        limit: i64,
+    ) -> ApiResult<Vec<synapse_storage::event::RejectedEvent>> {
+        self.event_reader
+            .get_rejected_events(filter, limit)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to get rejected events", &e))
+    }
+
     pub async fn get_state_events_by_type(&self, room_id: &str, event_type: &str) -> ApiResult<Vec<serde_json::Value>> {
         let events = self
             .event_reader
@@ -343,6 +410,36 @@ impl MessagingService {
         }))
     }
 
+    /// `GET /rooms/{roomId}/event/{eventId}`: the event with its bundled
+    /// relations if `user_id` may see it under the room's history visibility.
+    /// Events the user may not see are reported as missing.
+    pub async fn get_visible_event(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        event_id: &str,
+    ) -> ApiResult<serde_json::Value> {
+        let event = self
+            .event_reader
+            .get_event(event_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get event", &e))?
+            .filter(|event| event.room_id == room_id)
+            .ok_or_else(|| ApiError::not_found("Event not found".to_string()))?;
+        let event = self
+            .visible_events(user_id, vec![event])
+            .await?
+            .pop()
+            .ok_or_else(|| ApiError::not_found("Event not found".to_string()))?;
+
+        let mut event_json = self.client_events(user_id, std::slice::from_ref(&event)).await?.pop().unwrap_or_default();
+        event_json["room_id"] = json!(event.room_id);
+        if let Some(state_key) = &event.state_key {
+            event_json["state_key"] = json!(state_key);
+        }
+        Ok(event_json)
+    }
+
     pub async fn get_pending_events(&self, room_id: &str, limit: i64) -> ApiResult<Vec<synapse_storage::RoomEvent>> {
         self.event_reader
             .get_pending_room_events(room_id, limit)
@@ -446,6 +543,92 @@ impl MessagingService {
             .map_err(|e| ApiError::database_with_log("Failed to get forward extremities", &e))
     }
 
+    pub async fn get_rooms_by_forward_extremities(&self, min_count: i64, limit: i64) -> ApiResult<Vec<(String, i64)>> {
+        self.event_reader
+            .get_rooms_by_forward_extremities(min_count, limit)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to list rooms by forward extremities", &e))
+    }
+
+    /// Sends an empty `org.matrix.dummy_event` as a local member of the room.
+    /// Its `prev_events` are the room's forward extremities, so sending it
+    /// merges them. Returns `None` when no local user is joined.
+    pub async fn send_dummy_event(&self, room_id: &str) -> ApiResult<Option<String>> {
+        let members = self
+            .member_storage
+            .get_joined_members(room_id)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to get joined members", &e))?;
+        let local_suffix = format!(":{}", self.server_name);
+        let Some(sender) = members.into_iter().map(|member| member.user_id).find(|id| id.ends_with(&local_suffix))
+        else {
+            return Ok(None);
+        };
+
+        let event = self
+            .create_event(
+                CreateEventParams {
+                    event_id: generate_event_id(&self.server_name),
+                    room_id: room_id.to_string(),
+                    user_id: sender,
+                    event_type: "org.matrix.dummy_event".to_string(),
+                    content: json!({}),
+                    state_key: None,
+                    origin_server_ts: current_timestamp_millis(),
+                    redacts: None,
+                },
+                None,
+            )
+            .await?;
+        Ok(Some(event.event_id))
+    }
+
+    pub async fn get_room_dag_diagnostics(
+        &self,
+        room_id: &str,
+        depth: Option<i64>,
+    ) -> ApiResult<synapse_storage::event::RoomDagDiagnostics> {
+        self.event_reader
+            .get_room_dag_diagnostics(room_id, depth)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to inspect room DAG", &e))
+    }
+
+    /// Loads a room's events as stored PDUs for the admin export. The range
+    /// runs from `from_event` (or just after the `since` stream position) up
+    /// to and including `to_event`, in stream order.
+    pub async fn export_room_pdus(
+        &self,
+        room_id: &str,
+        from_event: Option<&str>,
+        to_event: Option<&str>,
+        since: Option<i64>,
+        limit: i64,
+    ) -> ApiResult<Vec<synapse_storage::event::ExportedPdu>> {
+        let after = match (since, from_event) {
+            (Some(since), _) => Some(since),
+            (None, Some(event_id)) => Some(self.export_stream_ordering(room_id, event_id).await? - 1),
+            (None, None) => None,
+        };
+        let until = match to_event {
+            Some(event_id) => Some(self.export_stream_ordering(room_id, event_id).await?),
+            None => None,
+        };
+
+        self.event_reader
+            .get_room_pdus(room_id, after, until, limit)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to export room events", &e))
+    }
+
+    async fn export_stream_ordering(&self, room_id: &str, event_id: &str) -> ApiResult<i64> {
+        self.event_reader
+            .get_event_stream_ordering_in_room(room_id, event_id)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to look up event", &e))?
+            .ok_or_else(|| ApiError::not_found(format!("Event {event_id} not found in this room")))
+    }
+
     pub async fn count_events_by_status(&self, room_id: &str, status: &str) -> i64 {
         self.event_reader.count_room_events_by_status(room_id, status).await.unwrap_or(0)
     }
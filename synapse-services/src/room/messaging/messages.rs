@@ -1,13 +1,34 @@
 //! Room message operations: send, paginate, ephemeral events, typing indicators.
 
 use crate::common::error::{ApiError, ApiResult};
+use crate::content_filter_service::ContentFilterDecision;
 use serde_json::json;
 use synapse_common::current_timestamp_millis;
-use synapse_common::{generate_event_id, generate_stream_token_from_ts, parse_stream_token};
-use synapse_storage::CreateEventParams;
+use synapse_common::{generate_event_id, RoomPaginationToken};
+use synapse_storage::{CreateEventParams, EventQueryFilter, RoomEvent, StateEvent};
 
+use super::receipts::PRIVATE_RECEIPT_EPHEMERAL_TYPE;
 use super::service::MessagingService;
 
+/// Query of a `/messages` request.
+#[derive(Debug, Clone, Default)]
+pub struct RoomMessagesParams {
+    /// Token to paginate from; the latest (`dir=b`) or earliest (`dir=f`)
+    /// position when absent.
+    pub from: Option<String>,
+    /// Token to stop at.
+    pub to: Option<String>,
+    pub limit: i64,
+    /// `"b"` or `"f"`.
+    pub direction: String,
+    pub filter: Option<EventQueryFilter>,
+    /// Return the membership of the chunk's senders in `state`.
+    pub lazy_load_members: bool,
+    /// With `lazy_load_members`, also repeat members this device was already
+    /// sent.
+    pub include_redundant_members: bool,
+}
+
 impl MessagingService {
     #[::tracing::instrument(skip(self, content))]
     pub async fn send_message(
@@ -25,8 +46,34 @@ impl MessagingService {
         {
             return Err(ApiError::forbidden("You are not a member of this room".to_string()));
         }
+        self.event_auth.check(room_id, user_id, event_type, None, content).await?;
 
         let event_id = generate_event_id(&self.server_name);
+
+        let masked_content;
+        let content = match &self.content_filter {
+            Some(filter) => {
+                let context = crate::module_service::SpamCheckContext {
+                    event_id: event_id.clone(),
+                    room_id: room_id.to_string(),
+                    sender: user_id.to_string(),
+                    event_type: event_type.to_string(),
+                    content: content.clone(),
+                };
+                match filter.check_event(&context).await? {
+                    ContentFilterDecision::Allow => content,
+                    ContentFilterDecision::Redact { content: masked } => {
+                        masked_content = masked;
+                        &masked_content
+                    }
+                    ContentFilterDecision::Reject { reason } => return Err(ApiError::forbidden(reason)),
+                    // Shadow-banned senders get a plausible event ID back; nothing is stored or federated.
+                    ContentFilterDecision::ShadowBan => return Ok(json!({ "event_id": event_id })),
+                }
+            }
+            None => content,
+        };
+
         let now = current_timestamp_millis();
         let max_ts = self.event_reader.get_max_origin_server_ts_for_room(room_id).await.unwrap_or(0);
         let now = now.max(max_ts + 1);
@@ -202,9 +249,7 @@ impl MessagingService {
         &self,
         room_id: &str,
         user_id: &str,
-        from: i64,
-        limit: i64,
-        direction: &str,
+        params: &RoomMessagesParams,
     ) -> ApiResult<serde_json::Value> {
         let is_member = self
             .member_storage
@@ -217,60 +262,249 @@ impl MessagingService {
                 .get_room(room_id)
                 .await
                 .map_err(|e| ApiError::internal_with_log("Failed to get room", &e))?;
-            let is_public = room.as_ref().is_some_and(|r| r.is_public);
-            if !is_public {
+            let is_peekable = room.as_ref().is_some_and(|r| r.is_public || r.history_visibility == "world_readable");
+            if !is_peekable {
                 return Err(ApiError::forbidden("You are not a member of this room".to_string()));
             }
         }
 
-        let normalized_direction = if direction == "f" { "f" } else { "b" };
-
-        let start_token = if from > 0 {
-            generate_stream_token_from_ts(Some(from))
-        } else {
-            let max_ts = self
-                .event_reader
-                .get_max_origin_server_ts_for_room(room_id)
-                .await
-                .map_err(|e| ApiError::internal_with_log("Failed to get room stream", &e))?;
-            generate_stream_token_from_ts(Some(max_ts))
+        let backwards = params.direction != "f";
+        let (start_token, from) = match params.from.as_deref() {
+            Some(token) => {
+                let token = Self::parse_pagination_token(token, "from")?;
+                (token, self.resolve_stream_position(room_id, token, backwards).await?)
+            }
+            None if backwards => {
+                let position = self
+                    .event_reader
+                    .get_room_stream_position(room_id, None)
+                    .await
+                    .map_err(|e| ApiError::internal_with_log("Failed to get room stream", &e))?;
+                (RoomPaginationToken::Stream(position), position)
+            }
+            None => (RoomPaginationToken::Stream(0), 0),
+        };
+        let to = match params.to.as_deref() {
+            Some(token) => {
+                let token = Self::parse_pagination_token(token, "to")?;
+                Some(self.resolve_stream_position(room_id, token, backwards).await?)
+            }
+            None => None,
         };
-
-        let from_ts = if from > 0 { parse_stream_token(&start_token).or(Some(from)) } else { None };
 
         let events = self
             .event_reader
-            .get_room_events_paginated(room_id, from_ts, limit, normalized_direction)
+            .get_room_events_page(room_id, from, to, params.limit, &params.direction, params.filter.as_ref())
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to get messages", &e))?;
 
-        let event_list: Vec<serde_json::Value> = events
-            .iter()
-            .map(|e| {
-                json!({
-                    "type": e.event_type,
-                    "content": e.content,
-                    "sender": e.user_id,
-                    "origin_server_ts": e.origin_server_ts,
-                    "event_id": e.event_id
-                })
-            })
-            .collect();
+        // Tokens come from the unfiltered page so hidden events are skipped
+        // rather than fetched again.
+        let visible = self.visible_events(user_id, events.clone()).await?;
+        let event_list = self.client_events(user_id, &visible).await?;
 
-        let end_token = events
+        let mut response = json!({
+            "chunk": event_list,
+            "start": start_token.to_string(),
+        });
+        // `end` is omitted once there is nothing further in this direction.
+        if let Some(last) = events.last() {
+            response["end"] = json!(Self::boundary_token(last, backwards).to_string());
+        }
+        if params.lazy_load_members {
+            response["state"] = json!(self.chunk_member_state(&visible).await?);
+        }
+        Ok(response)
+    }
+
+    /// `/context` for `event_id`: up to `limit` events on each side, with
+    /// `start`/`end` tokens that continue in `/messages`.
+    pub async fn get_event_context(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        event_id: &str,
+        limit: i64,
+        filter: Option<&EventQueryFilter>,
+    ) -> ApiResult<serde_json::Value> {
+        let event = self
+            .event_reader
+            .get_event(event_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get event", &e))?
+            .filter(|event| event.room_id == room_id)
+            .ok_or_else(|| ApiError::not_found("Event not found".to_string()))?;
+        let event = self
+            .visible_events(user_id, vec![event])
+            .await?
+            .pop()
+            .ok_or_else(|| ApiError::not_found("Event not found".to_string()))?;
+        let position = event.stream_ordering.unwrap_or(0);
+
+        let events_before = self
+            .event_reader
+            .get_room_events_page(room_id, position - 1, None, limit, "b", filter)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get preceding context", &e))?;
+        let events_after = self
+            .event_reader
+            .get_room_events_page(room_id, position, None, limit, "f", filter)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get following context", &e))?;
+
+        let start = events_before
             .last()
-            .map_or_else(|| start_token.clone(), |event| generate_stream_token_from_ts(Some(event.origin_server_ts)));
+            .map_or(RoomPaginationToken::Topological { topological: event.depth, stream: position - 1 }, |last| {
+                Self::boundary_token(last, true)
+            });
+        let end = events_after
+            .last()
+            .map_or(RoomPaginationToken::Topological { topological: event.depth, stream: position }, |last| {
+                Self::boundary_token(last, false)
+            });
+
+        let events_before = self.visible_events(user_id, events_before).await?;
+        let events_after = self.visible_events(user_id, events_after).await?;
+        // State at the last event returned, as the spec asks.
+        let state = self
+            .event_reader
+            .get_state_events_at_event(events_after.last().unwrap_or(&event))
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get context state", &e))?;
+
+        let returned: Vec<RoomEvent> =
+            std::iter::once(&event).chain(&events_before).chain(&events_after).cloned().collect();
+        let bundles = self.bundled_relations(user_id, &returned).await?;
+        let client_event = |event: &RoomEvent| {
+            let mut event_json = Self::pagination_event_json(event);
+            crate::sync_helpers::attach_bundled_relations(&mut event_json, &bundles);
+            event_json
+        };
 
         Ok(json!({
-            "chunk": event_list,
-            "start": start_token,
-            "end": end_token
+            "event": client_event(&event),
+            "events_before": events_before.iter().map(client_event).collect::<Vec<_>>(),
+            "events_after": events_after.iter().map(client_event).collect::<Vec<_>>(),
+            "state": state.iter().map(Self::state_event_json).collect::<Vec<_>>(),
+            "start": start.to_string(),
+            "end": end.to_string(),
         }))
     }
 
+    /// `m.room.member` events of the chunk's senders, taken from the state at
+    /// the first event returned.
+    async fn chunk_member_state(&self, chunk: &[RoomEvent]) -> ApiResult<Vec<serde_json::Value>> {
+        let Some(first) = chunk.first() else {
+            return Ok(Vec::new());
+        };
+        let senders: std::collections::HashSet<&str> = chunk.iter().map(|event| event.user_id.as_str()).collect();
+        let state = self
+            .event_reader
+            .get_state_events_at_event(first)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get member state", &e))?;
+        Ok(state
+            .iter()
+            .filter(|event| event.event_type.as_deref() == Some("m.room.member"))
+            .filter(|event| event.state_key.as_deref().is_some_and(|key| senders.contains(key)))
+            .map(Self::state_event_json)
+            .collect())
+    }
+
+    /// Client JSON of `events` with their bundled aggregations.
+    pub(super) async fn client_events(&self, user_id: &str, events: &[RoomEvent]) -> ApiResult<Vec<serde_json::Value>> {
+        let bundles = self.bundled_relations(user_id, events).await?;
+        Ok(events
+            .iter()
+            .map(|event| {
+                let mut event_json = Self::pagination_event_json(event);
+                crate::sync_helpers::attach_bundled_relations(&mut event_json, &bundles);
+                event_json
+            })
+            .collect())
+    }
+
+    async fn bundled_relations(
+        &self,
+        user_id: &str,
+        events: &[RoomEvent],
+    ) -> ApiResult<std::collections::HashMap<String, serde_json::Value>> {
+        crate::sync_helpers::bundled_relations(
+            self.event_reader.as_ref(),
+            self.event_visibility.as_deref(),
+            user_id,
+            events,
+        )
+        .await
+    }
+
+    pub(super) async fn visible_events(&self, user_id: &str, events: Vec<RoomEvent>) -> ApiResult<Vec<RoomEvent>> {
+        match &self.event_visibility {
+            Some(visibility) => visibility.filter_events_for_client(user_id, events).await,
+            None => Ok(events),
+        }
+    }
+
+    pub(super) fn parse_pagination_token(token: &str, param: &str) -> ApiResult<RoomPaginationToken> {
+        RoomPaginationToken::parse(token)
+            .ok_or_else(|| ApiError::invalid_param(format!("Invalid pagination token in '{param}'")))
+    }
+
+    /// Stream position of `token`. Timestamp tokens from earlier releases
+    /// name the gap before (`dir=b`) or after (`dir=f`) their timestamp.
+    pub(super) async fn resolve_stream_position(
+        &self,
+        room_id: &str,
+        token: RoomPaginationToken,
+        backwards: bool,
+    ) -> ApiResult<i64> {
+        let before_ts = match token {
+            RoomPaginationToken::Timestamp(ts) if backwards => ts,
+            RoomPaginationToken::Timestamp(ts) => ts.saturating_add(1),
+            token => return Ok(token.stream().unwrap_or_default()),
+        };
+        self.event_reader
+            .get_room_stream_position(room_id, Some(before_ts))
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get room stream", &e))
+    }
+
+    /// Token just past `event` in the pagination direction.
+    pub(super) fn boundary_token(event: &synapse_storage::RoomEvent, backwards: bool) -> RoomPaginationToken {
+        let stream = event.stream_ordering.unwrap_or(0);
+        RoomPaginationToken::Topological {
+            topological: event.depth,
+            stream: if backwards { stream - 1 } else { stream },
+        }
+    }
+
+    fn pagination_event_json(event: &synapse_storage::RoomEvent) -> serde_json::Value {
+        json!({
+            "type": event.event_type,
+            "content": event.content,
+            "sender": event.user_id,
+            "origin_server_ts": event.origin_server_ts,
+            "event_id": event.event_id
+        })
+    }
+
+    fn state_event_json(event: &StateEvent) -> serde_json::Value {
+        json!({
+            "type": event.event_type,
+            "state_key": event.state_key,
+            "content": event.content,
+            "sender": event.sender,
+            "origin_server_ts": event.origin_server_ts,
+            "event_id": event.event_id
+        })
+    }
+
+    /// Live ephemeral events of `room_id` as `user_id` sees them; private
+    /// receipts of other users are left out.
     pub async fn get_ephemeral_events_for_client(
         &self,
         room_id: &str,
+        user_id: &str,
         limit: i64,
     ) -> ApiResult<Vec<serde_json::Value>> {
         let now = current_timestamp_millis();
@@ -282,10 +516,16 @@ impl MessagingService {
 
         Ok(rows
             .into_iter()
+            .filter(|row| row.event_type != PRIVATE_RECEIPT_EPHEMERAL_TYPE || row.user_id == user_id)
             .map(|row| {
                 let event_id = format!("$ephemeral_{}", row.stream_id);
+                let event_type = if row.event_type == PRIVATE_RECEIPT_EPHEMERAL_TYPE {
+                    "m.receipt"
+                } else {
+                    row.event_type.as_str()
+                };
                 json!({
-                    "type": row.event_type,
+                    "type": event_type,
                     "sender": row.user_id,
                     "content": row.content,
                     "origin_server_ts": row.created_ts,
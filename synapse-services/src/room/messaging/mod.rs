@@ -3,4 +3,5 @@ pub mod events;
 pub mod messages;
 pub mod read_markers;
 pub mod receipts;
+pub mod relations;
 pub mod service;
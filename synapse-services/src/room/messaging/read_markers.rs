@@ -18,6 +18,27 @@ impl MessagingService {
             .map_err(|e| ApiError::internal_with_log(&format!("Failed to set {marker_type} marker"), &e))
     }
 
+    /// Total unread notifications across the rooms `user_id` has joined; the
+    /// badge count pushed to their devices after a read receipt.
+    pub async fn get_total_unread_count(&self, user_id: &str) -> ApiResult<u32> {
+        let room_ids = self
+            .member_storage
+            .get_joined_rooms(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get joined rooms", &e))?;
+        if room_ids.is_empty() {
+            return Ok(0);
+        }
+
+        let counts = self
+            .event_reader
+            .get_unread_counts_batch(&room_ids, user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get unread counts", &e))?;
+        let total: i64 = counts.iter().map(|c| c.notification_count.max(0)).sum();
+        Ok(u32::try_from(total).unwrap_or(u32::MAX))
+    }
+
     pub async fn set_read_markers(&self, room_id: &str, user_id: &str, body: &serde_json::Value) -> ApiResult<()> {
         if let Some(event_id) = body.get("m.fully_read").and_then(|v| v.as_str()) {
             if event_id.starts_with('$') {
@@ -31,6 +52,12 @@ impl MessagingService {
             }
         }
 
+        if let Some(event_id) = body.get("m.read.private").and_then(|v| v.as_str()) {
+            if event_id.starts_with('$') {
+                self.send_receipt(room_id, user_id, event_id, "m.read.private", &serde_json::json!({})).await?;
+            }
+        }
+
         if let Some(marked_unread) = body.get("m.marked_unread").and_then(|v| v.as_object()) {
             if let Some(events) = marked_unread.get("events").and_then(|v| v.as_array()) {
                 for event in events {
@@ -2,12 +2,23 @@
 
 use crate::common::error::{ApiError, ApiResult};
 use serde_json::json;
+use std::collections::{HashMap, HashSet};
 use synapse_common::current_timestamp_millis;
 use synapse_storage::Receipt;
 
 use super::service::MessagingService;
 
+/// `room_ephemeral` type of private receipts, which only their owner sees
+/// (as `m.receipt`).
+pub(crate) const PRIVATE_RECEIPT_EPHEMERAL_TYPE: &str = "m.receipt.private";
+
+/// Receipt type that must never leave the server nor reach other users.
+const PRIVATE_RECEIPT_TYPE: &str = "m.read.private";
+
 impl MessagingService {
+    /// Store a receipt of a local user. Every receipt moves the user's own
+    /// read position, clearing their notifications; only public ones are
+    /// shown to the room and sent over federation.
     pub async fn send_receipt(
         &self,
         room_id: &str,
@@ -20,7 +31,9 @@ impl MessagingService {
             .add_receipt(user_id, user_id, room_id, event_id, receipt_type, body)
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to store receipt", &e))?;
+        self.update_read_marker(room_id, user_id, event_id, receipt_type).await?;
 
+        let private = receipt_type == PRIVATE_RECEIPT_TYPE;
         let now_ts = current_timestamp_millis();
         let mut receipt_entry = body.as_object().cloned().unwrap_or_default();
         receipt_entry.insert("ts".to_string(), json!(now_ts));
@@ -32,16 +45,25 @@ impl MessagingService {
             }
         });
 
+        let ephemeral_type = if private { PRIVATE_RECEIPT_EPHEMERAL_TYPE } else { "m.receipt" };
         self.event_writer
-            .add_ephemeral_event(room_id, user_id, "m.receipt", &receipt_content, now_ts)
+            .add_ephemeral_event(room_id, user_id, ephemeral_type, &receipt_content, now_ts)
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to store ephemeral receipt", &e))?;
 
+        if private {
+            return Ok(());
+        }
         if let Some(event_broadcaster) = &self.event_broadcaster {
             let receipt_edu = json!({
                 "edu_type": "m.receipt",
-                "room_id": room_id,
-                "content": receipt_content
+                "content": {
+                    room_id: {
+                        receipt_type: {
+                            user_id: { "event_ids": [event_id], "data": receipt_entry }
+                        }
+                    }
+                }
             });
 
             let _ = event_broadcaster.broadcast_edu_to_room(room_id, &receipt_edu, &self.server_name).await;
@@ -50,10 +72,62 @@ impl MessagingService {
         Ok(())
     }
 
-    pub async fn get_receipts(&self, room_id: &str, receipt_type: &str, event_id: &str) -> ApiResult<Vec<Receipt>> {
+    /// Store the receipts a remote server sent for `room_id` in one batch and
+    /// expose them to local clients. Receipts of users not joined to the room
+    /// are dropped. Returns how many were stored.
+    pub async fn receive_remote_receipts(&self, room_id: &str, mut receipts: Vec<Receipt>) -> ApiResult<usize> {
+        let joined: HashSet<String> = self
+            .member_storage
+            .get_joined_members(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get room members", &e))?
+            .into_iter()
+            .map(|member| member.user_id)
+            .collect();
+        receipts.retain(|receipt| joined.contains(&receipt.user_id));
+        if receipts.is_empty() {
+            return Ok(0);
+        }
+
         self.room_storage
+            .add_receipts(room_id, &receipts)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to store receipts", &e))?;
+
+        // Ephemeral receipts are kept per user, like the local ones.
+        let mut by_user: HashMap<&str, serde_json::Value> = HashMap::new();
+        for receipt in &receipts {
+            let mut entry = receipt.data.as_object().cloned().unwrap_or_default();
+            entry.insert("ts".to_string(), json!(receipt.ts));
+            let content = by_user.entry(&receipt.user_id).or_insert_with(|| json!({}));
+            content[&receipt.event_id][&receipt.receipt_type] = json!({ &receipt.user_id: entry });
+        }
+        let now_ts = current_timestamp_millis();
+        for (user_id, content) in by_user {
+            self.event_writer
+                .add_ephemeral_event(room_id, user_id, "m.receipt", &content, now_ts)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to store ephemeral receipt", &e))?;
+        }
+
+        Ok(receipts.len())
+    }
+
+    /// Receipts of `receipt_type` on `event_id` as seen by `user_id`: private
+    /// receipts of other users are left out.
+    pub async fn get_receipts(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        receipt_type: &str,
+        event_id: &str,
+    ) -> ApiResult<Vec<Receipt>> {
+        let mut receipts = self
+            .room_storage
             .get_receipts(room_id, receipt_type, event_id)
             .await
-            .map_err(|e| ApiError::internal_with_log("Failed to get receipts", &e))
+            .map_err(|e| ApiError::internal_with_log("Failed to get receipts", &e))?;
+        receipts.retain(|receipt| receipt.receipt_type != PRIVATE_RECEIPT_TYPE || receipt.user_id == user_id);
+        Ok(receipts)
     }
 }
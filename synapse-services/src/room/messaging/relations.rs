@@ -0,0 +1,97 @@
+//! Event relations: `/relations` pagination over the events relating to a
+//! parent event.
+
+use crate::common::error::{ApiError, ApiResult};
+use serde_json::json;
+use synapse_storage::EventRelationsQuery;
+
+use super::service::MessagingService;
+
+/// Query of a `/relations` request.
+#[derive(Debug, Clone, Default)]
+pub struct EventRelationsParams {
+    /// Only relations of this `rel_type`.
+    pub rel_type: Option<String>,
+    /// With `rel_type`, only relation events of this type.
+    pub event_type: Option<String>,
+    /// Token to paginate from; the newest (`dir=b`) or oldest (`dir=f`)
+    /// relation when absent.
+    pub from: Option<String>,
+    /// Token to stop at.
+    pub to: Option<String>,
+    pub limit: i64,
+    /// `"b"` or `"f"`.
+    pub direction: String,
+}
+
+impl MessagingService {
+    /// `/relations` for `event_id`. Tokens are `/messages` tokens, so a
+    /// `next_batch` is only returned while the page came back full.
+    pub async fn get_event_relations(
+        &self,
+        room_id: &str,
+        user_id: &str,
+        event_id: &str,
+        params: &EventRelationsParams,
+    ) -> ApiResult<serde_json::Value> {
+        let parent = self
+            .event_reader
+            .get_event(event_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get event", &e))?
+            .filter(|event| event.room_id == room_id);
+        let parent = match parent {
+            Some(parent) => self.visible_events(user_id, vec![parent]).await?.pop(),
+            None => None,
+        };
+        if parent.is_none() {
+            return Err(ApiError::not_found("Event not found".to_string()));
+        }
+
+        let backwards = params.direction != "f";
+        let from = match params.from.as_deref() {
+            Some(token) => {
+                let token = Self::parse_pagination_token(token, "from")?;
+                Some(self.resolve_stream_position(room_id, token, backwards).await?)
+            }
+            None => None,
+        };
+        let to = match params.to.as_deref() {
+            Some(token) => {
+                let token = Self::parse_pagination_token(token, "to")?;
+                Some(self.resolve_stream_position(room_id, token, backwards).await?)
+            }
+            None => None,
+        };
+
+        let query = EventRelationsQuery {
+            rel_type: params.rel_type.clone(),
+            event_type: params.event_type.clone(),
+            from,
+            to,
+            limit: params.limit,
+            backwards,
+        };
+        let events = self
+            .event_reader
+            .get_event_relations(room_id, event_id, &query)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get relations", &e))?;
+
+        let next_batch = events
+            .last()
+            .filter(|_| events.len() as i64 >= params.limit)
+            .map(|last| Self::boundary_token(last, backwards).to_string());
+        let visible = self.visible_events(user_id, events).await?;
+        let chunk = self.client_events(user_id, &visible).await?;
+
+        let mut response = json!({ "chunk": chunk });
+        if let Some(next_batch) = next_batch {
+            response["next_batch"] = json!(next_batch);
+        }
+        if let Some(prev_batch) = &params.from {
+            response["prev_batch"] = json!(prev_batch);
+        }
+        Ok(response)
+    }
+}
@@ -43,6 +43,13 @@ pub struct MessagingService {
     /// Room summary service for updating room metadata on events.
     pub(crate) room_summary_service: Arc<RoomSummaryService>,
     pub(crate) cache: Arc<CacheManager>,
+    /// Pre-send content filter; `None` when filtering is not configured.
+    pub(crate) content_filter: Option<Arc<crate::content_filter_service::ContentFilterService>>,
+    /// Read-side visibility rules for `/messages` and `/context`; `None`
+    /// returns every stored event.
+    pub(crate) event_visibility: Option<Arc<crate::event_visibility_service::EventVisibilityService>>,
+    /// Auth rules checked before locally created events are stored.
+    pub(crate) event_auth: crate::auth::EventAuthChecker,
 }
 
 /// Configuration for constructing a [`MessagingService`].
@@ -63,11 +70,14 @@ pub struct MessagingServiceConfig {
     pub key_rotation_manager: Option<Arc<synapse_federation::KeyRotationManager>>,
     pub room_summary_service: Arc<RoomSummaryService>,
     pub cache: Arc<CacheManager>,
+    pub content_filter: Option<Arc<crate::content_filter_service::ContentFilterService>>,
+    pub event_visibility: Option<Arc<crate::event_visibility_service::EventVisibilityService>>,
 }
 
 impl MessagingService {
     pub fn new(config: MessagingServiceConfig) -> Self {
         Self {
+            event_auth: crate::auth::EventAuthChecker::new(config.event_reader.clone()),
             event_reader: config.event_reader,
             event_writer: config.event_writer,
             room_storage: config.room_storage,
@@ -85,9 +95,15 @@ impl MessagingService {
             key_rotation_manager: config.key_rotation_manager,
             room_summary_service: config.room_summary_service,
             cache: config.cache,
+            content_filter: config.content_filter,
+            event_visibility: config.event_visibility,
         }
     }
 
+    pub fn event_auth(&self) -> &crate::auth::EventAuthChecker {
+        &self.event_auth
+    }
+
     /// Dispatch an event to application services (best-effort).
     pub(crate) async fn dispatch_appservice_event(
         &self,
@@ -119,12 +135,32 @@ impl MessagingService {
             return Ok(());
         };
 
-        // 1. Fetch prev_events (forward extremities of the room).
-        let prev_events = self.event_reader.get_latest_event_ids_in_room(&event.room_id, 10).await.unwrap_or_default();
+        // 1. Fetch prev_events (forward extremities of the room), falling back
+        // to the most recent events while the room has none tracked yet.
+        let mut prev_events = self.event_reader.get_forward_extremities(&event.room_id, 10).await.unwrap_or_default();
+        if prev_events.is_empty() {
+            prev_events = self.event_reader.get_latest_event_ids_in_room(&event.room_id, 10).await.unwrap_or_default();
+        }
 
         // Exclude the event itself.
         let prev_events: Vec<String> = prev_events.into_iter().filter(|id| id != &event.event_id).collect();
 
+        // Link the event into the DAG; it replaces its prev_events as a
+        // forward extremity.
+        let depth =
+            match self.event_writer.record_local_event_graph(&event.room_id, &event.event_id, &prev_events).await {
+                Ok(depth) => depth,
+                Err(e) => {
+                    ::tracing::warn!(
+                        event_id = %event.event_id,
+                        room_id = %event.room_id,
+                        error = %e,
+                        "Failed to record event prev_events"
+                    );
+                    event.depth.max(1)
+                }
+            };
+
         // 2. Build the PDU JSON.
         let mut pdu = json!({
             "event_id": event.event_id,
@@ -136,6 +172,7 @@ impl MessagingService {
             "origin_server_ts": event.origin_server_ts,
             "origin": self.server_name,
             "prev_events": prev_events,
+            "depth": depth,
         });
 
         if let Some(ref state_key) = event.state_key {
@@ -154,7 +191,7 @@ impl MessagingService {
             .ok_or_else(|| ApiError::internal("No signing key available".to_string()))?;
 
         sign_and_hash_event(&self.server_name, &signing_key.key_id, &signing_key.secret_key, &mut pdu)
-            .map_err(|e| ApiError::internal(format!("Failed to sign event: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to sign event", &e))?;
 
         // 4. Persist signatures and hashes back to the events table.
         let signatures = pdu.get("signatures").cloned().unwrap_or(serde_json::Value::Null);
@@ -18,8 +18,14 @@ pub use lifecycle::service::LifecycleService;
 pub mod membership;
 pub use membership::service::MembershipService;
 pub mod messaging;
+pub use messaging::messages::RoomMessagesParams;
+pub use messaging::relations::EventRelationsParams;
 pub use messaging::service::MessagingService;
+pub mod power_level_templates;
+pub use power_level_templates::PowerLevelTemplate;
 pub mod service;
+pub mod shutdown;
+pub use shutdown::{ShutdownRoomOutcome, ShutdownRoomParams};
 pub mod space;
 pub mod state;
 pub use state::service::RoomStateService;
@@ -0,0 +1,201 @@
+//! Named power-level templates ("role presets") for common room shapes.
+//!
+//! A template only sets the level fields (`events_default`, `state_default`,
+//! `ban`, `events`, ...). The `users` map is always carried over from the
+//! room's existing content, so applying a template never grants or revokes
+//! anyone's personal level.
+
+use crate::common::error::{ApiError, ApiResult};
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PowerLevelTemplate {
+    /// Only moderators post; everyone else reads and reacts.
+    Announcement,
+    /// Anyone posts; moderators own room settings and moderation.
+    ModeratedCommunity,
+    /// Nobody but room admins can send anything.
+    ReadOnlyArchive,
+}
+
+impl PowerLevelTemplate {
+    pub const ALL: [Self; 3] = [Self::Announcement, Self::ModeratedCommunity, Self::ReadOnlyArchive];
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|template| template.name() == name)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Announcement => "announcement",
+            Self::ModeratedCommunity => "moderated_community",
+            Self::ReadOnlyArchive => "read_only_archive",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+        match self {
+            Self::Announcement => "Only moderators can post; members can read and react",
+            Self::ModeratedCommunity => "Members can post; moderators manage settings, kicks and bans",
+            Self::ReadOnlyArchive => "Only room admins can send events; the room is kept for reading",
+        }
+    }
+
+    /// The level fields this template sets, without a `users` map.
+    pub fn levels(self) -> Value {
+        match self {
+            Self::Announcement => json!({
+                "users_default": 0,
+                "events_default": 50,
+                "state_default": 50,
+                "invite": 50,
+                "kick": 50,
+                "ban": 50,
+                "redact": 50,
+                "events": {
+                    "m.reaction": 0,
+                    "m.room.name": 50,
+                    "m.room.topic": 50,
+                    "m.room.avatar": 50,
+                    "m.room.pinned_events": 50,
+                    "m.room.canonical_alias": 50,
+                    "m.room.power_levels": 100,
+                    "m.room.history_visibility": 100,
+                    "m.room.encryption": 100,
+                    "m.room.server_acl": 100,
+                    "m.room.tombstone": 100,
+                },
+                "notifications": { "room": 50 },
+            }),
+            Self::ModeratedCommunity => json!({
+                "users_default": 0,
+                "events_default": 0,
+                "state_default": 50,
+                "invite": 0,
+                "kick": 50,
+                "ban": 50,
+                "redact": 50,
+                "events": {
+                    "m.room.name": 50,
+                    "m.room.topic": 50,
+                    "m.room.avatar": 50,
+                    "m.room.pinned_events": 50,
+                    "m.room.canonical_alias": 50,
+                    "m.room.power_levels": 100,
+                    "m.room.history_visibility": 100,
+                    "m.room.encryption": 100,
+                    "m.room.server_acl": 100,
+                    "m.room.tombstone": 100,
+                },
+                "notifications": { "room": 50 },
+            }),
+            Self::ReadOnlyArchive => json!({
+                "users_default": 0,
+                "events_default": 100,
+                "state_default": 100,
+                "invite": 100,
+                "kick": 100,
+                "ban": 100,
+                "redact": 100,
+                "events": {
+                    "m.reaction": 100,
+                    "m.room.power_levels": 100,
+                    "m.room.tombstone": 100,
+                },
+                "notifications": { "room": 100 },
+            }),
+        }
+    }
+
+    /// Rewrite `current` with this template's levels, keeping its `users` map.
+    pub fn apply_to(self, current: &Value) -> Value {
+        let mut content = self.levels();
+        let users = current.get("users").filter(|users| users.is_object()).cloned().unwrap_or_else(|| json!({}));
+        content["users"] = users;
+        content
+    }
+}
+
+/// Check `content` against the power-level auth rules: every level must be an
+/// integer, and `sender` must keep enough power to change power levels again,
+/// so a template cannot lock the room.
+pub fn validate_power_levels_content(content: &Value, sender: &str) -> ApiResult<()> {
+    let Some(map) = content.as_object() else {
+        return Err(ApiError::invalid_param("m.room.power_levels content must be an object".to_string()));
+    };
+
+    for key in ["users_default", "events_default", "state_default", "invite", "kick", "ban", "redact"] {
+        if map.get(key).is_some_and(|level| level.as_i64().is_none()) {
+            return Err(ApiError::invalid_param(format!("Power level '{key}' must be an integer")));
+        }
+    }
+    for key in ["users", "events", "notifications"] {
+        match map.get(key) {
+            None => {}
+            Some(Value::Object(levels)) => {
+                if let Some((name, _)) = levels.iter().find(|(_, level)| level.as_i64().is_none()) {
+                    return Err(ApiError::invalid_param(format!("Power level '{key}.{name}' must be an integer")));
+                }
+            }
+            Some(_) => return Err(ApiError::invalid_param(format!("Power level '{key}' must be an object"))),
+        }
+    }
+
+    let level_of = |key: &str, default: i64| map.get(key).and_then(Value::as_i64).unwrap_or(default);
+    let sender_level = map
+        .get("users")
+        .and_then(|users| users.get(sender))
+        .and_then(Value::as_i64)
+        .unwrap_or_else(|| level_of("users_default", 0));
+    let required = map
+        .get("events")
+        .and_then(|events| events.get("m.room.power_levels"))
+        .and_then(Value::as_i64)
+        .unwrap_or_else(|| level_of("state_default", 50));
+
+    if sender_level < required {
+        return Err(ApiError::forbidden(format!(
+            "{sender} would be left at level {sender_level}, below the {required} needed to change power levels"
+        )));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn templates_round_trip_by_name() {
+        for template in PowerLevelTemplate::ALL {
+            assert_eq!(PowerLevelTemplate::from_name(template.name()), Some(template));
+        }
+        assert_eq!(PowerLevelTemplate::from_name("private_chat"), None);
+    }
+
+    #[test]
+    fn apply_to_keeps_users_and_replaces_levels() {
+        let current = json!({
+            "users": { "@admin:example.com": 100, "@mod:example.com": 50 },
+            "events_default": 0,
+            "state_default": 50,
+        });
+        let content = PowerLevelTemplate::ReadOnlyArchive.apply_to(&current);
+        assert_eq!(content["users"], current["users"]);
+        assert_eq!(content["events_default"], 100);
+        assert!(validate_power_levels_content(&content, "@admin:example.com").is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_locking_out_the_sender() {
+        let content = PowerLevelTemplate::Announcement.apply_to(&json!({ "users": { "@mod:example.com": 50 } }));
+        assert!(validate_power_levels_content(&content, "@mod:example.com").is_err());
+    }
+
+    #[test]
+    fn validate_rejects_non_integer_levels() {
+        let content = json!({ "users": { "@a:example.com": 100 }, "ban": "50" });
+        assert!(validate_power_levels_content(&content, "@a:example.com").is_err());
+    }
+}
@@ -42,6 +42,9 @@ pub struct CreateRoomConfig {
     /// Room version to record on m.room.create. Defaults to the server's
     /// capabilities default ("10") when None.
     pub room_version: Option<String>,
+    /// Named power-level template applied over the spec defaults, before
+    /// `power_level_content_override`.
+    pub power_level_template: Option<super::PowerLevelTemplate>,
     /// Power level overrides applied on top of the spec defaults.
     pub power_level_content_override: Option<serde_json::Value>,
 }
@@ -78,6 +81,12 @@ pub struct RoomServiceConfig {
     /// that leaving a LOCAL encrypted room marks the megolm session for
     /// rotation (forward secrecy). `None` in test setups.
     pub key_rotation_storage: Option<Arc<dyn synapse_e2ee::key_rotation::KeyRotationStorageApi>>,
+    /// Pre-send content filter handed to the messaging sub-service. `None`
+    /// disables filtering (and module spam checks) on the send path.
+    pub content_filter: Option<Arc<crate::content_filter_service::ContentFilterService>>,
+    /// Read-side visibility rules handed to the messaging sub-service. `None`
+    /// disables filtering of `/messages` and `/context`.
+    pub event_visibility: Option<Arc<crate::event_visibility_service::EventVisibilityService>>,
 }
 
 pub struct RoomService {
@@ -154,12 +163,13 @@ impl RoomService {
             key_rotation_manager: infra.key_rotation_manager.clone(),
             room_summary_service: config.room_summary_service.clone(),
             cache: config.cache.clone(),
+            content_filter: config.content_filter.clone(),
+            event_visibility: config.event_visibility.clone(),
         };
         let messaging = MessagingService::new(messaging_cfg);
 
         let state_cfg = RoomStateServiceConfig {
             room_storage: config.room_storage.clone(),
-            member_storage: config.member_storage.clone(),
             event_reader: config.event_reader.clone().expect("event_reader required"),
             event_writer: config.event_writer.clone().expect("event_writer required"),
             room_tag_storage: config.room_tag_storage.clone(),
@@ -575,6 +585,7 @@ impl From<crate::friend_room_service::FriendRoomCreateRoomConfig> for CreateRoom
             initial_state: config.initial_state,
             creation_content: config.creation_content,
             room_version: config.room_version,
+            power_level_template: None,
             power_level_content_override: config.power_level_content_override,
         }
     }
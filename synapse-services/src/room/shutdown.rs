@@ -0,0 +1,168 @@
+//! Admin room shutdown: evict every local member, block the room ID so
+//! nobody can get back in, drop it from the public directory and, if asked,
+//! move the evicted users into a fresh "violation notice" room.
+//!
+//! Reference: `element-hq/synapse` `synapse/handlers/room.py::RoomShutdownHandler`.
+//!
+//! Remote members are left alone: their servers hold their own copy of the
+//! room, and once the local users have left this server no longer takes part
+//! in it.
+
+use serde::Serialize;
+use serde_json::json;
+use synapse_common::{current_timestamp_millis, generate_event_id};
+
+use crate::common::error::{ApiError, ApiResult};
+
+use super::service::{CreateRoomConfig, RoomService};
+
+/// Name given to the replacement room when the caller does not pick one.
+pub const DEFAULT_SHUTDOWN_ROOM_NAME: &str = "Content Violation Notification";
+
+/// Notice posted in the replacement room when the caller does not supply one.
+pub const DEFAULT_SHUTDOWN_MESSAGE: &str =
+    "Sharing illegal content on this server is not permitted and rooms in violation will be blocked.";
+
+/// Options for [`RoomService::shutdown_room`].
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownRoomParams {
+    /// Administrator performing the shutdown, recorded as the blocker.
+    pub requester_id: String,
+    /// Local user that creates the replacement room and posts the notice.
+    /// No replacement room is created when `None`.
+    pub new_room_user_id: Option<String>,
+    pub new_room_name: Option<String>,
+    pub message: Option<String>,
+    /// Block the room ID so local users cannot join, be invited or knock.
+    pub block: bool,
+}
+
+/// What a shutdown did, in the shape of Synapse's delete-room response.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct ShutdownRoomOutcome {
+    pub kicked_users: Vec<String>,
+    pub failed_to_kick_users: Vec<String>,
+    pub local_aliases: Vec<String>,
+    pub new_room_id: Option<String>,
+}
+
+impl RoomService {
+    pub async fn shutdown_room(&self, room_id: &str, params: ShutdownRoomParams) -> ApiResult<ShutdownRoomOutcome> {
+        if !self.state.room_exists(room_id).await? {
+            return Err(ApiError::not_found("Room not found".to_string()));
+        }
+
+        if let Some(new_room_user_id) = params.new_room_user_id.as_deref() {
+            if self.membership.is_remote_user(new_room_user_id) {
+                return Err(ApiError::bad_request("new_room_user_id must be a local user".to_string()));
+            }
+            if !self
+                .user_storage
+                .user_exists(new_room_user_id)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Failed to check user existence", &e))?
+            {
+                return Err(ApiError::not_found(format!("User {new_room_user_id} not found")));
+            }
+        }
+
+        // Block first, so nobody can rejoin while the members are being removed.
+        if params.block {
+            self.state.block_room(room_id, &params.requester_id, params.message.as_deref()).await?;
+        }
+
+        let new_room_id = match params.new_room_user_id.as_deref() {
+            Some(new_room_user_id) => Some(self.create_violation_notice_room(new_room_user_id, &params).await?),
+            None => None,
+        };
+
+        let members = self
+            .member_storage
+            .get_room_members(room_id, "join")
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get room members", &e))?;
+
+        let mut outcome = ShutdownRoomOutcome { new_room_id: new_room_id.clone(), ..Default::default() };
+        for user_id in members.into_iter().map(|m| m.user_id).filter(|u| !self.membership.is_remote_user(u)) {
+            if let Err(e) = self.membership.leave_room(room_id, &user_id).await {
+                ::tracing::warn!(room_id = %room_id, user_id = %user_id, error = %e, "Failed to remove user during room shutdown");
+                outcome.failed_to_kick_users.push(user_id);
+                continue;
+            }
+
+            if let Some(new_room_id) = new_room_id.as_deref() {
+                if let Err(e) = self.membership.force_join_room(new_room_id, &user_id).await {
+                    ::tracing::warn!(
+                        room_id = %room_id,
+                        new_room_id = %new_room_id,
+                        user_id = %user_id,
+                        error = %e,
+                        "Failed to move user into the violation notice room"
+                    );
+                }
+            }
+            outcome.kicked_users.push(user_id);
+        }
+
+        outcome.local_aliases = self.state.get_room_aliases(room_id).await?;
+        for alias in &outcome.local_aliases {
+            self.state.remove_room_alias_by_name(alias).await?;
+        }
+        self.state.remove_room_directory(room_id).await?;
+        self.room_storage
+            .shutdown_room(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to shutdown room", &e))?;
+
+        ::tracing::info!(
+            room_id = %room_id,
+            kicked = outcome.kicked_users.len(),
+            failed = outcome.failed_to_kick_users.len(),
+            new_room_id = ?outcome.new_room_id,
+            blocked = params.block,
+            "Room shut down"
+        );
+
+        Ok(outcome)
+    }
+
+    /// Create the room evicted members are moved into. Everyone but the
+    /// creator starts below the default event level, so the notice is the
+    /// only thing said in it.
+    async fn create_violation_notice_room(&self, creator_id: &str, params: &ShutdownRoomParams) -> ApiResult<String> {
+        let config = CreateRoomConfig {
+            visibility: Some("private".to_string()),
+            name: Some(params.new_room_name.clone().unwrap_or_else(|| DEFAULT_SHUTDOWN_ROOM_NAME.to_string())),
+            preset: Some("public_chat".to_string()),
+            power_level_content_override: Some(json!({ "users_default": -10 })),
+            ..Default::default()
+        };
+        let created = self.lifecycle.create_room(creator_id, config).await?;
+        let new_room_id = created
+            .get("room_id")
+            .and_then(|value| value.as_str())
+            .ok_or_else(|| ApiError::internal("Room creation did not return a room ID"))?
+            .to_string();
+
+        self.messaging
+            .create_event(
+                synapse_storage::CreateEventParams {
+                    event_id: generate_event_id(&self.server_name),
+                    room_id: new_room_id.clone(),
+                    user_id: creator_id.to_string(),
+                    event_type: "m.room.message".to_string(),
+                    content: json!({
+                        "msgtype": "m.text",
+                        "body": params.message.as_deref().unwrap_or(DEFAULT_SHUTDOWN_MESSAGE),
+                    }),
+                    state_key: None,
+                    origin_server_ts: current_timestamp_millis(),
+                    redacts: None,
+                },
+                None,
+            )
+            .await?;
+
+        Ok(new_room_id)
+    }
+}
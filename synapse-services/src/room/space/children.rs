@@ -19,6 +19,13 @@ impl SpaceService {
 
         self.ensure_space_creator_access(&request.space_id, &request.sender).await?;
 
+        if request.order.as_deref().is_some_and(|order| !is_valid_space_child_order(order)) {
+            return Err(ApiError::bad_request(format!(
+                "order must be 1-{} printable ASCII characters",
+                SPACE_CHILD_ORDER_MAX_LEN
+            )));
+        }
+
         let _room = self
             .room_storage
             .get_room(&request.room_id)
@@ -38,11 +45,8 @@ impl SpaceService {
         })?;
 
         let event_id = format!("${}:{}", uuid::Uuid::new_v4(), self.server_name);
-        let content = json!({
-            "room_id": child.room_id,
-            "via": child.via_servers,
-            "suggested": child.is_suggested,
-        });
+        let mut content = space_child_event_content(&child);
+        content["room_id"] = json!(child.room_id);
 
         self.space_storage
             .add_space_event(&event_id, &child.space_id, "m.space.child", &child.sender, content, Some(&child.room_id))
@@ -181,10 +185,7 @@ impl SpaceService {
                 children_state: vec![serde_json::json!({
                     "type": "m.space.child",
                     "state_key": &child.room_id,
-                    "content": {
-                        "via": &child.via_servers,
-                        "suggested": child.is_suggested,
-                    },
+                    "content": space_child_event_content(child),
                     "sender": &child.sender,
                 })],
             }
@@ -297,6 +298,7 @@ impl SpaceService {
                     "room_type": "m.space",
                     "via_servers": child.via_servers,
                     "suggested": child.is_suggested,
+                    "order": child.order,
                 }))
             } else {
                 Some(serde_json::json!({
@@ -304,6 +306,7 @@ impl SpaceService {
                     "room_type": "m.room",
                     "via_servers": child.via_servers,
                     "suggested": child.is_suggested,
+                    "order": child.order,
                 }))
             }
         }))
@@ -324,10 +327,7 @@ impl SpaceService {
                 serde_json::json!({
                     "type": "m.space.child",
                     "state_key": &child.room_id,
-                    "content": {
-                        "via": &child.via_servers,
-                        "suggested": child.is_suggested,
-                    },
+                    "content": space_child_event_content(child),
                     "sender": &child.sender,
                 })
             }).collect::<Vec<_>>(),
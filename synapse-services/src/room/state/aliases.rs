@@ -2,6 +2,7 @@
 
 use crate::common::error::{ApiError, ApiResult};
 use serde_json::json;
+use synapse_storage::room::RoomPublicationRequest;
 
 use super::super::utils::validate_room_alias_input;
 use super::service::RoomStateService;
@@ -67,6 +68,57 @@ impl RoomStateService {
             .map_err(|e| ApiError::internal_with_log("Failed to remove room from directory", &e))
     }
 
+    /// Queue `room_id` for admin approval instead of listing it right away.
+    pub async fn request_room_publication(&self, room_id: &str, user_id: &str) -> ApiResult<RoomPublicationRequest> {
+        self.room_storage
+            .upsert_publication_request(room_id, user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to queue room publication request", &e))
+    }
+
+    pub async fn get_room_publication_request(&self, room_id: &str) -> ApiResult<Option<RoomPublicationRequest>> {
+        self.room_storage
+            .get_publication_request(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get room publication request", &e))
+    }
+
+    pub async fn list_room_publication_requests(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> ApiResult<Vec<RoomPublicationRequest>> {
+        if !matches!(status, "pending" | "approved" | "rejected") {
+            return Err(ApiError::invalid_param("status must be one of pending, approved, rejected".to_string()));
+        }
+        self.room_storage
+            .list_publication_requests(status, limit)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to list room publication requests", &e))
+    }
+
+    /// Approve (and list) or reject a pending publication request.
+    pub async fn review_room_publication_request(
+        &self,
+        room_id: &str,
+        approve: bool,
+        reviewed_by: &str,
+        reason: Option<&str>,
+    ) -> ApiResult<RoomPublicationRequest> {
+        self.room_storage
+            .review_publication_request(room_id, approve, reviewed_by, reason)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to review room publication request", &e))?
+            .ok_or_else(|| ApiError::not_found("No pending publication request for this room".to_string()))
+    }
+
+    pub async fn withdraw_room_publication_request(&self, room_id: &str) -> ApiResult<bool> {
+        self.room_storage
+            .withdraw_publication_request(room_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to withdraw room publication request", &e))
+    }
+
     pub async fn get_public_rooms(&self, limit: i64) -> ApiResult<serde_json::Value> {
         let rooms = self
             .room_storage
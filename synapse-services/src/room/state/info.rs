@@ -183,6 +183,12 @@ impl RoomStateService {
         self.room_storage.get_room(room_id).await.map_err(|e| ApiError::database_with_log("Failed to get room", &e))
     }
 
+    /// Whether non-members may peek into the room, i.e. its
+    /// `m.room.history_visibility` is `world_readable`.
+    pub async fn is_room_world_readable(&self, room_id: &str) -> ApiResult<bool> {
+        Ok(self.get_room_record(room_id).await?.is_some_and(|room| room.history_visibility == "world_readable"))
+    }
+
     pub async fn get_room_listings_status(&self, room_id: &str) -> ApiResult<Option<(bool, bool)>> {
         self.room_storage
             .get_room_listings_status(room_id)
@@ -204,18 +210,6 @@ impl RoomStateService {
             .map_err(|e| ApiError::internal_with_log("Failed to set room private", &e))
     }
 
-    pub async fn shutdown_room_and_remove_members(&self, room_id: &str) -> ApiResult<()> {
-        self.room_storage
-            .shutdown_room(room_id)
-            .await
-            .map_err(|e| ApiError::internal_with_log("Failed to shutdown room", &e))?;
-        self.member_storage
-            .remove_all_members(room_id)
-            .await
-            .map_err(|e| ApiError::database_with_log("Failed to remove room members", &e))?;
-        Ok(())
-    }
-
     pub async fn grant_room_admin(&self, room_id: &str, user_id: &str) -> ApiResult<()> {
         let event_id = synapse_common::generate_event_id(&self.server_name);
         let sender = format!("@admin:{}", self.server_name);
@@ -1,4 +1,5 @@
 pub mod aliases;
 pub mod info;
+pub mod resolution;
 pub mod service;
 pub mod tags;
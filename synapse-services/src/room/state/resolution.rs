@@ -0,0 +1,70 @@
+//! State resolution of inbound state events against the room's current state.
+
+use crate::common::error::{ApiError, ApiResult};
+use serde_json::Value;
+use std::collections::{HashMap, HashSet};
+use synapse_common::room_versions::DEFAULT_ROOM_VERSION;
+use synapse_federation::state_res::{self, StateMap, StateResEvent};
+
+use super::service::RoomStateService;
+
+impl RoomStateService {
+    /// Whether the state event `pdu` survives state resolution v2 against
+    /// the room's current state. The current state and the current state with
+    /// `pdu` applied are resolved as two forks, unless `pdu` already descends
+    /// from the event it replaces through its auth chain.
+    pub async fn inbound_state_wins_resolution(&self, room_id: &str, pdu: &Value) -> ApiResult<bool> {
+        let Some(incoming) = StateResEvent::from_pdu(pdu) else {
+            return Ok(true);
+        };
+        let Some(key) = incoming.key() else {
+            return Ok(true);
+        };
+
+        let current: StateMap = self
+            .event_reader
+            .get_state_events(room_id)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to get room state", &e))?
+            .into_iter()
+            .filter_map(|e| Some(((e.event_type?, e.state_key?), e.event_id)))
+            .collect();
+        let replaced = match current.get(&key) {
+            Some(replaced) if *replaced != incoming.event_id => replaced.clone(),
+            _ => return Ok(true),
+        };
+
+        let roots: Vec<String> = current.values().chain(&incoming.auth_events).cloned().collect();
+        let mut events: HashMap<String, StateResEvent> = self
+            .event_reader
+            .get_auth_chain_pdus(room_id, &roots)
+            .await
+            .map_err(|e| ApiError::database_with_log("Failed to load auth chains", &e))?
+            .iter()
+            .filter_map(|row| StateResEvent::from_pdu(&row.to_pdu(&self.server_name)))
+            .map(|event| (event.event_id.clone(), event))
+            .collect();
+
+        let mut seen = HashSet::new();
+        let mut stack: Vec<&str> = incoming.auth_events.iter().map(String::as_str).collect();
+        while let Some(id) = stack.pop() {
+            if id == replaced {
+                return Ok(true);
+            }
+            if seen.insert(id) {
+                if let Some(event) = events.get(id) {
+                    stack.extend(event.auth_events.iter().map(String::as_str));
+                }
+            }
+        }
+
+        let room_version = self.get_room_version(room_id).await?.unwrap_or_else(|| DEFAULT_ROOM_VERSION.to_string());
+        let mut candidate = current.clone();
+        candidate.insert(key.clone(), incoming.event_id.clone());
+        let incoming_id = incoming.event_id.clone();
+        events.insert(incoming_id.clone(), incoming);
+
+        let resolved = state_res::resolve(&room_version, &[current, candidate], &events);
+        Ok(resolved.get(&key) == Some(&incoming_id))
+    }
+}
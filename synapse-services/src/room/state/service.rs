@@ -6,14 +6,13 @@
 use crate::UserService;
 use std::sync::Arc;
 use synapse_storage::room_tag::RoomTagStoreApi;
-use synapse_storage::{MemberStoreApi, RoomStoreApi, UserStore};
+use synapse_storage::{RoomStoreApi, UserStore};
 
 /// Domain service for room state operations — aliases, tags, info queries,
 /// directory listings, block/unblock, encryption status, and admin search.
 #[derive(Clone)]
 pub struct RoomStateService {
     pub(crate) room_storage: Arc<dyn RoomStoreApi>,
-    pub(crate) member_storage: Arc<dyn MemberStoreApi>,
     pub(crate) event_reader: Arc<dyn synapse_storage::event::EventReader>,
     pub(crate) event_writer: Arc<dyn synapse_storage::event::EventWriter>,
     pub(crate) room_tag_storage: Arc<dyn RoomTagStoreApi>,
@@ -26,7 +25,6 @@ pub struct RoomStateService {
 /// Configuration for constructing a [`RoomStateService`].
 pub struct RoomStateServiceConfig {
     pub room_storage: Arc<dyn RoomStoreApi>,
-    pub member_storage: Arc<dyn MemberStoreApi>,
     pub event_reader: Arc<dyn synapse_storage::event::EventReader>,
     pub event_writer: Arc<dyn synapse_storage::event::EventWriter>,
     pub room_tag_storage: Arc<dyn RoomTagStoreApi>,
@@ -39,7 +37,6 @@ impl RoomStateService {
     pub fn new(config: RoomStateServiceConfig) -> Self {
         Self {
             room_storage: config.room_storage,
-            member_storage: config.member_storage,
             event_reader: config.event_reader,
             event_writer: config.event_writer,
             room_tag_storage: config.room_tag_storage,
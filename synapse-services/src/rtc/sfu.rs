@@ -96,7 +96,7 @@ pub struct LivekitClient {
 
 impl LivekitClient {
     pub fn new(config: LivekitConfig) -> Self {
-        Self { config, http_client: reqwest::Client::new() }
+        Self { config, http_client: synapse_common::outbound_http::client() }
     }
 
     pub async fn create_room(&self, request: CreateRoomRequest) -> Result<LivekitRoom, LivekitError> {
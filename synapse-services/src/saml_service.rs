@@ -5,15 +5,18 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::{Arc, Mutex, OnceLock};
 use std::time::{Duration, SystemTime, UNIX_EPOCH};
-use synapse_common::config::SamlConfig;
+use synapse_common::config::{SamlConfig, SsoCollisionPolicy};
 use synapse_common::current_timestamp_millis;
 use synapse_common::error::ApiError;
+use synapse_common::sso_mapping;
 use synapse_common::xml_parser::{parse_saml_metadata, parse_saml_response};
 use synapse_storage::saml::*;
 use tracing::info;
 
 const SAML_REQUEST_TTL_SECONDS: u64 = 600;
 const SAML_CLOCK_SKEW_SECONDS: i64 = 300;
+/// Upper bound on `alice1`, `alice2`, ... probes under `append_number`.
+const SAML_MAX_COLLISION_SUFFIX: u32 = 100;
 
 #[derive(Debug, Clone)]
 struct SamlPendingRequest {
@@ -86,7 +89,7 @@ impl SamlService {
         storage: Arc<dyn synapse_storage::saml::SamlStoreApi>,
         server_name: String,
     ) -> Self {
-        let http_client = reqwest::Client::builder()
+        let http_client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout))
             .build()
             .unwrap_or_else(|_| reqwest::Client::new());
@@ -152,6 +155,10 @@ impl SamlService {
                 "uid": self.config.attribute_mapping.uid,
                 "displayname": self.config.attribute_mapping.displayname,
                 "email": self.config.attribute_mapping.email,
+                "displayname_template": self.config.attribute_mapping.displayname_template,
+                "required_attributes": self.config.attribute_mapping.required_attributes,
+                "grandfather_by_email": self.config.attribute_mapping.grandfather_by_email,
+                "on_collision": self.config.attribute_mapping.on_collision,
             },
             "nameid_format": self.config.nameid_format,
             "allow_existing_users": self.config.allow_existing_users,
@@ -312,10 +319,7 @@ impl SamlService {
             }
             mapping.user_id
         } else {
-            if self.config.block_unknown_users {
-                return Err(ApiError::unauthorized("Unknown user blocked"));
-            }
-            format!("@{}:{}", user.localpart, self.server_name)
+            self.resolve_new_user_id(&user).await?
         };
 
         let session_id = Self::generate_session_id();
@@ -510,30 +514,80 @@ impl SamlService {
     ) -> Result<SamlUser, ApiError> {
         let mapping = &self.config.attribute_mapping;
 
-        let localpart = if self.config.use_name_id_for_user_id {
+        if let Some(name) = sso_mapping::missing_required_attribute(&mapping.required_attributes, attributes) {
+            return Err(ApiError::forbidden(format!("SAML assertion does not satisfy required attribute '{name}'")));
+        }
+
+        let first = |attr: &str| attributes.get(attr).and_then(|v| v.first()).map(String::as_str);
+        let lookup = |name: &str| match name {
+            "name_id" => Some(name_id),
+            "uid" => mapping.uid.as_deref().and_then(first).or_else(|| first("uid")),
+            other => first(other),
+        };
+
+        let localpart = if self.config.use_name_id_for_user_id || mapping.uid.is_none() {
             name_id.to_string()
-        } else if let Some(uid_attr) = &mapping.uid {
-            attributes
-                .get(uid_attr)
-                .and_then(|v| v.first())
-                .map_or_else(|| name_id.to_string(), |s| self.config.user_id_template.replace("{uid}", s))
         } else {
-            name_id.to_string()
+            // An assertion missing a template attribute keeps the historical
+            // NameID fallback rather than failing the login.
+            sso_mapping::render_template(&self.config.user_id_template, lookup).unwrap_or_else(|missing| {
+                tracing::debug!(attribute = %missing, "SAML user_id_template attribute missing, using NameID");
+                name_id.to_string()
+            })
         };
+        let localpart = sso_mapping::map_to_localpart(&localpart);
+        if localpart.is_empty() {
+            return Err(ApiError::forbidden("SAML assertion maps to an empty localpart"));
+        }
 
-        let displayname = mapping
-            .displayname
-            .as_ref()
-            .and_then(|attr| attributes.get(attr))
-            .and_then(|v| v.first())
-            .map(|s| s.to_string());
+        let displayname = match &mapping.displayname_template {
+            Some(template) => sso_mapping::render_template(template, lookup).ok(),
+            None => mapping.displayname.as_deref().and_then(first).map(str::to_string),
+        };
 
-        let email =
-            mapping.email.as_ref().and_then(|attr| attributes.get(attr)).and_then(|v| v.first()).map(|s| s.to_string());
+        let email = mapping.email.as_deref().and_then(first).map(str::to_string);
 
         Ok(SamlUser { name_id: name_id.to_string(), localpart, displayname, email, issuer: issuer.to_string() })
     }
 
+    /// Picks the local account for an identity seen for the first time:
+    /// the account holding its verified email when grandfathering is on,
+    /// otherwise the mapped localpart subject to the collision policy.
+    async fn resolve_new_user_id(&self, user: &SamlUser) -> Result<String, ApiError> {
+        let mapping = &self.config.attribute_mapping;
+        if mapping.grandfather_by_email {
+            if let Some(email) = &user.email {
+                if let Some(user_id) = self.storage.get_user_id_by_verified_email(email).await? {
+                    info!(user_id = %user_id, issuer = %user.issuer, "Linking SAML identity to existing account by email");
+                    return Ok(user_id);
+                }
+            }
+        }
+
+        if self.config.block_unknown_users {
+            return Err(ApiError::unauthorized("Unknown user blocked"));
+        }
+
+        let user_id = format!("@{}:{}", user.localpart, self.server_name);
+        if !self.storage.user_exists(&user_id).await? {
+            return Ok(user_id);
+        }
+        match mapping.on_collision {
+            SsoCollisionPolicy::Reject => Err(ApiError::forbidden(format!(
+                "SAML identity maps to {user_id}, which belongs to an account not linked to it"
+            ))),
+            SsoCollisionPolicy::AppendNumber => {
+                for suffix in 1..=SAML_MAX_COLLISION_SUFFIX {
+                    let candidate = format!("@{}{suffix}:{}", user.localpart, self.server_name);
+                    if !self.storage.user_exists(&candidate).await? {
+                        return Ok(candidate);
+                    }
+                }
+                Err(ApiError::forbidden(format!("No free localpart derived from {user_id}")))
+            }
+        }
+    }
+
     fn validate_response(
         &self,
         issuer: &str,
@@ -1185,6 +1239,7 @@ mod tests {
                 uid: Some("uid".to_string()),
                 displayname: Some("cn".to_string()),
                 email: Some("mail".to_string()),
+                ..Default::default()
             },
             nameid_format: "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent".to_string(),
             allow_existing_users: true,
@@ -1286,6 +1341,35 @@ mod tests {
         assert_eq!(session_index, Some("session123".to_string()));
     }
 
+    #[tokio::test]
+    async fn test_map_user_applies_templates_and_required_attributes() {
+        let mut service = create_test_service();
+        let mut config = create_test_config();
+        config.user_id_template = "{givenName}.{sn}".to_string();
+        config.attribute_mapping.displayname_template = Some("{givenName} {sn}".to_string());
+        config.attribute_mapping.required_attributes.insert("group".to_string(), Some("staff".to_string()));
+        service.config = Arc::new(config);
+
+        let mut attributes = HashMap::from([
+            ("givenName".to_string(), vec!["Alice".to_string()]),
+            ("sn".to_string(), vec!["Liddell".to_string()]),
+            ("mail".to_string(), vec!["alice@example.com".to_string()]),
+            ("group".to_string(), vec!["staff".to_string()]),
+        ]);
+        let user = service.map_user("AAbb12", "https://idp.example.com", &attributes).unwrap();
+        assert_eq!(user.localpart, "alice.liddell");
+        assert_eq!(user.displayname.as_deref(), Some("Alice Liddell"));
+        assert_eq!(user.email.as_deref(), Some("alice@example.com"));
+
+        attributes.remove("sn");
+        let user = service.map_user("AAbb12", "https://idp.example.com", &attributes).unwrap();
+        assert_eq!(user.localpart, "aabb12");
+        assert_eq!(user.displayname, None);
+
+        attributes.insert("group".to_string(), vec!["students".to_string()]);
+        assert!(service.map_user("AAbb12", "https://idp.example.com", &attributes).is_err());
+    }
+
     #[tokio::test]
     async fn test_validate_response_accepts_valid_constraints() {
         let mut config = create_test_config();
@@ -162,6 +162,36 @@ pub struct SearchRoomEvent {
     pub origin_server_ts: i64,
 }
 
+impl crate::event_visibility_service::VisibleEvent for SearchRoomEvent {
+    fn room_id(&self) -> &str {
+        &self.room_id
+    }
+
+    fn sender(&self) -> &str {
+        &self.sender
+    }
+
+    fn event_type(&self) -> &str {
+        &self.event_type
+    }
+
+    fn state_key(&self) -> Option<&str> {
+        None
+    }
+
+    fn stream_ordering(&self) -> Option<i64> {
+        None
+    }
+
+    fn origin_server_ts(&self) -> i64 {
+        self.origin_server_ts
+    }
+
+    fn content_mut(&mut self) -> &mut Value {
+        &mut self.content
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SearchRoomEventsPage {
     pub results: Vec<SearchRoomEvent>,
@@ -231,7 +261,7 @@ impl SearchService {
         let base_url = url.trim_end_matches('/').to_string();
 
         Self {
-            client: reqwest::Client::new(),
+            client: synapse_common::outbound_http::client(),
             enabled,
             base_url,
             index_name: index_name.to_string(),
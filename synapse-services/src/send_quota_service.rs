@@ -0,0 +1,284 @@
+//! Per-user daily message and invite quotas (`rate_limit.daily_quotas`).
+//!
+//! Counters are kept per UTC day in a Redis hash per user. While Redis is
+//! disabled or failing, the same counters are kept in `user_daily_quotas`;
+//! each backend is checked on its own, and admin usage reports add the two.
+
+use std::sync::Arc;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use serde::Serialize;
+use synapse_cache::CacheManager;
+use synapse_common::config::DailyQuotaConfig;
+use synapse_common::{current_timestamp_millis, ApiError, ApiResult};
+use synapse_storage::rate_limit::RateLimitStoreApi;
+use synapse_storage::UserStore;
+
+/// Redis keys outlive the day they count so a late reader still sees them.
+const QUOTA_KEY_TTL_SECS: u64 = 2 * 24 * 60 * 60;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum QuotaKind {
+    Messages,
+    Invites,
+}
+
+impl QuotaKind {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Messages => "messages",
+            Self::Invites => "invites",
+        }
+    }
+}
+
+/// A user's counters for the current day and the limits that apply to them.
+#[derive(Debug, Clone, Serialize)]
+pub struct DailyQuotaUsage {
+    pub user_id: String,
+    pub day: NaiveDate,
+    pub messages: i64,
+    pub invites: i64,
+    pub messages_per_day: u32,
+    pub invites_per_day: u32,
+    pub new_account: bool,
+    pub exempt: bool,
+}
+
+/// The limits for one user on one day.
+#[derive(Debug, Clone, Copy)]
+struct QuotaLimits {
+    messages: u32,
+    invites: u32,
+    new_account: bool,
+    exempt: bool,
+}
+
+impl QuotaLimits {
+    fn for_kind(&self, kind: QuotaKind) -> u32 {
+        match kind {
+            QuotaKind::Messages => self.messages,
+            QuotaKind::Invites => self.invites,
+        }
+    }
+}
+
+pub struct SendQuotaService {
+    config: DailyQuotaConfig,
+    storage: Arc<dyn RateLimitStoreApi>,
+    user_storage: Arc<dyn UserStore>,
+    cache: Arc<CacheManager>,
+}
+
+impl SendQuotaService {
+    pub fn new(
+        config: DailyQuotaConfig,
+        storage: Arc<dyn RateLimitStoreApi>,
+        user_storage: Arc<dyn UserStore>,
+        cache: Arc<CacheManager>,
+    ) -> Self {
+        Self { config, storage, user_storage, cache }
+    }
+
+    /// Counts `amount` sends of `kind` against the user's quota for today.
+    /// Fails with `M_LIMIT_EXCEEDED` (retry at the next UTC midnight) and
+    /// leaves the counter unchanged when they would go over it.
+    #[::tracing::instrument(skip(self))]
+    pub async fn consume(&self, user_id: &str, kind: QuotaKind, amount: u32) -> ApiResult<()> {
+        if !self.config.enabled || amount == 0 {
+            return Ok(());
+        }
+
+        let now = current_timestamp_millis();
+        let limits = self.limits_for(user_id, now).await?;
+        if limits.exempt {
+            return Ok(());
+        }
+
+        let (day, retry_after_ms) = quota_day(now);
+        let amount = i64::from(amount);
+        let count = self.increment(user_id, day, kind, amount).await?;
+        if count <= i64::from(limits.for_kind(kind)) {
+            return Ok(());
+        }
+
+        if let Err(error) = self.increment(user_id, day, kind, -amount).await {
+            ::tracing::warn!(user_id = %user_id, error = %error, "Failed to roll back rejected quota usage");
+        }
+        ::tracing::info!(
+            user_id = %user_id,
+            kind = kind.as_str(),
+            new_account = limits.new_account,
+            "Daily send quota exceeded"
+        );
+        Err(ApiError::rate_limited_with_retry(retry_after_ms))
+    }
+
+    /// Today's counters and limits for `user_id`.
+    pub async fn usage(&self, user_id: &str) -> ApiResult<DailyQuotaUsage> {
+        let now = current_timestamp_millis();
+        let limits = self.limits_for(user_id, now).await?;
+        let (day, _) = quota_day(now);
+
+        let mut usage = self
+            .storage
+            .get_daily_quota_usage(user_id, day)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load quota usage", &e))?;
+        if self.cache.is_redis_enabled() {
+            match self.cache.hgetall(&quota_key(user_id, day)).await {
+                Ok(counters) => {
+                    for (kind, count) in counters {
+                        *usage.entry(kind).or_default() += count.parse::<i64>().unwrap_or(0);
+                    }
+                }
+                Err(error) => ::tracing::warn!(user_id = %user_id, error = %error, "Failed to read quota counters"),
+            }
+        }
+
+        Ok(DailyQuotaUsage {
+            user_id: user_id.to_string(),
+            day,
+            messages: usage.get(QuotaKind::Messages.as_str()).copied().unwrap_or(0),
+            invites: usage.get(QuotaKind::Invites.as_str()).copied().unwrap_or(0),
+            messages_per_day: limits.messages,
+            invites_per_day: limits.invites,
+            new_account: limits.new_account,
+            exempt: limits.exempt,
+        })
+    }
+
+    /// Clears the user's counters in both backends.
+    pub async fn reset(&self, user_id: &str) -> ApiResult<()> {
+        let (day, _) = quota_day(current_timestamp_millis());
+        self.cache.delete(&quota_key(user_id, day)).await;
+        self.storage
+            .delete_daily_quota_usage(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to reset quota usage", &e))
+    }
+
+    async fn limits_for(&self, user_id: &str, now: i64) -> ApiResult<QuotaLimits> {
+        let user = self
+            .user_storage
+            .get_user_by_id(user_id)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to load user", &e))?;
+
+        let exempt = user
+            .as_ref()
+            .is_some_and(|user| (self.config.exempt_admins && user.is_admin) || user.appservice_id.is_some());
+        let new_account_age_ms =
+            i64::try_from(self.config.new_account_age_secs.saturating_mul(1000)).unwrap_or(i64::MAX);
+        let new_account = user.is_some_and(|user| now.saturating_sub(user.created_ts) < new_account_age_ms);
+
+        Ok(if new_account {
+            QuotaLimits {
+                messages: self.config.new_account_messages_per_day,
+                invites: self.config.new_account_invites_per_day,
+                new_account,
+                exempt,
+            }
+        } else {
+            QuotaLimits {
+                messages: self.config.messages_per_day,
+                invites: self.config.invites_per_day,
+                new_account,
+                exempt,
+            }
+        })
+    }
+
+    /// Adds `amount` to the counter, in Redis when it is up and in the
+    /// database otherwise, and returns the new value.
+    async fn increment(&self, user_id: &str, day: NaiveDate, kind: QuotaKind, amount: i64) -> ApiResult<i64> {
+        if self.cache.is_redis_enabled() {
+            let key = quota_key(user_id, day);
+            match self.cache.hincrby(&key, kind.as_str(), amount).await {
+                Ok(count) => {
+                    if count == amount {
+                        self.cache.expire(&key, QUOTA_KEY_TTL_SECS).await;
+                    }
+                    return Ok(count);
+                }
+                Err(error) => {
+                    ::tracing::warn!(user_id = %user_id, error = %error, "Quota counter unavailable in Redis; using database");
+                }
+            }
+        }
+
+        self.storage
+            .increment_daily_quota(user_id, day, kind.as_str(), amount)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to update quota usage", &e))
+    }
+}
+
+fn quota_key(user_id: &str, day: NaiveDate) -> String {
+    format!("quota:daily:{day}:{user_id}")
+}
+
+/// The UTC day containing `now_ms` and the milliseconds until it ends.
+fn quota_day(now_ms: i64) -> (NaiveDate, u64) {
+    let now = DateTime::<Utc>::from_timestamp_millis(now_ms).unwrap_or_default();
+    let day = now.date_naive();
+    let next_midnight = (day + Duration::days(1)).and_hms_opt(0, 0, 0).unwrap_or_default().and_utc();
+    let retry_after_ms = u64::try_from((next_midnight - now).num_milliseconds()).unwrap_or(0);
+    (day, retry_after_ms)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synapse_storage::test_mocks::{FakeUserStore, InMemoryRateLimitStore};
+
+    const ALICE: &str = "@alice:example.com";
+
+    fn service(config: DailyQuotaConfig) -> SendQuotaService {
+        SendQuotaService::new(
+            config,
+            Arc::new(InMemoryRateLimitStore::new()),
+            Arc::new(FakeUserStore::new()),
+            Arc::new(CacheManager::new(&synapse_cache::CacheConfig::default())),
+        )
+    }
+
+    fn enabled(invites_per_day: u32) -> DailyQuotaConfig {
+        DailyQuotaConfig { enabled: true, invites_per_day, new_account_age_secs: 0, ..DailyQuotaConfig::default() }
+    }
+
+    #[tokio::test]
+    async fn consume_rejects_past_the_limit_without_counting() {
+        let svc = service(enabled(2));
+        svc.consume(ALICE, QuotaKind::Invites, 2).await.unwrap();
+
+        let err = svc.consume(ALICE, QuotaKind::Invites, 1).await.unwrap_err();
+        assert!(err.to_string().contains("Rate limited"));
+        assert_eq!(svc.usage(ALICE).await.unwrap().invites, 2);
+    }
+
+    #[tokio::test]
+    async fn reset_clears_usage() {
+        let svc = service(enabled(2));
+        svc.consume(ALICE, QuotaKind::Invites, 2).await.unwrap();
+        svc.reset(ALICE).await.unwrap();
+
+        assert_eq!(svc.usage(ALICE).await.unwrap().invites, 0);
+        svc.consume(ALICE, QuotaKind::Invites, 1).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn disabled_quotas_are_not_counted() {
+        let svc = service(DailyQuotaConfig { invites_per_day: 0, ..DailyQuotaConfig::default() });
+        svc.consume(ALICE, QuotaKind::Invites, 5).await.unwrap();
+        assert_eq!(svc.usage(ALICE).await.unwrap().invites, 0);
+    }
+
+    #[test]
+    fn quota_day_retries_at_utc_midnight() {
+        // 2026-10-17T23:59:00Z
+        let (day, retry_after_ms) = quota_day(1_792_281_540_000);
+        assert_eq!(day, NaiveDate::from_ymd_opt(2026, 10, 17).unwrap());
+        assert_eq!(retry_after_ms, 60_000);
+    }
+}
@@ -299,4 +299,42 @@ impl ServerNotificationService {
             )
             .await
     }
+
+    /// Sends a server notice to `user_id` in a fresh notice room, from
+    /// `@server:<server_name>`. Returns the room ID, the message event ID and
+    /// the stored notice ID.
+    #[instrument(skip(self, body))]
+    pub async fn notify_user(
+        &self,
+        server_name: &str,
+        user_id: &str,
+        msgtype: &str,
+        body: &str,
+    ) -> Result<(String, String, i64), ApiError> {
+        let target_user = self.user_service.get_user_or_not_found(user_id).await?;
+
+        let room_id = format!("!server_notice_{}:{}", uuid::Uuid::new_v4(), server_name);
+        let server_user = format!("@server:{}", server_name);
+        let message_event_id = format!("${}:{}", uuid::Uuid::new_v4(), server_name);
+        let create_event_id = format!("${}:{}", uuid::Uuid::new_v4(), server_name);
+        let membership_event_id = format!("${}:{}", uuid::Uuid::new_v4(), server_name);
+
+        let notice_id = self
+            .send_server_notice(
+                &room_id,
+                &server_user,
+                &target_user.user_id,
+                &target_user.displayname,
+                &target_user.avatar_url,
+                &message_event_id,
+                &create_event_id,
+                &membership_event_id,
+                msgtype,
+                body,
+                synapse_common::current_timestamp_millis(),
+            )
+            .await?;
+
+        Ok((room_id, message_event_id, notice_id))
+    }
 }
@@ -61,7 +61,7 @@ impl SlidingSyncService {
         if receipts_enabled {
             let room_ids: Vec<String> =
                 rooms_response.as_object().map(|obj| obj.keys().cloned().collect()).unwrap_or_default();
-            let receipts = self.storage.get_receipts_for_rooms(&room_ids).await?;
+            let receipts = self.storage.get_receipts_for_rooms(&room_ids, user_id).await?;
             response_extensions.insert(
                 "receipts".to_string(),
                 serde_json::json!({
@@ -1,4 +1,5 @@
 use serde_json::Value;
+use synapse_common::RoomPaginationToken;
 
 use super::SlidingSyncService;
 use crate::sync_helpers;
@@ -20,7 +21,10 @@ impl SlidingSyncService {
         }
         events.reverse();
 
-        let prev_batch = events.first().map(|event| format!("t{}", event.origin_server_ts));
+        let prev_batch = events.first().map(|event| match event.stream_ordering {
+            Some(stream) => RoomPaginationToken::Stream(stream - 1).to_string(),
+            None => format!("t{}", event.origin_server_ts),
+        });
         let timeline = events.iter().map(sync_helpers::room_event_to_json).collect();
         Ok((timeline, limited, prev_batch))
     }
@@ -52,7 +52,7 @@ pub struct AliyunSmsProvider {
 impl AliyunSmsProvider {
     pub fn new(config: &SmsConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: synapse_common::outbound_http::client(),
             endpoint: if config.endpoint.is_empty() {
                 "dysmsapi.aliyuncs.com".to_string()
             } else {
@@ -103,7 +103,7 @@ impl AliyunSmsProvider {
     fn sign(&self, query: &str) -> Result<String, ApiError> {
         let string_to_sign = format!("GET&{}&{}", aliyun_percent_encode("/"), aliyun_percent_encode(query));
         let mut mac = Hmac::<Sha1>::new_from_slice(format!("{}&", self.access_key_secret).as_bytes())
-            .map_err(|e| ApiError::internal(format!("HMAC key initialization failed: {e}")))?;
+            .map_err(|e| ApiError::internal_with_log("HMAC key initialization failed", &e))?;
         mac.update(string_to_sign.as_bytes());
         let result = mac.finalize();
         Ok(base64::Engine::encode(&base64::engine::general_purpose::STANDARD, result.into_bytes()))
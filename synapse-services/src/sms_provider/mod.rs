@@ -54,7 +54,7 @@ pub struct HttpSmsProvider {
 impl HttpSmsProvider {
     pub fn new(config: &SmsConfig) -> Self {
         Self {
-            client: Client::new(),
+            client: synapse_common::outbound_http::client(),
             endpoint: config.endpoint.trim().to_string(),
             api_key: (!config.api_key.trim().is_empty()).then(|| config.api_key.clone()),
             api_secret: (!config.api_secret.trim().is_empty()).then(|| config.api_secret.clone()),
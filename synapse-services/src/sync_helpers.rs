@@ -4,11 +4,16 @@
 //! Federation-format variants live in `sync_service::response` where the extra
 //! depth/origin fields are needed.
 
-use serde_json::{json, Value};
+use std::collections::{HashMap, HashSet};
+
+use serde_json::{json, Map, Value};
 use synapse_common::current_timestamp_millis;
-use synapse_storage::event::RoomEvent;
+use synapse_storage::event::{EventReader, RoomEvent};
 use synapse_storage::StateEvent;
 
+use crate::common::error::{ApiError, ApiResult};
+use crate::event_visibility_service::EventVisibilityService;
+
 /// Convert a [`RoomEvent`] to its Client-format JSON representation.
 pub fn room_event_to_json(event: &RoomEvent) -> Value {
     let now = current_timestamp_millis();
@@ -52,3 +57,195 @@ pub fn state_event_to_json(event: &StateEvent) -> Value {
     }
     obj
 }
+
+/// Bundled aggregations (`unsigned["m.relations"]`) of the events of one room
+/// that have threads, edits or references, keyed by event ID. Thread replies
+/// and edits `user_id` may not see are left out.
+pub async fn bundled_relations(
+    event_reader: &dyn EventReader,
+    visibility: Option<&EventVisibilityService>,
+    user_id: &str,
+    events: &[RoomEvent],
+) -> ApiResult<HashMap<String, Value>> {
+    let Some(first) = events.first() else {
+        return Ok(HashMap::new());
+    };
+    let parent_ids: Vec<String> = events.iter().map(|event| event.event_id.clone()).collect();
+    let aggregations = event_reader
+        .get_relation_aggregations(&first.room_id, &parent_ids, user_id)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Failed to get relations", &e))?;
+    if aggregations.is_empty() {
+        return Ok(HashMap::new());
+    }
+
+    let related: Vec<RoomEvent> = aggregations
+        .values()
+        .flat_map(|aggregation| aggregation.thread_latest_event.iter().chain(aggregation.replacement.iter()))
+        .cloned()
+        .collect();
+    let related = match visibility {
+        Some(visibility) => visibility.filter_events_for_client(user_id, related).await?,
+        None => related,
+    };
+    let visible: HashSet<&str> = related.iter().map(|event| event.event_id.as_str()).collect();
+
+    let mut bundles = HashMap::new();
+    for event in events {
+        let Some(aggregation) = aggregations.get(&event.event_id) else { continue };
+        let mut relations = Map::new();
+        if let Some(latest) =
+            aggregation.thread_latest_event.as_ref().filter(|latest| visible.contains(latest.event_id.as_str()))
+        {
+            relations.insert(
+                "m.thread".to_string(),
+                json!({
+                    "latest_event": room_event_to_json(latest),
+                    "count": aggregation.thread_count,
+                    "current_user_participated": aggregation.thread_participated || event.user_id == user_id,
+                }),
+            );
+        }
+        if let Some(replacement) =
+            aggregation.replacement.as_ref().filter(|replacement| visible.contains(replacement.event_id.as_str()))
+        {
+            relations.insert("m.replace".to_string(), room_event_to_json(replacement));
+        }
+        if !aggregation.references.is_empty() {
+            let chunk: Vec<Value> = aggregation.references.iter().map(|id| json!({ "event_id": id })).collect();
+            relations.insert("m.reference".to_string(), json!({ "chunk": chunk }));
+        }
+        if !aggregation.annotations.is_empty() {
+            let chunk: Vec<Value> = aggregation
+                .annotations
+                .iter()
+                .map(|group| {
+                    json!({
+                        "type": group.event_type,
+                        "key": group.key,
+                        "count": group.count,
+                        "current_user_participated": group.current_user_participated,
+                    })
+                })
+                .collect();
+            relations.insert("m.annotation".to_string(), json!({ "chunk": chunk }));
+        }
+        if !relations.is_empty() {
+            bundles.insert(event.event_id.clone(), Value::Object(relations));
+        }
+    }
+    Ok(bundles)
+}
+
+/// Adds the bundle of `event_json`'s event from `bundles`, if any.
+pub fn attach_bundled_relations(event_json: &mut Value, bundles: &HashMap<String, Value>) {
+    let Some(relations) = event_json.get("event_id").and_then(Value::as_str).and_then(|id| bundles.get(id)) else {
+        return;
+    };
+    let relations = relations.clone();
+    if !event_json.get("unsigned").is_some_and(Value::is_object) {
+        event_json["unsigned"] = json!({});
+    }
+    event_json["unsigned"]["m.relations"] = relations;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use synapse_storage::test_mocks::InMemoryEventStore;
+
+    fn event(event_id: &str, sender: &str, stream_ordering: i64, content: Value) -> RoomEvent {
+        RoomEvent {
+            event_id: event_id.to_string(),
+            room_id: "!room:localhost".to_string(),
+            user_id: sender.to_string(),
+            event_type: "m.room.message".to_string(),
+            content,
+            state_key: None,
+            depth: stream_ordering,
+            origin_server_ts: 1_700_000_000_000 + stream_ordering,
+            processed_ts: 0,
+            not_before: 0,
+            status: None,
+            reference_image: None,
+            origin: "localhost".to_string(),
+            stream_ordering: Some(stream_ordering),
+            redacts: None,
+        }
+    }
+
+    fn related(event_id: &str, sender: &str, stream_ordering: i64, rel_type: &str, parent: &str) -> RoomEvent {
+        event(
+            event_id,
+            sender,
+            stream_ordering,
+            json!({ "body": event_id, "m.relates_to": { "rel_type": rel_type, "event_id": parent } }),
+        )
+    }
+
+    #[tokio::test]
+    async fn bundled_relations_summarise_threads_edits_and_references() {
+        let root = event("$root", "@alice:localhost", 1, json!({ "body": "root" }));
+        let plain = event("$plain", "@alice:localhost", 2, json!({ "body": "plain" }));
+        let store = InMemoryEventStore::new();
+        store
+            .seed_events(vec![
+                root.clone(),
+                plain.clone(),
+                related("$reply1", "@bob:localhost", 3, "m.thread", "$root"),
+                related("$edit", "@alice:localhost", 4, "m.replace", "$root"),
+                related("$forged_edit", "@bob:localhost", 5, "m.replace", "$root"),
+                related("$reply2", "@bob:localhost", 6, "m.thread", "$root"),
+                related("$ref", "@bob:localhost", 7, "m.reference", "$root"),
+            ])
+            .await;
+
+        let bundles = bundled_relations(&store, None, "@carol:localhost", &[root, plain]).await.unwrap();
+        assert!(!bundles.contains_key("$plain"));
+        let relations = &bundles["$root"];
+        assert_eq!(relations["m.thread"]["count"], 2);
+        assert_eq!(relations["m.thread"]["latest_event"]["event_id"], "$reply2");
+        assert_eq!(relations["m.thread"]["current_user_participated"], false);
+        assert_eq!(relations["m.replace"]["event_id"], "$edit");
+        assert_eq!(relations["m.reference"]["chunk"], json!([{ "event_id": "$ref" }]));
+
+        let mut event_json = json!({ "event_id": "$root", "unsigned": { "age": 5 } });
+        attach_bundled_relations(&mut event_json, &bundles);
+        assert_eq!(event_json["unsigned"]["age"], 5);
+        assert_eq!(event_json["unsigned"]["m.relations"]["m.replace"]["event_id"], "$edit");
+    }
+
+    #[tokio::test]
+    async fn bundled_relations_count_reactions_per_key() {
+        let root = event("$root", "@alice:localhost", 1, json!({ "body": "root" }));
+        let reaction = |event_id: &str, sender: &str, stream_ordering: i64, key: &str| {
+            let mut reaction = event(
+                event_id,
+                sender,
+                stream_ordering,
+                json!({ "m.relates_to": { "rel_type": "m.annotation", "event_id": "$root", "key": key } }),
+            );
+            reaction.event_type = "m.reaction".to_string();
+            reaction
+        };
+        let store = InMemoryEventStore::new();
+        store
+            .seed_events(vec![
+                root.clone(),
+                reaction("$smile", "@bob:localhost", 2, "😄"),
+                reaction("$up1", "@bob:localhost", 3, "👍"),
+                reaction("$up2", "@carol:localhost", 4, "👍"),
+                reaction("$up_again", "@carol:localhost", 5, "👍"),
+            ])
+            .await;
+
+        let bundles = bundled_relations(&store, None, "@carol:localhost", &[root]).await.unwrap();
+        assert_eq!(
+            bundles["$root"]["m.annotation"]["chunk"],
+            json!([
+                { "type": "m.reaction", "key": "👍", "count": 2, "current_user_participated": true },
+                { "type": "m.reaction", "key": "😄", "count": 1, "current_user_participated": false },
+            ])
+        );
+    }
+}
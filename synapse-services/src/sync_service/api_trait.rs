@@ -51,7 +51,22 @@ pub trait SyncServiceApi: Send + Sync {
 
     async fn get_public_rooms(&self, limit: i64, since: Option<&str>) -> ApiResult<serde_json::Value>;
 
-    async fn get_events(&self, user_id: &str, from: &str, timeout: u64) -> ApiResult<serde_json::Value>;
+    async fn filter_lazy_loaded_member_state(
+        &self,
+        user_id: &str,
+        device_id: Option<&str>,
+        room_id: &str,
+        member_events: Vec<serde_json::Value>,
+        include_redundant_members: bool,
+    ) -> Vec<serde_json::Value>;
+
+    async fn get_events(
+        &self,
+        user_id: &str,
+        from: Option<&str>,
+        filter_id: Option<&str>,
+        timeout: u64,
+    ) -> ApiResult<serde_json::Value>;
 }
 
 #[async_trait]
@@ -114,7 +129,25 @@ impl SyncServiceApi for SyncService {
         self.get_public_rooms(limit, since).await
     }
 
-    async fn get_events(&self, user_id: &str, from: &str, timeout: u64) -> ApiResult<serde_json::Value> {
-        self.get_events(user_id, from, timeout).await
+    async fn filter_lazy_loaded_member_state(
+        &self,
+        user_id: &str,
+        device_id: Option<&str>,
+        room_id: &str,
+        member_events: Vec<serde_json::Value>,
+        include_redundant_members: bool,
+    ) -> Vec<serde_json::Value> {
+        self.filter_lazy_loaded_member_state(user_id, device_id, room_id, member_events, include_redundant_members)
+            .await
+    }
+
+    async fn get_events(
+        &self,
+        user_id: &str,
+        from: Option<&str>,
+        filter_id: Option<&str>,
+        timeout: u64,
+    ) -> ApiResult<serde_json::Value> {
+        self.get_events(user_id, from, filter_id, timeout).await
     }
 }
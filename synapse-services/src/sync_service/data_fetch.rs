@@ -1,11 +1,12 @@
 use super::types::*;
 use super::SyncService;
 use crate::map_internal;
+use crate::room::messaging::receipts::PRIVATE_RECEIPT_EPHEMERAL_TYPE;
 use serde_json::{json, Value};
 use std::collections::{HashMap, HashSet};
 use synapse_common::current_timestamp_millis;
 use synapse_common::*;
-use synapse_storage::event::SinceFilter;
+use synapse_storage::event::{RoomEphemeralEvent, RoomEvent, SinceFilter, StateEvent};
 
 impl SyncService {
     pub(crate) async fn update_presence(&self, user_id: &str, set_presence: &str) -> ApiResult<()> {
@@ -79,6 +80,47 @@ impl SyncService {
             .collect())
     }
 
+    /// State of each left room as it stood at the start of its timeline.
+    /// The timeline ends at the leave, so nothing the room did after the user
+    /// left is sent; an incremental sync gets only what changed since `since`.
+    pub(crate) async fn get_left_room_state_batch(
+        &self,
+        room_ids: &[String],
+        event_format: SyncEventFormat,
+        since: SinceFilter,
+        timelines: &HashMap<String, &[RoomEvent]>,
+        membership_events: &HashMap<String, StateEvent>,
+    ) -> ApiResult<HashMap<String, Vec<Value>>> {
+        if room_ids.is_empty() {
+            return Ok(HashMap::new());
+        }
+
+        let until: HashMap<String, i64> = room_ids
+            .iter()
+            .filter_map(|room_id| {
+                let timeline_start = timelines
+                    .get(room_id)
+                    .and_then(|timeline| timeline.iter().filter_map(|event| event.stream_ordering).min());
+                let after_leave =
+                    membership_events.get(room_id).and_then(|event| event.stream_ordering).map(|stream| stream + 1);
+                timeline_start.or(after_leave).map(|until| (room_id.clone(), until))
+            })
+            .collect();
+        let state_by_room = self
+            .event_reader
+            .get_state_events_since_batch(room_ids, since, &until)
+            .await
+            .map_err(map_internal!("Failed to get left room state events"))?;
+
+        Ok(state_by_room
+            .into_iter()
+            .map(|(room_id, events)| {
+                let values = events.iter().map(|event| Self::state_event_to_json(event, event_format)).collect();
+                (room_id, values)
+            })
+            .collect())
+    }
+
     pub(crate) async fn get_state_events_for_sync_batch(
         &self,
         room_ids: &[String],
@@ -93,33 +135,52 @@ impl SyncService {
             return self.get_room_state_events_batch(room_ids, event_format).await;
         }
 
-        let delta_state_by_room = if let Some(stream_ord) = params.since_stream_ordering {
-            self.event_reader
-                .get_state_events_since_batch(room_ids, SinceFilter::StreamOrdering(stream_ord))
-                .await
-                .map_err(|e| ApiError::internal_with_log("Failed to get room state events", &e))?
-        } else {
-            self.event_reader
-                .get_state_events_since_batch(room_ids, SinceFilter::OriginServerTs(params.since_ts))
-                .await
-                .map_err(|e| ApiError::internal_with_log("Failed to get room state events", &e))?
+        let since = match params.since_stream_ordering {
+            Some(stream_ord) => SinceFilter::StreamOrdering(stream_ord),
+            None => SinceFilter::OriginServerTs(params.since_ts),
         };
+        let timeline_starts: HashMap<String, i64> = params
+            .timelines
+            .iter()
+            .filter_map(|(room_id, timeline)| {
+                timeline.iter().filter_map(|event| event.stream_ordering).min().map(|start| (room_id.clone(), start))
+            })
+            .collect();
+        let delta_state_by_room = self
+            .event_reader
+            .get_state_events_since_batch(room_ids, since, &timeline_starts)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to get room state events", &e))?;
 
-        let newly_visible_rooms: Vec<String> = delta_state_by_room
+        // A join in the gap or in the timeline makes the room new to the
+        // client, which then needs the full state rather than a delta.
+        let newly_visible_rooms: Vec<String> = room_ids
             .iter()
-            .filter_map(|(room_id, events)| {
-                let user_just_joined = events.iter().any(|e| {
-                    e.event_type.as_deref() == Some("m.room.member")
-                        && e.state_key.as_deref() == Some(params.user_id)
-                        && matches!(e.content.get("membership").and_then(|v| v.as_str()), Some("join") | Some("invite"))
-                        && e.stream_ordering.is_some()
+            .filter(|room_id| {
+                let joined_in_gap = delta_state_by_room.get(*room_id).is_some_and(|events| {
+                    events.iter().any(|e| {
+                        e.stream_ordering.is_some()
+                            && Self::is_visibility_membership(
+                                e.event_type.as_deref(),
+                                e.state_key.as_deref(),
+                                &e.content,
+                                params.user_id,
+                            )
+                    })
                 });
-                if user_just_joined {
-                    Some(room_id.clone())
-                } else {
-                    None
-                }
+                let joined_in_timeline = params.timelines.get(*room_id).is_some_and(|timeline| {
+                    timeline.iter().any(|e| {
+                        Self::is_visibility_membership(
+                            Some(&e.event_type),
+                            e.state_key.as_deref(),
+                            &e.content,
+                            params.user_id,
+                        )
+                    })
+                });
+                joined_in_gap || joined_in_timeline
             })
+            .cloned()
             .collect();
 
         let full_state_for_newly_visible = if newly_visible_rooms.is_empty() {
@@ -182,6 +243,18 @@ impl SyncService {
         Ok(result)
     }
 
+    /// Whether a membership event joins or invites `user_id`.
+    pub(crate) fn is_visibility_membership(
+        event_type: Option<&str>,
+        state_key: Option<&str>,
+        content: &Value,
+        user_id: &str,
+    ) -> bool {
+        event_type == Some("m.room.member")
+            && state_key == Some(user_id)
+            && matches!(content.get("membership").and_then(|v| v.as_str()), Some("join") | Some("invite"))
+    }
+
     pub(crate) async fn get_presence_events(
         &self,
         user_id: &str,
@@ -361,7 +434,11 @@ impl SyncService {
             .get_sync_rooms(user_id, true)
             .await
             .map_err(map_internal!("Failed to get sync rooms for device list left users"))?;
-        let room_ids: Vec<String> = room_memberships.into_iter().map(|membership| membership.room_id).collect();
+        let room_ids: Vec<String> = room_memberships
+            .into_iter()
+            .filter(|membership| !matches!(membership.membership.as_str(), "invite" | "knock"))
+            .map(|membership| membership.room_id)
+            .collect();
         if room_ids.is_empty() {
             return Ok(Vec::new());
         }
@@ -485,10 +562,22 @@ impl SyncService {
         since.as_ref().and_then(|token| token.device_list_stream_id).unwrap_or(0)
     }
 
+    /// `row` as `user_id` sees it in sync; private receipts are only
+    /// visible to their owner.
+    pub(crate) fn visible_ephemeral_event(row: &RoomEphemeralEvent, user_id: &str) -> Option<serde_json::Value> {
+        if row.event_type == PRIVATE_RECEIPT_EPHEMERAL_TYPE {
+            return (row.user_id == user_id).then(|| json!({ "type": "m.receipt", "content": row.content }));
+        }
+        Some(json!({
+            "type": row.event_type,
+            "content": row.content
+        }))
+    }
+
     pub(crate) async fn get_room_ephemeral_events(
         &self,
         room_id: &str,
-        _user_id: &str,
+        user_id: &str,
     ) -> ApiResult<Vec<serde_json::Value>> {
         let now = current_timestamp_millis();
         let limit = self.sync_ephemeral_limit();
@@ -498,15 +587,8 @@ impl SyncService {
             .await
             .map_err(map_internal!("Failed to get ephemeral events"))?;
 
-        let events: Vec<serde_json::Value> = rows
-            .iter()
-            .map(|row| {
-                json!({
-                    "type": row.event_type,
-                    "content": row.content
-                })
-            })
-            .collect();
+        let events: Vec<serde_json::Value> =
+            rows.iter().filter_map(|row| Self::visible_ephemeral_event(row, user_id)).collect();
 
         let events = Self::aggregate_ephemeral_events(events);
 
@@ -516,6 +598,7 @@ impl SyncService {
     pub(crate) async fn get_room_ephemeral_events_batch(
         &self,
         room_ids: &[String],
+        user_id: &str,
     ) -> ApiResult<HashMap<String, Vec<serde_json::Value>>> {
         let limit = self.sync_ephemeral_limit();
         let mut result: HashMap<String, Vec<serde_json::Value>> =
@@ -533,12 +616,7 @@ impl SyncService {
 
         for (room_id, room_events) in rows {
             if let Some(events) = result.get_mut(&room_id) {
-                for row in room_events {
-                    events.push(json!({
-                        "type": row.event_type,
-                        "content": row.content
-                    }));
-                }
+                events.extend(room_events.iter().filter_map(|row| Self::visible_ephemeral_event(row, user_id)));
             }
         }
 
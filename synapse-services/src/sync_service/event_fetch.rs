@@ -50,8 +50,16 @@ impl SyncService {
                 };
 
                 if events.values().all(|v| v.is_empty()) && timeout > 0 {
-                    let update =
-                        self.wait_for_incremental_update(user_id, device_id, room_ids, 0, since_token, timeout).await?;
+                    let update = self
+                        .wait_for_incremental_update(
+                            user_id,
+                            device_id,
+                            room_ids,
+                            SinceFilter::StreamOrdering(stream_ord),
+                            since_token,
+                            timeout,
+                        )
+                        .await?;
 
                     match update {
                         IncrementalUpdate::Events => match event_filter.as_ref() {
@@ -75,9 +83,10 @@ impl SyncService {
                                 .await
                                 .map_err(Into::into),
                         },
-                        IncrementalUpdate::Timeout | IncrementalUpdate::ToDevice | IncrementalUpdate::DeviceLists => {
-                            Ok(events)
-                        }
+                        IncrementalUpdate::Timeout
+                        | IncrementalUpdate::ToDevice
+                        | IncrementalUpdate::DeviceLists
+                        | IncrementalUpdate::Ephemeral => Ok(events),
                     }
                 } else {
                     Ok(events)
@@ -104,7 +113,14 @@ impl SyncService {
 
                 if events.values().all(|v| v.is_empty()) && timeout > 0 {
                     let update = self
-                        .wait_for_incremental_update(user_id, device_id, room_ids, since_ts, since_token, timeout)
+                        .wait_for_incremental_update(
+                            user_id,
+                            device_id,
+                            room_ids,
+                            SinceFilter::OriginServerTs(since_ts),
+                            since_token,
+                            timeout,
+                        )
                         .await?;
 
                     match update {
@@ -129,9 +145,10 @@ impl SyncService {
                                 .await
                                 .map_err(Into::into),
                         },
-                        IncrementalUpdate::Timeout | IncrementalUpdate::ToDevice | IncrementalUpdate::DeviceLists => {
-                            Ok(events)
-                        }
+                        IncrementalUpdate::Timeout
+                        | IncrementalUpdate::ToDevice
+                        | IncrementalUpdate::DeviceLists
+                        | IncrementalUpdate::Ephemeral => Ok(events),
                     }
                 } else {
                     Ok(events)
@@ -149,31 +166,78 @@ impl SyncService {
         }
     }
 
+    /// Timelines of rooms the user has left, ending at their leave event.
+    /// Events after the leave are not visible to the user and are never
+    /// fetched; on an incremental sync the timeline starts after `since_token`.
+    pub(crate) async fn fetch_left_room_events(
+        &self,
+        room_ids: &[String],
+        membership_events: &HashMap<String, StateEvent>,
+        since_token: Option<&SyncToken>,
+        limit: i64,
+        timeline_filter: Option<&SyncFilter>,
+    ) -> ApiResult<HashMap<String, Vec<RoomEvent>>> {
+        let event_filter = Self::event_query_filter_from_sync_filter(timeline_filter);
+        let fetch_limit = if limit <= 0 { 1 } else { limit.saturating_add(1) };
+        let since_stream_ordering =
+            since_token.filter(|t| t.stream_id < Self::TIMESTAMP_TOKEN_MIN && t.stream_id > 0).map(|t| t.stream_id);
+        let since_ts = Self::event_since_ts(&since_token.cloned());
+
+        let mut room_events = HashMap::new();
+        for room_id in room_ids {
+            let Some(left_at) = membership_events.get(room_id).and_then(|event| event.stream_ordering) else {
+                continue;
+            };
+            let mut events = self
+                .event_reader
+                .get_room_events_page(room_id, left_at, since_stream_ordering, fetch_limit, "b", event_filter.as_ref())
+                .await
+                .map_err(map_internal!("Failed to get left room events"))?;
+            if since_stream_ordering.is_none() && since_ts > 0 {
+                events.retain(|event| event.origin_server_ts > since_ts);
+            }
+            room_events.insert(room_id.clone(), events);
+        }
+        Ok(room_events)
+    }
+
+    /// Hold an incremental sync open until something new arrives for the
+    /// user or `timeout` elapses. The notifier listener is armed before each
+    /// storage check, so a write landing between the check and the wait ends
+    /// the wait at once; the poll interval only bounds how long a wake-up
+    /// that never arrives (e.g. from another instance without Redis) can be
+    /// missed.
     pub(crate) async fn wait_for_incremental_update(
         &self,
         user_id: &str,
         device_id: Option<&str>,
         room_ids: &[String],
-        since_ts: i64,
+        since: SinceFilter,
         since_token: Option<&SyncToken>,
         timeout: u64,
     ) -> ApiResult<IncrementalUpdate> {
         let timeout_duration = std::time::Duration::from_millis(timeout);
         let start = std::time::Instant::now();
         let poll_interval = self.sync_poll_interval();
+        // Receipts and typing carry no stream position in the token; only
+        // those written while this request waits count as new.
+        let waiting_since_ts = current_timestamp_millis();
 
         let since_to_device = since_token.and_then(|t| t.to_device_stream_id).unwrap_or(0);
         let since_device_lists = since_token.and_then(|t| t.device_list_stream_id).unwrap_or(0);
+        let listener = self.event_notifier.listener(room_ids, user_id);
 
         loop {
             if start.elapsed() >= timeout_duration {
                 return Ok(IncrementalUpdate::Timeout);
             }
 
-            let (has_events, has_to_device, has_device_lists) = tokio::try_join!(
-                self.has_incremental_room_updates(room_ids, since_ts),
+            let armed = listener.arm();
+            let (has_events, has_to_device, has_device_lists, has_ephemeral) = tokio::try_join!(
+                self.has_incremental_room_updates(room_ids, since),
                 self.has_incremental_to_device_updates(user_id, device_id, since_to_device),
                 self.has_incremental_device_list_updates(since_device_lists),
+                self.has_incremental_ephemeral_updates(room_ids, waiting_since_ts),
             )?;
 
             if has_events {
@@ -188,15 +252,32 @@ impl SyncService {
                 return Ok(IncrementalUpdate::DeviceLists);
             }
 
-            tokio::time::sleep(poll_interval).await;
+            if has_ephemeral {
+                return Ok(IncrementalUpdate::Ephemeral);
+            }
+
+            let remaining = timeout_duration.saturating_sub(start.elapsed());
+            armed.wait(poll_interval.min(remaining)).await;
+        }
+    }
+
+    async fn has_incremental_room_updates(&self, room_ids: &[String], since: SinceFilter) -> ApiResult<bool> {
+        match since {
+            SinceFilter::StreamOrdering(stream_ordering) => self
+                .event_reader
+                .get_room_events_after_stream(room_ids, stream_ordering, 1)
+                .await
+                .map(|events| !events.is_empty()),
+            SinceFilter::OriginServerTs(since_ts) => self.event_reader.has_room_events_since(room_ids, since_ts).await,
         }
+        .map_err(map_internal!("Failed to poll for events"))
     }
 
-    async fn has_incremental_room_updates(&self, room_ids: &[String], since_ts: i64) -> ApiResult<bool> {
+    async fn has_incremental_ephemeral_updates(&self, room_ids: &[String], since_ts: i64) -> ApiResult<bool> {
         self.event_reader
-            .has_room_events_since(room_ids, since_ts)
+            .has_ephemeral_events_since(room_ids, since_ts, current_timestamp_millis())
             .await
-            .map_err(map_internal!("Failed to poll for events"))
+            .map_err(map_internal!("Failed to poll for ephemeral events"))
     }
 
     async fn has_incremental_to_device_updates(
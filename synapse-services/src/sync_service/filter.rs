@@ -64,12 +64,14 @@ impl SyncService {
             not_types: filter.not_types.clone(),
             senders: filter.senders.clone(),
             not_senders: filter.not_senders.clone(),
+            contains_url: filter.contains_url,
         };
 
         if query_filter.types.as_ref().is_some_and(|values| !values.is_empty())
             || query_filter.not_types.as_ref().is_some_and(|values| !values.is_empty())
             || query_filter.senders.as_ref().is_some_and(|values| !values.is_empty())
             || query_filter.not_senders.as_ref().is_some_and(|values| !values.is_empty())
+            || query_filter.contains_url.is_some()
         {
             Some(query_filter)
         } else {
@@ -302,15 +304,17 @@ impl SyncService {
     }
 
     pub(crate) fn apply_timeline_limit(events: &[RoomEvent], timeline_limit: i64) -> (Vec<RoomEvent>, bool) {
-        if timeline_limit <= 0 {
-            return (Vec::new(), !events.is_empty());
-        }
-
-        let limited = events.len() as i64 > timeline_limit;
-        let mut events: Vec<RoomEvent> = events.iter().take(timeline_limit as usize).cloned().collect();
+        let window = Self::timeline_window(events, timeline_limit);
+        let limited = window.len() < events.len();
+        let mut events = window.to_vec();
         events.reverse();
         (events, limited)
     }
+
+    /// The newest-first events that `apply_timeline_limit` keeps.
+    pub(crate) fn timeline_window(events: &[RoomEvent], timeline_limit: i64) -> &[RoomEvent] {
+        &events[..events.len().min(timeline_limit.max(0) as usize)]
+    }
 }
 
 #[cfg(test)]
@@ -1115,6 +1119,22 @@ mod tests {
         assert_eq!(result[1]["type"], "m.typing");
     }
 
+    #[test]
+    fn private_receipts_are_only_visible_to_their_owner() {
+        let row = synapse_storage::event::RoomEphemeralEvent {
+            event_type: crate::room::messaging::receipts::PRIVATE_RECEIPT_EPHEMERAL_TYPE.to_string(),
+            user_id: "@owner:b".to_string(),
+            content: json!({"$e": {"m.read.private": {"@owner:b": {"ts": 1}}}}),
+            stream_id: 1,
+            created_ts: 1,
+        };
+
+        let own = SyncService::visible_ephemeral_event(&row, "@owner:b").expect("owner sees own receipt");
+        assert_eq!(own["type"], "m.receipt");
+        assert_eq!(own["content"], row.content);
+        assert!(SyncService::visible_ephemeral_event(&row, "@other:b").is_none());
+    }
+
     #[test]
     fn aggregate_ephemeral_empty_returns_empty() {
         let result = SyncService::aggregate_ephemeral_events(vec![]);
@@ -83,19 +83,64 @@ impl SyncService {
             timeline_limited,
         );
 
-        if !known_now.is_empty() {
-            let mut cache = self.lazy_loaded_members_cache.write().await;
-            // Enforce the same capacity limit as the insert path.
-            if cache.len() >= LAZY_LOADED_MEMBERS_CACHE_MAX_ENTRIES {
-                cache.clear();
-            }
-            cache.entry(cache_key).or_default().extend(known_now.iter().cloned());
-        }
+        self.remember_lazy_loaded_members(cache_key, &known_now).await;
         self.persist_lazy_loaded_members(user_id, device_id, room_id, &known_now).await;
 
         filtered_events
     }
 
+    /// Lazy-loaded `state` of a `/messages` page: member events this device
+    /// was already sent are dropped unless `include_redundant_members`, and
+    /// the rest are recorded so later syncs skip them as well.
+    pub async fn filter_lazy_loaded_member_state(
+        &self,
+        user_id: &str,
+        device_id: Option<&str>,
+        room_id: &str,
+        member_events: Vec<Value>,
+        include_redundant_members: bool,
+    ) -> Vec<Value> {
+        let known_members = self.get_known_lazy_loaded_members(user_id, device_id, room_id).await;
+        let (filtered_events, known_now) =
+            Self::drop_known_member_events(member_events, &known_members, include_redundant_members);
+
+        let cache_key = LazyLoadedMembersCacheKey::new(user_id, device_id, room_id);
+        self.remember_lazy_loaded_members(cache_key, &known_now).await;
+        self.persist_lazy_loaded_members(user_id, device_id, room_id, &known_now).await;
+        filtered_events
+    }
+
+    pub(crate) fn drop_known_member_events(
+        member_events: Vec<Value>,
+        known_members: &HashSet<String>,
+        include_redundant_members: bool,
+    ) -> (Vec<Value>, HashSet<String>) {
+        let mut known_now = HashSet::new();
+        let filtered_events = member_events
+            .into_iter()
+            .filter(|event| {
+                let Some(state_key) = event.get("state_key").and_then(|value| value.as_str()) else {
+                    return false;
+                };
+                known_now.insert(state_key.to_string());
+                include_redundant_members || !known_members.contains(state_key)
+            })
+            .collect();
+        (filtered_events, known_now)
+    }
+
+    async fn remember_lazy_loaded_members(&self, cache_key: LazyLoadedMembersCacheKey, known_now: &HashSet<String>) {
+        if known_now.is_empty() {
+            return;
+        }
+        let mut cache = self.lazy_loaded_members_cache.write().await;
+        // Enforce the same capacity limit as the insert path.
+        if cache.len() >= LAZY_LOADED_MEMBERS_CACHE_MAX_ENTRIES {
+            cache.clear();
+        }
+        cache.entry(cache_key).or_default().extend(known_now.iter().cloned());
+    }
+
     pub(crate) fn apply_lazy_load_members_with_cache(
         state_events: Vec<Value>,
         timeline_events: &[RoomEvent],
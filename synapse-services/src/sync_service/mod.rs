@@ -18,6 +18,7 @@ pub use types::{
     SyncRoomSection, SyncServiceDeps, SyncServiceRequest, SyncState, SyncToken,
 };
 
+use crate::map_internal;
 use crate::*;
 use serde_json::json;
 use std::collections::{HashMap, HashSet};
@@ -46,6 +47,8 @@ pub struct SyncService {
     pub(crate) metrics: Arc<MetricsCollector>,
     pub(crate) performance: synapse_common::config::PerformanceConfig,
     pub(crate) cache: Arc<synapse_cache::CacheManager>,
+    pub(crate) event_notifier: crate::event_notifier::EventNotifier,
+    pub(crate) event_visibility: Option<Arc<crate::event_visibility_service::EventVisibilityService>>,
 }
 
 /// Maximum number of (user, device, room) entries kept in the in-memory
@@ -55,6 +58,9 @@ pub struct SyncService {
 /// only causes a temporary increase in database queries.
 const LAZY_LOADED_MEMBERS_CACHE_MAX_ENTRIES: usize = 50_000;
 
+/// Rooms of one sync response that are built at the same time.
+const SYNC_ROOM_CONCURRENCY: usize = 16;
+
 impl SyncService {
     const TIMESTAMP_TOKEN_MIN: i64 = 1_000_000_000_000;
 
@@ -75,6 +81,38 @@ impl SyncService {
             metrics: deps.metrics,
             performance: deps.performance,
             cache: deps.cache,
+            event_notifier: crate::event_notifier::EventNotifier::new(),
+            event_visibility: None,
+        }
+    }
+
+    /// Wake long-polling incremental syncs through `notifier` rather than
+    /// relying only on the `sync_poll_interval_ms` database poll.
+    pub fn with_event_notifier(mut self, notifier: crate::event_notifier::EventNotifier) -> Self {
+        self.event_notifier = notifier;
+        self
+    }
+
+    /// Filter timelines through `visibility`. Without it every fetched event
+    /// is returned.
+    pub fn with_event_visibility(
+        mut self,
+        visibility: Arc<crate::event_visibility_service::EventVisibilityService>,
+    ) -> Self {
+        self.event_visibility = Some(visibility);
+        self
+    }
+
+    /// The timeline events `user_id` may see. Stream positions are taken
+    /// from the unfiltered events so hidden events are not fetched again.
+    pub(crate) async fn visible_room_events(
+        &self,
+        user_id: &str,
+        room_events: &HashMap<String, Vec<RoomEvent>>,
+    ) -> ApiResult<HashMap<String, Vec<RoomEvent>>> {
+        match &self.event_visibility {
+            Some(visibility) => visibility.filter_room_events_for_client(user_id, room_events).await,
+            None => Ok(room_events.clone()),
         }
     }
 
@@ -158,26 +196,63 @@ impl SyncService {
 
         let rooms_started = Instant::now();
         let include_leave = room_filter.and_then(|filter| filter.include_leave).unwrap_or(false);
-        let room_memberships = self.member_storage.get_sync_rooms(user_id, include_leave).await?;
-        let room_sections =
+        // Rooms left since the token are reported whatever `include_leave`
+        // says; the filter only asks for archived rooms on initial syncs.
+        let room_memberships =
+            self.member_storage.get_sync_rooms(user_id, include_leave || since_token.is_some()).await?;
+        let mut room_sections =
             Self::room_sections_from_memberships(&Self::filter_sync_rooms(room_memberships, room_filter));
-        let room_ids: Vec<String> = room_sections.keys().cloned().collect();
+        let membership_rooms: Vec<String> = room_sections
+            .iter()
+            .filter(|(_, section)| **section != SyncRoomSection::Join)
+            .map(|(room_id, _)| room_id.clone())
+            .collect();
+        let membership_events = self
+            .event_reader
+            .get_member_events_batch(&membership_rooms, user_id)
+            .await
+            .map_err(map_internal!("Failed to get sync membership events"))?;
+        Self::retain_changed_room_sections(&mut room_sections, &membership_events, since_token.as_ref(), include_leave);
+        let sections_rooms = |wanted: SyncRoomSection| -> Vec<String> {
+            room_sections
+                .iter()
+                .filter(|(_, section)| **section == wanted)
+                .map(|(room_id, _)| room_id.clone())
+                .collect()
+        };
+        let joined_room_ids = sections_rooms(SyncRoomSection::Join);
+        let left_room_ids = sections_rooms(SyncRoomSection::Leave);
+        let room_ids: Vec<String> = joined_room_ids.iter().chain(&left_room_ids).cloned().collect();
         let rooms_lookup_ms = rooms_started.elapsed().as_secs_f64() * 1000.0;
         self.observe_histogram("sync_rooms_lookup_duration_ms", rooms_lookup_ms);
 
         let event_fetch_started = Instant::now();
-        let room_events = self
+        let timeline_filter = room_filter.and_then(|filter| filter.timeline.as_ref());
+        // A membership change is news in itself, so don't hold the request
+        // open waiting for timeline events.
+        let has_membership_changes = room_sections.values().any(|section| *section != SyncRoomSection::Join);
+        let mut room_events = self
             .fetch_events(FetchEventsRequest {
                 user_id,
                 device_id,
-                room_ids: &room_ids,
+                room_ids: &joined_room_ids,
                 since_token: since_token.as_ref(),
-                timeout,
+                timeout: if has_membership_changes { 0 } else { timeout },
                 limit: timeline_limit,
-                timeline_filter: room_filter.and_then(|filter| filter.timeline.as_ref()),
+                timeline_filter,
                 is_incremental,
             })
             .await?;
+        room_events.extend(
+            self.fetch_left_room_events(
+                &left_room_ids,
+                &membership_events,
+                since_token.as_ref().filter(|_| is_incremental),
+                timeline_limit,
+                timeline_filter,
+            )
+            .await?,
+        );
         let event_fetch_ms = event_fetch_started.elapsed().as_secs_f64() * 1000.0;
         self.observe_histogram("sync_event_fetch_duration_ms", event_fetch_ms);
 
@@ -191,6 +266,7 @@ impl SyncService {
                 device_id,
                 room_ids: &room_ids,
                 room_sections: &room_sections,
+                membership_events: &membership_events,
                 room_events,
                 response_filter: response_filter.as_ref(),
                 timeline_limit,
@@ -374,42 +450,81 @@ impl SyncService {
         memberships: Vec<UserRoomMembership>,
         room_filter: Option<&RoomFilter>,
     ) -> Vec<UserRoomMembership> {
-        let allowed_rooms = room_filter.and_then(|filter| filter.rooms.as_ref());
-        let disallowed_rooms = room_filter.and_then(|filter| filter.not_rooms.as_ref());
-
         memberships
             .into_iter()
-            .filter(|membership| {
-                if let Some(rooms) = allowed_rooms {
-                    if !rooms.is_empty() && !rooms.iter().any(|room| room == &membership.room_id) {
-                        return false;
-                    }
-                }
-
-                if let Some(not_rooms) = disallowed_rooms {
-                    if not_rooms.iter().any(|room| room == &membership.room_id) {
-                        return false;
-                    }
-                }
-
-                true
-            })
+            .filter(|membership| Self::room_filter_allows(room_filter, &membership.room_id))
             .collect()
     }
 
+    pub(crate) fn room_filter_allows(room_filter: Option<&RoomFilter>, room_id: &str) -> bool {
+        let Some(room_filter) = room_filter else {
+            return true;
+        };
+
+        if let Some(rooms) = &room_filter.rooms {
+            if !rooms.is_empty() && !rooms.iter().any(|room| room == room_id) {
+                return false;
+            }
+        }
+
+        if let Some(not_rooms) = &room_filter.not_rooms {
+            if not_rooms.iter().any(|room| room == room_id) {
+                return false;
+            }
+        }
+
+        true
+    }
+
     pub(crate) fn room_sections_from_memberships(
         memberships: &[UserRoomMembership],
     ) -> HashMap<String, SyncRoomSection> {
         memberships
             .iter()
             .map(|membership| {
-                let section =
-                    if membership.membership == "leave" { SyncRoomSection::Leave } else { SyncRoomSection::Join };
+                let section = match membership.membership.as_str() {
+                    "invite" => SyncRoomSection::Invite,
+                    "knock" => SyncRoomSection::Knock,
+                    "leave" | "ban" => SyncRoomSection::Leave,
+                    _ => SyncRoomSection::Join,
+                };
                 (membership.room_id.clone(), section)
             })
             .collect()
     }
 
+    /// Drops the invited, knocked and left rooms a sync has nothing to say
+    /// about. An incremental sync keeps those whose membership changed after
+    /// the since token; an initial sync keeps every invite and knock, and
+    /// left rooms only when the filter sets `include_leave`.
+    pub(crate) fn retain_changed_room_sections(
+        room_sections: &mut HashMap<String, SyncRoomSection>,
+        membership_events: &HashMap<String, StateEvent>,
+        since_token: Option<&SyncToken>,
+        include_leave: bool,
+    ) {
+        room_sections.retain(|room_id, section| {
+            if *section == SyncRoomSection::Join {
+                return true;
+            }
+            let event = membership_events.get(room_id);
+            match since_token {
+                Some(token) => event.is_some_and(|event| Self::membership_changed_since(event, token)),
+                None if *section == SyncRoomSection::Leave => include_leave && event.is_some(),
+                None => true,
+            }
+        });
+    }
+
+    fn membership_changed_since(event: &StateEvent, since_token: &SyncToken) -> bool {
+        if since_token.stream_id > 0 && since_token.stream_id < Self::TIMESTAMP_TOKEN_MIN {
+            if let Some(stream_ordering) = event.stream_ordering {
+                return stream_ordering > since_token.stream_id;
+            }
+        }
+        event.origin_server_ts > Self::event_since_ts(&Some(since_token.clone()))
+    }
+
     pub(crate) fn event_since_ts(since_token: &Option<SyncToken>) -> i64 {
         match since_token {
             Some(token) if token.stream_id >= Self::TIMESTAMP_TOKEN_MIN => token.stream_id,
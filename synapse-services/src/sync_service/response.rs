@@ -1,13 +1,25 @@
 use super::types::*;
-use super::SyncService;
+use super::{SyncService, SYNC_ROOM_CONCURRENCY};
 use crate::map_internal;
 use crate::*;
+use futures::stream::{self, StreamExt};
 use serde_json::{json, Map, Value};
 use std::collections::{HashMap, HashSet};
 use synapse_common::current_timestamp_millis;
 use synapse_common::*;
 use synapse_storage::event::SinceFilter;
 
+/// State events copied into the stripped state of invites and knocks.
+const STRIPPED_STATE_TYPES: &[&str] = &[
+    "m.room.create",
+    "m.room.join_rules",
+    "m.room.name",
+    "m.room.avatar",
+    "m.room.canonical_alias",
+    "m.room.encryption",
+    "m.room.topic",
+];
+
 impl SyncService {
     pub(crate) async fn build_sync_response(
         &self,
@@ -18,6 +30,7 @@ impl SyncService {
             device_id,
             room_ids,
             room_sections,
+            membership_events,
             room_events,
             response_filter,
             timeline_limit,
@@ -87,8 +100,28 @@ impl SyncService {
         } else {
             HashMap::new()
         };
+        let visible_events = self.visible_room_events(user_id, &room_events).await?;
+        let timelines: HashMap<String, &[RoomEvent]> = rooms_to_include
+            .iter()
+            .filter_map(|room_id| {
+                visible_events
+                    .get(room_id)
+                    .map(|events| (room_id.clone(), Self::timeline_window(events, timeline_limit)))
+            })
+            .collect();
+        let (left_rooms_to_include, joined_rooms_to_include): (Vec<String>, Vec<String>) = rooms_to_include
+            .iter()
+            .cloned()
+            .partition(|room_id| room_sections.get(room_id) == Some(&SyncRoomSection::Leave));
+        let left_since = match (is_incremental, since_stream_ordering) {
+            (true, Some(stream_ord)) => SinceFilter::StreamOrdering(stream_ord),
+            (true, None) => SinceFilter::OriginServerTs(since_ts),
+            (false, _) => SinceFilter::StreamOrdering(0),
+        };
         let (
-            state_by_room,
+            mut state_by_room,
+            left_state_by_room,
+            (invite_rooms, knock_rooms),
             ephemeral_by_room,
             room_account_data_by_room,
             unread_counts_by_room,
@@ -98,18 +131,34 @@ impl SyncService {
             (device_lists, device_list_stream_id),
         ) = tokio::try_join!(
             self.get_state_events_for_sync_batch(
-                &rooms_to_include,
+                &joined_rooms_to_include,
                 event_format,
-                StateEventsBatchParams { since_ts, since_stream_ordering, is_incremental, lazy_load_members, user_id },
+                StateEventsBatchParams {
+                    since_ts,
+                    since_stream_ordering,
+                    is_incremental,
+                    lazy_load_members,
+                    user_id,
+                    timelines: &timelines,
+                },
             ),
-            self.get_room_ephemeral_events_batch(&rooms_to_include),
+            self.get_left_room_state_batch(
+                &left_rooms_to_include,
+                event_format,
+                left_since,
+                &timelines,
+                membership_events,
+            ),
+            self.build_membership_room_sections(room_sections, membership_events),
+            self.get_room_ephemeral_events_batch(&joined_rooms_to_include, user_id),
             self.get_room_account_data_events_batch(user_id, &rooms_to_include),
-            self.get_unread_counts_batch(&rooms_to_include, user_id),
+            self.get_unread_counts_batch(&joined_rooms_to_include, user_id),
             self.get_presence_events(user_id, since_token),
             self.get_account_data_events(user_id),
             self.get_to_device_events(user_id, device_id, since_token),
             self.get_device_lists(user_id, since_token),
         )?;
+        state_by_room.extend(left_state_by_room);
         let presence_events = Self::apply_sync_filter_to_values(
             presence_events,
             response_filter.and_then(|filter| filter.presence.as_ref()),
@@ -118,58 +167,83 @@ impl SyncService {
         let account_data_events = Self::apply_event_fields_to_values(account_data_events, event_fields);
         let to_device_events = Self::apply_event_fields_to_values(to_device_events, event_fields);
 
+        // Each room only reads the batched maps above plus its own lazy-load
+        // cache entry, so rooms are built concurrently. The bound keeps a user
+        // in hundreds of rooms from flooding the device store with lazy-load
+        // lookups at once.
+        let visible_events = &visible_events;
+        let state_by_room = &state_by_room;
+        let ephemeral_by_room = &ephemeral_by_room;
+        let room_account_data_by_room = &room_account_data_by_room;
+        let unread_counts_by_room = &unread_counts_by_room;
+        let changed_members_by_room = &changed_members_by_room;
+        let room_futures: Vec<_> = rooms_to_include
+            .iter()
+            .enumerate()
+            .map(|(index, room_id)| async move {
+                let events = visible_events.get(room_id).cloned().unwrap_or_default();
+                let (timeline_events, timeline_limited) = Self::apply_timeline_limit(&events, timeline_limit);
+                let state_events = Self::apply_sync_filter_to_values(
+                    state_by_room.get(room_id).cloned().unwrap_or_default(),
+                    room_filter.and_then(|filter| filter.state.as_ref()),
+                );
+                let state_events = self
+                    .apply_lazy_load_members(LazyLoadMembersRequest {
+                        state_events,
+                        timeline_events: &timeline_events,
+                        user_id,
+                        device_id,
+                        room_id,
+                        room_filter,
+                        changed_member_ids: changed_members_by_room.get(room_id),
+                        timeline_limited,
+                        enabled: lazy_load_members,
+                    })
+                    .await;
+                let state_events = Self::apply_event_fields_to_values(state_events, event_fields);
+                let ephemeral_events = Self::apply_sync_filter_to_values(
+                    ephemeral_by_room.get(room_id).cloned().unwrap_or_default(),
+                    room_filter.and_then(|filter| filter.ephemeral.as_ref()),
+                );
+                let ephemeral_events = Self::apply_event_fields_to_values(ephemeral_events, event_fields);
+                let account_data_events = Self::apply_sync_filter_to_values(
+                    room_account_data_by_room.get(room_id).cloned().unwrap_or_default(),
+                    room_filter.and_then(|filter| filter.account_data.as_ref()),
+                );
+                let account_data_events = Self::apply_event_fields_to_values(account_data_events, event_fields);
+                let (highlight_count, notification_count) =
+                    unread_counts_by_room.get(room_id).copied().unwrap_or((0, 0));
+                let bundled_relations = self.timeline_bundled_relations(user_id, &timeline_events).await;
+                let room_sync = Self::build_room_sync_value(BuildRoomSyncValueRequest {
+                    events,
+                    state_list: state_events,
+                    ephemeral_events,
+                    account_data_events,
+                    timeline_limit,
+                    counts: RoomSyncCounts { highlight_count, notification_count },
+                    event_fields,
+                    event_format,
+                    bundled_relations,
+                });
+                (index, room_sync)
+            })
+            .collect();
+        let mut room_syncs: Vec<(usize, Value)> =
+            stream::iter(room_futures).buffer_unordered(SYNC_ROOM_CONCURRENCY).collect().await;
+        // Rooms finish in any order; keep the response independent of that.
+        room_syncs.sort_unstable_by_key(|(index, _)| *index);
+
         let mut joined_rooms = Map::new();
         let mut left_rooms = Map::new();
-        for room_id in &rooms_to_include {
-            let events = room_events.get(room_id).cloned().unwrap_or_default();
-            let (timeline_events, timeline_limited) = Self::apply_timeline_limit(&events, timeline_limit);
-            let state_events = Self::apply_sync_filter_to_values(
-                state_by_room.get(room_id).cloned().unwrap_or_default(),
-                room_filter.and_then(|filter| filter.state.as_ref()),
-            );
-            let state_events = self
-                .apply_lazy_load_members(LazyLoadMembersRequest {
-                    state_events,
-                    timeline_events: &timeline_events,
-                    user_id,
-                    device_id,
-                    room_id,
-                    room_filter,
-                    changed_member_ids: changed_members_by_room.get(room_id),
-                    timeline_limited,
-                    enabled: lazy_load_members,
-                })
-                .await;
-            let state_events = Self::apply_event_fields_to_values(state_events, event_fields);
-            let ephemeral_events = Self::apply_sync_filter_to_values(
-                ephemeral_by_room.get(room_id).cloned().unwrap_or_default(),
-                room_filter.and_then(|filter| filter.ephemeral.as_ref()),
-            );
-            let ephemeral_events = Self::apply_event_fields_to_values(ephemeral_events, event_fields);
-            let account_data_events = Self::apply_sync_filter_to_values(
-                room_account_data_by_room.get(room_id).cloned().unwrap_or_default(),
-                room_filter.and_then(|filter| filter.account_data.as_ref()),
-            );
-            let account_data_events = Self::apply_event_fields_to_values(account_data_events, event_fields);
-            let (highlight_count, notification_count) = unread_counts_by_room.get(room_id).copied().unwrap_or((0, 0));
-            let room_sync = Self::build_room_sync_value(BuildRoomSyncValueRequest {
-                events,
-                state_list: state_events,
-                ephemeral_events,
-                account_data_events,
-                timeline_limit,
-                counts: RoomSyncCounts { highlight_count, notification_count },
-                event_fields,
-                event_format,
-            });
-
+        for (index, room_sync) in room_syncs {
+            let room_id = &rooms_to_include[index];
             if room_sync.is_object() && !room_sync.as_object().is_some_and(|o| o.is_empty()) {
                 match room_sections.get(room_id).copied().unwrap_or(SyncRoomSection::Join) {
-                    SyncRoomSection::Join => {
-                        joined_rooms.insert(room_id.clone(), room_sync);
-                    }
                     SyncRoomSection::Leave => {
-                        left_rooms.insert(room_id.clone(), room_sync);
+                        left_rooms.insert(room_id.clone(), Self::left_room_sync_value(room_sync));
+                    }
+                    SyncRoomSection::Join | SyncRoomSection::Invite | SyncRoomSection::Knock => {
+                        joined_rooms.insert(room_id.clone(), room_sync);
                     }
                 }
             }
@@ -192,8 +266,9 @@ impl SyncService {
             }.encode(),
             "rooms": {
                 "join": joined_rooms,
-                "invite": {},
-                "leave": left_rooms
+                "invite": invite_rooms,
+                "leave": left_rooms,
+                "knock": knock_rooms
             },
             "presence": { "events": presence_events },
             "account_data": { "events": account_data_events },
@@ -205,6 +280,94 @@ impl SyncService {
         }))
     }
 
+    /// `rooms.invite` and `rooms.knock`: each room is described only by the
+    /// stripped state the user is allowed to see before joining.
+    async fn build_membership_room_sections(
+        &self,
+        room_sections: &HashMap<String, SyncRoomSection>,
+        membership_events: &HashMap<String, StateEvent>,
+    ) -> ApiResult<(Map<String, Value>, Map<String, Value>)> {
+        let room_ids: Vec<String> = room_sections
+            .iter()
+            .filter(|(_, section)| matches!(section, SyncRoomSection::Invite | SyncRoomSection::Knock))
+            .map(|(room_id, _)| room_id.clone())
+            .collect();
+        if room_ids.is_empty() {
+            return Ok((Map::new(), Map::new()));
+        }
+
+        let state_by_room = self
+            .event_reader
+            .get_state_events_batch(&room_ids)
+            .await
+            .map_err(map_internal!("Failed to get invite room state"))?;
+
+        let mut invite_rooms = Map::new();
+        let mut knock_rooms = Map::new();
+        for room_id in room_ids {
+            let room_state = state_by_room.get(&room_id).map(Vec::as_slice).unwrap_or_default();
+            let membership_event = membership_events.get(&room_id);
+            if room_sections.get(&room_id) == Some(&SyncRoomSection::Knock) {
+                let events = Self::stripped_room_state(membership_event, room_state, "knock_room_state");
+                knock_rooms.insert(room_id, json!({ "knock_state": { "events": events } }));
+            } else {
+                let events = Self::stripped_room_state(membership_event, room_state, "invite_room_state");
+                invite_rooms.insert(room_id, json!({ "invite_state": { "events": events } }));
+            }
+        }
+        Ok((invite_rooms, knock_rooms))
+    }
+
+    /// Stripped state for an invited or knocked room, ending with the user's
+    /// own membership event. Invites received over federation carry the
+    /// inviting server's view in `unsigned.{unsigned_key}`, which is preferred
+    /// over the local copy of the room state.
+    pub(crate) fn stripped_room_state(
+        membership_event: Option<&StateEvent>,
+        room_state: &[StateEvent],
+        unsigned_key: &str,
+    ) -> Vec<Value> {
+        let carried_state = membership_event
+            .and_then(|event| event.unsigned.as_ref())
+            .and_then(|unsigned| unsigned.get(unsigned_key))
+            .and_then(Value::as_array);
+
+        let mut events: Vec<Value> = match carried_state {
+            Some(carried_state) => carried_state.clone(),
+            None => room_state
+                .iter()
+                .filter(|event| {
+                    event.state_key.as_deref() == Some("")
+                        && event
+                            .event_type
+                            .as_deref()
+                            .is_some_and(|event_type| STRIPPED_STATE_TYPES.contains(&event_type))
+                })
+                .map(Self::stripped_state_event)
+                .collect(),
+        };
+        events.extend(membership_event.map(Self::stripped_state_event));
+        events
+    }
+
+    fn stripped_state_event(event: &StateEvent) -> Value {
+        json!({
+            "type": event.event_type.as_deref().unwrap_or_default(),
+            "state_key": event.state_key.as_deref().unwrap_or_default(),
+            "content": event.content,
+            "sender": event.sender,
+        })
+    }
+
+    /// A left room carries no ephemeral events or notification counts.
+    fn left_room_sync_value(mut room_sync: Value) -> Value {
+        if let Some(room) = room_sync.as_object_mut() {
+            room.remove("ephemeral");
+            room.remove("unread_notifications");
+        }
+        room_sync
+    }
+
     async fn build_device_one_time_keys_count(&self, user_id: &str, device_id: Option<&str>) -> ApiResult<Value> {
         let Some(device_id) = device_id else {
             return Ok(json!({}));
@@ -285,7 +448,12 @@ impl SyncService {
     pub(crate) async fn build_room_sync(&self, request: BuildRoomSyncRequest<'_>) -> ApiResult<serde_json::Value> {
         let BuildRoomSyncRequest { room_id, user_id, device_id, events, since_token, is_incremental, room_filter } =
             request;
+        let events = match &self.event_visibility {
+            Some(visibility) => visibility.filter_events_for_client(user_id, events).await?,
+            None => events,
+        };
         let since_ts = Self::event_since_ts(&since_token.cloned());
+        let timelines = HashMap::from([(room_id.to_string(), Self::timeline_window(&events, self.sync_event_limit()))]);
         let (
             changed_member_ids,
             state_list,
@@ -320,6 +488,7 @@ impl SyncService {
                             is_incremental,
                             lazy_load_members,
                             user_id,
+                            timelines: &timelines,
                         },
                     )
                     .await?;
@@ -346,11 +515,11 @@ impl SyncService {
                 enabled: lazy_load_members,
             })
             .await;
-        let state_list = if is_incremental { Vec::new() } else { state_list };
         let ephemeral_events =
             Self::apply_sync_filter_to_values(ephemeral_events, room_filter.and_then(|f| f.ephemeral.as_ref()));
         let account_data_events =
             Self::apply_sync_filter_to_values(account_data_events, room_filter.and_then(|f| f.account_data.as_ref()));
+        let bundled_relations = self.timeline_bundled_relations(user_id, &timeline_events).await;
 
         Ok(Self::build_room_sync_value(BuildRoomSyncValueRequest {
             events,
@@ -361,9 +530,42 @@ impl SyncService {
             counts: RoomSyncCounts { highlight_count, notification_count },
             event_fields: None,
             event_format: SyncEventFormat::Client,
+            bundled_relations,
         }))
     }
 
+    /// Bundled aggregations of a room's timeline. A failed lookup only costs
+    /// the bundles, not the sync.
+    async fn timeline_bundled_relations(&self, user_id: &str, timeline: &[RoomEvent]) -> HashMap<String, Value> {
+        crate::sync_helpers::bundled_relations(
+            self.event_reader.as_ref(),
+            self.event_visibility.as_deref(),
+            user_id,
+            timeline,
+        )
+        .await
+        .unwrap_or_else(|e| {
+            ::tracing::warn!(error = %e, "Failed to bundle timeline relations");
+            HashMap::new()
+        })
+    }
+
+    /// Token just before the oldest timeline event, so that `/messages`
+    /// from it back-paginates over any gap a `limited` timeline skipped. An
+    /// empty timeline (`limit: 0`) points past the newest fetched event.
+    pub(crate) fn timeline_prev_batch(timeline: &[RoomEvent], fetched: &[RoomEvent]) -> String {
+        let position = match (timeline.first(), fetched.first()) {
+            (Some(oldest), _) => oldest.stream_ordering.map(|stream| stream - 1),
+            (None, Some(newest)) => newest.stream_ordering,
+            (None, None) => None,
+        };
+        match (position, timeline.first()) {
+            (Some(stream), _) => RoomPaginationToken::Stream(stream).to_string(),
+            (None, Some(oldest)) => format!("t{}", oldest.origin_server_ts),
+            (None, None) => format!("t{}", current_timestamp_millis()),
+        }
+    }
+
     pub(crate) fn event_to_json(event: &RoomEvent, event_format: SyncEventFormat) -> Value {
         let mut obj = crate::sync_helpers::room_event_to_json(event);
         if event_format == SyncEventFormat::Federation {
@@ -392,15 +594,18 @@ impl SyncService {
             counts,
             event_fields,
             event_format,
+            bundled_relations,
         } = request;
-        let (events, limited) = Self::apply_timeline_limit(&events, timeline_limit);
-        let event_list: Vec<Value> = events
+        let (timeline, limited) = Self::apply_timeline_limit(&events, timeline_limit);
+        let event_list: Vec<Value> = timeline
             .iter()
-            .map(|event| Self::filter_event_fields(Self::event_to_json(event, event_format), event_fields))
+            .map(|event| {
+                let mut event_json = Self::event_to_json(event, event_format);
+                crate::sync_helpers::attach_bundled_relations(&mut event_json, &bundled_relations);
+                Self::filter_event_fields(event_json, event_fields)
+            })
             .collect();
-        let prev_batch = events
-            .first()
-            .map_or_else(|| format!("t{}", current_timestamp_millis()), |event| format!("t{}", event.origin_server_ts));
+        let prev_batch = Self::timeline_prev_batch(&timeline, &events);
 
         json!({
             "state": {
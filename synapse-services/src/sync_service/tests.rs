@@ -465,6 +465,30 @@ fn test_apply_timeline_limit_truncates_events() {
     assert_eq!(events[1].event_id, "$event1");
 }
 
+#[test]
+fn test_timeline_window_is_the_part_apply_timeline_limit_keeps() {
+    let events = [sample_room_event("3"), sample_room_event("2"), sample_room_event("1")];
+
+    let window = SyncService::timeline_window(&events, 2);
+    assert_eq!(window.iter().map(|e| e.event_id.as_str()).collect::<Vec<_>>(), ["$event3", "$event2"]);
+    assert_eq!(SyncService::timeline_window(&events, 10).len(), 3);
+    assert!(SyncService::timeline_window(&events, 0).is_empty());
+    assert!(SyncService::timeline_window(&events, -1).is_empty());
+}
+
+#[test]
+fn test_is_visibility_membership_matches_own_join_and_invite_only() {
+    let join = json!({ "membership": "join" });
+    let invite = json!({ "membership": "invite" });
+    let leave = json!({ "membership": "leave" });
+
+    assert!(SyncService::is_visibility_membership(Some("m.room.member"), Some("@a:hs"), &join, "@a:hs"));
+    assert!(SyncService::is_visibility_membership(Some("m.room.member"), Some("@a:hs"), &invite, "@a:hs"));
+    assert!(!SyncService::is_visibility_membership(Some("m.room.member"), Some("@a:hs"), &leave, "@a:hs"));
+    assert!(!SyncService::is_visibility_membership(Some("m.room.member"), Some("@b:hs"), &join, "@a:hs"));
+    assert!(!SyncService::is_visibility_membership(Some("m.room.name"), Some("@a:hs"), &join, "@a:hs"));
+}
+
 #[test]
 fn test_slow_request_threshold() {
     assert!(SyncService::is_slow_request_for(750.0, 750));
@@ -629,6 +653,23 @@ fn test_apply_lazy_load_members_keeps_only_timeline_members_and_self() {
     assert!(known_now.contains("@alice:localhost"));
 }
 
+#[test]
+fn test_drop_known_member_events_skips_members_already_sent() {
+    let member = |user_id: &str| json!({ "type": "m.room.member", "state_key": user_id, "content": {} });
+    let known = HashSet::from(["@bob:localhost".to_string()]);
+
+    let (filtered, known_now) = SyncService::drop_known_member_events(
+        vec![member("@bob:localhost"), member("@carol:localhost")],
+        &known,
+        false,
+    );
+    assert_eq!(filtered, vec![member("@carol:localhost")]);
+    assert_eq!(known_now.len(), 2);
+
+    let (filtered, _) = SyncService::drop_known_member_events(vec![member("@bob:localhost")], &known, true);
+    assert_eq!(filtered.len(), 1);
+}
+
 #[test]
 fn test_apply_lazy_load_members_skips_cached_members_by_default() {
     let state_events = vec![
@@ -1022,6 +1063,7 @@ fn test_build_room_sync_value_empty_events() {
         counts: RoomSyncCounts { highlight_count: 0, notification_count: 0 },
         event_fields: None,
         event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
     });
 
     assert!(value["timeline"]["events"].is_array());
@@ -1039,8 +1081,10 @@ fn test_build_room_sync_value_empty_events() {
 fn test_build_room_sync_value_with_events() {
     let mut e1 = sample_room_event("_a");
     e1.origin_server_ts = 1_000;
+    e1.stream_ordering = Some(10);
     let mut e2 = sample_room_event("_b");
     e2.origin_server_ts = 2_000;
+    e2.stream_ordering = Some(20);
 
     let value = SyncService::build_room_sync_value(BuildRoomSyncValueRequest {
         events: vec![e1, e2],
@@ -1051,6 +1095,7 @@ fn test_build_room_sync_value_with_events() {
         counts: RoomSyncCounts { highlight_count: 1, notification_count: 5 },
         event_fields: None,
         event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
     });
 
     let timeline_events = value["timeline"]["events"].as_array().unwrap();
@@ -1059,8 +1104,8 @@ fn test_build_room_sync_value_with_events() {
     assert_eq!(timeline_events[0]["event_id"], "$event_b");
     assert_eq!(timeline_events[1]["event_id"], "$event_a");
     assert_eq!(value["timeline"]["limited"], false);
-    // prev_batch uses first event of the reversed list (e2, ts=2000)
-    assert_eq!(value["timeline"]["prev_batch"], "t2000");
+    // prev_batch sits just before the first event of the reversed list (e2, stream 20)
+    assert_eq!(value["timeline"]["prev_batch"], "s19");
     assert_eq!(value["unread_notifications"]["highlight_count"], 1);
     assert_eq!(value["unread_notifications"]["notification_count"], 5);
 }
@@ -1078,6 +1123,7 @@ fn test_build_room_sync_value_applies_timeline_limit() {
         counts: RoomSyncCounts { highlight_count: 0, notification_count: 0 },
         event_fields: None,
         event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
     });
 
     let timeline_events = value["timeline"]["events"].as_array().unwrap();
@@ -1086,9 +1132,51 @@ fn test_build_room_sync_value_applies_timeline_limit() {
 }
 
 #[test]
-fn test_build_room_sync_value_prev_batch_from_first_event() {
+fn test_build_room_sync_value_limited_prev_batch_precedes_oldest_timeline_event() {
+    // Newest first, as fetched: one more than the timeline limit.
+    let events: Vec<RoomEvent> = [("_3", 30), ("_2", 20), ("_1", 10)]
+        .into_iter()
+        .map(|(suffix, stream)| {
+            let mut event = sample_room_event(suffix);
+            event.stream_ordering = Some(stream);
+            event
+        })
+        .collect();
+
+    let value = SyncService::build_room_sync_value(BuildRoomSyncValueRequest {
+        events,
+        state_list: Vec::new(),
+        ephemeral_events: Vec::new(),
+        account_data_events: Vec::new(),
+        timeline_limit: 2,
+        counts: RoomSyncCounts { highlight_count: 0, notification_count: 0 },
+        event_fields: None,
+        event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
+    });
+
+    let timeline = &value["timeline"];
+    assert_eq!(timeline["limited"], true);
+    assert_eq!(timeline["events"][0]["event_id"], "$event_2");
+    assert_eq!(timeline["events"][1]["event_id"], "$event_3");
+    // `/messages?dir=b&from=s19` returns $event_2's predecessors, i.e. the gap.
+    assert_eq!(timeline["prev_batch"], "s19");
+}
+
+#[test]
+fn test_timeline_prev_batch_with_empty_timeline_points_past_newest_event() {
+    let mut newest = sample_room_event("_n");
+    newest.stream_ordering = Some(42);
+
+    assert_eq!(SyncService::timeline_prev_batch(&[], &[newest]), "s42");
+    assert!(SyncService::timeline_prev_batch(&[], &[]).starts_with('t'));
+}
+
+#[test]
+fn test_build_room_sync_value_prev_batch_falls_back_to_timestamp() {
     let mut event = sample_room_event("_pb");
     event.origin_server_ts = 1_500;
+    event.stream_ordering = None;
 
     let value = SyncService::build_room_sync_value(BuildRoomSyncValueRequest {
         events: vec![event],
@@ -1099,6 +1187,7 @@ fn test_build_room_sync_value_prev_batch_from_first_event() {
         counts: RoomSyncCounts { highlight_count: 0, notification_count: 0 },
         event_fields: None,
         event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
     });
 
     assert_eq!(value["timeline"]["prev_batch"], "t1500");
@@ -1117,6 +1206,7 @@ fn test_build_room_sync_value_applies_event_fields_filter() {
         counts: RoomSyncCounts { highlight_count: 0, notification_count: 0 },
         event_fields: Some(&["type".to_string(), "event_id".to_string(), "unsigned.age".to_string()]),
         event_format: SyncEventFormat::Client,
+        bundled_relations: HashMap::new(),
     });
 
     let timeline_event = &value["timeline"]["events"][0];
@@ -1833,10 +1923,16 @@ fn test_room_sections_leave_membership_maps_to_leave() {
 }
 
 #[test]
-fn test_room_sections_invite_membership_maps_to_join() {
-    let memberships = vec![UserRoomMembership { room_id: "!r3:b".into(), membership: "invite".into() }];
+fn test_room_sections_invite_and_knock_memberships_map_to_their_sections() {
+    let memberships = vec![
+        UserRoomMembership { room_id: "!r3:b".into(), membership: "invite".into() },
+        UserRoomMembership { room_id: "!r4:b".into(), membership: "knock".into() },
+        UserRoomMembership { room_id: "!r5:b".into(), membership: "ban".into() },
+    ];
     let sections = SyncService::room_sections_from_memberships(&memberships);
-    assert_eq!(sections.get("!r3:b").copied(), Some(SyncRoomSection::Join));
+    assert_eq!(sections.get("!r3:b").copied(), Some(SyncRoomSection::Invite));
+    assert_eq!(sections.get("!r4:b").copied(), Some(SyncRoomSection::Knock));
+    assert_eq!(sections.get("!r5:b").copied(), Some(SyncRoomSection::Leave));
 }
 
 #[test]
@@ -1857,3 +1953,106 @@ fn test_room_sections_empty() {
     let sections = SyncService::room_sections_from_memberships(&memberships);
     assert!(sections.is_empty());
 }
+
+fn membership_event(room_id: &str, membership: &str, stream_ordering: i64) -> StateEvent {
+    StateEvent {
+        event_id: format!("$member_{stream_ordering}"),
+        room_id: room_id.to_string(),
+        event_type: Some("m.room.member".to_string()),
+        content: json!({ "membership": membership }),
+        state_key: Some("@user:example.com".to_string()),
+        stream_ordering: Some(stream_ordering),
+        ..sample_state_event()
+    }
+}
+
+#[test]
+fn test_retain_changed_room_sections_keeps_only_membership_changes_after_since() {
+    let mut sections = HashMap::from([
+        ("!joined:b".to_string(), SyncRoomSection::Join),
+        ("!old_invite:b".to_string(), SyncRoomSection::Invite),
+        ("!new_invite:b".to_string(), SyncRoomSection::Invite),
+        ("!old_leave:b".to_string(), SyncRoomSection::Leave),
+        ("!new_leave:b".to_string(), SyncRoomSection::Leave),
+    ]);
+    let membership_events = HashMap::from([
+        ("!old_invite:b".to_string(), membership_event("!old_invite:b", "invite", 5)),
+        ("!new_invite:b".to_string(), membership_event("!new_invite:b", "invite", 15)),
+        ("!old_leave:b".to_string(), membership_event("!old_leave:b", "leave", 8)),
+        ("!new_leave:b".to_string(), membership_event("!new_leave:b", "leave", 12)),
+    ]);
+
+    SyncService::retain_changed_room_sections(&mut sections, &membership_events, Some(&make_token(10)), false);
+
+    let mut kept: Vec<&str> = sections.keys().map(String::as_str).collect();
+    kept.sort_unstable();
+    assert_eq!(kept, vec!["!joined:b", "!new_invite:b", "!new_leave:b"]);
+}
+
+#[test]
+fn test_retain_changed_room_sections_initial_sync_needs_include_leave_for_archived_rooms() {
+    let sections = HashMap::from([
+        ("!invite:b".to_string(), SyncRoomSection::Invite),
+        ("!leave:b".to_string(), SyncRoomSection::Leave),
+    ]);
+    let membership_events = HashMap::from([
+        ("!invite:b".to_string(), membership_event("!invite:b", "invite", 5)),
+        ("!leave:b".to_string(), membership_event("!leave:b", "leave", 8)),
+    ]);
+
+    let mut without_leave = sections.clone();
+    SyncService::retain_changed_room_sections(&mut without_leave, &membership_events, None, false);
+    assert_eq!(without_leave.len(), 1);
+    assert!(without_leave.contains_key("!invite:b"));
+
+    let mut with_leave = sections;
+    SyncService::retain_changed_room_sections(&mut with_leave, &membership_events, None, true);
+    assert_eq!(with_leave.len(), 2);
+}
+
+#[test]
+fn test_stripped_room_state_keeps_summary_events_and_own_membership() {
+    let name = sample_state_event();
+    let power_levels = StateEvent {
+        event_type: Some("m.room.power_levels".to_string()),
+        content: json!({ "users": {} }),
+        ..sample_state_event()
+    };
+    let invite = membership_event("!room:example.com", "invite", 11);
+
+    let stripped = SyncService::stripped_room_state(Some(&invite), &[name, power_levels], "invite_room_state");
+
+    assert_eq!(
+        stripped,
+        vec![
+            json!({
+                "type": "m.room.name",
+                "state_key": "",
+                "content": { "name": "Test Room" },
+                "sender": "@sender:example.com"
+            }),
+            json!({
+                "type": "m.room.member",
+                "state_key": "@user:example.com",
+                "content": { "membership": "invite" },
+                "sender": "@sender:example.com"
+            }),
+        ]
+    );
+}
+
+#[test]
+fn test_stripped_room_state_prefers_state_carried_by_the_invite() {
+    let carried =
+        json!({ "type": "m.room.name", "state_key": "", "content": { "name": "Remote" }, "sender": "@a:remote" });
+    let invite = StateEvent {
+        unsigned: Some(json!({ "invite_room_state": [carried.clone()] })),
+        ..membership_event("!room:example.com", "invite", 11)
+    };
+
+    let stripped = SyncService::stripped_room_state(Some(&invite), &[sample_state_event()], "invite_room_state");
+
+    assert_eq!(stripped.len(), 2);
+    assert_eq!(stripped[0], carried);
+    assert_eq!(stripped[1]["state_key"], "@user:example.com");
+}
@@ -149,6 +149,8 @@ pub struct BuildSyncResponseRequest<'a> {
     pub device_id: Option<&'a str>,
     pub room_ids: &'a [String],
     pub room_sections: &'a HashMap<String, SyncRoomSection>,
+    /// The user's own membership event in each invited, knocked or left room.
+    pub membership_events: &'a HashMap<String, StateEvent>,
     pub room_events: HashMap<String, Vec<RoomEvent>>,
     pub response_filter: Option<&'a SyncResponseFilter>,
     pub timeline_limit: i64,
@@ -175,6 +177,8 @@ pub struct BuildRoomSyncValueRequest<'a> {
     pub counts: RoomSyncCounts,
     pub event_fields: Option<&'a [String]>,
     pub event_format: SyncEventFormat,
+    /// `unsigned["m.relations"]` of timeline events, by event ID.
+    pub bundled_relations: HashMap<String, Value>,
 }
 
 pub struct LazyLoadMembersRequest<'a> {
@@ -236,6 +240,8 @@ impl Default for SyncResponseFilter {
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SyncRoomSection {
     Join,
+    Invite,
+    Knock,
     Leave,
 }
 
@@ -278,6 +284,8 @@ pub enum IncrementalUpdate {
     Events,
     ToDevice,
     DeviceLists,
+    /// A receipt or typing notification arrived while waiting.
+    Ephemeral,
     Timeout,
 }
 
@@ -300,6 +308,9 @@ pub struct StateEventsBatchParams<'a> {
     pub is_incremental: bool,
     pub lazy_load_members: bool,
     pub user_id: &'a str,
+    /// Timeline each room carries in this response. An incremental `state`
+    /// covers only the gap between the since token and the first of these.
+    pub timelines: &'a HashMap<String, &'a [RoomEvent]>,
 }
 
 #[cfg(test)]
@@ -79,13 +79,17 @@ pub fn build_test_config() -> Config {
             allow_public_rooms_over_federation: true,
             auto_join_rooms: vec![],
             autocreate_auto_join_rooms: true,
+            auto_join_mxid_localpart: None,
             encryption_enabled_by_default_for_room_type: None,
+            reject_unencrypted_messages_in_encrypted_rooms: false,
             app_service_config_files: vec![],
             presence_enabled: true,
             media_path: "./data/media".to_string(),
             megolm_encryption_key_path: None,
             enable_burn_after_read_processor: true,
             refresh_token_ttl_secs: 2_592_000,
+            expose_internal_errors: false,
+            http: Default::default(),
         },
         database: DatabaseConfig {
             host,
@@ -97,6 +101,10 @@ pub fn build_test_config() -> Config {
             max_size: test_pool_max_connections,
             min_idle: Some(test_pool_min_connections),
             connection_timeout: crate::test_utils::configured_test_pool_acquire_timeout().as_secs(),
+            schema_drift_policy: Default::default(),
+            migrations_dir: None,
+            event_partitioning: Default::default(),
+            state_compaction: Default::default(),
         },
         redis: RedisConfig {
             host: "localhost".to_string(),
@@ -150,7 +158,11 @@ pub fn build_test_config() -> Config {
             admission_mode: false,
             signing_key_master_key: None,
             event_broadcast_batch_size: 100,
+            event_broadcast_queue_size: 1000,
+            rejected_events_max: 10_000,
             rate_limit: FederationRateLimitConfig::default(),
+            outbound: synapse_common::config::FederationOutboundConfig::default(),
+            forward_extremities: synapse_common::config::ForwardExtremitiesConfig::default(),
         },
         security: SecurityConfig {
             secret: "test_secret".to_string(),
@@ -188,6 +200,7 @@ pub fn build_test_config() -> Config {
             require_manual_approval: false,
             approval_tokens: Vec::new(),
         },
+        admin_bootstrap: synapse_common::config::AdminBootstrapConfig::default(),
         builtin_oidc: synapse_common::config::BuiltinOidcConfig::default(),
         worker: WorkerConfig::default(),
         cors: CorsConfig::default(),
@@ -199,6 +212,7 @@ pub fn build_test_config() -> Config {
         url_preview: synapse_common::config::UrlPreviewConfig::default(),
         oidc: synapse_common::config::OidcConfig::default(),
         saml: synapse_common::config::SamlConfig::default(),
+        ldap: synapse_common::config::LdapConfig::default(),
         retention: synapse_common::config::RetentionConfig::default(),
         telemetry: synapse_common::telemetry_config::OpenTelemetryConfig::default(),
         prometheus: synapse_common::telemetry_config::PrometheusConfig::default(),
@@ -206,6 +220,15 @@ pub fn build_test_config() -> Config {
         experimental: synapse_common::config::ExperimentalConfig::default(),
         identity: synapse_common::config::IdentityConfig::default(),
         translate: synapse_common::config::TranslateConfig::default(),
+        content_filter: synapse_common::config::ContentFilterConfig::default(),
+        room_directory: synapse_common::config::RoomDirectoryConfig::default(),
+        room_creation: synapse_common::config::RoomCreationConfig::default(),
+        welcome: synapse_common::config::WelcomeConfig::default(),
+        policy_lists: synapse_common::config::PolicyListsConfig::default(),
+        column_encryption: synapse_common::config::ColumnEncryptionConfig::default(),
+        outbound_proxy: synapse_common::config::OutboundProxyConfig::default(),
+        egress: synapse_common::config::EgressConfig::default(),
+        health: synapse_common::config::HealthConfig::default(),
         sso_redirect_allowlist: vec![],
     }
 }
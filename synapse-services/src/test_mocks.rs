@@ -134,6 +134,16 @@ impl crate::auth::CredentialAuth for FakeCredentialAuth {
         Err(ApiError::unauthorized("mock credential_auth: login not configured"))
     }
 
+    async fn login_as_app_service(
+        &self,
+        _as_token: &str,
+        _user: &str,
+        _device_id: Option<&str>,
+        _initial_display_name: Option<&str>,
+    ) -> ApiResult<(User, String, String, String)> {
+        Err(ApiError::unauthorized("mock credential_auth: login_as_app_service not configured"))
+    }
+
     async fn register(
         &self,
         _username: &str,
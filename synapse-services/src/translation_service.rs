@@ -53,7 +53,7 @@ pub struct TranslationService {
 
 impl TranslationService {
     pub fn new(config: TranslateConfig) -> Self {
-        let http_client = Client::builder()
+        let http_client = synapse_common::outbound_http::client_builder()
             .timeout(Duration::from_secs(config.timeout_secs))
             .connect_timeout(Duration::from_secs(5))
             .pool_idle_timeout(Duration::from_secs(60))
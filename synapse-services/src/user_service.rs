@@ -124,6 +124,13 @@ impl UserService {
         Ok(())
     }
 
+    /// Mark `user_id` as erased: other users see their events' content
+    /// pruned from now on.
+    #[instrument(skip(self))]
+    pub async fn mark_erased(&self, user_id: &str) -> Result<(), ApiError> {
+        self.user_storage.mark_user_erased(user_id).await.map_err(Self::db_error)
+    }
+
     // ── search / listing ───────────────────────────────────────────────
 
     #[instrument(skip(self))]
@@ -181,7 +181,7 @@ impl VoiceService {
             .voice_storage
             .get_room_messages(room_id, limit, from_ts)
             .await
-            .map_err(|e| ApiError::internal(e.to_string()))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to load room voice messages", &e))?;
 
         let messages: Vec<serde_json::Value> = records.iter().map(|r| self.record_to_message_json(r)).collect();
 
@@ -204,7 +204,7 @@ impl VoiceService {
             .voice_storage
             .get_user_messages(user_id, limit, from_ts)
             .await
-            .map_err(|e| ApiError::internal(e.to_string()))?;
+            .map_err(|e| ApiError::internal_with_log("Failed to load user voice messages", &e))?;
 
         let messages: Vec<serde_json::Value> = records.iter().map(|r| self.record_to_message_json(r)).collect();
 
@@ -222,7 +222,7 @@ impl VoiceService {
             .voice_storage
             .get_by_media_id(media_id)
             .await
-            .map_err(|e| ApiError::internal(e.to_string()))?
+            .map_err(|e| ApiError::internal_with_log("Failed to load voice message", &e))?
             .ok_or_else(|| ApiError::not_found(format!("Voice message not found: {}", media_id)))?;
 
         let content_uri = synapse_common::media_locator::MediaLocator {
@@ -14,7 +14,7 @@ impl WebhookNotifier {
         let enabled = config.enabled;
 
         if enabled {
-            let http_client = reqwest::Client::new();
+            let http_client = synapse_common::outbound_http::client();
             let config_clone = config.clone();
 
             tokio::spawn(async move {
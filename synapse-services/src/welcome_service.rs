@@ -0,0 +1,67 @@
+//! Onboarding message for newly registered users, delivered as a server
+//! notice. Configured under `welcome`; off unless `welcome.enabled` is set
+//! and a template exists for the user's language or the default locale.
+
+use std::sync::Arc;
+
+use synapse_common::config::{render_welcome_template, WelcomeConfig};
+
+use crate::server_notification_service::ServerNotificationService;
+
+pub struct WelcomeService {
+    server_notification_service: Arc<ServerNotificationService>,
+    server_name: String,
+    config: WelcomeConfig,
+}
+
+impl WelcomeService {
+    pub fn new(
+        server_notification_service: Arc<ServerNotificationService>,
+        server_name: String,
+        config: WelcomeConfig,
+    ) -> Self {
+        Self { server_notification_service, server_name, config }
+    }
+
+    /// Render the welcome message for `user_id`, or `None` when disabled or
+    /// no template matches.
+    pub fn render(&self, user_id: &str, displayname: Option<&str>, accept_language: Option<&str>) -> Option<String> {
+        if !self.config.enabled {
+            return None;
+        }
+        let template = self.config.template_for(accept_language)?;
+        let localpart = user_id.strip_prefix('@').and_then(|id| id.split(':').next()).unwrap_or(user_id);
+        Some(render_welcome_template(
+            template,
+            &[
+                ("user_id", user_id),
+                ("localpart", localpart),
+                ("displayname", displayname.unwrap_or(localpart)),
+                ("server_name", &self.server_name),
+            ],
+        ))
+    }
+
+    /// Send the welcome notice on a detached task so registration never
+    /// waits on it; a failed delivery is only logged.
+    pub fn spawn_for_new_user(
+        self: &Arc<Self>,
+        user_id: String,
+        displayname: Option<String>,
+        accept_language: Option<String>,
+    ) {
+        let Some(body) = self.render(&user_id, displayname.as_deref(), accept_language.as_deref()) else {
+            return;
+        };
+        let service = Arc::clone(self);
+        tokio::spawn(async move {
+            if let Err(e) = service
+                .server_notification_service
+                .notify_user(&service.server_name, &user_id, &service.config.msgtype, &body)
+                .await
+            {
+                ::tracing::warn!(user_id = %user_id, error = %e, "Failed to send welcome message");
+            }
+        });
+    }
+}
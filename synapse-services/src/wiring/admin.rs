@@ -18,6 +18,7 @@ use synapse_storage::email_verification::EmailVerificationStorage;
 #[derive(Clone)]
 pub struct AdminUserServices {
     pub admin_registration_service: crate::admin_registration_service::AdminRegistrationService,
+    pub admin_bootstrap_service: Arc<crate::admin_bootstrap_service::AdminBootstrapService>,
     pub admin_user_service: Arc<crate::admin_user_service::AdminUserService>,
     pub email_verification_storage: Arc<dyn synapse_storage::email_verification::EmailVerificationStoreApi>,
     pub admin_token_service: Arc<crate::admin_token_service::AdminTokenService>,
@@ -44,6 +45,7 @@ pub struct AdminMediaServices {
 #[derive(Clone)]
 pub struct AdminSecurityServices {
     pub admin_security_service: Arc<crate::admin_security_service::AdminSecurityService>,
+    pub send_quota_service: Arc<crate::send_quota_service::SendQuotaService>,
     pub captcha_storage: Arc<dyn synapse_storage::captcha::CaptchaStoreApi>,
     pub captcha_service: Arc<crate::captcha_service::CaptchaService>,
     pub audit_storage: Arc<dyn synapse_storage::audit::AuditEventStoreApi>,
@@ -75,6 +77,7 @@ pub struct AdminModuleServices {
     pub external_service_integration: Arc<crate::external_service_integration::ExternalServiceIntegration>,
     pub rendezvous_storage: Arc<dyn synapse_storage::rendezvous::RendezvousStoreApi>,
     pub rendezvous_message_storage: Arc<dyn synapse_storage::rendezvous::RendezvousMessageStoreApi>,
+    pub rendezvous_channels: Arc<crate::rendezvous_channel_service::RendezvousChannelService>,
     pub worker_storage: Arc<dyn synapse_storage::worker::WorkerStoreApi>,
     pub worker_manager: Arc<crate::worker::WorkerManager>,
 }
@@ -102,6 +105,7 @@ impl AdminServices {
         credential_auth: &Arc<dyn CredentialAuth>,
         _room_auth: &Arc<dyn RoomAuth>,
         user_storage: &Arc<dyn UserStore>,
+        module_registry: &Arc<tokio::sync::RwLock<crate::module_service::ModuleRegistry>>,
         shutdown_token: &tokio_util::sync::CancellationToken,
     ) -> Self {
         let user_service = Arc::new(UserService::new(user_storage.clone()));
@@ -117,6 +121,13 @@ impl AdminServices {
             metrics.clone(),
         );
 
+        let admin_bootstrap_service = Arc::new(crate::admin_bootstrap_service::AdminBootstrapService::new(
+            credential_auth.clone(),
+            user_storage.clone(),
+            config.admin_bootstrap.clone(),
+            config.server.name.clone(),
+        ));
+
         let email_verification_storage: Arc<dyn synapse_storage::email_verification::EmailVerificationStoreApi> =
             Arc::new(EmailVerificationStorage::new(pool));
         let audit_storage: Arc<dyn synapse_storage::audit::AuditEventStoreApi> =
@@ -139,12 +150,16 @@ impl AdminServices {
             Arc::new(synapse_storage::background_update::BackgroundUpdateStorage::new(pool));
         let background_update_service = Arc::new(
             crate::background_update_service::BackgroundUpdateService::new(background_update_storage.clone())
-                .with_lock_retry_config(config.worker.lock_max_retries, config.worker.lock_max_retry_interval_ms),
+                .with_lock_retry_config(config.worker.lock_max_retries, config.worker.lock_max_retry_interval_ms)
+                .with_online_migrations(Arc::new(synapse_storage::online_migration::OnlineMigrationStorage::new(pool))),
         );
 
         let module_storage: Arc<dyn synapse_storage::module::ModuleStoreApi> =
             Arc::new(synapse_storage::module::ModuleStorage::new(pool));
-        let module_service = Arc::new(crate::module_service::ModuleService::new(module_storage.clone()));
+        let module_service = Arc::new(crate::module_service::ModuleService::with_registry(
+            module_storage.clone(),
+            module_registry.clone(),
+        ));
         let account_validity_service =
             Arc::new(crate::module_service::AccountValidityService::new(module_storage.clone()));
 
@@ -197,10 +212,27 @@ impl AdminServices {
         let push_notification_storage: Arc<dyn synapse_storage::push_notification::PushNotificationStoreApi> =
             Arc::new(synapse_storage::push_notification::PushNotificationStorage::new(pool));
         let account_data_storage_for_push = Arc::new(synapse_storage::account_data::AccountDataStorage::new(pool));
-        let push_notification_service = Arc::new(
+        let mut push_notification_service =
             crate::push_notification_service::PushNotificationService::new(push_notification_storage.clone())
-                .with_account_data_storage(account_data_storage_for_push),
-        );
+                .with_account_data_storage(account_data_storage_for_push)
+                .with_metrics(metrics.clone());
+        if let Some(apns) = config.push.apns.as_ref().filter(|_| config.push.is_enabled()) {
+            match crate::push::providers::ApnsProvider::from_config(apns, config.push.timeout) {
+                Ok(provider) => {
+                    push_notification_service = push_notification_service.with_apns_provider(Arc::new(provider))
+                }
+                Err(error) => ::tracing::warn!(error = %error, "APNS provider not configured"),
+            }
+        }
+        if let Some(fcm) = config.push.fcm.as_ref().filter(|_| config.push.is_enabled()) {
+            match crate::push::providers::FcmProvider::from_config(fcm, config.push.timeout) {
+                Ok(provider) => {
+                    push_notification_service = push_notification_service.with_fcm_provider(Arc::new(provider))
+                }
+                Err(error) => ::tracing::warn!(error = %error, "FCM provider not configured"),
+            }
+        }
+        let push_notification_service = Arc::new(push_notification_service);
 
         let media_quota_storage: Arc<dyn synapse_storage::media_quota::MediaQuotaStoreApi> =
             Arc::new(synapse_storage::media_quota::MediaQuotaStorage::new(pool));
@@ -214,6 +246,7 @@ impl AdminServices {
             Arc::new(synapse_storage::rendezvous::RendezvousStorage::new(pool.clone()));
         let rendezvous_message_storage: Arc<dyn synapse_storage::rendezvous::RendezvousMessageStoreApi> =
             Arc::new(synapse_storage::rendezvous::RendezvousMessageStorage::new(pool.clone()));
+        let rendezvous_channels = Arc::new(crate::rendezvous_channel_service::RendezvousChannelService::new());
 
         let app_service_storage: Arc<dyn synapse_storage::application_service::ApplicationServiceStoreApi> =
             Arc::new(ApplicationServiceStorage::new(pool));
@@ -259,6 +292,12 @@ impl AdminServices {
             user_service.clone(),
         ));
         let rate_limit_storage = Arc::new(RateLimitStorage::new(pool));
+        let send_quota_service = Arc::new(crate::send_quota_service::SendQuotaService::new(
+            config.rate_limit.daily_quotas.clone(),
+            rate_limit_storage.clone(),
+            user_storage.clone(),
+            cache.clone(),
+        ));
         let admin_security_service = Arc::new(crate::admin_security_service::AdminSecurityService::new(
             user_storage.clone(),
             user_service.clone(),
@@ -285,6 +324,7 @@ impl AdminServices {
         Self {
             user: AdminUserServices {
                 admin_registration_service,
+                admin_bootstrap_service,
                 admin_user_service,
                 email_verification_storage,
                 admin_token_service,
@@ -301,6 +341,7 @@ impl AdminServices {
             media: AdminMediaServices { admin_media_service, media_quota_storage, media_quota_service },
             security: AdminSecurityServices {
                 admin_security_service,
+                send_quota_service,
                 captcha_storage,
                 captcha_service,
                 audit_storage,
@@ -330,6 +371,7 @@ impl AdminServices {
                 external_service_integration,
                 rendezvous_storage,
                 rendezvous_message_storage,
+                rendezvous_channels,
                 worker_storage,
                 worker_manager,
             },
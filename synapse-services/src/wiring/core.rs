@@ -32,6 +32,7 @@ pub struct CoreServices {
     pub key_rotation_storage: synapse_e2ee::key_rotation::KeyRotationStorage,
     pub event_broadcaster: Arc<EventBroadcaster>,
     pub event_notifier: crate::event_notifier::EventNotifier,
+    pub event_bus: crate::event_bus::EventBus,
     pub account_data_service: Arc<crate::account_data_service::AccountDataService>,
     pub client_push_service: Arc<crate::client_push_service::ClientPushService>,
     pub user_service: Arc<UserService>,
@@ -48,6 +49,8 @@ impl CoreServices {
         user_storage: &Arc<dyn UserStore>,
         server_metrics: &Arc<ServerMetrics>,
         event_broadcaster: Arc<EventBroadcaster>,
+        event_notifier: crate::event_notifier::EventNotifier,
+        event_bus: crate::event_bus::EventBus,
     ) -> Self {
         let search_service = Arc::new(crate::search_service::SearchService::with_postgres(
             &infra.config.search.elasticsearch_url,
@@ -80,15 +83,18 @@ impl CoreServices {
 
         let user_service = Arc::new(UserService::new(user_storage.clone()));
 
-        let registration_service = Arc::new(crate::registration_service::RegistrationService::new(
-            user_service.clone(),
-            token_auth.clone(),
-            credential_auth.clone(),
-            infra.metrics.clone(),
-            &infra.config.server.name,
-            infra.config.server.enable_registration,
-            infra.task_queue.clone(),
-        ));
+        let registration_service = Arc::new(
+            crate::registration_service::RegistrationService::new(
+                user_service.clone(),
+                token_auth.clone(),
+                credential_auth.clone(),
+                infra.metrics.clone(),
+                &infra.config.server.name,
+                infra.config.server.enable_registration,
+                infra.task_queue.clone(),
+            )
+            .with_email_templates(Arc::new(load_email_templates(&infra.config))),
+        );
 
         let room_account_data_storage = Arc::new(RoomAccountDataStorage::new(&infra.pool));
         let account_data_storage: Arc<dyn synapse_storage::account_data::AccountDataStoreApi> =
@@ -123,10 +129,28 @@ impl CoreServices {
             validator: validator.clone(),
             key_rotation_storage: synapse_e2ee::key_rotation::KeyRotationStorage::new(infra.pool.clone()),
             event_broadcaster,
-            event_notifier: crate::event_notifier::EventNotifier::new(),
+            event_notifier,
+            event_bus,
             account_data_service,
             client_push_service,
             user_service,
         }
     }
 }
+
+/// Email templates from `smtp.templates`. An unreadable template directory
+/// is logged and the built-in templates are used, so a bad path cannot keep
+/// the server from starting.
+fn load_email_templates(config: &Config) -> crate::email_templates::EmailTemplates {
+    let templates_config = config.smtp.templates.clone();
+    let public_baseurl = config.server.get_public_baseurl();
+    crate::email_templates::EmailTemplates::load(templates_config.clone(), &config.server.name, &public_baseurl)
+        .unwrap_or_else(|e| {
+            ::tracing::error!(
+                template_dir = ?templates_config.template_dir,
+                error = %e,
+                "Failed to read email templates; using built-in templates"
+            );
+            crate::email_templates::EmailTemplates::builtin(templates_config, &config.server.name, &public_baseurl)
+        })
+}
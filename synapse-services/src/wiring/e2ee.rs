@@ -35,6 +35,7 @@ impl E2eeServices {
         pool: &Arc<sqlx::PgPool>,
         cache: &Arc<CacheManager>,
         user_storage: &Arc<dyn UserStore>,
+        server_metrics: &Arc<synapse_common::server_metrics::ServerMetrics>,
         megolm_encryption_key_path: Option<&str>,
     ) -> Self {
         let device_key_storage = synapse_e2ee::device_keys::DeviceKeyStorage::new(pool);
@@ -76,7 +77,11 @@ impl E2eeServices {
         let secure_backup_service = synapse_e2ee::secure_backup::SecureBackupService::new(pool);
 
         let to_device_storage = synapse_e2ee::to_device::ToDeviceStorage::new(pool);
-        let to_device_service = ToDeviceService::new(to_device_storage.clone()).with_user_storage(user_storage.clone());
+        let verification_tracker =
+            Arc::new(synapse_e2ee::to_device::VerificationFlowTracker::new().with_metrics(server_metrics.clone()));
+        let to_device_service = ToDeviceService::new(to_device_storage.clone())
+            .with_user_storage(user_storage.clone())
+            .with_verification_tracker(verification_tracker);
 
         let verification_storage = synapse_e2ee::verification::VerificationStorage::new(pool);
         let verification_service = VerificationService::new(std::sync::Arc::new(verification_storage));
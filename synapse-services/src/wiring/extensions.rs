@@ -22,12 +22,15 @@ pub struct ExtensionServices {
     pub rtc_domain_service: Arc<crate::rtc::RtcDomainService>,
     pub directory_service: Arc<crate::directory_service::DirectoryService>,
     pub media_domain_service: Arc<crate::media::MediaDomainService>,
+    pub policy_list_service: Arc<crate::policy_list_service::PolicyListService>,
     #[cfg(feature = "openclaw-routes")]
     pub ai_connection_storage: Arc<dyn synapse_storage::ai_connection::AiConnectionStoreApi>,
     #[cfg(feature = "server-notifications")]
     pub server_notification_storage: Arc<dyn synapse_storage::server_notification::ServerNotificationStoreApi>,
     #[cfg(feature = "server-notifications")]
     pub server_notification_service: Arc<crate::server_notification_service::ServerNotificationService>,
+    #[cfg(feature = "server-notifications")]
+    pub welcome_service: Arc<crate::welcome_service::WelcomeService>,
     #[cfg(feature = "privacy-ext")]
     pub privacy_storage: Arc<dyn synapse_storage::privacy::PrivacyStoreApi>,
     #[cfg(feature = "widgets")]
@@ -51,6 +54,7 @@ pub struct ExtensionServicesDeps<'a> {
     pub threepid_storage: Arc<dyn synapse_storage::ThreepidStoreApi>,
     pub presence_storage: &'a Arc<dyn synapse_storage::presence::PresenceStoreApi>,
     pub federation: &'a super::FederationServices,
+    pub federation_blacklist_service: Arc<crate::federation_blacklist_service::FederationBlacklistService>,
     pub media_service: &'a crate::media_service::MediaService,
     pub media_domain_service: &'a Arc<crate::media::MediaDomainService>,
     pub ui_auth_session_timeout: i64,
@@ -66,6 +70,7 @@ impl ExtensionServices {
             threepid_storage: _,
             presence_storage,
             federation,
+            federation_blacklist_service,
             media_service,
             media_domain_service,
             ui_auth_session_timeout,
@@ -90,6 +95,19 @@ impl ExtensionServices {
             Arc::new(federation.key_rotation_manager.clone()),
         ));
         #[cfg(feature = "friends")]
+        {
+            let client = Arc::downgrade(&friend_room_service.federation_client);
+            let local_origin = federation.federation_server_name.clone();
+            federation.signature_cache.register_key_rotation_listener(Arc::new(move |event| {
+                if event.origin != local_origin {
+                    return;
+                }
+                if let Some(client) = client.upgrade() {
+                    client.on_local_key_rotated(&event);
+                }
+            }));
+        }
+        #[cfg(feature = "friends")]
         let friend_federation = Arc::new(synapse_federation::FriendFederation::new(
             friend_room_service.clone() as Arc<dyn synapse_common::traits::FriendRoomProvider>
         ));
@@ -138,6 +156,19 @@ impl ExtensionServices {
             server_notification_storage.clone(),
             user_service.clone(),
         ));
+        #[cfg(feature = "server-notifications")]
+        let welcome_service = Arc::new(crate::welcome_service::WelcomeService::new(
+            server_notification_service.clone(),
+            infra.config.server.get_server_name().to_string(),
+            infra.config.welcome.clone(),
+        ));
+
+        let policy_list_service = Arc::new(crate::policy_list_service::PolicyListService::new(
+            rooms.room_service.clone(),
+            federation_blacklist_service,
+            infra.config.server.get_server_name().to_string(),
+            infra.config.policy_lists.clone(),
+        ));
 
         #[cfg(feature = "privacy-ext")]
         let privacy_storage: Arc<dyn synapse_storage::privacy::PrivacyStoreApi> =
@@ -199,12 +230,15 @@ impl ExtensionServices {
             rtc_domain_service,
             directory_service,
             media_domain_service: media_domain_service.clone(),
+            policy_list_service,
             #[cfg(feature = "openclaw-routes")]
             ai_connection_storage,
             #[cfg(feature = "server-notifications")]
             server_notification_storage,
             #[cfg(feature = "server-notifications")]
             server_notification_service,
+            #[cfg(feature = "server-notifications")]
+            welcome_service,
             #[cfg(feature = "privacy-ext")]
             privacy_storage,
             #[cfg(feature = "widgets")]
@@ -1,8 +1,9 @@
-//! Federation assembly — key rotation, federation client, device sync.
+//! Federation assembly — key rotation, signature cache, federation client,
+//! device sync.
 
 use std::sync::Arc;
 
-use synapse_cache::CacheManager;
+use synapse_cache::{CacheManager, FederationSignatureCache, SignatureCacheConfig};
 use synapse_common::config::Config;
 use synapse_common::task_queue::RedisTaskQueue;
 use synapse_e2ee::key_rotation::KeyRotationStorage;
@@ -13,6 +14,9 @@ use synapse_federation::{DeviceSyncManager, EventAuthChain, FederationClient, Ke
 pub struct FederationServices {
     pub event_auth_chain: EventAuthChain,
     pub key_rotation_manager: KeyRotationManager,
+    /// Verified-signature cache for inbound federation. Its key rotation
+    /// listeners are invoked whenever our own signing key rotates.
+    pub signature_cache: Arc<FederationSignatureCache>,
     pub key_rotation_service: Arc<crate::federation_key_rotation_service::FederationKeyRotationService>,
     pub federation_client: Arc<dyn FederationClientApi>,
     pub device_sync_manager: DeviceSyncManager,
@@ -39,19 +43,32 @@ impl FederationServices {
             config.server.signing_key_path.clone(),
             config.federation.signing_key_master_key.as_ref().map(|k| k.as_bytes().to_vec()),
         );
+        let signature_cache = Arc::new(FederationSignatureCache::new(SignatureCacheConfig::from_federation_config(
+            config.federation.signature_cache_ttl,
+            config.federation.key_cache_ttl,
+            config.federation.key_rotation_grace_period_ms,
+        )));
+        // Set before anything can rotate so every rotation reaches the
+        // cache's listeners, including ones registered later in wiring.
+        key_rotation_manager.set_signature_cache(signature_cache.clone());
+
         let key_rotation_service = Arc::new(crate::federation_key_rotation_service::FederationKeyRotationService::new(
             Arc::new(key_rotation_manager.clone()),
             Arc::new(KeyRotationStorage::new(pool.clone())),
         ));
 
-        let federation_client =
-            Arc::new(FederationClient::new(server_name.clone(), Arc::new(key_rotation_manager.clone())));
+        let federation_client = Arc::new(FederationClient::with_outbound_config(
+            server_name.clone(),
+            Arc::new(key_rotation_manager.clone()),
+            &config.federation.outbound,
+        ));
 
         let device_sync_manager = DeviceSyncManager::new(pool, Some(cache.clone()), task_queue.clone());
 
         Self {
             event_auth_chain,
             key_rotation_manager,
+            signature_cache,
             key_rotation_service,
             federation_client,
             device_sync_manager,
@@ -29,6 +29,9 @@ pub struct RoomSyncServices {
     pub thread_storage: Arc<dyn synapse_storage::thread::ThreadStoreApi>,
     pub thread_service: Arc<crate::thread_service::ThreadService>,
     pub room_tag_storage: Arc<dyn synapse_storage::room_tag::RoomTagStoreApi>,
+    pub auto_join_service: Arc<crate::auto_join_service::AutoJoinService>,
+    pub delayed_event_service: Arc<crate::delayed_event_service::DelayedEventService>,
+    pub event_visibility_service: Arc<crate::event_visibility_service::EventVisibilityService>,
 }
 
 impl RoomSyncServices {
@@ -46,12 +49,18 @@ impl RoomSyncServices {
         federation_client: Arc<dyn synapse_federation::client_api::FederationClientApi>,
         sticky_event_storage: Arc<dyn synapse_storage::sticky_event::StickyEventStoreApi>,
         user_service: Arc<UserService>,
+        content_filter: Option<Arc<crate::content_filter_service::ContentFilterService>>,
+        event_bus: &crate::event_bus::EventBus,
+        event_notifier: &crate::event_notifier::EventNotifier,
     ) -> Self {
         let server_name_for_storage = infra.config.server.get_server_name().to_string();
         let room_storage: Arc<dyn synapse_storage::room::RoomStoreApi> = Arc::new(RoomStorage::new(&infra.pool));
         let event_storage_concrete = Arc::new(EventStorage::new(&infra.pool, server_name_for_storage));
         let event_reader: Arc<dyn synapse_storage::event::EventReader> = event_storage_concrete.clone();
-        let event_writer: Arc<dyn synapse_storage::event::EventWriter> = event_storage_concrete.clone();
+        let event_writer: Arc<dyn synapse_storage::event::EventWriter> = Arc::new(
+            crate::event_bus::NotifyingEventWriter::new(event_storage_concrete.clone(), event_bus.clone())
+                .with_notifier(event_notifier.clone()),
+        );
         let device_storage: Arc<dyn synapse_storage::device::DeviceListStoreApi> =
             Arc::new(DeviceStorage::new(&infra.pool));
         let relations_storage: Arc<dyn synapse_storage::relations::RelationsStoreApi> =
@@ -73,6 +82,11 @@ impl RoomSyncServices {
         #[cfg(feature = "beacons")]
         let beacon_service = Arc::new(crate::beacon_service::BeaconService::new(beacon_storage, infra.cache.clone()));
 
+        let event_visibility_service = Arc::new(crate::event_visibility_service::EventVisibilityService::new(
+            event_reader.clone(),
+            user_service.store().clone(),
+        ));
+
         let room_service = Arc::new(crate::room_service::RoomService::new(crate::room_service::RoomServiceConfig {
             room_storage: room_storage.clone(),
             member_storage: member_storage.clone(),
@@ -101,8 +115,19 @@ impl RoomSyncServices {
                 Arc::new(synapse_e2ee::key_rotation::KeyRotationStorage::new(infra.pool.clone()))
                     as Arc<dyn synapse_e2ee::key_rotation::KeyRotationStorageApi>,
             ),
+            content_filter,
+            event_visibility: Some(event_visibility_service.clone()),
         }));
 
+        let auto_join_service =
+            Arc::new(crate::auto_join_service::AutoJoinService::new(room_service.clone(), &infra.config.server));
+        let delayed_event_service = Arc::new(crate::delayed_event_service::DelayedEventService::new(
+            Arc::new(synapse_storage::delayed_event::DelayedEventStorage::new(infra.pool.clone())),
+            room_service.clone(),
+            infra.config.server.get_server_name().to_string(),
+            infra.config.experimental.msc4140_max_delay_ms,
+        ));
+
         let sync_room_account_data_storage: Arc<dyn RoomAccountDataStoreApi> =
             Arc::new(RoomAccountDataStorage::new(&infra.pool));
         let sync_account_data_storage: Arc<dyn synapse_storage::account_data::AccountDataStoreApi> =
@@ -110,8 +135,8 @@ impl RoomSyncServices {
         let sync_device_key_storage: Arc<dyn synapse_e2ee::device_keys::DeviceKeyStoreApi> =
             Arc::new(synapse_e2ee::device_keys::DeviceKeyStorage::new(&infra.pool));
         let sync_key_rotation_storage = synapse_e2ee::key_rotation::KeyRotationStorage::new(infra.pool.clone());
-        let sync_service =
-            Arc::new(crate::sync_service::SyncService::from_deps(crate::sync_service::SyncServiceDeps {
+        let sync_service = Arc::new(
+            crate::sync_service::SyncService::from_deps(crate::sync_service::SyncServiceDeps {
                 presence_storage: presence_storage.clone(),
                 member_storage: member_storage.clone(),
                 event_reader: event_reader.clone(),
@@ -127,7 +152,10 @@ impl RoomSyncServices {
                 metrics: infra.metrics.clone(),
                 performance: infra.config.performance.clone(),
                 cache: infra.cache.clone(),
-            }));
+            })
+            .with_event_notifier(event_notifier.clone())
+            .with_event_visibility(event_visibility_service.clone()),
+        );
 
         let typing_service = Arc::new(crate::typing_service::TypingService::new(infra.cache.clone()));
 
@@ -182,6 +210,9 @@ impl RoomSyncServices {
             thread_storage,
             thread_service,
             room_tag_storage,
+            auto_join_service,
+            delayed_event_service,
+            event_visibility_service,
         }
     }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use sqlx::PgPool;
+use std::collections::HashMap;
 use std::sync::Arc;
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
@@ -17,6 +18,21 @@ pub trait AccountDataStoreApi: Send + Sync + std::fmt::Debug {
     async fn list_account_data(&self, user_id: &str) -> Result<Vec<AccountDataRecord>, ApiError>;
     async fn delete_account_data(&self, user_id: &str, data_type: &str) -> Result<bool, ApiError>;
     async fn upsert_account_data(&self, user_id: &str, data_type: &str, content: Value) -> Result<(), ApiError>;
+
+    /// `data_type` content for each of `user_ids` that has it set.
+    async fn get_account_data_content_for_users(
+        &self,
+        user_ids: &[String],
+        data_type: &str,
+    ) -> Result<HashMap<String, Value>, ApiError> {
+        let mut contents = HashMap::new();
+        for user_id in user_ids {
+            if let Some(content) = self.get_account_data_content(user_id, data_type).await? {
+                contents.insert(user_id.clone(), content);
+            }
+        }
+        Ok(contents)
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -80,6 +96,22 @@ impl AccountDataStoreApi for AccountDataStorage {
         .map_err(|e| ApiError::internal_with_log("Failed to upsert account data", &e))?;
         Ok(())
     }
+
+    async fn get_account_data_content_for_users(
+        &self,
+        user_ids: &[String],
+        data_type: &str,
+    ) -> Result<HashMap<String, Value>, ApiError> {
+        let rows = sqlx::query_as::<_, (String, Value)>(
+            "SELECT user_id, content FROM account_data WHERE user_id = ANY($1) AND data_type = $2",
+        )
+        .bind(user_ids)
+        .bind(data_type)
+        .fetch_all(&*self.pool)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        Ok(rows.into_iter().collect())
+    }
 }
 
 #[cfg(test)]
@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use synapse_common::ApiError;
+use synapse_common::{current_timestamp_millis, ApiError};
 
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MediaCursor {
@@ -112,7 +112,8 @@ impl AdminMediaStorage {
             ON CONFLICT (media_id) DO UPDATE
             SET content_type = EXCLUDED.content_type,
                 file_name = EXCLUDED.file_name,
-                size = EXCLUDED.size
+                size = EXCLUDED.size,
+                deleted_at = NULL
             "#,
         )
         .bind(media_id)
@@ -133,9 +134,10 @@ impl AdminMediaStorage {
         let media: Vec<AdminMediaRow> = sqlx::query_as::<_, AdminMediaRow>(
             r#"SELECT media_id, content_type, file_name, size, uploader_user_id, created_ts, last_accessed_at, quarantine_status
                FROM media_metadata
-               WHERE ($1::BIGINT IS NULL AND $2::TEXT IS NULL)
+               WHERE deleted_at IS NULL
+                 AND (($1::BIGINT IS NULL AND $2::TEXT IS NULL)
                   OR created_ts < $1
-                  OR (created_ts = $1 AND media_id < $2)
+                  OR (created_ts = $1 AND media_id < $2))
                ORDER BY created_ts DESC, media_id DESC
                LIMIT $3"#,
         )
@@ -160,7 +162,7 @@ impl AdminMediaStorage {
     pub async fn get_media_info(&self, media_id: &str) -> Result<Option<AdminMediaInfo>, ApiError> {
         let media: Option<AdminMediaRow> = sqlx::query_as::<_, AdminMediaRow>(
             r#"SELECT media_id, content_type, file_name, size, uploader_user_id, created_ts, last_accessed_at, quarantine_status
-               FROM media_metadata WHERE media_id = $1"#,
+               FROM media_metadata WHERE media_id = $1 AND deleted_at IS NULL"#,
         )
         .bind(media_id)
         .fetch_optional(&self.pool)
@@ -170,25 +172,32 @@ impl AdminMediaStorage {
         Ok(media.map(map_media_row))
     }
 
+    /// Tombstone a media row. [`Self::vacuum_deleted_media`] removes it, and
+    /// its thumbnails, later.
     pub async fn delete_media(&self, media_id: &str) -> Result<bool, ApiError> {
-        let result = sqlx::query("DELETE FROM media_metadata WHERE media_id = $1")
-            .bind(media_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        let result =
+            sqlx::query("UPDATE media_metadata SET deleted_at = $2 WHERE media_id = $1 AND deleted_at IS NULL")
+                .bind(media_id)
+                .bind(current_timestamp_millis())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
 
         Ok(result.rows_affected() > 0)
     }
 
     pub async fn get_media_quota(&self) -> Result<AdminMediaQuotaSummary, ApiError> {
-        let total_size = sqlx::query_scalar::<_, i64>("SELECT COALESCE(SUM(size), 0)::BIGINT FROM media_metadata")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
-        let total_count = sqlx::query_scalar::<_, i64>("SELECT COUNT(*)::BIGINT FROM media_metadata")
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        let total_size = sqlx::query_scalar::<_, i64>(
+            "SELECT COALESCE(SUM(size), 0)::BIGINT FROM media_metadata WHERE deleted_at IS NULL",
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        let total_count =
+            sqlx::query_scalar::<_, i64>("SELECT COUNT(*)::BIGINT FROM media_metadata WHERE deleted_at IS NULL")
+                .fetch_one(&self.pool)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
 
         Ok(AdminMediaQuotaSummary { total_size, total_count })
     }
@@ -197,7 +206,7 @@ impl AdminMediaStorage {
         let media: Vec<AdminMediaRow> = sqlx::query_as::<_, AdminMediaRow>(
             r#"SELECT media_id, content_type, file_name, size, uploader_user_id, created_ts,
                NULL::BIGINT AS last_accessed_at, NULL::TEXT AS quarantine_status
-               FROM media_metadata WHERE uploader_user_id = $1 ORDER BY created_ts DESC"#,
+               FROM media_metadata WHERE uploader_user_id = $1 AND deleted_at IS NULL ORDER BY created_ts DESC"#,
         )
         .bind(user_id)
         .fetch_all(&self.pool)
@@ -208,11 +217,35 @@ impl AdminMediaStorage {
     }
 
     pub async fn delete_user_media(&self, user_id: &str) -> Result<u64, ApiError> {
-        let result = sqlx::query("DELETE FROM media_metadata WHERE uploader_user_id = $1")
-            .bind(user_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+        let result =
+            sqlx::query("UPDATE media_metadata SET deleted_at = $2 WHERE uploader_user_id = $1 AND deleted_at IS NULL")
+                .bind(user_id)
+                .bind(current_timestamp_millis())
+                .execute(&self.pool)
+                .await
+                .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
+
+        Ok(result.rows_affected())
+    }
+
+    /// Physically delete up to `batch_size` tombstoned media rows, oldest
+    /// first. Returns the number of rows removed.
+    pub async fn vacuum_deleted_media(&self, batch_size: i64) -> Result<u64, ApiError> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM media_metadata
+            WHERE media_id IN (
+                SELECT media_id FROM media_metadata
+                WHERE deleted_at IS NOT NULL
+                ORDER BY deleted_at
+                LIMIT $1
+            )
+            "#,
+        )
+        .bind(batch_size)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Database error", &e))?;
 
         Ok(result.rows_affected())
     }
@@ -133,6 +133,13 @@ pub trait BackgroundUpdateStoreApi: Send + Sync {
         items_processed: i32,
         total_items: Option<i32>,
     ) -> Result<BackgroundUpdate, sqlx::Error>;
+    async fn save_batch_progress(
+        &self,
+        job_name: &str,
+        progress: serde_json::Value,
+        items_processed: i32,
+        total_items: Option<i32>,
+    ) -> Result<BackgroundUpdate, sqlx::Error>;
     async fn set_error(&self, job_name: &str, error_message: &str) -> Result<BackgroundUpdate, sqlx::Error>;
     async fn delete_update(&self, job_name: &str) -> Result<(), sqlx::Error>;
     async fn acquire_lock_with_retry(
@@ -332,6 +339,37 @@ impl BackgroundUpdateStorage {
         Ok(row)
     }
 
+    /// Record one batch of a batched job: replace `progress` with the job's own
+    /// resume state and add `items_processed` to the running count.
+    pub async fn save_batch_progress(
+        &self,
+        job_name: &str,
+        progress: serde_json::Value,
+        items_processed: i32,
+        total_items: Option<i32>,
+    ) -> Result<BackgroundUpdate, sqlx::Error> {
+        let row = sqlx::query_as::<_, BackgroundUpdate>(
+            r"
+            UPDATE background_updates SET
+                progress = $2,
+                processed_items = processed_items + $3,
+                total_items = COALESCE($4, total_items),
+                updated_ts = $5
+            WHERE update_name = $1
+            RETURNING *
+            ",
+        )
+        .bind(job_name)
+        .bind(&progress)
+        .bind(items_processed)
+        .bind(total_items)
+        .bind(current_timestamp_millis())
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(row)
+    }
+
     pub async fn set_error(&self, job_name: &str, error_message: &str) -> Result<BackgroundUpdate, sqlx::Error> {
         let now = current_timestamp_millis();
 
@@ -586,6 +624,15 @@ impl BackgroundUpdateStoreApi for BackgroundUpdateStorage {
     ) -> Result<BackgroundUpdate, sqlx::Error> {
         self.update_progress(job_name, items_processed, total_items).await
     }
+    async fn save_batch_progress(
+        &self,
+        job_name: &str,
+        progress: serde_json::Value,
+        items_processed: i32,
+        total_items: Option<i32>,
+    ) -> Result<BackgroundUpdate, sqlx::Error> {
+        self.save_batch_progress(job_name, progress, items_processed, total_items).await
+    }
     async fn set_error(&self, job_name: &str, error_message: &str) -> Result<BackgroundUpdate, sqlx::Error> {
         self.set_error(job_name, error_message).await
     }
@@ -1,11 +1,17 @@
 use std::sync::Arc;
 
 use sqlx::PgPool;
+use synapse_common::column_encryption::{self, columns};
 use synapse_common::current_timestamp_millis;
 use synapse_common::ApiError;
 
 use super::models::*;
 
+fn open_attribute(mut row: CasUserAttributeRow) -> Result<CasUserAttribute, ApiError> {
+    row.attribute_value = column_encryption::open(columns::CAS_USER_ATTRIBUTE_VALUE, &row.attribute_value)?;
+    Ok(row.into())
+}
+
 #[derive(Clone)]
 pub struct CasStorage {
     pool: PgPool,
@@ -323,6 +329,7 @@ impl CasStorage {
         attribute_value: &str,
     ) -> Result<CasUserAttribute, ApiError> {
         let now = current_timestamp_millis();
+        let attribute_value = column_encryption::seal(columns::CAS_USER_ATTRIBUTE_VALUE, attribute_value)?;
 
         let attr = sqlx::query_as::<_, CasUserAttributeRow>(
             r"
@@ -335,13 +342,13 @@ impl CasStorage {
         )
         .bind(user_id)
         .bind(attribute_name)
-        .bind(attribute_value)
+        .bind(&attribute_value)
         .bind(now)
         .fetch_one(&self.pool)
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to set CAS user attribute", &e))?;
 
-        Ok(attr.into())
+        open_attribute(attr)
     }
 
     pub async fn get_user_attributes(&self, user_id: &str) -> Result<Vec<CasUserAttribute>, ApiError> {
@@ -357,7 +364,7 @@ impl CasStorage {
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to get CAS user attributes", &e))?;
 
-        Ok(attrs.into_iter().map(CasUserAttribute::from).collect())
+        attrs.into_iter().map(open_attribute).collect()
     }
 
     pub async fn create_slo_session(
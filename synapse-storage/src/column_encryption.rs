@@ -0,0 +1,174 @@
+//! Re-encryption of sealed columns under the active column key.
+//!
+//! Rows written before column encryption was enabled, or sealed under a key
+//! that has since been rotated out of `active_key_id`, are opened and sealed
+//! again. Each update only applies if the row still holds the value that was
+//! read, so concurrent writes are never overwritten. Rows that cannot be
+//! opened (for example because their key was removed from config) are
+//! skipped and counted. Intended to be invoked by a scheduled background
+//! task (see `src/server/mod.rs`).
+
+use serde_json::Value;
+use sqlx::PgPool;
+use synapse_common::column_encryption::{columns, sealed_like_pattern, ColumnCipher, ColumnEncryptionError};
+
+#[derive(Debug, Clone, Copy)]
+pub struct EncryptedColumn {
+    /// Associated-data name from [`columns`].
+    pub name: &'static str,
+    table: &'static str,
+    key_column: &'static str,
+    key_type: &'static str,
+    value_column: &'static str,
+    json: bool,
+}
+
+pub const ENCRYPTED_COLUMNS: &[EncryptedColumn] = &[
+    EncryptedColumn {
+        name: columns::MEGOLM_SESSION_KEY,
+        table: "megolm_sessions",
+        key_column: "id",
+        key_type: "uuid",
+        value_column: "session_key",
+        json: false,
+    },
+    EncryptedColumn {
+        name: columns::MEGOLM_VODOZEMAC_PICKLE,
+        table: "megolm_sessions",
+        key_column: "id",
+        key_type: "uuid",
+        value_column: "vodozemac_pickle",
+        json: false,
+    },
+    EncryptedColumn {
+        name: columns::KEY_BACKUP_AUTH_DATA,
+        table: "key_backups",
+        key_column: "backup_id",
+        key_type: "bigint",
+        value_column: "auth_data",
+        json: true,
+    },
+    EncryptedColumn {
+        name: columns::SAML_SESSION_ATTRIBUTES,
+        table: "saml_sessions",
+        key_column: "id",
+        key_type: "bigint",
+        value_column: "attributes",
+        json: true,
+    },
+    EncryptedColumn {
+        name: columns::SAML_USER_MAPPING_ATTRIBUTES,
+        table: "saml_user_mapping",
+        key_column: "id",
+        key_type: "bigint",
+        value_column: "attributes",
+        json: true,
+    },
+    EncryptedColumn {
+        name: columns::SAML_AUTH_EVENT_ATTRIBUTES,
+        table: "saml_auth_events",
+        key_column: "id",
+        key_type: "bigint",
+        value_column: "attributes",
+        json: true,
+    },
+    EncryptedColumn {
+        name: columns::CAS_USER_ATTRIBUTE_VALUE,
+        table: "cas_user_attributes",
+        key_column: "id",
+        key_type: "bigint",
+        value_column: "attribute_value",
+        json: false,
+    },
+];
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReencryptionStats {
+    pub resealed: u64,
+    pub failed: u64,
+}
+
+#[derive(sqlx::FromRow)]
+struct StoredValueRow {
+    id: String,
+    value: String,
+}
+
+/// Reseals every row of `column` that is not already under the active key,
+/// reading `batch_size` rows per query. Does nothing while `cipher` has no
+/// active key.
+pub async fn reencrypt_column(
+    pool: &PgPool,
+    column: &EncryptedColumn,
+    cipher: &ColumnCipher,
+    batch_size: i64,
+) -> Result<ReencryptionStats, sqlx::Error> {
+    let mut stats = ReencryptionStats::default();
+    let Some(active_key_id) = cipher.active_key_id() else {
+        return Ok(stats);
+    };
+
+    let EncryptedColumn { table, key_column, key_type, value_column, .. } = *column;
+    let scalar = if column.json { format!("({value_column} #>> '{{}}')") } else { value_column.to_string() };
+    let select = format!(
+        "SELECT {key_column}::text AS id, {value_column}::text AS value FROM {table} \
+         WHERE {value_column} IS NOT NULL AND {scalar} NOT LIKE $1 \
+           AND ($2::text IS NULL OR {key_column} > $2::{key_type}) \
+         ORDER BY {key_column} LIMIT $3"
+    );
+    let update = format!(
+        "UPDATE {table} SET {value_column} = $1 WHERE {key_column} = $2::{key_type} AND {value_column}::text = $3"
+    );
+    let pattern = sealed_like_pattern(active_key_id);
+
+    let mut cursor: Option<String> = None;
+    loop {
+        let rows = sqlx::query_as::<_, StoredValueRow>(&select)
+            .bind(&pattern)
+            .bind(cursor.as_deref())
+            .bind(batch_size)
+            .fetch_all(pool)
+            .await?;
+        let exhausted = rows.len() < usize::try_from(batch_size).unwrap_or(usize::MAX);
+
+        for row in &rows {
+            let resealed = if column.json {
+                reseal_json(cipher, column.name, &row.value)
+            } else {
+                reseal_text(cipher, column.name, &row.value)
+            };
+            let query = match resealed {
+                Ok(Resealed::Text(value)) => sqlx::query(&update).bind(value),
+                Ok(Resealed::Json(value)) => sqlx::query(&update).bind(value),
+                Err(error) => {
+                    ::tracing::warn!(column = column.name, id = %row.id, error = %error, "Cannot re-encrypt column value");
+                    stats.failed += 1;
+                    continue;
+                }
+            };
+            let result = query.bind(&row.id).bind(&row.value).execute(pool).await?;
+            stats.resealed += result.rows_affected();
+        }
+
+        match rows.last() {
+            Some(last) if !exhausted => cursor = Some(last.id.clone()),
+            _ => return Ok(stats),
+        }
+    }
+}
+
+enum Resealed {
+    Text(String),
+    Json(Value),
+}
+
+fn reseal_text(cipher: &ColumnCipher, column: &str, stored: &str) -> Result<Resealed, ColumnEncryptionError> {
+    let plaintext = cipher.open(column, stored)?;
+    cipher.seal(column, &plaintext).map(Resealed::Text)
+}
+
+fn reseal_json(cipher: &ColumnCipher, column: &str, stored: &str) -> Result<Resealed, ColumnEncryptionError> {
+    let value = serde_json::from_str(stored).map_err(|_| ColumnEncryptionError::Malformed)?;
+    let value = cipher.open_json(column, value)?;
+    cipher.seal_json(column, &value).map(Resealed::Json)
+}
@@ -0,0 +1,262 @@
+//! Delayed events (MSC4140): events a client asked to be sent later.
+//!
+//! Rows are `pending` until they are due, `sending` while a worker holds them
+//! and then `sent`, `cancelled` or `failed`. A claim leases the row until
+//! `scheduled_ts`, so a worker that dies mid-send leaves it to be claimed
+//! again once the lease runs out.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct DelayedEvent {
+    pub id: i64,
+    pub delay_id: String,
+    pub room_id: String,
+    pub user_id: String,
+    pub device_id: String,
+    pub event_type: String,
+    pub state_key: Option<String>,
+    pub content: Value,
+    pub delay_ms: i64,
+    pub scheduled_ts: i64,
+    pub created_ts: i64,
+    pub status: String,
+    pub event_id: Option<String>,
+    pub last_error: Option<String>,
+}
+
+impl DelayedEvent {
+    /// When the current delay started; restarting moves it forward.
+    pub fn running_since(&self) -> i64 {
+        self.scheduled_ts - self.delay_ms
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct NewDelayedEvent {
+    pub delay_id: String,
+    pub room_id: String,
+    pub user_id: String,
+    pub device_id: String,
+    pub event_type: String,
+    pub state_key: Option<String>,
+    pub content: Value,
+    pub delay_ms: i64,
+}
+
+#[async_trait]
+pub trait DelayedEventStoreApi: Send + Sync {
+    async fn insert_delayed_event(&self, event: &NewDelayedEvent, now_ts: i64) -> Result<DelayedEvent, sqlx::Error>;
+
+    /// The user's delayed events that have not been sent yet, soonest first.
+    async fn get_pending_delayed_events(&self, user_id: &str) -> Result<Vec<DelayedEvent>, sqlx::Error>;
+
+    /// Starts the delay of a pending event again from `now_ts`. Returns the
+    /// number of rows updated.
+    async fn restart_delayed_event(&self, user_id: &str, delay_id: &str, now_ts: i64) -> Result<u64, sqlx::Error>;
+
+    async fn cancel_delayed_event(&self, user_id: &str, delay_id: &str) -> Result<u64, sqlx::Error>;
+
+    /// Claims one pending event of the user to send it right away.
+    async fn take_delayed_event(
+        &self,
+        user_id: &str,
+        delay_id: &str,
+        lease_until_ts: i64,
+    ) -> Result<Option<DelayedEvent>, sqlx::Error>;
+
+    /// Claims up to `limit` due events, including `sending` rows whose lease
+    /// expired.
+    async fn claim_due_delayed_events(
+        &self,
+        now_ts: i64,
+        lease_until_ts: i64,
+        limit: i64,
+    ) -> Result<Vec<DelayedEvent>, sqlx::Error>;
+
+    async fn mark_delayed_event_sent(&self, id: i64, event_id: &str) -> Result<(), sqlx::Error>;
+
+    async fn mark_delayed_event_failed(&self, id: i64, last_error: &str) -> Result<(), sqlx::Error>;
+
+    /// Cancels pending delayed state events overwritten by a newer state
+    /// event with the same type and state key.
+    async fn cancel_delayed_state_events(
+        &self,
+        room_id: &str,
+        event_type: &str,
+        state_key: &str,
+    ) -> Result<u64, sqlx::Error>;
+}
+
+const DELAYED_EVENT_COLUMNS: &str = "id, delay_id, room_id, user_id, device_id, event_type, state_key, content, \
+                                     delay_ms, scheduled_ts, created_ts, status, event_id, last_error";
+
+#[derive(Clone)]
+pub struct DelayedEventStorage {
+    pool: Arc<PgPool>,
+}
+
+impl DelayedEventStorage {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl DelayedEventStoreApi for DelayedEventStorage {
+    async fn insert_delayed_event(&self, event: &NewDelayedEvent, now_ts: i64) -> Result<DelayedEvent, sqlx::Error> {
+        sqlx::query_as::<_, DelayedEvent>(&format!(
+            r"
+            INSERT INTO delayed_events (
+                delay_id, room_id, user_id, device_id, event_type, state_key, content,
+                delay_ms, scheduled_ts, created_ts, status
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, 'pending')
+            RETURNING {DELAYED_EVENT_COLUMNS}
+            "
+        ))
+        .bind(&event.delay_id)
+        .bind(&event.room_id)
+        .bind(&event.user_id)
+        .bind(&event.device_id)
+        .bind(&event.event_type)
+        .bind(&event.state_key)
+        .bind(&event.content)
+        .bind(event.delay_ms)
+        .bind(now_ts + event.delay_ms)
+        .bind(now_ts)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    async fn get_pending_delayed_events(&self, user_id: &str) -> Result<Vec<DelayedEvent>, sqlx::Error> {
+        sqlx::query_as::<_, DelayedEvent>(&format!(
+            r"
+            SELECT {DELAYED_EVENT_COLUMNS} FROM delayed_events
+            WHERE user_id = $1 AND status = 'pending'
+            ORDER BY scheduled_ts ASC
+            "
+        ))
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    async fn restart_delayed_event(&self, user_id: &str, delay_id: &str, now_ts: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r"
+            UPDATE delayed_events SET scheduled_ts = $3 + delay_ms
+            WHERE user_id = $1 AND delay_id = $2 AND status = 'pending'
+            ",
+        )
+        .bind(user_id)
+        .bind(delay_id)
+        .bind(now_ts)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn cancel_delayed_event(&self, user_id: &str, delay_id: &str) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r"
+            UPDATE delayed_events SET status = 'cancelled'
+            WHERE user_id = $1 AND delay_id = $2 AND status = 'pending'
+            ",
+        )
+        .bind(user_id)
+        .bind(delay_id)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
+    async fn take_delayed_event(
+        &self,
+        user_id: &str,
+        delay_id: &str,
+        lease_until_ts: i64,
+    ) -> Result<Option<DelayedEvent>, sqlx::Error> {
+        sqlx::query_as::<_, DelayedEvent>(&format!(
+            r"
+            UPDATE delayed_events SET status = 'sending', scheduled_ts = $3
+            WHERE user_id = $1 AND delay_id = $2 AND status = 'pending'
+            RETURNING {DELAYED_EVENT_COLUMNS}
+            "
+        ))
+        .bind(user_id)
+        .bind(delay_id)
+        .bind(lease_until_ts)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    async fn claim_due_delayed_events(
+        &self,
+        now_ts: i64,
+        lease_until_ts: i64,
+        limit: i64,
+    ) -> Result<Vec<DelayedEvent>, sqlx::Error> {
+        sqlx::query_as::<_, DelayedEvent>(&format!(
+            r"
+            UPDATE delayed_events
+            SET status = 'sending', scheduled_ts = $2, retry_count = retry_count + 1
+            WHERE id IN (
+                SELECT id FROM delayed_events
+                WHERE status IN ('pending', 'sending') AND scheduled_ts <= $1
+                ORDER BY scheduled_ts ASC
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING {DELAYED_EVENT_COLUMNS}
+            "
+        ))
+        .bind(now_ts)
+        .bind(lease_until_ts)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    async fn mark_delayed_event_sent(&self, id: i64, event_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE delayed_events SET status = 'sent', event_id = $2 WHERE id = $1")
+            .bind(id)
+            .bind(event_id)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn mark_delayed_event_failed(&self, id: i64, last_error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query("UPDATE delayed_events SET status = 'failed', last_error = $2 WHERE id = $1")
+            .bind(id)
+            .bind(last_error)
+            .execute(&*self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn cancel_delayed_state_events(
+        &self,
+        room_id: &str,
+        event_type: &str,
+        state_key: &str,
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r"
+            UPDATE delayed_events SET status = 'cancelled'
+            WHERE room_id = $1 AND event_type = $2 AND state_key = $3 AND status = 'pending'
+            ",
+        )
+        .bind(room_id)
+        .bind(event_type)
+        .bind(state_key)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+}
@@ -0,0 +1,131 @@
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+
+#[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
+pub struct EmailRetryEntry {
+    pub id: i64,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+    pub attempts: i32,
+    pub next_attempt_ts: i64,
+    pub last_error: Option<String>,
+    pub status: String,
+    pub created_ts: i64,
+}
+
+/// Emails waiting for another delivery attempt, shared by all workers.
+pub struct EmailRetryStorage {
+    pool: PgPool,
+}
+
+impl EmailRetryStorage {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Queues an email whose first attempt failed at `now_ts`.
+    pub async fn enqueue(
+        &self,
+        recipient: &str,
+        subject: &str,
+        body: &str,
+        next_attempt_ts: i64,
+        last_error: &str,
+        now_ts: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query_as::<_, (i64,)>(
+            r"
+            INSERT INTO email_retry_queue (recipient, subject, body, attempts, next_attempt_ts, last_error, status, created_ts)
+            VALUES ($1, $2, $3, 1, $4, $5, 'pending', $6)
+            RETURNING id
+            ",
+        )
+        .bind(recipient)
+        .bind(subject)
+        .bind(body)
+        .bind(next_attempt_ts)
+        .bind(last_error)
+        .bind(now_ts)
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.0)
+    }
+
+    /// Takes up to `limit` due emails and pushes their next attempt to
+    /// `lease_until_ts`, so concurrent workers don't send them twice. A
+    /// worker that dies mid-send leaves the row to be retried after the
+    /// lease.
+    pub async fn claim_due(
+        &self,
+        now_ts: i64,
+        lease_until_ts: i64,
+        limit: i64,
+    ) -> Result<Vec<EmailRetryEntry>, sqlx::Error> {
+        sqlx::query_as::<_, EmailRetryEntry>(
+            r"
+            UPDATE email_retry_queue
+            SET next_attempt_ts = $2
+            WHERE id IN (
+                SELECT id FROM email_retry_queue
+                WHERE status = 'pending' AND next_attempt_ts <= $1
+                ORDER BY next_attempt_ts ASC
+                LIMIT $3
+                FOR UPDATE SKIP LOCKED
+            )
+            RETURNING id, recipient, subject, body, attempts, next_attempt_ts, last_error, status, created_ts
+            ",
+        )
+        .bind(now_ts)
+        .bind(lease_until_ts)
+        .bind(limit)
+        .fetch_all(&self.pool)
+        .await
+    }
+
+    pub async fn mark_sent(&self, id: i64) -> Result<(), sqlx::Error> {
+        sqlx::query("DELETE FROM email_retry_queue WHERE id = $1").bind(id).execute(&self.pool).await?;
+        Ok(())
+    }
+
+    /// Records another failed attempt and when to try next.
+    pub async fn reschedule(&self, id: i64, next_attempt_ts: i64, last_error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r"
+            UPDATE email_retry_queue
+            SET attempts = attempts + 1, next_attempt_ts = $2, last_error = $3
+            WHERE id = $1
+            ",
+        )
+        .bind(id)
+        .bind(next_attempt_ts)
+        .bind(last_error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Gives up on an email after its last attempt or a permanent error.
+    pub async fn mark_failed(&self, id: i64, last_error: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r"
+            UPDATE email_retry_queue
+            SET attempts = attempts + 1, status = 'failed', last_error = $2
+            WHERE id = $1
+            ",
+        )
+        .bind(id)
+        .bind(last_error)
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn count_pending(&self) -> Result<i64, sqlx::Error> {
+        let row = sqlx::query_as::<_, (i64,)>("SELECT COUNT(*) FROM email_retry_queue WHERE status = 'pending'")
+            .fetch_one(&self.pool)
+            .await?;
+        Ok(row.0)
+    }
+}
@@ -15,7 +15,7 @@ impl EventStorage {
             SELECT event_id, room_id, sender as user_id, event_type, content, state_key,
                    COALESCE(depth, 0) as depth, origin_server_ts, origin_server_ts as processed_at,
                    COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
-            FROM events WHERE event_id = $1
+            FROM events WHERE purged_at IS NULL AND event_id = $1
             ",
         )
         .bind(event_id)
@@ -24,12 +24,20 @@ impl EventStorage {
         Ok(event)
     }
 
+    /// Purge a room's history before `timestamp`, keeping `m.room.create`.
+    ///
+    /// Rows are only tombstoned (`purged_at`); [`Self::vacuum_purged_events`]
+    /// deletes them, and cascades to their dependants, in small batches later.
     pub async fn delete_events_before(&self, room_id: &str, timestamp: i64) -> Result<u64, sqlx::Error> {
         let result = sqlx::query(
-            "DELETE FROM events WHERE room_id = $1 AND origin_server_ts < $2 AND event_type != 'm.room.create'",
+            r"
+            UPDATE events SET purged_at = $3
+            WHERE purged_at IS NULL AND room_id = $1 AND origin_server_ts < $2 AND event_type != 'm.room.create'
+            ",
         )
         .bind(room_id)
         .bind(timestamp)
+        .bind(current_timestamp_millis())
         .execute(&*self.pool)
         .await?;
         Ok(result.rows_affected())
@@ -38,7 +46,7 @@ impl EventStorage {
     pub async fn get_room_events(&self, room_id: &str, limit: i64) -> Result<Vec<RoomEvent>, sqlx::Error> {
         let events = sqlx::query_as(&format!(
             "SELECT {ROOM_EVENT_COLS}
-            FROM events WHERE room_id = $1
+            FROM events WHERE purged_at IS NULL AND room_id = $1
             ORDER BY origin_server_ts DESC, stream_ordering DESC NULLS LAST, event_id DESC
             LIMIT $2
             "
@@ -58,7 +66,7 @@ impl EventStorage {
     ) -> Result<Vec<RoomEvent>, sqlx::Error> {
         let events = sqlx::query_as(&format!(
             "SELECT {ROOM_EVENT_COLS}
-            FROM events WHERE room_id = $1 AND event_type = $2
+            FROM events WHERE purged_at IS NULL AND room_id = $1 AND event_type = $2
             ORDER BY origin_server_ts DESC
             LIMIT $3
             "
@@ -74,7 +82,7 @@ impl EventStorage {
     pub async fn get_sender_events(&self, user_id: &str, limit: i64) -> Result<Vec<RoomEvent>, sqlx::Error> {
         let events = sqlx::query_as(&format!(
             "SELECT {ROOM_EVENT_COLS}
-            FROM events WHERE COALESCE(user_id, sender) = $1
+            FROM events WHERE purged_at IS NULL AND COALESCE(user_id, sender) = $1
             ORDER BY origin_server_ts DESC
             LIMIT $2
             "
@@ -89,7 +97,7 @@ impl EventStorage {
     pub async fn get_room_message_count(&self, room_id: &str) -> Result<i64, sqlx::Error> {
         let count = sqlx::query_scalar::<_, i64>(
             r"
-            SELECT COALESCE(COUNT(*), 0) FROM events WHERE room_id = $1 AND event_type = 'm.room.message'
+            SELECT COALESCE(COUNT(*), 0) FROM events WHERE purged_at IS NULL AND room_id = $1 AND event_type = 'm.room.message'
             ",
         )
         .bind(room_id)
@@ -101,7 +109,7 @@ impl EventStorage {
     pub async fn get_total_message_count(&self) -> Result<i64, sqlx::Error> {
         let count = sqlx::query_scalar::<_, i64>(
             r"
-            SELECT COALESCE(COUNT(*), 0) FROM events WHERE event_type = 'm.room.message'
+            SELECT COALESCE(COUNT(*), 0) FROM events WHERE purged_at IS NULL AND event_type = 'm.room.message'
             ",
         )
         .fetch_one(&*self.pool)
@@ -115,7 +123,7 @@ impl EventStorage {
         let count = sqlx::query_scalar::<_, i64>(
             r"
             SELECT COALESCE(COUNT(*), 0) FROM events
-            WHERE event_type = 'm.room.message' AND origin_server_ts >= $1
+            WHERE purged_at IS NULL AND event_type = 'm.room.message' AND origin_server_ts >= $1
             ",
         )
         .bind(cutoff)
@@ -124,18 +132,41 @@ impl EventStorage {
         Ok(count)
     }
 
+    /// Tombstone every event of a room; see [`Self::delete_events_before`].
     pub async fn delete_room_events(&self, room_id: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
             r"
-            DELETE FROM events WHERE room_id = $1
+            UPDATE events SET purged_at = $2 WHERE purged_at IS NULL AND room_id = $1
             ",
         )
         .bind(room_id)
+        .bind(current_timestamp_millis())
         .execute(&*self.pool)
         .await?;
         Ok(())
     }
 
+    /// Physically delete up to `batch_size` purged events, oldest tombstones
+    /// first. Returns the number of rows removed; fewer than `batch_size`
+    /// means nothing is left to reclaim.
+    pub async fn vacuum_purged_events(&self, batch_size: i64) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
+            r"
+            DELETE FROM events
+            WHERE event_id IN (
+                SELECT event_id FROM events
+                WHERE purged_at IS NOT NULL
+                ORDER BY purged_at
+                LIMIT $1
+            )
+            ",
+        )
+        .bind(batch_size)
+        .execute(&*self.pool)
+        .await?;
+        Ok(result.rows_affected())
+    }
+
     // -----------------------------------------------------------------------
     // Power levels
     // -----------------------------------------------------------------------
@@ -143,7 +174,7 @@ impl EventStorage {
     pub async fn count_room_events(&self, room_id: &str) -> Result<i64, sqlx::Error> {
         let count = sqlx::query_scalar::<_, i64>(
             r"
-            SELECT COALESCE(COUNT(*), 0) FROM events WHERE room_id = $1
+            SELECT COALESCE(COUNT(*), 0) FROM events WHERE purged_at IS NULL AND room_id = $1
             ",
         )
         .bind(room_id)
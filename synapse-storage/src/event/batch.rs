@@ -11,7 +11,7 @@ impl EventStorage {
     ) -> Result<Vec<RoomEvent>, sqlx::Error> {
         let events = sqlx::query_as(&format!(
             "SELECT {ROOM_EVENT_COLS}
-            FROM events WHERE room_id = $1 AND origin_server_ts > $2
+            FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND origin_server_ts > $2
             ORDER BY origin_server_ts ASC
             LIMIT $3
             "
@@ -27,7 +27,7 @@ impl EventStorage {
     pub async fn get_events_since(&self, since: i64, limit: i64) -> Result<Vec<RoomEvent>, sqlx::Error> {
         let events = sqlx::query_as(&format!(
             "SELECT {ROOM_EVENT_COLS}
-            FROM events WHERE origin_server_ts > $1
+            FROM events WHERE purged_at IS NULL AND NOT soft_failed AND origin_server_ts > $1
             ORDER BY origin_server_ts ASC
             LIMIT $2
             "
@@ -89,6 +89,9 @@ impl EventStorage {
         }
     }
 
+    /// The newest `limit_per_room` events of each room after the since
+    /// position, newest first. Sync asks for one more than its timeline limit
+    /// so that a gap before the timeline shows up as `limited`.
     async fn get_room_events_batch_inner(
         &self,
         room_ids: &[String],
@@ -124,10 +127,10 @@ impl EventStorage {
                     redacts,
                     ROW_NUMBER() OVER (
                         PARTITION BY room_id
-                        ORDER BY stream_ordering ASC
+                        ORDER BY stream_ordering DESC
                     ) AS rn
                 FROM events
-                WHERE room_id = ANY(
+                WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY(
             ",
         );
         query.push_bind(room_ids);
@@ -142,29 +145,7 @@ impl EventStorage {
         }
 
         if let Some(filter) = filter {
-            if let Some(types) = Self::non_empty_filter_values(filter.types.as_deref()) {
-                query.push(" AND event_type = ANY(");
-                query.push_bind(types);
-                query.push(")");
-            }
-
-            if let Some(not_types) = Self::non_empty_filter_values(filter.not_types.as_deref()) {
-                query.push(" AND NOT (event_type = ANY(");
-                query.push_bind(not_types);
-                query.push("))");
-            }
-
-            if let Some(senders) = Self::non_empty_filter_values(filter.senders.as_deref()) {
-                query.push(" AND COALESCE(user_id, sender) = ANY(");
-                query.push_bind(senders);
-                query.push(")");
-            }
-
-            if let Some(not_senders) = Self::non_empty_filter_values(filter.not_senders.as_deref()) {
-                query.push(" AND NOT (COALESCE(user_id, sender) = ANY(");
-                query.push_bind(not_senders);
-                query.push("))");
-            }
+            Self::push_event_query_filter(&mut query, filter);
         }
 
         query.push(
@@ -176,7 +157,7 @@ impl EventStorage {
         query.push_bind(limit_per_room);
         query.push(
             r"
-            ORDER BY room_id, origin_server_ts DESC
+            ORDER BY room_id, stream_ordering DESC
             ",
         );
 
@@ -196,7 +177,7 @@ impl EventStorage {
                    COALESCE(depth, 0) as depth, COALESCE(origin_server_ts, 0) as origin_server_ts, COALESCE(origin_server_ts, 0) as processed_at,
                    COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE event_id = ANY($1)
+            WHERE purged_at IS NULL AND NOT soft_failed AND event_id = ANY($1)
             ",
         )
         .bind(event_ids)
@@ -226,7 +207,7 @@ impl EventStorage {
             r"
             SELECT 1
             FROM events
-            WHERE room_id = ANY($1)
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1)
               AND origin_server_ts > $2
             LIMIT 1
             ",
@@ -256,7 +237,7 @@ impl EventStorage {
                    COALESCE(origin_server_ts, 0) as processed_at,
                    COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = ANY($1)
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1)
             ORDER BY room_id, origin_server_ts DESC
             ",
         )
@@ -279,7 +260,7 @@ impl EventStorage {
             r"
             SELECT room_id, COUNT(*) as count
             FROM events
-            WHERE room_id = ANY($1) AND event_type = 'm.room.message'
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) AND event_type = 'm.room.message'
             GROUP BY room_id
             ",
         )
@@ -296,6 +277,37 @@ impl EventStorage {
         Ok(result)
     }
 
+    /// Events in any of `room_ids` past stream position `after`, oldest
+    /// first. Unlike the per-room batches this is a single cut across rooms,
+    /// so a caller can resume from the last returned position without gaps.
+    pub async fn get_room_events_after_stream(
+        &self,
+        room_ids: &[String],
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        if room_ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        sqlx::query_as(
+            r"
+            SELECT event_id, room_id, COALESCE(user_id, sender) as user_id, event_type, content, state_key,
+                   COALESCE(depth, 0) as depth, COALESCE(origin_server_ts, 0) as origin_server_ts, COALESCE(origin_server_ts, 0) as processed_at,
+                   COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
+            FROM events
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) AND stream_ordering > $2
+            ORDER BY stream_ordering ASC
+            LIMIT $3
+            ",
+        )
+        .bind(room_ids)
+        .bind(after)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
     pub async fn get_max_stream_ordering(&self) -> Result<i64, sqlx::Error> {
         let result: Option<(i64,)> =
             sqlx::query_as("SELECT COALESCE(MAX(stream_ordering), 0) FROM events").fetch_optional(&*self.pool).await?;
@@ -303,11 +315,12 @@ impl EventStorage {
     }
 
     pub async fn get_max_origin_server_ts_for_room(&self, room_id: &str) -> Result<i64, sqlx::Error> {
-        let result: Option<(i64,)> =
-            sqlx::query_as("SELECT COALESCE(MAX(origin_server_ts), 0) FROM events WHERE room_id = $1")
-                .bind(room_id)
-                .fetch_optional(&*self.pool)
-                .await?;
+        let result: Option<(i64,)> = sqlx::query_as(
+            "SELECT COALESCE(MAX(origin_server_ts), 0) FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1",
+        )
+        .bind(room_id)
+        .fetch_optional(&*self.pool)
+        .await?;
         Ok(result.map_or(0, |r| r.0))
     }
 
@@ -324,7 +337,7 @@ impl EventStorage {
                    COALESCE(not_before, 0) as not_before, status, reference_image,
                    COALESCE(NULLIF(NULLIF(BTRIM(origin), ''), 'undefined'), 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = $1
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
               AND stream_ordering > $2
               AND is_redacted = false
             ORDER BY stream_ordering ASC
@@ -354,7 +367,7 @@ impl EventStorage {
                    COALESCE(not_before, 0) as not_before, status, reference_image,
                    COALESCE(NULLIF(NULLIF(BTRIM(origin), ''), 'undefined'), 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = $1
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
               AND stream_ordering {op} $2
               AND stream_ordering <= $4
               AND is_redacted = false
@@ -378,7 +391,7 @@ impl EventStorage {
     /// Check whether a room has an `m.room.encryption` state event.
     pub async fn check_room_has_encryption(&self, room_id: &str) -> Result<bool, sqlx::Error> {
         let row: Option<(i32,)> = sqlx::query_as(
-            "SELECT 1 FROM events WHERE room_id = $1 AND event_type = 'm.room.encryption' AND state_key IS NOT NULL LIMIT 1",
+            "SELECT 1 FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND event_type = 'm.room.encryption' AND state_key IS NOT NULL LIMIT 1",
         )
         .bind(room_id)
         .fetch_optional(&*self.pool)
@@ -400,7 +413,7 @@ impl EventStorage {
                    COALESCE(not_before, 0) as not_before, status, reference_image,
                    COALESCE(NULLIF(NULLIF(BTRIM(origin), ''), 'undefined'), 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = $1 AND status = 'pending'
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND status = 'pending'
             ORDER BY origin_server_ts ASC
             LIMIT $2
             ",
@@ -413,11 +426,13 @@ impl EventStorage {
 
     /// Count events in a room by status (e.g. "processing", "failed").
     pub async fn count_room_events_by_status(&self, room_id: &str, status: &str) -> Result<i64, sqlx::Error> {
-        let result: Option<(i64,)> = sqlx::query_as("SELECT COUNT(*) FROM events WHERE room_id = $1 AND status = $2")
-            .bind(room_id)
-            .bind(status)
-            .fetch_optional(&*self.pool)
-            .await?;
+        let result: Option<(i64,)> = sqlx::query_as(
+            "SELECT COUNT(*) FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND status = $2",
+        )
+        .bind(room_id)
+        .bind(status)
+        .fetch_optional(&*self.pool)
+        .await?;
         Ok(result.map_or(0, |r| r.0))
     }
 
@@ -429,6 +444,39 @@ impl EventStorage {
         values.filter(|entries| !entries.is_empty())
     }
 
+    /// Append `filter` as `AND ...` clauses on the `events` columns.
+    pub(crate) fn push_event_query_filter<'a>(query: &mut QueryBuilder<'a, Postgres>, filter: &'a EventQueryFilter) {
+        if let Some(types) = Self::non_empty_filter_values(filter.types.as_deref()) {
+            query.push(" AND event_type = ANY(");
+            query.push_bind(types);
+            query.push(")");
+        }
+
+        if let Some(not_types) = Self::non_empty_filter_values(filter.not_types.as_deref()) {
+            query.push(" AND NOT (event_type = ANY(");
+            query.push_bind(not_types);
+            query.push("))");
+        }
+
+        if let Some(senders) = Self::non_empty_filter_values(filter.senders.as_deref()) {
+            query.push(" AND COALESCE(user_id, sender) = ANY(");
+            query.push_bind(senders);
+            query.push(")");
+        }
+
+        if let Some(not_senders) = Self::non_empty_filter_values(filter.not_senders.as_deref()) {
+            query.push(" AND NOT (COALESCE(user_id, sender) = ANY(");
+            query.push_bind(not_senders);
+            query.push("))");
+        }
+
+        match filter.contains_url {
+            Some(true) => query.push(" AND content->'url' IS NOT NULL"),
+            Some(false) => query.push(" AND content->'url' IS NULL"),
+            None => query,
+        };
+    }
+
     fn group_room_events(
         room_ids: &[String],
         events: Vec<RoomEvent>,
@@ -1,7 +1,9 @@
 //! Event creation methods for [`EventStorage`].
 
+use super::extremities;
 use super::models::{CreateEventParams, RoomEvent};
 use super::EventStorage;
+use crate::state_groups;
 
 impl EventStorage {
     pub async fn create_event(
@@ -19,7 +21,7 @@ impl EventStorage {
             ";
 
         if let Some(tx) = tx {
-            sqlx::query_as(query)
+            let event = sqlx::query_as(query)
                 .bind(&params.event_id)
                 .bind(&params.room_id)
                 .bind(&params.user_id)
@@ -30,9 +32,11 @@ impl EventStorage {
                 .bind(params.origin_server_ts)
                 .bind(params.redacts.as_deref())
                 .fetch_one(&mut **tx)
-                .await
+                .await?;
+            state_groups::record_state_event(tx, &event).await?;
+            Ok(event)
         } else {
-            sqlx::query_as(query)
+            let event = sqlx::query_as(query)
                 .bind(&params.event_id)
                 .bind(&params.room_id)
                 .bind(&params.user_id)
@@ -43,7 +47,9 @@ impl EventStorage {
                 .bind(params.origin_server_ts)
                 .bind(params.redacts.as_deref())
                 .fetch_one(&*self.pool)
-                .await
+                .await?;
+            self.record_state_group(&event).await?;
+            Ok(event)
         }
     }
 
@@ -106,6 +112,10 @@ impl EventStorage {
                 .execute(&mut **tx)
                 .await?;
             }
+            if depth > 0 {
+                extremities::update_forward_extremities(tx, &params.room_id, &params.event_id, prev_events).await?;
+            }
+            state_groups::record_state_event(tx, &event).await?;
             event
         } else {
             let event = sqlx::query_as(query)
@@ -138,12 +148,28 @@ impl EventStorage {
                 .execute(&*self.pool)
                 .await?;
             }
+            if depth > 0 {
+                let mut conn = self.pool.acquire().await?;
+                extremities::update_forward_extremities(&mut conn, &params.room_id, &params.event_id, prev_events)
+                    .await?;
+            }
+            self.record_state_group(&event).await?;
             event
         };
 
         Ok(event)
     }
 
+    /// Records the state group of an event inserted outside a transaction.
+    async fn record_state_group(&self, event: &RoomEvent) -> Result<(), sqlx::Error> {
+        if event.state_key.is_none() {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        state_groups::record_state_event(&mut tx, event).await?;
+        tx.commit().await
+    }
+
     pub async fn upsert_power_levels_event(
         &self,
         event_id: &str,
@@ -153,11 +179,16 @@ impl EventStorage {
         origin_server_ts: i64,
         sender: &str,
     ) -> Result<(), sqlx::Error> {
+        // Update-then-insert rather than `ON CONFLICT (event_id)`: a
+        // partitioned `events` table has no unique index on `event_id` alone.
         sqlx::query(
             r"
+            WITH updated AS (
+                UPDATE events SET content = $4 WHERE event_id = $1 RETURNING 1
+            )
             INSERT INTO events (event_id, room_id, user_id, event_type, content, state_key, origin_server_ts, sender, unsigned)
-            VALUES ($1, $2, $3, 'm.room.power_levels', $4, '', $5, $6, '{}'::jsonb)
-            ON CONFLICT (event_id) DO UPDATE SET content = $4
+            SELECT $1, $2, $3, 'm.room.power_levels', $4, '', $5, $6, '{}'::jsonb
+            WHERE NOT EXISTS (SELECT 1 FROM updated)
             ",
         )
         .bind(event_id)
@@ -178,7 +209,7 @@ impl EventStorage {
                    COALESCE(depth, 0) as depth, COALESCE(origin_server_ts, 0) as origin_server_ts, COALESCE(origin_server_ts, 0) as processed_at,
                    COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = $1 AND event_type = 'm.room.create'
+            WHERE purged_at IS NULL AND room_id = $1 AND event_type = 'm.room.create'
             ORDER BY origin_server_ts ASC
             LIMIT 1
             ",
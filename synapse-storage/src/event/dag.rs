@@ -4,7 +4,11 @@ use std::collections::{HashSet, VecDeque};
 
 use sqlx::Row;
 
-use super::EventStorage;
+use super::{DagDepthStats, DagEventSummary, DagMissingReference, EventStorage, ExportedPdu, RoomDagDiagnostics};
+
+/// Maximum entries in each list returned by
+/// [`EventStorage::get_room_dag_diagnostics`].
+pub const DAG_DIAGNOSTICS_LIMIT: i64 = 100;
 
 impl EventStorage {
     /// Batch-check which event IDs exist locally.  Returns the subset of
@@ -18,7 +22,7 @@ impl EventStorage {
         let existing: Vec<String> = sqlx::query_scalar(
             r"
             SELECT event_id FROM events
-            WHERE event_id = ANY($1)
+            WHERE purged_at IS NULL AND event_id = ANY($1)
             ",
         )
         .bind(event_ids)
@@ -102,7 +106,7 @@ impl EventStorage {
             SELECT event_id, room_id, sender, event_type, content, state_key,
                    origin_server_ts, depth, origin
             FROM events
-            WHERE room_id = $1 AND event_id = ANY($2)
+            WHERE purged_at IS NULL AND room_id = $1 AND event_id = ANY($2)
             ORDER BY origin_server_ts ASC
             LIMIT $3
             ",
@@ -131,24 +135,6 @@ impl EventStorage {
         Ok(events)
     }
 
-    pub async fn get_forward_extremities_count(&self, room_id: &str) -> Result<i64, sqlx::Error> {
-        let count: i64 = sqlx::query_scalar(
-            r"
-            SELECT COUNT(*) FROM events
-            WHERE room_id = $1
-            AND state_key IS NOT NULL
-            AND event_id NOT IN (
-                SELECT content->>'prev_event_id' FROM events
-                WHERE room_id = $1 AND content->>'prev_event_id' IS NOT NULL
-            )
-            ",
-        )
-        .bind(room_id)
-        .fetch_one(&*self.pool)
-        .await?;
-        Ok(count)
-    }
-
     /// Returns the `event_id`s of the most recent events in a room, ordered
     /// by `origin_server_ts DESC`.  Used to seed outbound `/backfill` requests
     /// — the caller passes these IDs as the `v=` query parameters so the
@@ -157,7 +143,7 @@ impl EventStorage {
         let rows: Vec<(String,)> = sqlx::query_as(
             r"
             SELECT event_id FROM events
-            WHERE room_id = $1
+            WHERE purged_at IS NULL AND room_id = $1
             ORDER BY origin_server_ts DESC NULLS LAST, stream_ordering DESC NULLS LAST, event_id DESC
             LIMIT $2
             ",
@@ -168,4 +154,208 @@ impl EventStorage {
         .await?;
         Ok(rows.into_iter().map(|(id,)| id).collect())
     }
+
+    /// Returns `event_id`'s `stream_ordering`, if it is stored in `room_id`.
+    pub async fn get_event_stream_ordering_in_room(
+        &self,
+        room_id: &str,
+        event_id: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let stream_ordering: Option<Option<i64>> = sqlx::query_scalar(
+            r"
+            SELECT stream_ordering FROM events
+            WHERE purged_at IS NULL AND room_id = $1 AND event_id = $2
+            ",
+        )
+        .bind(room_id)
+        .bind(event_id)
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(stream_ordering.flatten())
+    }
+
+    /// Loads a room's events with their graph, hash and signature columns in
+    /// `stream_ordering` order, for `stream_ordering` in `(after, until]`.
+    pub async fn get_room_pdus(
+        &self,
+        room_id: &str,
+        after: Option<i64>,
+        until: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ExportedPdu>, sqlx::Error> {
+        sqlx::query_as::<_, ExportedPdu>(
+            r"
+            SELECT event_id, room_id, sender, event_type, content, state_key,
+                   COALESCE(depth, 0) AS depth, origin_server_ts,
+                   COALESCE(NULLIF(NULLIF(BTRIM(origin), ''), 'undefined'), 'self') AS origin,
+                   prev_events, auth_events, signatures, hashes, unsigned, redacts, stream_ordering
+            FROM events
+            WHERE purged_at IS NULL AND room_id = $1 AND stream_ordering IS NOT NULL
+              AND ($2::BIGINT IS NULL OR stream_ordering > $2)
+              AND ($3::BIGINT IS NULL OR stream_ordering <= $3)
+            ORDER BY stream_ordering ASC
+            LIMIT $4
+            ",
+        )
+        .bind(room_id)
+        .bind(after)
+        .bind(until)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// The stored events of `room_id` among `event_ids`, together with their
+    /// full auth chains. Room v1/v2 `[event_id, hashes]` auth references are
+    /// followed as well as plain event IDs.
+    pub async fn get_auth_chain_pdus(
+        &self,
+        room_id: &str,
+        event_ids: &[String],
+    ) -> Result<Vec<ExportedPdu>, sqlx::Error> {
+        sqlx::query_as::<_, ExportedPdu>(
+            r"
+            WITH RECURSIVE chain(event_id) AS (
+                SELECT UNNEST($2::TEXT[])
+                UNION
+                SELECT CASE WHEN jsonb_typeof(a.value) = 'array' THEN a.value->>0 ELSE a.value #>> '{}' END
+                FROM chain c
+                JOIN events e ON e.event_id = c.event_id AND e.room_id = $1
+                CROSS JOIN LATERAL jsonb_array_elements(
+                    CASE WHEN jsonb_typeof(e.auth_events) = 'array' THEN e.auth_events ELSE '[]'::JSONB END
+                ) a
+            )
+            SELECT e.event_id, e.room_id, e.sender, e.event_type, e.content, e.state_key,
+                   COALESCE(e.depth, 0) AS depth, e.origin_server_ts,
+                   COALESCE(NULLIF(NULLIF(BTRIM(e.origin), ''), 'undefined'), 'self') AS origin,
+                   e.prev_events, e.auth_events, e.signatures, e.hashes, e.unsigned, e.redacts,
+                   COALESCE(e.stream_ordering, 0) AS stream_ordering
+            FROM events e
+            JOIN chain c ON c.event_id = e.event_id
+            WHERE e.room_id = $1 AND e.purged_at IS NULL
+            ",
+        )
+        .bind(room_id)
+        .bind(event_ids)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Inspects the stored DAG of `room_id`: forward extremities, references
+    /// to events that are not stored, and depth statistics. With `depth`,
+    /// only the `depth` levels below the deepest event are inspected. Each
+    /// list holds at most [`DAG_DIAGNOSTICS_LIMIT`] entries, deepest first.
+    pub async fn get_room_dag_diagnostics(
+        &self,
+        room_id: &str,
+        depth: Option<i64>,
+    ) -> Result<RoomDagDiagnostics, sqlx::Error> {
+        let stats = sqlx::query_as::<_, DagDepthStats>(
+            r"
+            SELECT COUNT(*) AS event_count,
+                   COUNT(*) FILTER (WHERE COALESCE(depth, 0) <= 0) AS events_without_depth,
+                   MIN(depth) FILTER (WHERE depth > 0) AS min_depth,
+                   MAX(depth) FILTER (WHERE depth > 0) AS max_depth
+            FROM events
+            WHERE purged_at IS NULL AND room_id = $1
+            ",
+        )
+        .bind(room_id)
+        .fetch_one(&*self.pool)
+        .await?;
+        let min_depth = depth.zip(stats.max_depth).map(|(depth, max_depth)| (max_depth - depth).max(1));
+
+        let forward_extremities = sqlx::query_as::<_, DagEventSummary>(
+            r"
+            SELECT e.event_id, e.event_type, e.sender, e.depth, e.origin_server_ts
+            FROM events e
+            WHERE e.purged_at IS NULL AND e.room_id = $1 AND e.depth > 0
+              AND ($2::BIGINT IS NULL OR e.depth >= $2)
+              AND NOT EXISTS (
+                  SELECT 1 FROM event_edges ed
+                  JOIN events c ON c.event_id = ed.event_id
+                  WHERE ed.prev_event_id = e.event_id AND c.room_id = $1 AND c.purged_at IS NULL
+              )
+            ORDER BY e.depth DESC, e.origin_server_ts DESC
+            LIMIT $3
+            ",
+        )
+        .bind(room_id)
+        .bind(min_depth)
+        .bind(DAG_DIAGNOSTICS_LIMIT)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        let dangling_prev_events = sqlx::query_as::<_, DagMissingReference>(
+            r"
+            SELECT ed.event_id, ed.prev_event_id AS missing_event_id
+            FROM event_edges ed
+            JOIN events e ON e.event_id = ed.event_id
+            WHERE e.purged_at IS NULL AND e.room_id = $1
+              AND ($2::BIGINT IS NULL OR COALESCE(e.depth, 0) >= $2)
+              AND NOT EXISTS (
+                  SELECT 1 FROM events p WHERE p.event_id = ed.prev_event_id AND p.purged_at IS NULL
+              )
+            ORDER BY COALESCE(e.depth, 0) DESC, ed.event_id
+            LIMIT $3
+            ",
+        )
+        .bind(room_id)
+        .bind(min_depth)
+        .bind(DAG_DIAGNOSTICS_LIMIT)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        // Room v1/v2 store `auth_events` as `[event_id, hashes]` pairs, later
+        // versions as plain event IDs.
+        let missing_auth_events = sqlx::query_as::<_, DagMissingReference>(
+            r"
+            SELECT e.event_id, a.auth_event_id AS missing_event_id
+            FROM events e
+            CROSS JOIN LATERAL (
+                SELECT CASE jsonb_typeof(x) WHEN 'array' THEN x ->> 0 ELSE x #>> '{}' END AS auth_event_id
+                FROM jsonb_array_elements(
+                    CASE WHEN jsonb_typeof(e.auth_events) = 'array' THEN e.auth_events ELSE '[]'::jsonb END
+                ) AS x
+            ) a
+            WHERE e.purged_at IS NULL AND e.room_id = $1
+              AND ($2::BIGINT IS NULL OR COALESCE(e.depth, 0) >= $2)
+              AND NOT EXISTS (
+                  SELECT 1 FROM events m WHERE m.event_id = a.auth_event_id AND m.purged_at IS NULL
+              )
+            ORDER BY COALESCE(e.depth, 0) DESC, e.event_id
+            LIMIT $3
+            ",
+        )
+        .bind(room_id)
+        .bind(min_depth)
+        .bind(DAG_DIAGNOSTICS_LIMIT)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        let depth_violations: i64 = sqlx::query_scalar(
+            r"
+            SELECT COUNT(*)
+            FROM event_edges ed
+            JOIN events c ON c.event_id = ed.event_id
+            JOIN events p ON p.event_id = ed.prev_event_id
+            WHERE c.purged_at IS NULL AND p.purged_at IS NULL AND c.room_id = $1 AND c.depth > 0
+              AND ($2::BIGINT IS NULL OR c.depth >= $2)
+              AND c.depth <= COALESCE(p.depth, 0)
+            ",
+        )
+        .bind(room_id)
+        .bind(min_depth)
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(RoomDagDiagnostics {
+            min_depth,
+            depth: stats,
+            depth_violations,
+            forward_extremities,
+            dangling_prev_events,
+            missing_auth_events,
+        })
+    }
 }
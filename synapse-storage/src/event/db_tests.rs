@@ -227,6 +227,87 @@ async fn test_delete_room_events() {
     assert_eq!(count, 0);
 }
 
+#[tokio::test]
+async fn test_purged_events_are_hidden_until_vacuumed() {
+    let pool = test_pool().await;
+    let storage = EventStorage::new(&pool, test_server_name());
+    let room_id = format!("!evt_purge_{}:example.com", uuid::Uuid::new_v4());
+    let user_id = "@purger:example.com";
+    let event_id = format!("$purge_{}:example.com", uuid::Uuid::new_v4());
+
+    ensure_test_room(&pool, &room_id).await;
+    ensure_test_user(&pool, user_id).await;
+
+    let params = CreateEventParams {
+        event_id: event_id.clone(),
+        room_id: room_id.clone(),
+        user_id: user_id.to_string(),
+        event_type: "m.room.message".to_string(),
+        content: serde_json::json!({"body": "purge me"}),
+        state_key: None,
+        origin_server_ts: current_timestamp_millis() - 1000,
+        redacts: None,
+    };
+    storage.create_event(params, None).await.unwrap();
+
+    let purged = storage.delete_events_before(&room_id, current_timestamp_millis()).await.unwrap();
+    assert_eq!(purged, 1);
+    assert!(storage.get_event(&event_id).await.unwrap().is_none());
+
+    let row_exists = || async {
+        sqlx::query_scalar::<_, bool>("SELECT EXISTS (SELECT 1 FROM events WHERE event_id = $1)")
+            .bind(&event_id)
+            .fetch_one(&*pool)
+            .await
+            .unwrap()
+    };
+    assert!(row_exists().await, "purge only tombstones the row");
+
+    while row_exists().await {
+        assert!(storage.vacuum_purged_events(1000).await.unwrap() > 0);
+    }
+}
+
+#[tokio::test]
+async fn test_get_room_events_after_stream_is_ascending_across_rooms() {
+    let pool = test_pool().await;
+    let storage = EventStorage::new(&pool, test_server_name());
+    let rooms: Vec<String> = (0..2).map(|i| format!("!evt_after_{i}_{}:example.com", uuid::Uuid::new_v4())).collect();
+    let user_id = "@streamer:example.com";
+    ensure_test_user(&pool, user_id).await;
+
+    let start = storage.get_max_stream_ordering().await.unwrap();
+    let mut created = Vec::new();
+    for (i, room_id) in rooms.iter().cycle().take(4).enumerate() {
+        ensure_test_room(&pool, room_id).await;
+        let event = storage
+            .create_event(
+                CreateEventParams {
+                    event_id: format!("$after_{i}_{}:example.com", uuid::Uuid::new_v4()),
+                    room_id: room_id.clone(),
+                    user_id: user_id.to_string(),
+                    event_type: "m.room.message".to_string(),
+                    content: serde_json::json!({"body": i}),
+                    state_key: None,
+                    origin_server_ts: current_timestamp_millis(),
+                    redacts: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+        created.push(event.event_id);
+    }
+
+    let first_page = storage.get_room_events_after_stream(&rooms, start, 3).await.unwrap();
+    let ids: Vec<_> = first_page.iter().map(|e| e.event_id.clone()).collect();
+    assert_eq!(ids, created[..3]);
+
+    let resume_from = first_page.last().and_then(|e| e.stream_ordering).unwrap();
+    let rest = storage.get_room_events_after_stream(&rooms, resume_from, 3).await.unwrap();
+    assert_eq!(rest.iter().map(|e| e.event_id.clone()).collect::<Vec<_>>(), created[3..]);
+}
+
 #[tokio::test]
 async fn test_get_room_message_count() {
     let pool = test_pool().await;
@@ -337,6 +418,45 @@ async fn test_redact_event_content() {
     let _ = storage.delete_room_events(&room_id).await;
 }
 
+#[tokio::test]
+async fn test_redact_event_content_follows_room_version() {
+    let pool = test_pool().await;
+    let storage = EventStorage::new(&pool, test_server_name());
+    let room_id = format!("!redact_v_{}:example.com", uuid::Uuid::new_v4());
+    let user_id = "@redactor:example.com";
+    ensure_test_room(&pool, &room_id).await;
+    ensure_test_user(&pool, user_id).await;
+
+    let mut redacted = Vec::new();
+    for room_version in ["10", "11"] {
+        sqlx::query("UPDATE rooms SET room_version = $1 WHERE room_id = $2")
+            .bind(room_version)
+            .bind(&room_id)
+            .execute(&*pool)
+            .await
+            .unwrap();
+        let event_id = format!("$redact_v{room_version}_{}:example.com", uuid::Uuid::new_v4());
+        let params = CreateEventParams {
+            event_id: event_id.clone(),
+            room_id: room_id.clone(),
+            user_id: user_id.to_string(),
+            event_type: "m.room.power_levels".to_string(),
+            content: serde_json::json!({"ban": 50, "invite": 0, "notifications": {"room": 50}}),
+            state_key: Some(String::new()),
+            origin_server_ts: current_timestamp_millis(),
+            redacts: None,
+        };
+        storage.create_event(params, None).await.unwrap();
+        storage.redact_event_content(&event_id, Some(user_id)).await.unwrap();
+        redacted.push(storage.get_event(&event_id).await.unwrap().expect("event should exist").content);
+    }
+
+    assert_eq!(redacted[0], serde_json::json!({"ban": 50}));
+    assert_eq!(redacted[1], serde_json::json!({"ban": 50, "invite": 0}));
+
+    let _ = storage.delete_room_events(&room_id).await;
+}
+
 #[tokio::test]
 async fn test_save_and_get_event_signatures() {
     let pool = test_pool().await;
@@ -572,6 +692,40 @@ async fn test_create_event_with_graph_in_transaction() {
     let _ = storage.delete_room_events(&room_id).await;
 }
 
+#[tokio::test]
+async fn test_get_auth_chain_pdus_follows_auth_events() {
+    let pool = test_pool().await;
+    let storage = EventStorage::new(&pool, test_server_name());
+    let room_id = format!("!authchain_{}:example.com", uuid::Uuid::new_v4());
+    let user_id = "@chain:example.com";
+    ensure_test_room(&pool, &room_id).await;
+    ensure_test_user(&pool, user_id).await;
+
+    let mut ids = Vec::new();
+    for (depth, event_type) in [(1, "m.room.create"), (2, "m.room.member"), (3, "m.room.name")] {
+        let event_id = format!("$authchain_{depth}_{}:example.com", uuid::Uuid::new_v4());
+        let params = CreateEventParams {
+            event_id: event_id.clone(),
+            room_id: room_id.clone(),
+            user_id: user_id.to_string(),
+            event_type: event_type.to_string(),
+            content: serde_json::json!({}),
+            state_key: Some(String::new()),
+            origin_server_ts: current_timestamp_millis(),
+            redacts: None,
+        };
+        storage.create_event_with_graph(params, &[], &ids, depth, None).await.unwrap();
+        ids.push(event_id);
+    }
+
+    let chain = storage.get_auth_chain_pdus(&room_id, &ids[2..]).await.unwrap();
+    let mut chain_ids: Vec<String> = chain.into_iter().map(|pdu| pdu.event_id).collect();
+    chain_ids.sort_by_key(|id| ids.iter().position(|i| i == id));
+    assert_eq!(chain_ids, ids);
+
+    let _ = storage.delete_room_events(&room_id).await;
+}
+
 #[tokio::test]
 async fn test_update_event_signatures_and_hashes() {
     let pool = test_pool().await;
@@ -97,6 +97,37 @@ impl EventStorage {
         .await
     }
 
+    /// Whether any live ephemeral event in `room_ids` was written after
+    /// `since_ts`.
+    pub async fn has_ephemeral_events_since(
+        &self,
+        room_ids: &[String],
+        since_ts: i64,
+        now: i64,
+    ) -> Result<bool, sqlx::Error> {
+        if room_ids.is_empty() {
+            return Ok(false);
+        }
+
+        let row = sqlx::query_scalar::<_, i32>(
+            r"
+            SELECT 1
+            FROM room_ephemeral
+            WHERE room_id = ANY($1)
+              AND created_ts > $2
+              AND (expires_at IS NULL OR expires_at > $3)
+            LIMIT 1
+            ",
+        )
+        .bind(room_ids)
+        .bind(since_ts)
+        .bind(now)
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(row.is_some())
+    }
+
     pub async fn get_ephemeral_events_batch(
         &self,
         room_ids: &[String],
@@ -0,0 +1,134 @@
+//! Forward extremity tracking for [`EventStorage`].
+//!
+//! `event_forward_extremities` holds, per room, the events that no stored
+//! event lists in its `prev_events`. Each event stored with graph data
+//! replaces its prev events in the set, and locally created events do the
+//! same once their `prev_events` are chosen for federation.
+
+use sqlx::PgConnection;
+
+use super::EventStorage;
+
+/// Moves the room's forward extremities past `event_id`: its prev events stop
+/// being extremities, and the event becomes one unless a stored event
+/// already points at it (as with backfilled history).
+pub(crate) async fn update_forward_extremities(
+    conn: &mut PgConnection,
+    room_id: &str,
+    event_id: &str,
+    prev_events: &[String],
+) -> Result<(), sqlx::Error> {
+    if !prev_events.is_empty() {
+        sqlx::query("DELETE FROM event_forward_extremities WHERE room_id = $1 AND event_id = ANY($2)")
+            .bind(room_id)
+            .bind(prev_events)
+            .execute(&mut *conn)
+            .await?;
+    }
+    sqlx::query(
+        r"
+        INSERT INTO event_forward_extremities (room_id, event_id)
+        SELECT $1, $2
+        WHERE NOT EXISTS (SELECT 1 FROM event_edges WHERE prev_event_id = $2)
+        ON CONFLICT DO NOTHING
+        ",
+    )
+    .bind(room_id)
+    .bind(event_id)
+    .execute(&mut *conn)
+    .await?;
+    Ok(())
+}
+
+impl EventStorage {
+    /// Returns up to `limit` forward extremities of a room, deepest first.
+    pub async fn get_forward_extremities(&self, room_id: &str, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        sqlx::query_scalar(
+            r"
+            SELECT fe.event_id
+            FROM event_forward_extremities fe
+            JOIN events e ON e.event_id = fe.event_id
+            WHERE fe.room_id = $1 AND e.purged_at IS NULL
+            ORDER BY COALESCE(e.depth, 0) DESC, e.origin_server_ts DESC, fe.event_id
+            LIMIT $2
+            ",
+        )
+        .bind(room_id)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    pub async fn get_forward_extremities_count(&self, room_id: &str) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar("SELECT COUNT(*) FROM event_forward_extremities WHERE room_id = $1")
+            .bind(room_id)
+            .fetch_one(&*self.pool)
+            .await
+    }
+
+    /// Rooms with at least `min_count` forward extremities, most first.
+    pub async fn get_rooms_by_forward_extremities(
+        &self,
+        min_count: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        sqlx::query_as(
+            r"
+            SELECT room_id, COUNT(*) AS extremities
+            FROM event_forward_extremities
+            GROUP BY room_id
+            HAVING COUNT(*) >= $1
+            ORDER BY extremities DESC, room_id
+            LIMIT $2
+            ",
+        )
+        .bind(min_count)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Records the `prev_events` chosen for a locally created event when it
+    /// is sent over federation, and places it in the DAG one level below
+    /// the deepest of them. Returns the event's depth.
+    pub async fn record_local_event_graph(
+        &self,
+        room_id: &str,
+        event_id: &str,
+        prev_events: &[String],
+    ) -> Result<i64, sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        let depth: i64 = sqlx::query_scalar(
+            r"
+            UPDATE events
+            SET prev_events = to_jsonb($2::text[]),
+                depth = GREATEST(
+                    COALESCE(depth, 0),
+                    COALESCE((SELECT MAX(p.depth) FROM events p WHERE p.event_id = ANY($2)), 0) + 1
+                )
+            WHERE event_id = $1
+            RETURNING depth
+            ",
+        )
+        .bind(event_id)
+        .bind(prev_events)
+        .fetch_one(&mut *tx)
+        .await?;
+        if !prev_events.is_empty() {
+            sqlx::query(
+                r"
+                INSERT INTO event_edges (event_id, prev_event_id, is_state)
+                SELECT $1, unnest($2::text[]), false
+                ON CONFLICT DO NOTHING
+                ",
+            )
+            .bind(event_id)
+            .bind(prev_events)
+            .execute(&mut *tx)
+            .await?;
+        }
+        update_forward_extremities(&mut tx, room_id, event_id, prev_events).await?;
+        tx.commit().await?;
+        Ok(depth)
+    }
+}
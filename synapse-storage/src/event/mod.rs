@@ -3,16 +3,22 @@ pub mod batch;
 pub(crate) mod create;
 pub(crate) mod dag;
 pub(crate) mod ephemeral;
+pub(crate) mod extremities;
 pub(crate) mod models;
 pub(crate) mod pagination;
+pub mod partitioning;
 pub mod reader;
 pub(crate) mod redaction;
+pub(crate) mod rejected;
+pub(crate) mod relations;
 pub(crate) mod search;
 pub(crate) mod signature;
+pub(crate) mod soft_fail;
 pub mod state;
 pub(crate) mod unread;
 pub(crate) mod writer;
 
+pub use dag::DAG_DIAGNOSTICS_LIMIT;
 pub use models::*;
 pub use reader::EventReader;
 pub use writer::EventWriter;
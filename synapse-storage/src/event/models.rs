@@ -103,6 +103,9 @@ pub struct EventQueryFilter {
     pub not_types: Option<Vec<String>>,
     pub senders: Option<Vec<String>>,
     pub not_senders: Option<Vec<String>>,
+    /// `Some(true)` keeps only events whose content has a `url`,
+    /// `Some(false)` only those without.
+    pub contains_url: Option<bool>,
 }
 
 // ---------------------------------------------------------------------------
@@ -120,6 +123,267 @@ pub struct EventSignature {
     pub created_ts: Option<i64>,
 }
 
+/// An event row with the federation fields needed to rebuild its PDU, as
+/// returned by the admin room export.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct ExportedPdu {
+    pub event_id: String,
+    pub room_id: String,
+    pub sender: String,
+    pub event_type: String,
+    pub content: serde_json::Value,
+    pub state_key: Option<String>,
+    pub depth: i64,
+    pub origin_server_ts: i64,
+    pub origin: String,
+    pub prev_events: Option<serde_json::Value>,
+    pub auth_events: Option<serde_json::Value>,
+    pub signatures: Option<serde_json::Value>,
+    pub hashes: Option<serde_json::Value>,
+    pub unsigned: Option<serde_json::Value>,
+    pub redacts: Option<String>,
+    pub stream_ordering: i64,
+}
+
+impl ExportedPdu {
+    /// The event in federation PDU format. Locally created events store
+    /// `origin` as `self`, which is replaced by `server_name`. Missing graph
+    /// and signature columns are emitted empty rather than omitted, so gaps
+    /// are visible to verification tooling.
+    pub fn to_pdu(&self, server_name: &str) -> serde_json::Value {
+        let origin = if self.origin == "self" { server_name } else { self.origin.as_str() };
+        let mut pdu = serde_json::json!({
+            "event_id": self.event_id,
+            "room_id": self.room_id,
+            "sender": self.sender,
+            "type": self.event_type,
+            "content": self.content,
+            "depth": self.depth,
+            "origin": origin,
+            "origin_server_ts": self.origin_server_ts,
+            "prev_events": self.prev_events.clone().unwrap_or_else(|| serde_json::json!([])),
+            "auth_events": self.auth_events.clone().unwrap_or_else(|| serde_json::json!([])),
+            "signatures": self.signatures.clone().unwrap_or_else(|| serde_json::json!({})),
+            "hashes": self.hashes.clone().unwrap_or_else(|| serde_json::json!({})),
+        });
+        if let Some(state_key) = &self.state_key {
+            pdu["state_key"] = serde_json::Value::String(state_key.clone());
+        }
+        if let Some(redacts) = &self.redacts {
+            pdu["redacts"] = serde_json::Value::String(redacts.clone());
+        }
+        if let Some(unsigned) = self.unsigned.as_ref().filter(|u| u.as_object().is_some_and(|o| !o.is_empty())) {
+            pdu["unsigned"] = unsigned.clone();
+        }
+        pdu
+    }
+}
+
+/// An event with no children in the stored DAG.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DagEventSummary {
+    pub event_id: String,
+    pub event_type: String,
+    pub sender: String,
+    pub depth: i64,
+    pub origin_server_ts: i64,
+}
+
+/// A `prev_events` or `auth_events` reference from `event_id` to an event
+/// that is not stored.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct DagMissingReference {
+    pub event_id: String,
+    pub missing_event_id: String,
+}
+
+#[derive(Debug, Clone, Default, sqlx::FromRow)]
+pub struct DagDepthStats {
+    pub event_count: i64,
+    /// Events stored without a DAG position (locally created events that
+    /// predate graph tracking have depth 0).
+    pub events_without_depth: i64,
+    pub min_depth: Option<i64>,
+    pub max_depth: Option<i64>,
+}
+
+/// DAG health of a room, for diagnosing rooms stuck on graph gaps. The
+/// lists are limited to the events at or above `min_depth`.
+#[derive(Debug, Clone, Default)]
+pub struct RoomDagDiagnostics {
+    pub min_depth: Option<i64>,
+    pub depth: DagDepthStats,
+    /// Edges in the window whose event is not deeper than its prev event.
+    pub depth_violations: i64,
+    pub forward_extremities: Vec<DagEventSummary>,
+    pub dangling_prev_events: Vec<DagMissingReference>,
+    pub missing_auth_events: Vec<DagMissingReference>,
+}
+
+/// Why a DAG-valid event was soft-failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SoftFailKind {
+    /// Not allowed by the room's current state (e.g. a banned sender).
+    Auth,
+    /// Flagged by a spam checker and quarantined.
+    Spam,
+}
+
+impl SoftFailKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Auth => "auth",
+            Self::Spam => "spam",
+        }
+    }
+}
+
+impl std::str::FromStr for SoftFailKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auth" => Ok(Self::Auth),
+            "spam" => Ok(Self::Spam),
+            _ => Err(format!("Invalid soft-fail kind: {s}")),
+        }
+    }
+}
+
+/// A stored event that is hidden from clients and left out of the room's
+/// current state, but kept in the DAG for state resolution.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct SoftFailedEvent {
+    pub event_id: String,
+    pub event_type: String,
+    pub sender: String,
+    pub state_key: Option<String>,
+    pub depth: i64,
+    pub origin_server_ts: i64,
+    pub stream_ordering: i64,
+    pub soft_fail_kind: String,
+    pub soft_fail_reason: Option<String>,
+}
+
+/// Why an inbound PDU was rejected instead of being persisted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    /// Over the spec's size limits for the whole PDU or one of its fields.
+    TooLarge,
+    /// The content hash does not match the event.
+    ContentHash,
+    /// Missing or invalid signature from the sender's server.
+    Signature,
+    /// Not a well-formed PDU, or not attributable to the sending origin.
+    Malformed,
+    /// The origin may not send events into the room.
+    Auth,
+}
+
+impl RejectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::TooLarge => "too_large",
+            Self::ContentHash => "content_hash",
+            Self::Signature => "signature",
+            Self::Malformed => "malformed",
+            Self::Auth => "auth",
+        }
+    }
+}
+
+impl std::str::FromStr for RejectionReason {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "too_large" => Ok(Self::TooLarge),
+            "content_hash" => Ok(Self::ContentHash),
+            "signature" => Ok(Self::Signature),
+            "malformed" => Ok(Self::Malformed),
+            "auth" => Ok(Self::Auth),
+            _ => Err(format!("Invalid rejection reason: {s}")),
+        }
+    }
+}
+
+/// An inbound PDU to record as rejected. Fields other than the event ID and
+/// origin are taken from the PDU as-is, so any of them may be missing.
+#[derive(Debug, Clone)]
+pub struct RejectedEventParams {
+    pub event_id: String,
+    pub origin: String,
+    pub room_id: Option<String>,
+    pub sender: Option<String>,
+    pub event_type: Option<String>,
+    pub reason: RejectionReason,
+    pub detail: String,
+}
+
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct RejectedEvent {
+    pub id: i64,
+    pub event_id: String,
+    pub origin: String,
+    pub room_id: Option<String>,
+    pub sender: Option<String>,
+    pub event_type: Option<String>,
+    pub reason: String,
+    pub detail: String,
+    pub rejected_ts: i64,
+}
+
+/// Filters for listing rejected PDUs; unset fields match everything.
+#[derive(Debug, Clone, Default)]
+pub struct RejectedEventFilter {
+    pub room_id: Option<String>,
+    pub origin: Option<String>,
+    pub reason: Option<RejectionReason>,
+    /// Only rows with a smaller `id` (the previous page's `next_batch`).
+    pub before: Option<i64>,
+}
+
+/// One page of the events relating to a parent event, in stream order.
+/// Positions follow [`EventStorage::get_room_events_page`]; an unset `from`
+/// starts at the newest (`backwards`) or oldest relation.
+#[derive(Debug, Clone, Default)]
+pub struct EventRelationsQuery {
+    pub rel_type: Option<String>,
+    pub event_type: Option<String>,
+    pub from: Option<i64>,
+    pub to: Option<i64>,
+    pub limit: i64,
+    pub backwards: bool,
+}
+
+/// Relations of one event summarised for `unsigned.m.relations`.
+#[derive(Debug, Clone, Default)]
+pub struct RelationAggregation {
+    /// Number of `m.thread` replies.
+    pub thread_count: i64,
+    /// Most recent `m.thread` reply.
+    pub thread_latest_event: Option<RoomEvent>,
+    /// Threads the requesting user replied in.
+    pub thread_participated: bool,
+    /// Most recent `m.replace` sent by the sender of the parent.
+    pub replacement: Option<RoomEvent>,
+    /// `m.reference` event IDs, oldest first.
+    pub references: Vec<String>,
+    /// `m.annotation` groups, most used first.
+    pub annotations: Vec<AnnotationGroup>,
+}
+
+/// Annotations of one event type and key, e.g. every 👍 `m.reaction`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AnnotationGroup {
+    pub event_type: String,
+    pub key: String,
+    /// Distinct senders of the annotation.
+    pub count: i64,
+    /// Whether the requesting user is one of them.
+    pub current_user_participated: bool,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub struct EventReportId {
     pub id: i64,
@@ -3,8 +3,62 @@
 use super::models::{EventQueryFilter, RoomEvent};
 use super::EventStorage;
 use super::ROOM_EVENT_COLS;
+use sqlx::{Postgres, QueryBuilder};
 
 impl EventStorage {
+    /// One page of a room's timeline in stream order, for `/messages`.
+    ///
+    /// Positions are stream orderings with the events at or below a position
+    /// lying before it: `dir=b` returns events at or below `from` and above
+    /// `to`, newest first; `dir=f` returns events above `from` and at or
+    /// below `to`, oldest first.
+    pub async fn get_room_events_page(
+        &self,
+        room_id: &str,
+        from: i64,
+        to: Option<i64>,
+        limit: i64,
+        direction: &str,
+        filter: Option<&EventQueryFilter>,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        let backwards = direction != "f";
+        let mut query = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {ROOM_EVENT_COLS} FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = "
+        ));
+        query.push_bind(room_id);
+        query.push(if backwards { " AND stream_ordering <= " } else { " AND stream_ordering > " });
+        query.push_bind(from);
+        if let Some(to) = to {
+            query.push(if backwards { " AND stream_ordering > " } else { " AND stream_ordering <= " });
+            query.push_bind(to);
+        }
+        if let Some(filter) = filter {
+            Self::push_event_query_filter(&mut query, filter);
+        }
+        query.push(if backwards {
+            " ORDER BY stream_ordering DESC LIMIT "
+        } else {
+            " ORDER BY stream_ordering ASC LIMIT "
+        });
+        query.push_bind(limit);
+
+        query.build_query_as().fetch_all(&*self.pool).await
+    }
+
+    /// Highest stream ordering in the room, restricted to events sent before
+    /// `before_ts` when given. Resolves timestamp pagination tokens and the
+    /// implicit `from` of a `/messages` request.
+    pub async fn get_room_stream_position(&self, room_id: &str, before_ts: Option<i64>) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar(
+            "SELECT COALESCE(MAX(stream_ordering), 0) FROM events \
+             WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND ($2::BIGINT IS NULL OR origin_server_ts < $2)",
+        )
+        .bind(room_id)
+        .bind(before_ts)
+        .fetch_one(&*self.pool)
+        .await
+    }
+
     pub async fn get_room_events_paginated(
         &self,
         room_id: &str,
@@ -17,7 +71,7 @@ impl EventStorage {
                 sqlx::query_as(&format!(
                     "SELECT {ROOM_EVENT_COLS}
                     FROM events
-                    WHERE room_id = $1 AND origin_server_ts > $2
+                    WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND origin_server_ts > $2
                     ORDER BY origin_server_ts ASC
                     LIMIT $3
                     "
@@ -32,7 +86,7 @@ impl EventStorage {
                 sqlx::query_as(&format!(
                     "SELECT {ROOM_EVENT_COLS}
                     FROM events
-                    WHERE room_id = $1
+                    WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
                     ORDER BY origin_server_ts ASC
                     LIMIT $2
                     "
@@ -46,7 +100,7 @@ impl EventStorage {
                 sqlx::query_as(&format!(
                     "SELECT {ROOM_EVENT_COLS}
                     FROM events
-                    WHERE room_id = $1 AND origin_server_ts < $2
+                    WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND origin_server_ts < $2
                     ORDER BY origin_server_ts DESC
                     LIMIT $3
                     "
@@ -61,7 +115,7 @@ impl EventStorage {
                 sqlx::query_as(&format!(
                     "SELECT {ROOM_EVENT_COLS}
                     FROM events
-                    WHERE room_id = $1
+                    WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
                     ORDER BY origin_server_ts DESC
                     LIMIT $2
                     "
@@ -88,7 +142,7 @@ impl EventStorage {
             r"
             SELECT event_id, origin_server_ts
             FROM events
-            WHERE room_id = $1
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
               AND origin_server_ts IS NOT NULL
               AND origin_server_ts <= $2
             ORDER BY origin_server_ts DESC
@@ -106,7 +160,7 @@ impl EventStorage {
                 r"
                 SELECT content
                 FROM events
-                WHERE event_id = $1
+                WHERE purged_at IS NULL AND NOT soft_failed AND event_id = $1
                 ",
             )
             .bind(&event_id)
@@ -141,7 +195,7 @@ impl EventStorage {
                 r"
                 SELECT event_id, origin_server_ts
                 FROM events
-                WHERE room_id = $1
+                WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
                   AND origin_server_ts IS NOT NULL
                   AND origin_server_ts >= $2
                 ORDER BY origin_server_ts ASC
@@ -157,7 +211,7 @@ impl EventStorage {
                 r"
                 SELECT event_id, origin_server_ts
                 FROM events
-                WHERE room_id = $1
+                WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
                   AND origin_server_ts IS NOT NULL
                   AND origin_server_ts <= $2
                 ORDER BY origin_server_ts DESC
@@ -181,7 +235,7 @@ impl EventStorage {
             r"
             SELECT event_id, event_type AS type, COALESCE(user_id, sender) AS sender, content, origin_server_ts
             FROM events
-            WHERE room_id = $1 AND origin_server_ts < $2
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND origin_server_ts < $2
             ORDER BY origin_server_ts DESC
             LIMIT $3
             ",
@@ -217,7 +271,7 @@ impl EventStorage {
             r"
             SELECT event_id, event_type AS type, COALESCE(user_id, sender) AS sender, content, origin_server_ts
             FROM events
-            WHERE room_id = $1 AND origin_server_ts > $2
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND origin_server_ts > $2
             ORDER BY origin_server_ts ASC
             LIMIT $3
             ",
@@ -0,0 +1,528 @@
+//! Declarative partitioning of the `events` table.
+//!
+//! Large deployments can convert `events` into a partitioned table, either by
+//! `origin_server_ts` range or by a hash of `room_id`. Every query keeps
+//! working unchanged; what changes is how much of the table Postgres has to
+//! touch:
+//!
+//! * **range** — retention purges (`room_id = $1 AND origin_server_ts < $2`)
+//!   only visit the partitions before the cutoff, and partitions emptied by
+//!   purges are dropped outright instead of being left to vacuum. Maintenance
+//!   creates partitions ahead of time; rows outside every range land in
+//!   `events_default`.
+//! * **hash** — a room's whole timeline lives in one partition, so `/messages`
+//!   and room purges scan a single, smaller index. Hash partitions need no
+//!   maintenance.
+//!
+//! The conversion itself is an offline operation driven by the
+//! `partition_events` binary ([`EventStorage::partition_conversion_plan`] and
+//! [`EventStorage::apply_partition_conversion`]). Postgres requires the
+//! partition key in every unique constraint, so the primary key becomes
+//! `(event_id, origin_server_ts)` or `(event_id, room_id)` and `event_id` is
+//! no longer unique across partitions on its own. Foreign keys referencing
+//! `events(event_id)` cannot survive either; their `ON DELETE CASCADE` is
+//! replaced by a row trigger.
+
+use chrono::{TimeZone, Utc};
+
+use super::EventStorage;
+
+/// Name of the partition catching rows outside every range partition.
+pub const EVENTS_DEFAULT_PARTITION: &str = "events_default";
+
+/// Suffix given to the original table and its indexes by the conversion.
+pub const UNPARTITIONED_SUFFIX: &str = "_unpartitioned";
+
+/// Range partitions created for existing history are capped at this many;
+/// older rows go to [`EVENTS_DEFAULT_PARTITION`].
+pub const MAX_BACKFILL_RANGE_PARTITIONS: i64 = 120;
+
+const DAY_MS: i64 = 24 * 60 * 60 * 1000;
+
+/// How `events` is partitioned.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPartitionStrategy {
+    /// By `origin_server_ts`, `interval_days` per partition.
+    Range { interval_days: u32 },
+    /// By `room_id` over `partitions` hash partitions.
+    Hash { partitions: u32 },
+}
+
+/// Layout of the live `events` table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventTableLayout {
+    Unpartitioned,
+    Range,
+    Hash,
+}
+
+/// Bound of one partition, parsed from `pg_get_expr(relpartbound, oid)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventPartitionBound {
+    /// `FROM (from) TO (to)`; `None` stands for `MINVALUE` / `MAXVALUE`.
+    Range {
+        from: Option<i64>,
+        to: Option<i64>,
+    },
+    Hash {
+        modulus: u32,
+        remainder: u32,
+    },
+    Default,
+}
+
+impl EventPartitionBound {
+    pub fn parse(expr: &str) -> Option<Self> {
+        let expr = expr.trim();
+        if expr.eq_ignore_ascii_case("DEFAULT") {
+            return Some(Self::Default);
+        }
+        let values = expr.strip_prefix("FOR VALUES ")?;
+        if let Some(hash) = values.strip_prefix("WITH (").and_then(|rest| rest.strip_suffix(')')) {
+            let mut modulus = None;
+            let mut remainder = None;
+            for part in hash.split(',') {
+                let mut words = part.split_whitespace();
+                match (words.next(), words.next().and_then(|v| v.parse().ok())) {
+                    (Some("modulus"), Some(value)) => modulus = Some(value),
+                    (Some("remainder"), Some(value)) => remainder = Some(value),
+                    _ => return None,
+                }
+            }
+            return Some(Self::Hash { modulus: modulus?, remainder: remainder? });
+        }
+        let range = values.strip_prefix("FROM (")?;
+        let (from, to) = range.split_once(") TO (")?;
+        let to = to.strip_suffix(')')?;
+        Some(Self::Range { from: parse_range_value(from)?, to: parse_range_value(to)? })
+    }
+}
+
+/// `Some(None)` for `MINVALUE`/`MAXVALUE`, `None` when unparseable.
+fn parse_range_value(value: &str) -> Option<Option<i64>> {
+    let value = value.trim().trim_matches('\'');
+    if value.eq_ignore_ascii_case("MINVALUE") || value.eq_ignore_ascii_case("MAXVALUE") {
+        return Some(None);
+    }
+    value.parse().ok().map(Some)
+}
+
+/// One partition of `events`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventPartition {
+    pub name: String,
+    pub bound: EventPartitionBound,
+}
+
+/// Outcome of one maintenance pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EventPartitionMaintenance {
+    pub created: Vec<String>,
+    pub dropped: Vec<String>,
+}
+
+/// What the conversion needs to know about the live, unpartitioned table.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartitionConversionInputs {
+    /// `(index_name, CREATE INDEX statement)` for every index except the
+    /// primary key.
+    pub indexes: Vec<(String, String)>,
+    /// `(table, constraint, column)` of foreign keys referencing `events`.
+    pub referencing_foreign_keys: Vec<(String, String, String)>,
+    /// Oldest `origin_server_ts`, if the table has rows.
+    pub min_origin_server_ts: Option<i64>,
+}
+
+fn interval_ms(interval_days: u32) -> i64 {
+    i64::from(interval_days.max(1)) * DAY_MS
+}
+
+/// Start of the range window containing `ts`, windows being aligned to the
+/// Unix epoch.
+pub fn align_to_interval(ts: i64, interval_days: u32) -> i64 {
+    ts.div_euclid(interval_ms(interval_days)) * interval_ms(interval_days)
+}
+
+/// `events_p20261016` for a partition starting at `from_ts`.
+pub fn range_partition_name(from_ts: i64) -> String {
+    let date = Utc
+        .timestamp_millis_opt(from_ts)
+        .single()
+        .map_or_else(|| from_ts.to_string(), |d| d.format("%Y%m%d").to_string());
+    format!("events_p{date}")
+}
+
+pub fn hash_partition_name(remainder: u32) -> String {
+    format!("events_h{remainder:02}")
+}
+
+fn create_range_partition_sql(from_ts: i64, to_ts: i64) -> String {
+    format!(
+        "CREATE TABLE IF NOT EXISTS {} PARTITION OF events FOR VALUES FROM ({from_ts}) TO ({to_ts})",
+        range_partition_name(from_ts)
+    )
+}
+
+/// Range windows `[from, to)` still missing so that partitions exist until
+/// `premake` intervals after `now_ms`. New windows continue from the highest
+/// existing upper bound, so they never overlap existing partitions even when
+/// `interval_days` has changed since they were created.
+pub fn missing_range_windows(
+    partitions: &[EventPartition],
+    now_ms: i64,
+    interval_days: u32,
+    premake: u32,
+) -> Vec<(i64, i64)> {
+    let step = interval_ms(interval_days);
+    let horizon = align_to_interval(now_ms, interval_days) + step * (i64::from(premake) + 1);
+    let mut from = partitions
+        .iter()
+        .filter_map(|partition| match partition.bound {
+            EventPartitionBound::Range { to: Some(to), .. } => Some(to),
+            _ => None,
+        })
+        .max()
+        .unwrap_or_else(|| align_to_interval(now_ms, interval_days));
+    let mut windows = Vec::new();
+    while from < horizon {
+        windows.push((from, from + step));
+        from += step;
+    }
+    windows
+}
+
+/// SQL converting the unpartitioned `events` table in place. Runs in one
+/// transaction; the original table is kept as `events_unpartitioned` for the
+/// operator to drop once the result has been checked.
+pub fn conversion_plan(
+    strategy: EventPartitionStrategy,
+    inputs: &PartitionConversionInputs,
+    now_ms: i64,
+) -> Vec<String> {
+    let mut plan = Vec::new();
+
+    for (table, constraint, _) in &inputs.referencing_foreign_keys {
+        plan.push(format!("ALTER TABLE {table} DROP CONSTRAINT {constraint}"));
+    }
+
+    plan.push(format!("ALTER TABLE events RENAME TO events{UNPARTITIONED_SUFFIX}"));
+    plan.push(format!(
+        "ALTER TABLE events{UNPARTITIONED_SUFFIX} RENAME CONSTRAINT pk_events TO pk_events{UNPARTITIONED_SUFFIX}"
+    ));
+    for (index, _) in &inputs.indexes {
+        plan.push(format!("ALTER INDEX {index} RENAME TO {index}{UNPARTITIONED_SUFFIX}"));
+    }
+
+    let (partition_by, primary_key) = match strategy {
+        EventPartitionStrategy::Range { .. } => ("RANGE (origin_server_ts)", "(event_id, origin_server_ts)"),
+        EventPartitionStrategy::Hash { .. } => ("HASH (room_id)", "(event_id, room_id)"),
+    };
+    plan.push(format!(
+        "CREATE TABLE events (LIKE events{UNPARTITIONED_SUFFIX} INCLUDING DEFAULTS INCLUDING STORAGE INCLUDING COMMENTS) \
+         PARTITION BY {partition_by}"
+    ));
+    plan.push(format!("ALTER TABLE events ADD CONSTRAINT pk_events PRIMARY KEY {primary_key}"));
+    plan.push(
+        "ALTER TABLE events ADD CONSTRAINT fk_events_room FOREIGN KEY (room_id) REFERENCES rooms(room_id) \
+         ON DELETE CASCADE"
+            .to_string(),
+    );
+    // Dropping `events_unpartitioned` later must not take the sequence with it.
+    plan.push("ALTER SEQUENCE events_stream_ordering_seq OWNED BY events.stream_ordering".to_string());
+
+    match strategy {
+        EventPartitionStrategy::Range { interval_days } => {
+            let step = interval_ms(interval_days);
+            let current = align_to_interval(now_ms, interval_days);
+            let earliest = current - step * (MAX_BACKFILL_RANGE_PARTITIONS - 1);
+            let mut from = inputs
+                .min_origin_server_ts
+                .map_or(current, |ts| align_to_interval(ts, interval_days))
+                .clamp(earliest, current);
+            while from <= current {
+                plan.push(create_range_partition_sql(from, from + step));
+                from += step;
+            }
+            plan.push(format!("CREATE TABLE {EVENTS_DEFAULT_PARTITION} PARTITION OF events DEFAULT"));
+        }
+        EventPartitionStrategy::Hash { partitions } => {
+            let modulus = partitions.max(1);
+            for remainder in 0..modulus {
+                plan.push(format!(
+                    "CREATE TABLE {} PARTITION OF events FOR VALUES WITH (MODULUS {modulus}, REMAINDER {remainder})",
+                    hash_partition_name(remainder)
+                ));
+            }
+        }
+    }
+
+    // Indexes are built after the copy so each partition builds its index
+    // once instead of maintaining it row by row.
+    plan.push(format!("INSERT INTO events SELECT * FROM events{UNPARTITIONED_SUFFIX}"));
+    for (_, definition) in &inputs.indexes {
+        plan.push(definition.clone());
+    }
+
+    if !inputs.referencing_foreign_keys.is_empty() {
+        let deletes: String = inputs
+            .referencing_foreign_keys
+            .iter()
+            .map(|(table, _, column)| format!("DELETE FROM {table} WHERE {column} = OLD.event_id; "))
+            .collect();
+        plan.push(format!(
+            "CREATE OR REPLACE FUNCTION events_cascade_delete() RETURNS trigger AS $$ \
+             BEGIN {deletes}RETURN OLD; END; $$ LANGUAGE plpgsql"
+        ));
+        plan.push(
+            "CREATE TRIGGER events_cascade_delete AFTER DELETE ON events \
+             FOR EACH ROW EXECUTE FUNCTION events_cascade_delete()"
+                .to_string(),
+        );
+    }
+
+    plan.push("ANALYZE events".to_string());
+    plan
+}
+
+impl EventStorage {
+    /// Whether and how the live `events` table is partitioned.
+    pub async fn event_table_layout(&self) -> Result<EventTableLayout, sqlx::Error> {
+        let strategy: Option<String> = sqlx::query_scalar(
+            "SELECT pt.partstrat::TEXT FROM pg_partitioned_table pt \
+             WHERE pt.partrelid = to_regclass('events')",
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+        Ok(match strategy.as_deref() {
+            Some("r") => EventTableLayout::Range,
+            Some("h") => EventTableLayout::Hash,
+            _ => EventTableLayout::Unpartitioned,
+        })
+    }
+
+    pub async fn list_event_partitions(&self) -> Result<Vec<EventPartition>, sqlx::Error> {
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT c.relname::TEXT, pg_get_expr(c.relpartbound, c.oid) \
+             FROM pg_inherits i JOIN pg_class c ON c.oid = i.inhrelid \
+             WHERE i.inhparent = to_regclass('events') \
+             ORDER BY c.relname",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(name, bound)| EventPartitionBound::parse(&bound).map(|bound| EventPartition { name, bound }))
+            .collect())
+    }
+
+    /// Create the range partitions `premake` intervals ahead and, when
+    /// `drop_empty` is set, drop past partitions that purges have emptied.
+    /// Does nothing unless `events` is range partitioned.
+    pub async fn maintain_event_partitions(
+        &self,
+        now_ms: i64,
+        interval_days: u32,
+        premake: u32,
+        drop_empty: bool,
+    ) -> Result<EventPartitionMaintenance, sqlx::Error> {
+        let mut outcome = EventPartitionMaintenance::default();
+        if self.event_table_layout().await? != EventTableLayout::Range {
+            return Ok(outcome);
+        }
+        let partitions = self.list_event_partitions().await?;
+
+        for (from, to) in missing_range_windows(&partitions, now_ms, interval_days, premake) {
+            sqlx::query(&create_range_partition_sql(from, to)).execute(&*self.pool).await?;
+            outcome.created.push(range_partition_name(from));
+        }
+
+        if drop_empty {
+            let current = align_to_interval(now_ms, interval_days);
+            for partition in &partitions {
+                let EventPartitionBound::Range { to: Some(to), .. } = partition.bound else {
+                    continue;
+                };
+                if to > current {
+                    continue;
+                }
+                // The lock keeps a late backfilled event from landing between
+                // the emptiness check and the drop.
+                let mut tx = self.pool.begin().await?;
+                sqlx::query(&format!("LOCK TABLE {} IN ACCESS EXCLUSIVE MODE", partition.name))
+                    .execute(&mut *tx)
+                    .await?;
+                let has_rows: bool = sqlx::query_scalar(&format!("SELECT EXISTS (SELECT 1 FROM {})", partition.name))
+                    .fetch_one(&mut *tx)
+                    .await?;
+                if has_rows {
+                    tx.rollback().await?;
+                    continue;
+                }
+                sqlx::query(&format!("DROP TABLE {}", partition.name)).execute(&mut *tx).await?;
+                tx.commit().await?;
+                outcome.dropped.push(partition.name.clone());
+            }
+        }
+        Ok(outcome)
+    }
+
+    /// Inspect the unpartitioned `events` table and return the statements
+    /// converting it with `strategy`. Fails if it is already partitioned.
+    pub async fn partition_conversion_plan(
+        &self,
+        strategy: EventPartitionStrategy,
+        now_ms: i64,
+    ) -> Result<Vec<String>, sqlx::Error> {
+        if self.event_table_layout().await? != EventTableLayout::Unpartitioned {
+            return Err(sqlx::Error::Protocol("events is already partitioned".to_string()));
+        }
+        let indexes: Vec<(String, String)> = sqlx::query_as(
+            "SELECT i.indexname::TEXT, i.indexdef FROM pg_indexes i \
+             WHERE i.schemaname = current_schema() AND i.tablename = 'events' \
+             AND NOT EXISTS (SELECT 1 FROM pg_constraint c WHERE c.conname = i.indexname AND c.contype = 'p') \
+             ORDER BY i.indexname",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        let referencing_foreign_keys: Vec<(String, String, String)> = sqlx::query_as(
+            "SELECT c.conrelid::regclass::TEXT, c.conname::TEXT, a.attname::TEXT \
+             FROM pg_constraint c \
+             JOIN pg_attribute a ON a.attrelid = c.conrelid AND a.attnum = c.conkey[1] \
+             WHERE c.contype = 'f' AND c.confrelid = to_regclass('events') \
+             ORDER BY 1, 2",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        let min_origin_server_ts: Option<i64> =
+            sqlx::query_scalar("SELECT MIN(origin_server_ts) FROM events").fetch_one(&*self.pool).await?;
+
+        let inputs = PartitionConversionInputs { indexes, referencing_foreign_keys, min_origin_server_ts };
+        Ok(conversion_plan(strategy, &inputs, now_ms))
+    }
+
+    /// Run a plan from [`Self::partition_conversion_plan`] in one transaction.
+    pub async fn apply_partition_conversion(&self, plan: &[String]) -> Result<(), sqlx::Error> {
+        let mut tx = self.pool.begin().await?;
+        for statement in plan {
+            sqlx::query(statement).execute(&mut *tx).await?;
+        }
+        tx.commit().await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // 2026-10-16T00:00:00Z
+    const NOW: i64 = 1_792_108_800_000;
+
+    fn range(name: &str, from: i64, to: i64) -> EventPartition {
+        EventPartition { name: name.to_string(), bound: EventPartitionBound::Range { from: Some(from), to: Some(to) } }
+    }
+
+    #[test]
+    fn parses_partition_bounds() {
+        assert_eq!(
+            EventPartitionBound::parse("FOR VALUES FROM ('1700000000000') TO ('1702592000000')"),
+            Some(EventPartitionBound::Range { from: Some(1_700_000_000_000), to: Some(1_702_592_000_000) })
+        );
+        assert_eq!(
+            EventPartitionBound::parse("FOR VALUES FROM (MINVALUE) TO (5)"),
+            Some(EventPartitionBound::Range { from: None, to: Some(5) })
+        );
+        assert_eq!(
+            EventPartitionBound::parse("FOR VALUES WITH (modulus 16, remainder 3)"),
+            Some(EventPartitionBound::Hash { modulus: 16, remainder: 3 })
+        );
+        assert_eq!(EventPartitionBound::parse("DEFAULT"), Some(EventPartitionBound::Default));
+        assert_eq!(EventPartitionBound::parse("FOR VALUES IN ('a')"), None);
+    }
+
+    #[test]
+    fn range_partitions_are_named_by_start_date() {
+        assert_eq!(range_partition_name(NOW), "events_p20261016");
+        assert_eq!(hash_partition_name(7), "events_h07");
+        assert_eq!(align_to_interval(NOW + 5, 1), NOW);
+    }
+
+    #[test]
+    fn missing_windows_continue_from_highest_bound() {
+        let step = 30 * DAY_MS;
+        let current = align_to_interval(NOW, 30);
+        let windows = missing_range_windows(&[], NOW, 30, 2);
+        assert_eq!(
+            windows,
+            vec![
+                (current, current + step),
+                (current + step, current + 2 * step),
+                (current + 2 * step, current + 3 * step)
+            ]
+        );
+
+        // An existing partition of a different width is continued, not overlapped.
+        let existing = [range("events_p1", current - step, current + DAY_MS)];
+        let windows = missing_range_windows(&existing, NOW, 30, 0);
+        assert_eq!(windows, vec![(current + DAY_MS, current + DAY_MS + step)]);
+
+        let covered = [range("events_p2", current, current + 10 * step)];
+        assert!(missing_range_windows(&covered, NOW, 30, 3).is_empty());
+    }
+
+    #[test]
+    fn range_conversion_plan_rekeys_and_replaces_foreign_keys() {
+        let inputs = PartitionConversionInputs {
+            indexes: vec![(
+                "idx_events_room_time".to_string(),
+                "CREATE INDEX idx_events_room_time ON public.events USING btree (room_id, origin_server_ts DESC)"
+                    .to_string(),
+            )],
+            referencing_foreign_keys: vec![(
+                "event_edges".to_string(),
+                "fk_event_edges_event".to_string(),
+                "event_id".to_string(),
+            )],
+            min_origin_server_ts: Some(NOW - 45 * DAY_MS),
+        };
+        let plan = conversion_plan(EventPartitionStrategy::Range { interval_days: 30 }, &inputs, NOW);
+
+        assert_eq!(plan[0], "ALTER TABLE event_edges DROP CONSTRAINT fk_event_edges_event");
+        assert!(
+            plan.contains(&"ALTER INDEX idx_events_room_time RENAME TO idx_events_room_time_unpartitioned".to_string())
+        );
+        assert!(plan.iter().any(|s| s.ends_with("PARTITION BY RANGE (origin_server_ts)")));
+        assert!(plan.contains(
+            &"ALTER TABLE events ADD CONSTRAINT pk_events PRIMARY KEY (event_id, origin_server_ts)".to_string()
+        ));
+        let partitions = plan.iter().filter(|s| s.contains("PARTITION OF events FOR VALUES FROM")).count();
+        assert_eq!(partitions, 3);
+        assert!(plan.contains(&"CREATE TABLE events_default PARTITION OF events DEFAULT".to_string()));
+
+        let copy = plan.iter().position(|s| s.starts_with("INSERT INTO events SELECT")).unwrap_or(usize::MAX);
+        let index = plan.iter().position(|s| s.starts_with("CREATE INDEX idx_events_room_time")).unwrap_or(0);
+        assert!(copy < index);
+        assert!(plan.iter().any(|s| s.contains("DELETE FROM event_edges WHERE event_id = OLD.event_id")));
+    }
+
+    #[test]
+    fn range_conversion_caps_backfilled_partitions() {
+        let inputs = PartitionConversionInputs { min_origin_server_ts: Some(0), ..Default::default() };
+        let plan = conversion_plan(EventPartitionStrategy::Range { interval_days: 1 }, &inputs, NOW);
+        let partitions = plan.iter().filter(|s| s.contains("PARTITION OF events FOR VALUES FROM")).count();
+        assert_eq!(partitions as i64, MAX_BACKFILL_RANGE_PARTITIONS);
+    }
+
+    #[test]
+    fn hash_conversion_plan_keys_on_room() {
+        let plan =
+            conversion_plan(EventPartitionStrategy::Hash { partitions: 4 }, &PartitionConversionInputs::default(), NOW);
+        assert!(
+            plan.contains(&"ALTER TABLE events ADD CONSTRAINT pk_events PRIMARY KEY (event_id, room_id)".to_string())
+        );
+        assert!(plan.contains(
+            &"CREATE TABLE events_h03 PARTITION OF events FOR VALUES WITH (MODULUS 4, REMAINDER 3)".to_string()
+        ));
+        assert!(!plan.iter().any(|s| s.contains(EVENTS_DEFAULT_PARTITION)));
+        assert!(!plan.iter().any(|s| s.contains("events_cascade_delete")));
+    }
+}
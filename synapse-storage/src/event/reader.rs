@@ -27,6 +27,18 @@ pub trait EventReader: Send + Sync {
         direction: &str,
     ) -> Result<Vec<RoomEvent>, sqlx::Error>;
 
+    async fn get_room_events_page(
+        &self,
+        room_id: &str,
+        from: i64,
+        to: Option<i64>,
+        limit: i64,
+        direction: &str,
+        filter: Option<&EventQueryFilter>,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error>;
+
+    async fn get_room_stream_position(&self, room_id: &str, before_ts: Option<i64>) -> Result<i64, sqlx::Error>;
+
     async fn get_room_events_batch(
         &self,
         room_ids: &[String],
@@ -67,6 +79,8 @@ pub trait EventReader: Send + Sync {
         origin_server_ts: i64,
     ) -> Result<Vec<StateEvent>, sqlx::Error>;
 
+    async fn get_state_events_at_event(&self, event: &RoomEvent) -> Result<Vec<StateEvent>, sqlx::Error>;
+
     // ── helpers ───────────────────────────────────────────────────────
 
     async fn get_events_map(&self, event_ids: &[String]) -> Result<HashMap<String, RoomEvent>, sqlx::Error>;
@@ -93,6 +107,13 @@ pub trait EventReader: Send + Sync {
         limit: i64,
     ) -> Result<HashMap<String, Vec<RoomEphemeralEvent>>, sqlx::Error>;
 
+    async fn has_ephemeral_events_since(
+        &self,
+        room_ids: &[String],
+        since_ts: i64,
+        now: i64,
+    ) -> Result<bool, sqlx::Error>;
+
     // ── state-batch ─────────────────────────────────────────────────────
 
     async fn get_state_events_batch(
@@ -106,10 +127,23 @@ pub trait EventReader: Send + Sync {
         event_type: &str,
     ) -> Result<HashMap<String, Vec<StateEvent>>, sqlx::Error>;
 
+    async fn get_member_events_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, StateEvent>, sqlx::Error>;
+
+    async fn get_visibility_history_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, Vec<StateEvent>>, sqlx::Error>;
+
     async fn get_state_events_since_batch(
         &self,
         room_ids: &[String],
         since: SinceFilter,
+        until: &HashMap<String, i64>,
     ) -> Result<HashMap<String, Vec<StateEvent>>, sqlx::Error>;
 
     async fn get_membership_state_keys_since_batch(
@@ -137,6 +171,15 @@ pub trait EventReader: Send + Sync {
 
     async fn has_room_events_since(&self, room_ids: &[String], since: i64) -> Result<bool, sqlx::Error>;
 
+    async fn get_room_events_after_stream(
+        &self,
+        room_ids: &[String],
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error>;
+
+    async fn get_max_stream_ordering(&self) -> Result<i64, sqlx::Error>;
+
     // ── unread counts / room state copy ────────────────────────────────
     //
     // These queries read from the `events` table and were moved here from
@@ -168,8 +211,70 @@ pub trait EventReader: Send + Sync {
         limit: i64,
     ) -> Result<Vec<serde_json::Value>, sqlx::Error>;
 
+    async fn get_forward_extremities(&self, room_id: &str, limit: i64) -> Result<Vec<String>, sqlx::Error>;
+
     async fn get_forward_extremities_count(&self, room_id: &str) -> Result<i64, sqlx::Error>;
 
+    async fn get_rooms_by_forward_extremities(
+        &self,
+        min_count: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error>;
+
+    async fn get_event_stream_ordering_in_room(
+        &self,
+        room_id: &str,
+        event_id: &str,
+    ) -> Result<Option<i64>, sqlx::Error>;
+
+    async fn get_room_pdus(
+        &self,
+        room_id: &str,
+        after: Option<i64>,
+        until: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ExportedPdu>, sqlx::Error>;
+
+    async fn get_auth_chain_pdus(&self, room_id: &str, event_ids: &[String]) -> Result<Vec<ExportedPdu>, sqlx::Error>;
+
+    async fn get_room_dag_diagnostics(
+        &self,
+        room_id: &str,
+        depth: Option<i64>,
+    ) -> Result<RoomDagDiagnostics, sqlx::Error>;
+
+    async fn get_soft_failed_events(
+        &self,
+        room_id: &str,
+        kind: Option<SoftFailKind>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<SoftFailedEvent>, sqlx::Error>;
+
+    async fn count_soft_failed_events(&self, room_id: &str) -> Result<Vec<(String, i64)>, sqlx::Error>;
+
+    async fn get_rejected_events(
+        &self,
+        filter: &RejectedEventFilter,
+        limit: i64,
+    ) -> Result<Vec<RejectedEvent>, sqlx::Error>;
+
+    // ── relations ───────────────────────────────────────────────────────
+
+    async fn get_event_relations(
+        &self,
+        room_id: &str,
+        parent_id: &str,
+        params: &EventRelationsQuery,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error>;
+
+    async fn get_relation_aggregations(
+        &self,
+        room_id: &str,
+        parent_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, RelationAggregation>, sqlx::Error>;
+
     // ── context / pagination ────────────────────────────────────────────
 
     async fn find_event_id_by_timestamp(
@@ -246,6 +351,22 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.get_room_events_paginated(room_id, from, limit, direction).await
     }
 
+    async fn get_room_events_page(
+        &self,
+        room_id: &str,
+        from: i64,
+        to: Option<i64>,
+        limit: i64,
+        direction: &str,
+        filter: Option<&EventQueryFilter>,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        self.get_room_events_page(room_id, from, to, limit, direction, filter).await
+    }
+
+    async fn get_room_stream_position(&self, room_id: &str, before_ts: Option<i64>) -> Result<i64, sqlx::Error> {
+        self.get_room_stream_position(room_id, before_ts).await
+    }
+
     async fn get_room_events_batch(
         &self,
         room_ids: &[String],
@@ -298,6 +419,10 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.get_state_events_at_or_before(room_id, origin_server_ts).await
     }
 
+    async fn get_state_events_at_event(&self, event: &RoomEvent) -> Result<Vec<StateEvent>, sqlx::Error> {
+        self.get_state_events_at_event(event).await
+    }
+
     async fn get_events_map(&self, event_ids: &[String]) -> Result<HashMap<String, RoomEvent>, sqlx::Error> {
         self.get_events_map(event_ids).await
     }
@@ -332,6 +457,15 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.get_ephemeral_events_batch(room_ids, now, limit).await
     }
 
+    async fn has_ephemeral_events_since(
+        &self,
+        room_ids: &[String],
+        since_ts: i64,
+        now: i64,
+    ) -> Result<bool, sqlx::Error> {
+        self.has_ephemeral_events_since(room_ids, since_ts, now).await
+    }
+
     async fn get_state_events_batch(
         &self,
         room_ids: &[String],
@@ -347,12 +481,29 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.get_state_events_by_type_batch(room_ids, event_type).await
     }
 
+    async fn get_member_events_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, StateEvent>, sqlx::Error> {
+        self.get_member_events_batch(room_ids, user_id).await
+    }
+
+    async fn get_visibility_history_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, Vec<StateEvent>>, sqlx::Error> {
+        self.get_visibility_history_batch(room_ids, user_id).await
+    }
+
     async fn get_state_events_since_batch(
         &self,
         room_ids: &[String],
         since: SinceFilter,
+        until: &HashMap<String, i64>,
     ) -> Result<HashMap<String, Vec<StateEvent>>, sqlx::Error> {
-        self.get_state_events_since_batch(room_ids, since).await
+        self.get_state_events_since_batch(room_ids, since, until).await
     }
 
     async fn get_membership_state_keys_since_batch(
@@ -384,6 +535,19 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.has_room_events_since(room_ids, since).await
     }
 
+    async fn get_room_events_after_stream(
+        &self,
+        room_ids: &[String],
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        self.get_room_events_after_stream(room_ids, after, limit).await
+    }
+
+    async fn get_max_stream_ordering(&self) -> Result<i64, sqlx::Error> {
+        self.get_max_stream_ordering().await
+    }
+
     async fn find_missing_event_ids(&self, event_ids: &[String]) -> Result<Vec<String>, sqlx::Error> {
         self.find_missing_event_ids(event_ids).await
     }
@@ -398,10 +562,92 @@ impl crate::event::reader::EventReader for super::EventStorage {
         self.get_missing_events_between(room_id, earliest_events, latest_events, limit).await
     }
 
+    async fn get_forward_extremities(&self, room_id: &str, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        self.get_forward_extremities(room_id, limit).await
+    }
+
     async fn get_forward_extremities_count(&self, room_id: &str) -> Result<i64, sqlx::Error> {
         self.get_forward_extremities_count(room_id).await
     }
 
+    async fn get_rooms_by_forward_extremities(
+        &self,
+        min_count: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        self.get_rooms_by_forward_extremities(min_count, limit).await
+    }
+
+    async fn get_event_stream_ordering_in_room(
+        &self,
+        room_id: &str,
+        event_id: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        self.get_event_stream_ordering_in_room(room_id, event_id).await
+    }
+
+    async fn get_room_pdus(
+        &self,
+        room_id: &str,
+        after: Option<i64>,
+        until: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<ExportedPdu>, sqlx::Error> {
+        self.get_room_pdus(room_id, after, until, limit).await
+    }
+
+    async fn get_auth_chain_pdus(&self, room_id: &str, event_ids: &[String]) -> Result<Vec<ExportedPdu>, sqlx::Error> {
+        self.get_auth_chain_pdus(room_id, event_ids).await
+    }
+
+    async fn get_room_dag_diagnostics(
+        &self,
+        room_id: &str,
+        depth: Option<i64>,
+    ) -> Result<RoomDagDiagnostics, sqlx::Error> {
+        self.get_room_dag_diagnostics(room_id, depth).await
+    }
+
+    async fn get_soft_failed_events(
+        &self,
+        room_id: &str,
+        kind: Option<SoftFailKind>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<SoftFailedEvent>, sqlx::Error> {
+        self.get_soft_failed_events(room_id, kind, before, limit).await
+    }
+
+    async fn count_soft_failed_events(&self, room_id: &str) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        self.count_soft_failed_events(room_id).await
+    }
+
+    async fn get_rejected_events(
+        &self,
+        filter: &RejectedEventFilter,
+        limit: i64,
+    ) -> Result<Vec<RejectedEvent>, sqlx::Error> {
+        self.get_rejected_events(filter, limit).await
+    }
+
+    async fn get_event_relations(
+        &self,
+        room_id: &str,
+        parent_id: &str,
+        params: &EventRelationsQuery,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        self.get_event_relations(room_id, parent_id, params).await
+    }
+
+    async fn get_relation_aggregations(
+        &self,
+        room_id: &str,
+        parent_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, RelationAggregation>, sqlx::Error> {
+        self.get_relation_aggregations(room_id, parent_ids, user_id).await
+    }
+
     async fn find_event_id_by_timestamp(
         &self,
         room_id: &str,
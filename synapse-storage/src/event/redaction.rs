@@ -71,32 +71,35 @@ impl EventStorage {
         .await
     }
 
-    /// Redacts an event's content in-place according to the Matrix redaction
-    /// rules for room versions 1-10 (P0-06).
+    /// Redacts an event's content in-place according to the redaction
+    /// algorithm of the room's version, so `/messages`, `/context` and sync
+    /// serve the pruned event from then on.
     ///
-    /// Unlike the previous implementation which cleared content to `{}`, this
-    /// fetches the event type and retains the spec-mandated fields per event
-    /// type (e.g. `membership` for `m.room.member`, `users`/`ban`/... for
-    /// `m.room.power_levels`).  This keeps redacted state events functional
-    /// and matches Synapse/Synapse-Rust federation hash computation.
+    /// Rooms without a recorded version use the server default.
     ///
     /// `redacted_by` optionally records the user_id of the redactor.
     pub async fn redact_event_content(&self, event_id: &str, redacted_by: Option<&str>) -> Result<(), sqlx::Error> {
-        // Fetch the event type and content so we can apply the per-type
-        // retention table from synapse_common::redaction.
-        let row: Option<(String, serde_json::Value)> =
-            sqlx::query_as("SELECT event_type, content FROM events WHERE event_id = $1")
-                .bind(event_id)
-                .fetch_optional(&*self.pool)
-                .await?;
+        let row: Option<(String, serde_json::Value, Option<String>)> = sqlx::query_as(
+            r"
+            SELECT e.event_type, e.content, r.room_version
+            FROM events e
+            LEFT JOIN rooms r ON r.room_id = e.room_id
+            WHERE e.purged_at IS NULL AND e.event_id = $1
+            ",
+        )
+        .bind(event_id)
+        .fetch_optional(&*self.pool)
+        .await?;
 
-        let Some((event_type, content)) = row else {
+        let Some((event_type, content, room_version)) = row else {
             // Event not found — nothing to redact.  This is benign for
             // federation redaction PDUs that target events we don't have.
             return Ok(());
         };
 
-        let redacted_content = synapse_common::redaction::redact_content(&event_type, &content);
+        let room_version = room_version.as_deref().unwrap_or(synapse_common::room_versions::DEFAULT_ROOM_VERSION);
+        let redacted_content =
+            synapse_common::redaction::redact_content_for_room_version(room_version, &event_type, &content);
         let now = current_timestamp_millis();
 
         sqlx::query(
@@ -0,0 +1,74 @@
+//! Rejected inbound PDUs for [`EventStorage`].
+//!
+//! PDUs that fail the checks done before persistence (size limits, content
+//! hash, signatures, origin authorization) never reach `events`. They are
+//! recorded in `rejected_events` with the reason so that federation problems
+//! can be diagnosed after the fact. The table is capped: each insert trims
+//! it to the most recent `max_rows` entries.
+
+use synapse_common::current_timestamp_millis;
+
+use super::models::{RejectedEvent, RejectedEventFilter, RejectedEventParams};
+use super::EventStorage;
+
+impl EventStorage {
+    pub async fn record_rejected_event(&self, params: RejectedEventParams, max_rows: i64) -> Result<(), sqlx::Error> {
+        if max_rows <= 0 {
+            return Ok(());
+        }
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r"
+            INSERT INTO rejected_events (event_id, origin, room_id, sender, event_type, reason, detail, rejected_ts)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ",
+        )
+        .bind(&params.event_id)
+        .bind(&params.origin)
+        .bind(params.room_id.as_deref())
+        .bind(params.sender.as_deref())
+        .bind(params.event_type.as_deref())
+        .bind(params.reason.as_str())
+        .bind(&params.detail)
+        .bind(current_timestamp_millis())
+        .execute(&mut *tx)
+        .await?;
+        sqlx::query(
+            r"
+            DELETE FROM rejected_events
+            WHERE id <= (SELECT id FROM rejected_events ORDER BY id DESC OFFSET $1 LIMIT 1)
+            ",
+        )
+        .bind(max_rows)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Rejected PDUs matching `filter`, most recent first.
+    pub async fn get_rejected_events(
+        &self,
+        filter: &RejectedEventFilter,
+        limit: i64,
+    ) -> Result<Vec<RejectedEvent>, sqlx::Error> {
+        sqlx::query_as(
+            r"
+            SELECT id, event_id, origin, room_id, sender, event_type, reason, detail, rejected_ts
+            FROM rejected_events
+            WHERE ($1::TEXT IS NULL OR room_id = $1)
+              AND ($2::TEXT IS NULL OR origin = $2)
+              AND ($3::TEXT IS NULL OR reason = $3)
+              AND ($4::BIGINT IS NULL OR id < $4)
+            ORDER BY id DESC
+            LIMIT $5
+            ",
+        )
+        .bind(filter.room_id.as_deref())
+        .bind(filter.origin.as_deref())
+        .bind(filter.reason.map(|reason| reason.as_str()))
+        .bind(filter.before)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+}
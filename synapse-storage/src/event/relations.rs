@@ -0,0 +1,182 @@
+//! Event relations for [`EventStorage`].
+//!
+//! Relations are read straight from `events` by the `m.relates_to` of their
+//! content, so every event sent with one is related to its parent whichever
+//! endpoint sent it. `idx_events_relations`, built by the online migration
+//! runner, keys the lookups on `(room_id, parent event ID, stream_ordering)`.
+
+use std::collections::HashMap;
+
+use sqlx::{Postgres, QueryBuilder};
+
+use super::models::{AnnotationGroup, EventRelationsQuery, RelationAggregation, RoomEvent};
+use super::EventStorage;
+use super::ROOM_EVENT_COLS;
+
+/// `(parent event ID, rel_type)` of an event's `m.relates_to`.
+pub(crate) fn relates_to(content: &serde_json::Value) -> Option<(&str, &str)> {
+    let relation = content.get("m.relates_to")?;
+    Some((relation.get("event_id")?.as_str()?, relation.get("rel_type")?.as_str()?))
+}
+
+impl EventStorage {
+    /// One page of the events relating to `parent_id`.
+    pub async fn get_event_relations(
+        &self,
+        room_id: &str,
+        parent_id: &str,
+        params: &EventRelationsQuery,
+    ) -> Result<Vec<RoomEvent>, sqlx::Error> {
+        let mut query = QueryBuilder::<Postgres>::new(format!(
+            "SELECT {ROOM_EVENT_COLS} FROM events WHERE purged_at IS NULL AND NOT soft_failed AND room_id = "
+        ));
+        query.push_bind(room_id);
+        query.push(" AND content->'m.relates_to'->>'event_id' = ");
+        query.push_bind(parent_id);
+        if let Some(rel_type) = &params.rel_type {
+            query.push(" AND content->'m.relates_to'->>'rel_type' = ");
+            query.push_bind(rel_type);
+        }
+        if let Some(event_type) = &params.event_type {
+            query.push(" AND event_type = ");
+            query.push_bind(event_type);
+        }
+        if let Some(from) = params.from {
+            query.push(if params.backwards { " AND stream_ordering <= " } else { " AND stream_ordering > " });
+            query.push_bind(from);
+        }
+        if let Some(to) = params.to {
+            query.push(if params.backwards { " AND stream_ordering > " } else { " AND stream_ordering <= " });
+            query.push_bind(to);
+        }
+        query.push(if params.backwards {
+            " ORDER BY stream_ordering DESC LIMIT "
+        } else {
+            " ORDER BY stream_ordering ASC LIMIT "
+        });
+        query.push_bind(params.limit);
+
+        query.build_query_as().fetch_all(&*self.pool).await
+    }
+
+    /// Thread, edit, reference and annotation summaries of `parent_ids`, keyed
+    /// by parent.
+    /// Events without relations are absent from the map.
+    pub async fn get_relation_aggregations(
+        &self,
+        room_id: &str,
+        parent_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, RelationAggregation>, sqlx::Error> {
+        let mut aggregations: HashMap<String, RelationAggregation> = HashMap::new();
+        if parent_ids.is_empty() {
+            return Ok(aggregations);
+        }
+
+        let threads: Vec<(String, i64, bool)> = sqlx::query_as(
+            r"
+            SELECT content->'m.relates_to'->>'event_id', COUNT(*),
+                   COALESCE(BOOL_OR(COALESCE(user_id, sender) = $3), FALSE)
+            FROM events
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
+              AND content->'m.relates_to'->>'event_id' = ANY($2)
+              AND content->'m.relates_to'->>'rel_type' = 'm.thread'
+            GROUP BY 1
+            ",
+        )
+        .bind(room_id)
+        .bind(parent_ids)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        for (parent_id, count, participated) in threads {
+            let aggregation = aggregations.entry(parent_id).or_default();
+            aggregation.thread_count = count;
+            aggregation.thread_participated = participated;
+        }
+
+        // Latest thread reply and latest edit of each parent; edits by anyone
+        // but the original sender are ignored.
+        let latest: Vec<RoomEvent> = sqlx::query_as(&format!(
+            r"
+            SELECT DISTINCT ON (content->'m.relates_to'->>'event_id', content->'m.relates_to'->>'rel_type')
+                {ROOM_EVENT_COLS}
+            FROM events e
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
+              AND content->'m.relates_to'->>'event_id' = ANY($2)
+              AND (
+                content->'m.relates_to'->>'rel_type' = 'm.thread'
+                OR (content->'m.relates_to'->>'rel_type' = 'm.replace' AND EXISTS (
+                    SELECT 1 FROM events p
+                    WHERE p.event_id = e.content->'m.relates_to'->>'event_id'
+                      AND COALESCE(p.user_id, p.sender) = COALESCE(e.user_id, e.sender)
+                ))
+              )
+            ORDER BY content->'m.relates_to'->>'event_id', content->'m.relates_to'->>'rel_type',
+                     stream_ordering DESC
+            "
+        ))
+        .bind(room_id)
+        .bind(parent_ids)
+        .fetch_all(&*self.pool)
+        .await?;
+        for event in latest {
+            let Some((parent_id, rel_type)) = relates_to(&event.content) else { continue };
+            let aggregation = aggregations.entry(parent_id.to_string()).or_default();
+            if rel_type == "m.thread" {
+                aggregation.thread_latest_event = Some(event);
+            } else {
+                aggregation.replacement = Some(event);
+            }
+        }
+
+        let references: Vec<(String, String)> = sqlx::query_as(
+            r"
+            SELECT content->'m.relates_to'->>'event_id', event_id
+            FROM events
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
+              AND content->'m.relates_to'->>'event_id' = ANY($2)
+              AND content->'m.relates_to'->>'rel_type' = 'm.reference'
+            ORDER BY stream_ordering
+            ",
+        )
+        .bind(room_id)
+        .bind(parent_ids)
+        .fetch_all(&*self.pool)
+        .await?;
+        for (parent_id, event_id) in references {
+            aggregations.entry(parent_id).or_default().references.push(event_id);
+        }
+
+        // Redaction strips `m.relates_to`, so redacted annotations drop out.
+        let annotations: Vec<(String, String, String, i64, bool)> = sqlx::query_as(
+            r"
+            SELECT content->'m.relates_to'->>'event_id', event_type, content->'m.relates_to'->>'key',
+                   COUNT(DISTINCT COALESCE(user_id, sender)),
+                   COALESCE(BOOL_OR(COALESCE(user_id, sender) = $3), FALSE)
+            FROM events
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
+              AND content->'m.relates_to'->>'event_id' = ANY($2)
+              AND content->'m.relates_to'->>'rel_type' = 'm.annotation'
+              AND content->'m.relates_to'->>'key' IS NOT NULL
+            GROUP BY 1, 2, 3
+            ORDER BY 4 DESC, MIN(stream_ordering)
+            ",
+        )
+        .bind(room_id)
+        .bind(parent_ids)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+        for (parent_id, event_type, key, count, current_user_participated) in annotations {
+            aggregations.entry(parent_id).or_default().annotations.push(AnnotationGroup {
+                event_type,
+                key,
+                count,
+                current_user_participated,
+            });
+        }
+
+        Ok(aggregations)
+    }
+}
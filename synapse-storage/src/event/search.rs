@@ -16,7 +16,7 @@ impl EventStorage {
             r"
             SELECT event_id, event_type, sender, content, origin_server_ts
             FROM events
-            WHERE room_id = $1 AND event_type = 'm.room.message' AND LOWER(content::text) LIKE $2 AND is_redacted = false
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 AND event_type = 'm.room.message' AND LOWER(content::text) LIKE $2 AND is_redacted = false
             ORDER BY origin_server_ts DESC
             LIMIT $3
             ",
@@ -59,7 +59,7 @@ impl EventStorage {
         }
 
         let mut query_builder = QueryBuilder::<Postgres>::new(
-            "SELECT event_id, room_id, sender, event_type, content, origin_server_ts FROM events WHERE ",
+            "SELECT event_id, room_id, sender, event_type, content, origin_server_ts FROM events WHERE purged_at IS NULL AND NOT soft_failed AND ",
         );
 
         query_builder.push("(LOWER(content::text) LIKE ");
@@ -164,7 +164,8 @@ impl EventStorage {
                     ts_rank(to_tsvector('english', e.content), plainto_tsquery('english', $2)) as rank
                 FROM events e
                 INNER JOIN room_memberships rm ON e.room_id = rm.room_id AND rm.user_id = $1 AND rm.membership = 'join'
-                WHERE e.event_type = 'm.room.message'
+                WHERE e.purged_at IS NULL AND NOT e.soft_failed
+                    AND e.event_type = 'm.room.message'
                     AND e.stream_ordering > 0
                     AND to_tsvector('english', e.content) @@ plainto_tsquery('english', $2)
                     AND (
@@ -204,7 +205,8 @@ impl EventStorage {
                     ts_rank(to_tsvector('english', e.content), plainto_tsquery('english', $2)) as rank
                 FROM events e
                 INNER JOIN room_memberships rm ON e.room_id = rm.room_id AND rm.user_id = $1 AND rm.membership = 'join'
-                WHERE e.event_type = 'm.room.message'
+                WHERE e.purged_at IS NULL AND NOT e.soft_failed
+                    AND e.event_type = 'm.room.message'
                     AND e.stream_ordering > 0
                     AND to_tsvector('english', e.content) @@ plainto_tsquery('english', $2)
                 ORDER BY rank DESC, e.origin_server_ts DESC, e.event_id DESC
@@ -248,7 +250,7 @@ impl EventStorage {
                    COALESCE(depth, 0) as depth, COALESCE(origin_server_ts, 0) as origin_server_ts, COALESCE(origin_server_ts, 0) as processed_at,
                    COALESCE(not_before, 0) as not_before, status, reference_image, COALESCE(origin, 'self') as origin, stream_ordering, redacts
             FROM events
-            WHERE room_id = $1
+            WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1
               AND event_type = 'm.room.message'
               AND to_tsvector('english', content) @@ plainto_tsquery('english', $2)
             ORDER BY origin_server_ts DESC
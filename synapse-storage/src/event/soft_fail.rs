@@ -0,0 +1,114 @@
+//! Soft-failed events for [`EventStorage`].
+//!
+//! An inbound event that passes auth against its `auth_events` but not
+//! against the room's current state (or that a spam checker quarantines) is
+//! stored with `soft_failed` set. It keeps its `event_edges` so the DAG and
+//! state resolution see it, but it never becomes a forward extremity, never
+//! enters a state group, and client-facing queries skip it.
+
+use super::models::{CreateEventParams, RoomEvent, SoftFailKind, SoftFailedEvent};
+use super::EventStorage;
+
+impl EventStorage {
+    pub async fn create_soft_failed_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        kind: SoftFailKind,
+        reason: &str,
+    ) -> Result<RoomEvent, sqlx::Error> {
+        let prev_events_json = serde_json::to_value(prev_events).unwrap_or(serde_json::Value::Null);
+        let auth_events_json = serde_json::to_value(auth_events).unwrap_or(serde_json::Value::Null);
+
+        let mut tx = self.pool.begin().await?;
+        let event: RoomEvent = sqlx::query_as(
+            r"
+            INSERT INTO events (event_id, room_id, sender, user_id, event_type, content, state_key, origin_server_ts, is_redacted, redacts, depth, prev_events, auth_events, soft_failed, soft_fail_kind, soft_fail_reason)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, false, $9, $10, $11, $12, true, $13, $14)
+            RETURNING event_id, room_id, sender as user_id, event_type, content, state_key,
+                      COALESCE(depth, 0) as depth, origin_server_ts, origin_server_ts as processed_at,
+                      0::BIGINT as not_before, 'pending' as status, null as reference_image,
+                      'self' as origin, stream_ordering, redacts
+            ",
+        )
+        .bind(&params.event_id)
+        .bind(&params.room_id)
+        .bind(&params.user_id)
+        .bind(&params.user_id)
+        .bind(&params.event_type)
+        .bind(&params.content)
+        .bind(params.state_key.as_deref())
+        .bind(params.origin_server_ts)
+        .bind(params.redacts.as_deref())
+        .bind(depth)
+        .bind(&prev_events_json)
+        .bind(&auth_events_json)
+        .bind(kind.as_str())
+        .bind(reason)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        if !prev_events.is_empty() {
+            sqlx::query(
+                r"
+                INSERT INTO event_edges (event_id, prev_event_id, is_state)
+                SELECT $1, unnest($2::text[]), false
+                ON CONFLICT DO NOTHING
+                ",
+            )
+            .bind(&params.event_id)
+            .bind(prev_events)
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(event)
+    }
+
+    /// Soft-failed events of a room, newest first, starting below the
+    /// `before` stream position.
+    pub async fn get_soft_failed_events(
+        &self,
+        room_id: &str,
+        kind: Option<SoftFailKind>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<SoftFailedEvent>, sqlx::Error> {
+        sqlx::query_as(
+            r"
+            SELECT event_id, event_type, sender, state_key, COALESCE(depth, 0) AS depth, origin_server_ts,
+                   stream_ordering, soft_fail_kind, soft_fail_reason
+            FROM events
+            WHERE purged_at IS NULL AND soft_failed AND room_id = $1
+              AND ($2::TEXT IS NULL OR soft_fail_kind = $2)
+              AND ($3::BIGINT IS NULL OR stream_ordering < $3)
+            ORDER BY stream_ordering DESC
+            LIMIT $4
+            ",
+        )
+        .bind(room_id)
+        .bind(kind.map(|kind| kind.as_str()))
+        .bind(before)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Number of soft-failed events in a room per `soft_fail_kind`.
+    pub async fn count_soft_failed_events(&self, room_id: &str) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        sqlx::query_as(
+            r"
+            SELECT soft_fail_kind, COUNT(*)
+            FROM events
+            WHERE purged_at IS NULL AND soft_failed AND room_id = $1
+            GROUP BY soft_fail_kind
+            ORDER BY soft_fail_kind
+            ",
+        )
+        .bind(room_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+}
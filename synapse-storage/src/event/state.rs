@@ -25,7 +25,7 @@ impl EventStorage {
         sqlx::query_as::<_, StateEvent>(&format!(
             "SELECT {STATE_EVENT_OUTER_COLS} \
              FROM events \
-             WHERE room_id = $1 \
+             WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 \
                AND event_type = $2 \
                AND state_key = $3 \
                AND state_key IS NOT NULL \
@@ -46,7 +46,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (event_type, state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = $1 \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 \
                    AND state_key IS NOT NULL \
                  ORDER BY event_type, state_key, origin_server_ts DESC \
              ) s \
@@ -68,7 +68,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (event_type, state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = $1 \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 \
                    AND state_key IS NOT NULL \
                    AND origin_server_ts <= $2 \
                  ORDER BY event_type, state_key, origin_server_ts DESC, event_id DESC \
@@ -81,6 +81,34 @@ impl EventStorage {
         .await
     }
 
+    /// Room state at `event`, including `event` itself when it is a state
+    /// event. Resolved from the room's state groups; events older than the
+    /// room's first group fall back to a scan of the room's state events.
+    pub async fn get_state_events_at_event(&self, event: &RoomEvent) -> Result<Vec<StateEvent>, sqlx::Error> {
+        let group = match event.stream_ordering {
+            Some(stream_ordering) => {
+                crate::state_groups::state_group_at(&*self.pool, &event.room_id, stream_ordering).await?
+            }
+            None => None,
+        };
+        let Some(group) = group else {
+            return self.get_state_events_at_or_before(&event.room_id, event.origin_server_ts).await;
+        };
+
+        sqlx::query_as::<_, StateEvent>(&format!(
+            "SELECT {STATE_EVENT_OUTER_COLS} \
+             FROM events \
+             WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $2 \
+               AND event_id IN (SELECT event_id FROM ({}) resolved) \
+             ORDER BY origin_server_ts DESC, event_id ASC",
+            crate::state_groups::RESOLVE_STATE_GROUP_SQL
+        ))
+        .bind(group)
+        .bind(&event.room_id)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
     pub async fn get_state_events_by_type(
         &self,
         room_id: &str,
@@ -92,7 +120,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = $1 \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $1 \
                    AND event_type = $2 \
                    AND state_key IS NOT NULL \
                  ORDER BY state_key, origin_server_ts DESC \
@@ -121,7 +149,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (room_id, state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = ANY($1) \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
                    AND event_type = $2 \
                    AND state_key IS NOT NULL \
                  ORDER BY room_id, state_key, origin_server_ts DESC \
@@ -137,15 +165,80 @@ impl EventStorage {
         Ok(Self::group_state_events(room_ids, events))
     }
 
+    /// The current `m.room.member` event of `user_id` in each room.
+    pub async fn get_member_events_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<std::collections::HashMap<String, StateEvent>, sqlx::Error> {
+        if room_ids.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+
+        let events: Vec<StateEvent> = sqlx::query_as(&format!(
+            "SELECT {STATE_EVENT_OUTER_COLS} \
+             FROM ( \
+                 SELECT DISTINCT ON (room_id) \
+                        {STATE_EVENT_INNER_COLS} \
+                 FROM events \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
+                   AND event_type = 'm.room.member' \
+                   AND state_key = $2 \
+                 ORDER BY room_id, stream_ordering DESC \
+             ) s"
+        ))
+        .bind(room_ids)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(events.into_iter().map(|event| (event.room_id.clone(), event)).collect())
+    }
+
+    /// Every `m.room.history_visibility` event and every `m.room.member` event
+    /// of `user_id` in each room, oldest first: the inputs for deciding which
+    /// timeline events `user_id` may see.
+    pub async fn get_visibility_history_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<std::collections::HashMap<String, Vec<StateEvent>>, sqlx::Error> {
+        if room_ids.is_empty() {
+            return Ok(std::collections::HashMap::new());
+        }
+
+        let events: Vec<StateEvent> = sqlx::query_as(&format!(
+            "SELECT {STATE_EVENT_OUTER_COLS} \
+             FROM events \
+             WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
+               AND state_key IS NOT NULL \
+               AND (event_type = 'm.room.history_visibility' \
+                    OR (event_type = 'm.room.member' AND state_key = $2)) \
+             ORDER BY room_id, stream_ordering ASC, origin_server_ts ASC"
+        ))
+        .bind(room_ids)
+        .bind(user_id)
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(Self::group_state_events(room_ids, events))
+    }
+
+    /// Latest state per `(event_type, state_key)` changed after `since`, per
+    /// room. A room's entry in `until` is the `stream_ordering` of its first
+    /// timeline event: state from there on is already in the timeline, so the
+    /// delta stops before it. Rooms without an entry are unbounded.
     pub async fn get_state_events_since_batch(
         &self,
         room_ids: &[String],
         since: SinceFilter,
+        until: &std::collections::HashMap<String, i64>,
     ) -> Result<std::collections::HashMap<String, Vec<StateEvent>>, sqlx::Error> {
         if room_ids.is_empty() {
             return Ok(std::collections::HashMap::new());
         }
 
+        let until_streams: Vec<Option<i64>> = room_ids.iter().map(|room_id| until.get(room_id).copied()).collect();
         let col = since.column();
         let events: Vec<StateEvent> = sqlx::query_as(&format!(
             "SELECT {STATE_EVENT_OUTER_COLS} \
@@ -153,9 +246,10 @@ impl EventStorage {
                  SELECT DISTINCT ON (room_id, event_type, state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = ANY($1) \
-                   AND state_key IS NOT NULL \
+                 JOIN UNNEST($1::TEXT[], $3::BIGINT[]) AS bounds(room_id, until_stream) USING (room_id) \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND state_key IS NOT NULL \
                    AND {col} > $2 \
+                   AND (bounds.until_stream IS NULL OR stream_ordering < bounds.until_stream) \
                  ORDER BY room_id, event_type, state_key, {col} DESC \
              ) s \
              ORDER BY room_id, {col} DESC \
@@ -163,6 +257,7 @@ impl EventStorage {
         ))
         .bind(room_ids)
         .bind(since.value())
+        .bind(&until_streams)
         .fetch_all(&*self.pool)
         .await?;
 
@@ -182,7 +277,7 @@ impl EventStorage {
         let rows: Vec<(String, i64)> = sqlx::query_as(&format!(
             "SELECT room_id, MAX(origin_server_ts) AS latest_ts \
              FROM events \
-             WHERE room_id = ANY($1) \
+             WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
                AND state_key IS NOT NULL \
                AND {col} > $2 \
              GROUP BY room_id"
@@ -209,7 +304,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (room_id, event_type, state_key) \
                         {STATE_EVENT_INNER_COLS} \
                  FROM events \
-                 WHERE room_id = ANY($1) \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
                    AND state_key IS NOT NULL \
                  ORDER BY room_id, event_type, state_key, origin_server_ts DESC \
              ) s \
@@ -254,7 +349,7 @@ impl EventStorage {
                  SELECT DISTINCT ON (room_id, state_key) \
                         room_id, state_key \
                  FROM events \
-                 WHERE room_id = ANY($1) \
+                 WHERE purged_at IS NULL AND NOT soft_failed AND room_id = ANY($1) \
                    AND {col} > $2 \
                    AND event_type = 'm.room.member' \
                    AND state_key IS NOT NULL \
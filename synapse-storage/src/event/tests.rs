@@ -219,3 +219,82 @@ fn test_state_event_with_is_redacted() {
     assert!(event.is_redacted.unwrap_or(false));
     assert!(event.unsigned.is_some());
 }
+
+fn exported_pdu() -> ExportedPdu {
+    ExportedPdu {
+        event_id: "$event:example.com".to_string(),
+        room_id: "!room:example.com".to_string(),
+        sender: "@alice:example.com".to_string(),
+        event_type: "m.room.member".to_string(),
+        content: json!({"membership": "join"}),
+        state_key: Some("@alice:example.com".to_string()),
+        depth: 3,
+        origin_server_ts: 1234567890,
+        origin: "self".to_string(),
+        prev_events: Some(json!(["$prev:example.com"])),
+        auth_events: Some(json!(["$create:example.com"])),
+        signatures: Some(json!({"example.com": {"ed25519:a": "sig"}})),
+        hashes: Some(json!({"sha256": "hash"})),
+        unsigned: Some(json!({})),
+        redacts: None,
+        stream_ordering: 7,
+    }
+}
+
+#[test]
+fn test_exported_pdu_carries_graph_and_signatures() {
+    let pdu = exported_pdu().to_pdu("example.com");
+
+    assert_eq!(pdu["origin"], "example.com");
+    assert_eq!(pdu["type"], "m.room.member");
+    assert_eq!(pdu["state_key"], "@alice:example.com");
+    assert_eq!(pdu["depth"], 3);
+    assert_eq!(pdu["prev_events"], json!(["$prev:example.com"]));
+    assert_eq!(pdu["auth_events"], json!(["$create:example.com"]));
+    assert_eq!(pdu["signatures"]["example.com"]["ed25519:a"], "sig");
+    assert_eq!(pdu["hashes"]["sha256"], "hash");
+    assert!(pdu.get("unsigned").is_none());
+    assert!(pdu.get("redacts").is_none());
+}
+
+#[test]
+fn test_exported_pdu_without_stored_graph_emits_empty_fields() {
+    let mut exported = exported_pdu();
+    exported.origin = "remote.example.org".to_string();
+    exported.state_key = None;
+    exported.prev_events = None;
+    exported.auth_events = None;
+    exported.signatures = None;
+    exported.hashes = None;
+
+    let pdu = exported.to_pdu("example.com");
+
+    assert_eq!(pdu["origin"], "remote.example.org");
+    assert!(pdu.get("state_key").is_none());
+    assert_eq!(pdu["prev_events"], json!([]));
+    assert_eq!(pdu["auth_events"], json!([]));
+    assert_eq!(pdu["signatures"], json!({}));
+    assert_eq!(pdu["hashes"], json!({}));
+}
+
+#[test]
+fn test_soft_fail_kind_round_trips() {
+    for kind in [SoftFailKind::Auth, SoftFailKind::Spam] {
+        assert_eq!(kind.as_str().parse::<SoftFailKind>(), Ok(kind));
+    }
+    assert!("rejected".parse::<SoftFailKind>().is_err());
+}
+
+#[test]
+fn test_rejection_reason_round_trips() {
+    for reason in [
+        RejectionReason::TooLarge,
+        RejectionReason::ContentHash,
+        RejectionReason::Signature,
+        RejectionReason::Malformed,
+        RejectionReason::Auth,
+    ] {
+        assert_eq!(reason.as_str().parse::<RejectionReason>(), Ok(reason));
+    }
+    assert!("spam".parse::<RejectionReason>().is_err());
+}
@@ -20,7 +20,7 @@ impl EventStorage {
                 SELECT DISTINCT ON (event_type, state_key)
                     event_type, state_key, content, sender, origin_server_ts
                 FROM events
-                WHERE room_id = $2 AND state_key IS NOT NULL
+                WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $2 AND state_key IS NOT NULL
                 ORDER BY event_type, state_key, origin_server_ts DESC
             ) sub
             ON CONFLICT (room_id, type, state_key) DO UPDATE SET
@@ -39,6 +39,31 @@ pub trait EventWriter: Send + Sync {
         tx: Option<&mut sqlx::Transaction<'_, sqlx::Postgres>>,
     ) -> Result<RoomEvent, sqlx::Error>;
 
+    /// Like `create_event_with_graph`, for an event that is stored
+    /// soft-failed: hidden from clients and kept out of the current state.
+    #[allow(clippy::too_many_arguments)]
+    async fn create_soft_failed_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        kind: SoftFailKind,
+        reason: &str,
+    ) -> Result<RoomEvent, sqlx::Error>;
+
+    /// Records an inbound PDU rejected before persistence, keeping only the
+    /// most recent `max_rows` records. A non-positive `max_rows` records
+    /// nothing.
+    async fn record_rejected_event(&self, params: RejectedEventParams, max_rows: i64) -> Result<(), sqlx::Error>;
+
+    async fn record_local_event_graph(
+        &self,
+        room_id: &str,
+        event_id: &str,
+        prev_events: &[String],
+    ) -> Result<i64, sqlx::Error>;
+
     #[allow(clippy::too_many_arguments)]
     async fn save_event_signature(
         &self,
@@ -141,6 +166,31 @@ impl crate::event::writer::EventWriter for super::EventStorage {
         self.create_event_with_graph(params, prev_events, auth_events, depth, tx).await
     }
 
+    async fn record_local_event_graph(
+        &self,
+        room_id: &str,
+        event_id: &str,
+        prev_events: &[String],
+    ) -> Result<i64, sqlx::Error> {
+        self.record_local_event_graph(room_id, event_id, prev_events).await
+    }
+
+    async fn create_soft_failed_event_with_graph(
+        &self,
+        params: CreateEventParams,
+        prev_events: &[String],
+        auth_events: &[String],
+        depth: i64,
+        kind: SoftFailKind,
+        reason: &str,
+    ) -> Result<RoomEvent, sqlx::Error> {
+        self.create_soft_failed_event_with_graph(params, prev_events, auth_events, depth, kind, reason).await
+    }
+
+    async fn record_rejected_event(&self, params: RejectedEventParams, max_rows: i64) -> Result<(), sqlx::Error> {
+        self.record_rejected_event(params, max_rows).await
+    }
+
     async fn save_event_signature(
         &self,
         event_id: &str,
@@ -24,11 +24,14 @@ pub mod audit;
 /// Auth storage domain group — re-exports auth modules under `auth::`.
 pub mod auth;
 pub mod background_update;
+pub mod column_encryption;
 pub mod dehydrated_device;
+pub mod delayed_event;
 pub mod device;
 /// E2EE storage domain group — re-exports e2ee modules under `e2ee::`.
 pub mod e2ee;
 pub mod e2ee_audit;
+pub mod email_retry;
 pub mod email_verification;
 pub mod event;
 pub mod event_report;
@@ -48,6 +51,7 @@ pub mod module;
 pub mod monitoring;
 /// OIDC storage domain group — re-exports oidc modules under `oidc::`.
 pub mod oidc;
+pub mod online_migration;
 pub mod openid_token;
 pub mod performance;
 /// Backward-compatibility prelude — glob-import point for domain-grouped types.
@@ -70,8 +74,10 @@ pub mod room_tag;
 pub mod schema_health_check;
 pub mod schema_validator;
 pub mod search_index;
+pub mod server_identity;
 pub mod sliding_sync;
 pub mod space;
+pub mod state_compaction;
 pub mod state_groups;
 pub mod sticky_event;
 /// Sync storage domain group — re-exports sync modules under `sync::`.
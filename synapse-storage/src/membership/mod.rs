@@ -358,7 +358,7 @@ impl RoomMemberStorage {
                 r"
                 SELECT room_id, membership
                 FROM room_memberships
-                WHERE user_id = $1 AND membership IN ('join', 'leave')
+                WHERE user_id = $1 AND membership IN ('join', 'invite', 'knock', 'leave', 'ban')
                 ORDER BY updated_ts DESC NULLS LAST, room_id ASC
                 ",
             )
@@ -370,7 +370,7 @@ impl RoomMemberStorage {
                 r"
                 SELECT room_id, membership
                 FROM room_memberships
-                WHERE user_id = $1 AND membership = 'join'
+                WHERE user_id = $1 AND membership IN ('join', 'invite', 'knock')
                 ORDER BY updated_ts DESC NULLS LAST, room_id ASC
                 ",
             )
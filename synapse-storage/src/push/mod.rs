@@ -63,7 +63,7 @@ pub trait PushStoreApi: Send + Sync {
         kind: &str,
         rule_id: &str,
         actions: &Value,
-    ) -> Result<(), sqlx::Error>;
+    ) -> Result<u64, sqlx::Error>;
 
     async fn get_push_rule_enabled(
         &self,
@@ -80,7 +80,7 @@ pub trait PushStoreApi: Send + Sync {
         kind: &str,
         rule_id: &str,
         enabled: bool,
-    ) -> Result<(), sqlx::Error>;
+    ) -> Result<u64, sqlx::Error>;
 
     async fn get_user_push_rules(
         &self,
@@ -195,9 +195,9 @@ impl PushStorage {
         sqlx::query(
             "INSERT INTO push_rules (user_id, scope, kind, rule_id, pattern, conditions, actions, \
              is_enabled, is_default, priority_class, created_ts) \
-             VALUES ($1, $2, $3, $4, $5, $6, $7, true, false, 5, $8) \
+             VALUES ($1, $2, $3, $4, $5, COALESCE($6, '[]'::jsonb), $7, true, false, 5, $8) \
              ON CONFLICT (user_id, scope, kind, rule_id) DO UPDATE SET \
-             pattern = $5, conditions = $6, actions = $7",
+             pattern = $5, conditions = COALESCE($6, '[]'::jsonb), actions = $7, updated_ts = $8",
         )
         .bind(user_id)
         .bind(scope)
@@ -237,8 +237,8 @@ impl PushStorage {
         kind: &str,
         rule_id: &str,
         actions: &Value,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
             "UPDATE push_rules SET actions = $4 WHERE user_id = $1 AND scope = $2 AND kind = $3 AND rule_id = $5",
         )
         .bind(user_id)
@@ -248,7 +248,7 @@ impl PushStorage {
         .bind(rule_id)
         .execute(&*self.pool)
         .await?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
     pub async fn get_push_rule_enabled(
@@ -276,8 +276,8 @@ impl PushStorage {
         kind: &str,
         rule_id: &str,
         enabled: bool,
-    ) -> Result<(), sqlx::Error> {
-        sqlx::query(
+    ) -> Result<u64, sqlx::Error> {
+        let result = sqlx::query(
             "UPDATE push_rules SET is_enabled = $4 WHERE user_id = $1 AND scope = $2 AND kind = $3 AND rule_id = $5",
         )
         .bind(user_id)
@@ -287,7 +287,7 @@ impl PushStorage {
         .bind(rule_id)
         .execute(&*self.pool)
         .await?;
-        Ok(())
+        Ok(result.rows_affected())
     }
 
     pub async fn get_user_push_rules(
@@ -419,7 +419,7 @@ impl PushStoreApi for PushStorage {
         kind: &str,
         rule_id: &str,
         actions: &Value,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<u64, sqlx::Error> {
         self.update_push_rule_actions(user_id, scope, kind, rule_id, actions).await
     }
 
@@ -440,7 +440,7 @@ impl PushStoreApi for PushStorage {
         kind: &str,
         rule_id: &str,
         enabled: bool,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<u64, sqlx::Error> {
         self.set_push_rule_enabled(user_id, scope, kind, rule_id, enabled).await
     }
 
@@ -387,7 +387,7 @@ impl PushNotificationStorage {
     pub async fn get_user_push_rules(&self, user_id: &str) -> Result<Vec<PushRule>, ApiError> {
         let rows = sqlx::query_as::<_, PushRule>(
             r"
-            SELECT id, user_id, rule_id, scope, kind, priority, priority_class, conditions, actions, is_enabled, is_default, created_ts, updated_ts, pattern FROM push_rules
+            SELECT id, user_id, rule_id, scope, kind, priority, priority_class, COALESCE(conditions, '[]'::jsonb) AS conditions, actions, is_enabled, is_default, created_ts, updated_ts, pattern FROM push_rules
             WHERE (user_id = $1 OR user_id = '.default') AND is_enabled = true
             ORDER BY priority ASC
             ",
@@ -1,4 +1,6 @@
 use async_trait::async_trait;
+use chrono::NaiveDate;
+use std::collections::HashMap;
 use std::sync::Arc;
 
 use sqlx::PgPool;
@@ -21,6 +23,17 @@ pub trait RateLimitStoreApi: Send + Sync {
         burst_count: i32,
     ) -> Result<(), sqlx::Error>;
     async fn delete_user_rate_limit(&self, user_id: &str) -> Result<(), sqlx::Error>;
+    /// Adds `amount` to the user's `kind` counter for `day` and returns the new total.
+    async fn increment_daily_quota(
+        &self,
+        user_id: &str,
+        day: NaiveDate,
+        kind: &str,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error>;
+    /// The user's counters for `day`, keyed by kind.
+    async fn get_daily_quota_usage(&self, user_id: &str, day: NaiveDate) -> Result<HashMap<String, i64>, sqlx::Error>;
+    async fn delete_daily_quota_usage(&self, user_id: &str) -> Result<(), sqlx::Error>;
 }
 
 // ── Postgres implementation ─────────────────────────────────────────────
@@ -83,6 +96,62 @@ impl RateLimitStorage {
         .await?;
         Ok(())
     }
+
+    pub async fn increment_daily_quota(
+        &self,
+        user_id: &str,
+        day: NaiveDate,
+        kind: &str,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        sqlx::query_scalar::<_, i64>(
+            r"
+            INSERT INTO user_daily_quotas (user_id, day, kind, count)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (user_id, day, kind) DO UPDATE
+            SET count = user_daily_quotas.count + EXCLUDED.count
+            RETURNING count
+            ",
+        )
+        .bind(user_id)
+        .bind(day)
+        .bind(kind)
+        .bind(amount)
+        .fetch_one(self.pool.as_ref())
+        .await
+    }
+
+    pub async fn get_daily_quota_usage(
+        &self,
+        user_id: &str,
+        day: NaiveDate,
+    ) -> Result<HashMap<String, i64>, sqlx::Error> {
+        let rows = sqlx::query_as::<_, (String, i64)>(
+            r"
+            SELECT kind, count
+            FROM user_daily_quotas
+            WHERE user_id = $1 AND day = $2
+            ",
+        )
+        .bind(user_id)
+        .bind(day)
+        .fetch_all(self.pool.as_ref())
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    pub async fn delete_daily_quota_usage(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r"
+            DELETE FROM user_daily_quotas
+            WHERE user_id = $1
+            ",
+        )
+        .bind(user_id)
+        .execute(self.pool.as_ref())
+        .await?;
+        Ok(())
+    }
 }
 
 // ── Trait delegation ────────────────────────────────────────────────────
@@ -105,4 +174,22 @@ impl RateLimitStoreApi for RateLimitStorage {
     async fn delete_user_rate_limit(&self, user_id: &str) -> Result<(), sqlx::Error> {
         self.delete_user_rate_limit(user_id).await
     }
+
+    async fn increment_daily_quota(
+        &self,
+        user_id: &str,
+        day: NaiveDate,
+        kind: &str,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        self.increment_daily_quota(user_id, day, kind, amount).await
+    }
+
+    async fn get_daily_quota_usage(&self, user_id: &str, day: NaiveDate) -> Result<HashMap<String, i64>, sqlx::Error> {
+        self.get_daily_quota_usage(user_id, day).await
+    }
+
+    async fn delete_daily_quota_usage(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        self.delete_daily_quota_usage(user_id).await
+    }
 }
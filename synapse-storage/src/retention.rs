@@ -266,11 +266,13 @@ impl RetentionStorage {
         })
     }
 
+    /// Tombstone expired events; the maintenance vacuum deletes them later.
     pub async fn delete_events_before(&self, room_id: &str, cutoff_ts: i64) -> Result<i64, sqlx::Error> {
         let result = sqlx::query(
             r"
-            DELETE FROM events
-            WHERE room_id = $1
+            UPDATE events SET purged_at = $3
+            WHERE purged_at IS NULL
+            AND room_id = $1
             AND origin_server_ts < $2
             AND event_type NOT IN ('m.room.create', 'm.room.power_levels', 'm.room.join_rules', 'm.room.history_visibility')
             AND state_key IS NULL
@@ -278,6 +280,7 @@ impl RetentionStorage {
         )
         .bind(room_id)
         .bind(cutoff_ts)
+        .bind(current_timestamp_millis())
         .execute(&*self.pool)
         .await?;
 
@@ -443,6 +443,126 @@ impl RoomStorage {
         Ok(true)
     }
 
+    /// Queue `room_id` for directory publication. A reviewed request for the
+    /// same room is reopened as `pending` on behalf of the new requester.
+    pub async fn upsert_publication_request(
+        &self,
+        room_id: &str,
+        requested_by: &str,
+    ) -> Result<RoomPublicationRequest, sqlx::Error> {
+        sqlx::query_as::<_, RoomPublicationRequest>(
+            r"
+            INSERT INTO room_directory_publication_requests (room_id, requested_by, requested_ts, status)
+            VALUES ($1, $2, $3, 'pending')
+            ON CONFLICT (room_id) DO UPDATE SET
+                requested_by = EXCLUDED.requested_by,
+                requested_ts = EXCLUDED.requested_ts,
+                status = 'pending',
+                reviewed_by = NULL,
+                reviewed_ts = NULL,
+                reason = NULL
+            RETURNING room_id, requested_by, requested_ts, status, reviewed_by, reviewed_ts, reason
+            ",
+        )
+        .bind(room_id)
+        .bind(requested_by)
+        .bind(current_timestamp_millis())
+        .fetch_one(&*self.pool)
+        .await
+    }
+
+    pub async fn get_publication_request(&self, room_id: &str) -> Result<Option<RoomPublicationRequest>, sqlx::Error> {
+        sqlx::query_as::<_, RoomPublicationRequest>(
+            r"
+            SELECT room_id, requested_by, requested_ts, status, reviewed_by, reviewed_ts, reason
+            FROM room_directory_publication_requests
+            WHERE room_id = $1
+            ",
+        )
+        .bind(room_id)
+        .fetch_optional(&*self.pool)
+        .await
+    }
+
+    /// Oldest requests first, so the queue is worked in arrival order.
+    pub async fn list_publication_requests(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<RoomPublicationRequest>, sqlx::Error> {
+        sqlx::query_as::<_, RoomPublicationRequest>(
+            r"
+            SELECT room_id, requested_by, requested_ts, status, reviewed_by, reviewed_ts, reason
+            FROM room_directory_publication_requests
+            WHERE status = $1
+            ORDER BY requested_ts ASC, room_id ASC
+            LIMIT $2
+            ",
+        )
+        .bind(status)
+        .bind(limit)
+        .fetch_all(&*self.pool)
+        .await
+    }
+
+    /// Close a pending request. Approval lists the room in the same
+    /// transaction. Returns `None` when there is no pending request.
+    pub async fn review_publication_request(
+        &self,
+        room_id: &str,
+        approve: bool,
+        reviewed_by: &str,
+        reason: Option<&str>,
+    ) -> Result<Option<RoomPublicationRequest>, sqlx::Error> {
+        let now = current_timestamp_millis();
+        let mut tx = self.pool.begin().await?;
+
+        let reviewed = sqlx::query_as::<_, RoomPublicationRequest>(
+            r"
+            UPDATE room_directory_publication_requests
+            SET status = $2, reviewed_by = $3, reviewed_ts = $4, reason = $5
+            WHERE room_id = $1 AND status = 'pending'
+            RETURNING room_id, requested_by, requested_ts, status, reviewed_by, reviewed_ts, reason
+            ",
+        )
+        .bind(room_id)
+        .bind(if approve { "approved" } else { "rejected" })
+        .bind(reviewed_by)
+        .bind(now)
+        .bind(reason)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        if reviewed.is_some() && approve {
+            sqlx::query(
+                r"
+                INSERT INTO room_directory (room_id, is_public, added_ts)
+                VALUES ($1, true, $2)
+                ON CONFLICT (room_id) DO UPDATE SET is_public = true
+                ",
+            )
+            .bind(room_id)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+            sqlx::query("UPDATE rooms SET is_public = true WHERE room_id = $1").bind(room_id).execute(&mut *tx).await?;
+        }
+
+        tx.commit().await?;
+        Ok(reviewed)
+    }
+
+    /// Drop a still-pending request, e.g. when the room is made private again
+    /// before an admin got to it.
+    pub async fn withdraw_publication_request(&self, room_id: &str) -> Result<bool, sqlx::Error> {
+        let result =
+            sqlx::query("DELETE FROM room_directory_publication_requests WHERE room_id = $1 AND status = 'pending'")
+                .bind(room_id)
+                .execute(&*self.pool)
+                .await?;
+        Ok(result.rows_affected() > 0)
+    }
+
     pub async fn get_room_version_only(&self, room_id: &str) -> Result<Option<String>, sqlx::Error> {
         let result: Option<(String,)> = sqlx::query_as(r"SELECT room_version FROM rooms WHERE room_id = $1")
             .bind(room_id)
@@ -99,6 +99,10 @@ pub trait RoomStoreApi: Send + Sync {
         data: &serde_json::Value,
     ) -> Result<(), sqlx::Error>;
 
+    /// Upsert many receipts of `room_id` at once, the last one per user and
+    /// receipt type winning.
+    async fn add_receipts(&self, room_id: &str, receipts: &[Receipt]) -> Result<(), sqlx::Error>;
+
     async fn get_receipts(
         &self,
         room_id: &str,
@@ -176,6 +180,32 @@ pub trait RoomStoreApi: Send + Sync {
 
     async fn set_room_private_with_directory(&self, room_id: &str) -> Result<bool, sqlx::Error>;
 
+    // ── directory publication moderation ─────────────────────────────────
+
+    async fn upsert_publication_request(
+        &self,
+        room_id: &str,
+        requested_by: &str,
+    ) -> Result<RoomPublicationRequest, sqlx::Error>;
+
+    async fn get_publication_request(&self, room_id: &str) -> Result<Option<RoomPublicationRequest>, sqlx::Error>;
+
+    async fn list_publication_requests(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<RoomPublicationRequest>, sqlx::Error>;
+
+    async fn review_publication_request(
+        &self,
+        room_id: &str,
+        approve: bool,
+        reviewed_by: &str,
+        reason: Option<&str>,
+    ) -> Result<Option<RoomPublicationRequest>, sqlx::Error>;
+
+    async fn withdraw_publication_request(&self, room_id: &str) -> Result<bool, sqlx::Error>;
+
     async fn get_room_version_only(&self, room_id: &str) -> Result<Option<String>, sqlx::Error>;
 
     async fn search_all_rooms_admin(
@@ -327,6 +357,10 @@ impl RoomStoreApi for super::RoomStorage {
         self.add_receipt(user_id, sent_to, room_id, event_id, receipt_type, data).await
     }
 
+    async fn add_receipts(&self, room_id: &str, receipts: &[Receipt]) -> Result<(), sqlx::Error> {
+        self.add_receipts(room_id, receipts).await
+    }
+
     async fn get_receipts(
         &self,
         room_id: &str,
@@ -442,6 +476,40 @@ impl RoomStoreApi for super::RoomStorage {
         self.set_room_private_with_directory(room_id).await
     }
 
+    async fn upsert_publication_request(
+        &self,
+        room_id: &str,
+        requested_by: &str,
+    ) -> Result<RoomPublicationRequest, sqlx::Error> {
+        self.upsert_publication_request(room_id, requested_by).await
+    }
+
+    async fn get_publication_request(&self, room_id: &str) -> Result<Option<RoomPublicationRequest>, sqlx::Error> {
+        self.get_publication_request(room_id).await
+    }
+
+    async fn list_publication_requests(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<RoomPublicationRequest>, sqlx::Error> {
+        self.list_publication_requests(status, limit).await
+    }
+
+    async fn review_publication_request(
+        &self,
+        room_id: &str,
+        approve: bool,
+        reviewed_by: &str,
+        reason: Option<&str>,
+    ) -> Result<Option<RoomPublicationRequest>, sqlx::Error> {
+        self.review_publication_request(room_id, approve, reviewed_by, reason).await
+    }
+
+    async fn withdraw_publication_request(&self, room_id: &str) -> Result<bool, sqlx::Error> {
+        self.withdraw_publication_request(room_id).await
+    }
+
     async fn get_room_version_only(&self, room_id: &str) -> Result<Option<String>, sqlx::Error> {
         self.get_room_version_only(room_id).await
     }
@@ -1056,6 +1056,74 @@ impl RoomStorage {
         Ok(())
     }
 
+    /// Upsert many receipts of `room_id` in one round trip, replacing each
+    /// user's previous receipt of the same type. When `receipts` holds several
+    /// for the same user and type, the last one wins.
+    pub async fn add_receipts(&self, room_id: &str, receipts: &[Receipt]) -> Result<(), sqlx::Error> {
+        let mut latest: std::collections::HashMap<(&str, &str), &Receipt> =
+            std::collections::HashMap::with_capacity(receipts.len());
+        for receipt in receipts {
+            latest.insert((receipt.user_id.as_str(), receipt.receipt_type.as_str()), receipt);
+        }
+        if latest.is_empty() {
+            return Ok(());
+        }
+
+        let len = latest.len();
+        let (mut user_ids, mut receipt_types, mut event_ids, mut tss, mut datas) = (
+            Vec::with_capacity(len),
+            Vec::with_capacity(len),
+            Vec::with_capacity(len),
+            Vec::with_capacity(len),
+            Vec::with_capacity(len),
+        );
+        for receipt in latest.into_values() {
+            user_ids.push(receipt.user_id.as_str());
+            receipt_types.push(receipt.receipt_type.as_str());
+            event_ids.push(receipt.event_id.as_str());
+            tss.push(receipt.ts);
+            datas.push(if receipt.data.is_object() { receipt.data.clone() } else { json!({}) });
+        }
+
+        let mut tx = self.pool.begin().await?;
+        sqlx::query(
+            r"
+            DELETE FROM event_receipts r
+            USING UNNEST($2::text[], $3::text[], $4::text[]) AS n(user_id, receipt_type, event_id)
+            WHERE r.room_id = $1
+              AND r.user_id = n.user_id
+              AND r.receipt_type = n.receipt_type
+              AND r.event_id <> n.event_id
+            ",
+        )
+        .bind(room_id)
+        .bind(&user_ids)
+        .bind(&receipt_types)
+        .bind(&event_ids)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query(
+            r"
+            INSERT INTO event_receipts (event_id, room_id, user_id, receipt_type, ts, data, created_ts, updated_ts)
+            SELECT n.event_id, $1, n.user_id, n.receipt_type, n.ts, n.data, n.ts, n.ts
+            FROM UNNEST($2::text[], $3::text[], $4::text[], $5::bigint[], $6::jsonb[])
+                AS n(user_id, receipt_type, event_id, ts, data)
+            ON CONFLICT (event_id, room_id, user_id, receipt_type) DO UPDATE
+            SET ts = EXCLUDED.ts, data = EXCLUDED.data, updated_ts = EXCLUDED.updated_ts
+            ",
+        )
+        .bind(room_id)
+        .bind(&user_ids)
+        .bind(&receipt_types)
+        .bind(&event_ids)
+        .bind(&tss)
+        .bind(&datas)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
     pub async fn get_receipts(
         &self,
         room_id: &str,
@@ -1326,6 +1394,35 @@ mod db_tests {
         let _ = storage.delete_room(&room_id).await;
     }
 
+    #[tokio::test]
+    async fn test_publication_request_review_lists_room_once() {
+        let pool = test_pool().await;
+        let storage = RoomStorage::new(&pool);
+        let room_id = format!("!pub_req_test_{}:example.com", uuid::Uuid::new_v4());
+        storage.create_room(&room_id, "@c:example.com", "invite", "10", false).await.unwrap();
+
+        let request = storage.upsert_publication_request(&room_id, "@c:example.com").await.unwrap();
+        assert_eq!(request.status, "pending");
+        let pending = storage.list_publication_requests("pending", 1000).await.unwrap();
+        assert!(pending.iter().any(|r| r.room_id == room_id));
+        assert!(!storage.is_room_in_directory(&room_id).await.unwrap());
+
+        let reviewed = storage
+            .review_publication_request(&room_id, true, "@admin:example.com", None)
+            .await
+            .unwrap()
+            .expect("pending request should be reviewed");
+        assert_eq!(reviewed.status, "approved");
+        assert_eq!(reviewed.reviewed_by.as_deref(), Some("@admin:example.com"));
+        assert!(storage.is_room_in_directory(&room_id).await.unwrap());
+
+        let again = storage.review_publication_request(&room_id, false, "@admin:example.com", None).await.unwrap();
+        assert!(again.is_none());
+        assert!(!storage.withdraw_publication_request(&room_id).await.unwrap());
+
+        let _ = storage.delete_room(&room_id).await;
+    }
+
     #[tokio::test]
     async fn test_delete_room() {
         let pool = test_pool().await;
@@ -1762,6 +1859,43 @@ mod db_tests {
         sqlx::query("DELETE FROM event_receipts WHERE user_id = $1").bind(&user_id).execute(&*pool).await.unwrap();
     }
 
+    #[tokio::test]
+    async fn test_add_receipts_upserts_batch_and_replaces_previous() {
+        let pool = test_pool().await;
+        let storage = RoomStorage::new(&pool);
+        let room_id = format!("!rcpt3_{}:example.com", uuid::Uuid::new_v4());
+        let alice = format!("@rcpt3_a_{}:example.com", uuid::Uuid::new_v4());
+        let bob = format!("@rcpt3_b_{}:example.com", uuid::Uuid::new_v4());
+        let event1 = format!("$evt1_{}:example.com", uuid::Uuid::new_v4());
+        let event2 = format!("$evt2_{}:example.com", uuid::Uuid::new_v4());
+        let receipt = |user_id: &str, event_id: &str, ts: i64| Receipt {
+            user_id: user_id.to_string(),
+            event_id: event_id.to_string(),
+            receipt_type: "m.read".to_string(),
+            ts,
+            data: json!({ "ts": ts }),
+        };
+
+        storage.add_receipt(&alice, "server", &room_id, &event1, "m.read", &json!({})).await.unwrap();
+        storage
+            .add_receipts(
+                &room_id,
+                &[receipt(&alice, &event2, 10), receipt(&bob, &event1, 11), receipt(&bob, &event2, 12)],
+            )
+            .await
+            .expect("add_receipts should succeed");
+
+        assert!(storage.get_receipts(&room_id, "m.read", &event1).await.unwrap().is_empty());
+        let mut receipts = storage.get_receipts(&room_id, "m.read", &event2).await.unwrap();
+        receipts.sort_by(|a, b| a.ts.cmp(&b.ts));
+        assert_eq!(
+            receipts.iter().map(|r| (r.user_id.as_str(), r.ts)).collect::<Vec<_>>(),
+            [(alice.as_str(), 10), (bob.as_str(), 12)]
+        );
+
+        sqlx::query("DELETE FROM event_receipts WHERE room_id = $1").bind(&room_id).execute(&*pool).await.unwrap();
+    }
+
     #[tokio::test]
     async fn test_get_receipts_empty() {
         let pool = test_pool().await;
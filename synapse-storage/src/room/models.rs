@@ -211,6 +211,20 @@ pub struct RoomUnreadCounts {
     pub notification_count: i64,
 }
 
+/// Row of `room_directory_publication_requests`: a request to list a room in
+/// the public directory while publications require admin approval.
+/// `status` is `pending`, `approved` or `rejected`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::FromRow)]
+pub struct RoomPublicationRequest {
+    pub room_id: String,
+    pub requested_by: String,
+    pub requested_ts: i64,
+    pub status: String,
+    pub reviewed_by: Option<String>,
+    pub reviewed_ts: Option<i64>,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, sqlx::FromRow)]
 pub(crate) struct RoomRecord {
     pub(crate) room_id: String,
@@ -31,6 +31,8 @@ pub trait SamlStoreApi: Send + Sync {
         attributes: Option<&serde_json::Value>,
     ) -> Result<Option<SamlUserMapping>, ApiError>;
     async fn delete_user_mapping_by_name_id(&self, name_id: &str) -> Result<u64, ApiError>;
+    async fn user_exists(&self, user_id: &str) -> Result<bool, ApiError>;
+    async fn get_user_id_by_verified_email(&self, email: &str) -> Result<Option<String>, ApiError>;
     async fn create_identity_provider(
         &self,
         request: CreateSamlIdentityProviderRequest,
@@ -126,6 +128,14 @@ impl SamlStoreApi for SamlStorage {
         self.delete_user_mapping_by_name_id(name_id).await
     }
 
+    async fn user_exists(&self, user_id: &str) -> Result<bool, ApiError> {
+        self.user_exists(user_id).await
+    }
+
+    async fn get_user_id_by_verified_email(&self, email: &str) -> Result<Option<String>, ApiError> {
+        self.get_user_id_by_verified_email(email).await
+    }
+
     async fn create_identity_provider(
         &self,
         request: CreateSamlIdentityProviderRequest,
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::sync::Arc;
+use synapse_common::column_encryption::{self, columns};
 use synapse_common::current_timestamp_millis;
 
 use synapse_common::error::ApiError;
@@ -7,6 +8,21 @@ use tracing::{debug, info};
 
 use super::models::*;
 
+fn open_session(mut session: SamlSession) -> Result<SamlSession, ApiError> {
+    session.attributes = column_encryption::open_json(columns::SAML_SESSION_ATTRIBUTES, session.attributes)?;
+    Ok(session)
+}
+
+fn open_mapping(mut mapping: SamlUserMapping) -> Result<SamlUserMapping, ApiError> {
+    mapping.attributes = column_encryption::open_json(columns::SAML_USER_MAPPING_ATTRIBUTES, mapping.attributes)?;
+    Ok(mapping)
+}
+
+fn open_auth_event(mut event: SamlAuthEvent) -> Result<SamlAuthEvent, ApiError> {
+    event.attributes = column_encryption::open_json(columns::SAML_AUTH_EVENT_ATTRIBUTES, event.attributes)?;
+    Ok(event)
+}
+
 pub struct SamlStorage {
     pool: Arc<sqlx::PgPool>,
 }
@@ -19,7 +35,10 @@ impl SamlStorage {
     pub async fn create_session(&self, request: CreateSamlSessionRequest) -> Result<SamlSession, ApiError> {
         let now = current_timestamp_millis();
         let expires_at = current_timestamp_millis() + request.expires_in_seconds * 1000;
-        let attributes_json = serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({}));
+        let attributes_json = column_encryption::seal_json(
+            columns::SAML_SESSION_ATTRIBUTES,
+            &serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({})),
+        )?;
 
         let row = sqlx::query_as::<_, SamlSession>(
             r#"
@@ -44,7 +63,7 @@ impl SamlStorage {
         .map_err(|e| ApiError::internal_with_log("Failed to create SAML session", &e))?;
 
         info!("Created SAML session: {} for user: {}", request.session_id, request.user_id);
-        Ok(row)
+        open_session(row)
     }
 
     pub async fn get_session(&self, session_id: &str) -> Result<Option<SamlSession>, ApiError> {
@@ -55,7 +74,7 @@ impl SamlStorage {
                 .await
                 .map_err(|e| ApiError::internal_with_log("Failed to get SAML session", &e))?;
 
-        Ok(row)
+        row.map(open_session).transpose()
     }
 
     pub async fn get_session_by_user(&self, user_id: &str) -> Result<Option<SamlSession>, ApiError> {
@@ -74,7 +93,7 @@ impl SamlStorage {
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to get SAML session by user", &e))?;
 
-        Ok(row)
+        row.map(open_session).transpose()
     }
 
     pub async fn update_session_last_used(&self, session_id: &str) -> Result<(), ApiError> {
@@ -120,7 +139,10 @@ impl SamlStorage {
         request: CreateSamlUserMappingRequest,
     ) -> Result<SamlUserMapping, ApiError> {
         let now = current_timestamp_millis();
-        let attributes_json = serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({}));
+        let attributes_json = column_encryption::seal_json(
+            columns::SAML_USER_MAPPING_ATTRIBUTES,
+            &serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({})),
+        )?;
 
         let row = sqlx::query_as::<_, SamlUserMapping>(
             r#"
@@ -146,7 +168,7 @@ impl SamlStorage {
         .map_err(|e| ApiError::internal_with_log("Failed to create SAML user mapping", &e))?;
 
         info!("Created/updated SAML user mapping: {} -> {}", request.name_id, request.user_id);
-        Ok(row)
+        open_mapping(row)
     }
 
     pub async fn get_user_mapping_by_name_id(
@@ -162,7 +184,7 @@ impl SamlStorage {
                 .await
                 .map_err(|e| ApiError::internal_with_log("Failed to get SAML user mapping", &e))?;
 
-        Ok(row)
+        row.map(open_mapping).transpose()
     }
 
     pub async fn get_user_mapping_by_user_id(&self, user_id: &str) -> Result<Option<SamlUserMapping>, ApiError> {
@@ -172,7 +194,7 @@ impl SamlStorage {
             .await
             .map_err(|e| ApiError::internal_with_log("Failed to get SAML user mapping", &e))?;
 
-        Ok(row)
+        row.map(open_mapping).transpose()
     }
 
     pub async fn delete_user_mapping(&self, name_id: &str, issuer: &str) -> Result<(), ApiError> {
@@ -221,7 +243,7 @@ impl SamlStorage {
         }
         .map_err(|e| ApiError::internal_with_log("Failed to list SAML user mappings", &e))?;
 
-        Ok(rows)
+        rows.into_iter().map(open_mapping).collect()
     }
 
     /// Fetch the first SAML user mapping matching `name_id` across any issuer.
@@ -245,7 +267,7 @@ impl SamlStorage {
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to get SAML user mapping", &e))?;
 
-        Ok(row)
+        row.map(open_mapping).transpose()
     }
 
     /// Update the first mapping matching `name_id` (across any issuer).
@@ -265,7 +287,10 @@ impl SamlStorage {
         };
 
         let target_user_id = new_user_id.unwrap_or(&existing.user_id);
-        let target_attributes = attributes.cloned().unwrap_or(existing.attributes.clone());
+        let target_attributes = column_encryption::seal_json(
+            columns::SAML_USER_MAPPING_ATTRIBUTES,
+            attributes.unwrap_or(&existing.attributes),
+        )?;
 
         let row = sqlx::query_as::<_, SamlUserMapping>(
             r#"
@@ -283,6 +308,7 @@ impl SamlStorage {
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to update SAML user mapping", &e))?;
 
+        let row = row.map(open_mapping).transpose()?;
         if row.is_some() {
             info!("Updated SAML user mapping: {} -> {}", name_id, target_user_id);
         }
@@ -306,6 +332,32 @@ impl SamlStorage {
         Ok(count)
     }
 
+    /// Whether a local account with this MXID exists, linked to SAML or not.
+    pub async fn user_exists(&self, user_id: &str) -> Result<bool, ApiError> {
+        sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM users WHERE user_id = $1)")
+            .bind(user_id)
+            .fetch_one(&*self.pool)
+            .await
+            .map_err(|e| ApiError::internal_with_log("Failed to check SAML user collision", &e))
+    }
+
+    /// Local account owning `email` as a verified threepid, used to link a
+    /// first-time SAML identity to a pre-existing account.
+    pub async fn get_user_id_by_verified_email(&self, email: &str) -> Result<Option<String>, ApiError> {
+        sqlx::query_scalar(
+            r#"
+            SELECT user_id FROM user_threepids
+            WHERE medium = 'email' AND LOWER(address) = LOWER($1)
+              AND (is_verified OR validated_at IS NOT NULL)
+            LIMIT 1
+            "#,
+        )
+        .bind(email)
+        .fetch_optional(&*self.pool)
+        .await
+        .map_err(|e| ApiError::internal_with_log("Failed to look up user by email", &e))
+    }
+
     pub async fn create_identity_provider(
         &self,
         request: CreateSamlIdentityProviderRequest,
@@ -423,7 +475,10 @@ impl SamlStorage {
 
     pub async fn create_auth_event(&self, request: CreateSamlAuthEventRequest) -> Result<SamlAuthEvent, ApiError> {
         let now = current_timestamp_millis();
-        let attributes_json = serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({}));
+        let attributes_json = column_encryption::seal_json(
+            columns::SAML_AUTH_EVENT_ATTRIBUTES,
+            &serde_json::to_value(&request.attributes).unwrap_or(serde_json::json!({})),
+        )?;
 
         let row = sqlx::query_as::<_, SamlAuthEvent>(
             r#"
@@ -452,7 +507,7 @@ impl SamlStorage {
         .map_err(|e| ApiError::internal_with_log("Failed to create SAML auth event", &e))?;
 
         debug!("Created SAML auth event: {} - {}", request.event_type, request.status);
-        Ok(row)
+        open_auth_event(row)
     }
 
     pub async fn get_auth_events_by_user(&self, user_id: &str, limit: i64) -> Result<Vec<SamlAuthEvent>, ApiError> {
@@ -470,7 +525,7 @@ impl SamlStorage {
         .await
         .map_err(|e| ApiError::internal_with_log("Failed to get auth events", &e))?;
 
-        Ok(rows)
+        rows.into_iter().map(open_auth_event).collect()
     }
 
     pub async fn create_logout_request(
@@ -1,5 +1,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres};
+use std::collections::{BTreeMap, BTreeSet};
+use std::path::Path;
 use std::sync::Arc;
 
 pub struct SchemaValidator {
@@ -54,6 +56,442 @@ const REQUIRED_COLUMNS: &[(&str, &str)] = &[
     ("notifications", "user_id"),
 ];
 
+/// Tables, columns and indexes declared by the migration chain.
+///
+/// Built by replaying the DDL of every forward migration in file-name order:
+/// `CREATE TABLE` column lists, `ALTER TABLE ... ADD/DROP/RENAME COLUMN`,
+/// table renames and drops, and `CREATE/DROP/ALTER INDEX`. Statements are
+/// applied unconditionally, including those inside `DO` guards, so the
+/// result is the shape a fully migrated database converges on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExpectedSchema {
+    /// Table name → column names.
+    pub tables: BTreeMap<String, BTreeSet<String>>,
+    /// Index name → owning table.
+    pub indexes: BTreeMap<String, String>,
+    /// Number of migration files replayed.
+    pub migration_files: usize,
+}
+
+/// Difference between the live schema and [`ExpectedSchema`]. Only objects
+/// missing from the database are reported; extra objects are not drift.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SchemaDriftReport {
+    pub migrations_dir: String,
+    pub migration_files: usize,
+    pub expected_tables: usize,
+    pub expected_indexes: usize,
+    pub missing_tables: Vec<String>,
+    /// `table.column` entries for tables that exist.
+    pub missing_columns: Vec<String>,
+    /// Indexes of tables that exist.
+    pub missing_indexes: Vec<String>,
+}
+
+impl SchemaDriftReport {
+    pub fn has_drift(&self) -> bool {
+        !self.missing_tables.is_empty() || !self.missing_columns.is_empty() || !self.missing_indexes.is_empty()
+    }
+}
+
+impl ExpectedSchema {
+    /// Replay every `*.sql` file in `dir` except `*.undo.sql`, sorted by name.
+    /// Subdirectories (such as `archive/`) are not read.
+    pub fn from_migrations_dir(dir: &Path) -> std::io::Result<Self> {
+        let mut files: Vec<_> = std::fs::read_dir(dir)?
+            .filter_map(Result::ok)
+            .map(|entry| entry.path())
+            .filter(|path| {
+                path.is_file()
+                    && path
+                        .file_name()
+                        .and_then(|n| n.to_str())
+                        .is_some_and(|n| n.ends_with(".sql") && !n.ends_with(".undo.sql"))
+            })
+            .collect();
+        files.sort();
+
+        let mut schema = Self::default();
+        for file in &files {
+            schema.apply_sql(&std::fs::read_to_string(file)?);
+        }
+        schema.migration_files = files.len();
+        Ok(schema)
+    }
+
+    /// Apply the DDL statements found in `sql`.
+    pub fn apply_sql(&mut self, sql: &str) {
+        let tokens = tokenize_sql(sql);
+        let mut i = 0;
+        while i < tokens.len() {
+            i = if is_kw(&tokens, i, "CREATE") {
+                self.apply_create(&tokens, i + 1)
+            } else if is_kw(&tokens, i, "ALTER") && is_kw(&tokens, i + 1, "TABLE") {
+                self.apply_alter_table(&tokens, i + 2)
+            } else if is_kw(&tokens, i, "ALTER") && is_kw(&tokens, i + 1, "INDEX") {
+                self.apply_alter_index(&tokens, i + 2)
+            } else if is_kw(&tokens, i, "DROP") && is_kw(&tokens, i + 1, "TABLE") {
+                self.apply_drop(&tokens, i + 2, true)
+            } else if is_kw(&tokens, i, "DROP") && is_kw(&tokens, i + 1, "INDEX") {
+                self.apply_drop(&tokens, i + 2, false)
+            } else {
+                i + 1
+            };
+        }
+    }
+
+    fn apply_create(&mut self, tokens: &[SqlToken], mut i: usize) -> usize {
+        if is_kw(tokens, i, "UNIQUE") {
+            i += 1;
+        }
+        if is_kw(tokens, i, "INDEX") {
+            i = skip_kws(tokens, i + 1, &["CONCURRENTLY"]);
+            i = skip_if_not_exists(tokens, i);
+            let (Some(name), true) = (ident_at(tokens, i), is_kw(tokens, i + 1, "ON")) else {
+                return i;
+            };
+            i = skip_kws(tokens, i + 2, &["ONLY"]);
+            if let Some(table) = ident_at(tokens, i) {
+                self.indexes.insert(name, table);
+            }
+            return i;
+        }
+
+        i = skip_kws(tokens, i, &["UNLOGGED"]);
+        if !is_kw(tokens, i, "TABLE") {
+            return i;
+        }
+        i = skip_if_not_exists(tokens, i + 1);
+        let Some(table) = ident_at(tokens, i) else {
+            return i;
+        };
+        i += 1;
+        if !matches!(tokens.get(i), Some(SqlToken::Punct('('))) {
+            return i;
+        }
+
+        let columns = self.tables.entry(table).or_default();
+        let mut depth = 0usize;
+        let mut item_start = true;
+        while let Some(token) = tokens.get(i) {
+            match token {
+                SqlToken::Punct('(') => {
+                    depth += 1;
+                    if depth == 1 {
+                        item_start = true;
+                        i += 1;
+                        continue;
+                    }
+                }
+                SqlToken::Punct(')') => {
+                    depth = depth.saturating_sub(1);
+                    if depth == 0 {
+                        return i + 1;
+                    }
+                }
+                SqlToken::Punct(',') if depth == 1 => {
+                    item_start = true;
+                    i += 1;
+                    continue;
+                }
+                SqlToken::Ident(name) if depth == 1 && item_start => {
+                    if !TABLE_CONSTRAINT_KEYWORDS.iter().any(|kw| name.eq_ignore_ascii_case(kw)) {
+                        columns.insert(name.clone());
+                    }
+                }
+                _ => {}
+            }
+            item_start = false;
+            i += 1;
+        }
+        i
+    }
+
+    fn apply_alter_table(&mut self, tokens: &[SqlToken], mut i: usize) -> usize {
+        i = skip_if_exists(tokens, i);
+        i = skip_kws(tokens, i, &["ONLY"]);
+        let Some(mut table) = ident_at(tokens, i) else {
+            return i;
+        };
+        i += 1;
+
+        let mut depth = 0usize;
+        let mut action_start = true;
+        while let Some(token) = tokens.get(i) {
+            match token {
+                SqlToken::Punct(';') if depth == 0 => return i + 1,
+                SqlToken::Punct('(') => depth += 1,
+                SqlToken::Punct(')') => depth = depth.saturating_sub(1),
+                SqlToken::Punct(',') if depth == 0 => {
+                    action_start = true;
+                    i += 1;
+                    continue;
+                }
+                SqlToken::Ident(_) if depth == 0 && action_start => {
+                    i = self.apply_alter_table_action(tokens, i, &mut table);
+                    action_start = false;
+                    continue;
+                }
+                _ => {}
+            }
+            action_start = false;
+            i += 1;
+        }
+        i
+    }
+
+    fn apply_alter_table_action(&mut self, tokens: &[SqlToken], i: usize, table: &mut String) -> usize {
+        if is_kw(tokens, i, "ADD") {
+            let mut j = i + 1;
+            if is_kw(tokens, j, "COLUMN") {
+                j += 1;
+            } else if ident_at(tokens, j)
+                .is_some_and(|kw| TABLE_CONSTRAINT_KEYWORDS.iter().any(|c| kw.eq_ignore_ascii_case(c)))
+            {
+                return j;
+            }
+            j = skip_if_not_exists(tokens, j);
+            if let Some(column) = ident_at(tokens, j) {
+                self.tables.entry(table.clone()).or_default().insert(column);
+            }
+            return j + 1;
+        }
+
+        if is_kw(tokens, i, "DROP") {
+            let mut j = i + 1;
+            if is_kw(tokens, j, "COLUMN") {
+                j += 1;
+            } else if is_kw(tokens, j, "CONSTRAINT") {
+                return j;
+            }
+            j = skip_if_exists(tokens, j);
+            if let (Some(column), Some(columns)) = (ident_at(tokens, j), self.tables.get_mut(table.as_str())) {
+                columns.remove(&column);
+            }
+            return j + 1;
+        }
+
+        if is_kw(tokens, i, "RENAME") {
+            if is_kw(tokens, i + 1, "TO") {
+                if let Some(new_name) = ident_at(tokens, i + 2) {
+                    self.rename_table(table, &new_name);
+                    *table = new_name;
+                }
+                return i + 3;
+            }
+            if is_kw(tokens, i + 1, "CONSTRAINT") {
+                return i + 2;
+            }
+            let j = if is_kw(tokens, i + 1, "COLUMN") { i + 2 } else { i + 1 };
+            if let (Some(from), true, Some(to)) =
+                (ident_at(tokens, j), is_kw(tokens, j + 1, "TO"), ident_at(tokens, j + 2))
+            {
+                if let Some(columns) = self.tables.get_mut(table.as_str()) {
+                    if columns.remove(&from) {
+                        columns.insert(to);
+                    }
+                }
+            }
+            return j + 3;
+        }
+
+        i + 1
+    }
+
+    fn apply_alter_index(&mut self, tokens: &[SqlToken], mut i: usize) -> usize {
+        i = skip_if_exists(tokens, i);
+        if let (Some(from), true, true, Some(to)) =
+            (ident_at(tokens, i), is_kw(tokens, i + 1, "RENAME"), is_kw(tokens, i + 2, "TO"), ident_at(tokens, i + 3))
+        {
+            if let Some(table) = self.indexes.remove(&from) {
+                self.indexes.insert(to, table);
+            }
+            return i + 4;
+        }
+        i
+    }
+
+    fn apply_drop(&mut self, tokens: &[SqlToken], mut i: usize, is_table: bool) -> usize {
+        if !is_table {
+            i = skip_kws(tokens, i, &["CONCURRENTLY"]);
+        }
+        i = skip_if_exists(tokens, i);
+        while let Some(name) = ident_at(tokens, i) {
+            if is_table {
+                self.tables.remove(&name);
+                self.indexes.retain(|_, owner| *owner != name);
+            } else {
+                self.indexes.remove(&name);
+            }
+            i += 1;
+            if !matches!(tokens.get(i), Some(SqlToken::Punct(','))) {
+                break;
+            }
+            i += 1;
+        }
+        i
+    }
+
+    fn rename_table(&mut self, from: &str, to: &str) {
+        if let Some(columns) = self.tables.remove(from) {
+            self.tables.insert(to.to_string(), columns);
+        }
+        for owner in self.indexes.values_mut() {
+            if owner == from {
+                *owner = to.to_string();
+            }
+        }
+    }
+
+    /// Compare against the live tables/columns and index names.
+    pub fn diff(
+        &self,
+        live_columns: &BTreeMap<String, BTreeSet<String>>,
+        live_indexes: &BTreeSet<String>,
+    ) -> SchemaDriftReport {
+        let mut report = SchemaDriftReport {
+            migration_files: self.migration_files,
+            expected_tables: self.tables.len(),
+            expected_indexes: self.indexes.len(),
+            ..SchemaDriftReport::default()
+        };
+        for (table, columns) in &self.tables {
+            match live_columns.get(table) {
+                None => report.missing_tables.push(table.clone()),
+                Some(live) => {
+                    report.missing_columns.extend(columns.difference(live).map(|column| format!("{table}.{column}")))
+                }
+            }
+        }
+        report.missing_indexes = self
+            .indexes
+            .iter()
+            .filter(|(name, table)| live_columns.contains_key(*table) && !live_indexes.contains(*name))
+            .map(|(name, _)| name.clone())
+            .collect();
+        report
+    }
+}
+
+/// Leading keywords of table-level constraints in a column list.
+const TABLE_CONSTRAINT_KEYWORDS: &[&str] = &["CONSTRAINT", "PRIMARY", "UNIQUE", "FOREIGN", "CHECK", "EXCLUDE", "LIKE"];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SqlToken {
+    /// Identifier or keyword; unquoted ones are lower-cased and any schema
+    /// qualifier is dropped.
+    Ident(String),
+    Punct(char),
+}
+
+/// Split SQL into identifiers and punctuation, skipping comments, string
+/// literals and numbers. Dollar-quoted bodies are *not* skipped so DDL in
+/// `DO $$ ... $$` guards is seen.
+fn tokenize_sql(sql: &str) -> Vec<SqlToken> {
+    let chars: Vec<char> = sql.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i];
+        if c == '-' && chars.get(i + 1) == Some(&'-') {
+            while i < chars.len() && chars[i] != '\n' {
+                i += 1;
+            }
+        } else if c == '/' && chars.get(i + 1) == Some(&'*') {
+            i += 2;
+            while i < chars.len() && !(chars[i] == '*' && chars.get(i + 1) == Some(&'/')) {
+                i += 1;
+            }
+            i += 2;
+        } else if c == '\'' {
+            i += 1;
+            while i < chars.len() {
+                if chars[i] == '\'' {
+                    if chars.get(i + 1) == Some(&'\'') {
+                        i += 1;
+                    } else {
+                        break;
+                    }
+                }
+                i += 1;
+            }
+            i += 1;
+        } else if c == '"' || c.is_alphabetic() || c == '_' {
+            let mut name = String::new();
+            while i < chars.len() {
+                if chars[i] == '"' {
+                    i += 1;
+                    let start = i;
+                    while i < chars.len() && chars[i] != '"' {
+                        i += 1;
+                    }
+                    name = chars[start..i.min(chars.len())].iter().collect();
+                    i += 1;
+                } else if chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$' {
+                    let start = i;
+                    while i < chars.len() && (chars[i].is_alphanumeric() || chars[i] == '_' || chars[i] == '$') {
+                        i += 1;
+                    }
+                    name = chars[start..i].iter().collect::<String>().to_lowercase();
+                } else {
+                    break;
+                }
+                // `schema.table`: keep only the last segment.
+                if chars.get(i) == Some(&'.') && chars.get(i + 1).is_some_and(|n| *n == '"' || n.is_alphabetic()) {
+                    i += 1;
+                } else {
+                    break;
+                }
+            }
+            tokens.push(SqlToken::Ident(name));
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].is_ascii_alphanumeric() || chars[i] == '.') {
+                i += 1;
+            }
+        } else {
+            if !c.is_whitespace() {
+                tokens.push(SqlToken::Punct(c));
+            }
+            i += 1;
+        }
+    }
+    tokens
+}
+
+fn is_kw(tokens: &[SqlToken], i: usize, keyword: &str) -> bool {
+    matches!(tokens.get(i), Some(SqlToken::Ident(word)) if word.eq_ignore_ascii_case(keyword))
+}
+
+fn ident_at(tokens: &[SqlToken], i: usize) -> Option<String> {
+    match tokens.get(i) {
+        Some(SqlToken::Ident(word)) => Some(word.clone()),
+        _ => None,
+    }
+}
+
+fn skip_kws(tokens: &[SqlToken], mut i: usize, keywords: &[&str]) -> usize {
+    while keywords.iter().any(|kw| is_kw(tokens, i, kw)) {
+        i += 1;
+    }
+    i
+}
+
+fn skip_if_not_exists(tokens: &[SqlToken], i: usize) -> usize {
+    if is_kw(tokens, i, "IF") && is_kw(tokens, i + 1, "NOT") && is_kw(tokens, i + 2, "EXISTS") {
+        i + 3
+    } else {
+        i
+    }
+}
+
+fn skip_if_exists(tokens: &[SqlToken], i: usize) -> usize {
+    if is_kw(tokens, i, "IF") && is_kw(tokens, i + 1, "EXISTS") {
+        i + 2
+    } else {
+        i
+    }
+}
+
 impl SchemaValidator {
     pub fn new(pool: Arc<Pool<Postgres>>) -> Self {
         Self { pool }
@@ -116,6 +554,30 @@ impl SchemaValidator {
             .await
     }
 
+    /// Diff the live schema against the migrations in `migrations_dir`.
+    pub async fn detect_drift(&self, migrations_dir: &Path) -> Result<SchemaDriftReport, sqlx::Error> {
+        let expected = ExpectedSchema::from_migrations_dir(migrations_dir).map_err(sqlx::Error::Io)?;
+
+        let rows: Vec<(String, String)> = sqlx::query_as(
+            "SELECT c.table_name::text, c.column_name::text \
+             FROM information_schema.columns c \
+             JOIN information_schema.tables t \
+               ON t.table_schema = c.table_schema AND t.table_name = c.table_name \
+             WHERE c.table_schema = current_schema() AND t.table_type = 'BASE TABLE'",
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+        let mut live_columns: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (table, column) in rows {
+            live_columns.entry(table).or_default().insert(column);
+        }
+        let live_indexes: BTreeSet<String> = self.validate_indexes().await?.into_iter().collect();
+
+        let mut report = expected.diff(&live_columns, &live_indexes);
+        report.migrations_dir = migrations_dir.display().to_string();
+        Ok(report)
+    }
+
     pub async fn validate_required_tables(&self, tables: &[&str]) -> Result<Vec<String>, sqlx::Error> {
         let mut missing = Vec::new();
         for table in tables {
@@ -198,6 +660,74 @@ impl SchemaValidator {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_expected_schema_replays_ddl() {
+        let mut schema = ExpectedSchema::default();
+        schema.apply_sql(
+            r#"
+            -- CREATE TABLE ignored (x INT);
+            CREATE TABLE IF NOT EXISTS public.widgets (
+                id BIGSERIAL,
+                name TEXT NOT NULL DEFAULT 'a,b',
+                amount NUMERIC(10, 2),
+                "order" TEXT,
+                CONSTRAINT pk_widgets PRIMARY KEY (id),
+                UNIQUE (name)
+            );
+            CREATE UNIQUE INDEX IF NOT EXISTS idx_widgets_name ON widgets(name);
+            CREATE INDEX idx_widgets_amount ON widgets USING btree (amount);
+            CREATE TABLE old_things (id INT);
+            DO $$ BEGIN
+                IF NOT EXISTS (SELECT 1 FROM information_schema.columns WHERE table_name = 'widgets') THEN
+                    ALTER TABLE widgets ADD COLUMN IF NOT EXISTS owner TEXT, ADD CONSTRAINT fk_x FOREIGN KEY (owner) REFERENCES users(user_id);
+                END IF;
+            END $$;
+            ALTER TABLE widgets RENAME COLUMN amount TO total;
+            ALTER TABLE widgets DROP COLUMN IF EXISTS name;
+            ALTER INDEX IF EXISTS idx_widgets_amount RENAME TO idx_widgets_total;
+            DROP INDEX IF EXISTS idx_widgets_name;
+            DROP TABLE IF EXISTS old_things;
+            "#,
+        );
+
+        let columns: Vec<&str> = schema.tables["widgets"].iter().map(String::as_str).collect();
+        assert_eq!(columns, vec!["id", "order", "owner", "total"]);
+        assert!(!schema.tables.contains_key("ignored"));
+        assert!(!schema.tables.contains_key("old_things"));
+        assert_eq!(schema.indexes.keys().map(String::as_str).collect::<Vec<_>>(), vec!["idx_widgets_total"]);
+    }
+
+    #[test]
+    fn test_drift_report_lists_missing_objects() {
+        let mut schema = ExpectedSchema::default();
+        schema.apply_sql(
+            "CREATE TABLE a (x INT, y INT); CREATE INDEX idx_a_y ON a(y); \
+             CREATE TABLE b (z INT); CREATE INDEX idx_b_z ON b(z);",
+        );
+        let live_columns = BTreeMap::from([("a".to_string(), BTreeSet::from(["x".to_string()]))]);
+        let report = schema.diff(&live_columns, &BTreeSet::new());
+        assert!(report.has_drift());
+        assert_eq!(report.missing_tables, vec!["b"]);
+        assert_eq!(report.missing_columns, vec!["a.y"]);
+        assert_eq!(report.missing_indexes, vec!["idx_a_y"]);
+        assert_eq!(report.expected_tables, 2);
+    }
+
+    #[test]
+    fn test_expected_schema_from_repository_migrations() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../migrations");
+        let schema = ExpectedSchema::from_migrations_dir(&dir).unwrap_or_default();
+        assert!(schema.migration_files >= 2);
+        for (table, column) in REQUIRED_COLUMNS {
+            assert!(schema.tables.get(*table).is_some_and(|c| c.contains(*column)), "{table}.{column}");
+        }
+        assert!(schema.tables["events"].contains("redacts"));
+        assert!(schema.tables["space_children"].contains("order"));
+        assert!(schema.tables["url_preview_cache"].contains("expires_at"));
+        assert!(!schema.tables["url_preview_cache"].contains("expires_ts"));
+        assert!(schema.indexes.contains_key("idx_destination_rooms_stream"));
+    }
+
     #[test]
     fn test_schema_validation_result_defaults() {
         let result = SchemaValidationResult::default();
@@ -0,0 +1,102 @@
+//! The `server_name` a database belongs to.
+//!
+//! User IDs, room aliases, event IDs and signing key references all embed
+//! the server name, so starting against an existing database under a
+//! different name silently corrupts it. The first startup records the
+//! configured name; later startups compare against it (see
+//! `src/server/mod.rs`).
+
+use sqlx::PgPool;
+
+/// Outcome of comparing the configured `server_name` with the stored one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ServerNameCheck {
+    /// No name was stored yet; the configured one has been recorded.
+    Recorded,
+    /// The configured name matches the stored one.
+    Unchanged,
+    /// The names differed and the stored name was overwritten on request.
+    Renamed { previous: String },
+    /// The names differ. The server must not start.
+    Mismatch { stored: String },
+}
+
+impl ServerNameCheck {
+    pub fn is_mismatch(&self) -> bool {
+        matches!(self, Self::Mismatch { .. })
+    }
+}
+
+fn compare_server_name(stored: &str, configured: &str, force_rename: bool) -> ServerNameCheck {
+    if stored == configured {
+        ServerNameCheck::Unchanged
+    } else if force_rename {
+        ServerNameCheck::Renamed { previous: stored.to_string() }
+    } else {
+        ServerNameCheck::Mismatch { stored: stored.to_string() }
+    }
+}
+
+/// Records `configured` on first use, otherwise compares it with the stored
+/// name. With `force_rename` a differing stored name is replaced instead of
+/// reported as a mismatch.
+pub async fn verify_server_name(
+    pool: &PgPool,
+    configured: &str,
+    force_rename: bool,
+) -> Result<ServerNameCheck, sqlx::Error> {
+    let now = chrono::Utc::now().timestamp_millis();
+    let mut tx = pool.begin().await?;
+
+    let inserted = sqlx::query(
+        "INSERT INTO server_identity (id, server_name, created_ts) VALUES (TRUE, $1, $2) ON CONFLICT (id) DO NOTHING",
+    )
+    .bind(configured)
+    .bind(now)
+    .execute(&mut *tx)
+    .await?
+    .rows_affected();
+    if inserted > 0 {
+        tx.commit().await?;
+        return Ok(ServerNameCheck::Recorded);
+    }
+
+    let stored: String =
+        sqlx::query_scalar("SELECT server_name FROM server_identity WHERE id FOR UPDATE").fetch_one(&mut *tx).await?;
+    let check = compare_server_name(&stored, configured, force_rename);
+    if matches!(check, ServerNameCheck::Renamed { .. }) {
+        sqlx::query("UPDATE server_identity SET server_name = $1, updated_ts = $2 WHERE id")
+            .bind(configured)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+    }
+    tx.commit().await?;
+    Ok(check)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matching_name_is_unchanged() {
+        assert_eq!(compare_server_name("example.org", "example.org", false), ServerNameCheck::Unchanged);
+        assert_eq!(compare_server_name("example.org", "example.org", true), ServerNameCheck::Unchanged);
+    }
+
+    #[test]
+    fn differing_name_is_a_mismatch_without_force() {
+        let check = compare_server_name("example.org", "example.com", false);
+        assert!(check.is_mismatch());
+        assert_eq!(check, ServerNameCheck::Mismatch { stored: "example.org".to_string() });
+    }
+
+    #[test]
+    fn differing_name_is_renamed_with_force() {
+        assert_eq!(
+            compare_server_name("example.org", "example.com", true),
+            ServerNameCheck::Renamed { previous: "example.org".to_string() }
+        );
+    }
+}
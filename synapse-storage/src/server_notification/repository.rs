@@ -810,7 +810,7 @@ impl ServerNotificationStorage {
             r#"
             INSERT INTO events (event_id, room_id, user_id, event_type, content, origin_server_ts, sender, state_key)
             VALUES ($1, $2, $3, 'm.room.create', $4, $5, $6, '')
-            ON CONFLICT (event_id) DO NOTHING
+            ON CONFLICT DO NOTHING
             "#,
         )
         .bind(create_event_id)
@@ -831,7 +831,7 @@ impl ServerNotificationStorage {
             r#"
             INSERT INTO events (event_id, room_id, user_id, event_type, content, origin_server_ts, sender, state_key)
             VALUES ($1, $2, $3, 'm.room.member', $4, $5, $6, $7)
-            ON CONFLICT (event_id) DO NOTHING
+            ON CONFLICT DO NOTHING
             "#,
         )
         .bind(membership_event_id)
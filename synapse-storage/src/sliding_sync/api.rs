@@ -127,7 +127,11 @@ pub trait SlidingSyncStoreApi: Send + Sync {
     async fn get_global_account_data(&self, user_id: &str) -> Result<serde_json::Value, sqlx::Error>;
     async fn get_room_account_data(&self, user_id: &str, room_ids: &[String])
         -> Result<serde_json::Value, sqlx::Error>;
-    async fn get_receipts_for_rooms(&self, room_ids: &[String]) -> Result<serde_json::Value, sqlx::Error>;
+    async fn get_receipts_for_rooms(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<serde_json::Value, sqlx::Error>;
     async fn delete_connection_data(
         &self,
         user_id: &str,
@@ -318,8 +322,12 @@ impl SlidingSyncStoreApi for SlidingSyncStorage {
     ) -> Result<serde_json::Value, sqlx::Error> {
         self.get_room_account_data(user_id, room_ids).await
     }
-    async fn get_receipts_for_rooms(&self, room_ids: &[String]) -> Result<serde_json::Value, sqlx::Error> {
-        self.get_receipts_for_rooms(room_ids).await
+    async fn get_receipts_for_rooms(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<serde_json::Value, sqlx::Error> {
+        self.get_receipts_for_rooms(room_ids, user_id).await
     }
     async fn delete_connection_data(
         &self,
@@ -782,7 +782,7 @@ async fn test_get_receipts_for_rooms_empty_input() {
     let pool = test_pool().await;
     let storage = SlidingSyncStorage::new(pool.clone());
 
-    let data = storage.get_receipts_for_rooms(&[]).await.expect("get_receipts_for_rooms empty");
+    let data = storage.get_receipts_for_rooms(&[], "@nobody:example.com").await.expect("get_receipts_for_rooms empty");
     assert_eq!(data, serde_json::json!({}));
 }
 
@@ -812,13 +812,47 @@ async fn test_get_receipts_for_rooms_with_rows() {
     .await
     .expect("should insert event_receipt");
 
-    let data = storage.get_receipts_for_rooms(&[room_id.clone()]).await.expect("get_receipts_for_rooms should succeed");
+    let data = storage
+        .get_receipts_for_rooms(&[room_id.clone()], &user_id)
+        .await
+        .expect("get_receipts_for_rooms should succeed");
     let obj = data.as_object().expect("should be object");
     assert!(obj.contains_key(&room_id), "should contain the room_id key");
 
     sqlx::query("DELETE FROM event_receipts WHERE room_id = $1").bind(&room_id).execute(&*pool).await.unwrap();
 }
 
+#[tokio::test]
+async fn test_get_receipts_for_rooms_hides_other_users_private_receipts() {
+    let pool = test_pool().await;
+    let storage = SlidingSyncStorage::new(pool.clone());
+    let room_id = unique_id("!room");
+    let owner = unique_id("@owner");
+    let event_id = unique_id("$event");
+    let now = current_timestamp_millis();
+
+    sqlx::query(
+        r#"
+        INSERT INTO event_receipts (event_id, room_id, user_id, receipt_type, ts, data, created_ts, updated_ts)
+        VALUES ($1, $2, $3, 'm.read.private', $4, '{}', $4, $4)
+        "#,
+    )
+    .bind(&event_id)
+    .bind(&room_id)
+    .bind(&owner)
+    .bind(now)
+    .execute(&*pool)
+    .await
+    .expect("should insert event_receipt");
+
+    let own = storage.get_receipts_for_rooms(&[room_id.clone()], &owner).await.unwrap();
+    assert!(own.get(&room_id).is_some(), "the owner sees their private receipt");
+    let other = storage.get_receipts_for_rooms(&[room_id.clone()], &unique_id("@other")).await.unwrap();
+    assert!(other.get(&room_id).is_none(), "others must not see the private receipt");
+
+    sqlx::query("DELETE FROM event_receipts WHERE room_id = $1").bind(&room_id).execute(&*pool).await.unwrap();
+}
+
 #[tokio::test]
 async fn test_delete_connection_data_removes_all() {
     let pool = test_pool().await;
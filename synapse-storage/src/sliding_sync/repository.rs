@@ -727,7 +727,13 @@ impl SlidingSyncStorage {
     }
 
     #[allow(clippy::expect_used)]
-    pub async fn get_receipts_for_rooms(&self, room_ids: &[String]) -> Result<serde_json::Value, sqlx::Error> {
+    /// Receipts of `room_ids` as `user_id` sees them: other users' private
+    /// receipts are left out.
+    pub async fn get_receipts_for_rooms(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<serde_json::Value, sqlx::Error> {
         self.ensure_schema()?;
         if room_ids.is_empty() {
             return Ok(serde_json::json!({}));
@@ -738,9 +744,11 @@ impl SlidingSyncStorage {
             SELECT room_id, event_id, user_id, receipt_type, ts, data
             FROM event_receipts
             WHERE room_id = ANY($1::text[])
+              AND (receipt_type <> 'm.read.private' OR user_id = $2)
             ",
         )
         .bind(room_ids)
+        .bind(user_id)
         .fetch_all(&*self.pool)
         .await?;
 
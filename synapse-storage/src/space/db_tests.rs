@@ -196,6 +196,7 @@ async fn test_space_children_crud() {
             sender: "@sender:example.com".to_string(),
             is_suggested: true,
             via_servers: vec!["example.com".to_string()],
+            order: None,
         })
         .await
         .expect("add_child should succeed");
@@ -390,6 +391,7 @@ async fn test_get_child_spaces() {
             sender: "@sender:example.com".to_string(),
             is_suggested: false,
             via_servers: vec!["example.com".to_string()],
+            order: None,
         })
         .await
         .unwrap();
@@ -529,6 +531,7 @@ async fn test_get_space_member_and_child_count() {
             sender: "@sender:example.com".to_string(),
             is_suggested: false,
             via_servers: vec!["example.com".to_string()],
+            order: None,
         })
         .await
         .unwrap();
@@ -726,6 +729,7 @@ async fn test_get_recursive_hierarchy_with_children() {
             sender: "@rp:example.com".to_string(),
             is_suggested: false,
             via_servers: vec!["example.com".to_string()],
+            order: None,
         })
         .await
         .unwrap();
@@ -812,6 +816,7 @@ async fn test_get_space_hierarchy_paginated_with_from() {
                 sender: "@pf:example.com".to_string(),
                 is_suggested: false,
                 via_servers: vec!["example.com".to_string()],
+                order: None,
             })
             .await
             .unwrap();
@@ -977,6 +982,7 @@ async fn test_get_parent_spaces() {
             sender: "@p:example.com".to_string(),
             is_suggested: false,
             via_servers: vec!["example.com".to_string()],
+            order: None,
         })
         .await
         .unwrap();
@@ -1286,6 +1292,7 @@ async fn test_get_space_room_ids() {
                 sender: "@rids:example.com".to_string(),
                 is_suggested: false,
                 via_servers: vec!["example.com".to_string()],
+                order: None,
             })
             .await
             .unwrap();
@@ -1374,6 +1381,7 @@ async fn test_get_space_children_paginated_no_cursor() {
                 sender: "@cp:example.com".to_string(),
                 is_suggested: false,
                 via_servers: vec!["example.com".to_string()],
+                order: None,
             })
             .await
             .unwrap();
@@ -1426,6 +1434,7 @@ async fn test_get_space_children_paginated_with_cursor() {
                 sender: "@cc:example.com".to_string(),
                 is_suggested: false,
                 via_servers: vec!["example.com".to_string()],
+                order: None,
             })
             .await
             .unwrap();
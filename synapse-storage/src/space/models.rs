@@ -86,6 +86,47 @@ pub struct AddChildRequest {
     pub sender: String,
     pub is_suggested: bool,
     pub via_servers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub order: Option<String>,
+}
+
+/// Maximum length of an `m.space.child` `order` key.
+pub const SPACE_CHILD_ORDER_MAX_LEN: usize = 50;
+
+/// Whether `order` is a valid `m.space.child` ordering key: non-empty, at
+/// most [`SPACE_CHILD_ORDER_MAX_LEN`] characters, all in `\x20`..=`\x7E`.
+pub fn is_valid_space_child_order(order: &str) -> bool {
+    !order.is_empty() && order.len() <= SPACE_CHILD_ORDER_MAX_LEN && order.bytes().all(|b| (0x20..=0x7E).contains(&b))
+}
+
+/// Order space children as the spec requires for hierarchy output: children
+/// with a valid `order` come first, compared lexicographically by that key;
+/// the rest follow. Ties fall back to the `m.space.child` timestamp and then
+/// the room ID. Invalid `order` values are treated as absent.
+pub fn sort_space_children(children: &mut [SpaceChild]) {
+    children.sort_by(|a, b| {
+        let a_order = a.order.as_deref().filter(|o| is_valid_space_child_order(o));
+        let b_order = b.order.as_deref().filter(|o| is_valid_space_child_order(o));
+        let by_order = match (a_order, b_order) {
+            (Some(a), Some(b)) => a.cmp(b),
+            (Some(_), None) => std::cmp::Ordering::Less,
+            (None, Some(_)) => std::cmp::Ordering::Greater,
+            (None, None) => std::cmp::Ordering::Equal,
+        };
+        by_order.then_with(|| a.added_ts.cmp(&b.added_ts)).then_with(|| a.room_id.cmp(&b.room_id))
+    });
+}
+
+/// Content of the `m.space.child` state event describing `child`.
+pub fn space_child_event_content(child: &SpaceChild) -> serde_json::Value {
+    let mut content = serde_json::json!({
+        "via": child.via_servers,
+        "suggested": child.is_suggested,
+    });
+    if let Some(order) = child.order.as_deref().filter(|o| is_valid_space_child_order(o)) {
+        content["order"] = serde_json::Value::String(order.to_string());
+    }
+    content
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, Default)]
@@ -129,14 +129,15 @@ impl SpaceStorage {
         sqlx::query_as::<_, SpaceChild>(
             r#"
             INSERT INTO space_children (
-                space_id, room_id, sender, is_suggested, via_servers, added_ts
+                space_id, room_id, sender, is_suggested, via_servers, added_ts, "order"
             )
-            VALUES ($1, $2, $3, $4, $5, $6)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
             ON CONFLICT (space_id, room_id) DO UPDATE SET
                 via_servers = EXCLUDED.via_servers,
                 is_suggested = EXCLUDED.is_suggested,
                 sender = EXCLUDED.sender,
-                added_ts = EXCLUDED.added_ts
+                added_ts = EXCLUDED.added_ts,
+                "order" = EXCLUDED."order"
             RETURNING
                 id,
                 space_id,
@@ -145,7 +146,7 @@ impl SpaceStorage {
                 is_suggested,
                 ARRAY(SELECT jsonb_array_elements_text(via_servers)) as via_servers,
                 added_ts,
-                NULL::TEXT as "order",
+                NULLIF("order", '') as "order",
                 NULL::BOOLEAN as suggested,
                 NULL::TEXT as added_by,
                 NULL::BIGINT as removed_ts
@@ -157,6 +158,7 @@ impl SpaceStorage {
         .bind(request.is_suggested)
         .bind(&via_servers)
         .bind(now)
+        .bind(&request.order)
         .fetch_one(&*self.pool)
         .await
     }
@@ -182,7 +184,7 @@ impl SpaceStorage {
                 is_suggested,
                 ARRAY(SELECT jsonb_array_elements_text(via_servers)) as via_servers,
                 added_ts,
-                NULL::TEXT as "order",
+                NULLIF("order", '') as "order",
                 NULL::BOOLEAN as suggested,
                 NULL::TEXT as added_by,
                 NULL::BIGINT as removed_ts
@@ -194,6 +196,10 @@ impl SpaceStorage {
         .bind(space_id)
         .fetch_all(&*self.pool)
         .await
+        .map(|mut children| {
+            sort_space_children(&mut children);
+            children
+        })
     }
 
     pub async fn get_child_spaces(&self, room_id: &str) -> Result<Vec<SpaceChild>, sqlx::Error> {
@@ -207,7 +213,7 @@ impl SpaceStorage {
                 is_suggested,
                 ARRAY(SELECT jsonb_array_elements_text(via_servers)) as via_servers,
                 added_ts,
-                NULL::TEXT as "order",
+                NULLIF("order", '') as "order",
                 NULL::BOOLEAN as suggested,
                 NULL::TEXT as added_by,
                 NULL::BIGINT as removed_ts
@@ -657,7 +663,7 @@ impl SpaceStorage {
                     is_suggested,
                     via_servers,
                     added_ts,
-                    NULL::TEXT as "order",
+                    NULLIF("order", '') as "order",
                     NULL::BOOLEAN as suggested,
                     NULL::TEXT as added_by,
                     NULL::BIGINT as removed_ts
@@ -668,7 +674,11 @@ impl SpaceStorage {
             )
             .bind(space_id)
             .fetch_all(&*self.pool)
-            .await?
+            .await
+            .map(|mut children| {
+                sort_space_children(&mut children);
+                children
+            })?
         } else {
             self.get_space_children(space_id).await?
         };
@@ -794,10 +804,7 @@ impl SpaceStorage {
                 serde_json::json!({
                     "type": "m.space.child",
                     "state_key": child.room_id,
-                    "content": {
-                        "via": child.via_servers,
-                        "suggested": child.is_suggested,
-                    },
+                    "content": space_child_event_content(&child),
                     "sender": child.sender,
                     "origin_server_ts": child.added_ts,
                 })
@@ -940,7 +947,7 @@ impl SpaceStorage {
                     is_suggested,
                     COALESCE(ARRAY(SELECT jsonb_array_elements_text(via_servers)), '{}') AS via_servers,
                     added_ts,
-                    NULL::TEXT AS "order",
+                    NULLIF("order", '') AS "order",
                     NULL::BOOLEAN AS suggested,
                     NULL::TEXT AS added_by,
                     NULL::BIGINT AS removed_ts
@@ -967,7 +974,7 @@ impl SpaceStorage {
                     is_suggested,
                     COALESCE(ARRAY(SELECT jsonb_array_elements_text(via_servers)), '{}') AS via_servers,
                     added_ts,
-                    NULL::TEXT AS "order",
+                    NULLIF("order", '') AS "order",
                     NULL::BOOLEAN AS suggested,
                     NULL::TEXT AS added_by,
                     NULL::BIGINT AS removed_ts
@@ -117,6 +117,7 @@ fn test_add_child_request() {
         sender: "@user:localhost".to_string(),
         is_suggested: true,
         via_servers: vec!["server1.com".to_string(), "server2.com".to_string()],
+        order: None,
     };
 
     let json = serde_json::to_string(&request).unwrap();
@@ -260,3 +261,53 @@ fn test_space_hierarchy_response_fields() {
     assert!(resp.rooms.is_empty());
     assert_eq!(resp.next_batch, Some("next".to_string()));
 }
+
+#[test]
+fn test_is_valid_space_child_order() {
+    assert!(is_valid_space_child_order("a"));
+    assert!(is_valid_space_child_order("~ zz"));
+    assert!(is_valid_space_child_order(&"x".repeat(SPACE_CHILD_ORDER_MAX_LEN)));
+    assert!(!is_valid_space_child_order(""));
+    assert!(!is_valid_space_child_order(&"x".repeat(SPACE_CHILD_ORDER_MAX_LEN + 1)));
+    assert!(!is_valid_space_child_order("tab\there"));
+    assert!(!is_valid_space_child_order("ümlaut"));
+}
+
+#[test]
+fn test_sort_space_children_orders_by_key_then_timestamp() {
+    let child = |room_id: &str, order: Option<&str>, added_ts: i64| SpaceChild {
+        room_id: room_id.to_string(),
+        order: order.map(str::to_string),
+        added_ts,
+        ..create_test_space_child()
+    };
+    let mut children = vec![
+        child("!late:localhost", None, 30),
+        child("!invalid:localhost", Some("bad\u{7f}"), 10),
+        child("!b:localhost", Some("b"), 40),
+        child("!a:localhost", Some("a"), 50),
+        child("!early:localhost", None, 20),
+    ];
+
+    sort_space_children(&mut children);
+
+    let room_ids: Vec<_> = children.iter().map(|c| c.room_id.as_str()).collect();
+    assert_eq!(
+        room_ids,
+        vec!["!a:localhost", "!b:localhost", "!invalid:localhost", "!early:localhost", "!late:localhost"]
+    );
+}
+
+#[test]
+fn test_space_child_event_content_includes_valid_order_only() {
+    let mut child = create_test_space_child();
+    child.is_suggested = true;
+    child.order = Some("m".to_string());
+    let content = space_child_event_content(&child);
+    assert_eq!(content["via"], serde_json::json!(["localhost"]));
+    assert_eq!(content["suggested"], true);
+    assert_eq!(content["order"], "m");
+
+    child.order = Some(String::new());
+    assert!(space_child_event_content(&child).get("order").is_none());
+}
@@ -0,0 +1,462 @@
+//! Compaction of archived state groups.
+//!
+//! New state groups are written as short delta chains with a snapshot every
+//! [`STATE_GROUP_SNAPSHOT_INTERVAL`] groups, which keeps lookups of recent
+//! state cheap. Old state is read far less often, so for groups older than a
+//! cutoff this pass trades lookup length for space:
+//!
+//! - groups whose state equals their parent's are merged into the parent;
+//! - delta entries that repeat the parent's state are dropped;
+//! - snapshots are thinned to one every `archive_snapshot_interval` groups,
+//!   the others stored as deltas;
+//! - delta chains longer than that are rolled up into a snapshot.
+//!
+//! The state every remaining group resolves to is unchanged. Progress is kept
+//! per room in `state_compaction_progress`, so each pass only reads groups
+//! archived since the last one. Intended to be invoked by a scheduled
+//! background task (see `src/server/mod.rs`).
+
+use crate::state_groups::{RESOLVE_STATE_GROUP_SQL, STATE_GROUP_SNAPSHOT_INTERVAL};
+use sqlx::PgPool;
+use std::collections::HashMap;
+
+/// Longest delta chain compaction produces; resolution walks at most 1000
+/// groups, and young groups may add another snapshot interval on top.
+pub const MAX_ARCHIVE_SNAPSHOT_INTERVAL: i32 = 800;
+
+/// Groups of one room read per pass; the rest wait for the next pass.
+const MAX_GROUPS_PER_ROOM_PASS: i64 = 2_000;
+
+type StateMap = HashMap<(String, String), String>;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StateCompactionParams {
+    /// Only groups created before this timestamp (ms) are compacted.
+    pub older_than_ts: i64,
+    /// Target number of groups between two archived snapshots.
+    pub archive_snapshot_interval: i32,
+    /// Rooms compacted per pass.
+    pub max_rooms: i64,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StateCompactionStats {
+    pub rooms: u64,
+    pub groups_merged: u64,
+    pub snapshots_created: u64,
+    pub snapshots_thinned: u64,
+    pub rows_before: u64,
+    pub rows_after: u64,
+}
+
+impl StateCompactionStats {
+    /// `state_group_state` rows removed.
+    pub fn rows_saved(&self) -> u64 {
+        self.rows_before.saturating_sub(self.rows_after)
+    }
+
+    fn add(&mut self, other: &Self) {
+        self.rooms += other.rooms;
+        self.groups_merged += other.groups_merged;
+        self.snapshots_created += other.snapshots_created;
+        self.snapshots_thinned += other.snapshots_thinned;
+        self.rows_before += other.rows_before;
+        self.rows_after += other.rows_after;
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct LoadedGroup {
+    pub id: i64,
+    pub is_snapshot: bool,
+    pub delta_depth: i32,
+    pub entries: StateMap,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum GroupRewrite {
+    /// Same state as `into`, the previous remaining group.
+    Merge {
+        into: i64,
+    },
+    Store {
+        is_snapshot: bool,
+        delta_depth: i32,
+        entries: StateMap,
+    },
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct CompactionPlan {
+    pub rewrites: Vec<(i64, GroupRewrite)>,
+    /// Last group left in place, where the next pass resumes.
+    pub last_group: Option<i64>,
+    pub stats: StateCompactionStats,
+}
+
+/// The group the compacted range continues from: its id, resolved state and
+/// distance from its snapshot.
+pub(crate) struct CompactionBase {
+    pub id: i64,
+    pub state: StateMap,
+    pub delta_depth: i32,
+}
+
+/// Plans the rewrite of `groups`, a room's consecutive groups in id order,
+/// each the child of the one before (or of `base`).
+pub(crate) fn plan_compaction(
+    base: Option<CompactionBase>,
+    groups: &[LoadedGroup],
+    archive_snapshot_interval: i32,
+) -> CompactionPlan {
+    let mut plan = CompactionPlan::default();
+    let (mut prev, mut state, mut depth) = match base {
+        Some(base) => (Some(base.id), base.state, base.delta_depth),
+        None => (None, StateMap::new(), 0),
+    };
+
+    for group in groups {
+        plan.stats.rows_before += group.entries.len() as u64;
+        let resolved = if group.is_snapshot {
+            group.entries.clone()
+        } else {
+            let mut resolved = state.clone();
+            resolved.extend(group.entries.iter().map(|(key, event_id)| (key.clone(), event_id.clone())));
+            resolved
+        };
+
+        if let Some(into) = prev.filter(|_| resolved == state) {
+            plan.rewrites.push((group.id, GroupRewrite::Merge { into }));
+            plan.stats.groups_merged += 1;
+            continue;
+        }
+
+        // State entries are only ever replaced, but a snapshot that lost an
+        // entry cannot be written as a delta.
+        let drops_entries = state.keys().any(|key| !resolved.contains_key(key));
+        let (is_snapshot, delta_depth, entries) =
+            if prev.is_none() || drops_entries || depth + 1 >= archive_snapshot_interval {
+                (true, 0, resolved.clone())
+            } else {
+                let delta = resolved
+                    .iter()
+                    .filter(|(key, event_id)| state.get(*key) != Some(*event_id))
+                    .map(|(key, event_id)| (key.clone(), event_id.clone()))
+                    .collect();
+                (false, depth + 1, delta)
+            };
+
+        match (group.is_snapshot, is_snapshot) {
+            (false, true) => plan.stats.snapshots_created += 1,
+            (true, false) => plan.stats.snapshots_thinned += 1,
+            _ => {}
+        }
+        plan.stats.rows_after += entries.len() as u64;
+        if is_snapshot != group.is_snapshot || delta_depth != group.delta_depth || entries != group.entries {
+            plan.rewrites.push((group.id, GroupRewrite::Store { is_snapshot, delta_depth, entries }));
+        }
+
+        prev = Some(group.id);
+        state = resolved;
+        depth = delta_depth;
+    }
+
+    plan.last_group = prev;
+    plan
+}
+
+/// Compacts the archived state groups of up to `params.max_rooms` rooms,
+/// largest backlog first.
+pub async fn compact_state_groups(
+    pool: &PgPool,
+    params: &StateCompactionParams,
+) -> Result<StateCompactionStats, sqlx::Error> {
+    let archive_snapshot_interval =
+        params.archive_snapshot_interval.clamp(STATE_GROUP_SNAPSHOT_INTERVAL, MAX_ARCHIVE_SNAPSHOT_INTERVAL);
+    let rooms: Vec<(String,)> = sqlx::query_as(
+        r#"
+        SELECT g.room_id
+        FROM state_groups g
+        LEFT JOIN state_compaction_progress p ON p.room_id = g.room_id
+        WHERE g.stream_ordering IS NOT NULL
+          AND g.created_ts < $1
+          AND g.id > COALESCE(p.last_state_group_id, 0)
+        GROUP BY g.room_id
+        ORDER BY COUNT(*) DESC
+        LIMIT $2
+        "#,
+    )
+    .bind(params.older_than_ts)
+    .bind(params.max_rooms)
+    .fetch_all(pool)
+    .await?;
+
+    let mut stats = StateCompactionStats::default();
+    for (room_id,) in rooms {
+        let room_stats = compact_room(pool, &room_id, params.older_than_ts, archive_snapshot_interval).await?;
+        stats.add(&room_stats);
+    }
+    Ok(stats)
+}
+
+async fn compact_room(
+    pool: &PgPool,
+    room_id: &str,
+    older_than_ts: i64,
+    archive_snapshot_interval: i32,
+) -> Result<StateCompactionStats, sqlx::Error> {
+    let mut tx = pool.begin().await?;
+    // Same lock as new state groups take, so the room's head stays put.
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))").bind(room_id).execute(&mut *tx).await?;
+
+    let resume_after: Option<(i64, i32)> = sqlx::query_as(
+        r#"
+        SELECT g.id, g.delta_depth
+        FROM state_compaction_progress p
+        JOIN state_groups g ON g.id = p.last_state_group_id
+        WHERE p.room_id = $1
+        "#,
+    )
+    .bind(room_id)
+    .fetch_optional(&mut *tx)
+    .await?;
+
+    let rows: Vec<(i64, bool, i32, Option<i64>)> = sqlx::query_as(
+        r#"
+        SELECT g.id, g.is_snapshot, g.delta_depth, e.prev_state_group_id
+        FROM state_groups g
+        LEFT JOIN state_group_edges e ON e.state_group_id = g.id
+        WHERE g.room_id = $1
+          AND g.stream_ordering IS NOT NULL
+          AND g.created_ts < $2
+          AND g.id > $3
+        ORDER BY g.id
+        LIMIT $4
+        "#,
+    )
+    .bind(room_id)
+    .bind(older_than_ts)
+    .bind(resume_after.map_or(0, |(id, _)| id))
+    .bind(MAX_GROUPS_PER_ROOM_PASS)
+    .fetch_all(&mut *tx)
+    .await?;
+
+    // Groups are written as a single chain starting at a snapshot; anything
+    // else is left alone.
+    let mut expected_prev = resume_after.map(|(id, _)| id);
+    let mut chain_len = 0;
+    for (id, _, _, prev) in &rows {
+        if *prev != expected_prev {
+            break;
+        }
+        expected_prev = Some(*id);
+        chain_len += 1;
+    }
+    if let Some(&(group_id, ..)) = rows.get(chain_len) {
+        tracing::warn!(room_id = %room_id, group_id, "State group chain branches; compaction skips past it");
+        if chain_len == 0 {
+            // Left as stored; later groups continue from its resolved state.
+            record_progress(&mut tx, room_id, group_id, 0).await?;
+            tx.commit().await?;
+            return Ok(StateCompactionStats::default());
+        }
+    }
+    let rows = &rows[..chain_len];
+    let Some(&(last_id, ..)) = rows.last() else {
+        return Ok(StateCompactionStats::default());
+    };
+
+    let ids: Vec<i64> = rows.iter().map(|(id, ..)| *id).collect();
+    let entry_rows: Vec<(i64, String, String, String)> = sqlx::query_as(
+        "SELECT state_group_id, event_type, state_key, event_id FROM state_group_state WHERE state_group_id = ANY($1)",
+    )
+    .bind(&ids)
+    .fetch_all(&mut *tx)
+    .await?;
+    let mut entries_by_group: HashMap<i64, StateMap> = HashMap::new();
+    for (group_id, event_type, state_key, event_id) in entry_rows {
+        entries_by_group.entry(group_id).or_default().insert((event_type, state_key), event_id);
+    }
+    let groups: Vec<LoadedGroup> = rows
+        .iter()
+        .map(|&(id, is_snapshot, delta_depth, _)| LoadedGroup {
+            id,
+            is_snapshot,
+            delta_depth,
+            entries: entries_by_group.remove(&id).unwrap_or_default(),
+        })
+        .collect();
+
+    let base = match resume_after {
+        Some((id, delta_depth)) => {
+            let state: Vec<(String, String, String)> =
+                sqlx::query_as(RESOLVE_STATE_GROUP_SQL).bind(id).fetch_all(&mut *tx).await?;
+            let state = state.into_iter().map(|(event_type, state_key, event_id)| ((event_type, state_key), event_id));
+            Some(CompactionBase { id, state: state.collect(), delta_depth })
+        }
+        None => None,
+    };
+
+    let plan = plan_compaction(base, &groups, archive_snapshot_interval);
+    for (group_id, rewrite) in &plan.rewrites {
+        match rewrite {
+            GroupRewrite::Merge { into } => {
+                sqlx::query("UPDATE state_group_edges SET prev_state_group_id = $2 WHERE prev_state_group_id = $1")
+                    .bind(group_id)
+                    .bind(into)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("UPDATE event_to_state_groups SET state_group_id = $2 WHERE state_group_id = $1")
+                    .bind(group_id)
+                    .bind(into)
+                    .execute(&mut *tx)
+                    .await?;
+                sqlx::query("DELETE FROM state_groups WHERE id = $1").bind(group_id).execute(&mut *tx).await?;
+            }
+            GroupRewrite::Store { is_snapshot, delta_depth, entries } => {
+                sqlx::query("DELETE FROM state_group_state WHERE state_group_id = $1")
+                    .bind(group_id)
+                    .execute(&mut *tx)
+                    .await?;
+                let (mut event_types, mut state_keys, mut event_ids) = (Vec::new(), Vec::new(), Vec::new());
+                for ((event_type, state_key), event_id) in entries {
+                    event_types.push(event_type.as_str());
+                    state_keys.push(state_key.as_str());
+                    event_ids.push(event_id.as_str());
+                }
+                sqlx::query(
+                    r#"
+                    INSERT INTO state_group_state (state_group_id, event_type, state_key, event_id)
+                    SELECT $1, unnest($2::text[]), unnest($3::text[]), unnest($4::text[])
+                    "#,
+                )
+                .bind(group_id)
+                .bind(&event_types)
+                .bind(&state_keys)
+                .bind(&event_ids)
+                .execute(&mut *tx)
+                .await?;
+                sqlx::query("UPDATE state_groups SET is_snapshot = $2, delta_depth = $3 WHERE id = $1")
+                    .bind(group_id)
+                    .bind(is_snapshot)
+                    .bind(delta_depth)
+                    .execute(&mut *tx)
+                    .await?;
+            }
+        }
+    }
+
+    let mut stats = plan.stats;
+    stats.rooms = 1;
+    record_progress(&mut tx, room_id, plan.last_group.unwrap_or(last_id), stats.rows_saved()).await?;
+    tx.commit().await?;
+    Ok(stats)
+}
+
+async fn record_progress(
+    conn: &mut sqlx::PgConnection,
+    room_id: &str,
+    last_state_group_id: i64,
+    rows_saved: u64,
+) -> Result<(), sqlx::Error> {
+    sqlx::query(
+        r#"
+        INSERT INTO state_compaction_progress (room_id, last_state_group_id, rows_saved, compacted_ts)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (room_id) DO UPDATE SET
+            last_state_group_id = EXCLUDED.last_state_group_id,
+            rows_saved = state_compaction_progress.rows_saved + EXCLUDED.rows_saved,
+            compacted_ts = EXCLUDED.compacted_ts
+        "#,
+    )
+    .bind(room_id)
+    .bind(last_state_group_id)
+    .bind(i64::try_from(rows_saved).unwrap_or(i64::MAX))
+    .bind(synapse_common::current_timestamp_millis())
+    .execute(conn)
+    .await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(event_type: &str, state_key: &str, event_id: &str) -> ((String, String), String) {
+        ((event_type.to_string(), state_key.to_string()), event_id.to_string())
+    }
+
+    fn snapshot(id: i64, entries: impl IntoIterator<Item = ((String, String), String)>) -> LoadedGroup {
+        LoadedGroup { id, is_snapshot: true, delta_depth: 0, entries: entries.into_iter().collect() }
+    }
+
+    fn delta(id: i64, delta_depth: i32, entries: impl IntoIterator<Item = ((String, String), String)>) -> LoadedGroup {
+        LoadedGroup { id, is_snapshot: false, delta_depth, entries: entries.into_iter().collect() }
+    }
+
+    #[test]
+    fn group_without_changes_is_merged_into_its_parent() {
+        let create = entry("m.room.create", "", "$create");
+        let groups = vec![snapshot(1, [create.clone()]), delta(2, 1, [create])];
+        let plan = plan_compaction(None, &groups, 500);
+        assert_eq!(plan.rewrites, vec![(2, GroupRewrite::Merge { into: 1 })]);
+        assert_eq!(plan.last_group, Some(1));
+        assert_eq!(plan.stats.groups_merged, 1);
+        assert_eq!(plan.stats.rows_saved(), 1);
+    }
+
+    #[test]
+    fn intermediate_snapshots_become_deltas() {
+        let create = entry("m.room.create", "", "$create");
+        let alice = entry("m.room.member", "@alice:test", "$alice");
+        let bob = entry("m.room.member", "@bob:test", "$bob");
+        let groups = vec![
+            snapshot(1, [create.clone()]),
+            delta(2, 1, [alice.clone()]),
+            snapshot(3, [create, alice, bob.clone()]),
+        ];
+        let plan = plan_compaction(None, &groups, 500);
+        assert_eq!(
+            plan.rewrites,
+            vec![(3, GroupRewrite::Store { is_snapshot: false, delta_depth: 2, entries: [bob].into_iter().collect() })]
+        );
+        assert_eq!(plan.stats.snapshots_thinned, 1);
+        assert_eq!(plan.stats.rows_saved(), 2);
+    }
+
+    #[test]
+    fn long_delta_chains_are_rolled_up() {
+        let create = entry("m.room.create", "", "$create");
+        let topic = entry("m.room.topic", "", "$topic");
+        let base = CompactionBase { id: 10, state: [create.clone()].into_iter().collect(), delta_depth: 3 };
+        let groups = vec![delta(11, 4, [topic.clone()])];
+        let plan = plan_compaction(Some(base), &groups, 4);
+        assert_eq!(
+            plan.rewrites,
+            vec![(
+                11,
+                GroupRewrite::Store {
+                    is_snapshot: true,
+                    delta_depth: 0,
+                    entries: [create, topic].into_iter().collect()
+                }
+            )]
+        );
+        assert_eq!(plan.stats.snapshots_created, 1);
+    }
+
+    #[test]
+    fn redundant_delta_entries_are_dropped() {
+        let create = entry("m.room.create", "", "$create");
+        let topic = entry("m.room.topic", "", "$topic");
+        let groups = vec![snapshot(1, [create.clone()]), delta(2, 1, [create, topic.clone()])];
+        let plan = plan_compaction(None, &groups, 500);
+        assert_eq!(
+            plan.rewrites,
+            vec![(
+                2,
+                GroupRewrite::Store { is_snapshot: false, delta_depth: 1, entries: [topic].into_iter().collect() }
+            )]
+        );
+    }
+}
@@ -20,8 +20,28 @@ const EVENT_TO_STATE_GROUP_COLS: &str = "event_id, state_group_id";
 /// Columns for `state_group_state`.
 const STATE_GROUP_STATE_COLS: &str = "state_group_id, event_type, state_key, event_id";
 
-/// Inner columns for `state_group_state` (without state_group_id).
-const STATE_GROUP_STATE_INNER_COLS: &str = "event_type, state_key, event_id";
+/// A room's state groups are deltas holding only the entry their event
+/// changed; every this many groups the full state is stored instead, which
+/// bounds how far back resolving any group has to walk.
+pub const STATE_GROUP_SNAPSHOT_INTERVAL: i32 = 100;
+
+/// `(event_type, state_key, event_id)` rows of the full state at group `$1`:
+/// the group's own entries override those of its parents, and the walk stops
+/// at the nearest snapshot.
+pub(crate) const RESOLVE_STATE_GROUP_SQL: &str = "
+    WITH RECURSIVE chain(id, distance) AS (
+        SELECT $1::BIGINT, 0
+        UNION ALL
+        SELECT e.prev_state_group_id, c.distance + 1
+        FROM chain c
+        JOIN state_groups g ON g.id = c.id AND NOT g.is_snapshot
+        JOIN state_group_edges e ON e.state_group_id = c.id
+        WHERE c.distance < 1000
+    )
+    SELECT DISTINCT ON (s.event_type, s.state_key) s.event_type, s.state_key, s.event_id
+    FROM chain c
+    JOIN state_group_state s ON s.state_group_id = c.id
+    ORDER BY s.event_type, s.state_key, c.distance";
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::FromRow)]
 pub struct StateGroup {
@@ -381,67 +401,183 @@ impl StateGroupStorage {
         Ok(row.map(|r| r.0))
     }
 
-    /// 递归获取某个 state_group 的状态（沿 DAG 边向上查找）
+    /// 递归获取某个 state_group 的状态（沿 DAG 边向上查找，止于最近的快照）
     pub async fn resolve_state_for_group(
         &self,
         state_group_id: i64,
     ) -> Result<std::collections::HashMap<(String, String), String>, sqlx::Error> {
-        use std::collections::{HashMap, HashSet, VecDeque};
-
         tracing::debug!(state_group_id = state_group_id, "Resolving state for group");
 
-        let mut result: HashMap<(String, String), String> = HashMap::new();
-        let mut visited: HashSet<i64> = HashSet::new();
-        let mut queue: VecDeque<i64> = VecDeque::new();
-        queue.push_back(state_group_id);
-
-        let mut depth = 0i64;
-
-        while let Some(sg_id) = queue.pop_front() {
-            depth += 1;
-            if depth > 100 {
-                tracing::warn!(
-                    state_group_id = state_group_id,
-                    depth = depth,
-                    "State resolution depth exceeds 100, possible cycle"
-                );
-            }
-
-            if !visited.insert(sg_id) {
-                continue;
-            }
-
-            // Load state entries for this group
-            let state_rows: Vec<(String, String, String)> = sqlx::query_as(&format!(
-                "SELECT {}
-                     FROM state_group_state WHERE state_group_id = $1",
-                STATE_GROUP_STATE_INNER_COLS
+        let rows: Vec<(String, String, String)> =
+            sqlx::query_as(RESOLVE_STATE_GROUP_SQL).bind(state_group_id).fetch_all(&self.pool).await?;
+
+        Ok(rows.into_iter().map(|(event_type, state_key, event_id)| ((event_type, state_key), event_id)).collect())
+    }
+
+    /// The state group in effect at stream position `stream_ordering`, if the
+    /// room had one by then.
+    pub async fn get_state_group_at(&self, room_id: &str, stream_ordering: i64) -> Result<Option<i64>, sqlx::Error> {
+        state_group_at(&self.pool, room_id, stream_ordering).await
+    }
+}
+
+/// How the state group for a new state event is stored.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum NextStateGroup {
+    /// Full state: the room's first group, or the delta chain is long enough.
+    Snapshot { prev: Option<i64> },
+    /// Only the changed entry, on top of `prev`.
+    Delta { prev: i64, delta_depth: i32 },
+    /// The event is older than the room's latest group (backfill); current
+    /// state ignores it, so no group is created.
+    Skip,
+}
+
+/// The room's latest state group as `(id, delta_depth, created_ts)`.
+pub(crate) fn next_state_group(head: Option<(i64, i32, i64)>, origin_server_ts: i64) -> NextStateGroup {
+    match head {
+        None => NextStateGroup::Snapshot { prev: None },
+        Some((_, _, head_ts)) if origin_server_ts < head_ts => NextStateGroup::Skip,
+        Some((prev, delta_depth, _)) if delta_depth + 1 >= STATE_GROUP_SNAPSHOT_INTERVAL => {
+            NextStateGroup::Snapshot { prev: Some(prev) }
+        }
+        Some((prev, delta_depth, _)) => NextStateGroup::Delta { prev, delta_depth: delta_depth + 1 },
+    }
+}
+
+pub(crate) async fn state_group_at(
+    executor: impl sqlx::PgExecutor<'_>,
+    room_id: &str,
+    stream_ordering: i64,
+) -> Result<Option<i64>, sqlx::Error> {
+    let row: Option<(i64,)> = sqlx::query_as(
+        r#"
+        SELECT id FROM state_groups
+        WHERE room_id = $1 AND stream_ordering IS NOT NULL AND stream_ordering <= $2
+        ORDER BY stream_ordering DESC
+        LIMIT 1
+        "#,
+    )
+    .bind(room_id)
+    .bind(stream_ordering)
+    .fetch_optional(executor)
+    .await?;
+    Ok(row.map(|r| r.0))
+}
+
+/// Records the state group created by the state event `event`, which must
+/// already be inserted on `conn`. Must run inside a transaction: groups of
+/// one room are serialized with an advisory lock held until commit.
+///
+/// A room without groups (created before state groups were written) starts
+/// with a snapshot of its current state taken from `events`.
+pub(crate) async fn record_state_event(
+    conn: &mut sqlx::PgConnection,
+    event: &crate::event::RoomEvent,
+) -> Result<(), sqlx::Error> {
+    let Some(state_key) = event.state_key.as_deref() else {
+        return Ok(());
+    };
+
+    sqlx::query("SELECT pg_advisory_xact_lock(hashtext($1))").bind(&event.room_id).execute(&mut *conn).await?;
+
+    let head: Option<(i64, i32, i64)> = sqlx::query_as(
+        "SELECT id, delta_depth, created_ts FROM state_groups WHERE room_id = $1 ORDER BY id DESC LIMIT 1",
+    )
+    .bind(&event.room_id)
+    .fetch_optional(&mut *conn)
+    .await?;
+
+    let next = next_state_group(head, event.origin_server_ts);
+    let (prev, is_snapshot, delta_depth) = match next {
+        NextStateGroup::Skip => return Ok(()),
+        NextStateGroup::Snapshot { prev } => (prev, true, 0),
+        NextStateGroup::Delta { prev, delta_depth } => (Some(prev), false, delta_depth),
+    };
+
+    let inserted: Option<(i64,)> = sqlx::query_as(
+        r#"
+        INSERT INTO state_groups (room_id, event_id, state_hash, created_ts, is_snapshot, delta_depth, stream_ordering)
+        VALUES ($1, $2, encode(sha256(convert_to($1 || '|' || $2, 'UTF8')), 'hex'), $3, $4, $5, $6)
+        ON CONFLICT (state_hash) DO NOTHING
+        RETURNING id
+        "#,
+    )
+    .bind(&event.room_id)
+    .bind(&event.event_id)
+    .bind(event.origin_server_ts)
+    .bind(is_snapshot)
+    .bind(delta_depth)
+    .bind(event.stream_ordering)
+    .fetch_optional(&mut *conn)
+    .await?;
+    // Already recorded, e.g. the same event persisted twice.
+    let Some((group_id,)) = inserted else {
+        return Ok(());
+    };
+
+    match (is_snapshot, prev) {
+        (true, None) => {
+            sqlx::query(
+                r#"
+                INSERT INTO state_group_state (state_group_id, event_type, state_key, event_id)
+                SELECT DISTINCT ON (event_type, state_key) $1, event_type, state_key, event_id
+                FROM events
+                WHERE purged_at IS NULL AND NOT soft_failed AND room_id = $2 AND state_key IS NOT NULL
+                ORDER BY event_type, state_key, origin_server_ts DESC, event_id DESC
+                "#,
+            )
+            .bind(group_id)
+            .bind(&event.room_id)
+            .execute(&mut *conn)
+            .await?;
+        }
+        (true, Some(prev)) => {
+            sqlx::query(&format!(
+                "INSERT INTO state_group_state (state_group_id, event_type, state_key, event_id) \
+                 SELECT $2, event_type, state_key, event_id FROM ({RESOLVE_STATE_GROUP_SQL}) resolved"
             ))
-            .bind(sg_id)
-            .fetch_all(&self.pool)
+            .bind(prev)
+            .bind(group_id)
+            .execute(&mut *conn)
             .await?;
-
-            for (event_type, state_key, event_id) in state_rows {
-                let key = (event_type, state_key);
-                result.entry(key).or_insert(event_id);
-            }
-
-            // Load prev groups
-            let prev_rows: Vec<(i64,)> =
-                sqlx::query_as(r#"SELECT prev_state_group_id FROM state_group_edges WHERE state_group_id = $1"#)
-                    .bind(sg_id)
-                    .fetch_all(&self.pool)
-                    .await?;
-
-            for (prev_id,) in prev_rows {
-                if !visited.contains(&prev_id) {
-                    queue.push_back(prev_id);
-                }
-            }
         }
+        (false, _) => {}
+    }
 
-        Ok(result)
+    if let Some(prev) = prev {
+        sqlx::query("INSERT INTO state_group_edges (state_group_id, prev_state_group_id) VALUES ($1, $2)")
+            .bind(group_id)
+            .bind(prev)
+            .execute(&mut *conn)
+            .await?;
     }
+    sqlx::query(
+        r#"
+        INSERT INTO state_group_state (state_group_id, event_type, state_key, event_id)
+        VALUES ($1, $2, $3, $4)
+        ON CONFLICT (state_group_id, event_type, state_key) DO UPDATE SET event_id = EXCLUDED.event_id
+        "#,
+    )
+    .bind(group_id)
+    .bind(&event.event_type)
+    .bind(state_key)
+    .bind(&event.event_id)
+    .execute(&mut *conn)
+    .await?;
+    sqlx::query(
+        r#"
+        INSERT INTO event_to_state_groups (event_id, state_group_id)
+        VALUES ($1, $2)
+        ON CONFLICT (event_id) DO UPDATE SET state_group_id = EXCLUDED.state_group_id
+        "#,
+    )
+    .bind(&event.event_id)
+    .bind(group_id)
+    .execute(&mut *conn)
+    .await?;
+
+    Ok(())
 }
 
 #[async_trait]
@@ -528,6 +664,34 @@ impl StateGroupStoreApi for StateGroupStorage {
     }
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn first_group_of_a_room_is_a_snapshot() {
+        assert_eq!(next_state_group(None, 1_000), NextStateGroup::Snapshot { prev: None });
+    }
+
+    #[test]
+    fn groups_are_deltas_until_the_snapshot_interval() {
+        assert_eq!(next_state_group(Some((7, 0, 1_000)), 1_000), NextStateGroup::Delta { prev: 7, delta_depth: 1 });
+        assert_eq!(
+            next_state_group(Some((7, STATE_GROUP_SNAPSHOT_INTERVAL - 2, 1_000)), 2_000),
+            NextStateGroup::Delta { prev: 7, delta_depth: STATE_GROUP_SNAPSHOT_INTERVAL - 1 }
+        );
+        assert_eq!(
+            next_state_group(Some((7, STATE_GROUP_SNAPSHOT_INTERVAL - 1, 1_000)), 2_000),
+            NextStateGroup::Snapshot { prev: Some(7) }
+        );
+    }
+
+    #[test]
+    fn backfilled_state_does_not_move_the_head() {
+        assert_eq!(next_state_group(Some((7, 3, 2_000)), 1_999), NextStateGroup::Skip);
+    }
+}
+
 #[cfg(test)]
 mod db_tests {
     use super::*;
@@ -130,6 +130,24 @@ impl BackgroundUpdateStoreApi for InMemoryBackgroundUpdateStore {
         Ok(update.clone())
     }
 
+    async fn save_batch_progress(
+        &self,
+        job_name: &str,
+        progress: serde_json::Value,
+        items_processed: i32,
+        total_items: Option<i32>,
+    ) -> Result<BackgroundUpdate, sqlx::Error> {
+        let mut updates = self.updates.write().await;
+        let update = updates.get_mut(job_name).ok_or_else(|| sqlx::Error::RowNotFound)?;
+        update.progress = progress;
+        update.processed_items += items_processed;
+        if let Some(t) = total_items {
+            update.total_items = t;
+        }
+        update.updated_ts = Some(current_timestamp_millis());
+        Ok(update.clone())
+    }
+
     async fn set_error(&self, job_name: &str, error_message: &str) -> Result<BackgroundUpdate, sqlx::Error> {
         let mut updates = self.updates.write().await;
         let update = updates.get_mut(job_name).ok_or_else(|| sqlx::Error::RowNotFound)?;
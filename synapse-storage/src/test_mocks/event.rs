@@ -5,11 +5,17 @@ use synapse_common::current_timestamp_millis;
 #[derive(Clone, Default)]
 pub struct InMemoryEventStore {
     events: Arc<RwLock<HashMap<String, crate::event::RoomEvent>>>, // event_id → event
+    soft_failed: Arc<RwLock<Vec<(String, crate::event::SoftFailedEvent)>>>, // (room_id, event)
+    rejected: Arc<RwLock<Vec<crate::event::RejectedEvent>>>,
 }
 
 impl InMemoryEventStore {
     pub fn new() -> Self {
-        Self { events: Arc::new(RwLock::new(HashMap::new())) }
+        Self {
+            events: Arc::new(RwLock::new(HashMap::new())),
+            soft_failed: Arc::new(RwLock::new(Vec::new())),
+            rejected: Arc::new(RwLock::new(Vec::new())),
+        }
     }
 
     pub async fn create_event(
@@ -259,6 +265,54 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(matched)
     }
 
+    async fn get_room_events_page(
+        &self,
+        room_id: &str,
+        from: i64,
+        to: Option<i64>,
+        limit: i64,
+        direction: &str,
+        filter: Option<&crate::event::EventQueryFilter>,
+    ) -> Result<Vec<crate::event::RoomEvent>, sqlx::Error> {
+        let backwards = direction != "f";
+        let events = self.events.read().await;
+        let mut matched: Vec<_> = events
+            .values()
+            .filter(|e| e.room_id == room_id)
+            .filter(|e| {
+                let stream = e.stream_ordering.unwrap_or(0);
+                if backwards {
+                    stream <= from && to.is_none_or(|to| stream > to)
+                } else {
+                    stream > from && to.is_none_or(|to| stream <= to)
+                }
+            })
+            .filter(|e| {
+                filter.is_none_or(|f| {
+                    f.types.as_ref().is_none_or(|types| types.is_empty() || types.contains(&e.event_type))
+                        && f.senders.as_ref().is_none_or(|senders| senders.is_empty() || senders.contains(&e.user_id))
+                })
+            })
+            .cloned()
+            .collect();
+        matched.sort_by_key(|e| e.stream_ordering.unwrap_or(0));
+        if backwards {
+            matched.reverse();
+        }
+        matched.truncate(limit as usize);
+        Ok(matched)
+    }
+
+    async fn get_room_stream_position(&self, room_id: &str, before_ts: Option<i64>) -> Result<i64, sqlx::Error> {
+        let events = self.events.read().await;
+        Ok(events
+            .values()
+            .filter(|e| e.room_id == room_id && before_ts.is_none_or(|ts| e.origin_server_ts < ts))
+            .filter_map(|e| e.stream_ordering)
+            .max()
+            .unwrap_or(0))
+    }
+
     async fn get_room_events_batch(
         &self,
         room_ids: &[String],
@@ -269,13 +323,12 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
             room_ids.iter().map(|id| (id.clone(), Vec::new())).collect();
         for (_eid, event) in events.iter() {
             if let Some(bucket) = result.get_mut(&event.room_id) {
-                if bucket.len() < limit_per_room as usize {
-                    bucket.push(event.clone());
-                }
+                bucket.push(event.clone());
             }
         }
         for bucket in result.values_mut() {
-            bucket.sort_by_key(|e| std::cmp::Reverse(e.origin_server_ts));
+            bucket.sort_by_key(|e| std::cmp::Reverse(e.stream_ordering.unwrap_or(0)));
+            bucket.truncate(limit_per_room.max(0) as usize);
         }
         Ok(result)
     }
@@ -303,22 +356,12 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
                 }
             }
             if let Some(bucket) = result.get_mut(&event.room_id) {
-                if bucket.len() < limit_per_room as usize {
-                    bucket.push(event.clone());
-                }
+                bucket.push(event.clone());
             }
         }
-        match since {
-            crate::event::SinceFilter::OriginServerTs(_) => {
-                for bucket in result.values_mut() {
-                    bucket.sort_by_key(|e| e.origin_server_ts);
-                }
-            }
-            crate::event::SinceFilter::StreamOrdering(_) => {
-                for bucket in result.values_mut() {
-                    bucket.sort_by_key(|e| e.stream_ordering.unwrap_or(0));
-                }
-            }
+        for bucket in result.values_mut() {
+            bucket.sort_by_key(|e| std::cmp::Reverse(e.stream_ordering.unwrap_or(0)));
+            bucket.truncate(limit_per_room.max(0) as usize);
         }
         Ok(result)
     }
@@ -488,6 +531,14 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(results)
     }
 
+    async fn get_state_events_at_event(
+        &self,
+        event: &crate::event::RoomEvent,
+    ) -> Result<Vec<crate::event::StateEvent>, sqlx::Error> {
+        crate::event::reader::EventReader::get_state_events_at_or_before(self, &event.room_id, event.origin_server_ts)
+            .await
+    }
+
     async fn get_events_map(
         &self,
         event_ids: &[String],
@@ -532,6 +583,15 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(room_ids.iter().map(|id| (id.clone(), Vec::new())).collect())
     }
 
+    async fn has_ephemeral_events_since(
+        &self,
+        _room_ids: &[String],
+        _since_ts: i64,
+        _now: i64,
+    ) -> Result<bool, sqlx::Error> {
+        Ok(false)
+    }
+
     async fn get_state_events_batch(
         &self,
         room_ids: &[String],
@@ -603,10 +663,52 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(result)
     }
 
+    async fn get_member_events_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, crate::event::StateEvent>, sqlx::Error> {
+        let members = self.get_state_events_by_type_batch(room_ids, "m.room.member").await?;
+        Ok(members
+            .into_iter()
+            .filter_map(|(room_id, events)| {
+                events
+                    .into_iter()
+                    .filter(|event| event.state_key.as_deref() == Some(user_id))
+                    .max_by_key(|event| event.stream_ordering.unwrap_or(0))
+                    .map(|event| (room_id, event))
+            })
+            .collect())
+    }
+
+    async fn get_visibility_history_batch(
+        &self,
+        room_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, Vec<crate::event::StateEvent>>, sqlx::Error> {
+        let mut members = self.get_state_events_by_type_batch(room_ids, "m.room.member").await?;
+        let mut visibility = self.get_state_events_by_type_batch(room_ids, "m.room.history_visibility").await?;
+        Ok(room_ids
+            .iter()
+            .map(|room_id| {
+                let mut history: Vec<_> = members
+                    .remove(room_id)
+                    .unwrap_or_default()
+                    .into_iter()
+                    .filter(|event| event.state_key.as_deref() == Some(user_id))
+                    .chain(visibility.remove(room_id).unwrap_or_default())
+                    .collect();
+                history.sort_by_key(|event| (event.stream_ordering.unwrap_or(0), event.origin_server_ts));
+                (room_id.clone(), history)
+            })
+            .collect())
+    }
+
     async fn get_state_events_since_batch(
         &self,
         room_ids: &[String],
         since: crate::event::SinceFilter,
+        until: &HashMap<String, i64>,
     ) -> Result<HashMap<String, Vec<crate::event::StateEvent>>, sqlx::Error> {
         let events = self.events.read().await;
         let filter_by = match since {
@@ -620,7 +722,10 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         };
         let mut result: HashMap<String, Vec<crate::event::StateEvent>> =
             room_ids.iter().map(|id| (id.clone(), Vec::new())).collect();
-        for event in events.values().filter(filter_by) {
+        let before_timeline = |e: &&crate::event::RoomEvent| {
+            until.get(&e.room_id).is_none_or(|until| e.stream_ordering.unwrap_or(0) < *until)
+        };
+        for event in events.values().filter(filter_by).filter(before_timeline) {
             if let Some(bucket) = result.get_mut(&event.room_id) {
                 bucket.push(crate::event::StateEvent {
                     event_id: event.event_id.clone(),
@@ -693,6 +798,28 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(false)
     }
 
+    async fn get_room_events_after_stream(
+        &self,
+        room_ids: &[String],
+        after: i64,
+        limit: i64,
+    ) -> Result<Vec<crate::event::RoomEvent>, sqlx::Error> {
+        let events = self.events.read().await;
+        let mut matching: Vec<_> = events
+            .values()
+            .filter(|event| room_ids.contains(&event.room_id) && event.stream_ordering.unwrap_or(0) > after)
+            .cloned()
+            .collect();
+        matching.sort_by_key(|event| event.stream_ordering.unwrap_or(0));
+        matching.truncate(limit.max(0) as usize);
+        Ok(matching)
+    }
+
+    async fn get_max_stream_ordering(&self) -> Result<i64, sqlx::Error> {
+        let events = self.events.read().await;
+        Ok(events.values().filter_map(|event| event.stream_ordering).max().unwrap_or(0))
+    }
+
     async fn find_missing_event_ids(&self, event_ids: &[String]) -> Result<Vec<String>, sqlx::Error> {
         let events = self.events.read().await;
         Ok(event_ids.iter().filter(|id| !events.contains_key(*id)).cloned().collect())
@@ -708,11 +835,241 @@ impl crate::event::reader::EventReader for InMemoryEventStore {
         Ok(Vec::new())
     }
 
+    async fn get_forward_extremities(&self, room_id: &str, limit: i64) -> Result<Vec<String>, sqlx::Error> {
+        self.get_latest_event_ids_in_room(room_id, limit).await
+    }
+
     async fn get_forward_extremities_count(&self, room_id: &str) -> Result<i64, sqlx::Error> {
         let events = self.events.read().await;
         Ok(events.values().filter(|e| e.room_id == room_id).count() as i64)
     }
 
+    async fn get_rooms_by_forward_extremities(
+        &self,
+        min_count: i64,
+        limit: i64,
+    ) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let events = self.events.read().await;
+        let mut counts: HashMap<String, i64> = HashMap::new();
+        for event in events.values() {
+            *counts.entry(event.room_id.clone()).or_default() += 1;
+        }
+        let mut rooms: Vec<_> = counts.into_iter().filter(|(_, count)| *count >= min_count).collect();
+        rooms.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+        rooms.truncate(limit as usize);
+        Ok(rooms)
+    }
+
+    async fn get_event_stream_ordering_in_room(
+        &self,
+        room_id: &str,
+        event_id: &str,
+    ) -> Result<Option<i64>, sqlx::Error> {
+        let events = self.events.read().await;
+        Ok(events.get(event_id).filter(|e| e.room_id == room_id).and_then(|e| e.stream_ordering))
+    }
+
+    async fn get_room_pdus(
+        &self,
+        room_id: &str,
+        after: Option<i64>,
+        until: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<crate::event::ExportedPdu>, sqlx::Error> {
+        let events = self.events.read().await;
+        let mut pdus: Vec<crate::event::ExportedPdu> = events
+            .values()
+            .filter(|e| e.room_id == room_id)
+            .filter_map(|e| {
+                let stream_ordering = e.stream_ordering?;
+                let in_range = after.is_none_or(|a| stream_ordering > a) && until.is_none_or(|u| stream_ordering <= u);
+                in_range.then(|| exported_pdu(e, stream_ordering))
+            })
+            .collect();
+        pdus.sort_by_key(|p| p.stream_ordering);
+        pdus.truncate(limit.max(0) as usize);
+        Ok(pdus)
+    }
+
+    async fn get_auth_chain_pdus(
+        &self,
+        room_id: &str,
+        event_ids: &[String],
+    ) -> Result<Vec<crate::event::ExportedPdu>, sqlx::Error> {
+        // Auth events are not recorded in memory, so only the requested
+        // events themselves are returned.
+        let events = self.events.read().await;
+        Ok(event_ids
+            .iter()
+            .filter_map(|id| events.get(id))
+            .filter(|e| e.room_id == room_id)
+            .map(|e| exported_pdu(e, e.stream_ordering.unwrap_or(0)))
+            .collect())
+    }
+
+    async fn get_room_dag_diagnostics(
+        &self,
+        room_id: &str,
+        depth: Option<i64>,
+    ) -> Result<crate::event::RoomDagDiagnostics, sqlx::Error> {
+        let events = self.events.read().await;
+        let room_events: Vec<_> = events.values().filter(|e| e.room_id == room_id).collect();
+        let graph_depths = || room_events.iter().map(|e| e.depth).filter(|d| *d > 0);
+        let stats = crate::event::DagDepthStats {
+            event_count: room_events.len() as i64,
+            events_without_depth: room_events.iter().filter(|e| e.depth <= 0).count() as i64,
+            min_depth: graph_depths().min(),
+            max_depth: graph_depths().max(),
+        };
+        Ok(crate::event::RoomDagDiagnostics {
+            min_depth: depth.zip(stats.max_depth).map(|(depth, max_depth)| (max_depth - depth).max(1)),
+            depth: stats,
+            ..Default::default()
+        })
+    }
+
+    async fn get_soft_failed_events(
+        &self,
+        room_id: &str,
+        kind: Option<crate::event::SoftFailKind>,
+        before: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<crate::event::SoftFailedEvent>, sqlx::Error> {
+        let soft_failed = self.soft_failed.read().await;
+        let mut matched: Vec<_> = soft_failed
+            .iter()
+            .filter(|(room, _)| room == room_id)
+            .map(|(_, e)| e)
+            .filter(|e| kind.is_none_or(|kind| e.soft_fail_kind == kind.as_str()))
+            .filter(|e| before.is_none_or(|before| e.stream_ordering < before))
+            .cloned()
+            .collect();
+        matched.sort_by_key(|e| std::cmp::Reverse(e.stream_ordering));
+        matched.truncate(limit as usize);
+        Ok(matched)
+    }
+
+    async fn count_soft_failed_events(&self, room_id: &str) -> Result<Vec<(String, i64)>, sqlx::Error> {
+        let soft_failed = self.soft_failed.read().await;
+        let mut counts: std::collections::BTreeMap<String, i64> = std::collections::BTreeMap::new();
+        for (_, event) in soft_failed.iter().filter(|(room, _)| room == room_id) {
+            *counts.entry(event.soft_fail_kind.clone()).or_default() += 1;
+        }
+        Ok(counts.into_iter().collect())
+    }
+
+    async fn get_rejected_events(
+        &self,
+        filter: &crate::event::RejectedEventFilter,
+        limit: i64,
+    ) -> Result<Vec<crate::event::RejectedEvent>, sqlx::Error> {
+        let rejected = self.rejected.read().await;
+        Ok(rejected
+            .iter()
+            .rev()
+            .filter(|e| filter.room_id.is_none() || e.room_id == filter.room_id)
+            .filter(|e| filter.origin.as_ref().is_none_or(|origin| &e.origin == origin))
+            .filter(|e| filter.reason.is_none_or(|reason| e.reason == reason.as_str()))
+            .filter(|e| filter.before.is_none_or(|before| e.id < before))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_event_relations(
+        &self,
+        room_id: &str,
+        parent_id: &str,
+        params: &crate::event::EventRelationsQuery,
+    ) -> Result<Vec<crate::event::RoomEvent>, sqlx::Error> {
+        let events = self.events.read().await;
+        let mut matched: Vec<_> = events
+            .values()
+            .filter(|e| e.room_id == room_id)
+            .filter(|e| {
+                crate::event::relations::relates_to(&e.content).is_some_and(|(parent, rel_type)| {
+                    parent == parent_id && params.rel_type.as_deref().is_none_or(|wanted| wanted == rel_type)
+                })
+            })
+            .filter(|e| params.event_type.as_ref().is_none_or(|event_type| &e.event_type == event_type))
+            .filter(|e| {
+                let stream = e.stream_ordering.unwrap_or(0);
+                if params.backwards {
+                    params.from.is_none_or(|from| stream <= from) && params.to.is_none_or(|to| stream > to)
+                } else {
+                    params.from.is_none_or(|from| stream > from) && params.to.is_none_or(|to| stream <= to)
+                }
+            })
+            .cloned()
+            .collect();
+        matched.sort_by_key(|e| e.stream_ordering.unwrap_or(0));
+        if params.backwards {
+            matched.reverse();
+        }
+        matched.truncate(params.limit as usize);
+        Ok(matched)
+    }
+
+    async fn get_relation_aggregations(
+        &self,
+        room_id: &str,
+        parent_ids: &[String],
+        user_id: &str,
+    ) -> Result<HashMap<String, crate::event::RelationAggregation>, sqlx::Error> {
+        let events = self.events.read().await;
+        let mut related: Vec<_> = events.values().filter(|e| e.room_id == room_id).collect();
+        related.sort_by_key(|e| e.stream_ordering.unwrap_or(0));
+        let mut aggregations: HashMap<String, crate::event::RelationAggregation> = HashMap::new();
+        let mut annotation_senders: HashMap<(&str, usize), HashSet<&str>> = HashMap::new();
+        for event in related {
+            let Some((parent_id, rel_type)) = crate::event::relations::relates_to(&event.content) else { continue };
+            if !parent_ids.iter().any(|id| id == parent_id) {
+                continue;
+            }
+            match rel_type {
+                "m.thread" => {
+                    let aggregation = aggregations.entry(parent_id.to_string()).or_default();
+                    aggregation.thread_count += 1;
+                    aggregation.thread_participated |= event.user_id == user_id;
+                    aggregation.thread_latest_event = Some(event.clone());
+                }
+                "m.replace" if events.get(parent_id).is_some_and(|parent| parent.user_id == event.user_id) => {
+                    aggregations.entry(parent_id.to_string()).or_default().replacement = Some(event.clone());
+                }
+                "m.reference" => {
+                    aggregations.entry(parent_id.to_string()).or_default().references.push(event.event_id.clone());
+                }
+                "m.annotation" => {
+                    let Some(key) = event.content["m.relates_to"]["key"].as_str() else { continue };
+                    let annotations = &mut aggregations.entry(parent_id.to_string()).or_default().annotations;
+                    let group_index = annotations
+                        .iter()
+                        .position(|group| group.event_type == event.event_type && group.key == key)
+                        .unwrap_or_else(|| {
+                            annotations.push(crate::event::AnnotationGroup {
+                                event_type: event.event_type.clone(),
+                                key: key.to_string(),
+                                count: 0,
+                                current_user_participated: false,
+                            });
+                            annotations.len() - 1
+                        });
+                    let senders = annotation_senders.entry((parent_id, group_index)).or_insert_with(HashSet::new);
+                    if senders.insert(event.user_id.as_str()) {
+                        annotations[group_index].count += 1;
+                    }
+                    annotations[group_index].current_user_participated |= event.user_id == user_id;
+                }
+                _ => {}
+            }
+        }
+        for aggregation in aggregations.values_mut() {
+            // Stable sort keeps first-used order among equal counts.
+            aggregation.annotations.sort_by_key(|group| std::cmp::Reverse(group.count));
+        }
+        Ok(aggregations)
+    }
+
     async fn find_event_id_by_timestamp(
         &self,
         room_id: &str,
@@ -916,6 +1273,89 @@ impl crate::event::writer::EventWriter for InMemoryEventStore {
         Ok(event)
     }
 
+    async fn create_soft_failed_event_with_graph(
+        &self,
+        params: crate::event::CreateEventParams,
+        _prev_events: &[String],
+        _auth_events: &[String],
+        depth: i64,
+        kind: crate::event::SoftFailKind,
+        reason: &str,
+    ) -> Result<crate::event::RoomEvent, sqlx::Error> {
+        let mut soft_failed = self.soft_failed.write().await;
+        let stream_ordering = soft_failed.len() as i64 + 1;
+        soft_failed.push((
+            params.room_id.clone(),
+            crate::event::SoftFailedEvent {
+                event_id: params.event_id.clone(),
+                event_type: params.event_type.clone(),
+                sender: params.user_id.clone(),
+                state_key: params.state_key.clone(),
+                depth,
+                origin_server_ts: params.origin_server_ts,
+                stream_ordering,
+                soft_fail_kind: kind.as_str().to_string(),
+                soft_fail_reason: Some(reason.to_string()),
+            },
+        ));
+        Ok(crate::event::RoomEvent {
+            event_id: params.event_id,
+            room_id: params.room_id,
+            user_id: params.user_id,
+            event_type: params.event_type,
+            content: params.content,
+            state_key: params.state_key,
+            depth,
+            origin_server_ts: params.origin_server_ts,
+            processed_ts: current_timestamp_millis(),
+            not_before: 0,
+            status: None,
+            reference_image: None,
+            origin: String::new(),
+            stream_ordering: Some(stream_ordering),
+            redacts: params.redacts,
+        })
+    }
+
+    async fn record_rejected_event(
+        &self,
+        params: crate::event::RejectedEventParams,
+        max_rows: i64,
+    ) -> Result<(), sqlx::Error> {
+        if max_rows <= 0 {
+            return Ok(());
+        }
+        let mut rejected = self.rejected.write().await;
+        let id = rejected.last().map_or(1, |e| e.id + 1);
+        rejected.push(crate::event::RejectedEvent {
+            id,
+            event_id: params.event_id,
+            origin: params.origin,
+            room_id: params.room_id,
+            sender: params.sender,
+            event_type: params.event_type,
+            reason: params.reason.as_str().to_string(),
+            detail: params.detail,
+            rejected_ts: current_timestamp_millis(),
+        });
+        let excess = rejected.len().saturating_sub(max_rows as usize);
+        rejected.drain(..excess);
+        Ok(())
+    }
+
+    async fn record_local_event_graph(
+        &self,
+        _room_id: &str,
+        event_id: &str,
+        prev_events: &[String],
+    ) -> Result<i64, sqlx::Error> {
+        let mut events = self.events.write().await;
+        let depth = prev_events.iter().filter_map(|id| events.get(id)).map(|e| e.depth).max().unwrap_or(0) + 1;
+        let event = events.get_mut(event_id).ok_or(sqlx::Error::RowNotFound)?;
+        event.depth = event.depth.max(depth);
+        Ok(event.depth)
+    }
+
     async fn save_event_signature(
         &self,
         _event_id: &str,
@@ -1016,3 +1456,24 @@ impl crate::event::writer::EventWriter for InMemoryEventStore {
         Ok(())
     }
 }
+
+fn exported_pdu(e: &crate::event::RoomEvent, stream_ordering: i64) -> crate::event::ExportedPdu {
+    crate::event::ExportedPdu {
+        event_id: e.event_id.clone(),
+        room_id: e.room_id.clone(),
+        sender: e.user_id.clone(),
+        event_type: e.event_type.clone(),
+        content: e.content.clone(),
+        state_key: e.state_key.clone(),
+        depth: e.depth,
+        origin_server_ts: e.origin_server_ts,
+        origin: e.origin.clone(),
+        prev_events: None,
+        auth_events: None,
+        signatures: None,
+        hashes: None,
+        unsigned: None,
+        redacts: e.redacts.clone(),
+        stream_ordering,
+    }
+}
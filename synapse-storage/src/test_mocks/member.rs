@@ -181,7 +181,9 @@ impl crate::membership::api::MemberStoreApi for InMemoryMemberStore {
         let members = self.members.read().await;
         let mut result: Vec<crate::membership::UserRoomMembership> = members
             .iter()
-            .filter(|((_, uid), m)| uid == user_id && (include_leave || m.membership != "leave"))
+            .filter(|((_, uid), m)| {
+                uid == user_id && (include_leave || !matches!(m.membership.as_str(), "leave" | "ban"))
+            })
             .map(|((rid, _), m)| crate::membership::UserRoomMembership {
                 room_id: rid.clone(),
                 membership: m.membership.clone(),
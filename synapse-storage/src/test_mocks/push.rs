@@ -155,7 +155,7 @@ impl PushStoreApi for InMemoryPushStore {
         kind: &str,
         rule_id: &str,
         actions: &Value,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<u64, sqlx::Error> {
         if let Some(entry) = self.push_rules.write().await.get_mut(&(
             user_id.to_string(),
             scope.to_string(),
@@ -163,8 +163,9 @@ impl PushStoreApi for InMemoryPushStore {
             rule_id.to_string(),
         )) {
             entry.actions = actions.clone();
+            return Ok(1);
         }
-        Ok(())
+        Ok(0)
     }
 
     async fn get_push_rule_enabled(
@@ -189,7 +190,7 @@ impl PushStoreApi for InMemoryPushStore {
         kind: &str,
         rule_id: &str,
         enabled: bool,
-    ) -> Result<(), sqlx::Error> {
+    ) -> Result<u64, sqlx::Error> {
         if let Some(entry) = self.push_rules.write().await.get_mut(&(
             user_id.to_string(),
             scope.to_string(),
@@ -197,8 +198,9 @@ impl PushStoreApi for InMemoryPushStore {
             rule_id.to_string(),
         )) {
             entry.is_enabled = enabled;
+            return Ok(1);
         }
-        Ok(())
+        Ok(0)
     }
 
     async fn get_user_push_rules(
@@ -3,11 +3,12 @@ use super::*;
 #[derive(Default)]
 pub struct InMemoryRateLimitStore {
     limits: Arc<RwLock<HashMap<String, crate::rate_limit::RateLimitRecord>>>,
+    daily_quotas: Arc<RwLock<HashMap<(String, chrono::NaiveDate, String), i64>>>,
 }
 
 impl InMemoryRateLimitStore {
     pub fn new() -> Self {
-        Self { limits: Arc::new(RwLock::new(HashMap::new())) }
+        Self::default()
     }
 }
 
@@ -40,4 +41,37 @@ impl RateLimitStoreApi for InMemoryRateLimitStore {
         self.limits.write().await.remove(user_id);
         Ok(())
     }
+
+    async fn increment_daily_quota(
+        &self,
+        user_id: &str,
+        day: chrono::NaiveDate,
+        kind: &str,
+        amount: i64,
+    ) -> Result<i64, sqlx::Error> {
+        let mut quotas = self.daily_quotas.write().await;
+        let count = quotas.entry((user_id.to_string(), day, kind.to_string())).or_insert(0);
+        *count += amount;
+        Ok(*count)
+    }
+
+    async fn get_daily_quota_usage(
+        &self,
+        user_id: &str,
+        day: chrono::NaiveDate,
+    ) -> Result<HashMap<String, i64>, sqlx::Error> {
+        Ok(self
+            .daily_quotas
+            .read()
+            .await
+            .iter()
+            .filter(|((user, quota_day, _), _)| user == user_id && *quota_day == day)
+            .map(|((_, _, kind), count)| (kind.clone(), *count))
+            .collect())
+    }
+
+    async fn delete_daily_quota_usage(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        self.daily_quotas.write().await.retain(|(user, _, _), _| user != user_id);
+        Ok(())
+    }
 }
@@ -6,6 +6,8 @@ pub struct InMemoryRoomStore {
     rooms: Arc<RwLock<HashMap<String, crate::room::Room>>>,
     aliases: Arc<RwLock<HashMap<String, String>>>,   // alias → room_id
     directories: Arc<RwLock<HashMap<String, bool>>>, // room_id → is_public
+    blocked: Arc<RwLock<HashMap<String, i64>>>,      // room_id → blocked_at
+    publication_requests: Arc<RwLock<HashMap<String, crate::room::RoomPublicationRequest>>>,
 }
 
 impl InMemoryRoomStore {
@@ -14,6 +16,8 @@ impl InMemoryRoomStore {
             rooms: Arc::new(RwLock::new(HashMap::new())),
             aliases: Arc::new(RwLock::new(HashMap::new())),
             directories: Arc::new(RwLock::new(HashMap::new())),
+            blocked: Arc::new(RwLock::new(HashMap::new())),
+            publication_requests: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 
@@ -304,6 +308,10 @@ impl crate::room::api::RoomStoreApi for InMemoryRoomStore {
         Ok(())
     }
 
+    async fn add_receipts(&self, _room_id: &str, _receipts: &[crate::room::Receipt]) -> Result<(), sqlx::Error> {
+        Ok(())
+    }
+
     async fn get_receipts(
         &self,
         _room_id: &str,
@@ -405,20 +413,21 @@ impl crate::room::api::RoomStoreApi for InMemoryRoomStore {
 
     async fn block_room(
         &self,
-        _room_id: &str,
-        _blocked_at: i64,
+        room_id: &str,
+        blocked_at: i64,
         _blocked_by: &str,
         _reason: Option<&str>,
     ) -> Result<(), sqlx::Error> {
-        // No blocked_rooms table in mock; no-op.
+        self.blocked.write().await.insert(room_id.to_string(), blocked_at);
         Ok(())
     }
 
-    async fn get_room_block_status(&self, _room_id: &str) -> Result<Option<i64>, sqlx::Error> {
-        Ok(None)
+    async fn get_room_block_status(&self, room_id: &str) -> Result<Option<i64>, sqlx::Error> {
+        Ok(self.blocked.read().await.get(room_id).copied())
     }
 
-    async fn unblock_room(&self, _room_id: &str) -> Result<(), sqlx::Error> {
+    async fn unblock_room(&self, room_id: &str) -> Result<(), sqlx::Error> {
+        self.blocked.write().await.remove(room_id);
         Ok(())
     }
 
@@ -480,6 +489,79 @@ impl crate::room::api::RoomStoreApi for InMemoryRoomStore {
         }
     }
 
+    async fn upsert_publication_request(
+        &self,
+        room_id: &str,
+        requested_by: &str,
+    ) -> Result<crate::room::RoomPublicationRequest, sqlx::Error> {
+        let request = crate::room::RoomPublicationRequest {
+            room_id: room_id.to_string(),
+            requested_by: requested_by.to_string(),
+            requested_ts: synapse_common::current_timestamp_millis(),
+            status: "pending".to_string(),
+            reviewed_by: None,
+            reviewed_ts: None,
+            reason: None,
+        };
+        self.publication_requests.write().await.insert(room_id.to_string(), request.clone());
+        Ok(request)
+    }
+
+    async fn get_publication_request(
+        &self,
+        room_id: &str,
+    ) -> Result<Option<crate::room::RoomPublicationRequest>, sqlx::Error> {
+        Ok(self.publication_requests.read().await.get(room_id).cloned())
+    }
+
+    async fn list_publication_requests(
+        &self,
+        status: &str,
+        limit: i64,
+    ) -> Result<Vec<crate::room::RoomPublicationRequest>, sqlx::Error> {
+        let mut requests: Vec<_> =
+            self.publication_requests.read().await.values().filter(|r| r.status == status).cloned().collect();
+        requests.sort_by(|a, b| (a.requested_ts, &a.room_id).cmp(&(b.requested_ts, &b.room_id)));
+        requests.truncate(usize::try_from(limit).unwrap_or(0));
+        Ok(requests)
+    }
+
+    async fn review_publication_request(
+        &self,
+        room_id: &str,
+        approve: bool,
+        reviewed_by: &str,
+        reason: Option<&str>,
+    ) -> Result<Option<crate::room::RoomPublicationRequest>, sqlx::Error> {
+        let mut requests = self.publication_requests.write().await;
+        let Some(request) = requests.get_mut(room_id).filter(|r| r.status == "pending") else {
+            return Ok(None);
+        };
+        request.status = if approve { "approved" } else { "rejected" }.to_string();
+        request.reviewed_by = Some(reviewed_by.to_string());
+        request.reviewed_ts = Some(synapse_common::current_timestamp_millis());
+        request.reason = reason.map(str::to_string);
+        let reviewed = request.clone();
+        drop(requests);
+
+        if approve {
+            self.directories.write().await.insert(room_id.to_string(), true);
+            if let Some(room) = self.rooms.write().await.get_mut(room_id) {
+                room.is_public = true;
+            }
+        }
+        Ok(Some(reviewed))
+    }
+
+    async fn withdraw_publication_request(&self, room_id: &str) -> Result<bool, sqlx::Error> {
+        let mut requests = self.publication_requests.write().await;
+        if requests.get(room_id).is_some_and(|r| r.status == "pending") {
+            requests.remove(room_id);
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
     async fn get_room_version_only(&self, room_id: &str) -> Result<Option<String>, sqlx::Error> {
         let rooms = self.rooms.read().await;
         Ok(rooms.get(room_id).map(|r| r.room_version.clone()))
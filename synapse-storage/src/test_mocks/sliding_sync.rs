@@ -389,7 +389,11 @@ impl crate::sliding_sync::SlidingSyncStoreApi for InMemorySlidingSyncStore {
         Ok(serde_json::Value::Object(result))
     }
 
-    async fn get_receipts_for_rooms(&self, room_ids: &[String]) -> Result<serde_json::Value, sqlx::Error> {
+    async fn get_receipts_for_rooms(
+        &self,
+        room_ids: &[String],
+        _user_id: &str,
+    ) -> Result<serde_json::Value, sqlx::Error> {
         let data = self.receipts.read().await;
         let mut result = serde_json::Map::new();
         for room_id in room_ids {
@@ -127,7 +127,7 @@ impl crate::space::SpaceStoreApi for InMemorySpaceStore {
             is_suggested: request.is_suggested,
             via_servers: request.via_servers,
             added_ts: now,
-            order: None,
+            order: request.order,
             suggested: Some(request.is_suggested),
             added_by: None,
             removed_ts: None,
@@ -143,7 +143,10 @@ impl crate::space::SpaceStoreApi for InMemorySpaceStore {
     }
 
     async fn get_space_children(&self, space_id: &str) -> Result<Vec<crate::space::SpaceChild>, sqlx::Error> {
-        Ok(self.children.read().await.iter().filter(|c| c.space_id == space_id).cloned().collect())
+        let mut children: Vec<_> =
+            self.children.read().await.iter().filter(|c| c.space_id == space_id).cloned().collect();
+        crate::space::sort_space_children(&mut children);
+        Ok(children)
     }
 
     async fn get_child_spaces(&self, room_id: &str) -> Result<Vec<crate::space::SpaceChild>, sqlx::Error> {
@@ -3,6 +3,7 @@ use super::*;
 use crate::ai_connection::AiConnectionStoreApi;
 #[cfg(feature = "burn-after-read")]
 use crate::burn_after_read::BurnAfterReadStoreApi;
+use crate::event::{EventReader, EventWriter, RejectedEventFilter, RejectedEventParams, RejectionReason, SoftFailKind};
 use crate::oidc_user_mapping::OidcUserMappingStoreApi;
 use crate::room_summary::RoomSummaryStoreApi;
 use crate::sliding_sync::SlidingSyncStoreApi;
@@ -130,6 +131,63 @@ async fn event_redact_content_replaces_with_empty_json() {
     assert_eq!(redacted.content, serde_json::json!({}));
 }
 
+#[tokio::test]
+async fn rejected_events_are_capped_and_filtered() {
+    let store = InMemoryEventStore::new();
+    for (i, reason) in
+        [RejectionReason::Signature, RejectionReason::TooLarge, RejectionReason::Signature].into_iter().enumerate()
+    {
+        let params = RejectedEventParams {
+            event_id: format!("$ev{i}:remote.org"),
+            origin: "remote.org".into(),
+            room_id: Some("!r:example.com".into()),
+            sender: Some("@mallory:remote.org".into()),
+            event_type: Some("m.room.message".into()),
+            reason,
+            detail: "rejected".into(),
+        };
+        store.record_rejected_event(params, 2).await.unwrap();
+    }
+
+    let all = store.get_rejected_events(&RejectedEventFilter::default(), 10).await.unwrap();
+    assert_eq!(all.iter().map(|e| e.event_id.as_str()).collect::<Vec<_>>(), ["$ev2:remote.org", "$ev1:remote.org"]);
+    let filter = RejectedEventFilter { reason: Some(RejectionReason::Signature), ..Default::default() };
+    let signature = store.get_rejected_events(&filter, 10).await.unwrap();
+    assert_eq!(signature.len(), 1);
+    assert_eq!(signature[0].event_id, "$ev2:remote.org");
+    let page = RejectedEventFilter { before: Some(all[0].id), ..Default::default() };
+    assert_eq!(store.get_rejected_events(&page, 10).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn soft_failed_events_are_listed_by_kind_and_hidden_from_room_events() {
+    let store = InMemoryEventStore::new();
+    for (event_id, kind) in [("$auth:remote.org", SoftFailKind::Auth), ("$spam:remote.org", SoftFailKind::Spam)] {
+        let params = crate::event::CreateEventParams {
+            event_id: event_id.into(),
+            room_id: "!r:example.com".into(),
+            user_id: "@mallory:remote.org".into(),
+            event_type: "m.room.message".into(),
+            content: serde_json::json!({"body": "hi"}),
+            state_key: None,
+            origin_server_ts: 1_700_000_000_000,
+            redacts: None,
+        };
+        store.create_soft_failed_event_with_graph(params, &[], &[], 5, kind, "rejected").await.unwrap();
+    }
+
+    let all = store.get_soft_failed_events("!r:example.com", None, None, 10).await.unwrap();
+    assert_eq!(all.iter().map(|e| e.event_id.as_str()).collect::<Vec<_>>(), ["$spam:remote.org", "$auth:remote.org"]);
+    let spam = store.get_soft_failed_events("!r:example.com", Some(SoftFailKind::Spam), None, 10).await.unwrap();
+    assert_eq!(spam.len(), 1);
+    assert_eq!(spam[0].soft_fail_kind, "spam");
+    assert_eq!(
+        store.count_soft_failed_events("!r:example.com").await.unwrap(),
+        vec![("auth".to_string(), 1), ("spam".to_string(), 1)]
+    );
+    assert!(store.get_event("$auth:remote.org").await.unwrap().is_none());
+}
+
 // ── EventReader state event tests ────────────────────────────────
 
 #[tokio::test]
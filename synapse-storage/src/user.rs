@@ -1,10 +1,10 @@
 use async_trait::async_trait;
 use serde::{Deserialize, Serialize};
 use sqlx::{Pool, Postgres, Row};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use synapse_cache::CacheManager;
-use synapse_common::constants::USER_PROFILE_CACHE_TTL;
+use synapse_common::constants::{USER_DIRECTORY_ACCOUNT_DATA_TYPE, USER_PROFILE_CACHE_TTL};
 use synapse_common::current_timestamp_millis;
 use tracing;
 
@@ -169,6 +169,8 @@ pub trait UserStore: Send + Sync {
 
     async fn user_exists(&self, user_id: &str) -> Result<bool, sqlx::Error>;
 
+    async fn has_active_admin(&self) -> Result<bool, sqlx::Error>;
+
     async fn filter_existing_users(&self, user_ids: &[String]) -> Result<Vec<String>, sqlx::Error>;
 
     async fn get_user_count(&self) -> Result<i64, sqlx::Error>;
@@ -201,6 +203,10 @@ pub trait UserStore: Send + Sync {
 
     async fn set_shadow_ban(&self, user_id: &str, is_shadow_banned: bool) -> Result<bool, sqlx::Error>;
 
+    async fn mark_user_erased(&self, user_id: &str) -> Result<(), sqlx::Error>;
+
+    async fn get_erased_users(&self, user_ids: &[String]) -> Result<HashSet<String>, sqlx::Error>;
+
     async fn delete_user(&self, user_id: &str) -> Result<(), sqlx::Error>;
 
     async fn set_guest_status(&self, user_id: &str, is_guest: bool) -> Result<(), sqlx::Error>;
@@ -582,6 +588,18 @@ impl UserStorage {
         .await
     }
 
+    pub async fn has_active_admin(&self) -> Result<bool, sqlx::Error> {
+        sqlx::query_scalar::<_, bool>(
+            r"
+            SELECT EXISTS (
+                SELECT 1 FROM users WHERE COALESCE(is_admin, FALSE) AND COALESCE(is_deactivated, FALSE) = FALSE
+            )
+            ",
+        )
+        .fetch_one(&*self.pool)
+        .await
+    }
+
     pub async fn user_exists(&self, user_id: &str) -> Result<bool, sqlx::Error> {
         let result = sqlx::query(
             r"
@@ -689,6 +707,32 @@ impl UserStorage {
         Ok(result.rows_affected() > 0)
     }
 
+    /// Record that `user_id` asked for their messages to be erased.
+    pub async fn mark_user_erased(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        sqlx::query(
+            r"INSERT INTO erased_users (user_id, erased_ts) VALUES ($1, $2)
+              ON CONFLICT (user_id) DO NOTHING",
+        )
+        .bind(user_id)
+        .bind(current_timestamp_millis())
+        .execute(&*self.pool)
+        .await?;
+        Ok(())
+    }
+
+    /// The subset of `user_ids` that have been erased.
+    pub async fn get_erased_users(&self, user_ids: &[String]) -> Result<HashSet<String>, sqlx::Error> {
+        if user_ids.is_empty() {
+            return Ok(HashSet::new());
+        }
+
+        let rows: Vec<String> = sqlx::query_scalar(r"SELECT user_id FROM erased_users WHERE user_id = ANY($1)")
+            .bind(user_ids)
+            .fetch_all(&*self.pool)
+            .await?;
+        Ok(rows.into_iter().collect())
+    }
+
     pub async fn set_account_data(
         &self,
         user_id: &str,
@@ -796,6 +840,10 @@ impl UserStorage {
                 u.created_ts
             FROM candidate_matches cm
             JOIN users u ON u.user_id = cm.user_id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM account_data ad
+                WHERE ad.user_id = u.user_id AND ad.data_type = $6 AND ad.content->'hidden' = 'true'::jsonb
+            )
             ORDER BY
                 cm.match_priority ASC,
                 cm.match_similarity DESC,
@@ -813,6 +861,7 @@ impl UserStorage {
             .bind(&contains_pattern)
             .bind(normalized)
             .bind(limit)
+            .bind(USER_DIRECTORY_ACCOUNT_DATA_TYPE)
             .fetch_all(&*self.pool)
             .await
     }
@@ -1037,7 +1086,7 @@ impl UserStorage {
         let exact_pattern = escaped.clone();
         let prefix_pattern = format!("{escaped}%");
         let contains_pattern = format!("%{escaped}%");
-        let cache_key = format!("user:directory_search:v1:{}:{}:{}", normalized.to_lowercase(), safe_limit, exact_only);
+        let cache_key = format!("user:directory_search:v2:{}:{}:{}", normalized.to_lowercase(), safe_limit, exact_only);
 
         if let Ok(Some(cached)) = self.cache.get::<Vec<UserDirectorySearchResult>>(&cache_key).await {
             return Ok(cached);
@@ -1194,6 +1243,10 @@ impl UserStorage {
             FROM candidate_matches cm
             JOIN users u ON u.user_id = cm.user_id
             LEFT JOIN presence p ON p.user_id = u.user_id
+            WHERE NOT EXISTS (
+                SELECT 1 FROM account_data ad
+                WHERE ad.user_id = u.user_id AND ad.data_type = $7 AND ad.content->'hidden' = 'true'::jsonb
+            )
             ORDER BY
                 cm.rank_score DESC,
                 COALESCE(p.last_active_ts, 0) DESC,
@@ -1208,6 +1261,7 @@ impl UserStorage {
         .bind(exact_only)
         .bind(normalized)
         .bind(safe_limit)
+        .bind(USER_DIRECTORY_ACCOUNT_DATA_TYPE)
         .fetch_all(&*self.pool)
         .await?;
 
@@ -1446,6 +1500,10 @@ impl UserStore for UserStorage {
         self.user_exists(user_id).await
     }
 
+    async fn has_active_admin(&self) -> Result<bool, sqlx::Error> {
+        self.has_active_admin().await
+    }
+
     async fn filter_existing_users(&self, user_ids: &[String]) -> Result<Vec<String>, sqlx::Error> {
         self.filter_existing_users(user_ids).await
     }
@@ -1502,6 +1560,14 @@ impl UserStore for UserStorage {
         self.set_shadow_ban(user_id, is_shadow_banned).await
     }
 
+    async fn mark_user_erased(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        self.mark_user_erased(user_id).await
+    }
+
+    async fn get_erased_users(&self, user_ids: &[String]) -> Result<HashSet<String>, sqlx::Error> {
+        self.get_erased_users(user_ids).await
+    }
+
     async fn delete_user(&self, user_id: &str) -> Result<(), sqlx::Error> {
         self.delete_user(user_id).await
     }
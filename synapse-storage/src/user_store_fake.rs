@@ -1,5 +1,5 @@
 use async_trait::async_trait;
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use tokio::sync::RwLock;
 
@@ -13,6 +13,7 @@ use crate::user::{
 pub struct FakeUserStore {
     locked_users: Arc<RwLock<Vec<LockedUser>>>,
     users: Arc<RwLock<HashMap<String, User>>>,
+    erased_users: Arc<RwLock<HashSet<String>>>,
 }
 
 impl FakeUserStore {
@@ -49,7 +50,11 @@ impl FakeUserStore {
                 must_change_password: false,
             },
         );
-        Self { locked_users: Arc::new(RwLock::new(Vec::new())), users: Arc::new(RwLock::new(users)) }
+        Self {
+            locked_users: Arc::new(RwLock::new(Vec::new())),
+            users: Arc::new(RwLock::new(users)),
+            erased_users: Arc::new(RwLock::new(HashSet::new())),
+        }
     }
 
     /// Insert (or replace) a user in the in-memory store for test setup.
@@ -168,6 +173,10 @@ impl UserStore for FakeUserStore {
         Ok(self.users.read().await.contains_key(user_id))
     }
 
+    async fn has_active_admin(&self) -> Result<bool, sqlx::Error> {
+        Ok(self.users.read().await.values().any(|user| user.is_admin && !user.is_deactivated))
+    }
+
     async fn filter_existing_users(&self, _user_ids: &[String]) -> Result<Vec<String>, sqlx::Error> {
         Ok(vec![])
     }
@@ -230,6 +239,16 @@ impl UserStore for FakeUserStore {
         }
     }
 
+    async fn mark_user_erased(&self, user_id: &str) -> Result<(), sqlx::Error> {
+        self.erased_users.write().await.insert(user_id.to_string());
+        Ok(())
+    }
+
+    async fn get_erased_users(&self, user_ids: &[String]) -> Result<HashSet<String>, sqlx::Error> {
+        let erased = self.erased_users.read().await;
+        Ok(user_ids.iter().filter(|user_id| erased.contains(*user_id)).cloned().collect())
+    }
+
     async fn delete_user(&self, _user_id: &str) -> Result<(), sqlx::Error> {
         Ok(())
     }
@@ -290,8 +309,12 @@ impl UserStore for FakeUserStore {
         Ok(vec![])
     }
 
-    async fn get_users_map(&self, _user_ids: &[String]) -> Result<HashMap<String, User>, sqlx::Error> {
-        Ok(HashMap::new())
+    async fn get_users_map(&self, user_ids: &[String]) -> Result<HashMap<String, User>, sqlx::Error> {
+        let users = self.users.read().await;
+        Ok(user_ids
+            .iter()
+            .filter_map(|user_id| users.get(user_id).map(|user| (user_id.clone(), user.clone())))
+            .collect())
     }
 
     async fn get_account_data_content(
@@ -70,7 +70,7 @@ fn compute_hmac(
     mac.update(password.as_bytes());
     mac.update(b"\0");
     if admin {
-        mac.update(b"admin\x00\x00\x00");
+        mac.update(b"admin");
     } else {
         mac.update(b"notadmin");
     }
@@ -236,6 +236,30 @@ async fn test_register_admin_user_success() {
     assert_eq!(resp.expires_in, 3600);
 }
 
+#[tokio::test]
+async fn test_register_admin_user_accepts_legacy_padded_admin_tag() {
+    let pool = crate::require_test_pool().await;
+    let shared_secret = "test_shared_secret";
+    let service = create_service(&pool, shared_secret, true);
+    let nonce = service.generate_nonce().await.unwrap().nonce;
+    let username = format!("legacy_admin_{}", unique_id());
+    let password = "Password123!";
+    let mut mac = HmacSha256::new_from_slice(shared_secret.as_bytes()).unwrap();
+    mac.update(format!("{nonce}\0{username}\0{password}\0").as_bytes());
+    mac.update(b"admin\x00\x00\x00");
+    let request = AdminRegisterRequest {
+        nonce,
+        username,
+        password: password.to_string(),
+        admin: Some(true),
+        user_type: None,
+        displayname: None,
+        mac: hex::encode(mac.finalize().into_bytes()),
+    };
+    let result = service.register_admin_user(request).await;
+    assert!(result.is_ok(), "expected success, got error: {:?}", result.err());
+}
+
 #[tokio::test]
 async fn test_register_admin_user_non_admin() {
     let pool = crate::require_test_pool().await;
@@ -344,6 +368,32 @@ async fn test_nonce_consumed_after_use() {
     assert!(result2.is_err(), "nonce should be consumed and rejected on second use");
 }
 
+#[tokio::test]
+async fn test_concurrent_nonce_reuse_registers_once() {
+    let pool = crate::require_test_pool().await;
+    let shared_secret = "test_shared_secret";
+    let service = create_service(&pool, shared_secret, true);
+    let nonce = service.generate_nonce().await.unwrap().nonce;
+    let password = "Password123!";
+    let request = |username: String| {
+        let mac = compute_hmac(shared_secret, &nonce, &username, password, false, None);
+        AdminRegisterRequest {
+            nonce: nonce.clone(),
+            username,
+            password: password.to_string(),
+            admin: None,
+            user_type: None,
+            displayname: None,
+            mac,
+        }
+    };
+    let (first, second) = tokio::join!(
+        service.register_admin_user(request(format!("race_a_{}", unique_id()))),
+        service.register_admin_user(request(format!("race_b_{}", unique_id()))),
+    );
+    assert_eq!(first.is_ok() as u8 + second.is_ok() as u8, 1, "exactly one registration may use the nonce");
+}
+
 #[tokio::test]
 async fn test_hmac_mismatch_tampered_username() {
     let pool = crate::require_test_pool().await;
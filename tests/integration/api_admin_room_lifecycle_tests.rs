@@ -401,3 +401,85 @@ async fn test_admin_backfill_requires_existing_room() {
     let response = ServiceExt::<Request<Body>>::oneshot(app, backfill_request).await.unwrap();
     assert_eq!(response.status(), StatusCode::NOT_FOUND);
 }
+
+async fn send_json(
+    app: &axum::Router,
+    method: &str,
+    uri: &str,
+    token: &str,
+    body: Option<Value>,
+) -> (StatusCode, Value) {
+    let request = Request::builder()
+        .method(method)
+        .uri(uri)
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json")
+        .body(body.map_or_else(Body::empty, |b| Body::from(b.to_string())))
+        .unwrap();
+    let response = ServiceExt::<Request<Body>>::oneshot(app.clone(), request).await.unwrap();
+    let status = response.status();
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 1024).await.unwrap();
+    (status, serde_json::from_slice(&body).unwrap_or(Value::Null))
+}
+
+/// 测试房间关闭：踢出本地成员、封禁房间 ID、迁移到通知房间并从目录移除
+#[tokio::test]
+async fn test_admin_shutdown_room_blocks_rejoin_and_moves_members() {
+    let Some(app) = super::setup_fresh_test_app().await else {
+        return;
+    };
+    let (admin_token, _) = super::get_admin_token(&app).await;
+    let user_token = super::create_test_user(&app).await;
+
+    let (_, whoami) = send_json(&app, "GET", "/_matrix/client/v3/account/whoami", &admin_token, None).await;
+    let admin_user_id = whoami["user_id"].as_str().unwrap().to_string();
+    let (_, whoami) = send_json(&app, "GET", "/_matrix/client/v3/account/whoami", &user_token, None).await;
+    let user_id = whoami["user_id"].as_str().unwrap().to_string();
+
+    // 1. 普通用户创建公开房间
+    let (status, created) = send_json(
+        &app,
+        "POST",
+        "/_matrix/client/v3/createRoom",
+        &user_token,
+        Some(json!({ "name": "Doomed Room", "preset": "public_chat", "visibility": "public" })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK);
+    let room_id = created["room_id"].as_str().unwrap().to_string();
+
+    // 2. 管理员关闭房间，并把成员迁移到通知房间
+    let (status, shutdown) = send_json(
+        &app,
+        "POST",
+        "/_synapse/admin/v1/shutdown_room",
+        &admin_token,
+        Some(json!({
+            "room_id": room_id,
+            "new_room_user_id": admin_user_id,
+            "message": "This room has been shut down."
+        })),
+    )
+    .await;
+    assert_eq!(status, StatusCode::OK, "{shutdown}");
+    assert!(shutdown["kicked_users"].as_array().unwrap().iter().any(|u| u == &json!(user_id)));
+    assert_eq!(shutdown["failed_to_kick_users"], json!([]));
+    let new_room_id = shutdown["new_room_id"].as_str().unwrap().to_string();
+
+    // 3. 房间已被封禁，用户无法重新加入
+    let encoded_room_id = room_id.replace('!', "%21").replace(':', "%3A");
+    let (_, block) =
+        send_json(&app, "GET", &format!("/_synapse/admin/v1/rooms/{}/block", encoded_room_id), &admin_token, None)
+            .await;
+    assert_eq!(block["block"], json!(true));
+
+    let (status, _) =
+        send_json(&app, "POST", &format!("/_matrix/client/v3/rooms/{}/join", encoded_room_id), &user_token, None).await;
+    assert_eq!(status, StatusCode::FORBIDDEN);
+
+    // 4. 用户已被迁移到通知房间
+    let (_, joined) = send_json(&app, "GET", "/_matrix/client/v3/joined_rooms", &user_token, None).await;
+    let joined_rooms = joined["joined_rooms"].as_array().unwrap();
+    assert!(joined_rooms.iter().any(|r| r == &json!(new_room_id)));
+    assert!(!joined_rooms.iter().any(|r| r == &json!(room_id)));
+}
@@ -123,3 +123,61 @@ async fn test_sliding_sync_rate_limited_returns_retry_after_ms() {
     assert_eq!(json["errcode"], "M_LIMIT_EXCEEDED");
     assert_eq!(json["retry_after_ms"], 1000);
 }
+
+async fn create_room(app: &axum::Router, token: &str) -> String {
+    let request = Request::builder()
+        .method("POST")
+        .uri("/_matrix/client/v3/createRoom")
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"preset":"private_chat"}"#))
+        .unwrap();
+    let response = app.clone().oneshot(super::with_local_connect_info(request)).await.unwrap();
+    assert_eq!(response.status(), StatusCode::OK);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 16).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    json["room_id"].as_str().unwrap().to_string()
+}
+
+async fn send_text(app: &axum::Router, token: &str, room_id: &str, txn_id: &str) -> axum::response::Response {
+    let request = Request::builder()
+        .method("PUT")
+        .uri(format!("/_matrix/client/v3/rooms/{}/send/m.room.message/{}", room_id, txn_id))
+        .header("Authorization", format!("Bearer {}", token))
+        .header("Content-Type", "application/json")
+        .body(Body::from(r#"{"msgtype":"m.text","body":"hello"}"#))
+        .unwrap();
+    app.clone().oneshot(super::with_local_connect_info(request)).await.unwrap()
+}
+
+#[tokio::test]
+async fn test_room_send_flood_control_is_per_room() {
+    let Some(pool) = super::get_test_pool().await else {
+        return;
+    };
+    let mut container = ServiceContainer::new_test_with_pool(pool).await;
+    container.core.config.rate_limit.enabled = false;
+    container.core.config.rate_limit.room_send.enabled = true;
+    container.core.config.rate_limit.room_send.per_second = 1;
+    container.core.config.rate_limit.room_send.burst_size = 1;
+    let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+    let app = synapse_rust::web::create_router(AppState::new(container, cache));
+
+    let token = register_user_and_get_token(&app).await;
+    let busy_room = create_room(&app, &token).await;
+    let quiet_room = create_room(&app, &token).await;
+
+    assert_eq!(send_text(&app, &token, &busy_room, "txn-1").await.status(), StatusCode::OK);
+
+    // Replaying a transaction is served from the dedup cache and is not limited.
+    assert_eq!(send_text(&app, &token, &busy_room, "txn-1").await.status(), StatusCode::OK);
+
+    let response = send_text(&app, &token, &busy_room, "txn-2").await;
+    assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+    let body = axum::body::to_bytes(response.into_body(), 1024 * 16).await.unwrap();
+    let json: serde_json::Value = serde_json::from_slice(&body).unwrap();
+    assert_eq!(json["errcode"], "M_LIMIT_EXCEEDED");
+    assert!(json["retry_after_ms"].as_u64().is_some_and(|ms| ms > 0));
+
+    assert_eq!(send_text(&app, &token, &quiet_room, "txn-3").await.status(), StatusCode::OK);
+}
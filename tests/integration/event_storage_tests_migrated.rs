@@ -196,6 +196,12 @@ async fn test_get_room_events_batch_limit_per_room() {
     let events = events_map.values().next().unwrap();
 
     assert_eq!(events.len(), 3, "Should respect the limit per room");
+    let event_ids: Vec<&str> = events.iter().map(|event| event.event_id.as_str()).collect();
+    assert_eq!(
+        event_ids,
+        ["$event_limit10:localhost", "$event_limit9:localhost", "$event_limit8:localhost"],
+        "Should keep the newest events, newest first"
+    );
     teardown_test_database(pool.as_ref()).await;
 }
 
@@ -56,6 +56,7 @@ mod password_hash_pool_tests;
 mod protocol_compliance_tests;
 mod regex_cache_tests;
 mod rtc_transports_tests;
+mod test_server_tests;
 mod transaction_tests;
 mod voice_routes_tests;
 mod worker_task_recovery_tests;
@@ -564,7 +565,7 @@ async fn register_admin_token(app: &axum::Router, user_type: Option<&str>) -> (S
     message.push(b'\x00');
     message.extend("AdminTest@123".as_bytes());
     message.push(b'\x00');
-    message.extend(b"admin\x00\x00\x00");
+    message.extend(b"admin");
     if let Some(user_type) = user_type {
         message.push(b'\x00');
         message.extend(user_type.as_bytes());
@@ -2,13 +2,14 @@
 use serde_json::json;
 use std::sync::atomic::{AtomicU64, Ordering};
 use std::sync::Arc;
-use synapse_common::current_timestamp_millis;
+use synapse_common::{current_timestamp_millis, RoomPaginationToken};
 use wiremock::{matchers::method, Mock, MockServer, ResponseTemplate};
 
 use synapse_federation::event_broadcaster::EventBroadcaster;
 use synapse_rust::cache::{CacheConfig, CacheManager};
 use synapse_rust::common::Validator;
 use synapse_services::application_service::{ApplicationServiceManager, ApplicationServiceScheduler};
+use synapse_services::room::{EventRelationsParams, RoomMessagesParams};
 use synapse_services::room_service::{CreateRoomConfig, RoomService};
 use synapse_services::room_summary_service::RoomSummaryService;
 use synapse_services::UserService;
@@ -293,7 +294,11 @@ async fn create_test_user(pool: &sqlx::PgPool, user_id: &str, username: &str) {
 }
 
 fn create_room_service(pool: &Arc<sqlx::PgPool>, cache: Arc<CacheManager>) -> RoomService {
-    build_room_service(pool, cache, None)
+    build_room_service(pool, cache, None, false)
+}
+
+fn create_room_service_with_visibility(pool: &Arc<sqlx::PgPool>, cache: Arc<CacheManager>) -> RoomService {
+    build_room_service(pool, cache, None, true)
 }
 
 fn create_room_service_with_appservice(
@@ -301,13 +306,14 @@ fn create_room_service_with_appservice(
     cache: Arc<CacheManager>,
     app_service_manager: Arc<ApplicationServiceManager>,
 ) -> RoomService {
-    build_room_service(pool, cache, Some(app_service_manager))
+    build_room_service(pool, cache, Some(app_service_manager), false)
 }
 
 fn build_room_service(
     pool: &Arc<sqlx::PgPool>,
     cache: Arc<CacheManager>,
     app_service_manager: Option<Arc<ApplicationServiceManager>>,
+    with_visibility: bool,
 ) -> RoomService {
     let member_storage = Arc::new(RoomMemberStorage::new(pool, "localhost"));
     let event_storage: Arc<synapse_storage::event::EventStorage> =
@@ -317,6 +323,12 @@ fn build_room_service(
     let room_summary_service =
         Arc::new(RoomSummaryService::new(room_summary_storage, event_storage.clone(), Some(member_storage.clone())));
     let user_storage = Arc::new(UserStorage::new(pool, canonical_cache.clone()));
+    let event_visibility = with_visibility.then(|| {
+        Arc::new(synapse_services::event_visibility_service::EventVisibilityService::new(
+            event_storage.clone(),
+            user_storage.clone(),
+        ))
+    });
 
     RoomService::new(synapse_services::room_service::RoomServiceConfig {
         room_storage: Arc::new(RoomStorage::new(pool)),
@@ -346,6 +358,8 @@ fn build_room_service(
         sticky_event_storage: Arc::new(StickyEventStorage::new(pool.clone())),
         cache,
         key_rotation_storage: None,
+        content_filter: None,
+        event_visibility,
     })
 }
 
@@ -616,7 +630,23 @@ async fn test_send_message_success() {
     let val = result.unwrap();
     assert!(val["event_id"].as_str().unwrap().starts_with('$'));
 
-    let messages = room_service.messaging.get_room_messages(room_id, &alice_id, 0, 10, "b").await.unwrap();
+    let messages = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams {
+                from: None,
+                to: None,
+                limit: 10,
+                direction: "b".to_string(),
+                filter: None,
+                lazy_load_members: false,
+                include_redundant_members: false,
+            },
+        )
+        .await
+        .unwrap();
     let chunk = messages["chunk"].as_array().unwrap();
     let event = chunk
         .iter()
@@ -664,10 +694,28 @@ async fn test_get_room_messages_supports_sync_prev_batch_token() {
             .unwrap();
     }
 
-    let messages = room_service.messaging.get_room_messages(room_id, &alice_id, base_ts + 3000, 2, "b").await.unwrap();
+    let from = Some(format!("t{}", base_ts + 3000));
+    let messages = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams {
+                from,
+                to: None,
+                limit: 2,
+                direction: "b".to_string(),
+                filter: None,
+                lazy_load_members: false,
+                include_redundant_members: false,
+            },
+        )
+        .await
+        .unwrap();
 
     assert_eq!(messages["start"], format!("t{}", base_ts + 3000));
-    assert_eq!(messages["end"], format!("t{}", base_ts + 1000));
+    let end = messages["end"].as_str().unwrap();
+    assert!(matches!(RoomPaginationToken::parse(end), Some(RoomPaginationToken::Topological { .. })));
 
     let chunk = messages["chunk"].as_array().unwrap();
     assert_eq!(chunk.len(), 2);
@@ -675,6 +723,29 @@ async fn test_get_room_messages_supports_sync_prev_batch_token() {
     assert_eq!(chunk[1]["origin_server_ts"], base_ts + 1000);
     assert_eq!(chunk[0]["content"]["body"], format!("msg-{}", base_ts + 2000));
     assert_eq!(chunk[1]["content"]["body"], format!("msg-{}", base_ts + 1000));
+
+    // Continuing from `end` picks up before the oldest event of the page.
+    let from = Some(end.to_string());
+    let older = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams {
+                from,
+                to: None,
+                limit: 10,
+                direction: "b".to_string(),
+                filter: None,
+                lazy_load_members: false,
+                include_redundant_members: false,
+            },
+        )
+        .await
+        .unwrap();
+    let older_chunk = older["chunk"].as_array().unwrap();
+    assert!(older_chunk.iter().all(|event| event["origin_server_ts"].as_i64().unwrap() < base_ts + 1000));
+    assert!(!older_chunk.is_empty());
 }
 
 #[tokio::test]
@@ -715,10 +786,28 @@ async fn test_get_room_messages_supports_forward_pagination_from_stream_token()
             .unwrap();
     }
 
-    let messages = room_service.messaging.get_room_messages(room_id, &alice_id, base_ts + 1000, 2, "f").await.unwrap();
+    let from = Some(format!("t{}", base_ts + 1000));
+    let messages = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams {
+                from,
+                to: None,
+                limit: 2,
+                direction: "f".to_string(),
+                filter: None,
+                lazy_load_members: false,
+                include_redundant_members: false,
+            },
+        )
+        .await
+        .unwrap();
 
     assert_eq!(messages["start"], format!("t{}", base_ts + 1000));
-    assert_eq!(messages["end"], format!("t{}", base_ts + 3000));
+    let end = messages["end"].as_str().unwrap();
+    assert!(matches!(RoomPaginationToken::parse(end), Some(RoomPaginationToken::Topological { .. })));
 
     let chunk = messages["chunk"].as_array().unwrap();
     assert_eq!(chunk.len(), 2);
@@ -726,6 +815,147 @@ async fn test_get_room_messages_supports_forward_pagination_from_stream_token()
     assert_eq!(chunk[1]["origin_server_ts"], base_ts + 3000);
     assert_eq!(chunk[0]["content"]["body"], format!("msg-{}", base_ts + 2000));
     assert_eq!(chunk[1]["content"]["body"], format!("msg-{}", base_ts + 3000));
+
+    // Nothing follows the newest event, so the next page is empty and has no `end`.
+    let from = Some(end.to_string());
+    let newer = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams {
+                from,
+                to: None,
+                limit: 10,
+                direction: "f".to_string(),
+                filter: None,
+                lazy_load_members: false,
+                include_redundant_members: false,
+            },
+        )
+        .await
+        .unwrap();
+    assert!(newer["chunk"].as_array().unwrap().is_empty());
+    assert!(newer.get("end").is_none());
+}
+
+#[tokio::test]
+async fn test_get_event_relations_paginates_and_bundles_aggregations() {
+    let pool = crate::require_test_pool().await;
+    setup_test_database(&pool).await;
+
+    let id = unique_id();
+    let alice_id = format!("@alice_{id}:localhost");
+    let alice_name = format!("alice_{id}");
+    create_test_user(&pool, &alice_id, &alice_name).await;
+
+    let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+    let room_service = create_room_service(&pool, cache.clone());
+
+    let room_val = room_service.lifecycle.create_room(&alice_id, CreateRoomConfig::default()).await.unwrap();
+    let room_id = room_val["room_id"].as_str().unwrap();
+    let base_ts = current_timestamp_millis() + 10_000;
+    let root_id = format!("$relations_root_{id}");
+
+    let events = [
+        (root_id.clone(), json!({"msgtype": "m.text", "body": "root"})),
+        (
+            format!("$relations_thread1_{id}"),
+            json!({"body": "one", "m.relates_to": {"rel_type": "m.thread", "event_id": root_id}}),
+        ),
+        (
+            format!("$relations_edit_{id}"),
+            json!({
+                "body": "* root",
+                "m.new_content": {"body": "root"},
+                "m.relates_to": {"rel_type": "m.replace", "event_id": root_id}
+            }),
+        ),
+        (
+            format!("$relations_thread2_{id}"),
+            json!({"body": "two", "m.relates_to": {"rel_type": "m.thread", "event_id": root_id}}),
+        ),
+        (
+            format!("$relations_thread3_{id}"),
+            json!({"body": "three", "m.relates_to": {"rel_type": "m.thread", "event_id": root_id}}),
+        ),
+    ];
+    for (offset, (event_id, content)) in events.into_iter().enumerate() {
+        room_service
+            .messaging
+            .create_event(
+                CreateEventParams {
+                    event_id,
+                    room_id: room_id.to_string(),
+                    user_id: alice_id.clone(),
+                    event_type: "m.room.message".to_string(),
+                    content,
+                    state_key: None,
+                    origin_server_ts: base_ts + offset as i64,
+                    redacts: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+    room_service
+        .messaging
+        .create_event(
+            CreateEventParams {
+                event_id: format!("$relations_reaction_{id}"),
+                room_id: room_id.to_string(),
+                user_id: alice_id.clone(),
+                event_type: "m.reaction".to_string(),
+                content: json!({"m.relates_to": {"rel_type": "m.annotation", "event_id": root_id, "key": "👍"}}),
+                state_key: None,
+                origin_server_ts: base_ts + 10,
+                redacts: None,
+            },
+            None,
+        )
+        .await
+        .unwrap();
+
+    let mut params = EventRelationsParams {
+        rel_type: Some("m.thread".to_string()),
+        limit: 2,
+        direction: "b".to_string(),
+        ..Default::default()
+    };
+    let page = room_service.messaging.get_event_relations(room_id, &alice_id, &root_id, &params).await.unwrap();
+    let chunk = page["chunk"].as_array().unwrap();
+    assert_eq!(chunk.len(), 2);
+    assert_eq!(chunk[0]["event_id"], format!("$relations_thread3_{id}"));
+    assert_eq!(chunk[1]["event_id"], format!("$relations_thread2_{id}"));
+
+    params.from = page["next_batch"].as_str().map(str::to_string);
+    assert!(params.from.is_some());
+    let page = room_service.messaging.get_event_relations(room_id, &alice_id, &root_id, &params).await.unwrap();
+    let chunk = page["chunk"].as_array().unwrap();
+    assert_eq!(chunk.len(), 1);
+    assert_eq!(chunk[0]["event_id"], format!("$relations_thread1_{id}"));
+    assert!(page.get("next_batch").is_none());
+
+    let messages = room_service
+        .messaging
+        .get_room_messages(
+            room_id,
+            &alice_id,
+            &RoomMessagesParams { limit: 10, direction: "b".to_string(), ..Default::default() },
+        )
+        .await
+        .unwrap();
+    let root = messages["chunk"].as_array().unwrap().iter().find(|event| event["event_id"] == root_id).unwrap();
+    let relations = &root["unsigned"]["m.relations"];
+    assert_eq!(relations["m.thread"]["count"], 3);
+    assert_eq!(relations["m.thread"]["latest_event"]["event_id"], format!("$relations_thread3_{id}"));
+    assert_eq!(relations["m.thread"]["current_user_participated"], true);
+    assert_eq!(relations["m.replace"]["event_id"], format!("$relations_edit_{id}"));
+    assert_eq!(
+        relations["m.annotation"]["chunk"],
+        json!([{"type": "m.reaction", "key": "👍", "count": 1, "current_user_participated": true}])
+    );
 }
 
 #[tokio::test]
@@ -4961,3 +5191,67 @@ async fn test_upgrade_room_not_found() {
 
     assert!(result.is_err());
 }
+
+#[tokio::test]
+async fn test_get_visible_event_respects_history_visibility() {
+    let pool = crate::require_test_pool().await;
+    setup_test_database(&pool).await;
+
+    let id = unique_id();
+    let alice_id = format!("@alice_{id}:localhost");
+    let bob_id = format!("@bob_{id}:localhost");
+    create_test_user(&pool, &alice_id, &format!("alice_{id}")).await;
+    create_test_user(&pool, &bob_id, &format!("bob_{id}")).await;
+
+    let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+    let room_service = create_room_service_with_visibility(&pool, cache.clone());
+
+    let room_val = room_service.lifecycle.create_room(&alice_id, CreateRoomConfig::default()).await.unwrap();
+    let room_id = room_val["room_id"].as_str().unwrap();
+    let base_ts = current_timestamp_millis() + 10_000;
+
+    let early_id = format!("$early_{id}");
+    let late_id = format!("$late_{id}");
+    let events = [
+        (
+            format!("$hv_{id}"),
+            alice_id.clone(),
+            "m.room.history_visibility",
+            json!({"history_visibility": "joined"}),
+            Some(String::new()),
+        ),
+        (early_id.clone(), alice_id.clone(), "m.room.message", json!({"msgtype": "m.text", "body": "early"}), None),
+        (format!("$join_{id}"), bob_id.clone(), "m.room.member", json!({"membership": "join"}), Some(bob_id.clone())),
+        (late_id.clone(), alice_id.clone(), "m.room.message", json!({"msgtype": "m.text", "body": "late"}), None),
+    ];
+    for (offset, (event_id, sender, event_type, content, state_key)) in events.into_iter().enumerate() {
+        room_service
+            .messaging
+            .create_event(
+                CreateEventParams {
+                    event_id,
+                    room_id: room_id.to_string(),
+                    user_id: sender,
+                    event_type: event_type.to_string(),
+                    content,
+                    state_key,
+                    origin_server_ts: base_ts + offset as i64 * 1000,
+                    redacts: None,
+                },
+                None,
+            )
+            .await
+            .unwrap();
+    }
+
+    let early = room_service.messaging.get_visible_event(room_id, &bob_id, &early_id).await;
+    assert!(early.is_err(), "event before bob joined must be hidden: {early:?}");
+
+    let late = room_service.messaging.get_visible_event(room_id, &bob_id, &late_id).await.unwrap();
+    assert_eq!(late["event_id"], late_id);
+    assert_eq!(late["room_id"], room_id);
+    assert_eq!(late["content"]["body"], "late");
+
+    let own = room_service.messaging.get_visible_event(room_id, &alice_id, &early_id).await.unwrap();
+    assert_eq!(own["event_id"], early_id);
+}
@@ -1184,6 +1184,7 @@ async fn test_schema_contract_room_summary_queue_and_children_query_and_write_re
             sender: creator.clone(),
             is_suggested: true,
             via_servers: vec!["localhost".to_string()],
+            order: None,
         })
         .await
         .expect("Failed to add space child fixture");
@@ -1195,6 +1196,7 @@ async fn test_schema_contract_room_summary_queue_and_children_query_and_write_re
             sender: creator.clone(),
             is_suggested: false,
             via_servers: vec!["localhost".to_string(), "matrix.org".to_string()],
+            order: None,
         })
         .await
         .expect("Failed to upsert space child fixture");
@@ -1481,6 +1483,7 @@ async fn test_schema_contract_space_summary_query_and_write_read_closure() {
             sender: creator.clone(),
             is_suggested: true,
             via_servers: vec!["localhost".to_string()],
+            order: None,
         })
         .await
         .expect("Failed to add space child");
@@ -1667,6 +1670,7 @@ async fn test_schema_contract_space_children_and_hierarchy_query_and_write_read_
             sender: root_creator.clone(),
             is_suggested: true,
             via_servers: vec!["localhost".to_string()],
+            order: None,
         })
         .await
         .expect("Failed to add child space relation");
@@ -1680,6 +1684,7 @@ async fn test_schema_contract_space_children_and_hierarchy_query_and_write_read_
             sender: child_creator.clone(),
             is_suggested: false,
             via_servers: vec!["localhost".to_string(), "example.com".to_string()],
+            order: None,
         })
         .await
         .expect("Failed to add nested leaf relation");
@@ -366,6 +366,8 @@ fn create_room_service(
         sticky_event_storage: Arc::new(StickyEventStorage::new(pool.clone())),
         cache,
         key_rotation_storage: None,
+        content_filter: None,
+        event_visibility: None,
     })
 }
 
@@ -483,6 +485,92 @@ async fn test_incremental_sync_does_not_replay_old_timeline() {
     assert!(joined_rooms.is_empty(), "incremental sync should not replay unchanged rooms");
 }
 
+#[tokio::test]
+async fn test_sync_reports_invites_and_rooms_left_since_token() {
+    let pool = crate::require_test_pool().await;
+    setup_test_database(&pool).await;
+    create_test_user(&pool, "@alice:localhost", "alice").await;
+    create_test_user(&pool, "@bob:localhost", "bob").await;
+
+    let cache = Arc::new(CacheManager::new(&CacheConfig::default()));
+    let canonical_cache = cache.clone();
+    let presence_storage = Arc::new(PresenceStorage::new(pool.clone(), canonical_cache.clone()));
+    let member_storage = Arc::new(RoomMemberStorage::new(&pool, "localhost"));
+    let event_storage = Arc::new(EventStorage::new(&pool, "localhost".to_string()));
+    let room_storage = Arc::new(RoomStorage::new(&pool));
+    let user_storage: Arc<dyn UserStore> = Arc::new(UserStorage::new(&pool, canonical_cache));
+
+    let room_service = create_room_service(
+        &pool,
+        room_storage.clone(),
+        member_storage.clone(),
+        event_storage.clone(),
+        user_storage.clone(),
+    );
+
+    let sync_service = SyncService::new(
+        presence_storage,
+        member_storage,
+        event_storage,
+        room_storage,
+        Arc::new(RoomAccountDataStorage::new(&pool)),
+        Arc::new(AccountDataStorage::new(&pool)),
+        Arc::new(FilterStorage::new(&pool)),
+        Arc::new(DeviceStorage::new(&pool)),
+        Arc::new(DeviceKeyStorage::new(&pool)) as Arc<dyn synapse_e2ee::device_keys::DeviceKeyStoreApi>,
+        KeyRotationStorage::new(pool.clone()),
+        ToDeviceStorage::new(&pool),
+        Arc::new(MetricsCollector::new()),
+        PerformanceConfig::default(),
+        Arc::new(CacheManager::new(&CacheConfig::default())),
+    );
+
+    let config = CreateRoomConfig { name: Some("Invite Room".to_string()), ..Default::default() };
+    let room_val = room_service.lifecycle.create_room("@alice:localhost", config).await.unwrap();
+    let room_id = room_val["room_id"].as_str().unwrap().to_string();
+    room_service.membership.invite_user(&room_id, "@alice:localhost", "@bob:localhost").await.unwrap();
+
+    let invited_sync = sync_service.sync("@bob:localhost", None, 0, false, "offline", None, None).await.unwrap();
+    assert!(!invited_sync["rooms"]["join"].as_object().unwrap().contains_key(&room_id));
+    let invite_state = invited_sync["rooms"]["invite"][&room_id]["invite_state"]["events"].as_array().unwrap();
+    assert!(invite_state
+        .iter()
+        .any(|event| event["type"] == "m.room.name" && event["content"]["name"] == "Invite Room"));
+    assert!(invite_state.iter().any(|event| {
+        event["type"] == "m.room.member"
+            && event["state_key"] == "@bob:localhost"
+            && event["content"]["membership"] == "invite"
+    }));
+
+    room_service.membership.join_room(&room_id, "@bob:localhost").await.unwrap();
+    let joined_sync = sync_service.sync("@bob:localhost", None, 0, false, "offline", None, None).await.unwrap();
+    assert!(joined_sync["rooms"]["join"].as_object().unwrap().contains_key(&room_id));
+    assert!(joined_sync["rooms"]["invite"].as_object().unwrap().is_empty());
+    let since = joined_sync["next_batch"].as_str().unwrap().to_string();
+
+    let before_leave = json!({"msgtype": "m.text", "body": "Before leave"});
+    room_service.messaging.send_message(&room_id, "@alice:localhost", "m.room.message", &before_leave).await.unwrap();
+    room_service.membership.leave_room(&room_id, "@bob:localhost").await.unwrap();
+    let after_leave = json!({"msgtype": "m.text", "body": "After leave"});
+    room_service.messaging.send_message(&room_id, "@alice:localhost", "m.room.message", &after_leave).await.unwrap();
+
+    let left_sync =
+        sync_service.sync("@bob:localhost", None, 0, false, "offline", None, Some(since.as_str())).await.unwrap();
+    assert!(!left_sync["rooms"]["join"].as_object().unwrap().contains_key(&room_id));
+    let timeline = left_sync["rooms"]["leave"][&room_id]["timeline"]["events"].as_array().unwrap();
+    assert!(timeline.iter().any(|event| event["content"]["body"] == "Before leave"));
+    assert!(!timeline.iter().any(|event| event["content"]["body"] == "After leave"));
+    let last = timeline.last().unwrap();
+    assert_eq!(last["type"], "m.room.member");
+    assert_eq!(last["content"]["membership"], "leave");
+
+    let archived_sync = sync_service
+        .sync("@bob:localhost", None, 0, false, "offline", None, Some(left_sync["next_batch"].as_str().unwrap()))
+        .await
+        .unwrap();
+    assert!(archived_sync["rooms"]["leave"].as_object().unwrap().is_empty());
+}
+
 #[tokio::test]
 async fn test_sync_offline_presence_overwrites_previous_presence_state() {
     let pool = crate::require_test_pool().await;
@@ -0,0 +1,31 @@
+use serde_json::json;
+use synapse_rust::test_server::TestServer;
+
+#[tokio::test]
+async fn test_server_seeds_room_and_syncs_messages() {
+    let Ok(server) = TestServer::start().await else {
+        return;
+    };
+    let suffix = rand::random::<u32>();
+    let alice = server.register_user(&format!("ts_alice_{suffix}")).await.expect("register alice");
+    let bob = server.register_user(&format!("ts_bob_{suffix}")).await.expect("register bob");
+
+    let room_id = server.seed_room(&alice, &[&bob]).await.expect("seed room");
+    let event_id = server.client(&bob).send_text(&room_id, "hello from bob").await.expect("send message");
+
+    let sync = server.client(&alice).sync(None).await.expect("sync");
+    let timeline = sync["rooms"]["join"][&room_id]["timeline"]["events"].as_array().cloned().unwrap_or_default();
+    assert!(timeline.iter().any(|event| event["event_id"] == json!(event_id)), "sent event missing from sync");
+}
+
+#[tokio::test]
+async fn test_server_reports_failed_requests() {
+    let Ok(server) = TestServer::start().await else {
+        return;
+    };
+    let user = server.register_user(&format!("ts_carol_{}", rand::random::<u32>())).await.expect("register");
+
+    let response = server.client(&user).get("/_matrix/client/v3/rooms/!missing:localhost/state").await.unwrap();
+    assert!(!response.is_success());
+    assert!(response.into_result().is_err());
+}
@@ -50,7 +50,9 @@ fn create_test_config() -> Config {
             allow_public_rooms_over_federation: true,
             auto_join_rooms: vec![],
             autocreate_auto_join_rooms: true,
+            auto_join_mxid_localpart: None,
             encryption_enabled_by_default_for_room_type: None,
+            reject_unencrypted_messages_in_encrypted_rooms: false,
             app_service_config_files: vec![],
             presence_enabled: true,
             ..Default::default()
@@ -65,6 +67,10 @@ fn create_test_config() -> Config {
             max_size: 10,
             min_idle: Some(2),
             connection_timeout: 30,
+            schema_drift_policy: Default::default(),
+            migrations_dir: None,
+            event_partitioning: Default::default(),
+            state_compaction: Default::default(),
         },
         redis: RedisConfig {
             host: "localhost".to_string(),
@@ -153,6 +159,7 @@ fn create_test_config() -> Config {
             require_manual_approval: false,
             approval_tokens: Vec::new(),
         },
+        admin_bootstrap: synapse_rust::common::config::AdminBootstrapConfig::default(),
         worker: WorkerConfig::default(),
         cors: CorsConfig::default(),
         smtp: SmtpConfig::default(),
@@ -164,6 +171,7 @@ fn create_test_config() -> Config {
         oidc: synapse_rust::common::config::OidcConfig::default(),
         builtin_oidc: synapse_rust::common::config::BuiltinOidcConfig::default(),
         saml: synapse_rust::common::config::SamlConfig::default(),
+        ldap: synapse_rust::common::config::LdapConfig::default(),
         retention: synapse_rust::common::config::RetentionConfig::default(),
         telemetry: synapse_rust::common::OpenTelemetryConfig::default(),
         prometheus: synapse_rust::common::PrometheusConfig::default(),
@@ -171,6 +179,15 @@ fn create_test_config() -> Config {
         experimental: ExperimentalConfig::default(),
         identity: synapse_rust::common::config::IdentityConfig::default(),
         translate: synapse_rust::common::config::TranslateConfig::default(),
+        content_filter: synapse_rust::common::config::ContentFilterConfig::default(),
+        room_directory: synapse_rust::common::config::RoomDirectoryConfig::default(),
+        room_creation: synapse_rust::common::config::RoomCreationConfig::default(),
+        welcome: synapse_rust::common::config::WelcomeConfig::default(),
+        policy_lists: synapse_rust::common::config::PolicyListsConfig::default(),
+        column_encryption: synapse_rust::common::config::ColumnEncryptionConfig::default(),
+        outbound_proxy: synapse_rust::common::config::OutboundProxyConfig::default(),
+        egress: synapse_rust::common::config::EgressConfig::default(),
+        health: synapse_rust::common::config::HealthConfig::default(),
         sso_redirect_allowlist: vec![],
     }
 }
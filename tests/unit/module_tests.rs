@@ -328,12 +328,12 @@ async fn test_password_auth_provider_trait() {
     }
 
     #[async_trait::async_trait]
-    impl PasswordAuthProviderTrait for TestPasswordProvider {
+    impl PasswordAuthProvider for TestPasswordProvider {
         fn name(&self) -> &str {
             &self.name
         }
 
-        async fn check(
+        async fn check_password(
             &self,
             context: &PasswordAuthContext,
         ) -> Result<PasswordAuthOutput, synapse_rust::common::error::ApiError> {
@@ -356,7 +356,16 @@ async fn test_password_auth_provider_trait() {
         initial_device_display_name: None,
     };
 
-    let result = provider.check(&context).await.unwrap();
+    let result = provider.check_password(&context).await.unwrap();
     assert!(result.valid);
     assert_eq!(result.user_id, Some("@test:localhost".to_string()));
+
+    assert!(provider.handles_user("@test:localhost"));
+    assert!(provider.allows_local_password("@test:localhost"));
+    assert!(provider.on_logged_in("@test:localhost", "DEVICE").await.is_ok());
+
+    let mut registry = ModuleRegistry::new();
+    registry.register_password_provider(std::sync::Arc::new(provider));
+    assert_eq!(registry.password_providers().len(), 1);
+    assert_eq!(registry.password_providers()[0].name(), "test_provider");
 }
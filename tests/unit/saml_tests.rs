@@ -19,6 +19,7 @@ mod tests {
                 uid: Some("uid".to_string()),
                 displayname: Some("cn".to_string()),
                 email: Some("mail".to_string()),
+                ..Default::default()
             },
             nameid_format: "urn:oasis:names:tc:SAML:2.0:nameid-format:persistent".to_string(),
             allow_existing_users: true,